@@ -1,3 +1,5 @@
+use crate::switches::{DEFAULT_IPC_BINARY_LIMIT, ProcessEnvironment, SubprocessConfig};
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum GodotRenderBackend {
     #[default]
@@ -17,6 +19,29 @@ pub struct SecurityConfig {
     pub disable_web_security: bool,
 }
 
+/// Where Chromium subprocesses write their log.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum LogOutput {
+    /// Standard error.
+    #[default]
+    Stderr,
+    /// Appended to the file at this absolute path.
+    File(String),
+    /// No logging at all.
+    Disabled,
+}
+
+impl LogOutput {
+    /// Returns the switches, with their values, that select this output.
+    pub fn switches(&self) -> Vec<(&'static str, Option<String>)> {
+        match self {
+            Self::Stderr => vec![("enable-logging", Some("stderr".to_string()))],
+            Self::File(path) => vec![("enable-logging", None), ("log-file", Some(path.clone()))],
+            Self::Disabled => vec![("disable-logging", None)],
+        }
+    }
+}
+
 /// GPU device identifiers for GPU selection across all platforms.
 ///
 /// These vendor and device IDs are passed to CEF via `--gpu-vendor-id` and
@@ -64,6 +89,20 @@ pub struct OsrApp {
     cache_size_mb: i32,
     /// Custom command-line switches
     custom_switches: Vec<String>,
+    /// Application-defined URL schemes registered alongside `res` and `user`
+    custom_schemes: Vec<String>,
+    /// Register the Godot schemes without `CSP_BYPASSING`
+    enforce_scheme_csp: bool,
+    /// Maximum size in bytes of a binary IPC message sent from the page
+    ipc_binary_limit: usize,
+    /// Forward the page's notifications to Godot
+    web_notifications: bool,
+    /// Ports of local game servers treated as secure origins (empty = none)
+    local_ports: Vec<u16>,
+    /// Locale and environment variables forwarded to subprocesses
+    environment: ProcessEnvironment,
+    /// Log destination of subprocesses
+    log_output: LogOutput,
 }
 
 impl Default for OsrApp {
@@ -85,6 +124,13 @@ impl OsrApp {
             proxy_bypass_list: String::new(),
             cache_size_mb: 0,
             custom_switches: Vec::new(),
+            custom_schemes: Vec::new(),
+            enforce_scheme_csp: false,
+            ipc_binary_limit: DEFAULT_IPC_BINARY_LIMIT,
+            web_notifications: false,
+            local_ports: Vec::new(),
+            environment: ProcessEnvironment::default(),
+            log_output: LogOutput::default(),
         }
     }
 
@@ -131,6 +177,46 @@ impl OsrApp {
     pub fn custom_switches(&self) -> &[String] {
         &self.custom_switches
     }
+
+    pub fn custom_schemes(&self) -> &[String] {
+        &self.custom_schemes
+    }
+
+    pub fn enforce_scheme_csp(&self) -> bool {
+        self.enforce_scheme_csp
+    }
+
+    pub fn ipc_binary_limit(&self) -> usize {
+        self.ipc_binary_limit
+    }
+
+    pub fn web_notifications(&self) -> bool {
+        self.web_notifications
+    }
+
+    pub fn local_ports(&self) -> &[u16] {
+        &self.local_ports
+    }
+
+    pub fn environment(&self) -> &ProcessEnvironment {
+        &self.environment
+    }
+
+    pub fn log_output(&self) -> &LogOutput {
+        &self.log_output
+    }
+
+    /// Configuration forwarded to subprocesses through command-line switches.
+    pub fn subprocess_config(&self) -> SubprocessConfig {
+        SubprocessConfig {
+            custom_schemes: self.custom_schemes.clone(),
+            enforce_scheme_csp: self.enforce_scheme_csp,
+            ipc_binary_limit: self.ipc_binary_limit,
+            web_notifications: self.web_notifications,
+            local_ports: self.local_ports.clone(),
+            environment: self.environment.clone(),
+        }
+    }
 }
 
 pub struct OsrAppBuilder {
@@ -144,6 +230,13 @@ pub struct OsrAppBuilder {
     proxy_bypass_list: String,
     cache_size_mb: i32,
     custom_switches: Vec<String>,
+    custom_schemes: Vec<String>,
+    enforce_scheme_csp: bool,
+    ipc_binary_limit: usize,
+    web_notifications: bool,
+    local_ports: Vec<u16>,
+    environment: ProcessEnvironment,
+    log_output: LogOutput,
 }
 
 impl Default for OsrAppBuilder {
@@ -165,6 +258,13 @@ impl OsrAppBuilder {
             proxy_bypass_list: String::new(),
             cache_size_mb: 0,
             custom_switches: Vec::new(),
+            custom_schemes: Vec::new(),
+            enforce_scheme_csp: false,
+            ipc_binary_limit: DEFAULT_IPC_BINARY_LIMIT,
+            web_notifications: false,
+            local_ports: Vec::new(),
+            environment: ProcessEnvironment::default(),
+            log_output: LogOutput::default(),
         }
     }
 
@@ -218,6 +318,51 @@ impl OsrAppBuilder {
         self
     }
 
+    pub fn custom_schemes(mut self, custom_schemes: Vec<String>) -> Self {
+        self.custom_schemes = custom_schemes;
+        self
+    }
+
+    pub fn enforce_scheme_csp(mut self, enforce_scheme_csp: bool) -> Self {
+        self.enforce_scheme_csp = enforce_scheme_csp;
+        self
+    }
+
+    pub fn ipc_binary_limit(mut self, ipc_binary_limit: usize) -> Self {
+        self.ipc_binary_limit = ipc_binary_limit;
+        self
+    }
+
+    pub fn web_notifications(mut self, web_notifications: bool) -> Self {
+        self.web_notifications = web_notifications;
+        self
+    }
+
+    pub fn local_ports(mut self, local_ports: Vec<u16>) -> Self {
+        self.local_ports = local_ports;
+        self
+    }
+
+    pub fn environment(mut self, environment: ProcessEnvironment) -> Self {
+        self.environment = environment;
+        self
+    }
+
+    pub fn log_output(mut self, log_output: LogOutput) -> Self {
+        self.log_output = log_output;
+        self
+    }
+
+    /// Applies configuration received from the browser process.
+    pub fn subprocess_config(self, config: SubprocessConfig) -> Self {
+        self.custom_schemes(config.custom_schemes)
+            .enforce_scheme_csp(config.enforce_scheme_csp)
+            .ipc_binary_limit(config.ipc_binary_limit)
+            .web_notifications(config.web_notifications)
+            .local_ports(config.local_ports)
+            .environment(config.environment)
+    }
+
     pub fn build(self) -> OsrApp {
         OsrApp {
             godot_backend: self.godot_backend,
@@ -230,6 +375,37 @@ impl OsrAppBuilder {
             proxy_bypass_list: self.proxy_bypass_list,
             cache_size_mb: self.cache_size_mb,
             custom_switches: self.custom_switches,
+            custom_schemes: self.custom_schemes,
+            enforce_scheme_csp: self.enforce_scheme_csp,
+            ipc_binary_limit: self.ipc_binary_limit,
+            web_notifications: self.web_notifications,
+            local_ports: self.local_ports,
+            environment: self.environment,
+            log_output: self.log_output,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_output_switches() {
+        assert_eq!(
+            LogOutput::Stderr.switches(),
+            vec![("enable-logging", Some("stderr".to_string()))]
+        );
+        assert_eq!(
+            LogOutput::File("/tmp/cef.log".to_string()).switches(),
+            vec![
+                ("enable-logging", None),
+                ("log-file", Some("/tmp/cef.log".to_string())),
+            ]
+        );
+        assert_eq!(
+            LogOutput::Disabled.switches(),
+            vec![("disable-logging", None)]
+        );
+    }
+}
@@ -1,31 +1,56 @@
 use std::cell::RefCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
 
 use cef::{
     BrowserProcessHandler, CommandLine, ImplBrowserProcessHandler, ImplCommandLine,
     WrapBrowserProcessHandler, rc::Rc, wrap_browser_process_handler,
 };
 
-use crate::app::{GpuDeviceIds, SecurityConfig};
+use crate::app::{GpuDeviceIds, LogOutput, SecurityConfig};
+use crate::switches::SubprocessConfig;
+
+/// Number of helper processes CEF started in this process.
+static CHILD_PROCESS_LAUNCHES: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns how many helper processes (renderer, GPU, utility...) CEF has
+/// started so far.
+pub fn child_process_launch_count() -> usize {
+    CHILD_PROCESS_LAUNCHES.load(Ordering::Relaxed)
+}
 
 #[derive(Clone)]
 pub struct OsrBrowserProcessHandler {
     is_cef_ready: RefCell<bool>,
     security_config: SecurityConfig,
     gpu_device_ids: Option<GpuDeviceIds>,
+    subprocess_config: SubprocessConfig,
+    log_output: LogOutput,
 }
 
 impl Default for OsrBrowserProcessHandler {
     fn default() -> Self {
-        Self::new(SecurityConfig::default(), None)
+        Self::new(
+            SecurityConfig::default(),
+            None,
+            SubprocessConfig::default(),
+            LogOutput::default(),
+        )
     }
 }
 
 impl OsrBrowserProcessHandler {
-    pub fn new(security_config: SecurityConfig, gpu_device_ids: Option<GpuDeviceIds>) -> Self {
+    pub fn new(
+        security_config: SecurityConfig,
+        gpu_device_ids: Option<GpuDeviceIds>,
+        subprocess_config: SubprocessConfig,
+        log_output: LogOutput,
+    ) -> Self {
         Self {
             is_cef_ready: RefCell::new(false),
             security_config,
             gpu_device_ids,
+            subprocess_config,
+            log_output,
         }
     }
 }
@@ -41,6 +66,7 @@ wrap_browser_process_handler! {
         }
 
         fn on_before_child_process_launch(&self, command_line: Option<&mut CommandLine>) {
+            CHILD_PROCESS_LAUNCHES.fetch_add(1, Ordering::Relaxed);
             let Some(command_line) = command_line else {
                 return;
             };
@@ -58,7 +84,21 @@ wrap_browser_process_handler! {
             }
 
             command_line.append_switch(Some(&"disable-session-crashed-bubble".into()));
-            command_line.append_switch(Some(&"enable-logging=stderr".into()));
+
+            // CEF copies its own logging switches to subprocesses; these
+            // only fill in what it left out
+            for (name, value) in self.handler.log_output.switches() {
+                if command_line.has_switch(Some(&name.into())) == 1 {
+                    continue;
+                }
+                match value {
+                    Some(value) => command_line.append_switch_with_value(
+                        Some(&name.into()),
+                        Some(&value.as_str().into()),
+                    ),
+                    None => command_line.append_switch(Some(&name.into())),
+                }
+            }
 
             if let Some(ids) = &self.handler.gpu_device_ids {
                 command_line.append_switch_with_value(
@@ -70,6 +110,21 @@ wrap_browser_process_handler! {
                     Some(&ids.to_device_arg().as_str().into()),
                 );
             }
+
+            // Subprocesses build their own `OsrApp` and need the same configuration
+            for (name, value) in self.handler.subprocess_config.to_switches() {
+                // Keep values Chromium already set, e.g. its own `--lang`
+                if command_line.has_switch(Some(&name.into())) == 1 {
+                    continue;
+                }
+                match value {
+                    Some(value) => command_line.append_switch_with_value(
+                        Some(&name.into()),
+                        Some(&value.as_str().into()),
+                    ),
+                    None => command_line.append_switch(Some(&name.into())),
+                }
+            }
         }
     }
 }
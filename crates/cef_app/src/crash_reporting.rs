@@ -0,0 +1,237 @@
+//! Crash dump collection for the CEF processes.
+//!
+//! CEF enables its Crashpad-based crash reporter when it finds a
+//! `crash_reporter.cfg` file while a process starts, so the file has to exist
+//! before `cef::initialize` / `cef::execute_process` run. On Windows and Linux
+//! it is read from the directory of the executable; on macOS from the
+//! `Contents/Resources` directory of the top-level app bundle.
+//!
+//! The browser process chooses the dump directory and passes it to the
+//! subprocesses through [`CRASH_DUMP_LOCATION_ENV`]. Collection is disabled
+//! when the variable is not set.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Name of the file CEF reads the crash reporter configuration from.
+pub const CRASH_REPORTER_CFG: &str = "crash_reporter.cfg";
+
+/// Environment variable CEF's crash reporter reads the dump directory from.
+pub const CRASH_DUMP_LOCATION_ENV: &str = "BREAKPAD_DUMP_LOCATION";
+
+/// File extension of the minidumps written by Crashpad.
+pub const CRASH_DUMP_EXTENSION: &str = "dmp";
+
+/// Returns the contents of the generated `crash_reporter.cfg`.
+///
+/// No `ServerURL` is set, so dumps are only stored locally and never uploaded.
+pub fn crash_reporter_config() -> String {
+    format!(
+        "# Generated by Godot CEF. Local changes are overwritten.\n\
+         [Config]\n\
+         ProductName=Godot CEF\n\
+         ProductVersion={}\n\
+         AppName=GodotCef\n\
+         RateLimitEnabled=false\n\
+         MaxDatabaseSizeInMb=50\n\
+         MaxDatabaseAgeInDays=30\n",
+        env!("CARGO_PKG_VERSION")
+    )
+}
+
+/// Returns the directory CEF reads `crash_reporter.cfg` from for `executable`.
+///
+/// On macOS `executable` is the helper inside
+/// `Godot CEF.app/Contents/Frameworks/<Helper>.app/Contents/MacOS/`, and the
+/// file belongs in `Godot CEF.app/Contents/Resources`.
+pub fn crash_reporter_config_dir(executable: &Path) -> Option<PathBuf> {
+    let exe_dir = executable.parent()?;
+    if cfg!(target_os = "macos") {
+        // MacOS -> Contents -> <Helper>.app -> Frameworks -> Contents
+        let contents = exe_dir.ancestors().nth(4)?;
+        Some(contents.join("Resources"))
+    } else {
+        Some(exe_dir.to_path_buf())
+    }
+}
+
+/// Writes `crash_reporter.cfg` to `dir` unless it already has the expected
+/// contents. Returns the path of the file.
+pub fn write_crash_reporter_config(dir: &Path) -> io::Result<PathBuf> {
+    let path = dir.join(CRASH_REPORTER_CFG);
+    let config = crash_reporter_config();
+    if std::fs::read_to_string(&path).is_ok_and(|existing| existing == config) {
+        return Ok(path);
+    }
+
+    std::fs::create_dir_all(dir)?;
+    std::fs::write(&path, config)?;
+    Ok(path)
+}
+
+/// Removes a `crash_reporter.cfg` previously generated in `dir`. Files not
+/// written by Godot CEF are left alone.
+pub fn remove_crash_reporter_config(dir: &Path) -> io::Result<()> {
+    let path = dir.join(CRASH_REPORTER_CFG);
+    match std::fs::read_to_string(&path) {
+        Ok(existing) if existing == crash_reporter_config() => std::fs::remove_file(path),
+        Ok(_) => Ok(()),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    }
+}
+
+/// A minidump left behind by a crashed process.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CrashDump {
+    pub path: PathBuf,
+    pub size_bytes: u64,
+    /// Last modification time in seconds since the Unix epoch.
+    pub modified_unix: u64,
+}
+
+/// Lists the minidumps under `dir`, oldest first.
+///
+/// Crashpad keeps dumps in subdirectories of its database (`pending`,
+/// `completed`, `reports`), so the directory is searched recursively.
+pub fn find_crash_dumps(dir: &Path) -> Vec<CrashDump> {
+    let mut dumps = Vec::new();
+    collect_crash_dumps(dir, 0, &mut dumps);
+    dumps.sort_by(|a, b| {
+        a.modified_unix
+            .cmp(&b.modified_unix)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    dumps
+}
+
+/// Crashpad databases are at most a few levels deep.
+const MAX_SEARCH_DEPTH: usize = 4;
+
+fn collect_crash_dumps(dir: &Path, depth: usize, dumps: &mut Vec<CrashDump>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    for entry in entries.flatten() {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let path = entry.path();
+        if metadata.is_dir() {
+            if depth < MAX_SEARCH_DEPTH {
+                collect_crash_dumps(&path, depth + 1, dumps);
+            }
+            continue;
+        }
+
+        let is_dump = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case(CRASH_DUMP_EXTENSION));
+        if !is_dump {
+            continue;
+        }
+
+        let modified_unix = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map_or(0, |duration| duration.as_secs());
+        dumps.push(CrashDump {
+            path,
+            size_bytes: metadata.len(),
+            modified_unix,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "godot-cef-crash-test-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_crash_reporter_config_has_no_upload_url() {
+        let config = crash_reporter_config();
+        assert!(config.contains("[Config]"));
+        assert!(config.contains("ProductName=Godot CEF"));
+        assert!(!config.contains("ServerURL"));
+    }
+
+    #[test]
+    fn test_crash_reporter_config_dir() {
+        if cfg!(target_os = "macos") {
+            let helper = Path::new(
+                "/bin/Godot CEF.app/Contents/Frameworks/Godot CEF Helper.app/Contents/MacOS/Godot CEF Helper",
+            );
+            assert_eq!(
+                crash_reporter_config_dir(helper),
+                Some(PathBuf::from("/bin/Godot CEF.app/Contents/Resources"))
+            );
+        } else {
+            let helper = Path::new("/addons/godot_cef/bin/gdcef_helper");
+            assert_eq!(
+                crash_reporter_config_dir(helper),
+                Some(PathBuf::from("/addons/godot_cef/bin"))
+            );
+        }
+    }
+
+    #[test]
+    fn test_write_and_remove_crash_reporter_config() {
+        let dir = temp_dir("config");
+        let path = write_crash_reporter_config(&dir).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            crash_reporter_config()
+        );
+
+        remove_crash_reporter_config(&dir).unwrap();
+        assert!(!path.exists());
+
+        // A file written by someone else is kept
+        std::fs::write(&path, "[Config]\nServerURL=https://example.com\n").unwrap();
+        remove_crash_reporter_config(&dir).unwrap();
+        assert!(path.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_find_crash_dumps() {
+        let dir = temp_dir("dumps");
+        std::fs::create_dir_all(dir.join("completed")).unwrap();
+        std::fs::create_dir_all(dir.join("pending")).unwrap();
+        std::fs::write(dir.join("completed/a.dmp"), [0u8; 4]).unwrap();
+        std::fs::write(dir.join("pending/b.DMP"), [0u8; 2]).unwrap();
+        std::fs::write(dir.join("settings.dat"), [0u8; 8]).unwrap();
+
+        let dumps = find_crash_dumps(&dir);
+        let mut names: Vec<_> = dumps
+            .iter()
+            .map(|dump| {
+                dump.path
+                    .file_name()
+                    .unwrap()
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["a.dmp", "b.DMP"]);
+        assert_eq!(dumps.iter().map(|dump| dump.size_bytes).sum::<u64>(), 6);
+
+        assert!(find_crash_dumps(&dir.join("missing")).is_empty());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,73 @@
+(function() {
+    if (window.__displayMediaHelperInitialized) return;
+    window.__displayMediaHelperInitialized = true;
+
+    const mediaDevices = navigator.mediaDevices;
+    if (!mediaDevices || typeof window.__requestDisplayMedia !== 'function') return;
+
+    let nextRequestId = 1;
+    // getDisplayMedia() calls waiting for Godot, by request id
+    const pendingRequests = new Map();
+    // Canvases streamed to the page, by request id
+    const captures = new Map();
+
+    // getDisplayMedia() is answered by Godot with offer_display_media()
+    mediaDevices.getDisplayMedia = function() {
+        return new Promise(function(resolve, reject) {
+            const id = nextRequestId++;
+            pendingRequests.set(id, { resolve: resolve, reject: reject });
+            if (!window.__requestDisplayMedia(id)) {
+                pendingRequests.delete(id);
+                reject(new DOMException('Display capture is unavailable', 'NotSupportedError'));
+            }
+        });
+    };
+
+    function endCapture(id) {
+        if (!captures.delete(id)) return;
+        window.__stopDisplayMedia(id);
+    }
+
+    window.__resolveDisplayMedia = function(id, granted, width, height, frameRate) {
+        const request = pendingRequests.get(id);
+        if (!request) return;
+        pendingRequests.delete(id);
+
+        if (!granted) {
+            request.reject(new DOMException('Permission denied', 'NotAllowedError'));
+            return;
+        }
+
+        const canvas = document.createElement('canvas');
+        canvas.width = width;
+        canvas.height = height;
+        const context = canvas.getContext('2d');
+        const stream = canvas.captureStream(frameRate);
+        captures.set(id, { canvas: canvas, context: context });
+
+        // Godot stops sending frames once the page stops the track
+        stream.getVideoTracks().forEach(function(track) {
+            const stop = track.stop.bind(track);
+            track.stop = function() {
+                stop();
+                endCapture(id);
+            };
+            track.addEventListener('ended', function() {
+                endCapture(id);
+            });
+        });
+        request.resolve(stream);
+    };
+
+    window.__pushDisplayMediaFrame = function(id, width, height, buffer) {
+        const capture = captures.get(id);
+        if (!capture) return;
+
+        if (capture.canvas.width !== width || capture.canvas.height !== height) {
+            capture.canvas.width = width;
+            capture.canvas.height = height;
+        }
+        const pixels = new Uint8ClampedArray(buffer);
+        capture.context.putImageData(new ImageData(pixels, width, height), 0, 0);
+    };
+})();
@@ -0,0 +1,30 @@
+(function() {
+    if (window.__domQueryHelperInitialized) return;
+    window.__domQueryHelperInitialized = true;
+
+    // Kinds of query, as sent with the domQuery message
+    const DUMP = 0;
+    const COUNT = 1;
+
+    // Answers dump_dom_async() and count_elements(). The selector arrives as
+    // an argument, never as part of a script, so it needs no escaping. Dumps
+    // return the HTML (null if nothing matches), counts the number of
+    // matches; both return null for an invalid selector.
+    window.__godotDomQuery = function(kind, selector, maxLength) {
+        let element;
+        try {
+            if (kind === COUNT) {
+                return document.querySelectorAll(selector).length;
+            }
+            element = selector === '' ? document.documentElement : document.querySelector(selector);
+        } catch (e) {
+            return null;
+        }
+        if (kind !== DUMP || !element) return null;
+
+        const html = element.outerHTML;
+        if (html.length <= maxLength) return html;
+        return html.slice(0, maxLength) +
+            '\n<!-- godot-cef: truncated, ' + maxLength + ' of ' + html.length + ' characters shown -->';
+    };
+})();
@@ -0,0 +1,68 @@
+(function() {
+    if (window.__firstPaintHelperInitialized) return;
+    window.__firstPaintHelperInitialized = true;
+
+    // Only the main frame's document is reported, once
+    if (window !== window.top || typeof window.__reportFirstPaint !== 'function') return;
+
+    // Time after load that a page with content gets to paint it before the
+    // fallback reports anyway
+    const FALLBACK_DELAY_MS = 3000;
+
+    let reported = false;
+    let observer = null;
+
+    function disconnect() {
+        if (observer) {
+            observer.disconnect();
+            observer = null;
+        }
+    }
+
+    function report(time) {
+        if (reported) return;
+        reported = true;
+        disconnect();
+        window.__reportFirstPaint(time);
+    }
+
+    if (typeof PerformanceObserver === 'function') {
+        try {
+            observer = new PerformanceObserver(function(list) {
+                const entry = list.getEntriesByName('first-contentful-paint')[0];
+                if (entry) report(entry.startTime);
+            });
+            observer.observe({ type: 'paint', buffered: true });
+        } catch (e) {
+            observer = null;
+        }
+    }
+
+    function hasContent() {
+        const body = document.body;
+        return !!body && (body.childElementCount > 0 || body.textContent.trim() !== '');
+    }
+
+    // Some documents never get the entry (about:blank, pages without text
+    // or images): after load, they are reported at their next frame, and
+    // pages with content once they had time to paint it
+    function fallback() {
+        requestAnimationFrame(function() {
+            if (reported) return;
+            if (!hasContent()) {
+                report(performance.now());
+                return;
+            }
+            setTimeout(function() {
+                requestAnimationFrame(function() { report(performance.now()); });
+            }, FALLBACK_DELAY_MS);
+        });
+    }
+
+    if (document.readyState === 'complete') {
+        fallback();
+    } else {
+        window.addEventListener('load', fallback, { once: true });
+    }
+    window.addEventListener('pagehide', disconnect, { once: true });
+})();
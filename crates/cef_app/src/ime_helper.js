@@ -158,6 +158,28 @@
         }
     }, true);
 
+    // Scrolling and zooming move the caret without changing the selection.
+    // Report at most once per frame, as scroll events fire continuously.
+    let caretReportScheduled = false;
+    function scheduleCaretReport() {
+        if (!window.__imeActive || caretReportScheduled) return;
+        caretReportScheduled = true;
+        requestAnimationFrame(function() {
+            caretReportScheduled = false;
+            if (window.__imeActive && isEditableElement(document.activeElement)) {
+                window.__reportCaretBounds();
+            }
+        });
+    }
+
+    // Capture scrolls of nested scroll containers too
+    document.addEventListener('scroll', scheduleCaretReport, true);
+    window.addEventListener('resize', scheduleCaretReport);
+    if (window.visualViewport) {
+        window.visualViewport.addEventListener('resize', scheduleCaretReport);
+        window.visualViewport.addEventListener('scroll', scheduleCaretReport);
+    }
+
     document.addEventListener('mouseup', function(e) {
         if (window.__imeActive && isEditableElement(document.activeElement)) {
             setTimeout(function() { window.__reportCaretBounds(); }, 10);
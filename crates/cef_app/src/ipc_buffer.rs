@@ -0,0 +1,122 @@
+//! Buffering of Godot-to-page IPC messages that arrive before the page has
+//! installed its `onIpcMessage` / `onIpcBinaryMessage` handler.
+//!
+//! Messages are kept per frame and flushed in order once the handler is set.
+//! The buffer is bounded; when it is full the oldest message is dropped.
+
+use std::collections::{HashMap, VecDeque};
+
+/// Maximum number of messages buffered per frame.
+pub(crate) const MAX_PENDING_IPC_MESSAGES: usize = 128;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PendingIpcMessage {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+impl PendingIpcMessage {
+    fn is_binary(&self) -> bool {
+        matches!(self, PendingIpcMessage::Binary(_))
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct PendingIpcMessages {
+    frames: HashMap<String, VecDeque<PendingIpcMessage>>,
+}
+
+impl PendingIpcMessages {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Buffers a message for `frame_id`. Returns `false` if an older message
+    /// had to be dropped to make room.
+    pub fn push(&mut self, frame_id: &str, message: PendingIpcMessage) -> bool {
+        let queue = self.frames.entry(frame_id.to_string()).or_default();
+        let mut kept_all = true;
+        if queue.len() >= MAX_PENDING_IPC_MESSAGES {
+            queue.pop_front();
+            kept_all = false;
+        }
+        queue.push_back(message);
+        kept_all
+    }
+
+    /// Removes and returns the buffered messages of one kind, in arrival order.
+    pub fn take(&mut self, frame_id: &str, binary: bool) -> Vec<PendingIpcMessage> {
+        let Some(queue) = self.frames.get_mut(frame_id) else {
+            return Vec::new();
+        };
+
+        let (taken, kept): (VecDeque<_>, VecDeque<_>) =
+            queue.drain(..).partition(|m| m.is_binary() == binary);
+        *queue = kept;
+        if queue.is_empty() {
+            self.frames.remove(frame_id);
+        }
+
+        taken.into_iter().collect()
+    }
+
+    /// Drops everything buffered for `frame_id` (e.g. on navigation).
+    pub fn clear(&mut self, frame_id: &str) {
+        self.frames.remove(frame_id);
+    }
+
+    #[cfg(test)]
+    fn len(&self, frame_id: &str) -> usize {
+        self.frames.get(frame_id).map_or(0, VecDeque::len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(s: &str) -> PendingIpcMessage {
+        PendingIpcMessage::Text(s.to_string())
+    }
+
+    #[test]
+    fn test_take_preserves_order_per_kind() {
+        let mut pending = PendingIpcMessages::new();
+        pending.push("main", text("a"));
+        pending.push("main", PendingIpcMessage::Binary(vec![1]));
+        pending.push("main", text("b"));
+
+        assert_eq!(pending.take("main", false), vec![text("a"), text("b")]);
+        assert_eq!(pending.len("main"), 1);
+        assert_eq!(
+            pending.take("main", true),
+            vec![PendingIpcMessage::Binary(vec![1])]
+        );
+        assert_eq!(pending.len("main"), 0);
+    }
+
+    #[test]
+    fn test_buffer_is_bounded() {
+        let mut pending = PendingIpcMessages::new();
+        for i in 0..MAX_PENDING_IPC_MESSAGES {
+            assert!(pending.push("main", text(&i.to_string())));
+        }
+        assert!(!pending.push("main", text("overflow")));
+        assert_eq!(pending.len("main"), MAX_PENDING_IPC_MESSAGES);
+
+        let taken = pending.take("main", false);
+        assert_eq!(taken.first(), Some(&text("1")));
+        assert_eq!(taken.last(), Some(&text("overflow")));
+    }
+
+    #[test]
+    fn test_frames_are_independent() {
+        let mut pending = PendingIpcMessages::new();
+        pending.push("main", text("a"));
+        pending.push("child", text("b"));
+
+        pending.clear("main");
+        assert!(pending.take("main", false).is_empty());
+        assert_eq!(pending.take("child", false), vec![text("b")]);
+    }
+}
@@ -0,0 +1,32 @@
+(function() {
+    if (window.__ipcHelperInitialized) return;
+    window.__ipcHelperInitialized = true;
+
+    // Messages sent from Godot before the page installs its handler are buffered
+    // in the render process. Turning the handler slots into accessors lets us
+    // flush that buffer as soon as a handler is assigned.
+    function defineHandlerProperty(name, kind) {
+        let handler = window[name];
+
+        Object.defineProperty(window, name, {
+            configurable: true,
+            enumerable: true,
+            get: function() {
+                return handler;
+            },
+            set: function(value) {
+                handler = value;
+                if (typeof value === 'function' && window.__flushIpcMessages) {
+                    window.__flushIpcMessages(kind);
+                }
+            }
+        });
+
+        if (typeof handler === 'function' && window.__flushIpcMessages) {
+            window.__flushIpcMessages(kind);
+        }
+    }
+
+    defineHandlerProperty('onIpcMessage', 'text');
+    defineHandlerProperty('onIpcBinaryMessage', 'binary');
+})();
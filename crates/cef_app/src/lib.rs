@@ -1,15 +1,31 @@
 mod app;
 mod browser_process;
+mod crash_reporting;
+mod ipc_buffer;
 mod loader;
 mod render_handler;
 mod render_process;
+mod startup_scripts;
+mod switches;
 mod types;
 mod v8_handlers;
 
-pub use app::{GodotRenderBackend, GpuDeviceIds, OsrApp, OsrAppBuilder, SecurityConfig};
-pub use loader::{load_cef_framework_from_path, load_sandbox_from_path};
+pub use app::{GodotRenderBackend, GpuDeviceIds, LogOutput, OsrApp, OsrAppBuilder, SecurityConfig};
+pub use browser_process::child_process_launch_count;
+pub use crash_reporting::{
+    CRASH_DUMP_LOCATION_ENV, CrashDump, crash_reporter_config_dir, find_crash_dumps,
+    remove_crash_reporter_config, write_crash_reporter_config,
+};
+pub use loader::{LoaderError, load_cef_framework_from_path, load_sandbox_from_path};
 pub use render_handler::OsrRenderHandler;
-pub use types::{CursorType, FrameBuffer, PhysicalSize, PopupRect, PopupState};
+pub use startup_scripts::{STARTUP_SCRIPTS_KEY, startup_scripts_list};
+pub use switches::{
+    DEFAULT_IPC_BINARY_LIMIT, PREFLIGHT_EXIT_CODE, PREFLIGHT_PROCESS_TYPE, ProcessEnvironment,
+    SubprocessConfig, decode_ports,
+};
+pub use types::{
+    CursorType, DomQueryKind, FrameBuffer, PhysicalSize, PopupRect, PopupState, TextInputType,
+};
 
 use crate::browser_process::{BrowserProcessHandlerBuilder, OsrBrowserProcessHandler};
 use crate::render_process::{OsrRenderProcessHandler, RenderProcessHandlerBuilder};
@@ -26,22 +42,13 @@ wrap_app! {
                 return;
             };
 
-            let options = cef::SchemeOptions::STANDARD.get_raw()
-                | cef::SchemeOptions::LOCAL.get_raw()
-                | cef::SchemeOptions::SECURE.get_raw()
-                | cef::SchemeOptions::CORS_ENABLED.get_raw()
-                | cef::SchemeOptions::FETCH_ENABLED.get_raw()
-                | cef::SchemeOptions::CSP_BYPASSING.get_raw();
-
-            #[cfg(target_os = "windows")]
-            {
-                registrar.add_custom_scheme(Some(&"res".into()), options);
-                registrar.add_custom_scheme(Some(&"user".into()), options);
-            }
-            #[cfg(not(target_os = "windows"))]
-            {
-                registrar.add_custom_scheme(Some(&"res".into()), options as i32);
-                registrar.add_custom_scheme(Some(&"user".into()), options as i32);
+            let options = godot_scheme_options(self.app.enforce_scheme_csp());
+            let schemes = ["res", "user"]
+                .into_iter()
+                .chain(self.app.custom_schemes().iter().map(String::as_str));
+
+            for scheme in schemes {
+                registrar.add_custom_scheme(Some(&scheme.into()), options as _);
             }
         }
 
@@ -59,12 +66,23 @@ wrap_app! {
             command_line.append_switch(Some(&"noerrdialogs".into()));
             command_line.append_switch(Some(&"hide-crash-restore-bubble".into()));
             command_line.append_switch(Some(&"use-mock-keychain".into()));
-            command_line.append_switch(Some(&"enable-logging=stderr".into()));
             command_line.append_switch(Some(&"transparent-painting-enabled".into()));
             command_line.append_switch(Some(&"enable-zero-copy".into()));
             command_line.append_switch(Some(&"off-screen-rendering-enabled".into()));
             command_line.append_switch(Some(&"use-views".into()));
 
+            // Subprocesses already got these from the browser process
+            for (name, value) in self.app.log_output().switches() {
+                if command_line.has_switch(Some(&name.into())) == 1 {
+                    continue;
+                }
+                match value {
+                    Some(value) => command_line
+                        .append_switch_with_value(Some(&name.into()), Some(&value.as_str().into())),
+                    None => command_line.append_switch(Some(&name.into())),
+                }
+            }
+
             // Only enable remote debugging in debug builds or when running from the editor
             // for security purposes. In production builds, this should be disabled.
             if self.app.enable_remote_debugging() {
@@ -103,6 +121,21 @@ wrap_app! {
                         .append_switch_with_value(Some(&"disk-cache-size".into()), Some(&cache_size_bytes.as_str().into()));
                 }
 
+            // Let pages reach local game servers as secure origins, so
+            // mixed-content rules do not block them. allow-insecure-localhost
+            // also accepts invalid certificates from every https://localhost
+            // and 127.0.0.1 port, not only these ones, so it is only added
+            // when the game opted in
+            let local_ports = self.app.local_ports();
+            if !local_ports.is_empty() {
+                let origins = switches::secure_localhost_origins(local_ports);
+                command_line.append_switch_with_value(
+                    Some(&"unsafely-treat-insecure-origin-as-secure".into()),
+                    Some(&origins.as_str().into()),
+                );
+                command_line.append_switch(Some(&"allow-insecure-localhost".into()));
+            }
+
             // Apply custom command-line switches
             for switch in self.app.custom_switches() {
                 let trimmed = switch.trim();
@@ -127,13 +160,20 @@ wrap_app! {
                 OsrBrowserProcessHandler::new(
                     self.app.security_config().clone(),
                     self.app.gpu_device_ids(),
+                    self.app.subprocess_config(),
+                    self.app.log_output().clone(),
                 ),
             ))
         }
 
         fn render_process_handler(&self) -> Option<cef::RenderProcessHandler> {
             Some(RenderProcessHandlerBuilder::build(
-                OsrRenderProcessHandler::new(),
+                OsrRenderProcessHandler::new(
+                    self.app.ipc_binary_limit(),
+                    self.app.web_notifications(),
+                    self.app.local_ports().to_vec(),
+                    self.app.custom_schemes().to_vec(),
+                ),
             ))
         }
     }
@@ -144,3 +184,45 @@ impl AppBuilder {
         Self::new(app)
     }
 }
+
+/// Options of the `res`, `user` and custom schemes. SECURE, CORS_ENABLED and
+/// FETCH_ENABLED let their pages fetch from other origins, including local
+/// game servers, without being treated as mixed content.
+fn godot_scheme_options(enforce_scheme_csp: bool) -> i64 {
+    let mut options = [
+        cef::SchemeOptions::STANDARD,
+        cef::SchemeOptions::LOCAL,
+        cef::SchemeOptions::SECURE,
+        cef::SchemeOptions::CORS_ENABLED,
+        cef::SchemeOptions::FETCH_ENABLED,
+    ]
+    .into_iter()
+    .fold(0, |options, option| options | option.get_raw() as i64);
+
+    // Pages only honour their Content-Security-Policy without CSP_BYPASSING
+    if !enforce_scheme_csp {
+        options |= cef::SchemeOptions::CSP_BYPASSING.get_raw() as i64;
+    }
+    options
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_godot_scheme_options() {
+        for enforce_scheme_csp in [false, true] {
+            let options = godot_scheme_options(enforce_scheme_csp);
+            for option in [
+                cef::SchemeOptions::SECURE,
+                cef::SchemeOptions::CORS_ENABLED,
+                cef::SchemeOptions::FETCH_ENABLED,
+            ] {
+                assert_ne!(options & option.get_raw() as i64, 0);
+            }
+            let csp_bypassing = cef::SchemeOptions::CSP_BYPASSING.get_raw() as i64;
+            assert_eq!(options & csp_bypassing == 0, enforce_scheme_csp);
+        }
+    }
+}
@@ -3,31 +3,62 @@
 //! This module provides shared functionality for loading the CEF framework
 //! and sandbox on different platforms.
 
-use std::path::Path;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Error returned when the CEF framework or sandbox cannot be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoaderError {
+    /// The library does not exist at the expected path.
+    MissingPath(PathBuf),
+    /// The path cannot be passed to the C API (e.g. it contains a NUL byte).
+    InvalidPath(OsString),
+    /// The dynamic loader rejected the library.
+    DlopenFailed(String),
+    /// The sandbox library does not export `cef_sandbox_initialize`.
+    SandboxInitFailed,
+}
+
+impl fmt::Display for LoaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoaderError::MissingPath(path) => {
+                write!(f, "library not found at {}", path.display())
+            }
+            LoaderError::InvalidPath(path) => {
+                write!(f, "invalid library path: {}", path.to_string_lossy())
+            }
+            LoaderError::DlopenFailed(msg) => write!(f, "failed to load library: {}", msg),
+            LoaderError::SandboxInitFailed => {
+                write!(f, "cef_sandbox_initialize not found in the sandbox library")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoaderError {}
 
 /// Loads the CEF framework library from the given path (macOS-specific).
 ///
 /// # Arguments
 /// * `framework_path` - Path to the `Chromium Embedded Framework.framework` directory.
 ///
-/// # Panics
-/// Panics if the framework cannot be loaded.
-///
 /// # Safety
 /// This function calls the CEF C API directly to load the library. The path must
 /// point to a valid CEF framework.
 #[cfg(target_os = "macos")]
-pub fn load_cef_framework_from_path(framework_path: &Path) {
+pub fn load_cef_framework_from_path(framework_path: &Path) -> Result<(), LoaderError> {
     use cef::sys::cef_load_library;
 
-    let path = framework_path
-        .join("Chromium Embedded Framework")
+    let library_path = framework_path.join("Chromium Embedded Framework");
+    let path = library_path
         .canonicalize()
-        .expect("Failed to canonicalize CEF framework path");
+        .map_err(|_| LoaderError::MissingPath(library_path))?;
 
     use std::os::unix::ffi::OsStrExt;
     let path = std::ffi::CString::new(path.as_os_str().as_bytes())
-        .expect("Failed to convert library path to CString");
+        .map_err(|_| LoaderError::InvalidPath(path.into_os_string()))?;
 
     // SAFETY: We're calling the CEF C API with a valid path. The path has been
     // validated above by canonicalize(). The cef_load_library function is
@@ -38,13 +69,20 @@ pub fn load_cef_framework_from_path(framework_path: &Path) {
         cef_load_library(arg_path) == 1
     };
 
-    assert!(result, "Failed to load macOS CEF framework");
+    if result {
+        Ok(())
+    } else {
+        Err(LoaderError::DlopenFailed(
+            "cef_load_library returned an error".to_string(),
+        ))
+    }
 }
 
 /// No-op on non-macOS platforms.
 #[cfg(not(target_os = "macos"))]
-pub fn load_cef_framework_from_path(_framework_path: &Path) {
+pub fn load_cef_framework_from_path(_framework_path: &Path) -> Result<(), LoaderError> {
     // CEF is linked directly on Windows and Linux
+    Ok(())
 }
 
 /// Loads the CEF sandbox from the given framework path (macOS-specific).
@@ -57,30 +95,56 @@ pub fn load_cef_framework_from_path(_framework_path: &Path) {
 /// This function dynamically loads and calls the CEF sandbox initialization function.
 /// The framework_path must point to a valid CEF framework containing the sandbox library.
 #[cfg(target_os = "macos")]
-pub fn load_sandbox_from_path(framework_path: &Path, args: &cef::MainArgs) {
+pub fn load_sandbox_from_path(
+    framework_path: &Path,
+    args: &cef::MainArgs,
+) -> Result<(), LoaderError> {
     use libloading::Library;
 
-    let path = framework_path
-        .join("Libraries/libcef_sandbox.dylib")
+    let library_path = framework_path.join("Libraries/libcef_sandbox.dylib");
+    let path = library_path
         .canonicalize()
-        .expect("Failed to canonicalize sandbox library path");
+        .map_err(|_| LoaderError::MissingPath(library_path))?;
 
     // SAFETY: We're loading a known CEF library and calling its documented
     // initialization function. The library path has been validated.
     unsafe {
-        let lib = Library::new(path).expect("Failed to load CEF sandbox library");
+        let lib = Library::new(path).map_err(|e| LoaderError::DlopenFailed(e.to_string()))?;
         let func =
             lib.get::<unsafe extern "C" fn(
                 argc: std::os::raw::c_int,
                 argv: *mut *mut ::std::os::raw::c_char,
             )>(b"cef_sandbox_initialize\0")
-                .expect("Failed to find cef_sandbox_initialize function");
+                .map_err(|_| LoaderError::SandboxInitFailed)?;
         func(args.argc, args.argv);
     }
+    Ok(())
 }
 
 /// No-op on non-macOS platforms.
 #[cfg(not(target_os = "macos"))]
-pub fn load_sandbox_from_path(_framework_path: &Path, _args: &cef::MainArgs) {
+pub fn load_sandbox_from_path(
+    _framework_path: &Path,
+    _args: &cef::MainArgs,
+) -> Result<(), LoaderError> {
     // Sandbox is handled differently on Windows and Linux
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loader_error_display() {
+        let missing = LoaderError::MissingPath(PathBuf::from("/bin/libcef_sandbox.dylib"));
+        assert_eq!(
+            missing.to_string(),
+            "library not found at /bin/libcef_sandbox.dylib"
+        );
+        assert_eq!(
+            LoaderError::DlopenFailed("image not found".to_string()).to_string(),
+            "failed to load library: image not found"
+        );
+    }
 }
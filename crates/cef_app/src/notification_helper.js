@@ -0,0 +1,121 @@
+(function() {
+    if (window.__notificationHelperInitialized) return;
+    window.__notificationHelperInitialized = true;
+
+    if (typeof window.__showNotification !== 'function') return;
+
+    let nextId = 1;
+    // Notifications shown and not closed yet, by the tag given to Godot
+    const shown = new Map();
+
+    function callHandler(notification, type) {
+        const event = new Event(type);
+        const handler = notification['on' + type];
+        if (typeof handler === 'function') {
+            try {
+                handler.call(notification, event);
+            } catch (error) {
+                setTimeout(function() { throw error; });
+            }
+        }
+        notification.dispatchEvent(event);
+    }
+
+    function resolveUrl(url) {
+        if (!url) return '';
+        try {
+            return new URL(String(url), document.baseURI).href;
+        } catch (error) {
+            return '';
+        }
+    }
+
+    // There is no system notification UI in an off-screen browser; the
+    // notification is shown by the game from `web_notification`
+    class Notification extends EventTarget {
+        constructor(title, options) {
+            super();
+            if (arguments.length === 0) {
+                throw new TypeError("Failed to construct 'Notification': 1 argument required, but only 0 present.");
+            }
+            options = options || {};
+            this.title = String(title);
+            this.body = options.body === undefined ? '' : String(options.body);
+            this.icon = resolveUrl(options.icon);
+            this.tag = options.tag === undefined ? '' : String(options.tag);
+            this.data = options.data === undefined ? null : options.data;
+            this.dir = options.dir || 'auto';
+            this.lang = options.lang || '';
+            this.silent = options.silent === undefined ? null : !!options.silent;
+            this.requireInteraction = !!options.requireInteraction;
+            this.onclick = null;
+            this.onshow = null;
+            this.onclose = null;
+            this.onerror = null;
+
+            // Godot identifies notifications by tag, so untagged ones get one
+            const tag = this.tag || 'godot-notification-' + nextId++;
+            Object.defineProperty(this, '__godotTag', { value: tag });
+
+            const notification = this;
+            setTimeout(function() {
+                // A notification with the same tag replaces the shown one
+                shown.set(tag, notification);
+                if (window.__showNotification(notification.title, notification.body, notification.icon, tag)) {
+                    callHandler(notification, 'show');
+                } else {
+                    shown.delete(tag);
+                    callHandler(notification, 'error');
+                }
+            });
+        }
+
+        close() {
+            const tag = this.__godotTag;
+            if (shown.get(tag) !== this) return;
+            shown.delete(tag);
+            window.__closeNotification(tag);
+            callHandler(this, 'close');
+        }
+
+        static get permission() {
+            return 'granted';
+        }
+
+        static requestPermission(callback) {
+            if (typeof callback === 'function') {
+                setTimeout(function() { callback('granted'); });
+            }
+            return Promise.resolve('granted');
+        }
+
+        static get maxActions() {
+            return 0;
+        }
+    }
+
+    Object.defineProperty(window, 'Notification', {
+        value: Notification,
+        writable: true,
+        configurable: true,
+    });
+
+    // Called with activate_web_notification() when the player clicks the
+    // game's notification
+    window.__activateNotification = function(tag) {
+        const notification = shown.get(tag);
+        if (notification) callHandler(notification, 'click');
+    };
+
+    // Libraries check the permission before creating notifications
+    const permissions = navigator.permissions;
+    if (permissions && typeof permissions.query === 'function') {
+        const query = permissions.query.bind(permissions);
+        permissions.query = function(descriptor) {
+            if (descriptor && descriptor.name === 'notifications') {
+                return Promise.resolve({ name: 'notifications', state: 'granted', onchange: null });
+            }
+            return query(descriptor);
+        };
+    }
+})();
@@ -5,6 +5,8 @@ use crate::types::{CursorType, FrameBuffer, PhysicalSize, PopupState};
 #[derive(Clone)]
 pub struct OsrRenderHandler {
     pub device_scale_factor: Arc<Mutex<f32>>,
+    /// Scale factor of the screen the view is on, reported to CEF.
+    pub display_scale: Arc<Mutex<f32>>,
     pub size: Arc<Mutex<PhysicalSize<f32>>>,
     pub frame_buffer: Arc<Mutex<FrameBuffer>>,
     pub cursor_type: Arc<Mutex<CursorType>>,
@@ -12,10 +14,11 @@ pub struct OsrRenderHandler {
 }
 
 impl OsrRenderHandler {
-    pub fn new(device_scale_factor: f32, size: PhysicalSize<f32>) -> Self {
+    pub fn new(device_scale_factor: f32, display_scale: f32, size: PhysicalSize<f32>) -> Self {
         Self {
             size: Arc::new(Mutex::new(size)),
             device_scale_factor: Arc::new(Mutex::new(device_scale_factor)),
+            display_scale: Arc::new(Mutex::new(display_scale)),
             frame_buffer: Arc::new(Mutex::new(FrameBuffer::new())),
             cursor_type: Arc::new(Mutex::new(CursorType::default())),
             popup_state: Arc::new(Mutex::new(PopupState::new())),
@@ -34,6 +37,10 @@ impl OsrRenderHandler {
         self.device_scale_factor.clone()
     }
 
+    pub fn get_display_scale(&self) -> Arc<Mutex<f32>> {
+        self.display_scale.clone()
+    }
+
     pub fn get_cursor_type(&self) -> Arc<Mutex<CursorType>> {
         self.cursor_type.clone()
     }
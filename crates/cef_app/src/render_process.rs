@@ -1,27 +1,119 @@
+use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
 
 use cef::sys::cef_v8_propertyattribute_t;
 use cef::{
-    Browser, CefStringUtf16, Domnode, Frame, ImplBinaryValue, ImplDomnode, ImplFrame,
-    ImplListValue, ImplProcessMessage, ImplRenderProcessHandler, ImplV8Context, ImplV8Value,
-    ProcessId, ProcessMessage, RenderProcessHandler, V8Context, V8Propertyattribute,
-    WrapRenderProcessHandler, process_message_create, rc::Rc,
-    v8_value_create_array_buffer_with_copy, v8_value_create_function, v8_value_create_string,
+    Browser, CefStringUtf16, DictionaryValue, Domnode, Frame, ImplBinaryValue, ImplBrowser,
+    ImplDictionaryValue, ImplDomnode, ImplFrame, ImplListValue, ImplProcessMessage,
+    ImplRenderProcessHandler, ImplV8Context, ImplV8Value, ListValue, ProcessId, ProcessMessage,
+    RenderProcessHandler, V8Context, V8Propertyattribute, V8Value, WrapRenderProcessHandler,
+    process_message_create, rc::Rc, v8_value_create_array_buffer_with_copy, v8_value_create_bool,
+    v8_value_create_function, v8_value_create_int, v8_value_create_string,
     wrap_render_process_handler,
 };
 
+use crate::ipc_buffer::{PendingIpcMessage, PendingIpcMessages};
+use crate::startup_scripts::{STARTUP_SCRIPTS_KEY, StartupScripts, read_startup_scripts};
 use crate::v8_handlers::{
-    OsrImeCaretHandler, OsrImeCaretHandlerBuilder, OsrIpcBinaryHandler, OsrIpcBinaryHandlerBuilder,
-    OsrIpcHandler, OsrIpcHandlerBuilder,
+    OsrDisplayMediaHandler, OsrDisplayMediaHandlerBuilder, OsrFirstPaintHandler,
+    OsrFirstPaintHandlerBuilder, OsrImeCaretHandler, OsrImeCaretHandlerBuilder,
+    OsrIpcBinaryHandler, OsrIpcBinaryHandlerBuilder, OsrIpcFlushHandler, OsrIpcFlushHandlerBuilder,
+    OsrIpcHandler, OsrIpcHandlerBuilder, OsrNotificationHandler, OsrNotificationHandlerBuilder,
+    OsrScrollPositionHandler, OsrScrollPositionHandlerBuilder,
 };
+use crate::{DomQueryKind, TextInputType};
 
 #[derive(Clone)]
-pub(crate) struct OsrRenderProcessHandler {}
+pub(crate) struct OsrRenderProcessHandler {
+    ipc_binary_limit: usize,
+    /// Replace `window.Notification` with the shim forwarding to Godot.
+    web_notifications: bool,
+    /// Ports of local game servers, exposed as `window.GODOT_LOCAL_PORTS`.
+    local_ports: Vec<u16>,
+    /// Custom schemes whose frames, like `res://` and `user://` ones, get
+    /// `window.GODOT_LOCAL_PORTS`.
+    custom_schemes: Vec<String>,
+    pending_ipc: Arc<Mutex<PendingIpcMessages>>,
+    startup_scripts: Arc<Mutex<StartupScripts>>,
+    /// Browsers this process already reported its pid to.
+    reported_browsers: Arc<Mutex<HashSet<i32>>>,
+}
 
 impl OsrRenderProcessHandler {
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(
+        ipc_binary_limit: usize,
+        web_notifications: bool,
+        local_ports: Vec<u16>,
+        custom_schemes: Vec<String>,
+    ) -> Self {
+        Self {
+            ipc_binary_limit,
+            web_notifications,
+            local_ports,
+            custom_schemes,
+            pending_ipc: Arc::new(Mutex::new(PendingIpcMessages::new())),
+            startup_scripts: Arc::new(Mutex::new(StartupScripts::default())),
+            reported_browsers: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Buffers a message until the page installs its handler.
+    /// When the buffer is full the oldest message is dropped.
+    fn buffer_ipc_message(&self, frame: &Frame, message: PendingIpcMessage) {
+        if let Ok(mut pending) = self.pending_ipc.lock() {
+            pending.push(&frame_identifier(frame), message);
+        }
+    }
+
+    /// Tells the browser process which process renders `browser`, once per
+    /// browser. A cross-process navigation reports from the new process.
+    fn report_process_id(&self, browser: &Browser, frame: &Frame) {
+        let first = self
+            .reported_browsers
+            .lock()
+            .is_ok_and(|mut reported| reported.insert(browser.identifier()));
+        if !first {
+            return;
+        }
+        let route = CefStringUtf16::from("rendererProcessId");
+        if let Some(mut process_message) = process_message_create(Some(&route)) {
+            if let Some(args) = process_message.argument_list() {
+                args.set_int(0, std::process::id() as i32);
+            }
+            frame.send_process_message(ProcessId::BROWSER, Some(&mut process_message));
+        }
     }
+
+    fn set_startup_scripts(&self, browser: &Browser, list: &cef::ListValue) {
+        if let Ok(mut startup_scripts) = self.startup_scripts.lock() {
+            startup_scripts.set(browser.identifier(), read_startup_scripts(list));
+        }
+    }
+}
+
+/// Defines `window.GODOT_LOCAL_PORTS`, a frozen array of the local game
+/// server ports, so pages do not hardcode them.
+fn local_ports_script(ports: &[u16]) -> String {
+    let ports = ports
+        .iter()
+        .map(u16::to_string)
+        .collect::<Vec<_>>()
+        .join(",");
+    format!("Object.defineProperty(window,'GODOT_LOCAL_PORTS',{{value:Object.freeze([{ports}])}});")
+}
+
+/// Whether a frame showing `url` gets `window.GODOT_LOCAL_PORTS`: only the
+/// game's own pages from `res://`, `user://` or a custom scheme, so web
+/// content in other frames cannot discover the local servers.
+fn exposes_local_ports(url: &str, custom_schemes: &[String]) -> bool {
+    let Some((scheme, _)) = url.split_once(':') else {
+        return false;
+    };
+    scheme.eq_ignore_ascii_case("res")
+        || scheme.eq_ignore_ascii_case("user")
+        || custom_schemes
+            .iter()
+            .any(|custom| custom.eq_ignore_ascii_case(scheme))
 }
 
 wrap_render_process_handler! {
@@ -30,11 +122,37 @@ wrap_render_process_handler! {
     }
 
     impl RenderProcessHandler {
-        fn on_context_created(&self, _browser: Option<&mut Browser>, frame: Option<&mut Frame>, context: Option<&mut V8Context>) {
+        fn on_browser_created(&self, browser: Option<&mut Browser>, extra_info: Option<&mut DictionaryValue>) {
+            // Arrives before the first navigation commits, unlike a process
+            // message from the browser process
+            if let Some(browser) = browser
+                && let Some(extra_info) = extra_info
+                && let Some(list) = extra_info.list(Some(&STARTUP_SCRIPTS_KEY.into())) {
+                    self.handler.set_startup_scripts(browser, &list);
+                }
+        }
+
+        fn on_browser_destroyed(&self, browser: Option<&mut Browser>) {
+            if let Some(browser) = browser {
+                if let Ok(mut startup_scripts) = self.handler.startup_scripts.lock() {
+                    startup_scripts.remove(browser.identifier());
+                }
+                if let Ok(mut reported) = self.handler.reported_browsers.lock() {
+                    reported.remove(&browser.identifier());
+                }
+            }
+        }
+
+        fn on_context_created(&self, browser: Option<&mut Browser>, frame: Option<&mut Frame>, context: Option<&mut V8Context>) {
             if let Some(context) = context {
                 let global = context.global();
                 if let Some(global) = global
                     && let Some(frame) = frame {
+                        if frame.is_main() != 0
+                            && let Some(browser) = browser.as_deref() {
+                                self.handler.report_process_id(browser, frame);
+                            }
+
                         let frame_arc = Arc::new(Mutex::new(frame.clone()));
 
                         let key: cef::CefStringUtf16 = "sendIpcMessage".to_string().as_str().into();
@@ -43,21 +161,104 @@ wrap_render_process_handler! {
                         global.set_value_bykey(Some(&key), Some(&mut func), V8Propertyattribute::from(cef_v8_propertyattribute_t(0)));
 
                         let binary_key: cef::CefStringUtf16 = "sendIpcBinaryMessage".into();
-                        let mut binary_handler = OsrIpcBinaryHandlerBuilder::build(OsrIpcBinaryHandler::new(Some(frame_arc.clone())));
+                        let mut binary_handler = OsrIpcBinaryHandlerBuilder::build(OsrIpcBinaryHandler::new(Some(frame_arc.clone()), self.handler.ipc_binary_limit));
                         let mut binary_func = v8_value_create_function(Some(&"sendIpcBinaryMessage".into()), Some(&mut binary_handler)).unwrap();
                         global.set_value_bykey(Some(&binary_key), Some(&mut binary_func), V8Propertyattribute::from(cef_v8_propertyattribute_t(0)));
 
+                        let flush_key: cef::CefStringUtf16 = "__flushIpcMessages".into();
+                        let mut flush_handler = OsrIpcFlushHandlerBuilder::build(OsrIpcFlushHandler::new(Some(frame_arc.clone()), self.handler.pending_ipc.clone()));
+                        let mut flush_func = v8_value_create_function(Some(&"__flushIpcMessages".into()), Some(&mut flush_handler)).unwrap();
+                        global.set_value_bykey(Some(&flush_key), Some(&mut flush_func), V8Propertyattribute::from(cef_v8_propertyattribute_t(0)));
+
                         let caret_key: cef::CefStringUtf16 = "__sendImeCaretPosition".into();
-                        let mut caret_handler = OsrImeCaretHandlerBuilder::build(OsrImeCaretHandler::new(Some(frame_arc)));
+                        let mut caret_handler = OsrImeCaretHandlerBuilder::build(OsrImeCaretHandler::new(Some(frame_arc.clone())));
                         let mut caret_func = v8_value_create_function(Some(&"__sendImeCaretPosition".into()), Some(&mut caret_handler)).unwrap();
                         global.set_value_bykey(Some(&caret_key), Some(&mut caret_func), V8Propertyattribute::from(cef_v8_propertyattribute_t(0)));
 
+                        for (name, route) in [("__requestDisplayMedia", "displayMediaRequest"), ("__stopDisplayMedia", "displayMediaStopped")] {
+                            let display_media_key: cef::CefStringUtf16 = name.into();
+                            let mut display_media_handler = OsrDisplayMediaHandlerBuilder::build(OsrDisplayMediaHandler::new(Some(frame_arc.clone()), route));
+                            let mut display_media_func = v8_value_create_function(Some(&name.into()), Some(&mut display_media_handler)).unwrap();
+                            global.set_value_bykey(Some(&display_media_key), Some(&mut display_media_func), V8Propertyattribute::from(cef_v8_propertyattribute_t(0)));
+                        }
+
+                        if self.handler.web_notifications {
+                            for (name, route) in [("__showNotification", "notificationShown"), ("__closeNotification", "notificationClosed")] {
+                                let notification_key: cef::CefStringUtf16 = name.into();
+                                let mut notification_handler = OsrNotificationHandlerBuilder::build(OsrNotificationHandler::new(Some(frame_arc.clone()), route));
+                                let mut notification_func = v8_value_create_function(Some(&name.into()), Some(&mut notification_handler)).unwrap();
+                                global.set_value_bykey(Some(&notification_key), Some(&mut notification_func), V8Propertyattribute::from(cef_v8_propertyattribute_t(0)));
+                            }
+                        }
+
+                        let scroll_key: cef::CefStringUtf16 = "__reportScrollPosition".into();
+                        let mut scroll_handler = OsrScrollPositionHandlerBuilder::build(OsrScrollPositionHandler::new(Some(frame_arc.clone())));
+                        let mut scroll_func = v8_value_create_function(Some(&"__reportScrollPosition".into()), Some(&mut scroll_handler)).unwrap();
+                        global.set_value_bykey(Some(&scroll_key), Some(&mut scroll_func), V8Propertyattribute::from(cef_v8_propertyattribute_t(0)));
+
+                        let first_paint_key: cef::CefStringUtf16 = "__reportFirstPaint".into();
+                        let mut first_paint_handler = OsrFirstPaintHandlerBuilder::build(OsrFirstPaintHandler::new(Some(frame_arc.clone())));
+                        let mut first_paint_func = v8_value_create_function(Some(&"__reportFirstPaint".into()), Some(&mut first_paint_handler)).unwrap();
+                        global.set_value_bykey(Some(&first_paint_key), Some(&mut first_paint_func), V8Propertyattribute::from(cef_v8_propertyattribute_t(0)));
+
+                        let ipc_script: cef::CefStringUtf16 = include_str!("ipc_helper.js").into();
+                        frame.execute_java_script(Some(&ipc_script), None, 0);
+
                         let helper_script: cef::CefStringUtf16 = include_str!("ime_helper.js").into();
                         frame.execute_java_script(Some(&helper_script), None, 0);
+
+                        let display_media_script: cef::CefStringUtf16 = include_str!("display_media_helper.js").into();
+                        frame.execute_java_script(Some(&display_media_script), None, 0);
+
+                        if self.handler.web_notifications {
+                            let notification_script: cef::CefStringUtf16 = include_str!("notification_helper.js").into();
+                            frame.execute_java_script(Some(&notification_script), None, 0);
+                        }
+
+                        let session_script: cef::CefStringUtf16 = include_str!("session_helper.js").into();
+                        frame.execute_java_script(Some(&session_script), None, 0);
+
+                        let first_paint_script: cef::CefStringUtf16 = include_str!("first_paint_helper.js").into();
+                        frame.execute_java_script(Some(&first_paint_script), None, 0);
+
+                        let dom_query_script: cef::CefStringUtf16 = include_str!("dom_query_helper.js").into();
+                        frame.execute_java_script(Some(&dom_query_script), None, 0);
+
+                        let url = CefStringUtf16::from(&frame.url()).to_string();
+                        if exposes_local_ports(&url, &self.handler.custom_schemes) {
+                            let local_ports_script: cef::CefStringUtf16 = local_ports_script(&self.handler.local_ports).as_str().into();
+                            frame.execute_java_script(Some(&local_ports_script), None, 0);
+                        }
+
+                        // Run after the bindings are installed, each on its own so
+                        // one that throws does not stop the others
+                        let startup_scripts = browser
+                            .zip(self.handler.startup_scripts.lock().ok())
+                            .map(|(browser, scripts)| scripts.get(browser.identifier()).to_vec())
+                            .unwrap_or_default();
+                        for script in startup_scripts {
+                            let script: cef::CefStringUtf16 = script.as_str().into();
+                            frame.execute_java_script(Some(&script), None, 0);
+                        }
                     }
             }
         }
 
+        fn on_context_released(&self, _browser: Option<&mut Browser>, frame: Option<&mut Frame>, _context: Option<&mut V8Context>) {
+            // Messages buffered for the old document must not leak into the next one
+            if let Some(frame) = frame {
+                if let Ok(mut pending) = self.handler.pending_ipc.lock() {
+                    pending.clear(&frame_identifier(frame));
+                }
+
+                // Captures of the old document end with it
+                let route = CefStringUtf16::from("displayMediaReleased");
+                if let Some(mut process_message) = process_message_create(Some(&route)) {
+                    frame.send_process_message(ProcessId::BROWSER, Some(&mut process_message));
+                }
+            }
+        }
+
         fn on_focused_node_changed(&self, _browser: Option<&mut Browser>, frame: Option<&mut Frame>, node: Option<&mut Domnode>) {
             if let Some(node) = node
                 && node.is_editable() == 1 {
@@ -67,6 +268,7 @@ wrap_render_process_handler! {
                     if let Some(mut process_message) = process_message {
                         if let Some(argument_list) = process_message.argument_list() {
                             argument_list.set_bool(0, true as _);
+                            argument_list.set_int(1, text_input_type(node) as i32);
                         }
 
                         if let Some(frame) = frame {
@@ -96,7 +298,7 @@ wrap_render_process_handler! {
 
         fn on_process_message_received(
             &self,
-            _browser: Option<&mut Browser>,
+            browser: Option<&mut Browser>,
             frame: Option<&mut Frame>,
             _source_process: ProcessId,
             message: Option<&mut ProcessMessage>,
@@ -110,27 +312,74 @@ wrap_render_process_handler! {
                         let msg_cef = args.string(0);
                         let msg_str = CefStringUtf16::from(&msg_cef);
 
-                        if let Some(frame) = frame {
-                            invoke_js_string_callback(frame, "onIpcMessage", &msg_str);
-                        }
+                        if let Some(frame) = frame
+                            && !invoke_js_string_callback(frame, "onIpcMessage", &msg_str) {
+                                self.handler.buffer_ipc_message(frame, PendingIpcMessage::Text(msg_str.to_string()));
+                            }
                     }
                     return 1;
                 }
                 "ipcBinaryGodotToRenderer" => {
                     if let Some(args) = message.argument_list()
-                        && let Some(binary_value) = args.binary(0) {
-                            let size = binary_value.size();
-                            if size > 0 {
-                                let mut buffer = vec![0u8; size];
-                                let copied = binary_value.data(Some(&mut buffer), 0);
-                                if copied > 0 {
-                                    buffer.truncate(copied);
-
-                                    if let Some(frame) = frame {
-                                        invoke_js_binary_callback(frame, "onIpcBinaryMessage", &buffer);
-                                    }
-                                }
-                            }
+                        && let Some(buffer) = binary_argument(&args, 0)
+                        && let Some(frame) = frame
+                        && !invoke_js_binary_callback(frame, "onIpcBinaryMessage", &buffer) {
+                            self.handler.buffer_ipc_message(frame, PendingIpcMessage::Binary(buffer));
+                        }
+                    return 1;
+                }
+                "displayMediaResponse" => {
+                    if let Some(args) = message.argument_list()
+                        && let Some(frame) = frame {
+                            invoke_js_callback(frame, "__resolveDisplayMedia", || {
+                                Some(vec![
+                                    v8_value_create_int(args.int(0)),
+                                    v8_value_create_bool(args.bool(1)),
+                                    v8_value_create_int(args.int(2)),
+                                    v8_value_create_int(args.int(3)),
+                                    v8_value_create_int(args.int(4)),
+                                ])
+                            });
+                        }
+                    return 1;
+                }
+                "displayMediaFrame" => {
+                    if let Some(args) = message.argument_list()
+                        && let Some(pixels) = binary_argument(&args, 3)
+                        && let Some(frame) = frame {
+                            invoke_js_callback(frame, "__pushDisplayMediaFrame", || {
+                                Some(vec![
+                                    v8_value_create_int(args.int(0)),
+                                    v8_value_create_int(args.int(1)),
+                                    v8_value_create_int(args.int(2)),
+                                    Some(create_array_buffer(&pixels)?),
+                                ])
+                            });
+                        }
+                    return 1;
+                }
+                "notificationActivate" => {
+                    if let Some(args) = message.argument_list()
+                        && let Some(frame) = frame {
+                            let tag = CefStringUtf16::from(&args.string(0));
+                            invoke_js_callback(frame, "__activateNotification", || {
+                                Some(vec![v8_value_create_string(Some(&tag))])
+                            });
+                        }
+                    return 1;
+                }
+                "domQuery" => {
+                    if let Some(args) = message.argument_list()
+                        && let Some(frame) = frame {
+                            answer_dom_query(frame, &args);
+                        }
+                    return 1;
+                }
+                STARTUP_SCRIPTS_KEY => {
+                    if let Some(browser) = browser
+                        && let Some(args) = message.argument_list()
+                        && let Some(list) = args.list(0) {
+                            self.handler.set_startup_scripts(browser, &list);
                         }
                     return 1;
                 }
@@ -142,46 +391,159 @@ wrap_render_process_handler! {
     }
 }
 
-/// Invoke a JavaScript callback with a string argument.
-fn invoke_js_string_callback(frame: &mut Frame, callback_name: &str, msg_str: &CefStringUtf16) {
+/// Returns a stable key for a frame within this render process.
+pub(crate) fn frame_identifier(frame: &Frame) -> String {
+    CefStringUtf16::from(&frame.identifier()).to_string()
+}
+
+/// Invoke a JavaScript callback with the arguments built by `args`, which
+/// runs inside the frame's V8 context.
+/// Returns `false` if the page has not installed the callback.
+pub(crate) fn invoke_js_callback(
+    frame: &mut Frame,
+    callback_name: &str,
+    args: impl FnOnce() -> Option<Vec<Option<V8Value>>>,
+) -> bool {
+    call_js_function(frame, callback_name, args, |_| ()).is_some()
+}
+
+/// Calls the global JavaScript function `function_name` with the arguments
+/// built by `args` and passes its return value (`None` if it threw) to
+/// `read`, still inside the frame's V8 context.
+/// Returns `None` if the page has not installed the function.
+fn call_js_function<T>(
+    frame: &mut Frame,
+    function_name: &str,
+    args: impl FnOnce() -> Option<Vec<Option<V8Value>>>,
+    read: impl FnOnce(Option<V8Value>) -> T,
+) -> Option<T> {
+    let mut result = None;
     if let Some(context) = frame.v8_context()
         && context.enter() != 0
     {
         if let Some(mut global) = context.global() {
-            let callback_key: CefStringUtf16 = callback_name.into();
-            if let Some(callback) = global.value_bykey(Some(&callback_key))
-                && callback.is_function() != 0
-                && let Some(str_value) = v8_value_create_string(Some(msg_str))
+            let function_key: CefStringUtf16 = function_name.into();
+            if let Some(function) = global.value_bykey(Some(&function_key))
+                && function.is_function() != 0
+                && let Some(args) = args()
             {
-                let args = [Some(str_value)];
-                let _ = callback.execute_function(Some(&mut global), Some(&args));
+                let value = function.execute_function(Some(&mut global), Some(&args));
+                result = Some(read(value));
             }
         }
         context.exit();
     }
+    result
 }
 
-/// Invoke a JavaScript callback with an ArrayBuffer argument.
-fn invoke_js_binary_callback(frame: &mut Frame, callback_name: &str, buffer: &[u8]) {
-    if let Some(context) = frame.v8_context()
-        && context.enter() != 0
-    {
-        if let Some(mut global) = context.global() {
-            let callback_key: CefStringUtf16 = callback_name.into();
-            let mut buffer_copy = buffer.to_owned();
-            if let Some(callback) = global.value_bykey(Some(&callback_key))
-                && callback.is_function() != 0
-                && let Some(array_buffer) = v8_value_create_array_buffer_with_copy(
-                    buffer_copy.as_mut_ptr(),
-                    buffer_copy.len(),
-                )
-            {
-                let args = [Some(array_buffer)];
-                let _ = callback.execute_function(Some(&mut global), Some(&args));
+/// Runs the page's DOM query helper for a `domQuery` message and sends the
+/// result back as `domQueryResult`: the kind, the selector and the HTML or
+/// count. Without a match or with an invalid selector, the HTML is empty and
+/// the count is -1.
+fn answer_dom_query(frame: &mut Frame, args: &ListValue) {
+    let kind = args.int(0);
+    let Some(query) = DomQueryKind::from_i32(kind) else {
+        return;
+    };
+    let selector = CefStringUtf16::from(&args.string(1));
+    let max_length = args.int(2);
+
+    let route = CefStringUtf16::from("domQueryResult");
+    let Some(mut process_message) = process_message_create(Some(&route)) else {
+        return;
+    };
+    let Some(result_args) = process_message.argument_list() else {
+        return;
+    };
+    result_args.set_int(0, kind);
+    result_args.set_string(1, Some(&selector));
+    match query {
+        DomQueryKind::Dump => result_args.set_string(2, Some(&CefStringUtf16::from(""))),
+        DomQueryKind::Count => result_args.set_int(2, -1),
+    };
+
+    call_js_function(
+        frame,
+        "__godotDomQuery",
+        || {
+            Some(vec![
+                v8_value_create_int(kind),
+                v8_value_create_string(Some(&selector)),
+                v8_value_create_int(max_length),
+            ])
+        },
+        |value| match value {
+            Some(value) if value.is_string() != 0 => {
+                let html = CefStringUtf16::from(&value.string_value());
+                result_args.set_string(2, Some(&html));
             }
-        }
-        context.exit();
+            Some(value) if value.is_int() != 0 => {
+                result_args.set_int(2, value.int_value());
+            }
+            _ => {}
+        },
+    );
+    frame.send_process_message(ProcessId::BROWSER, Some(&mut process_message));
+}
+
+/// Invoke a JavaScript callback with a string argument.
+/// Returns `false` if the page has not installed the callback.
+pub(crate) fn invoke_js_string_callback(
+    frame: &mut Frame,
+    callback_name: &str,
+    msg_str: &CefStringUtf16,
+) -> bool {
+    invoke_js_callback(frame, callback_name, || {
+        v8_value_create_string(Some(msg_str)).map(|value| vec![Some(value)])
+    })
+}
+
+/// Creates an ArrayBuffer holding a copy of `buffer`.
+fn create_array_buffer(buffer: &[u8]) -> Option<V8Value> {
+    let mut buffer_copy = buffer.to_owned();
+    v8_value_create_array_buffer_with_copy(buffer_copy.as_mut_ptr(), buffer_copy.len())
+}
+
+/// Invoke a JavaScript callback with an ArrayBuffer argument.
+/// Returns `false` if the page has not installed the callback.
+pub(crate) fn invoke_js_binary_callback(
+    frame: &mut Frame,
+    callback_name: &str,
+    buffer: &[u8],
+) -> bool {
+    invoke_js_callback(frame, callback_name, || {
+        create_array_buffer(buffer).map(|value| vec![Some(value)])
+    })
+}
+
+/// Reads the binary argument at `index` of a process message.
+fn binary_argument(args: &ListValue, index: usize) -> Option<Vec<u8>> {
+    let binary_value = args.binary(index)?;
+    let size = binary_value.size();
+    if size == 0 {
+        return None;
     }
+    let mut buffer = vec![0u8; size];
+    let copied = binary_value.data(Some(&mut buffer), 0);
+    if copied == 0 {
+        return None;
+    }
+    buffer.truncate(copied);
+    Some(buffer)
+}
+
+/// Reads the kind of text field from the focused element's attributes.
+fn text_input_type(node: &Domnode) -> TextInputType {
+    let attribute = |name: &str| {
+        let name = CefStringUtf16::from(name);
+        CefStringUtf16::from(&node.element_attribute(Some(&name))).to_string()
+    };
+    TextInputType::from_attributes(
+        &CefStringUtf16::from(&node.element_tag_name()).to_string(),
+        &attribute("type"),
+        &attribute("inputmode"),
+        &attribute("autocomplete"),
+    )
 }
 
 impl RenderProcessHandlerBuilder {
@@ -189,3 +551,42 @@ impl RenderProcessHandlerBuilder {
         Self::new(handler)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_ports_script() {
+        assert_eq!(
+            local_ports_script(&[8080, 9000]),
+            "Object.defineProperty(window,'GODOT_LOCAL_PORTS',{value:Object.freeze([8080,9000])});"
+        );
+        assert!(local_ports_script(&[]).contains("Object.freeze([])"));
+    }
+
+    #[test]
+    fn test_exposes_local_ports() {
+        let custom_schemes = vec!["game".to_string()];
+        assert!(exposes_local_ports("res://ui/menu.html", &custom_schemes));
+        assert!(exposes_local_ports(
+            "user://cache/page.html",
+            &custom_schemes
+        ));
+        assert!(exposes_local_ports(
+            "GAME://hud/index.html",
+            &custom_schemes
+        ));
+        assert!(!exposes_local_ports(
+            "https://example.com/",
+            &custom_schemes
+        ));
+        assert!(!exposes_local_ports(
+            "http://127.0.0.1:18080/",
+            &custom_schemes
+        ));
+        assert!(!exposes_local_ports("about:blank", &custom_schemes));
+        assert!(!exposes_local_ports("", &custom_schemes));
+        assert!(!exposes_local_ports("game://hud/index.html", &[]));
+    }
+}
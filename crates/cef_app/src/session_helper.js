@@ -0,0 +1,57 @@
+(function() {
+    if (window.__sessionHelperInitialized) return;
+    window.__sessionHelperInitialized = true;
+
+    // Only the main frame's scroll position is tracked, for saved sessions,
+    // get_scroll_position() and the scrolled signal
+    if (window !== window.top || typeof window.__reportScrollPosition !== 'function') return;
+
+    let reportScheduled = false;
+
+    function reportScrollPosition() {
+        reportScheduled = false;
+        const root = document.scrollingElement || document.documentElement;
+        const maxX = root ? Math.max(0, root.scrollWidth - root.clientWidth) : 0;
+        const maxY = root ? Math.max(0, root.scrollHeight - root.clientHeight) : 0;
+        window.__reportScrollPosition(window.scrollX, window.scrollY, maxX, maxY);
+    }
+
+    function scheduleReport() {
+        if (reportScheduled) return;
+        reportScheduled = true;
+        requestAnimationFrame(reportScrollPosition);
+    }
+
+    // Reported at most once per frame, so save_session() can read it at any
+    // time. Resizes change the scrollable range without scrolling.
+    window.addEventListener('scroll', scheduleReport, { passive: true });
+    window.addEventListener('resize', scheduleReport);
+    window.addEventListener('pageshow', reportScrollPosition);
+    if (typeof ResizeObserver === 'function') {
+        const observeDocument = function() {
+            if (document.documentElement) {
+                new ResizeObserver(scheduleReport).observe(document.documentElement);
+            }
+        };
+        if (document.documentElement) {
+            observeDocument();
+        } else {
+            document.addEventListener('DOMContentLoaded', observeDocument, { once: true });
+        }
+    }
+
+    // Content loaded after load_finished (images, client rendering) can keep
+    // the page too short to scroll to the position, so it is retried for a
+    // few frames until the page is tall enough
+    window.__restoreScrollPosition = function(x, y, maxFrames) {
+        let frames = 0;
+        function attempt() {
+            window.scrollTo(x, y);
+            const reached = Math.abs(window.scrollX - x) < 1 && Math.abs(window.scrollY - y) < 1;
+            if (!reached && ++frames < maxFrames) {
+                requestAnimationFrame(attempt);
+            }
+        }
+        attempt();
+    };
+})();
@@ -0,0 +1,82 @@
+//! Scripts set with `set_startup_scripts`, run in every new document before
+//! the page's own scripts.
+//!
+//! The browser process embeds the scripts in the browser's `extra_info`,
+//! which each render process receives in `on_browser_created` before the
+//! first navigation commits, and sends later changes with a process message.
+//! Scripts are kept per browser, since browsers can share a render process.
+
+use std::collections::HashMap;
+
+use cef::{CefStringUtf16, ImplListValue, ListValue, list_value_create};
+
+/// Key of the scripts in the browser's `extra_info`, and route of the
+/// process message that updates them.
+pub const STARTUP_SCRIPTS_KEY: &str = "startupScripts";
+
+/// Returns the scripts as a CEF list, for `extra_info` and process messages.
+pub fn startup_scripts_list(scripts: &[String]) -> Option<ListValue> {
+    let list = list_value_create()?;
+    list.set_size(scripts.len());
+    for (index, script) in scripts.iter().enumerate() {
+        list.set_string(index, Some(&CefStringUtf16::from(script.as_str())));
+    }
+    Some(list)
+}
+
+/// Reads the scripts back from a CEF list.
+pub(crate) fn read_startup_scripts(list: &ListValue) -> Vec<String> {
+    (0..list.size())
+        .map(|index| CefStringUtf16::from(&list.string(index)).to_string())
+        .collect()
+}
+
+#[derive(Default)]
+pub(crate) struct StartupScripts {
+    browsers: HashMap<i32, Vec<String>>,
+}
+
+impl StartupScripts {
+    /// Replaces the scripts of a browser.
+    pub fn set(&mut self, browser_id: i32, scripts: Vec<String>) {
+        if scripts.is_empty() {
+            self.browsers.remove(&browser_id);
+        } else {
+            self.browsers.insert(browser_id, scripts);
+        }
+    }
+
+    pub fn get(&self, browser_id: i32) -> &[String] {
+        self.browsers.get(&browser_id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Forgets a browser that closed.
+    pub fn remove(&mut self, browser_id: i32) {
+        self.browsers.remove(&browser_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_per_browser() {
+        let mut scripts = StartupScripts::default();
+        assert!(scripts.get(1).is_empty());
+
+        scripts.set(1, vec!["a".to_string(), "b".to_string()]);
+        scripts.set(2, vec!["c".to_string()]);
+        assert_eq!(scripts.get(1), ["a", "b"]);
+        assert_eq!(scripts.get(2), ["c"]);
+
+        // Updates replace the whole list
+        scripts.set(1, vec!["d".to_string()]);
+        assert_eq!(scripts.get(1), ["d"]);
+        scripts.set(1, Vec::new());
+        assert!(scripts.get(1).is_empty());
+
+        scripts.remove(2);
+        assert!(scripts.get(2).is_empty());
+    }
+}
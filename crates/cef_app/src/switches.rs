@@ -0,0 +1,507 @@
+//! Command-line switches used to forward browser-process configuration to
+//! the helper subprocesses.
+//!
+//! Subprocesses construct their own `OsrApp`, so anything they need to know
+//! before CEF initializes (such as custom scheme names) is passed through
+//! switches appended in `on_before_child_process_launch`.
+
+/// Comma-separated list of application-defined schemes.
+pub const CUSTOM_SCHEMES_SWITCH: &str = "godot-custom-schemes";
+
+/// Present when the Godot schemes are registered without `CSP_BYPASSING`.
+pub const ENFORCE_SCHEME_CSP_SWITCH: &str = "godot-enforce-scheme-csp";
+
+/// Maximum size in bytes of a binary IPC message sent from the page.
+pub const IPC_BINARY_LIMIT_SWITCH: &str = "godot-ipc-binary-limit";
+
+/// Default maximum size of a binary IPC message sent from the page (32 MB).
+pub const DEFAULT_IPC_BINARY_LIMIT: usize = 32 * 1024 * 1024;
+
+/// Present when pages may show notifications through the Godot shim of the
+/// `Notification` API.
+pub const WEB_NOTIFICATIONS_SWITCH: &str = "godot-web-notifications";
+
+/// Comma-separated ports of local game servers, exposed to pages as
+/// `window.GODOT_LOCAL_PORTS`.
+pub const LOCAL_PORTS_SWITCH: &str = "godot-local-ports";
+
+/// Chromium's UI language switch (BCP 47, e.g. `en-US`). Renderers also use it
+/// as their ICU default locale.
+pub const LANG_SWITCH: &str = "lang";
+
+/// Fontconfig configuration directory of the browser process.
+pub const FONTCONFIG_PATH_SWITCH: &str = "godot-fontconfig-path";
+
+/// Time zone (`TZ`) of the browser process.
+pub const TIME_ZONE_SWITCH: &str = "godot-time-zone";
+
+/// Comma-separated `NAME=VALUE` pairs set verbatim in the subprocess
+/// environment, with `%` and `,` percent-encoded in values.
+pub const FORWARDED_ENV_SWITCH: &str = "godot-forwarded-env";
+
+/// `--type` of a helper started by the browser process only to check that
+/// it runs; it exits with [`PREFLIGHT_EXIT_CODE`] before starting CEF.
+pub const PREFLIGHT_PROCESS_TYPE: &str = "preflight-check";
+
+/// Exit code of a helper started with [`PREFLIGHT_PROCESS_TYPE`]. Not a
+/// generic success code, so a different binary at the helper path fails.
+pub const PREFLIGHT_EXIT_CODE: u8 = 73;
+
+/// Environment of the browser process that subprocesses need even when the
+/// OS starts them with a stripped environment (e.g. games launched by Steam).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProcessEnvironment {
+    /// Locale in BCP 47 form, e.g. `en-US`.
+    pub locale: Option<String>,
+    /// Value for `FONTCONFIG_PATH`.
+    pub fontconfig_path: Option<String>,
+    /// Value for `TZ`.
+    pub time_zone: Option<String>,
+    /// Variables set verbatim, overriding the inherited values.
+    pub forwarded: Vec<(String, String)>,
+}
+
+impl ProcessEnvironment {
+    /// Returns the environment variables a subprocess should set.
+    ///
+    /// Locale, fontconfig and time zone only fill in variables for which
+    /// `is_set` returns false; forwarded variables are always set.
+    pub fn variables(&self, is_set: impl Fn(&str) -> bool) -> Vec<(String, String)> {
+        let mut variables = Vec::new();
+        let mut fill_in = |name: &str, value: Option<String>| {
+            if let Some(value) = value
+                && !is_set(name)
+            {
+                variables.push((name.to_string(), value));
+            }
+        };
+
+        fill_in("LANG", self.locale.as_deref().map(posix_locale));
+        fill_in("FONTCONFIG_PATH", self.fontconfig_path.clone());
+        fill_in("TZ", self.time_zone.clone());
+
+        variables.extend(self.forwarded.iter().cloned());
+        variables
+    }
+}
+
+/// Converts a BCP 47 locale (`en-US`) to a POSIX one (`en_US.UTF-8`).
+///
+/// A script subtag picks the region where it is implied (`zh-Hant` is
+/// `zh_TW`) or becomes a modifier (`sr-Latn-RS` is `sr_RS.UTF-8@latin`).
+/// Without a region, the language's main one is used (`de` is `de_DE`).
+/// Anything that is not a language falls back to `C.UTF-8`.
+pub fn posix_locale(locale: &str) -> String {
+    let mut subtags = locale.split(['-', '_']);
+    let language = subtags.next().unwrap_or_default().to_ascii_lowercase();
+    if !(2..=3).contains(&language.len()) || !language.bytes().all(|b| b.is_ascii_lowercase()) {
+        return "C.UTF-8".to_string();
+    }
+
+    let mut script = None;
+    let mut region = None;
+    for subtag in subtags {
+        if subtag.len() == 4 && script.is_none() && region.is_none() {
+            script = Some(subtag.to_ascii_lowercase());
+        } else if subtag.len() == 2 && subtag.bytes().all(|b| b.is_ascii_alphabetic()) {
+            region = Some(subtag.to_ascii_uppercase());
+            break;
+        } else {
+            // Numeric regions (`es-419`) and variants have no POSIX form
+            break;
+        }
+    }
+
+    let modifier = match script.as_deref() {
+        Some("latn") if language != "zh" => "@latin",
+        Some("cyrl") if language != "zh" => "@cyrillic",
+        _ => "",
+    };
+    let region = region.unwrap_or_else(|| match (language.as_str(), script.as_deref()) {
+        ("zh", Some("hant")) => "TW".to_string(),
+        _ => default_region(&language)
+            .map(str::to_string)
+            .unwrap_or_else(|| language.to_ascii_uppercase()),
+    });
+    format!("{language}_{region}.UTF-8{modifier}")
+}
+
+/// Main region of languages whose code is not also the code of that region.
+fn default_region(language: &str) -> Option<&'static str> {
+    Some(match language {
+        "ar" => "EG",
+        "ca" => "ES",
+        "cs" => "CZ",
+        "da" => "DK",
+        "el" => "GR",
+        "en" => "US",
+        "et" => "EE",
+        "fa" => "IR",
+        "he" => "IL",
+        "hi" => "IN",
+        "ja" => "JP",
+        "ko" => "KR",
+        "ms" => "MY",
+        "nb" => "NO",
+        "sl" => "SI",
+        "sr" => "RS",
+        "sv" => "SE",
+        "uk" => "UA",
+        "vi" => "VN",
+        "zh" => "CN",
+        _ => return None,
+    })
+}
+
+/// Configuration that the browser process forwards to its subprocesses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SubprocessConfig {
+    /// Application-defined URL schemes registered alongside `res` and `user`.
+    pub custom_schemes: Vec<String>,
+    /// Register the Godot schemes without `CSP_BYPASSING`.
+    pub enforce_scheme_csp: bool,
+    /// Maximum size in bytes of a binary IPC message sent from the page.
+    pub ipc_binary_limit: usize,
+    /// Forward the page's notifications to Godot.
+    pub web_notifications: bool,
+    /// Ports of local game servers treated as secure origins.
+    pub local_ports: Vec<u16>,
+    /// Environment to restore before CEF starts in the subprocess.
+    pub environment: ProcessEnvironment,
+}
+
+impl Default for SubprocessConfig {
+    fn default() -> Self {
+        Self {
+            custom_schemes: Vec::new(),
+            enforce_scheme_csp: false,
+            ipc_binary_limit: DEFAULT_IPC_BINARY_LIMIT,
+            web_notifications: false,
+            local_ports: Vec::new(),
+            environment: ProcessEnvironment::default(),
+        }
+    }
+}
+
+impl SubprocessConfig {
+    /// Returns the switches to append, as `(name, value)` pairs.
+    /// Switches without a value are returned with `None`.
+    pub fn to_switches(&self) -> Vec<(&'static str, Option<String>)> {
+        let mut switches = Vec::new();
+
+        if !self.custom_schemes.is_empty() {
+            switches.push((
+                CUSTOM_SCHEMES_SWITCH,
+                Some(encode_custom_schemes(&self.custom_schemes)),
+            ));
+        }
+        if self.enforce_scheme_csp {
+            switches.push((ENFORCE_SCHEME_CSP_SWITCH, None));
+        }
+        if self.ipc_binary_limit != DEFAULT_IPC_BINARY_LIMIT {
+            switches.push((
+                IPC_BINARY_LIMIT_SWITCH,
+                Some(self.ipc_binary_limit.to_string()),
+            ));
+        }
+        if self.web_notifications {
+            switches.push((WEB_NOTIFICATIONS_SWITCH, None));
+        }
+
+        if !self.local_ports.is_empty() {
+            switches.push((LOCAL_PORTS_SWITCH, Some(encode_ports(&self.local_ports))));
+        }
+
+        let environment = &self.environment;
+        let values = [
+            (LANG_SWITCH, &environment.locale),
+            (FONTCONFIG_PATH_SWITCH, &environment.fontconfig_path),
+            (TIME_ZONE_SWITCH, &environment.time_zone),
+        ];
+        for (name, value) in values {
+            if let Some(value) = value {
+                switches.push((name, Some(value.clone())));
+            }
+        }
+        if !environment.forwarded.is_empty() {
+            switches.push((
+                FORWARDED_ENV_SWITCH,
+                Some(encode_environment(&environment.forwarded)),
+            ));
+        }
+
+        switches
+    }
+
+    /// Rebuilds the configuration from a subprocess command line.
+    ///
+    /// `lookup` returns `None` for a missing switch and `Some(value)` for a
+    /// present one (an empty string for switches without a value).
+    pub fn from_switches(lookup: impl Fn(&str) -> Option<String>) -> Self {
+        let defaults = Self::default();
+
+        Self {
+            custom_schemes: lookup(CUSTOM_SCHEMES_SWITCH)
+                .map(|value| decode_custom_schemes(&value))
+                .unwrap_or(defaults.custom_schemes),
+            enforce_scheme_csp: lookup(ENFORCE_SCHEME_CSP_SWITCH).is_some(),
+            ipc_binary_limit: lookup(IPC_BINARY_LIMIT_SWITCH)
+                .and_then(|value| value.trim().parse::<usize>().ok())
+                .filter(|limit| *limit > 0)
+                .unwrap_or(defaults.ipc_binary_limit),
+            web_notifications: lookup(WEB_NOTIFICATIONS_SWITCH).is_some(),
+            local_ports: lookup(LOCAL_PORTS_SWITCH)
+                .map(|value| decode_ports(&value))
+                .unwrap_or(defaults.local_ports),
+            environment: ProcessEnvironment {
+                locale: lookup(LANG_SWITCH).filter(|value| !value.is_empty()),
+                fontconfig_path: lookup(FONTCONFIG_PATH_SWITCH).filter(|value| !value.is_empty()),
+                time_zone: lookup(TIME_ZONE_SWITCH).filter(|value| !value.is_empty()),
+                forwarded: lookup(FORWARDED_ENV_SWITCH)
+                    .map(|value| decode_environment(&value))
+                    .unwrap_or_default(),
+            },
+        }
+    }
+}
+
+/// Encodes a list of scheme names into a single switch value.
+pub fn encode_custom_schemes(schemes: &[String]) -> String {
+    schemes.join(",")
+}
+
+/// Decodes a switch value produced by [`encode_custom_schemes`].
+pub fn decode_custom_schemes(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// Encodes a list of ports into a single switch value.
+pub fn encode_ports(ports: &[u16]) -> String {
+    ports
+        .iter()
+        .map(u16::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Decodes a comma-separated list of ports, skipping invalid entries, `0`
+/// and duplicates.
+pub fn decode_ports(value: &str) -> Vec<u16> {
+    let mut ports = Vec::new();
+    for port in value
+        .split(',')
+        .filter_map(|s| s.trim().parse::<u16>().ok())
+    {
+        if port != 0 && !ports.contains(&port) {
+            ports.push(port);
+        }
+    }
+    ports
+}
+
+/// Value of Chromium's `unsafely-treat-insecure-origin-as-secure` switch
+/// for local game servers listening on `ports`.
+pub fn secure_localhost_origins(ports: &[u16]) -> String {
+    ports
+        .iter()
+        .flat_map(|port| {
+            [
+                format!("http://127.0.0.1:{port}"),
+                format!("ws://127.0.0.1:{port}"),
+            ]
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Encodes environment variables into a single switch value.
+///
+/// Variables whose name is empty or contains `=`, `,` or `%` cannot be
+/// encoded and are skipped.
+pub fn encode_environment(variables: &[(String, String)]) -> String {
+    variables
+        .iter()
+        .filter(|(name, _)| !name.is_empty() && !name.contains(['=', ',', '%']))
+        .map(|(name, value)| {
+            let value = value.replace('%', "%25").replace(',', "%2C");
+            format!("{name}={value}")
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Decodes a switch value produced by [`encode_environment`].
+pub fn decode_environment(value: &str) -> Vec<(String, String)> {
+    value
+        .split(',')
+        .filter_map(|entry| entry.split_once('='))
+        .filter(|(name, _)| !name.is_empty())
+        .map(|(name, value)| {
+            let value = value.replace("%2C", ",").replace("%25", "%");
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn round_trip(config: &SubprocessConfig) -> SubprocessConfig {
+        let switches: HashMap<&str, String> = config
+            .to_switches()
+            .into_iter()
+            .map(|(name, value)| (name, value.unwrap_or_default()))
+            .collect();
+        SubprocessConfig::from_switches(|name| switches.get(name).cloned())
+    }
+
+    #[test]
+    fn test_custom_schemes_round_trip() {
+        let schemes = vec!["app".to_string(), "game-data".to_string()];
+        let encoded = encode_custom_schemes(&schemes);
+        assert_eq!(encoded, "app,game-data");
+        assert_eq!(decode_custom_schemes(&encoded), schemes);
+    }
+
+    #[test]
+    fn test_decode_custom_schemes_ignores_empty_entries() {
+        assert_eq!(decode_custom_schemes(""), Vec::<String>::new());
+        assert_eq!(
+            decode_custom_schemes(" app, ,mods "),
+            vec!["app".to_string(), "mods".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_default_config_has_no_switches() {
+        let config = SubprocessConfig::default();
+        assert!(config.to_switches().is_empty());
+        assert_eq!(round_trip(&config), config);
+    }
+
+    #[test]
+    fn test_subprocess_config_round_trip() {
+        let config = SubprocessConfig {
+            custom_schemes: vec!["app".to_string()],
+            enforce_scheme_csp: true,
+            ipc_binary_limit: 1024,
+            web_notifications: true,
+            local_ports: vec![8080, 9000],
+            environment: ProcessEnvironment {
+                locale: Some("pt-BR".to_string()),
+                fontconfig_path: Some("/opt/game/fonts".to_string()),
+                time_zone: Some("Europe/Berlin".to_string()),
+                forwarded: vec![("STEAM_COMPAT".to_string(), "1".to_string())],
+            },
+        };
+        assert_eq!(round_trip(&config), config);
+    }
+
+    #[test]
+    fn test_environment_round_trip_escapes_separators() {
+        let variables = vec![
+            ("LIST".to_string(), "a,b=c".to_string()),
+            ("PERCENT".to_string(), "100%2C".to_string()),
+            ("EMPTY".to_string(), String::new()),
+        ];
+        assert_eq!(
+            decode_environment(&encode_environment(&variables)),
+            variables
+        );
+    }
+
+    #[test]
+    fn test_encode_environment_skips_invalid_names() {
+        let variables = vec![
+            ("A=B".to_string(), "1".to_string()),
+            (String::new(), "2".to_string()),
+            ("OK".to_string(), "3".to_string()),
+        ];
+        assert_eq!(encode_environment(&variables), "OK=3");
+    }
+
+    #[test]
+    fn test_environment_variables_only_fill_in_missing_values() {
+        let environment = ProcessEnvironment {
+            locale: Some("de-DE".to_string()),
+            fontconfig_path: Some("/etc/fonts".to_string()),
+            time_zone: Some("UTC".to_string()),
+            forwarded: vec![("TZ".to_string(), "Asia/Tokyo".to_string())],
+        };
+
+        assert_eq!(
+            environment.variables(|name| name == "FONTCONFIG_PATH"),
+            vec![
+                ("LANG".to_string(), "de_DE.UTF-8".to_string()),
+                ("TZ".to_string(), "UTC".to_string()),
+                ("TZ".to_string(), "Asia/Tokyo".to_string()),
+            ]
+        );
+        assert!(
+            ProcessEnvironment::default()
+                .variables(|_| false)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_posix_locale() {
+        assert_eq!(posix_locale("en-US"), "en_US.UTF-8");
+        assert_eq!(posix_locale("pt_BR"), "pt_BR.UTF-8");
+        // Language only
+        assert_eq!(posix_locale("de"), "de_DE.UTF-8");
+        assert_eq!(posix_locale("ja"), "ja_JP.UTF-8");
+        assert_eq!(posix_locale("en"), "en_US.UTF-8");
+        // Scripts
+        assert_eq!(posix_locale("zh-Hant-TW"), "zh_TW.UTF-8");
+        assert_eq!(posix_locale("zh-Hant-HK"), "zh_HK.UTF-8");
+        assert_eq!(posix_locale("zh-Hant"), "zh_TW.UTF-8");
+        assert_eq!(posix_locale("zh-Hans"), "zh_CN.UTF-8");
+        assert_eq!(posix_locale("sr-Latn-RS"), "sr_RS.UTF-8@latin");
+        // Numeric regions are dropped
+        assert_eq!(posix_locale("es-419"), "es_ES.UTF-8");
+        // Not a language
+        assert_eq!(posix_locale(""), "C.UTF-8");
+        assert_eq!(posix_locale("C"), "C.UTF-8");
+        assert_eq!(posix_locale("12-34"), "C.UTF-8");
+    }
+
+    #[test]
+    fn test_invalid_ipc_binary_limit_falls_back_to_default() {
+        let config = SubprocessConfig::from_switches(|name| {
+            (name == IPC_BINARY_LIMIT_SWITCH).then(|| "lots".to_string())
+        });
+        assert_eq!(config.ipc_binary_limit, DEFAULT_IPC_BINARY_LIMIT);
+
+        let config = SubprocessConfig::from_switches(|name| {
+            (name == IPC_BINARY_LIMIT_SWITCH).then(|| "0".to_string())
+        });
+        assert_eq!(config.ipc_binary_limit, DEFAULT_IPC_BINARY_LIMIT);
+    }
+
+    #[test]
+    fn test_decode_ports_skips_invalid_entries() {
+        assert_eq!(
+            decode_ports(" 8080, abc,0,65536,9000,8080,"),
+            vec![8080, 9000]
+        );
+        assert_eq!(decode_ports(""), Vec::<u16>::new());
+        assert_eq!(encode_ports(&[8080, 9000]), "8080,9000");
+    }
+
+    #[test]
+    fn test_secure_localhost_origins() {
+        assert_eq!(
+            secure_localhost_origins(&[8080, 9000]),
+            "http://127.0.0.1:8080,ws://127.0.0.1:8080,http://127.0.0.1:9000,ws://127.0.0.1:9000"
+        );
+        assert_eq!(secure_localhost_origins(&[]), "");
+    }
+}
@@ -109,3 +109,159 @@ pub enum CursorType {
     NotAllowed,
     Progress,
 }
+
+/// Kind of query sent to the render process with the `domQuery` message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DomQueryKind {
+    /// `outerHTML` of the first match, for `dump_dom_async`.
+    Dump = 0,
+    /// Number of matches, for `count_elements`.
+    Count = 1,
+}
+
+impl DomQueryKind {
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Dump),
+            1 => Some(Self::Count),
+            _ => None,
+        }
+    }
+}
+
+/// Kind of text field that has focus, sent with the `triggerIme` message so
+/// the IME proxy can configure itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextInputType {
+    #[default]
+    Text = 0,
+    Multiline = 1,
+    Password = 2,
+    Email = 3,
+    Number = 4,
+    Decimal = 5,
+    Phone = 6,
+    Url = 7,
+}
+
+impl TextInputType {
+    pub fn from_i32(value: i32) -> Option<Self> {
+        match value {
+            0 => Some(Self::Text),
+            1 => Some(Self::Multiline),
+            2 => Some(Self::Password),
+            3 => Some(Self::Email),
+            4 => Some(Self::Number),
+            5 => Some(Self::Decimal),
+            6 => Some(Self::Phone),
+            7 => Some(Self::Url),
+            _ => None,
+        }
+    }
+
+    /// Derives the type from the focused element's tag name and its `type`,
+    /// `inputmode` and `autocomplete` attributes.
+    ///
+    /// Password fields win over everything else, including text fields whose
+    /// `autocomplete` asks for a password (e.g. a "show password" toggle).
+    /// Otherwise `inputmode` refines the `type`, as it does for the page's
+    /// own virtual keyboard.
+    pub fn from_attributes(
+        tag_name: &str,
+        input_type: &str,
+        input_mode: &str,
+        autocomplete: &str,
+    ) -> Self {
+        let is_input = tag_name.eq_ignore_ascii_case("input");
+        let input_type = input_type.trim().to_ascii_lowercase();
+        let autocomplete = autocomplete.to_ascii_lowercase();
+
+        if is_input
+            && (input_type == "password"
+                || autocomplete
+                    .split_whitespace()
+                    .any(|token| token == "current-password" || token == "new-password"))
+        {
+            return Self::Password;
+        }
+
+        match input_mode.trim().to_ascii_lowercase().as_str() {
+            "numeric" => return Self::Number,
+            "decimal" => return Self::Decimal,
+            "tel" => return Self::Phone,
+            "email" => return Self::Email,
+            "url" => return Self::Url,
+            _ => {}
+        }
+
+        if !is_input {
+            return Self::Multiline;
+        }
+        match input_type.as_str() {
+            "email" => Self::Email,
+            "number" => Self::Number,
+            "tel" => Self::Phone,
+            "url" => Self::Url,
+            _ => Self::Text,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_input_type_from_input_types() {
+        let input = |input_type| TextInputType::from_attributes("INPUT", input_type, "", "");
+        assert_eq!(input("password"), TextInputType::Password);
+        assert_eq!(input("email"), TextInputType::Email);
+        assert_eq!(input("number"), TextInputType::Number);
+        assert_eq!(input("tel"), TextInputType::Phone);
+        assert_eq!(input("URL"), TextInputType::Url);
+        assert_eq!(input("search"), TextInputType::Text);
+        assert_eq!(input(""), TextInputType::Text);
+    }
+
+    #[test]
+    fn test_text_input_type_refinements() {
+        // inputmode refines the type
+        assert_eq!(
+            TextInputType::from_attributes("INPUT", "text", "decimal", ""),
+            TextInputType::Decimal
+        );
+        assert_eq!(
+            TextInputType::from_attributes("TEXTAREA", "", "", ""),
+            TextInputType::Multiline
+        );
+        assert_eq!(
+            TextInputType::from_attributes("DIV", "", "numeric", ""),
+            TextInputType::Number
+        );
+
+        // Passwords are never refined away
+        assert_eq!(
+            TextInputType::from_attributes("INPUT", "password", "numeric", ""),
+            TextInputType::Password
+        );
+        assert_eq!(
+            TextInputType::from_attributes("INPUT", "text", "", "username current-password"),
+            TextInputType::Password
+        );
+        assert_eq!(
+            TextInputType::from_attributes("TEXTAREA", "", "", "new-password"),
+            TextInputType::Multiline
+        );
+    }
+
+    #[test]
+    fn test_text_input_type_round_trip() {
+        for value in 0..8 {
+            assert_eq!(
+                TextInputType::from_i32(value).map(|t| t as i32),
+                Some(value)
+            );
+        }
+        assert_eq!(TextInputType::from_i32(8), None);
+    }
+}
@@ -6,6 +6,11 @@ use cef::{
     process_message_create, rc::Rc, v8_value_create_bool, wrap_v8_handler,
 };
 
+use crate::ipc_buffer::{PendingIpcMessage, PendingIpcMessages};
+use crate::render_process::{
+    frame_identifier, invoke_js_binary_callback, invoke_js_string_callback,
+};
+
 #[derive(Clone)]
 pub(crate) struct OsrIpcHandler {
     frame: Option<Arc<Mutex<Frame>>>,
@@ -82,11 +87,12 @@ wrap_v8_handler! {
 #[derive(Clone)]
 pub(crate) struct OsrIpcBinaryHandler {
     frame: Option<Arc<Mutex<Frame>>>,
+    max_size: usize,
 }
 
 impl OsrIpcBinaryHandler {
-    pub fn new(frame: Option<Arc<Mutex<Frame>>>) -> Self {
-        Self { frame }
+    pub fn new(frame: Option<Arc<Mutex<Frame>>>, max_size: usize) -> Self {
+        Self { frame, max_size }
     }
 }
 
@@ -96,6 +102,44 @@ impl OsrIpcBinaryHandlerBuilder {
     }
 }
 
+/// Returns `true` if a binary message of `len` bytes may be sent to Godot.
+fn is_valid_binary_message_len(len: usize, max_size: usize) -> bool {
+    len > 0 && len <= max_size
+}
+
+/// Copies the bytes of an `ArrayBuffer` or an `ArrayBuffer` view (typed array or
+/// `DataView`). Returns `None` for other values and for empty or oversized data.
+fn copy_array_buffer_bytes(value: &V8Value, max_size: usize) -> Option<Vec<u8>> {
+    let (buffer, offset, len) = if value.is_array_buffer() == 1 {
+        let len = value.array_buffer_byte_length();
+        (value.clone(), 0, len)
+    } else if value.is_object() == 1 {
+        let buffer = value.value_bykey(Some(&"buffer".into()))?;
+        if buffer.is_array_buffer() != 1 {
+            return None;
+        }
+        let offset = value.value_bykey(Some(&"byteOffset".into()))?.uint_value() as usize;
+        let len = value.value_bykey(Some(&"byteLength".into()))?.uint_value() as usize;
+        if offset.checked_add(len)? > buffer.array_buffer_byte_length() {
+            return None;
+        }
+        (buffer, offset, len)
+    } else {
+        return None;
+    };
+
+    if !is_valid_binary_message_len(len, max_size) {
+        return None;
+    }
+
+    let data_ptr = buffer.array_buffer_data();
+    if data_ptr.is_null() {
+        return None;
+    }
+
+    Some(unsafe { std::slice::from_raw_parts((data_ptr as *const u8).add(offset), len).to_vec() })
+}
+
 wrap_v8_handler! {
     pub(crate) struct OsrIpcBinaryHandlerBuilder {
         handler: OsrIpcBinaryHandler,
@@ -114,25 +158,12 @@ wrap_v8_handler! {
                 && let Some(arg) = arguments.first()
                 && let Some(arg) = arg
             {
-                if arg.is_array_buffer() != 1 {
+                // Zero-length and oversized buffers are rejected
+                let Some(data) = copy_array_buffer_bytes(arg, self.handler.max_size) else {
                     if let Some(retval) = retval {
                         *retval = v8_value_create_bool(false as _);
                     }
                     return 0;
-                }
-
-                let data_ptr = arg.array_buffer_data();
-                let data_len = arg.array_buffer_byte_length();
-
-                if data_ptr.is_null() || data_len == 0 {
-                    if let Some(retval) = retval {
-                        *retval = v8_value_create_bool(false as _);
-                    }
-                    return 0;
-                }
-
-                let data: Vec<u8> = unsafe {
-                    std::slice::from_raw_parts(data_ptr as *const u8, data_len).to_vec()
                 };
 
                 let Some(mut binary_value) = binary_value_create(Some(&data)) else {
@@ -174,6 +205,80 @@ wrap_v8_handler! {
     }
 }
 
+/// Delivers messages buffered before the page installed `onIpcMessage` or
+/// `onIpcBinaryMessage`. Called by `ipc_helper.js` when a handler is assigned.
+#[derive(Clone)]
+pub(crate) struct OsrIpcFlushHandler {
+    frame: Option<Arc<Mutex<Frame>>>,
+    pending: Arc<Mutex<PendingIpcMessages>>,
+}
+
+impl OsrIpcFlushHandler {
+    pub fn new(frame: Option<Arc<Mutex<Frame>>>, pending: Arc<Mutex<PendingIpcMessages>>) -> Self {
+        Self { frame, pending }
+    }
+}
+
+impl OsrIpcFlushHandlerBuilder {
+    pub(crate) fn build(handler: OsrIpcFlushHandler) -> V8Handler {
+        Self::new(handler)
+    }
+}
+
+wrap_v8_handler! {
+    pub(crate) struct OsrIpcFlushHandlerBuilder {
+        handler: OsrIpcFlushHandler,
+    }
+
+    impl V8Handler {
+        fn execute(
+            &self,
+            _name: Option<&CefStringUtf16>,
+            _object: Option<&mut V8Value>,
+            arguments: Option<&[Option<V8Value>]>,
+            _retval: Option<&mut Option<cef::V8Value>>,
+            _exception: Option<&mut CefStringUtf16>
+        ) -> i32 {
+            let binary = arguments
+                .and_then(|args| args.first())
+                .and_then(|arg| arg.as_ref())
+                .filter(|arg| arg.is_string() == 1)
+                .map(|arg| CefStringUtf16::from(&arg.string_value()).to_string() == "binary")
+                .unwrap_or(false);
+
+            // Clone the frame out of the mutex: the page callbacks may call
+            // `sendIpcMessage`, which locks the same frame.
+            let Some(mut frame) = self
+                .handler
+                .frame
+                .as_ref()
+                .and_then(|frame| frame.lock().ok().map(|frame| frame.clone()))
+            else {
+                return 0;
+            };
+
+            let messages = match self.handler.pending.lock() {
+                Ok(mut pending) => pending.take(&frame_identifier(&frame), binary),
+                Err(_) => return 0,
+            };
+
+            for message in messages {
+                match message {
+                    PendingIpcMessage::Text(text) => {
+                        let text: CefStringUtf16 = text.as_str().into();
+                        invoke_js_string_callback(&mut frame, "onIpcMessage", &text);
+                    }
+                    PendingIpcMessage::Binary(data) => {
+                        invoke_js_binary_callback(&mut frame, "onIpcBinaryMessage", &data);
+                    }
+                }
+            }
+
+            1
+        }
+    }
+}
+
 #[derive(Clone)]
 pub(crate) struct OsrImeCaretHandler {
     frame: Option<Arc<Mutex<Frame>>>,
@@ -254,3 +359,274 @@ wrap_v8_handler! {
         }
     }
 }
+
+#[derive(Clone)]
+pub(crate) struct OsrDisplayMediaHandler {
+    frame: Option<Arc<Mutex<Frame>>>,
+    /// Route of the process message carrying the page's request id.
+    route: &'static str,
+}
+
+impl OsrDisplayMediaHandler {
+    pub fn new(frame: Option<Arc<Mutex<Frame>>>, route: &'static str) -> Self {
+        Self { frame, route }
+    }
+}
+
+impl OsrDisplayMediaHandlerBuilder {
+    pub(crate) fn build(handler: OsrDisplayMediaHandler) -> V8Handler {
+        Self::new(handler)
+    }
+}
+
+wrap_v8_handler! {
+    pub(crate) struct OsrDisplayMediaHandlerBuilder {
+        handler: OsrDisplayMediaHandler,
+    }
+
+    impl V8Handler {
+        fn execute(
+            &self,
+            _name: Option<&CefStringUtf16>,
+            _object: Option<&mut V8Value>,
+            arguments: Option<&[Option<V8Value>]>,
+            retval: Option<&mut Option<cef::V8Value>>,
+            _exception: Option<&mut CefStringUtf16>
+        ) -> i32 {
+            let mut sent = false;
+            if let Some(arguments) = arguments
+                && let Some(Some(id_arg)) = arguments.first()
+                && id_arg.is_int() != 0
+                && let Some(frame) = self.handler.frame.as_ref()
+                && let Ok(frame) = frame.lock()
+            {
+                let route = CefStringUtf16::from(self.handler.route);
+                if let Some(mut process_message) = process_message_create(Some(&route)) {
+                    if let Some(argument_list) = process_message.argument_list() {
+                        argument_list.set_int(0, id_arg.int_value());
+                    }
+                    frame.send_process_message(ProcessId::BROWSER, Some(&mut process_message));
+                    sent = true;
+                }
+            }
+
+            if let Some(retval) = retval {
+                *retval = v8_value_create_bool(sent as _);
+            }
+
+            sent as _
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct OsrNotificationHandler {
+    frame: Option<Arc<Mutex<Frame>>>,
+    /// Route of the process message carrying the string arguments.
+    route: &'static str,
+}
+
+impl OsrNotificationHandler {
+    pub fn new(frame: Option<Arc<Mutex<Frame>>>, route: &'static str) -> Self {
+        Self { frame, route }
+    }
+}
+
+impl OsrNotificationHandlerBuilder {
+    pub(crate) fn build(handler: OsrNotificationHandler) -> V8Handler {
+        Self::new(handler)
+    }
+}
+
+wrap_v8_handler! {
+    pub(crate) struct OsrNotificationHandlerBuilder {
+        handler: OsrNotificationHandler,
+    }
+
+    impl V8Handler {
+        fn execute(
+            &self,
+            _name: Option<&CefStringUtf16>,
+            _object: Option<&mut V8Value>,
+            arguments: Option<&[Option<V8Value>]>,
+            retval: Option<&mut Option<cef::V8Value>>,
+            _exception: Option<&mut CefStringUtf16>
+        ) -> i32 {
+            let mut sent = false;
+            // The helper passes strings only: title, body, icon URL and tag
+            // when shown, the tag when closed
+            let strings: Option<Vec<CefStringUtf16>> = arguments.and_then(|arguments| {
+                arguments
+                    .iter()
+                    .map(|arg| {
+                        arg.as_ref()
+                            .filter(|arg| arg.is_string() != 0)
+                            .map(|arg| CefStringUtf16::from(&arg.string_value()))
+                    })
+                    .collect()
+            });
+            if let Some(strings) = strings
+                && !strings.is_empty()
+                && let Some(frame) = self.handler.frame.as_ref()
+                && let Ok(frame) = frame.lock()
+            {
+                let route = CefStringUtf16::from(self.handler.route);
+                if let Some(mut process_message) = process_message_create(Some(&route)) {
+                    if let Some(argument_list) = process_message.argument_list() {
+                        for (index, value) in strings.iter().enumerate() {
+                            argument_list.set_string(index, Some(value));
+                        }
+                    }
+                    frame.send_process_message(ProcessId::BROWSER, Some(&mut process_message));
+                    sent = true;
+                }
+            }
+
+            if let Some(retval) = retval {
+                *retval = v8_value_create_bool(sent as _);
+            }
+
+            sent as _
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct OsrScrollPositionHandler {
+    frame: Option<Arc<Mutex<Frame>>>,
+}
+
+impl OsrScrollPositionHandler {
+    pub fn new(frame: Option<Arc<Mutex<Frame>>>) -> Self {
+        Self { frame }
+    }
+}
+
+impl OsrScrollPositionHandlerBuilder {
+    pub(crate) fn build(handler: OsrScrollPositionHandler) -> V8Handler {
+        Self::new(handler)
+    }
+}
+
+wrap_v8_handler! {
+    pub(crate) struct OsrScrollPositionHandlerBuilder {
+        handler: OsrScrollPositionHandler,
+    }
+
+    impl V8Handler {
+        fn execute(
+            &self,
+            _name: Option<&CefStringUtf16>,
+            _object: Option<&mut V8Value>,
+            arguments: Option<&[Option<V8Value>]>,
+            retval: Option<&mut Option<cef::V8Value>>,
+            _exception: Option<&mut CefStringUtf16>
+        ) -> i32 {
+            let mut sent = false;
+            if let Some(arguments) = arguments
+                && let Some(Some(x_arg)) = arguments.first()
+                && let Some(Some(y_arg)) = arguments.get(1)
+                && x_arg.is_double() != 0
+                && y_arg.is_double() != 0
+                && let Some(frame) = self.handler.frame.as_ref()
+                && let Ok(frame) = frame.lock()
+            {
+                let route = CefStringUtf16::from("scrollPosition");
+                if let Some(mut process_message) = process_message_create(Some(&route)) {
+                    // The scrollable range is optional and defaults to 0
+                    let number = |index: usize| {
+                        arguments
+                            .get(index)
+                            .and_then(|arg| arg.as_ref())
+                            .filter(|arg| arg.is_double() != 0)
+                            .map_or(0.0, |arg| arg.double_value())
+                    };
+                    if let Some(argument_list) = process_message.argument_list() {
+                        argument_list.set_double(0, x_arg.double_value());
+                        argument_list.set_double(1, y_arg.double_value());
+                        argument_list.set_double(2, number(2));
+                        argument_list.set_double(3, number(3));
+                    }
+                    frame.send_process_message(ProcessId::BROWSER, Some(&mut process_message));
+                    sent = true;
+                }
+            }
+
+            if let Some(retval) = retval {
+                *retval = v8_value_create_bool(sent as _);
+            }
+
+            sent as _
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_message_len_limits() {
+        assert!(!is_valid_binary_message_len(0, 1024));
+        assert!(is_valid_binary_message_len(1, 1024));
+        assert!(is_valid_binary_message_len(1024, 1024));
+        assert!(!is_valid_binary_message_len(1025, 1024));
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct OsrFirstPaintHandler {
+    frame: Option<Arc<Mutex<Frame>>>,
+}
+
+impl OsrFirstPaintHandler {
+    pub fn new(frame: Option<Arc<Mutex<Frame>>>) -> Self {
+        Self { frame }
+    }
+}
+
+impl OsrFirstPaintHandlerBuilder {
+    pub(crate) fn build(handler: OsrFirstPaintHandler) -> V8Handler {
+        Self::new(handler)
+    }
+}
+
+wrap_v8_handler! {
+    pub(crate) struct OsrFirstPaintHandlerBuilder {
+        handler: OsrFirstPaintHandler,
+    }
+
+    impl V8Handler {
+        fn execute(
+            &self,
+            _name: Option<&CefStringUtf16>,
+            _object: Option<&mut V8Value>,
+            arguments: Option<&[Option<V8Value>]>,
+            retval: Option<&mut Option<cef::V8Value>>,
+            _exception: Option<&mut CefStringUtf16>
+        ) -> i32 {
+            let mut sent = false;
+            if let Some(arguments) = arguments
+                && let Some(Some(time_arg)) = arguments.first()
+                && time_arg.is_double() != 0
+                && let Some(frame) = self.handler.frame.as_ref()
+                && let Ok(frame) = frame.lock()
+            {
+                let route = CefStringUtf16::from("firstContentfulPaint");
+                if let Some(mut process_message) = process_message_create(Some(&route)) {
+                    if let Some(argument_list) = process_message.argument_list() {
+                        argument_list.set_double(0, time_arg.double_value());
+                    }
+                    frame.send_process_message(ProcessId::BROWSER, Some(&mut process_message));
+                    sent = true;
+                }
+            }
+
+            if let Some(retval) = retval {
+                *retval = v8_value_create_bool(sent as _);
+            }
+
+            sent as _
+        }
+    }
+}
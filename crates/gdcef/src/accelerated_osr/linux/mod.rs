@@ -5,7 +5,7 @@
 
 mod vulkan;
 
-use super::RenderBackend;
+use super::{CopyOutcome, RenderBackend};
 use cef::AcceleratedPaintInfo;
 use godot::global::{godot_print, godot_warn};
 use godot::prelude::*;
@@ -22,6 +22,15 @@ impl GodotTextureImporter {
     pub fn new() -> Option<Self> {
         let render_backend = RenderBackend::detect();
 
+        if render_backend == RenderBackend::Vulkan
+            && !crate::vulkan_hook::is_external_memory_enabled()
+        {
+            godot_warn!(
+                "[AcceleratedOSR/Linux] Godot's Vulkan device was created without the DMA-BUF \
+                 external memory extensions"
+            );
+        }
+
         if !render_backend.supports_accelerated_osr() {
             godot_warn!(
                 "[AcceleratedOSR/Linux] Render backend {:?} does not support accelerated OSR",
@@ -50,18 +59,28 @@ impl GodotTextureImporter {
         self.vulkan_importer.queue_copy(info)
     }
 
-    pub fn process_pending_copy(&mut self, dst_rd_rid: Rid) -> Result<(), String> {
-        self.vulkan_importer.process_pending_copy(dst_rd_rid)
+    pub fn process_pending_copy(
+        &mut self,
+        dst_rd_rid: Rid,
+        dst_size: (u32, u32),
+    ) -> Result<CopyOutcome, String> {
+        self.vulkan_importer
+            .process_pending_copy(dst_rd_rid, dst_size)
     }
 
     pub fn wait_for_copy(&mut self) -> Result<(), String> {
         self.vulkan_importer.wait_for_copy()
     }
+
+    /// Device loss is only detected on D3D12.
+    pub fn is_device_lost(&mut self) -> bool {
+        false
+    }
 }
 
 pub fn is_supported() -> bool {
     let render_backend = RenderBackend::detect();
-    render_backend == RenderBackend::Vulkan
+    render_backend == RenderBackend::Vulkan && render_backend.supports_accelerated_osr()
 }
 
 unsafe impl Send for GodotTextureImporter {}
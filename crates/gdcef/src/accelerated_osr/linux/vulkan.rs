@@ -3,6 +3,7 @@
 //! This module imports DMA-BUF file descriptors from CEF into Vulkan images
 //! and copies them to Godot's RenderingDevice textures.
 
+use crate::accelerated_osr::{CopyOutcome, frame_fits_destination};
 use ash::vk;
 use cef::ColorType;
 use godot::classes::RenderingServer;
@@ -457,16 +458,25 @@ impl VulkanTextureImporter {
         Ok(())
     }
 
-    pub fn process_pending_copy(&mut self, dst_rd_rid: Rid) -> Result<(), String> {
+    pub fn process_pending_copy(
+        &mut self,
+        dst_rd_rid: Rid,
+        dst_size: (u32, u32),
+    ) -> Result<CopyOutcome, String> {
         let mut pending = match self.pending_copy.take() {
             Some(p) => p,
-            None => return Ok(()), // Nothing to do
+            None => return Ok(CopyOutcome::Copied), // Nothing to do
         };
 
         if !dst_rd_rid.is_valid() {
             return Err("Destination RID is invalid".into());
         }
 
+        // A frame painted before a resize; dropping it closes its fds
+        if !frame_fits_destination((pending.width, pending.height), dst_size) {
+            return Ok(CopyOutcome::Stale);
+        }
+
         // Wait for any previous in-flight copy to complete before reusing resources
         if self.copy_in_flight {
             self.wait_for_copy()?;
@@ -511,7 +521,7 @@ impl VulkanTextureImporter {
         self.submit_copy_async(src_image, dst_image, pending.width, pending.height)?;
         self.copy_in_flight = true;
 
-        Ok(())
+        Ok(CopyOutcome::Copied)
     }
 
     pub fn wait_for_copy(&mut self) -> Result<(), String> {
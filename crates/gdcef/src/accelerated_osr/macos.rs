@@ -1,4 +1,4 @@
-use super::RenderBackend;
+use super::{CopyOutcome, RenderBackend, frame_fits_destination};
 use cef::AcceleratedPaintInfo;
 use godot::classes::RenderingServer;
 use godot::classes::rendering_device::DriverResource;
@@ -59,6 +59,17 @@ unsafe extern "C" {
     fn IOSurfaceGetHeight(buffer: *mut c_void) -> usize;
 }
 
+/// Returns the size the IOSurface was allocated with, which can lag behind
+/// the coded size CEF reports while the view is being resized.
+fn io_surface_size(io_surface: *mut c_void) -> (u32, u32) {
+    unsafe {
+        (
+            IOSurfaceGetWidth(io_surface) as u32,
+            IOSurfaceGetHeight(io_surface) as u32,
+        )
+    }
+}
+
 pub struct NativeTextureImporter {
     device: Retained<AnyObject>,
     command_queue: Retained<AnyObject>,
@@ -163,20 +174,12 @@ impl NativeTextureImporter {
             return Err(format!("Invalid dimensions: {}x{}", width, height));
         }
 
-        let (ios_width, ios_height) = unsafe {
-            (
-                IOSurfaceGetWidth(io_surface),
-                IOSurfaceGetHeight(io_surface),
-            )
-        };
-        if ios_width != width as usize || ios_height != height as usize {
-            godot_warn!(
-                "[AcceleratedOSR/macOS] Dimension mismatch: IOSurface {}x{}, expected {}x{}",
-                ios_width,
-                ios_height,
-                width,
-                height
-            );
+        let (ios_width, ios_height) = io_surface_size(io_surface);
+        if ios_width != width || ios_height != height {
+            return Err(format!(
+                "Dimension mismatch: IOSurface {}x{}, expected {}x{}",
+                ios_width, ios_height, width, height
+            ));
         }
 
         // Using sRGB formats to ensure correct gamma handling for web content
@@ -264,16 +267,29 @@ impl GodotTextureImporter {
         Ok(())
     }
 
-    pub fn process_pending_copy(&mut self, dst_rd_rid: Rid) -> Result<(), String> {
+    pub fn process_pending_copy(
+        &mut self,
+        dst_rd_rid: Rid,
+        dst_size: (u32, u32),
+    ) -> Result<CopyOutcome, String> {
         let pending = match self.pending_copy.take() {
             Some(p) => p,
-            None => return Ok(()), // Nothing to do
+            None => return Ok(CopyOutcome::Copied), // Nothing to do
         };
 
         if !dst_rd_rid.is_valid() {
             return Err("Destination RID is invalid".into());
         }
 
+        // A frame painted before a resize, or an IOSurface still allocated at
+        // the old size: blitting it would leave the texture stretched until
+        // the next paint, so it is released (dropping pending) and skipped
+        if !frame_fits_destination((pending.width, pending.height), dst_size)
+            || !frame_fits_destination(io_surface_size(pending.io_surface), dst_size)
+        {
+            return Ok(CopyOutcome::Stale);
+        }
+
         // Create Metal texture from IOSurface (source)
         let src_metal_texture = self.metal_importer.import_io_surface(
             pending.io_surface,
@@ -313,12 +329,17 @@ impl GodotTextureImporter {
         )?;
 
         // pending is dropped here, which releases the IOSurface
-        Ok(())
+        Ok(CopyOutcome::Copied)
     }
 
     pub fn wait_for_copy(&mut self) -> Result<(), String> {
         Ok(())
     }
+
+    /// Device loss is only detected on D3D12.
+    pub fn is_device_lost(&mut self) -> bool {
+        false
+    }
 }
 
 impl Drop for GodotTextureImporter {
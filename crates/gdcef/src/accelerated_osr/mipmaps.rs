@@ -0,0 +1,210 @@
+//! Mipmap generation for the accelerated render path.
+//!
+//! The importers copy each frame into mip level 0 with the native API. The
+//! smaller levels are then drawn with Godot's RenderingDevice, each from the
+//! one above it with a linear sampler, so the same pass runs on Vulkan, D3D12
+//! and Metal. Drawing through the sRGB view averages in linear space.
+//!
+//! Framebuffers and uniform sets are created once per destination texture
+//! and freed by the RenderingDevice together with it.
+
+use godot::classes::rendering_device::{
+    RenderPrimitive, SamplerFilter, SamplerRepeatMode, ShaderStage, UniformType,
+};
+use godot::classes::{
+    RdPipelineColorBlendState, RdPipelineColorBlendStateAttachment, RdPipelineDepthStencilState,
+    RdPipelineMultisampleState, RdPipelineRasterizationState, RdSamplerState, RdShaderSource,
+    RdTextureView, RdUniform, RenderingDevice, RenderingServer,
+};
+use godot::prelude::*;
+
+const VERTEX_SHADER: &str = r#"#version 450
+layout(location = 0) out vec2 uv;
+
+void main() {
+    // One triangle covering the target
+    uv = vec2((gl_VertexIndex << 1) & 2, gl_VertexIndex & 2);
+    gl_Position = vec4(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+"#;
+
+const FRAGMENT_SHADER: &str = r#"#version 450
+layout(location = 0) in vec2 uv;
+layout(location = 0) out vec4 color;
+layout(set = 0, binding = 0) uniform sampler2D source;
+
+void main() {
+    // Halfway between four texels of the level above, averaging them
+    color = textureLod(source, uv, 0.0);
+}
+"#;
+
+/// Vertex format of pipelines without vertex buffers.
+const NO_VERTEX_FORMAT: i64 = -1;
+
+/// A level drawn from the one above it.
+struct MipLevel {
+    framebuffer: Rid,
+    /// Samples the level above.
+    uniform_set: Rid,
+}
+
+/// Draws the mip levels of the destination texture after each copy.
+#[derive(Default)]
+pub struct MipmapGenerator {
+    shader: Option<Rid>,
+    sampler: Option<Rid>,
+    /// Created with the first framebuffer, whose format it needs.
+    pipeline: Option<Rid>,
+    /// The texture the levels belong to.
+    texture: Option<Rid>,
+    levels: Vec<MipLevel>,
+}
+
+fn rendering_device() -> Result<Gd<RenderingDevice>, String> {
+    RenderingServer::singleton()
+        .get_rendering_device()
+        .ok_or_else(|| "Failed to get RenderingDevice".to_string())
+}
+
+impl MipmapGenerator {
+    /// Draws levels `1..mipmaps` of `texture` from its level 0.
+    pub fn generate(&mut self, texture: Rid, mipmaps: u32) -> Result<(), String> {
+        let mut rd = rendering_device()?;
+        if self.texture != Some(texture) {
+            self.prepare_levels(&mut rd, texture, mipmaps)?;
+        }
+        let Some(pipeline) = self.pipeline else {
+            return Ok(());
+        };
+
+        // In order, each level is drawn from the one drawn before
+        for level in &self.levels {
+            let draw_list = rd.draw_list_begin(level.framebuffer);
+            rd.draw_list_bind_render_pipeline(draw_list, pipeline);
+            rd.draw_list_bind_uniform_set(draw_list, level.uniform_set, 0);
+            rd.draw_list_draw_ex(draw_list, false, 1)
+                .procedural_vertex_count(3)
+                .done();
+            rd.draw_list_end();
+        }
+        Ok(())
+    }
+
+    /// Creates a view, a framebuffer and a uniform set per level of
+    /// `texture`, and the shader, sampler and pipeline on first use.
+    fn prepare_levels(
+        &mut self,
+        rd: &mut Gd<RenderingDevice>,
+        texture: Rid,
+        mipmaps: u32,
+    ) -> Result<(), String> {
+        // The views of the previous texture are freed with it
+        self.texture = Some(texture);
+        self.levels.clear();
+        let shader = match self.shader {
+            Some(shader) => shader,
+            None => *self.shader.insert(create_shader(rd)?),
+        };
+        if self.sampler.is_none() {
+            let mut sampler_state = RdSamplerState::new_gd();
+            sampler_state.set_min_filter(SamplerFilter::LINEAR);
+            sampler_state.set_mag_filter(SamplerFilter::LINEAR);
+            sampler_state.set_repeat_u(SamplerRepeatMode::CLAMP_TO_EDGE);
+            sampler_state.set_repeat_v(SamplerRepeatMode::CLAMP_TO_EDGE);
+            let sampler = rd.sampler_create(&sampler_state);
+            if !sampler.is_valid() {
+                return Err("Failed to create mipmap sampler".into());
+            }
+            self.sampler = Some(sampler);
+        }
+
+        let sampler = self.sampler.unwrap_or(Rid::Invalid);
+
+        let view = RdTextureView::new_gd();
+        let mut source = rd.texture_create_shared_from_slice(&view, texture, 0, 0);
+        for mipmap in 1..mipmaps {
+            let target = rd.texture_create_shared_from_slice(&view, texture, 0, mipmap);
+            let framebuffer = rd.framebuffer_create(&array![target]);
+            let mut uniform = RdUniform::new_gd();
+            uniform.set_uniform_type(UniformType::SAMPLER_WITH_TEXTURE);
+            uniform.set_binding(0);
+            uniform.add_id(sampler);
+            uniform.add_id(source);
+            let uniform_set = rd.uniform_set_create(&array![&uniform], shader, 0);
+            if !framebuffer.is_valid() || !uniform_set.is_valid() {
+                self.levels.clear();
+                return Err(format!("Failed to prepare mip level {mipmap}"));
+            }
+            if self.pipeline.is_none() {
+                let format = rd.framebuffer_get_format(framebuffer);
+                self.pipeline = Some(create_pipeline(rd, shader, format)?);
+            }
+            self.levels.push(MipLevel {
+                framebuffer,
+                uniform_set,
+            });
+            source = target;
+        }
+        Ok(())
+    }
+
+    /// Frees the shader, its pipeline and the sampler. Must run on the main
+    /// thread, so it is not done on drop.
+    pub fn free(&mut self) {
+        let Ok(mut rd) = rendering_device() else {
+            return;
+        };
+        // The pipeline depends on the shader and is freed with it
+        for rid in [self.shader.take(), self.sampler.take()]
+            .into_iter()
+            .flatten()
+        {
+            rd.free_rid(rid);
+        }
+        *self = Self::default();
+    }
+}
+
+fn create_shader(rd: &mut Gd<RenderingDevice>) -> Result<Rid, String> {
+    let mut source = RdShaderSource::new_gd();
+    source.set_stage_source(ShaderStage::VERTEX, VERTEX_SHADER);
+    source.set_stage_source(ShaderStage::FRAGMENT, FRAGMENT_SHADER);
+    let spirv = rd
+        .shader_compile_spirv_from_source(&source)
+        .ok_or("Failed to compile the mipmap shader")?;
+    for stage in [ShaderStage::VERTEX, ShaderStage::FRAGMENT] {
+        let error = spirv.get_stage_compile_error(stage);
+        if !error.is_empty() {
+            return Err(format!("Failed to compile the mipmap shader: {error}"));
+        }
+    }
+    let shader = rd.shader_create_from_spirv(&spirv);
+    if !shader.is_valid() {
+        return Err("Failed to create the mipmap shader".into());
+    }
+    Ok(shader)
+}
+
+fn create_pipeline(
+    rd: &mut Gd<RenderingDevice>,
+    shader: Rid,
+    framebuffer_format: i64,
+) -> Result<Rid, String> {
+    let mut blend = RdPipelineColorBlendState::new_gd();
+    blend.set_attachments(&array![&RdPipelineColorBlendStateAttachment::new_gd()]);
+    let pipeline = rd.render_pipeline_create(
+        shader,
+        framebuffer_format,
+        NO_VERTEX_FORMAT,
+        RenderPrimitive::TRIANGLES,
+        &RdPipelineRasterizationState::new_gd(),
+        &RdPipelineMultisampleState::new_gd(),
+        &RdPipelineDepthStencilState::new_gd(),
+        &blend,
+    );
+    if !pipeline.is_valid() {
+        return Err("Failed to create the mipmap pipeline".into());
+    }
+    Ok(pipeline)
+}
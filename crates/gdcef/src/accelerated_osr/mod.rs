@@ -2,6 +2,7 @@
 mod linux;
 #[cfg(target_os = "macos")]
 mod macos;
+mod mipmaps;
 #[cfg(target_os = "windows")]
 mod windows;
 
@@ -9,7 +10,14 @@ use cef::{AcceleratedPaintInfo, PaintElementType};
 use godot::classes::RenderingServer;
 use godot::global::godot_print;
 use godot::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::frame_pacing::FramePacerHandle;
+use crate::render_stats::RenderStatsHandle;
+
+pub use mipmaps::MipmapGenerator;
 
 #[cfg(target_os = "linux")]
 pub use linux::GodotTextureImporter;
@@ -66,20 +74,62 @@ impl RenderBackend {
             RenderBackend::Metal => true,
             #[cfg(target_os = "windows")]
             RenderBackend::D3D12 => true,
-            #[cfg(all(target_os = "windows", target_arch = "x86_64"))]
-            RenderBackend::Vulkan => true,
-            #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
-            RenderBackend::Vulkan => true,
+            // Requires the external memory extensions added by the Vulkan layer
+            #[cfg(any(target_os = "windows", target_os = "linux"))]
+            RenderBackend::Vulkan => crate::vulkan_hook::is_external_memory_enabled(),
             _ => false,
         }
     }
 }
 
+/// Result of processing a pending copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyOutcome {
+    /// The frame was copied, or no frame was pending.
+    Copied,
+    /// The frame was painted at a different size than the destination and
+    /// was released without being copied.
+    Stale,
+}
+
+/// Returns true if a shared texture of `frame_size` can be copied into a
+/// destination of `dst_size`.
+///
+/// CEF keeps delivering frames at the old size for a moment after a resize,
+/// while the destination may already have been recreated at the new size.
+/// Copying such a frame would show it cropped or stretched, so importers
+/// skip it and wait for the next paint at the right size.
+pub fn frame_fits_destination(frame_size: (u32, u32), dst_size: (u32, u32)) -> bool {
+    frame_size == dst_size && frame_size.0 > 0 && frame_size.1 > 0
+}
+
+/// Render states created and not yet torn down. CEF must not shut down
+/// while one is live, its importer still holding GPU resources.
+static LIVE_RENDER_STATES: AtomicUsize = AtomicUsize::new(0);
+
+/// Returns the number of render states not yet torn down.
+pub fn live_render_states() -> usize {
+    LIVE_RENDER_STATES.load(Ordering::Acquire)
+}
+
+const TORN_DOWN: &str = "The render state was torn down";
+
+/// Shared by the render handler and the `CefTexture`. CEF keeps the handler,
+/// and with it the state, until it releases the client, which can be after
+/// the RenderingDevice or CEF itself is gone. The GPU resources therefore
+/// aren't freed on drop but by [`AcceleratedRenderState::teardown`], which
+/// the `CefTexture` calls before closing the browser.
 pub struct AcceleratedRenderState {
-    pub importer: GodotTextureImporter,
+    /// `None` once torn down.
+    pub importer: Option<GodotTextureImporter>,
     pub dst_rd_rid: Rid,
     pub dst_width: u32,
     pub dst_height: u32,
+    /// Mip levels of `dst_rd_rid`, 1 without mipmaps.
+    pub dst_mipmaps: u32,
+    /// Set with `generate_mipmaps`; textures created while it is set get
+    /// mip levels, which it draws after each copy.
+    pub mipmap_generator: Option<MipmapGenerator>,
     pub needs_resize: Option<(u32, u32)>,
     pub popup_rd_rid: Option<Rid>,
     pub popup_width: u32,
@@ -88,15 +138,30 @@ pub struct AcceleratedRenderState {
     pub popup_has_content: bool,
     pub needs_popup_texture: Option<(u32, u32)>,
     pub has_pending_copy: bool,
+    /// Size of the frame behind `has_pending_copy`.
+    pub pending_copy_size: (u32, u32),
 }
 
 impl AcceleratedRenderState {
-    pub fn new(importer: GodotTextureImporter, dst_rd_rid: Rid, width: u32, height: u32) -> Self {
+    pub fn new(
+        importer: GodotTextureImporter,
+        dst_rd_rid: Rid,
+        width: u32,
+        height: u32,
+        mipmap_generator: Option<MipmapGenerator>,
+    ) -> Self {
+        LIVE_RENDER_STATES.fetch_add(1, Ordering::AcqRel);
         Self {
-            importer,
+            importer: Some(importer),
             dst_rd_rid,
             dst_width: width,
             dst_height: height,
+            dst_mipmaps: if mipmap_generator.is_some() {
+                crate::render::mipmap_count(width, height)
+            } else {
+                1
+            },
+            mipmap_generator,
             needs_resize: None,
             popup_rd_rid: None,
             popup_width: 0,
@@ -105,37 +170,167 @@ impl AcceleratedRenderState {
             popup_has_content: false,
             needs_popup_texture: None,
             has_pending_copy: false,
+            pending_copy_size: (0, 0),
         }
     }
 
-    pub fn process_pending_copy(&mut self) -> Result<(), String> {
+    pub fn process_pending_copy(&mut self) -> Result<CopyOutcome, String> {
         if !self.has_pending_copy {
-            return Ok(());
+            return Ok(CopyOutcome::Copied);
         }
 
-        self.importer.process_pending_copy(self.dst_rd_rid)?;
+        let (dst_rd_rid, dst_size) = (self.dst_rd_rid, (self.dst_width, self.dst_height));
+        let outcome = self
+            .importer_mut()?
+            .process_pending_copy(dst_rd_rid, dst_size)?;
+        self.has_pending_copy = false;
+        if outcome == CopyOutcome::Copied
+            && self.dst_mipmaps > 1
+            && let Some(generator) = &mut self.mipmap_generator
+        {
+            // The levels are drawn from level 0, which the importer writes
+            // outside of Godot's command buffers
+            self.importer
+                .as_mut()
+                .ok_or_else(|| TORN_DOWN.to_string())?
+                .wait_for_copy()?;
+            generator.generate(self.dst_rd_rid, self.dst_mipmaps)?;
+        }
+        Ok(outcome)
+    }
+
+    /// Replaces the destination texture with one created for the current
+    /// `mipmap_generator`. Returns the replaced texture.
+    pub fn replace_destination(&mut self, rid: Rid, width: u32, height: u32) -> Rid {
+        self.dst_width = width;
+        self.dst_height = height;
+        self.dst_mipmaps = if self.mipmap_generator.is_some() {
+            crate::render::mipmap_count(width, height)
+        } else {
+            1
+        };
+        std::mem::replace(&mut self.dst_rd_rid, rid)
+    }
+
+    pub fn importer_mut(&mut self) -> Result<&mut GodotTextureImporter, String> {
+        self.importer.as_mut().ok_or_else(|| TORN_DOWN.to_string())
+    }
+
+    pub fn is_torn_down(&self) -> bool {
+        self.importer.is_none()
+    }
+
+    /// Frees the GPU resources, in the order they depend on each other: the
+    /// copy in flight is waited for, the importer with its command pool is
+    /// dropped, then the mipmap pipeline and the textures are freed. Must run
+    /// on the main thread while the RenderingDevice is alive, after frames
+    /// stopped being requested and before the browser is closed. Paints
+    /// arriving afterwards are ignored.
+    pub fn teardown(&mut self) {
+        let Some(mut importer) = self.importer.take() else {
+            return;
+        };
+        debug_assert!(
+            RenderingServer::singleton()
+                .get_rendering_device()
+                .is_some(),
+            "AcceleratedRenderState torn down after the RenderingDevice"
+        );
+        if let Err(e) = importer.wait_for_copy() {
+            godot::global::godot_warn!(
+                "[AcceleratedOSR] Copy in flight did not finish before teardown: {}",
+                e
+            );
+        }
+        drop(importer);
         self.has_pending_copy = false;
-        Ok(())
+        self.needs_resize = None;
+        self.needs_popup_texture = None;
+
+        if let Some(mut generator) = self.mipmap_generator.take() {
+            generator.free();
+        }
+        crate::render::free_rd_texture(std::mem::replace(&mut self.dst_rd_rid, Rid::Invalid));
+        if let Some(popup_rid) = self.popup_rd_rid.take() {
+            crate::render::free_rd_texture(popup_rid);
+        }
+        LIVE_RENDER_STATES.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl Drop for AcceleratedRenderState {
+    fn drop(&mut self) {
+        debug_assert!(
+            self.is_torn_down(),
+            "AcceleratedRenderState dropped without teardown()"
+        );
+    }
+}
+
+/// Delay before (and between) attempts to rebuild GPU resources after the
+/// device was lost, giving Godot's RenderingDevice time to recover.
+const DEVICE_RECOVERY_DELAY: Duration = Duration::from_secs(1);
+
+/// Failed rebuild attempts after which software rendering is used instead.
+const MAX_DEVICE_RECOVERY_ATTEMPTS: u32 = 2;
+
+/// Tracks recovery from a lost GPU device (driver update, TDR, ...).
+#[derive(Debug)]
+pub struct DeviceLossRecovery {
+    next_attempt: Instant,
+    failed_attempts: u32,
+}
+
+impl DeviceLossRecovery {
+    pub fn new(now: Instant) -> Self {
+        Self {
+            next_attempt: now + DEVICE_RECOVERY_DELAY,
+            failed_attempts: 0,
+        }
+    }
+
+    /// Returns true if the next rebuild attempt should run now.
+    pub fn is_due(&self, now: Instant) -> bool {
+        now >= self.next_attempt
+    }
+
+    /// Records a failed rebuild attempt. Returns true once recovery should be
+    /// abandoned in favor of software rendering.
+    pub fn attempt_failed(&mut self, now: Instant) -> bool {
+        self.failed_attempts += 1;
+        self.next_attempt = now + DEVICE_RECOVERY_DELAY;
+        self.failed_attempts >= MAX_DEVICE_RECOVERY_ATTEMPTS
     }
 }
 
 #[derive(Clone)]
 pub struct AcceleratedRenderHandler {
     pub device_scale_factor: Arc<Mutex<f32>>,
+    /// Scale factor of the screen the view is on, reported to CEF.
+    pub display_scale: Arc<Mutex<f32>>,
     pub size: Arc<Mutex<cef_app::PhysicalSize<f32>>>,
     pub cursor_type: Arc<Mutex<cef_app::CursorType>>,
     pub popup_state: Arc<Mutex<cef_app::PopupState>>,
     render_state: Option<Arc<Mutex<AcceleratedRenderState>>>,
+    render_stats: Option<RenderStatsHandle>,
+    frame_pacer: Option<FramePacerHandle>,
 }
 
 impl AcceleratedRenderHandler {
-    pub fn new(device_scale_factor: f32, size: cef_app::PhysicalSize<f32>) -> Self {
+    pub fn new(
+        device_scale_factor: f32,
+        display_scale: f32,
+        size: cef_app::PhysicalSize<f32>,
+    ) -> Self {
         Self {
             device_scale_factor: Arc::new(Mutex::new(device_scale_factor)),
+            display_scale: Arc::new(Mutex::new(display_scale)),
             size: Arc::new(Mutex::new(size)),
             cursor_type: Arc::new(Mutex::new(cef_app::CursorType::default())),
             popup_state: Arc::new(Mutex::new(cef_app::PopupState::new())),
             render_state: None,
+            render_stats: None,
+            frame_pacer: None,
         }
     }
 
@@ -143,6 +338,14 @@ impl AcceleratedRenderHandler {
         self.render_state = Some(state);
     }
 
+    pub fn set_render_stats(&mut self, stats: RenderStatsHandle) {
+        self.render_stats = Some(stats);
+    }
+
+    pub fn set_frame_pacer(&mut self, pacer: FramePacerHandle) {
+        self.frame_pacer = Some(pacer);
+    }
+
     pub fn on_accelerated_paint(
         &self,
         type_: PaintElementType,
@@ -159,6 +362,10 @@ impl AcceleratedRenderHandler {
             let Ok(mut state) = render_state_arc.lock() else {
                 return;
             };
+            // The browser is closing
+            if state.is_torn_down() {
+                return;
+            }
 
             let need_new_texture = match state.popup_rd_rid {
                 None => true,
@@ -172,14 +379,17 @@ impl AcceleratedRenderHandler {
 
             // For popups, use synchronous copy (they're small and infrequent)
             if let Some(popup_rid) = state.popup_rd_rid {
-                let result = state
-                    .importer
-                    .queue_copy(info)
-                    .and_then(|_| state.importer.process_pending_copy(popup_rid))
-                    .and_then(|_| state.importer.wait_for_copy());
+                let popup_size = (state.popup_width, state.popup_height);
+                let result = state.importer_mut().and_then(|importer| {
+                    importer
+                        .queue_copy(info)
+                        .and_then(|_| importer.process_pending_copy(popup_rid, popup_size))
+                        .and_then(|outcome| importer.wait_for_copy().map(|_| outcome))
+                });
 
                 match result {
-                    Ok(_) => {
+                    Ok(CopyOutcome::Stale) => {}
+                    Ok(CopyOutcome::Copied) => {
                         state.popup_dirty = true;
                         state.popup_has_content = true;
                     }
@@ -201,6 +411,20 @@ impl AcceleratedRenderHandler {
         let src_width = info.extra.coded_size.width as u32;
         let src_height = info.extra.coded_size.height as u32;
 
+        if let Some(stats) = &self.render_stats {
+            stats.record_paint();
+        }
+
+        // A copy still waiting for Godot would be replaced unseen
+        if let Some(pacer) = &self.frame_pacer
+            && pacer.should_skip_paint()
+        {
+            if let Some(stats) = &self.render_stats {
+                stats.record_skipped_frame();
+            }
+            return;
+        }
+
         // Queue the copy operation for deferred processing
         // This returns immediately after duplicating the handle
         let Some(render_state_arc) = &self.render_state else {
@@ -211,21 +435,38 @@ impl AcceleratedRenderHandler {
             godot::global::godot_error!("[AcceleratedOSR] Failed to lock render state");
             return;
         };
+        if state.is_torn_down() {
+            return;
+        }
 
         // Check if texture dimensions changed - defer resize to main loop
         if src_width != state.dst_width || src_height != state.dst_height {
             state.needs_resize = Some((src_width, src_height));
             // Note: we still queue the copy below to capture this frame.
-            // The frame will be processed AFTER resize in update_texture().
+            // The frame will be processed AFTER resize in update_texture(), and
+            // skipped there if the destination has another size by then.
         }
 
         // Queue the copy operation (fast - just duplicates handle)
         // The actual GPU work will be done in process_pending_copy()
         // We queue even during resize to capture the frame - dst_rd_rid will be
         // passed at processing time after any resize is complete.
-        match state.importer.queue_copy(info) {
+        match state
+            .importer_mut()
+            .and_then(|importer| importer.queue_copy(info))
+        {
             Ok(_) => {
+                // A copy still pending here was never consumed by the main loop
+                if state.has_pending_copy
+                    && let Some(stats) = &self.render_stats
+                {
+                    stats.record_dropped_frame();
+                }
                 state.has_pending_copy = true;
+                state.pending_copy_size = (src_width, src_height);
+                if let Some(pacer) = &self.frame_pacer {
+                    pacer.frame_queued();
+                }
             }
             Err(e) => {
                 if !e.contains("D3D12 device removed") {
@@ -246,6 +487,10 @@ impl AcceleratedRenderHandler {
         self.device_scale_factor.clone()
     }
 
+    pub fn get_display_scale(&self) -> Arc<Mutex<f32>> {
+        self.display_scale.clone()
+    }
+
     pub fn get_cursor_type(&self) -> Arc<Mutex<cef_app::CursorType>> {
         self.cursor_type.clone()
     }
@@ -289,11 +534,54 @@ impl GodotTextureImporter {
         Err("Accelerated OSR not supported on this platform".to_string())
     }
 
-    pub fn process_pending_copy(&mut self, _dst_rd_rid: Rid) -> Result<(), String> {
+    pub fn process_pending_copy(
+        &mut self,
+        _dst_rd_rid: Rid,
+        _dst_size: (u32, u32),
+    ) -> Result<CopyOutcome, String> {
         Err("Accelerated OSR not supported on this platform".to_string())
     }
 
     pub fn wait_for_copy(&mut self) -> Result<(), String> {
         Err("Accelerated OSR not supported on this platform".to_string())
     }
+
+    pub fn is_device_lost(&mut self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_fits_destination() {
+        assert!(frame_fits_destination((800, 600), (800, 600)));
+        // A frame painted before the destination was resized
+        assert!(!frame_fits_destination((800, 600), (1024, 768)));
+        assert!(!frame_fits_destination((800, 600), (800, 601)));
+        assert!(!frame_fits_destination((0, 0), (0, 0)));
+    }
+
+    #[test]
+    fn test_device_loss_recovery_waits_before_each_attempt() {
+        let start = Instant::now();
+        let mut recovery = DeviceLossRecovery::new(start);
+        assert!(!recovery.is_due(start));
+        assert!(recovery.is_due(start + DEVICE_RECOVERY_DELAY));
+
+        let failed_at = start + DEVICE_RECOVERY_DELAY;
+        assert!(!recovery.attempt_failed(failed_at));
+        assert!(!recovery.is_due(failed_at));
+        assert!(recovery.is_due(failed_at + DEVICE_RECOVERY_DELAY));
+    }
+
+    #[test]
+    fn test_device_loss_recovery_gives_up_after_two_failures() {
+        let start = Instant::now();
+        let mut recovery = DeviceLossRecovery::new(start);
+        assert!(!recovery.attempt_failed(start));
+        assert!(recovery.attempt_failed(start));
+    }
 }
@@ -1,3 +1,4 @@
+use crate::accelerated_osr::{CopyOutcome, frame_fits_destination};
 use godot::classes::RenderingServer;
 use godot::classes::rendering_device::DriverResource;
 use godot::global::{godot_error, godot_print, godot_warn};
@@ -11,8 +12,10 @@ use windows::Win32::Graphics::Direct3D12::{
     D3D12_RESOURCE_BARRIER_0, D3D12_RESOURCE_BARRIER_ALL_SUBRESOURCES,
     D3D12_RESOURCE_BARRIER_FLAG_NONE, D3D12_RESOURCE_BARRIER_TYPE_TRANSITION, D3D12_RESOURCE_DESC,
     D3D12_RESOURCE_DIMENSION_TEXTURE2D, D3D12_RESOURCE_STATE_COMMON,
-    D3D12_RESOURCE_STATE_COPY_DEST, D3D12_RESOURCE_TRANSITION_BARRIER, ID3D12CommandAllocator,
-    ID3D12CommandQueue, ID3D12Device, ID3D12Fence, ID3D12GraphicsCommandList, ID3D12Resource,
+    D3D12_RESOURCE_STATE_COPY_DEST, D3D12_RESOURCE_TRANSITION_BARRIER, D3D12_TEXTURE_COPY_LOCATION,
+    D3D12_TEXTURE_COPY_LOCATION_0, D3D12_TEXTURE_COPY_TYPE_SUBRESOURCE_INDEX,
+    ID3D12CommandAllocator, ID3D12CommandQueue, ID3D12Device, ID3D12Fence,
+    ID3D12GraphicsCommandList, ID3D12Resource,
 };
 use windows::Win32::Graphics::Dxgi::{CreateDXGIFactory, IDXGIAdapter, IDXGIFactory};
 use windows::Win32::System::Threading::{
@@ -154,19 +157,22 @@ impl D3D12TextureImporter {
     }
 
     pub fn check_device_state(&mut self) -> Result<(), String> {
-        let reason = unsafe { self.device.GetDeviceRemovedReason() };
-        if reason.is_ok() {
-            self.device_removed_logged = false;
-            Ok(())
-        } else if !self.device_removed_logged {
-            godot_warn!(
-                "[AcceleratedOSR/D3D12] D3D12 device removed: {:?}",
-                reason.err()
-            );
-            self.device_removed_logged = true;
-            Err("D3D12 device removed".into())
-        } else {
-            Err("D3D12 device removed".into())
+        match unsafe { self.device.GetDeviceRemovedReason() } {
+            Ok(()) => {
+                self.device_removed_logged = false;
+                Ok(())
+            }
+            Err(reason) => {
+                if !self.device_removed_logged {
+                    godot_warn!(
+                        "[AcceleratedOSR/D3D12] D3D12 device removed, reason 0x{:08X}: {}",
+                        reason.code().0 as u32,
+                        reason.message()
+                    );
+                    self.device_removed_logged = true;
+                }
+                Err("D3D12 device removed".into())
+            }
         }
     }
 
@@ -188,10 +194,11 @@ impl D3D12TextureImporter {
         if let Err(e) = result {
             let device_reason = unsafe { self.device.GetDeviceRemovedReason() };
             if !self.device_removed_logged {
-                if device_reason.is_err() {
+                if let Err(reason) = device_reason {
                     godot_warn!(
-                        "[AcceleratedOSR/D3D12] Device removed: {:?}",
-                        device_reason.err()
+                        "[AcceleratedOSR/D3D12] Device removed, reason 0x{:08X}: {}",
+                        reason.code().0 as u32,
+                        reason.message()
                     );
                 } else {
                     godot_warn!("[AcceleratedOSR/D3D12] OpenSharedHandle failed: {:?}", e);
@@ -244,18 +251,27 @@ impl D3D12TextureImporter {
         Ok(())
     }
 
-    pub fn process_pending_copy(&mut self, dst_rd_rid: Rid) -> Result<(), String> {
+    pub fn process_pending_copy(
+        &mut self,
+        dst_rd_rid: Rid,
+        dst_size: (u32, u32),
+    ) -> Result<CopyOutcome, String> {
         self.check_device_state()?;
 
         let pending = match self.pending_copy.take() {
             Some(p) => p,
-            None => return Ok(()), // Nothing to do
+            None => return Ok(CopyOutcome::Copied), // Nothing to do
         };
 
         if !dst_rd_rid.is_valid() {
             return Err("Destination RID is invalid".into());
         }
 
+        // A frame painted before a resize; dropping it closes its handle
+        if !frame_fits_destination((pending.width, pending.height), dst_size) {
+            return Ok(CopyOutcome::Stale);
+        }
+
         // Wait for any previous in-flight copy to complete before reusing resources
         if self.copy_in_flight {
             self.wait_for_copy()?;
@@ -311,7 +327,7 @@ impl D3D12TextureImporter {
         // Prevent pending's Drop from closing the handle (we transferred ownership)
         std::mem::forget(pending);
 
-        Ok(())
+        Ok(CopyOutcome::Copied)
     }
 
     pub fn wait_for_copy(&mut self) -> Result<(), String> {
@@ -382,7 +398,18 @@ impl D3D12TextureImporter {
         };
 
         unsafe { command_list.ResourceBarrier(&[dst_barrier]) };
-        unsafe { command_list.CopyResource(dst_resource, src_resource) };
+        // Only the first mip level: the destination has more with
+        // `generate_mipmaps`, which `CopyResource` does not allow
+        let copy_location = |resource: &ID3D12Resource| D3D12_TEXTURE_COPY_LOCATION {
+            pResource: unsafe { std::mem::transmute_copy(resource) },
+            Type: D3D12_TEXTURE_COPY_TYPE_SUBRESOURCE_INDEX,
+            Anonymous: D3D12_TEXTURE_COPY_LOCATION_0 {
+                SubresourceIndex: 0,
+            },
+        };
+        let dst_location = copy_location(dst_resource);
+        let src_location = copy_location(src_resource);
+        unsafe { command_list.CopyTextureRegion(&dst_location, 0, 0, 0, &src_location, None) };
 
         // Transition back to COMMON for shader read
         let dst_barrier_after = D3D12_RESOURCE_BARRIER {
@@ -1,7 +1,7 @@
 mod d3d12;
 mod vulkan;
 
-use super::RenderBackend;
+use super::{CopyOutcome, RenderBackend};
 use godot::classes::RenderingServer;
 use godot::global::{godot_print, godot_warn};
 use godot::prelude::*;
@@ -38,6 +38,15 @@ impl GodotTextureImporter {
     pub fn new() -> Option<Self> {
         let render_backend = RenderBackend::detect();
 
+        if render_backend == RenderBackend::Vulkan
+            && !crate::vulkan_hook::is_external_memory_enabled()
+        {
+            godot_warn!(
+                "[AcceleratedOSR/Windows] Godot's Vulkan device was created without \
+                 VK_KHR_external_memory_win32"
+            );
+        }
+
         if !render_backend.supports_accelerated_osr() {
             godot_warn!(
                 "[AcceleratedOSR/Windows] Render backend {:?} does not support accelerated OSR. \
@@ -80,10 +89,18 @@ impl GodotTextureImporter {
         }
     }
 
-    pub fn process_pending_copy(&mut self, dst_rd_rid: Rid) -> Result<(), String> {
+    pub fn process_pending_copy(
+        &mut self,
+        dst_rd_rid: Rid,
+        dst_size: (u32, u32),
+    ) -> Result<CopyOutcome, String> {
         match &mut self.backend {
-            TextureImporterBackend::D3D12(importer) => importer.process_pending_copy(dst_rd_rid),
-            TextureImporterBackend::Vulkan(importer) => importer.process_pending_copy(dst_rd_rid),
+            TextureImporterBackend::D3D12(importer) => {
+                importer.process_pending_copy(dst_rd_rid, dst_size)
+            }
+            TextureImporterBackend::Vulkan(importer) => {
+                importer.process_pending_copy(dst_rd_rid, dst_size)
+            }
         }
     }
 
@@ -93,6 +110,14 @@ impl GodotTextureImporter {
             TextureImporterBackend::Vulkan(importer) => importer.wait_for_copy(),
         }
     }
+
+    /// Returns true if the GPU device was removed (driver update, TDR, ...).
+    pub fn is_device_lost(&mut self) -> bool {
+        match &mut self.backend {
+            TextureImporterBackend::D3D12(importer) => importer.check_device_state().is_err(),
+            TextureImporterBackend::Vulkan(_) => false,
+        }
+    }
 }
 
 impl Drop for GodotTextureImporter {
@@ -1,3 +1,4 @@
+use crate::accelerated_osr::{CopyOutcome, frame_fits_destination};
 use ash::vk;
 use godot::classes::RenderingServer;
 use godot::classes::rendering_device::DriverResource;
@@ -415,16 +416,25 @@ impl VulkanTextureImporter {
         Ok(())
     }
 
-    pub fn process_pending_copy(&mut self, dst_rd_rid: Rid) -> Result<(), String> {
+    pub fn process_pending_copy(
+        &mut self,
+        dst_rd_rid: Rid,
+        dst_size: (u32, u32),
+    ) -> Result<CopyOutcome, String> {
         let pending = match self.pending_copy.take() {
             Some(p) => p,
-            None => return Ok(()), // Nothing to do
+            None => return Ok(CopyOutcome::Copied), // Nothing to do
         };
 
         if !dst_rd_rid.is_valid() {
             return Err("Destination RID is invalid".into());
         }
 
+        // A frame painted before a resize; dropping it closes its handle
+        if !frame_fits_destination((pending.width, pending.height), dst_size) {
+            return Ok(CopyOutcome::Stale);
+        }
+
         // Wait for any previous in-flight copy to complete before reusing resources
         if self.copy_in_flight {
             self.wait_for_copy()?;
@@ -461,7 +471,7 @@ impl VulkanTextureImporter {
         // We need to prevent the Drop impl from closing it.
         std::mem::forget(pending);
 
-        Ok(())
+        Ok(CopyOutcome::Copied)
     }
 
     pub fn wait_for_copy(&mut self) -> Result<(), String> {
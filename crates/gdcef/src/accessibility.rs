@@ -0,0 +1,621 @@
+//! Accessibility tree of a browser, for screen-reader integration.
+//!
+//! With accessibility enabled, CEF reports Chromium's accessibility tree as
+//! incremental updates. The accessibility handler converts them to the plain
+//! structs below on the CEF UI thread, and `AccessibilityTree` applies them on
+//! the Godot main thread. Only the tree of the main frame is tracked; iframes
+//! report trees of their own, which are ignored.
+
+use cef::{CefString, DictionaryValue, ImplDictionaryValue, ImplListValue, ImplValue, Value};
+use std::collections::{BTreeSet, HashMap};
+
+/// Nodes kept per browser. Nodes added beyond it are dropped, and the tree is
+/// reported as truncated.
+pub const MAX_NODES: usize = 10_000;
+
+/// Levels below the root included when the tree is flattened.
+pub const MAX_DEPTH: usize = 64;
+
+/// Rectangle in view coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AxBounds {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AxNode {
+    pub id: i32,
+    pub role: String,
+    pub name: String,
+    pub value: String,
+    /// Bounds relative to the offset container, or to the view without one.
+    pub location: AxBounds,
+    pub offset_container_id: Option<i32>,
+    pub child_ids: Vec<i32>,
+}
+
+/// One update of an accessibility tree, as serialized by Chromium.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AxTreeUpdate {
+    pub tree_id: String,
+    /// Parent tree from the tree data: empty for the main frame's tree, and
+    /// `None` when the update carries no tree data.
+    pub parent_tree_id: Option<String>,
+    pub root_id: Option<i32>,
+    pub focus_id: Option<i32>,
+    /// Node whose descendants are removed before the nodes are applied.
+    pub node_id_to_clear: Option<i32>,
+    pub nodes: Vec<AxNode>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AxLocationChange {
+    pub tree_id: String,
+    pub id: i32,
+    pub location: AxBounds,
+    pub offset_container_id: Option<i32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum AccessibilityEvent {
+    Tree(AxTreeUpdate),
+    Location(AxLocationChange),
+}
+
+/// Nodes added, changed or removed by the applied events.
+#[derive(Debug, Default, PartialEq)]
+pub struct AxChanges {
+    pub updated: BTreeSet<i32>,
+    pub removed: BTreeSet<i32>,
+    pub focus_changed: bool,
+}
+
+impl AxChanges {
+    pub fn is_empty(&self) -> bool {
+        self.updated.is_empty() && self.removed.is_empty() && !self.focus_changed
+    }
+
+    fn update(&mut self, id: i32) {
+        self.removed.remove(&id);
+        self.updated.insert(id);
+    }
+
+    fn remove(&mut self, id: i32) {
+        self.updated.remove(&id);
+        self.removed.insert(id);
+    }
+}
+
+/// Flattened node tree of the main frame, kept up to date from the
+/// incremental updates.
+#[derive(Default)]
+pub struct AccessibilityTree {
+    tree_id: String,
+    root_id: Option<i32>,
+    focus_id: Option<i32>,
+    nodes: HashMap<i32, AxNode>,
+    parents: HashMap<i32, i32>,
+    truncated: bool,
+}
+
+impl AccessibilityTree {
+    pub fn root_id(&self) -> Option<i32> {
+        self.root_id
+    }
+
+    pub fn focus_id(&self) -> Option<i32> {
+        self.focus_id
+    }
+
+    pub fn node(&self, id: i32) -> Option<&AxNode> {
+        self.nodes.get(&id)
+    }
+
+    pub fn parent_id(&self, id: i32) -> Option<i32> {
+        self.parents.get(&id).copied()
+    }
+
+    /// Whether nodes were dropped because the tree reached `MAX_NODES`.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub fn apply(&mut self, event: &AccessibilityEvent, changes: &mut AxChanges) {
+        match event {
+            AccessibilityEvent::Tree(update) => self.apply_update(update, changes),
+            AccessibilityEvent::Location(change) => self.apply_location_change(change, changes),
+        }
+    }
+
+    fn apply_update(&mut self, update: &AxTreeUpdate, changes: &mut AxChanges) {
+        // A new main-frame tree replaces the old one, e.g. after a navigation
+        if update.parent_tree_id.as_deref() == Some("") && update.tree_id != self.tree_id {
+            for &id in self.nodes.keys() {
+                changes.remove(id);
+            }
+            changes.focus_changed |= self.focus_id.is_some();
+            self.clear();
+            self.tree_id = update.tree_id.clone();
+        }
+        if update.tree_id != self.tree_id {
+            return;
+        }
+
+        if let Some(id) = update.node_id_to_clear
+            && let Some(node) = self.nodes.get_mut(&id)
+        {
+            for child in std::mem::take(&mut node.child_ids) {
+                self.remove_subtree(child, changes);
+            }
+        }
+        if let Some(root_id) = update.root_id
+            && self.root_id != Some(root_id)
+        {
+            if let Some(old_root_id) = self.root_id {
+                self.remove_subtree(old_root_id, changes);
+            }
+            self.root_id = Some(root_id);
+        }
+
+        let mut dropped_children = Vec::new();
+        for node in &update.nodes {
+            match self.nodes.get(&node.id) {
+                Some(old) => dropped_children.extend(
+                    old.child_ids
+                        .iter()
+                        .filter(|child| !node.child_ids.contains(child)),
+                ),
+                None if self.nodes.len() >= MAX_NODES => {
+                    self.truncated = true;
+                    continue;
+                }
+                None => {}
+            }
+            for &child in &node.child_ids {
+                self.parents.insert(child, node.id);
+            }
+            self.nodes.insert(node.id, node.clone());
+            changes.update(node.id);
+        }
+        // Children moved to another parent in the same update stay
+        for id in dropped_children {
+            let reparented = self
+                .parent_id(id)
+                .and_then(|parent| self.nodes.get(&parent))
+                .is_some_and(|parent| parent.child_ids.contains(&id));
+            if !reparented {
+                self.remove_subtree(id, changes);
+            }
+        }
+
+        if let Some(focus_id) = update.focus_id
+            && self.focus_id != Some(focus_id)
+        {
+            self.focus_id = Some(focus_id);
+            changes.focus_changed = true;
+        }
+    }
+
+    fn apply_location_change(&mut self, change: &AxLocationChange, changes: &mut AxChanges) {
+        if change.tree_id != self.tree_id {
+            return;
+        }
+        if let Some(node) = self.nodes.get_mut(&change.id) {
+            node.location = change.location;
+            node.offset_container_id = change.offset_container_id;
+            changes.update(change.id);
+        }
+    }
+
+    fn remove_subtree(&mut self, id: i32, changes: &mut AxChanges) {
+        let mut pending = vec![id];
+        while let Some(id) = pending.pop() {
+            if let Some(node) = self.nodes.remove(&id) {
+                pending.extend(node.child_ids);
+                changes.remove(id);
+            }
+            self.parents.remove(&id);
+            if self.root_id == Some(id) {
+                self.root_id = None;
+            }
+            if self.focus_id == Some(id) {
+                self.focus_id = None;
+                changes.focus_changed = true;
+            }
+        }
+    }
+
+    /// Returns the bounds of a node in view coordinates, adding up the
+    /// offsets of its containers.
+    pub fn bounds(&self, id: i32) -> Option<AxBounds> {
+        let mut node = self.nodes.get(&id)?;
+        let mut bounds = node.location;
+        for _ in 0..MAX_DEPTH {
+            let Some(container) = node
+                .offset_container_id
+                .and_then(|container| self.nodes.get(&container))
+            else {
+                break;
+            };
+            bounds.x += container.location.x;
+            bounds.y += container.location.y;
+            node = container;
+        }
+        Some(bounds)
+    }
+
+    /// Returns the ids and depths of the nodes in document order, down to
+    /// `MAX_DEPTH` levels below the root.
+    pub fn flatten(&self) -> Vec<(i32, usize)> {
+        let mut flattened = Vec::new();
+        let mut pending: Vec<(i32, usize)> = self.root_id.map(|id| (id, 0)).into_iter().collect();
+        while let Some((id, depth)) = pending.pop() {
+            if flattened.len() >= MAX_NODES {
+                break;
+            }
+            let Some(node) = self.nodes.get(&id) else {
+                continue;
+            };
+            flattened.push((id, depth));
+            if depth < MAX_DEPTH {
+                pending.extend(node.child_ids.iter().rev().map(|&child| (child, depth + 1)));
+            }
+        }
+        flattened
+    }
+}
+
+fn key(name: &str) -> CefString {
+    CefString::from(name)
+}
+
+fn dictionary_string(dictionary: &DictionaryValue, name: &str) -> String {
+    CefString::from(&dictionary.string(Some(&key(name)))).to_string()
+}
+
+/// Reads a node id, which CEF serializes either as an integer or as a string.
+/// Chromium uses 0 for "no node", and -1 for "no offset container".
+fn dictionary_id(dictionary: &DictionaryValue, name: &str) -> Option<i32> {
+    if dictionary.has_key(Some(&key(name))) == 0 {
+        return None;
+    }
+    let text = dictionary_string(dictionary, name);
+    let id = if text.is_empty() {
+        dictionary.int(Some(&key(name)))
+    } else {
+        text.parse().ok()?
+    };
+    (id > 0).then_some(id)
+}
+
+fn dictionary_bounds(dictionary: &DictionaryValue) -> AxBounds {
+    let Some(location) = dictionary.dictionary(Some(&key("location"))) else {
+        return AxBounds::default();
+    };
+    let double = |name: &str| location.double(Some(&key(name))) as f32;
+    AxBounds {
+        x: double("x"),
+        y: double("y"),
+        width: double("width"),
+        height: double("height"),
+    }
+}
+
+fn parse_node(dictionary: &DictionaryValue) -> Option<AxNode> {
+    let id = dictionary_id(dictionary, "id")?;
+    let attributes = dictionary.dictionary(Some(&key("attributes")));
+    let attribute = |name: &str| {
+        attributes
+            .as_ref()
+            .map(|a| dictionary_string(a, name))
+            .unwrap_or_default()
+    };
+    let child_ids = dictionary
+        .list(Some(&key("child_ids")))
+        .map(|children| {
+            (0..children.size())
+                .filter_map(|index| {
+                    let text = CefString::from(&children.string(index)).to_string();
+                    if text.is_empty() {
+                        Some(children.int(index))
+                    } else {
+                        text.parse().ok()
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(AxNode {
+        id,
+        role: dictionary_string(dictionary, "role"),
+        name: attribute("name"),
+        value: attribute("value"),
+        location: dictionary_bounds(dictionary),
+        offset_container_id: dictionary_id(dictionary, "offset_container_id"),
+        child_ids,
+    })
+}
+
+fn parse_update(tree_id: &str, dictionary: &DictionaryValue) -> AxTreeUpdate {
+    let tree_data = dictionary.dictionary(Some(&key("tree_data")));
+    let nodes = dictionary
+        .list(Some(&key("nodes")))
+        .map(|nodes| {
+            (0..nodes.size())
+                .filter_map(|index| nodes.dictionary(index))
+                .filter_map(|node| parse_node(&node))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    AxTreeUpdate {
+        tree_id: tree_id.to_string(),
+        parent_tree_id: tree_data
+            .as_ref()
+            .map(|data| dictionary_string(data, "parent_tree_id")),
+        root_id: dictionary_id(dictionary, "root_id"),
+        focus_id: tree_data
+            .as_ref()
+            .and_then(|data| dictionary_id(data, "focus_id")),
+        node_id_to_clear: dictionary_id(dictionary, "node_id_to_clear"),
+        nodes,
+    }
+}
+
+/// Converts the value of `on_accessibility_tree_change` to tree updates.
+pub fn parse_tree_change(value: &mut Value) -> Vec<AccessibilityEvent> {
+    let Some(root) = value.dictionary() else {
+        return Vec::new();
+    };
+    let tree_id = dictionary_string(&root, "ax_tree_id");
+    let Some(updates) = root.list(Some(&key("updates"))) else {
+        return Vec::new();
+    };
+    (0..updates.size())
+        .filter_map(|index| updates.dictionary(index))
+        .map(|update| AccessibilityEvent::Tree(parse_update(&tree_id, &update)))
+        .collect()
+}
+
+/// Converts the value of `on_accessibility_location_change` to location
+/// changes. Older CEF versions send a bare list of changes.
+pub fn parse_location_change(value: &mut Value) -> Vec<AccessibilityEvent> {
+    let Some(changes) = value.list().or_else(|| {
+        value
+            .dictionary()
+            .and_then(|d| d.list(Some(&key("updates"))))
+    }) else {
+        return Vec::new();
+    };
+    (0..changes.size())
+        .filter_map(|index| changes.dictionary(index))
+        .filter_map(|change| {
+            let id = dictionary_id(&change, "id")?;
+            let new_location = change.dictionary(Some(&key("new_location")))?;
+            Some(AccessibilityEvent::Location(AxLocationChange {
+                tree_id: dictionary_string(&change, "ax_tree_id"),
+                id,
+                location: dictionary_bounds(&new_location),
+                offset_container_id: dictionary_id(&new_location, "offset_container_id"),
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: i32, child_ids: &[i32]) -> AxNode {
+        AxNode {
+            id,
+            role: "generic".to_string(),
+            child_ids: child_ids.to_vec(),
+            ..Default::default()
+        }
+    }
+
+    fn main_tree_update(nodes: Vec<AxNode>) -> AccessibilityEvent {
+        AccessibilityEvent::Tree(AxTreeUpdate {
+            tree_id: "main".to_string(),
+            parent_tree_id: Some(String::new()),
+            root_id: Some(1),
+            focus_id: Some(1),
+            nodes,
+            ..Default::default()
+        })
+    }
+
+    fn partial_update(tree_id: &str, nodes: Vec<AxNode>) -> AccessibilityEvent {
+        AccessibilityEvent::Tree(AxTreeUpdate {
+            tree_id: tree_id.to_string(),
+            nodes,
+            ..Default::default()
+        })
+    }
+
+    fn ids(ids: &[i32]) -> BTreeSet<i32> {
+        ids.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_initial_tree() {
+        let mut tree = AccessibilityTree::default();
+        let mut changes = AxChanges::default();
+        tree.apply(
+            &main_tree_update(vec![
+                node(1, &[2, 3]),
+                node(2, &[4]),
+                node(3, &[]),
+                node(4, &[]),
+            ]),
+            &mut changes,
+        );
+
+        assert_eq!(changes.updated, ids(&[1, 2, 3, 4]));
+        assert!(changes.focus_changed);
+        assert_eq!(tree.flatten(), vec![(1, 0), (2, 1), (4, 2), (3, 1)]);
+        assert_eq!(tree.parent_id(4), Some(2));
+        assert_eq!(tree.focus_id(), Some(1));
+    }
+
+    #[test]
+    fn test_removed_and_reparented_children() {
+        let mut tree = AccessibilityTree::default();
+        tree.apply(
+            &main_tree_update(vec![
+                node(1, &[2, 3]),
+                node(2, &[4]),
+                node(3, &[5]),
+                node(5, &[]),
+            ]),
+            &mut AxChanges::default(),
+        );
+
+        // 3 is removed with its subtree, 4 moves under 1
+        let mut changes = AxChanges::default();
+        tree.apply(
+            &partial_update("main", vec![node(1, &[2, 4]), node(2, &[])]),
+            &mut changes,
+        );
+        assert_eq!(changes.updated, ids(&[1, 2]));
+        assert_eq!(changes.removed, ids(&[3, 5]));
+        assert!(!changes.focus_changed);
+        assert_eq!(tree.parent_id(4), Some(1));
+        assert!(tree.node(5).is_none());
+    }
+
+    #[test]
+    fn test_node_id_to_clear() {
+        let mut tree = AccessibilityTree::default();
+        tree.apply(
+            &main_tree_update(vec![node(1, &[2]), node(2, &[3]), node(3, &[])]),
+            &mut AxChanges::default(),
+        );
+
+        let mut changes = AxChanges::default();
+        let mut update = AxTreeUpdate {
+            tree_id: "main".to_string(),
+            node_id_to_clear: Some(1),
+            nodes: vec![node(1, &[6]), node(6, &[])],
+            ..Default::default()
+        };
+        tree.apply(&AccessibilityEvent::Tree(update.clone()), &mut changes);
+        assert_eq!(changes.removed, ids(&[2, 3]));
+        assert_eq!(tree.flatten(), vec![(1, 0), (6, 1)]);
+
+        // Updates of other trees, such as iframes, are ignored
+        update.tree_id = "iframe".to_string();
+        update.node_id_to_clear = None;
+        update.nodes = vec![node(1, &[])];
+        let mut changes = AxChanges::default();
+        tree.apply(&AccessibilityEvent::Tree(update), &mut changes);
+        assert!(changes.is_empty());
+        assert_eq!(tree.flatten(), vec![(1, 0), (6, 1)]);
+    }
+
+    #[test]
+    fn test_new_main_tree_replaces_old() {
+        let mut tree = AccessibilityTree::default();
+        tree.apply(
+            &main_tree_update(vec![node(1, &[2]), node(2, &[])]),
+            &mut AxChanges::default(),
+        );
+
+        let mut changes = AxChanges::default();
+        tree.apply(
+            &AccessibilityEvent::Tree(AxTreeUpdate {
+                tree_id: "next".to_string(),
+                parent_tree_id: Some(String::new()),
+                root_id: Some(1),
+                nodes: vec![node(1, &[])],
+                ..Default::default()
+            }),
+            &mut changes,
+        );
+        assert_eq!(changes.updated, ids(&[1]));
+        assert_eq!(changes.removed, ids(&[2]));
+        assert!(changes.focus_changed);
+        assert_eq!(tree.focus_id(), None);
+    }
+
+    #[test]
+    fn test_bounds_with_offset_container() {
+        let mut tree = AccessibilityTree::default();
+        let mut root = node(1, &[2]);
+        root.location = AxBounds {
+            x: 10.0,
+            y: 20.0,
+            width: 300.0,
+            height: 200.0,
+        };
+        tree.apply(
+            &main_tree_update(vec![root, node(2, &[])]),
+            &mut AxChanges::default(),
+        );
+
+        let mut changes = AxChanges::default();
+        tree.apply(
+            &AccessibilityEvent::Location(AxLocationChange {
+                tree_id: "main".to_string(),
+                id: 2,
+                location: AxBounds {
+                    x: 5.0,
+                    y: 5.0,
+                    width: 50.0,
+                    height: 10.0,
+                },
+                offset_container_id: Some(1),
+            }),
+            &mut changes,
+        );
+        assert_eq!(changes.updated, ids(&[2]));
+        assert_eq!(
+            tree.bounds(2),
+            Some(AxBounds {
+                x: 15.0,
+                y: 25.0,
+                width: 50.0,
+                height: 10.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_node_limit() {
+        let mut tree = AccessibilityTree::default();
+        let children: Vec<i32> = (2..=MAX_NODES as i32 + 1).collect();
+        let mut nodes = vec![node(1, &children)];
+        nodes.extend(children.iter().map(|&id| node(id, &[])));
+        tree.apply(&main_tree_update(nodes), &mut AxChanges::default());
+
+        assert!(tree.is_truncated());
+        assert_eq!(tree.flatten().len(), MAX_NODES);
+        assert!(tree.node(MAX_NODES as i32 + 1).is_none());
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        let mut tree = AccessibilityTree::default();
+        let depth = MAX_DEPTH as i32 + 5;
+        let nodes = (1..=depth)
+            .map(|id| {
+                let children = if id < depth { vec![id + 1] } else { Vec::new() };
+                node(id, &children)
+            })
+            .collect();
+        tree.apply(&main_tree_update(nodes), &mut AxChanges::default());
+
+        let flattened = tree.flatten();
+        assert_eq!(flattened.len(), MAX_DEPTH + 1);
+        assert_eq!(flattened.last(), Some(&(MAX_DEPTH as i32 + 1, MAX_DEPTH)));
+    }
+}
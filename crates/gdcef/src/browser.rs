@@ -3,40 +3,252 @@
 //! This module contains the core state types used by CefTexture for managing
 //! the browser instance and rendering mode.
 
-use cef_app::{CursorType, FrameBuffer, PhysicalSize, PopupState};
+use cef_app::{CursorType, FrameBuffer, PhysicalSize, PopupState, TextInputType};
 use godot::classes::{ImageTexture, Texture2Drd};
 use godot::prelude::*;
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 use std::sync::atomic::AtomicBool;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, RwLock};
 
 #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
 use crate::accelerated_osr::AcceleratedRenderState;
+use crate::accessibility::AccessibilityEvent;
+use crate::cookies::CookieRecord;
+use crate::event_queue::{BoundedQueue, OverflowPolicy, PushOutcome, QueueCategory, QueueLimits};
+use crate::frame_pacing::FramePacerHandle;
+use crate::render_stats::RenderStatsHandle;
+use crate::security::{
+    CertificateDecisions, ClientCertificateRequests, ExtraHeaders, NavigationAllowlist,
+};
+use crate::software_upload::SoftwareUploader;
+
+/// Kind of a queued event that becomes a signal. Recorded in arrival order
+/// so that signals of different kinds are emitted in the order they
+/// happened, e.g. `url_changed` before `load_finished` of a navigation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EventKind {
+    Message,
+    BinaryMessage,
+    UrlChange,
+    SecurityState,
+    CertificateError,
+    ClientCertificate,
+    NavigationBlocked,
+    ExternalProtocol,
+    TitleChange,
+    LoadingState,
+    LoadingStateChange,
+    ConsoleMessage,
+    Drag,
+    DownloadRequest,
+    DownloadUpdate,
+    SchemeRequest,
+    DevToolsResult,
+    DevToolsEvent,
+    DisplayMedia,
+    PageSaved,
+    FirstPaint,
+    DomQuery,
+    WebNotification,
+    BrowserShortcut,
+    BrowserGpuInfo,
+    CookiesExported,
+    CookiesImported,
+}
+
+impl EventKind {
+    /// All kinds, in the order used for events whose arrival was not recorded.
+    pub const ALL: [Self; 27] = [
+        Self::Message,
+        Self::BinaryMessage,
+        Self::UrlChange,
+        Self::SecurityState,
+        Self::CertificateError,
+        Self::ClientCertificate,
+        Self::NavigationBlocked,
+        Self::ExternalProtocol,
+        Self::TitleChange,
+        Self::LoadingState,
+        Self::LoadingStateChange,
+        Self::ConsoleMessage,
+        Self::Drag,
+        Self::DownloadRequest,
+        Self::DownloadUpdate,
+        Self::SchemeRequest,
+        Self::DevToolsResult,
+        Self::DevToolsEvent,
+        Self::DisplayMedia,
+        Self::PageSaved,
+        Self::FirstPaint,
+        Self::DomQuery,
+        Self::WebNotification,
+        Self::BrowserShortcut,
+        Self::BrowserGpuInfo,
+        Self::CookiesExported,
+        Self::CookiesImported,
+    ];
+}
+
+/// Returns the order in which to emit drained events, given how many of each
+/// kind were drained: the recorded arrival order, then any event whose
+/// arrival was not recorded.
+pub fn emission_order(
+    recorded: &[EventKind],
+    count: impl Fn(EventKind) -> usize,
+) -> Vec<EventKind> {
+    let mut remaining = EventKind::ALL.map(count);
+    let mut order = Vec::with_capacity(remaining.iter().sum());
+    for &kind in recorded {
+        let left = &mut remaining[kind as usize];
+        if *left > 0 {
+            *left -= 1;
+            order.push(kind);
+        }
+    }
+    for kind in EventKind::ALL {
+        order.extend(std::iter::repeat_n(kind, remaining[kind as usize]));
+    }
+    order
+}
 
 /// Represents a loading state event from the browser.
 #[derive(Debug, Clone)]
 pub enum LoadingStateEvent {
     /// Page started loading.
-    Started { url: String },
+    Started {
+        url: String,
+        frame_name: String,
+        is_main: bool,
+    },
     /// Page finished loading.
-    Finished { url: String, http_status_code: i32 },
+    Finished {
+        url: String,
+        http_status_code: i32,
+        frame_name: String,
+        is_main: bool,
+    },
     /// Page load error.
     Error {
         url: String,
         error_code: i32,
         error_text: String,
+        frame_name: String,
+        is_main: bool,
     },
 }
 
-/// IME composition range info for caret positioning.
-#[derive(Clone, Copy, Debug)]
+impl LoadingStateEvent {
+    /// Whether the event is about the main frame rather than an iframe.
+    pub fn is_main(&self) -> bool {
+        match self {
+            Self::Started { is_main, .. }
+            | Self::Finished { is_main, .. }
+            | Self::Error { is_main, .. } => *is_main,
+        }
+    }
+
+    /// Name of the frame, empty for the main frame and unnamed iframes.
+    pub fn frame_name(&self) -> &str {
+        match self {
+            Self::Started { frame_name, .. }
+            | Self::Finished { frame_name, .. }
+            | Self::Error { frame_name, .. } => frame_name,
+        }
+    }
+}
+
+/// A frame's address changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UrlChangeEvent {
+    pub url: String,
+    pub is_main: bool,
+}
+
+/// Navigation state of the browser, as shown by toolbar buttons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadingStateChangeEvent {
+    pub is_loading: bool,
+    pub can_go_back: bool,
+    pub can_go_forward: bool,
+}
+
+/// Bounds of a composition character in view coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CharacterBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl CharacterBounds {
+    /// Returns the smallest rectangle containing all of `bounds`.
+    pub fn union(bounds: &[Self]) -> Option<Self> {
+        let first = bounds.first()?;
+        let (mut left, mut top) = (first.x, first.y);
+        let (mut right, mut bottom) = (first.x + first.width, first.y + first.height);
+        for b in &bounds[1..] {
+            left = left.min(b.x);
+            top = top.min(b.y);
+            right = right.max(b.x + b.width);
+            bottom = bottom.max(b.y + b.height);
+        }
+        Some(Self {
+            x: left,
+            y: top,
+            width: right - left,
+            height: bottom - top,
+        })
+    }
+}
+
+/// IME caret and composition layout, used to place the candidate window.
+#[derive(Clone, Debug)]
 pub struct ImeCompositionRange {
-    /// Caret X position in view coordinates.
+    /// Caret X position in view coordinates (see `css_pixels`).
     pub caret_x: i32,
-    /// Caret Y position in view coordinates.
+    /// Caret Y position in view coordinates (see `css_pixels`).
     pub caret_y: i32,
     /// Caret height in pixels.
     pub caret_height: i32,
+    /// Whether the caret was measured by the page in CSS pixels, which the
+    /// zoom level scales, rather than in view coordinates.
+    pub css_pixels: bool,
+    /// Bounds of each UTF-16 code unit of the composition. Empty for carets
+    /// measured by the page.
+    pub character_bounds: Vec<CharacterBounds>,
+}
+
+impl ImeCompositionRange {
+    /// Builds the range reported by the render handler. The caret spans the
+    /// whole composition rather than its last character, which sits at the
+    /// left edge in right-to-left text.
+    pub fn from_character_bounds(character_bounds: Vec<CharacterBounds>) -> Option<Self> {
+        let caret = CharacterBounds::union(&character_bounds)?;
+        Some(Self {
+            caret_x: caret.x,
+            caret_y: caret.y,
+            caret_height: caret.height,
+            css_pixels: false,
+            character_bounds,
+        })
+    }
+
+    /// Narrows the caret to the code units `start..end` of the composition,
+    /// e.g. the clause being converted, when their bounds are known.
+    pub fn anchored_to(mut self, start: usize, end: usize) -> Self {
+        if let Some(clause) = self
+            .character_bounds
+            .get(start..end)
+            .and_then(CharacterBounds::union)
+        {
+            self.caret_x = clause.x;
+            self.caret_y = clause.y;
+            self.caret_height = clause.height;
+        }
+        self
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -59,6 +271,29 @@ pub struct DragDataInfo {
     pub file_names: Vec<String>,
 }
 
+/// Drag data of a drag started in the page, kept to write the dragged file
+/// out once the drag has started.
+///
+/// CEF drag data is reference counted and may be used on any thread.
+#[derive(Clone)]
+pub struct DragFileSource(pub cef::DragData);
+
+unsafe impl Send for DragFileSource {}
+
+impl std::fmt::Debug for DragFileSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("DragFileSource")
+    }
+}
+
+/// Files written out for a drag started in the page.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractedDragFiles {
+    pub paths: Vec<String>,
+    /// Size of the dragged file when it exceeded the size limit.
+    pub oversized: Option<u64>,
+}
+
 #[derive(Debug, Clone)]
 pub enum DragEvent {
     Started {
@@ -66,6 +301,8 @@ pub enum DragEvent {
         x: i32,
         y: i32,
         allowed_ops: u32,
+        /// Set when the drag carries files.
+        file_source: Option<DragFileSource>,
     },
     UpdateCursor {
         operation: u32,
@@ -74,6 +311,11 @@ pub enum DragEvent {
         drag_data: DragDataInfo,
         mask: u32,
     },
+    /// The files of drag `id` were written out.
+    FilesReady {
+        id: u64,
+        files: ExtractedDragFiles,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -100,46 +342,575 @@ pub struct DownloadUpdateEvent {
     pub is_canceled: bool,
 }
 
+/// A completed request served by one of the Godot scheme handlers.
+#[derive(Debug, Clone)]
+pub struct SchemeRequestEvent {
+    pub url: String,
+    pub method: String,
+    pub resolved_path: String,
+    pub status_code: i32,
+    pub bytes_served: u64,
+    pub duration_ms: f64,
+    pub is_range: bool,
+    pub is_multipart: bool,
+}
+
+/// Details of a server certificate, as reported to GDScript.
+#[derive(Debug, Clone, Default)]
+pub struct CertificateInfo {
+    pub subject: String,
+    pub issuer: String,
+    /// Start of the validity period, in seconds since the Unix epoch.
+    pub valid_from: i64,
+    /// End of the validity period, in seconds since the Unix epoch.
+    pub valid_until: i64,
+    /// SHA-256 fingerprint of the DER-encoded certificate.
+    pub fingerprint_sha256: String,
+}
+
+/// Connection security of the main frame after a navigation commits.
+#[derive(Debug, Clone)]
+pub struct SecurityStateEvent {
+    pub url: String,
+    /// `false` for pages that are not served over TLS (http://, res://, ...).
+    pub applicable: bool,
+    pub is_secure_connection: bool,
+    /// Raw `cef_cert_status_t` flags.
+    pub cert_status: u32,
+    pub certificate: Option<CertificateInfo>,
+}
+
+/// A certificate error waiting for `resolve_certificate_error`.
+#[derive(Debug, Clone)]
+pub struct CertificateErrorEvent {
+    pub error_id: i64,
+    pub url: String,
+    /// Raw `cef_cert_status_t` flags.
+    pub cert_status: u32,
+    /// The net error code reported by CEF (e.g. `ERR_CERT_DATE_INVALID`).
+    pub error_code: i32,
+    pub certificate: CertificateInfo,
+}
+
+/// A server asking for a client certificate, waiting for
+/// `select_client_certificate` or `decline_client_certificate`.
+#[derive(Debug, Clone)]
+pub struct ClientCertificateEvent {
+    pub request_id: i64,
+    pub host: String,
+    /// Metadata of the offered certificates, in the order their index
+    /// refers to.
+    pub certificates: Vec<CertificateInfo>,
+}
+
+/// A main-frame navigation cancelled by the navigation allowlist.
+#[derive(Debug, Clone)]
+pub struct NavigationBlockedEvent {
+    pub url: String,
+    /// `true` if the navigation was triggered by the user (e.g. a link click)
+    /// rather than by script.
+    pub user_gesture: bool,
+}
+
+/// Result of a DevTools protocol method sent with `send_devtools_message`.
+#[derive(Debug, Clone)]
+pub struct DevToolsResultEvent {
+    pub message_id: i32,
+    pub success: bool,
+    /// Raw JSON of the `result` object, or of the `error` object on failure.
+    pub result: String,
+}
+
+/// A cookie `import_cookies` could not set.
+#[derive(Debug, Clone, Default)]
+pub struct CookieImportFailure {
+    /// Position of the cookie in the snapshot, `-1` if the whole snapshot
+    /// was rejected.
+    pub index: i64,
+    pub name: String,
+    pub domain: String,
+    pub error: String,
+}
+
+/// Outcome of `import_cookies`, once every cookie was set or failed.
+#[derive(Debug, Clone, Default)]
+pub struct CookiesImportedEvent {
+    pub ok_count: i64,
+    pub failed: Vec<CookieImportFailure>,
+}
+
+/// A DevTools protocol event, sent for the domains the page enabled.
+#[derive(Debug, Clone)]
+pub struct DevToolsEvent {
+    pub method: String,
+    /// Raw JSON of the event parameters.
+    pub params: String,
+}
+
+/// The main frame's document painted its first content.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FirstPaintEvent {
+    pub url: String,
+    /// Time since the navigation started, in milliseconds.
+    pub time_ms: f64,
+}
+
+/// Result of `dump_dom_async` or `count_elements`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomQueryEvent {
+    /// HTML of the matched element, empty if nothing matched.
+    Dumped { selector: String, html: String },
+    /// Number of matched elements, -1 for an invalid selector.
+    Counted { selector: String, count: i32 },
+}
+
+/// A notification a page showed or closed with the `Notification` API.
+/// `tag` identifies the notification within its frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebNotificationEvent {
+    Shown {
+        frame_id: String,
+        title: String,
+        body: String,
+        icon_url: String,
+        tag: String,
+    },
+    Closed {
+        frame_id: String,
+        tag: String,
+    },
+}
+
+/// A page snapshot requested with `save_page` was written, or failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PageSavedEvent {
+    /// Path as given to `save_page`.
+    pub path: String,
+    pub ok: bool,
+}
+
+/// A page's `getDisplayMedia` call, or the end of one of its captures.
+/// `page_request_id` identifies the call within its frame.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DisplayMediaEvent {
+    Requested {
+        frame_id: String,
+        page_request_id: i32,
+    },
+    /// The page stopped the capture track.
+    Stopped {
+        frame_id: String,
+        page_request_id: i32,
+    },
+    /// The frame's document went away with its requests and captures.
+    FrameReleased { frame_id: String },
+}
+
+/// Scroll offset and scrollable range of the main frame, in CSS pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScrollPosition {
+    pub x: f64,
+    pub y: f64,
+    pub max_x: f64,
+    pub max_y: f64,
+}
+
+/// The renderer process of the main frame started or went away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RendererProcessEvent {
+    /// The process reported its pid.
+    Started(u32),
+    /// The process crashed or was killed.
+    Terminated,
+}
+
+/// An entry of the browser's navigation history.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HistoryEntry {
+    pub url: String,
+    pub title: String,
+}
+
 /// Consolidated event queues for browser-to-Godot communication.
 ///
 /// All UI-thread callbacks write to this single structure, which is then
 /// drained once per frame in `on_process`. This reduces lock overhead
 /// compared to having separate `Arc<Mutex<...>>` for each queue.
-#[derive(Default)]
+///
+/// Queues a page can fill are bounded, see [`crate::event_queue`]. Those
+/// holding callbacks that wait for a decision are not, as a dropped
+/// callback would leave its request hanging; they only grow with the
+/// requests CEF makes.
 pub struct EventQueues {
     /// IPC messages from the browser (string).
-    pub messages: VecDeque<String>,
+    pub messages: BoundedQueue<String>,
     /// Binary IPC messages from the browser.
-    pub binary_messages: VecDeque<Vec<u8>>,
+    pub binary_messages: BoundedQueue<Vec<u8>>,
     /// URL change notifications.
-    pub url_changes: VecDeque<String>,
+    pub url_changes: BoundedQueue<UrlChangeEvent>,
     /// Title change notifications.
-    pub title_changes: VecDeque<String>,
+    pub title_changes: BoundedQueue<String>,
     /// Loading state events.
-    pub loading_states: VecDeque<LoadingStateEvent>,
-    /// IME enable/disable requests.
-    pub ime_enables: VecDeque<bool>,
+    pub loading_states: BoundedQueue<LoadingStateEvent>,
+    /// Navigation state changes (loading, back/forward availability).
+    pub loading_state_changes: BoundedQueue<LoadingStateChangeEvent>,
+    /// IME enable requests with the kind of focused text field, or `None`
+    /// to disable IME.
+    pub ime_enables: BoundedQueue<Option<TextInputType>>,
     /// IME composition range (latest value wins).
     pub ime_composition_range: Option<ImeCompositionRange>,
+    /// Estimated progress of the current load, 0 to 1 (latest value wins).
+    pub loading_progress: Option<f64>,
+    /// Main-frame scroll position reported by the page (latest value wins).
+    pub scroll_position: Option<ScrollPosition>,
+    /// Renderer process of the main frame (latest value wins).
+    pub renderer_process: Option<RendererProcessEvent>,
     /// Console messages.
-    pub console_messages: VecDeque<ConsoleMessageEvent>,
+    pub console_messages: BoundedQueue<ConsoleMessageEvent>,
     /// Drag events.
-    pub drag_events: VecDeque<DragEvent>,
-    /// Download request events.
+    pub drag_events: BoundedQueue<DragEvent>,
+    /// Download request events. Unbounded, like the other requests waiting
+    /// for an answer: dropping one would leave its CEF callback pending.
     pub download_requests: VecDeque<DownloadRequestEvent>,
     /// Download update events.
-    pub download_updates: VecDeque<DownloadUpdateEvent>,
+    pub download_updates: BoundedQueue<DownloadUpdateEvent>,
+    /// Completed scheme handler requests (only when request logging is enabled).
+    pub scheme_requests: BoundedQueue<SchemeRequestEvent>,
+    /// Security state changes of the main frame.
+    pub security_states: BoundedQueue<SecurityStateEvent>,
+    /// Certificate errors waiting for a decision (unbounded).
+    pub certificate_errors: VecDeque<CertificateErrorEvent>,
+    /// Client certificate requests waiting for a selection (unbounded).
+    pub client_certificates: VecDeque<ClientCertificateEvent>,
+    /// Main-frame navigations blocked by the navigation allowlist.
+    pub navigation_blocked: BoundedQueue<NavigationBlockedEvent>,
+    /// External protocol URLs (mailto:, steam://, ...) the page tried to open.
+    pub external_protocols: BoundedQueue<String>,
+    /// Results of DevTools protocol methods.
+    pub devtools_results: BoundedQueue<DevToolsResultEvent>,
+    /// DevTools protocol events.
+    pub devtools_events: BoundedQueue<DevToolsEvent>,
+    /// Accessibility tree updates and location changes.
+    pub accessibility_events: BoundedQueue<AccessibilityEvent>,
+    /// Display capture requests and ends from `getDisplayMedia`.
+    pub display_media_events: BoundedQueue<DisplayMediaEvent>,
+    /// Page snapshots written by `save_page`.
+    pub pages_saved: BoundedQueue<PageSavedEvent>,
+    /// First contentful paints of main-frame documents.
+    pub first_paints: BoundedQueue<FirstPaintEvent>,
+    /// Results of `dump_dom_async` and `count_elements`.
+    pub dom_queries: BoundedQueue<DomQueryEvent>,
+    /// Notifications shown and closed by pages.
+    pub web_notifications: BoundedQueue<WebNotificationEvent>,
+    /// Actions of the shortcuts registered with `register_browser_shortcut`
+    /// that were pressed.
+    pub browser_shortcuts: BoundedQueue<String>,
+    /// Results of the `SystemInfo.getInfo` methods sent for
+    /// `get_browser_gpu_info_async` and the debug report.
+    pub browser_gpu_info: BoundedQueue<DevToolsResultEvent>,
+    /// Cookies collected by `export_cookies`.
+    pub cookies_exported: BoundedQueue<Vec<CookieRecord>>,
+    /// Outcomes of `import_cookies`.
+    pub cookies_imported: BoundedQueue<CookiesImportedEvent>,
+    /// Kinds of the events above, in arrival order.
+    pub event_order: BoundedQueue<EventKind>,
+    /// By kind, the number of records at the front of `event_order` whose
+    /// events were dropped, skipped by `drain_event_order`. Negative after
+    /// `event_order` itself evicted records of events still queued.
+    dropped_records: [isize; EventKind::ALL.len()],
 }
 
+/// Number of `VecDeque` queues in `EventQueues`.
+const UNBOUNDED_QUEUES: usize = 3;
+
 impl EventQueues {
-    pub fn new() -> Self {
-        Self::default()
+    pub fn new(limits: &QueueLimits) -> Self {
+        let mut queues = Self {
+            messages: BoundedQueue::for_category(limits, QueueCategory::Ipc),
+            binary_messages: BoundedQueue::for_category(limits, QueueCategory::Ipc),
+            url_changes: BoundedQueue::for_category(limits, QueueCategory::Navigation),
+            title_changes: BoundedQueue::for_category(limits, QueueCategory::Navigation),
+            loading_states: BoundedQueue::for_category(limits, QueueCategory::Loading),
+            loading_state_changes: BoundedQueue::for_category(limits, QueueCategory::Loading),
+            ime_enables: BoundedQueue::for_category(limits, QueueCategory::Events),
+            ime_composition_range: None,
+            loading_progress: None,
+            scroll_position: None,
+            renderer_process: None,
+            console_messages: BoundedQueue::for_category(limits, QueueCategory::Console),
+            drag_events: BoundedQueue::for_category(limits, QueueCategory::Events),
+            download_requests: VecDeque::new(),
+            download_updates: BoundedQueue::for_category(limits, QueueCategory::Events),
+            scheme_requests: BoundedQueue::for_category(limits, QueueCategory::Events),
+            security_states: BoundedQueue::for_category(limits, QueueCategory::Events),
+            certificate_errors: VecDeque::new(),
+            client_certificates: VecDeque::new(),
+            navigation_blocked: BoundedQueue::for_category(limits, QueueCategory::Events),
+            external_protocols: BoundedQueue::for_category(limits, QueueCategory::Events),
+            devtools_results: BoundedQueue::for_category(limits, QueueCategory::Events),
+            devtools_events: BoundedQueue::for_category(limits, QueueCategory::Events),
+            accessibility_events: BoundedQueue::for_category(limits, QueueCategory::Events),
+            display_media_events: BoundedQueue::for_category(limits, QueueCategory::Events),
+            pages_saved: BoundedQueue::for_category(limits, QueueCategory::Events),
+            first_paints: BoundedQueue::for_category(limits, QueueCategory::Events),
+            dom_queries: BoundedQueue::for_category(limits, QueueCategory::Events),
+            web_notifications: BoundedQueue::for_category(limits, QueueCategory::Events),
+            browser_shortcuts: BoundedQueue::for_category(limits, QueueCategory::Events),
+            browser_gpu_info: BoundedQueue::for_category(limits, QueueCategory::Events),
+            cookies_exported: BoundedQueue::for_category(limits, QueueCategory::Events),
+            cookies_imported: BoundedQueue::for_category(limits, QueueCategory::Events),
+            event_order: BoundedQueue::new(0, OverflowPolicy::DropOldest),
+            dropped_records: [0; EventKind::ALL.len()],
+        };
+        // Room to record every bounded event, and as many requests of each
+        // unbounded queue as an events queue holds. Requests beyond that are
+        // still emitted, after the recorded events.
+        let order_capacity = queues
+            .bounded_queues()
+            .iter()
+            .map(|queue| queue.capacity())
+            .sum::<usize>()
+            + UNBOUNDED_QUEUES * limits.capacity(QueueCategory::Events);
+        queues.event_order = BoundedQueue::new(order_capacity, OverflowPolicy::DropOldest);
+        queues
+    }
+
+    /// Records the arrival of an event of `kind` pushed with `outcome`.
+    pub fn record(&mut self, kind: EventKind, outcome: PushOutcome) {
+        match outcome {
+            PushOutcome::Queued => self.push_record(kind),
+            // The record of the dropped event is the oldest of its kind
+            PushOutcome::DroppedOldest => {
+                self.dropped_records[kind as usize] += 1;
+                self.push_record(kind);
+            }
+            // The replaced event keeps its place
+            PushOutcome::Coalesced | PushOutcome::Dropped => {}
+        }
+    }
+
+    fn push_record(&mut self, kind: EventKind) {
+        // Evict the oldest record here to keep the dropped counts exact: it is
+        // the oldest of its kind, so either the record of a dropped event or
+        // the record of the next event of its kind to be dropped
+        if self.event_order.len() == self.event_order.capacity()
+            && let Some(oldest) = self.event_order.pop_front()
+        {
+            self.dropped_records[oldest as usize] -= 1;
+        }
+        self.event_order.push_back(kind);
+    }
+
+    /// Drains the records of the queued events, in arrival order.
+    pub fn drain_event_order(&mut self) -> Vec<EventKind> {
+        let mut dropped = std::mem::take(&mut self.dropped_records);
+        self.event_order
+            .drain(..)
+            .filter(|&kind| {
+                let dropped = &mut dropped[kind as usize];
+                if *dropped > 0 {
+                    *dropped -= 1;
+                    return false;
+                }
+                true
+            })
+            .collect()
+    }
+
+    fn bounded_queues(&self) -> [&dyn QueueStats; 26] {
+        [
+            &self.messages,
+            &self.binary_messages,
+            &self.url_changes,
+            &self.title_changes,
+            &self.loading_states,
+            &self.loading_state_changes,
+            &self.ime_enables,
+            &self.console_messages,
+            &self.drag_events,
+            &self.download_updates,
+            &self.scheme_requests,
+            &self.security_states,
+            &self.navigation_blocked,
+            &self.external_protocols,
+            &self.devtools_results,
+            &self.devtools_events,
+            &self.accessibility_events,
+            &self.display_media_events,
+            &self.pages_saved,
+            &self.first_paints,
+            &self.dom_queries,
+            &self.web_notifications,
+            &self.browser_shortcuts,
+            &self.browser_gpu_info,
+            &self.cookies_exported,
+            &self.cookies_imported,
+        ]
+    }
+
+    /// Pending events, capacity and overflows of the queues of `category`.
+    pub fn backlog(&self, category: QueueCategory) -> QueueBacklog {
+        let mut backlog = QueueBacklog::default();
+        for queue in self
+            .bounded_queues()
+            .into_iter()
+            .filter(|queue| queue.category() == Some(category))
+        {
+            backlog.pending += queue.len();
+            backlog.capacity = queue.capacity();
+            backlog.overflowed += queue.overflowed();
+        }
+        backlog
+    }
+
+    /// Number of events waiting to be emitted.
+    pub fn pending_len(&self) -> usize {
+        self.messages.len()
+            + self.binary_messages.len()
+            + self.url_changes.len()
+            + self.title_changes.len()
+            + self.loading_states.len()
+            + self.loading_state_changes.len()
+            + self.ime_enables.len()
+            + self.console_messages.len()
+            + self.drag_events.len()
+            + self.download_requests.len()
+            + self.download_updates.len()
+            + self.scheme_requests.len()
+            + self.security_states.len()
+            + self.certificate_errors.len()
+            + self.client_certificates.len()
+            + self.navigation_blocked.len()
+            + self.external_protocols.len()
+            + self.devtools_results.len()
+            + self.devtools_events.len()
+            + self.accessibility_events.len()
+            + self.display_media_events.len()
+            + self.pages_saved.len()
+            + self.first_paints.len()
+            + self.dom_queries.len()
+            + self.web_notifications.len()
+            + self.browser_shortcuts.len()
+            + self.browser_gpu_info.len()
+            + self.cookies_exported.len()
+            + self.cookies_imported.len()
+    }
+
+    /// Releases the capacity kept by queues after they were drained. Bounded
+    /// queues keep the capacity they allocated up front.
+    pub fn shrink_to_fit(&mut self) {
+        self.messages.shrink_to_fit();
+        self.binary_messages.shrink_to_fit();
+        self.url_changes.shrink_to_fit();
+        self.title_changes.shrink_to_fit();
+        self.loading_states.shrink_to_fit();
+        self.loading_state_changes.shrink_to_fit();
+        self.ime_enables.shrink_to_fit();
+        self.console_messages.shrink_to_fit();
+        self.drag_events.shrink_to_fit();
+        self.download_requests.shrink_to_fit();
+        self.download_updates.shrink_to_fit();
+        self.scheme_requests.shrink_to_fit();
+        self.security_states.shrink_to_fit();
+        self.certificate_errors.shrink_to_fit();
+        self.client_certificates.shrink_to_fit();
+        self.navigation_blocked.shrink_to_fit();
+        self.external_protocols.shrink_to_fit();
+        self.devtools_results.shrink_to_fit();
+        self.devtools_events.shrink_to_fit();
+        self.accessibility_events.shrink_to_fit();
+        self.display_media_events.shrink_to_fit();
+        self.pages_saved.shrink_to_fit();
+        self.first_paints.shrink_to_fit();
+        self.dom_queries.shrink_to_fit();
+        self.web_notifications.shrink_to_fit();
+        self.browser_shortcuts.shrink_to_fit();
+        self.browser_gpu_info.shrink_to_fit();
+        self.cookies_exported.shrink_to_fit();
+        self.cookies_imported.shrink_to_fit();
+        self.event_order.shrink_to_fit();
+    }
+}
+
+/// Pending events, capacity and overflows of a category of queues.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct QueueBacklog {
+    pub pending: usize,
+    /// Capacity of each queue of the category.
+    pub capacity: usize,
+    /// Events dropped or coalesced since the browser was created.
+    pub overflowed: u64,
+}
+
+/// Size of a bounded queue, whatever its events.
+trait QueueStats {
+    fn len(&self) -> usize;
+    fn capacity(&self) -> usize;
+    fn overflowed(&self) -> u64;
+    fn category(&self) -> Option<QueueCategory>;
+}
+
+impl<T> QueueStats for BoundedQueue<T> {
+    fn len(&self) -> usize {
+        BoundedQueue::len(self)
+    }
+
+    fn capacity(&self) -> usize {
+        BoundedQueue::capacity(self)
+    }
+
+    fn overflowed(&self) -> u64 {
+        BoundedQueue::overflowed(self)
+    }
+
+    fn category(&self) -> Option<QueueCategory> {
+        BoundedQueue::category(self)
     }
 }
 
 /// Shared handle to consolidated event queues.
 pub type EventQueuesHandle = Arc<Mutex<EventQueues>>;
 
+/// Pending certificate error callbacks and remembered decisions.
+pub type CertificateDecisionsHandle = Arc<Mutex<CertificateDecisions<cef::Callback>>>;
+
+/// Pending client certificate requests with their offered certificates.
+pub type ClientCertificateRequestsHandle = Arc<
+    Mutex<ClientCertificateRequests<cef::SelectClientCertificateCallback, cef::X509Certificate>>,
+>;
+
+/// MHTML snapshots waiting for their `Page.captureSnapshot` result: the
+/// DevTools message id, with the path given to `save_page` and the file to
+/// write.
+pub type PageSavesHandle = Arc<Mutex<HashMap<i32, (String, PathBuf)>>>;
+
+/// DevTools message ids of the `SystemInfo.getInfo` methods waiting for
+/// their result, which the DevTools message observer queues apart from the
+/// results of `send_devtools_message`.
+pub type GpuInfoRequestsHandle = Arc<Mutex<HashSet<i32>>>;
+
+/// Navigation allowlist shared with the CEF UI thread.
+pub type NavigationAllowlistHandle = Arc<RwLock<NavigationAllowlist>>;
+
+/// Extra request headers shared with the CEF IO thread.
+pub type ExtraHeadersHandle = Arc<RwLock<ExtraHeaders>>;
+
+/// Shortcuts registered with `register_browser_shortcut`, read by the
+/// keyboard handler on the CEF UI thread.
+pub type BrowserShortcutsHandle = Arc<RwLock<crate::input::BrowserShortcuts>>;
+
+/// Position reported to pages instead of the OS location.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeolocationOverride {
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Accuracy radius in meters.
+    pub accuracy: f64,
+}
+
+/// Active geolocation override, shared with the permission handler.
+pub type GeolocationOverrideHandle = Arc<RwLock<Option<GeolocationOverride>>>;
+
 /// Audio parameters from CEF audio stream.
 #[derive(Clone, Debug)]
 #[allow(dead_code)]
@@ -185,6 +956,10 @@ pub struct DragState {
 pub enum RenderMode {
     /// Software rendering using a CPU frame buffer.
     Software {
+        /// Worker uploading frames into `texture`, unless uploads happen on
+        /// the main thread. Declared first so it stops before the texture
+        /// is freed.
+        uploader: Option<SoftwareUploader>,
         /// Shared frame buffer containing RGBA pixel data.
         frame_buffer: Arc<Mutex<FrameBuffer>>,
         /// Godot ImageTexture for display.
@@ -218,6 +993,8 @@ pub struct App {
     pub render_size: Option<Arc<Mutex<PhysicalSize<f32>>>>,
     /// Shared device scale factor for DPI awareness.
     pub device_scale_factor: Option<Arc<Mutex<f32>>>,
+    /// Shared scale factor of the screen the view is on.
+    pub display_scale: Option<Arc<Mutex<f32>>>,
     /// Shared cursor type from CEF.
     pub cursor_type: Option<Arc<Mutex<CursorType>>>,
     /// Shared popup state for <select> dropdowns.
@@ -235,4 +1012,171 @@ pub struct App {
     pub audio_sample_rate: Option<AudioSampleRateState>,
     /// Shutdown flag for audio handler to suppress errors during cleanup.
     pub audio_shutdown_flag: Option<AudioShutdownFlag>,
+    /// Certificate errors waiting for `resolve_certificate_error`.
+    pub certificate_decisions: Option<CertificateDecisionsHandle>,
+    /// Client certificate requests waiting for `select_client_certificate`.
+    pub client_certificate_requests: Option<ClientCertificateRequestsHandle>,
+    /// Allowlist for main-frame navigations. Lives as long as the node so it
+    /// can be configured before the browser is created.
+    pub navigation_allowlist: NavigationAllowlistHandle,
+    /// Geolocation override set with `set_geolocation_override`. Lives as
+    /// long as the node so it survives navigations and browser recreation.
+    pub geolocation_override: GeolocationOverrideHandle,
+    /// Headers set with `set_extra_headers` and `set_extra_headers_for_host`.
+    /// Lives as long as the node so it survives browser recreation.
+    pub extra_headers: ExtraHeadersHandle,
+    /// Shortcuts set with `register_browser_shortcut`. Lives as long as the
+    /// node so it survives browser recreation.
+    pub browser_shortcuts: BrowserShortcutsHandle,
+    /// MHTML snapshots requested with `save_page`, written by the DevTools
+    /// message observer.
+    pub page_saves: PageSavesHandle,
+    /// `SystemInfo.getInfo` methods waiting for their result.
+    pub gpu_info_requests: GpuInfoRequestsHandle,
+    /// Frame render statistics shared with the render handler.
+    pub render_stats: Option<RenderStatsHandle>,
+    /// Frames handed to Godot, for `frame_pacing`.
+    pub frame_pacer: Option<FramePacerHandle>,
+    /// Renderer group whose shared request context the browser uses; `Some`
+    /// while the browser holds it.
+    pub renderer_group: Option<String>,
+    /// Keeps the DevTools message observer registered while the browser lives.
+    pub devtools_registration: Option<cef::Registration>,
+    /// Whether the browser was created with audio capture.
+    pub audio_capture_enabled: bool,
+    /// Whether the browser was created to wait for external begin frames,
+    /// see `frame_scheduling`.
+    pub external_begin_frames: bool,
+    /// Pid of the renderer process of the main frame, once it reported it.
+    pub renderer_process_id: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bounds(x: i32, y: i32, width: i32, height: i32) -> CharacterBounds {
+        CharacterBounds {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_emission_order() {
+        use EventKind::*;
+        let counts = |kind| match kind {
+            UrlChange => 2,
+            LoadingState => 2,
+            Message => 1,
+            _ => 0,
+        };
+        // A second navigation's URL change stays after the first one's load
+        let recorded = [UrlChange, LoadingState, LoadingState, UrlChange, Message];
+        assert_eq!(emission_order(&recorded, counts), recorded);
+
+        // Unrecorded events follow in the default order; stale entries are
+        // skipped
+        assert_eq!(
+            emission_order(&[LoadingState, TitleChange], counts),
+            vec![LoadingState, Message, UrlChange, UrlChange, LoadingState]
+        );
+    }
+
+    #[test]
+    fn test_record_keeps_order_in_step_with_queues() {
+        let mut limits = QueueLimits::default();
+        limits.set_capacity(QueueCategory::Navigation, 2);
+        limits.set_capacity(QueueCategory::Ipc, 1);
+        let mut queues = EventQueues::new(&limits);
+
+        fn push_title(queues: &mut EventQueues, title: &str) {
+            let outcome = queues.title_changes.push_back(title.to_string());
+            queues.record(EventKind::TitleChange, outcome);
+        }
+        push_title(&mut queues, "1");
+        for message in ["m1", "m2"] {
+            let outcome = queues.messages.push_back(message.to_string());
+            queues.record(EventKind::Message, outcome);
+        }
+        push_title(&mut queues, "2");
+        // Drops title "1" and its record
+        push_title(&mut queues, "3");
+
+        assert_eq!(
+            queues.drain_event_order(),
+            vec![
+                EventKind::Message,
+                EventKind::TitleChange,
+                EventKind::TitleChange
+            ]
+        );
+        assert_eq!(
+            queues.title_changes.drain(..).collect::<Vec<_>>(),
+            vec!["2", "3"]
+        );
+        assert_eq!(queues.messages.drain(..).collect::<Vec<_>>(), vec!["m1"]);
+    }
+
+    #[test]
+    fn test_event_order_eviction_keeps_dropped_counts() {
+        let mut limits = QueueLimits::default();
+        for category in QueueCategory::ALL {
+            limits.set_capacity(category, 1);
+        }
+        let mut queues = EventQueues::new(&limits);
+        let order_capacity = queues.event_order.capacity();
+
+        let outcome = queues.external_protocols.push_back("mailto:a".to_string());
+        queues.record(EventKind::ExternalProtocol, outcome);
+        // Fills the order with dropped titles, then evicts the live record
+        // of "mailto:a"
+        for i in 0..order_capacity {
+            let outcome = queues.title_changes.push_back(i.to_string());
+            queues.record(EventKind::TitleChange, outcome);
+        }
+        // "mailto:a" is dropped after its record was evicted
+        let outcome = queues.external_protocols.push_back("mailto:b".to_string());
+        queues.record(EventKind::ExternalProtocol, outcome);
+
+        assert_eq!(
+            queues.drain_event_order(),
+            vec![EventKind::TitleChange, EventKind::ExternalProtocol]
+        );
+    }
+
+    #[test]
+    fn test_character_bounds_union() {
+        assert_eq!(CharacterBounds::union(&[]), None);
+        assert_eq!(
+            CharacterBounds::union(&[bounds(30, 10, 10, 20), bounds(10, 12, 10, 20)]),
+            Some(bounds(10, 10, 30, 22))
+        );
+    }
+
+    #[test]
+    fn test_ime_composition_range_from_character_bounds() {
+        assert!(ImeCompositionRange::from_character_bounds(Vec::new()).is_none());
+
+        // Right-to-left text ends at the left edge; the caret still spans it
+        let range = ImeCompositionRange::from_character_bounds(vec![
+            bounds(50, 10, 10, 20),
+            bounds(40, 10, 10, 20),
+            bounds(30, 10, 10, 20),
+        ])
+        .unwrap();
+        assert_eq!(
+            (range.caret_x, range.caret_y, range.caret_height),
+            (30, 10, 20)
+        );
+        assert!(!range.css_pixels);
+
+        let clause = range.anchored_to(1, 2);
+        assert_eq!((clause.caret_x, clause.caret_y), (40, 10));
+        // Out of range clauses keep the whole composition
+        let range = clause.anchored_to(2, 5);
+        assert_eq!(range.caret_x, 40);
+    }
 }
@@ -1,6 +1,9 @@
-use cef::Settings;
-use godot::classes::{Engine, Os};
+use cef::{RequestContextSettings, Settings};
+use godot::classes::{DisplayServer, Engine, Os, RenderingServer};
 use godot::prelude::*;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
 
 #[cfg(target_os = "macos")]
@@ -8,24 +11,49 @@ use crate::utils::get_framework_path;
 use crate::utils::get_subprocess_path;
 
 use crate::accelerated_osr::RenderBackend;
+use crate::browser::EventQueuesHandle;
 use crate::error::{CefError, CefResult};
-use crate::settings;
+use crate::{cef_log, cef_manager, fonts, godot_protocol, helper_check, settings, webrender};
 
 struct CefState {
-    ref_count: usize,
+    /// `CefTexture` nodes holding CEF, in the order they took it.
+    instances: Vec<InstanceId>,
     initialized: bool,
+    /// The CEF library was loaded and can be called.
+    framework_loaded: bool,
+    /// Set by `shutdown`; CEF cannot be initialized again in this process.
+    shut_down: bool,
 }
 
 static CEF_STATE: Mutex<CefState> = Mutex::new(CefState {
-    ref_count: 0,
+    instances: Vec::new(),
     initialized: false,
+    framework_loaded: cfg!(not(target_os = "macos")),
+    shut_down: false,
 });
 
-pub fn cef_retain() -> CefResult<()> {
+/// Takes a hold on CEF for the node `instance`, initializing CEF for the
+/// first one. Taking it again for the same node does nothing.
+pub fn cef_retain(instance: InstanceId) -> CefResult<()> {
     let mut state = CEF_STATE.lock().unwrap();
 
-    if state.ref_count == 0 {
+    if state.shut_down {
+        return Err(CefError::InitializationFailed(
+            "CEF was shut down by CefManager.shutdown()".to_string(),
+        ));
+    }
+    if state.instances.contains(&instance) {
+        return Ok(());
+    }
+
+    if state.instances.is_empty() {
+        if is_headless() {
+            return Err(CefError::InitializationFailed(
+                "Godot is running headless".to_string(),
+            ));
+        }
         load_cef_framework()?;
+        state.framework_loaded = true;
         cef::api_hash(cef::sys::CEF_API_VERSION_LAST, 0);
         initialize_cef()?;
         state.initialized = true;
@@ -33,21 +61,232 @@ pub fn cef_retain() -> CefResult<()> {
         settings::warn_if_insecure_settings();
     }
 
-    state.ref_count += 1;
+    state.instances.push(instance);
+    drop(state);
+    cef_manager::notify_instance_created(instance);
     Ok(())
 }
 
-pub fn cef_release() {
+/// Returns whether CEF has been initialized in this process.
+pub fn is_cef_initialized() -> bool {
+    CEF_STATE
+        .lock()
+        .map(|state| state.initialized)
+        .unwrap_or(false)
+}
+
+/// Returns whether Godot runs without a display or a real renderer, as with
+/// `--headless` and dedicated server exports. Off-screen rendering has
+/// nothing to draw into there.
+pub fn is_headless() -> bool {
+    let display_server = DisplayServer::singleton().get_name().to_string();
+    let rendering_driver = RenderingServer::singleton()
+        .get_current_rendering_driver_name()
+        .to_string();
+    is_headless_environment(&display_server, &rendering_driver)
+}
+
+/// `--headless` also selects the dummy rendering driver, which a server
+/// export can use with a regular display server.
+fn is_headless_environment(display_server: &str, rendering_driver: &str) -> bool {
+    display_server == "headless" || rendering_driver.is_empty() || rendering_driver == "dummy"
+}
+
+/// Returns whether the CEF library can be called. On macOS the framework is
+/// only loaded when the first node takes its hold on CEF.
+pub fn is_cef_framework_loaded() -> bool {
+    CEF_STATE
+        .lock()
+        .map(|state| state.framework_loaded)
+        .unwrap_or(false)
+}
+
+/// Returns whether `shutdown` has run, after which no browser can be created.
+pub fn is_shut_down() -> bool {
+    CEF_STATE
+        .lock()
+        .map(|state| state.shut_down)
+        .unwrap_or(false)
+}
+
+/// Returns the number of `CefTexture` nodes currently holding CEF.
+pub fn browser_count() -> usize {
+    CEF_STATE
+        .lock()
+        .map(|state| state.instances.len())
+        .unwrap_or(0)
+}
+
+/// Returns the `CefTexture` nodes currently holding CEF.
+pub fn instances() -> Vec<InstanceId> {
+    CEF_STATE
+        .lock()
+        .map(|state| state.instances.clone())
+        .unwrap_or_default()
+}
+
+/// Scheme handlers registered on a request context.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContextSchemes {
+    pub allow_res: bool,
+    pub allow_user: bool,
+    pub restrict_res_origin: bool,
+}
+
+/// Values shared by name and dropped when their last user releases them.
+struct SharedByName<T> {
+    entries: HashMap<String, (T, usize)>,
+}
+
+impl<T: Clone> SharedByName<T> {
+    fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Returns the value named `name`, creating it with `create` for the
+    /// first user.
+    fn acquire(&mut self, name: &str, create: impl FnOnce() -> Option<T>) -> Option<T> {
+        if let Some((value, users)) = self.entries.get_mut(name) {
+            *users += 1;
+            return Some(value.clone());
+        }
+        let value = create()?;
+        self.entries.insert(name.to_string(), (value.clone(), 1));
+        Some(value)
+    }
+
+    fn get(&self, name: &str) -> Option<&T> {
+        self.entries.get(name).map(|(value, _)| value)
+    }
+
+    fn release(&mut self, name: &str) {
+        if let Some((_, users)) = self.entries.get_mut(name) {
+            *users -= 1;
+            if *users == 0 {
+                self.entries.remove(name);
+            }
+        }
+    }
+}
+
+thread_local! {
+    /// Request contexts of the renderer groups. CEF objects stay on the main
+    /// thread, like the `CefTexture`s that use them.
+    static RENDERER_GROUPS: RefCell<SharedByName<(cef::RequestContext, ContextSchemes)>> =
+        RefCell::new(SharedByName::new());
+}
+
+fn create_request_context(
+    schemes: ContextSchemes,
+    request_log: Option<EventQueuesHandle>,
+) -> Option<cef::RequestContext> {
+    let mut context = cef::request_context_create_context(
+        Some(&RequestContextSettings::default()),
+        Some(&mut webrender::RequestContextHandlerImpl::build(
+            webrender::OsrRequestContextHandler {},
+        )),
+    );
+
+    // Register the enabled res://, user:// and custom scheme handlers on this specific request context
+    if let Some(ctx) = context.as_mut() {
+        if schemes.allow_res {
+            godot_protocol::register_res_scheme_handler_on_context(
+                ctx,
+                request_log.clone(),
+                schemes.restrict_res_origin,
+            );
+        }
+        if schemes.allow_user {
+            godot_protocol::register_user_scheme_handler_on_context(ctx, request_log.clone());
+        }
+        godot_protocol::register_custom_scheme_handlers_on_context(ctx, request_log);
+    }
+
+    context
+}
+
+/// Returns the request context for a new browser.
+///
+/// Without a `group`, every browser gets a context of its own. Browsers of
+/// the same renderer group share one context, created by the first of them,
+/// so Chromium can run their same-site pages in one renderer process. Every
+/// context acquired for a group must be given back with
+/// [`release_request_context`].
+pub fn acquire_request_context(
+    group: &str,
+    schemes: ContextSchemes,
+    request_log: Option<EventQueuesHandle>,
+) -> Option<cef::RequestContext> {
+    if group.is_empty() {
+        return create_request_context(schemes, request_log);
+    }
+
+    RENDERER_GROUPS.with_borrow_mut(|groups| {
+        if let Some((_, group_schemes)) = groups.get(group)
+            && *group_schemes != schemes
+        {
+            godot::global::godot_warn!(
+                "[CefInit] Renderer group '{}' keeps the scheme settings of its first browser",
+                group
+            );
+        }
+        // The context outlives the browser that creates it, so scheme
+        // requests are not logged to that browser's queues
+        groups
+            .acquire(group, || {
+                create_request_context(schemes, None).map(|context| (context, schemes))
+            })
+            .map(|(context, _)| context)
+    })
+}
+
+/// Gives back a context acquired for `group`; the last browser of the group
+/// releases the shared context.
+pub fn release_request_context(group: &str) {
+    if !group.is_empty() {
+        RENDERER_GROUPS.with_borrow_mut(|groups| groups.release(group));
+    }
+}
+
+/// Releases the hold of the node `instance` on CEF, shutting CEF down once
+/// no node holds it. Does nothing if the node holds none.
+pub fn cef_release(instance: InstanceId) {
     let mut state = CEF_STATE.lock().unwrap();
 
-    if state.ref_count == 0 {
+    let Some(index) = state.instances.iter().position(|id| *id == instance) else {
         return;
+    };
+    state.instances.remove(index);
+
+    if state.instances.is_empty() {
+        shutdown_cef(&mut state);
+        drop(state);
+        cef_manager::notify_all_browsers_closed();
     }
+}
 
-    state.ref_count -= 1;
+/// Shuts CEF down for good, once `CefManager.shutdown()` closed the
+/// browsers of every node.
+pub fn shutdown() {
+    let mut state = CEF_STATE.lock().unwrap();
+    state.shut_down = true;
+    // Nodes freed without releasing their hold must not keep CEF alive
+    state.instances.clear();
+    shutdown_cef(&mut state);
+}
 
-    if state.ref_count == 0 && state.initialized {
+fn shutdown_cef(state: &mut CefState) {
+    if state.initialized {
+        debug_assert_eq!(
+            crate::accelerated_osr::live_render_states(),
+            0,
+            "CEF shut down before every accelerated render state was torn down"
+        );
         cef::shutdown();
+        cef_log::stop_forwarding();
+        fonts::uninstall_bundled_fonts();
         state.initialized = false;
     }
 }
@@ -58,8 +297,8 @@ fn load_cef_framework() -> CefResult<()> {
     let framework_path = get_framework_path().map_err(|e| {
         CefError::FrameworkLoadFailed(format!("Failed to get CEF framework path: {}", e))
     })?;
-    cef_app::load_cef_framework_from_path(&framework_path);
-    Ok(())
+    cef_app::load_cef_framework_from_path(&framework_path)
+        .map_err(|e| CefError::FrameworkLoadFailed(format!("{} ({})", e, framework_path.display())))
 }
 
 #[cfg(not(target_os = "macos"))]
@@ -70,11 +309,12 @@ fn load_cef_framework() -> CefResult<()> {
 
 /// Loads the CEF sandbox (macOS-specific)
 #[cfg(target_os = "macos")]
-fn load_sandbox(args: &cef::MainArgs) {
-    match get_framework_path() {
-        Ok(framework_path) => cef_app::load_sandbox_from_path(&framework_path, args),
-        Err(e) => godot::global::godot_warn!("Failed to load CEF sandbox: {}", e),
-    }
+fn load_sandbox(args: &cef::MainArgs) -> CefResult<()> {
+    let framework_path = get_framework_path().map_err(|e| {
+        CefError::FrameworkLoadFailed(format!("Failed to get CEF framework path: {}", e))
+    })?;
+    cef_app::load_sandbox_from_path(&framework_path, args)
+        .map_err(|e| CefError::FrameworkLoadFailed(format!("Failed to load CEF sandbox: {}", e)))
 }
 
 fn detect_godot_render_backend() -> cef_app::GodotRenderBackend {
@@ -90,9 +330,10 @@ fn detect_godot_render_backend() -> cef_app::GodotRenderBackend {
 
 /// Determines if remote debugging should be enabled.
 ///
-/// Remote debugging is only enabled when:
-/// - Godot is compiled in debug mode (OS.is_debug_build() returns true), OR
-/// - The game is running from the Godot editor (Engine.is_editor_hint() returns true)
+/// Remote debugging is only enabled when Godot is compiled in debug mode
+/// (OS.is_debug_build() returns true), which includes games launched from
+/// the editor. The editor's own CEF, used for previews, leaves the port to
+/// the game.
 ///
 /// This is a security measure to prevent remote debugging in production builds.
 fn should_enable_remote_debugging() -> bool {
@@ -102,10 +343,231 @@ fn should_enable_remote_debugging() -> bool {
     let is_debug_build = os.is_debug_build();
     let is_editor_hint = engine.is_editor_hint();
 
-    is_debug_build || is_editor_hint
+    is_debug_build && !is_editor_hint
+}
+
+/// Collects the locale and environment the CEF subprocesses should share with
+/// the browser process.
+fn process_environment() -> cef_app::ProcessEnvironment {
+    let locale = Os::singleton().get_locale().to_string().replace('_', "-");
+    let forwarded = settings::get_forwarded_env_vars()
+        .into_iter()
+        .filter_map(|name| {
+            let value = std::env::var(&name).ok()?;
+            Some((name, value))
+        })
+        .collect();
+
+    cef_app::ProcessEnvironment {
+        locale: (!locale.is_empty()).then_some(locale),
+        fontconfig_path: std::env::var("FONTCONFIG_PATH").ok(),
+        time_zone: std::env::var("TZ").ok(),
+        forwarded,
+    }
+}
+
+/// Chromium switch that lets every page play media without a user gesture.
+const AUTOPLAY_SWITCH: &str = "autoplay-policy=no-user-gesture-required";
+
+/// Adds the autoplay switch to the custom switches when `allow_autoplay` is
+/// set, unless they already choose an autoplay policy.
+fn with_autoplay_switch(mut switches: Vec<String>, allow_autoplay: bool) -> Vec<String> {
+    let has_policy = switches.iter().any(|switch| {
+        switch
+            .trim_start_matches('-')
+            .starts_with("autoplay-policy")
+    });
+    if allow_autoplay && !has_policy {
+        switches.push(AUTOPLAY_SWITCH.to_string());
+    }
+    switches
+}
+
+/// Chromium switch that lets arrow keys move the focus between elements.
+const SPATIAL_NAVIGATION_SWITCH: &str = "enable-spatial-navigation";
+
+/// Adds the spatial navigation switch to the custom switches when enabled.
+fn with_spatial_navigation_switch(switches: Vec<String>, enabled: bool) -> Vec<String> {
+    with_switch(switches, SPATIAL_NAVIGATION_SWITCH, enabled)
+}
+
+/// Chromium switch that renders text with grayscale instead of subpixel
+/// (LCD) antialiasing.
+const DISABLE_LCD_TEXT_SWITCH: &str = "disable-lcd-text";
+
+/// Chromium switch that places glyphs on whole pixels.
+const DISABLE_FONT_SUBPIXEL_POSITIONING_SWITCH: &str = "disable-font-subpixel-positioning";
+
+/// Adds the text rendering switches chosen in the project settings.
+fn with_text_rendering_switches(switches: Vec<String>) -> Vec<String> {
+    let switches = with_switch(
+        switches,
+        DISABLE_LCD_TEXT_SWITCH,
+        settings::is_grayscale_antialiasing_forced(),
+    );
+    with_switch(
+        switches,
+        DISABLE_FONT_SUBPIXEL_POSITIONING_SWITCH,
+        settings::is_font_subpixel_positioning_disabled(),
+    )
+}
+
+/// Adds a switch without a value to the custom switches when `enabled`,
+/// unless they already contain it.
+fn with_switch(mut switches: Vec<String>, name: &str, enabled: bool) -> Vec<String> {
+    let present = switches
+        .iter()
+        .any(|switch| switch.trim_start_matches('-') == name);
+    if enabled && !present {
+        switches.push(name.to_string());
+    }
+    switches
+}
+
+/// Maps a value of the log severity setting to CEF's severity.
+fn log_severity(index: i64) -> cef::LogSeverity {
+    match index {
+        1 => cef::LogSeverity::VERBOSE,
+        2 => cef::LogSeverity::INFO,
+        3 => cef::LogSeverity::WARNING,
+        4 => cef::LogSeverity::ERROR,
+        5 => cef::LogSeverity::FATAL,
+        6 => cef::LogSeverity::DISABLE,
+        _ => cef::LogSeverity::DEFAULT,
+    }
+}
+
+/// Returns the data directory of this process: a subdirectory named after
+/// the process id when several instances must not share one.
+fn instance_data_path(path: PathBuf, append_process_id: bool, process_id: u32) -> PathBuf {
+    if append_process_id {
+        path.join(process_id.to_string())
+    } else {
+        path
+    }
+}
+
+/// Returns the data directory of the editor's previews, next to the game's
+/// so the two never lock the same directory.
+fn editor_data_path(path: PathBuf) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push("-editor");
+    path.with_file_name(name)
+}
+
+/// Creates `dir` if needed and checks that files can be created in it.
+fn ensure_writable_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let probe = dir.join(".godot_cef_write_test");
+    std::fs::File::create(&probe)?;
+    std::fs::remove_file(&probe)
+}
+
+/// Returns the configured data path, or the default one if the configured
+/// path cannot be written to.
+fn root_cache_path() -> PathBuf {
+    let append_process_id = settings::is_process_id_appended_to_data_path();
+    let is_editor = Engine::singleton().is_editor_hint();
+    let data_path = |path: PathBuf| {
+        let path = if is_editor {
+            editor_data_path(path)
+        } else {
+            path
+        };
+        instance_data_path(path, append_process_id, std::process::id())
+    };
+    let path = data_path(settings::get_data_path());
+    let Err(e) = ensure_writable_dir(&path) else {
+        return path;
+    };
+
+    let fallback = data_path(settings::get_default_data_path());
+    godot::global::godot_warn!(
+        "[CefInit] Data path '{}' is not writable ({}), using '{}' instead",
+        path.display(),
+        e,
+        fallback.display()
+    );
+    if let Err(e) = ensure_writable_dir(&fallback) {
+        godot::global::godot_warn!(
+            "[CefInit] Data path '{}' is not writable either: {}",
+            fallback.display(),
+            e
+        );
+    }
+    fallback
+}
+
+/// Returns where CEF logs, falling back to stderr if the log file's
+/// directory cannot be written to. Forwarding the log to the Godot output
+/// needs a file, so it logs to the default one when none is configured.
+fn log_output(severity: cef::LogSeverity) -> cef_app::LogOutput {
+    if matches!(severity, cef::LogSeverity::DISABLE) {
+        return cef_app::LogOutput::Disabled;
+    }
+    let log_file = settings::get_log_file()
+        .or_else(|| settings::is_log_forwarding_enabled().then(cef_log::prepare_default_log_file));
+    let Some(log_file) = log_file else {
+        return cef_app::LogOutput::Stderr;
+    };
+
+    let writable = log_file
+        .parent()
+        .map_or(Ok(()), ensure_writable_dir)
+        .and_then(|()| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&log_file)
+                .map(drop)
+        });
+    match (writable, log_file.to_str()) {
+        (Ok(()), Some(path)) => cef_app::LogOutput::File(path.to_string()),
+        (Ok(()), None) => {
+            godot::global::godot_warn!(
+                "[CefInit] Log file path '{}' is not valid UTF-8, logging to stderr",
+                log_file.display()
+            );
+            cef_app::LogOutput::Stderr
+        }
+        (Err(e), _) => {
+            godot::global::godot_warn!(
+                "[CefInit] Log file '{}' is not writable ({}), logging to stderr",
+                log_file.display(),
+                e
+            );
+            cef_app::LogOutput::Stderr
+        }
+    }
 }
 
 /// Initializes CEF with the given settings
+/// Returns the helper subprocess path after checking that the helper exists
+/// and is executable and, with `godot_cef/debug/helper_preflight_check`,
+/// that it runs. Without these checks a broken helper only shows as blank
+/// browsers.
+fn checked_subprocess_path() -> CefResult<PathBuf> {
+    let path = get_subprocess_path().map_err(|e| {
+        // The canonical path cannot be resolved for a missing helper
+        let expected = crate::utils::installation_files()
+            .ok()
+            .and_then(|files| files.into_iter().find(|(name, _)| *name == "helper"));
+        match expected {
+            Some((_, path)) => CefError::HelperUnavailable {
+                path,
+                reason: format!("could not be resolved: {}", e),
+            },
+            None => CefError::InitializationFailed(format!("Failed to get subprocess path: {}", e)),
+        }
+    })?;
+
+    helper_check::verify_helper(&path)?;
+    if settings::is_helper_preflight_enabled() {
+        helper_check::run_preflight(&path)?;
+    }
+    Ok(path)
+}
+
 fn initialize_cef() -> CefResult<()> {
     let args = cef::args::Args::new();
     let godot_backend = detect_godot_render_backend();
@@ -116,8 +578,32 @@ fn initialize_cef() -> CefResult<()> {
     let user_agent = settings::get_user_agent();
     let proxy_server = settings::get_proxy_server();
     let proxy_bypass_list = settings::get_proxy_bypass_list();
+    let local_ports = settings::get_secure_localhost_ports();
     let cache_size_mb = settings::get_cache_size_mb();
-    let custom_switches = settings::get_custom_switches();
+    let custom_switches = with_text_rendering_switches(with_spatial_navigation_switch(
+        with_autoplay_switch(
+            settings::get_custom_switches(),
+            settings::is_autoplay_allowed(),
+        ),
+        settings::is_spatial_navigation_enabled(),
+    ));
+    let custom_schemes = crate::godot_protocol::registered_custom_scheme_names();
+    let enforce_scheme_csp = settings::is_scheme_csp_enforced();
+    let ipc_binary_limit = settings::get_max_binary_message_size();
+    let web_notifications = settings::are_web_notifications_allowed();
+    let mut environment = process_environment();
+    // Registered before the subprocesses start enumerating fonts
+    environment.forwarded.extend(fonts::install_bundled_fonts());
+    let log_severity = log_severity(settings::get_log_severity());
+    let log_output = log_output(log_severity);
+    crate::godot_protocol::set_mime_overrides(settings::get_mime_overrides());
+    crate::godot_protocol::set_response_header_config(settings::get_response_header_config());
+    crate::godot_protocol::set_directory_index_requires_trailing_slash(
+        settings::is_directory_index_trailing_slash_required(),
+    );
+    crate::godot_protocol::set_reencode_imported_textures(
+        settings::are_imported_textures_reencoded(),
+    );
 
     #[allow(unused_mut)]
     let mut app_builder = cef_app::OsrApp::builder()
@@ -128,8 +614,15 @@ fn initialize_cef() -> CefResult<()> {
         .user_agent(user_agent)
         .proxy_server(proxy_server)
         .proxy_bypass_list(proxy_bypass_list)
+        .local_ports(local_ports)
         .cache_size_mb(cache_size_mb)
-        .custom_switches(custom_switches);
+        .custom_switches(custom_switches)
+        .custom_schemes(custom_schemes)
+        .enforce_scheme_csp(enforce_scheme_csp)
+        .ipc_binary_limit(ipc_binary_limit)
+        .web_notifications(web_notifications)
+        .environment(environment)
+        .log_output(log_output.clone());
 
     #[cfg(any(target_os = "windows", target_os = "linux", target_os = "macos"))]
     {
@@ -146,14 +639,19 @@ fn initialize_cef() -> CefResult<()> {
 
     let mut app = cef_app::AppBuilder::build(app_builder.build());
 
+    // Must happen before CEF reads crash_reporter.cfg in cef::initialize
+    crate::crash_reports::configure_crash_reporting();
+
     #[cfg(target_os = "macos")]
-    load_sandbox(args.as_main_args());
+    load_sandbox(args.as_main_args())?;
 
-    let subprocess_path = get_subprocess_path().map_err(|e| {
-        CefError::InitializationFailed(format!("Failed to get subprocess path: {}", e))
-    })?;
+    let subprocess_path = checked_subprocess_path()?;
 
-    let root_cache_path = settings::get_data_path();
+    let root_cache_path = root_cache_path();
+    let log_file = match &log_output {
+        cef_app::LogOutput::File(path) => path.as_str(),
+        _ => "",
+    };
 
     let settings = Settings {
         browser_subprocess_path: subprocess_path
@@ -164,7 +662,8 @@ fn initialize_cef() -> CefResult<()> {
             .into(),
         windowless_rendering_enabled: true as _,
         external_message_pump: true as _,
-        log_severity: cef::LogSeverity::DEFAULT as _,
+        log_severity: log_severity as _,
+        log_file: log_file.into(),
         root_cache_path: root_cache_path
             .to_str()
             .ok_or_else(|| {
@@ -211,6 +710,12 @@ fn initialize_cef() -> CefResult<()> {
         }
     };
 
+    if settings::is_log_forwarding_enabled()
+        && let cef_app::LogOutput::File(path) = &log_output
+    {
+        cef_log::start_forwarding(PathBuf::from(path));
+    }
+
     let ret = cef::initialize(
         Some(args.as_main_args()),
         Some(&settings),
@@ -219,6 +724,8 @@ fn initialize_cef() -> CefResult<()> {
     );
 
     if ret != 1 {
+        cef_log::stop_forwarding();
+        fonts::uninstall_bundled_fonts();
         return Err(CefError::InitializationFailed(
             "CEF initialization returned error code".to_string(),
         ));
@@ -226,3 +733,137 @@ fn initialize_cef() -> CefResult<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_by_name() {
+        let mut shared = SharedByName::new();
+        let mut created = 0;
+        let mut create = || {
+            created += 1;
+            Some(created)
+        };
+
+        assert_eq!(shared.acquire("panels", &mut create), Some(1));
+        assert_eq!(shared.acquire("panels", &mut create), Some(1));
+        assert_eq!(shared.acquire("hud", &mut create), Some(2));
+
+        shared.release("panels");
+        assert_eq!(shared.get("panels"), Some(&1));
+        shared.release("panels");
+        assert_eq!(shared.get("panels"), None);
+        assert_eq!(shared.acquire("panels", &mut create), Some(3));
+
+        // A failed creation leaves nothing behind
+        assert_eq!(shared.acquire("broken", || None), None);
+        assert_eq!(shared.get("broken"), None);
+        shared.release("broken");
+    }
+
+    #[test]
+    fn test_with_autoplay_switch() {
+        let switches = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            with_autoplay_switch(switches(&["--mute-audio"]), true),
+            switches(&["--mute-audio", AUTOPLAY_SWITCH])
+        );
+        assert_eq!(
+            with_autoplay_switch(switches(&["--mute-audio"]), false),
+            switches(&["--mute-audio"])
+        );
+        // A policy chosen in the custom switches wins
+        assert_eq!(
+            with_autoplay_switch(switches(&["--autoplay-policy=user-gesture-required"]), true),
+            switches(&["--autoplay-policy=user-gesture-required"])
+        );
+    }
+
+    #[test]
+    fn test_with_spatial_navigation_switch() {
+        let switches = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            with_spatial_navigation_switch(switches(&["--mute-audio"]), true),
+            switches(&["--mute-audio", SPATIAL_NAVIGATION_SWITCH])
+        );
+        assert_eq!(
+            with_spatial_navigation_switch(switches(&["--mute-audio"]), false),
+            switches(&["--mute-audio"])
+        );
+        assert_eq!(
+            with_spatial_navigation_switch(switches(&["--enable-spatial-navigation"]), true),
+            switches(&["--enable-spatial-navigation"])
+        );
+    }
+
+    #[test]
+    fn test_log_severity() {
+        assert!(matches!(log_severity(0), cef::LogSeverity::DEFAULT));
+        assert!(matches!(log_severity(1), cef::LogSeverity::VERBOSE));
+        assert!(matches!(log_severity(4), cef::LogSeverity::ERROR));
+        assert!(matches!(log_severity(6), cef::LogSeverity::DISABLE));
+        assert!(matches!(log_severity(42), cef::LogSeverity::DEFAULT));
+    }
+
+    #[test]
+    fn test_instance_data_path() {
+        let path = PathBuf::from("/data/cef");
+        assert_eq!(instance_data_path(path.clone(), false, 1234), path);
+        assert_eq!(
+            instance_data_path(path, true, 1234),
+            PathBuf::from("/data/cef/1234")
+        );
+    }
+
+    #[test]
+    fn test_editor_data_path() {
+        assert_eq!(
+            editor_data_path(PathBuf::from("/data/cef-data")),
+            PathBuf::from("/data/cef-data-editor")
+        );
+    }
+
+    #[test]
+    fn test_ensure_writable_dir() {
+        let dir = std::env::temp_dir()
+            .join(format!("godot-cef-test-{}", std::process::id()))
+            .join("nested");
+        ensure_writable_dir(&dir).unwrap();
+        assert!(dir.is_dir());
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        std::fs::remove_dir_all(dir.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_with_switch() {
+        let switches = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            with_switch(switches(&[]), DISABLE_LCD_TEXT_SWITCH, true),
+            switches(&[DISABLE_LCD_TEXT_SWITCH])
+        );
+        assert_eq!(
+            with_switch(
+                switches(&["--disable-lcd-text"]),
+                DISABLE_LCD_TEXT_SWITCH,
+                true
+            ),
+            switches(&["--disable-lcd-text"])
+        );
+        assert_eq!(
+            with_switch(switches(&[]), DISABLE_LCD_TEXT_SWITCH, false),
+            switches(&[])
+        );
+    }
+
+    #[test]
+    fn test_is_headless_environment() {
+        assert!(is_headless_environment("headless", "dummy"));
+        assert!(is_headless_environment("X11", "dummy"));
+        assert!(is_headless_environment("headless", "vulkan"));
+        assert!(is_headless_environment("Windows", ""));
+        assert!(!is_headless_environment("X11", "vulkan"));
+        assert!(!is_headless_environment("macOS", "metal"));
+    }
+}
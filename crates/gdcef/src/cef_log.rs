@@ -0,0 +1,354 @@
+//! Forwarding of CEF's log to the Godot output.
+//!
+//! With `godot_cef/logging/forward_to_output`, CEF logs to a file (under
+//! `user://godot_cef_logs/` unless `godot_cef/logging/log_file` names one)
+//! and a thread tails it: warnings and errors are printed with a `[CEF]`
+//! prefix, at most `MAX_FORWARDED_PER_SECOND` per second, and the last lines
+//! of every severity are kept for `CefTexture.get_recent_cef_logs`. The
+//! default file is rotated at startup, keeping the logs of the previous runs.
+
+use godot::classes::ProjectSettings;
+use godot::global::{godot_error, godot_warn};
+use godot::prelude::*;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const LOG_DIR: &str = "user://godot_cef_logs";
+const LOG_FILE_NAME: &str = "cef.log";
+
+/// Logs of previous runs kept next to the current one.
+const KEPT_LOGS: usize = 3;
+
+/// Time between two reads of the log file.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Lines kept for `get_recent_cef_logs`.
+const RECENT_LINES: usize = 1000;
+
+/// Lines printed per second before the rest are counted and skipped.
+const MAX_FORWARDED_PER_SECOND: usize = 20;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum LineSeverity {
+    Verbose,
+    Info,
+    Warning,
+    Error,
+    Fatal,
+}
+
+/// Reads the severity of a Chromium log line, e.g.
+/// `[1234:5678:0101/120000.000:WARNING:file.cc(42)] message`.
+fn parse_severity(line: &str) -> Option<LineSeverity> {
+    let prefix = line.strip_prefix('[')?.split_once(']')?.0;
+    prefix.split(':').find_map(|field| match field {
+        "INFO" => Some(LineSeverity::Info),
+        "WARNING" => Some(LineSeverity::Warning),
+        "ERROR" => Some(LineSeverity::Error),
+        "FATAL" => Some(LineSeverity::Fatal),
+        _ if field.starts_with("VERBOSE") => Some(LineSeverity::Verbose),
+        _ => None,
+    })
+}
+
+/// Limits printed lines to a number per second.
+struct RateLimiter {
+    window_start: Option<Instant>,
+    printed: usize,
+    skipped: usize,
+}
+
+impl RateLimiter {
+    fn new() -> Self {
+        Self {
+            window_start: None,
+            printed: 0,
+            skipped: 0,
+        }
+    }
+
+    /// Returns whether a line may be printed, and the number of lines
+    /// skipped in the previous second when this one starts a new second.
+    fn allow(&mut self, now: Instant) -> (bool, usize) {
+        let mut skipped = 0;
+        if self
+            .window_start
+            .is_none_or(|start| now.duration_since(start) >= Duration::from_secs(1))
+        {
+            self.window_start = Some(now);
+            self.printed = 0;
+            skipped = std::mem::take(&mut self.skipped);
+        }
+        if self.printed < MAX_FORWARDED_PER_SECOND {
+            self.printed += 1;
+            (true, skipped)
+        } else {
+            self.skipped += 1;
+            (false, skipped)
+        }
+    }
+}
+
+/// Reads the lines appended to a file since the last read.
+struct LogTail {
+    path: PathBuf,
+    offset: u64,
+    partial: Vec<u8>,
+}
+
+impl LogTail {
+    /// Starts at the current end of the file, so earlier runs appending to
+    /// the same file are not read again.
+    fn new(path: PathBuf) -> Self {
+        let offset = std::fs::metadata(&path).map_or(0, |metadata| metadata.len());
+        Self {
+            path,
+            offset,
+            partial: Vec::new(),
+        }
+    }
+
+    /// Returns the complete lines appended since the last call. The file is
+    /// opened again each time, and read from the start if it became shorter,
+    /// so a rotated or truncated file is followed.
+    fn read_lines(&mut self) -> io::Result<Vec<String>> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+        if file.metadata()?.len() < self.offset {
+            self.offset = 0;
+            self.partial.clear();
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut appended = Vec::new();
+        self.offset += file.read_to_end(&mut appended)? as u64;
+        self.partial.extend_from_slice(&appended);
+
+        let Some(end) = self.partial.iter().rposition(|&byte| byte == b'\n') else {
+            return Ok(Vec::new());
+        };
+        let complete: Vec<u8> = self.partial.drain(..=end).collect();
+        Ok(complete
+            .split(|&byte| byte == b'\n')
+            .map(|line| {
+                String::from_utf8_lossy(line)
+                    .trim_end_matches('\r')
+                    .to_string()
+            })
+            .filter(|line| !line.is_empty())
+            .collect())
+    }
+}
+
+/// Renames `cef.log` to `cef.1.log`, `cef.1.log` to `cef.2.log` and so on,
+/// removing the oldest.
+fn rotate_logs(dir: &Path) {
+    let name = |index: usize| match index {
+        0 => dir.join(LOG_FILE_NAME),
+        _ => dir.join(format!("cef.{}.log", index)),
+    };
+    let _ = std::fs::remove_file(name(KEPT_LOGS));
+    for index in (0..KEPT_LOGS).rev() {
+        let _ = std::fs::rename(name(index), name(index + 1));
+    }
+}
+
+/// Absolute path of the default log file, rotating the previous logs.
+pub fn prepare_default_log_file() -> PathBuf {
+    let dir: GString = LOG_DIR.into();
+    let dir = PathBuf::from(
+        ProjectSettings::singleton()
+            .globalize_path(&dir)
+            .to_string(),
+    );
+    rotate_logs(&dir);
+    dir.join(LOG_FILE_NAME)
+}
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+struct Forwarder {
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+static FORWARDER: Mutex<Option<Forwarder>> = Mutex::new(None);
+
+/// Starts printing the warnings and errors written to `path`.
+pub fn start_forwarding(path: PathBuf) {
+    let Ok(mut forwarder) = FORWARDER.lock() else {
+        return;
+    };
+    if forwarder.is_some() {
+        return;
+    }
+
+    let (stop, stopped) = mpsc::channel();
+    let mut tail = LogTail::new(path);
+    let spawned = std::thread::Builder::new()
+        .name("godot-cef-log".to_string())
+        .spawn(move || {
+            let mut limiter = RateLimiter::new();
+            loop {
+                let stopping = !matches!(
+                    stopped.recv_timeout(POLL_INTERVAL),
+                    Err(RecvTimeoutError::Timeout)
+                );
+                match tail.read_lines() {
+                    Ok(lines) => forward_lines(lines, &mut limiter),
+                    Err(e) => {
+                        godot_warn!("[CEF] Stopped reading the log file: {}", e);
+                        break;
+                    }
+                }
+                if stopping {
+                    break;
+                }
+            }
+        });
+
+    match spawned {
+        Ok(thread) => *forwarder = Some(Forwarder { stop, thread }),
+        Err(e) => godot_warn!("[CEF] Failed to start forwarding the log: {}", e),
+    }
+}
+
+/// Stops forwarding after reading what was written so far.
+pub fn stop_forwarding() {
+    let forwarder = FORWARDER
+        .lock()
+        .ok()
+        .and_then(|mut forwarder| forwarder.take());
+    if let Some(forwarder) = forwarder {
+        let _ = forwarder.stop.send(());
+        let _ = forwarder.thread.join();
+    }
+}
+
+fn forward_lines(lines: Vec<String>, limiter: &mut RateLimiter) {
+    for line in lines {
+        let severity = parse_severity(&line);
+        if severity >= Some(LineSeverity::Warning) {
+            let (allowed, skipped) = limiter.allow(Instant::now());
+            if skipped > 0 {
+                godot_warn!("[CEF] {} log lines skipped", skipped);
+            }
+            if allowed {
+                if severity == Some(LineSeverity::Warning) {
+                    godot_warn!("[CEF] {}", line);
+                } else {
+                    godot_error!("[CEF] {}", line);
+                }
+            }
+        }
+
+        if let Ok(mut recent) = RECENT.lock() {
+            if recent.len() == RECENT_LINES {
+                recent.pop_front();
+            }
+            recent.push_back(line);
+        }
+    }
+}
+
+/// Returns up to `max_lines` of the last lines read from the log, oldest
+/// first.
+pub fn recent_lines(max_lines: usize) -> Vec<String> {
+    let Ok(recent) = RECENT.lock() else {
+        return Vec::new();
+    };
+    let skip = recent.len().saturating_sub(max_lines);
+    recent.iter().skip(skip).cloned().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_parse_severity() {
+        assert_eq!(
+            parse_severity("[1234:5678:0101/120000.000:WARNING:file.cc(42)] message"),
+            Some(LineSeverity::Warning)
+        );
+        assert_eq!(
+            parse_severity("[0101/120000.000:ERROR:gpu_init.cc(9)] GPU: failed"),
+            Some(LineSeverity::Error)
+        );
+        assert_eq!(
+            parse_severity("[1:2:0101/120000.000:VERBOSE1:file.cc(3)] detail"),
+            Some(LineSeverity::Verbose)
+        );
+        assert_eq!(parse_severity("continuation of a message"), None);
+        assert_eq!(parse_severity("[not a log prefix] ERROR"), None);
+    }
+
+    #[test]
+    fn test_rate_limiter() {
+        let mut limiter = RateLimiter::new();
+        let start = Instant::now();
+        for _ in 0..MAX_FORWARDED_PER_SECOND {
+            assert_eq!(limiter.allow(start), (true, 0));
+        }
+        assert_eq!(limiter.allow(start), (false, 0));
+        assert_eq!(
+            limiter.allow(start + Duration::from_millis(900)),
+            (false, 0)
+        );
+        assert_eq!(limiter.allow(start + Duration::from_secs(1)), (true, 2));
+        assert_eq!(limiter.allow(start + Duration::from_secs(1)), (true, 0));
+    }
+
+    #[test]
+    fn test_log_tail() {
+        let dir = std::env::temp_dir().join(format!("godot-cef-log-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(LOG_FILE_NAME);
+        std::fs::write(&path, "earlier run\n").unwrap();
+
+        let mut tail = LogTail::new(path.clone());
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(b"first\r\nsecond\nthi").unwrap();
+        assert_eq!(tail.read_lines().unwrap(), ["first", "second"]);
+        file.write_all(b"rd\n").unwrap();
+        assert_eq!(tail.read_lines().unwrap(), ["third"]);
+
+        // Rotation leaves a new, shorter file behind
+        std::fs::write(&path, "new\n").unwrap();
+        assert_eq!(tail.read_lines().unwrap(), ["new"]);
+        std::fs::remove_file(&path).unwrap();
+        assert!(tail.read_lines().unwrap().is_empty());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_rotate_logs() {
+        let dir = std::env::temp_dir().join(format!("godot-cef-rotate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        for run in 0..=KEPT_LOGS + 1 {
+            rotate_logs(&dir);
+            std::fs::write(dir.join(LOG_FILE_NAME), run.to_string()).unwrap();
+        }
+
+        let read = |name: &str| std::fs::read_to_string(dir.join(name)).unwrap();
+        assert_eq!(read("cef.log"), (KEPT_LOGS + 1).to_string());
+        assert_eq!(read("cef.1.log"), KEPT_LOGS.to_string());
+        assert_eq!(read("cef.3.log"), "1");
+        assert!(!dir.join("cef.4.log").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,144 @@
+//! `CefManager`: the engine singleton for operations that concern every
+//! `CefTexture` rather than a single one.
+//!
+//! It is registered with the engine at the Scene stage, so GDScript reaches
+//! it as `CefManager` without an autoload. Nodes register with it when they
+//! take their hold on CEF and leave it when they release it, see
+//! `cef_init::cef_retain`.
+
+use godot::classes::Engine;
+use godot::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::cef_init;
+use crate::cef_texture::CefTexture;
+
+const SINGLETON_NAME: &str = "CefManager";
+
+static GLOBAL_MUTE: AtomicBool = AtomicBool::new(false);
+
+/// Returns whether `set_global_mute` muted every browser.
+pub fn is_globally_muted() -> bool {
+    GLOBAL_MUTE.load(Ordering::Relaxed)
+}
+
+#[derive(GodotClass)]
+#[class(tool, no_init, base=Object)]
+pub struct CefManager {
+    base: Base<Object>,
+}
+
+#[godot_api]
+impl CefManager {
+    #[signal]
+    fn instance_created(texture: Gd<CefTexture>);
+
+    #[signal]
+    fn all_browsers_closed();
+
+    /// Returns the number of `CefTexture` nodes holding a browser.
+    #[func]
+    pub fn get_active_browser_count(&self) -> i64 {
+        cef_init::browser_count() as i64
+    }
+
+    /// Returns the `CefTexture` nodes holding a browser, in creation order.
+    #[func]
+    pub fn get_all_instances(&self) -> Array<Gd<CefTexture>> {
+        live_instances().collect()
+    }
+
+    /// Closes every browser and shuts CEF down ahead of quitting, e.g.
+    /// before hot-reloading the extension. CEF cannot be initialized again
+    /// afterwards, so `CefTexture` nodes stay empty. Call it deferred from
+    /// `CefTexture` signal handlers.
+    #[func]
+    pub fn shutdown(&mut self) {
+        for mut texture in live_instances() {
+            texture.bind_mut().shut_down();
+        }
+        cef_init::shutdown();
+    }
+
+    /// Mutes or unmutes every browser, including those created later.
+    /// Browsers muted with `set_audio_muted` before stay muted.
+    #[func]
+    pub fn set_global_mute(&mut self, muted: bool) {
+        GLOBAL_MUTE.store(muted, Ordering::Relaxed);
+    }
+
+    #[func]
+    pub fn is_global_mute(&self) -> bool {
+        is_globally_muted()
+    }
+
+    /// Returns the versions of CEF (`cef`) and Chromium (`chromium`) in use.
+    /// On macOS the dictionary is empty until the first `CefTexture` loaded
+    /// the CEF framework.
+    #[func]
+    pub fn get_cef_version(&self) -> VarDictionary {
+        let mut versions = VarDictionary::new();
+        if !cef_init::is_cef_framework_loaded() {
+            return versions;
+        }
+        let cef = format!(
+            "{}.{}.{}",
+            cef::sys::CEF_VERSION_MAJOR,
+            cef::sys::CEF_VERSION_MINOR,
+            cef::sys::CEF_VERSION_PATCH
+        );
+        let chromium = format!(
+            "{}.{}.{}.{}",
+            cef::sys::CHROME_VERSION_MAJOR,
+            cef::sys::CHROME_VERSION_MINOR,
+            cef::sys::CHROME_VERSION_BUILD,
+            cef::sys::CHROME_VERSION_PATCH
+        );
+        versions.set("cef", cef);
+        versions.set("chromium", chromium);
+        versions
+    }
+}
+
+fn live_instances() -> impl Iterator<Item = Gd<CefTexture>> {
+    cef_init::instances()
+        .into_iter()
+        .filter_map(|id| Gd::<CefTexture>::try_from_instance_id(id).ok())
+}
+
+/// Registers the singleton with the engine.
+pub fn register_singleton() {
+    let manager = Gd::from_init_fn(|base| CefManager { base });
+    Engine::singleton().register_singleton(SINGLETON_NAME, &manager.upcast::<Object>());
+}
+
+/// Unregisters and frees the singleton.
+pub fn unregister_singleton() {
+    let mut engine = Engine::singleton();
+    if let Some(manager) = engine.get_singleton(SINGLETON_NAME) {
+        engine.unregister_singleton(SINGLETON_NAME);
+        manager.free();
+    }
+}
+
+/// Emits a signal of the singleton once the caller returned, so handlers
+/// may use the node that triggered it.
+fn emit_deferred(signal: &str, args: &[Variant]) {
+    if let Some(mut manager) = Engine::singleton().get_singleton(SINGLETON_NAME) {
+        let mut call_args = vec![signal.to_variant()];
+        call_args.extend_from_slice(args);
+        manager.call_deferred("emit_signal", &call_args);
+    }
+}
+
+/// Emits `instance_created` for a node that took its hold on CEF.
+pub fn notify_instance_created(instance: InstanceId) {
+    if let Ok(texture) = Gd::<CefTexture>::try_from_instance_id(instance) {
+        emit_deferred("instance_created", &[texture.to_variant()]);
+    }
+}
+
+/// Emits `all_browsers_closed` once the last node released CEF.
+pub fn notify_all_browsers_closed() {
+    emit_deferred("all_browsers_closed", &[]);
+}
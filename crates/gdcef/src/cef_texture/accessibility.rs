@@ -0,0 +1,134 @@
+//! Accessibility tree access for CefTexture.
+//!
+//! Tree updates drained from the event queues are applied to the node's
+//! `AccessibilityTree`, and only the nodes they touched are emitted. Nodes
+//! reach GDScript as dictionaries with their bounds in local coordinates, so
+//! a narrator or focus highlight can draw over the node directly.
+
+use super::CefTexture;
+use cef::{ImplBrowser, ImplBrowserHost};
+use godot::prelude::*;
+
+use crate::accessibility::{AccessibilityEvent, AxBounds, AxChanges};
+
+fn local_rect(bounds: AxBounds, scale: f32) -> Rect2 {
+    Rect2::new(
+        Vector2::new(bounds.x, bounds.y) * scale,
+        Vector2::new(bounds.width, bounds.height) * scale,
+    )
+}
+
+impl CefTexture {
+    /// Turns on Chromium's accessibility tree when the project enables it.
+    pub(super) fn enable_accessibility(&mut self) {
+        if !crate::settings::is_accessibility_enabled() {
+            return;
+        }
+        if let Some(browser) = self.app.browser.as_ref()
+            && let Some(host) = browser.host()
+        {
+            host.set_accessibility_state(cef::State::from(cef::sys::cef_state_t::STATE_ENABLED));
+        }
+    }
+
+    /// Scale from view coordinates to local coordinates, the inverse of the
+    /// one applied to mouse positions.
+    fn view_to_local_scale(&self) -> f32 {
+        self.get_device_scale_factor() / self.get_pixel_scale_factor()
+    }
+
+    fn accessibility_node(&self, id: i32, scale: f32) -> Option<VarDictionary> {
+        let tree = &self.accessibility_tree;
+        let node = tree.node(id)?;
+        let bounds = tree.bounds(id).unwrap_or_default();
+        Some(vdict! {
+            "id": id as i64,
+            "parent_id": tree.parent_id(id).map_or(-1, i64::from),
+            "role": GString::from(&node.role),
+            "name": GString::from(&node.name),
+            "value": GString::from(&node.value),
+            "bounds": local_rect(bounds, scale),
+            "focused": tree.focus_id() == Some(id),
+        })
+    }
+
+    pub(super) fn accessibility_tree_dictionary(&self) -> VarDictionary {
+        let tree = &self.accessibility_tree;
+        let scale = self.view_to_local_scale();
+        let mut nodes = Array::<VarDictionary>::new();
+        for (id, depth) in tree.flatten() {
+            if let Some(mut node) = self.accessibility_node(id, scale) {
+                node.set("depth", depth as i64);
+                nodes.push(&node);
+            }
+        }
+        vdict! {
+            "root_id": tree.root_id().map_or(-1, i64::from),
+            "focus_id": tree.focus_id().map_or(-1, i64::from),
+            "nodes": nodes,
+            "truncated": tree.is_truncated(),
+        }
+    }
+
+    pub(super) fn process_accessibility_events(&mut self, events: &[AccessibilityEvent]) {
+        if events.is_empty() {
+            return;
+        }
+        let mut changes = AxChanges::default();
+        for event in events {
+            self.accessibility_tree.apply(event, &mut changes);
+        }
+        if changes.is_empty() {
+            return;
+        }
+
+        let scale = self.view_to_local_scale();
+        let mut updated = Array::<VarDictionary>::new();
+        for &id in &changes.updated {
+            if let Some(node) = self.accessibility_node(id, scale) {
+                updated.push(&node);
+            }
+        }
+        let removed: PackedInt32Array = changes.removed.iter().copied().collect();
+        if !updated.is_empty() || !removed.is_empty() {
+            self.emit_browser_signal(
+                "accessibility_tree_changed",
+                &[
+                    ("updated", updated.to_variant()),
+                    ("removed", removed.to_variant()),
+                ],
+            );
+        }
+
+        if changes.focus_changed {
+            let node = self
+                .accessibility_tree
+                .focus_id()
+                .and_then(|id| self.accessibility_node(id, scale))
+                .unwrap_or_default();
+            self.emit_browser_signal(
+                "accessibility_focus_changed",
+                &[("node", node.to_variant())],
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_rect() {
+        let bounds = AxBounds {
+            x: 10.0,
+            y: 20.0,
+            width: 30.0,
+            height: 40.0,
+        };
+        assert_eq!(
+            local_rect(bounds, 2.0),
+            Rect2::new(Vector2::new(20.0, 40.0), Vector2::new(60.0, 80.0))
+        );
+    }
+}
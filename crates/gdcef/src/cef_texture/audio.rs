@@ -0,0 +1,55 @@
+//! Per-browser choice of audio capture.
+//!
+//! With capture, CEF hands the audio of the pages to the node instead of
+//! playing it, for `create_audio_stream` and `push_audio_to_playback`. The
+//! choice is made when the browser is created, as CEF only asks for the
+//! audio handler then.
+
+use super::CefTexture;
+use godot::prelude::*;
+
+/// Whether a browser captures its audio.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum AudioCapture {
+    /// Follow `godot_cef/audio/enable_audio_capture`.
+    #[default]
+    ProjectDefault,
+    ForceOn,
+    ForceOff,
+}
+
+impl AudioCapture {
+    fn resolve(self, project_default: bool) -> bool {
+        match self {
+            Self::ProjectDefault => project_default,
+            Self::ForceOn => true,
+            Self::ForceOff => false,
+        }
+    }
+}
+
+impl CefTexture {
+    /// Returns whether the browser captures its audio: as created if it
+    /// exists, otherwise as it would be created now.
+    pub(super) fn effective_audio_capture(&self) -> bool {
+        if self.app.browser.is_some() {
+            return self.app.audio_capture_enabled;
+        }
+        self.audio_capture
+            .resolve(crate::settings::is_audio_capture_enabled())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        assert!(AudioCapture::ProjectDefault.resolve(true));
+        assert!(!AudioCapture::ProjectDefault.resolve(false));
+        assert!(AudioCapture::ForceOn.resolve(false));
+        assert!(!AudioCapture::ForceOff.resolve(true));
+    }
+}
@@ -1,16 +1,22 @@
 use super::CefTexture;
-use cef::{BrowserSettings, ImplBrowser, ImplBrowserHost, RequestContextSettings, WindowInfo};
+use cef::{
+    BrowserSettings, ImplBrowser, ImplBrowserHost, ImplCallback,
+    ImplSelectClientCertificateCallback, WindowInfo,
+};
 use cef_app::PhysicalSize;
 use godot::classes::{AudioServer, ImageTexture};
 use godot::prelude::*;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use crate::accelerated_osr::{
     self, AcceleratedRenderState, GodotTextureImporter, PlatformAcceleratedRenderHandler,
 };
 use crate::browser::{PopupStateQueue, RenderMode};
 use crate::error::CefError;
-use crate::{godot_protocol, render, webrender};
+use crate::input::ClickCounter;
+use crate::resize_debounce::ResizeDebouncer;
+use crate::{cef_init, render, webrender};
 
 fn get_godot_audio_sample_rate() -> i32 {
     AudioServer::singleton().get_mix_rate() as i32
@@ -27,50 +33,137 @@ fn color_to_cef_color(color: Color) -> u32 {
 impl CefTexture {
     pub(super) fn cleanup_instance(&mut self) {
         if self.app.browser.is_none() {
-            crate::cef_init::cef_release();
+            self.release_cef();
             return;
         }
 
+        // Hide the TextureRect and clear its texture BEFORE freeing resources.
+        // This prevents Godot from trying to render with an invalid texture during shutdown.
+        self.base_mut().set_visible(false);
+
+        self.cancel_pending_ime_composition();
+        self.release_browser();
+
+        self.ime_active = false;
+        self.ime_proxy = None;
+
+        if let Some(mut overlay) = self.popup_overlay.take() {
+            overlay.queue_free();
+        }
+        self.popup_texture = None;
+        self.release_debug_overlay();
+        self.release_gamepad_navigation();
+
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+        {
+            self.popup_texture_2d_rd = None;
+        }
+
+        self.release_cef();
+    }
+
+    /// Closes the browser for `CefManager.shutdown()` and keeps the node from
+    /// creating another one.
+    pub(crate) fn shut_down(&mut self) {
+        self.cleanup_instance();
+        self.disabled = true;
+        self.base_mut().set_process(false);
+    }
+
+    /// Releases this node's hold on CEF and re-evaluates the helper process
+    /// priority, which depends on how many browsers are left.
+    pub(super) fn release_cef(&mut self) {
+        crate::cef_init::cef_release(self.base().instance_id());
+        if std::mem::take(&mut self.background_mode) {
+            crate::helper_priority::leave_background();
+        } else {
+            crate::helper_priority::refresh();
+        }
+    }
+
+    /// Closes the browser and frees its rendering resources, leaving the node
+    /// ready for `create_browser` again.
+    pub(super) fn release_browser(&mut self) {
+        self.release_dev_tools_front_end();
+        self.release_alpha_mask();
+        self.release_display_media();
+        self.release_user_scripts();
+        self.release_user_gesture_evals();
+        self.release_web_notifications();
+        self.release_browser_gpu_info();
+        self.reset_idle_state();
+        self.global_mute = false;
+        self.muted_by_global_mute = false;
+
         // Signal audio handler that we're shutting down to suppress "socket closed" errors
         if let Some(ref shutdown_flag) = self.app.audio_shutdown_flag {
             use std::sync::atomic::Ordering;
             shutdown_flag.store(true, Ordering::Relaxed);
         }
 
-        // Hide the TextureRect and clear its texture BEFORE freeing resources.
-        // This prevents Godot from trying to render with an invalid texture during shutdown.
-        self.base_mut().set_visible(false);
-
+        // Accelerated resources go first, in a fixed order: CEF stops
+        // painting, the importer and the RD textures are freed while the
+        // RenderingDevice is alive, and only then is the browser closed
         #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
         if let Some(RenderMode::Accelerated {
             render_state,
             texture_2d_rd,
         }) = &mut self.app.render_mode
         {
+            self.app.external_begin_frames = false;
+            if let Some(host) = self.app.browser.as_ref().and_then(|browser| browser.host()) {
+                host.was_hidden(true as _);
+            }
             // Clear the RD texture RID from the Texture2Drd to break the reference
             // before we free the underlying RD texture.
             texture_2d_rd.set_texture_rd_rid(Rid::Invalid);
             if let Some(popup_texture_2d_rd) = &mut self.popup_texture_2d_rd {
                 popup_texture_2d_rd.set_texture_rd_rid(Rid::Invalid);
             }
-            if let Ok(mut state) = render_state.lock() {
-                render::free_rd_texture(state.dst_rd_rid);
-                // Also free popup texture RID if it exists
-                if let Some(popup_rid) = state.popup_rd_rid.take() {
-                    render::free_rd_texture(popup_rid);
-                }
+            // A poisoned lock must not leave the resources behind
+            let mut state = render_state
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            state.teardown();
+            if let Some((_, displayed_rid)) = self.resized_texture.take() {
+                render::free_rd_texture(displayed_rid);
+            }
+        }
+
+        // Deny certificate errors that were never resolved
+        if let Some(decisions) = self.app.certificate_decisions.take()
+            && let Ok(mut decisions) = decisions.lock()
+        {
+            for callback in decisions.take_pending() {
+                callback.cancel();
+            }
+        }
+        if let Some(requests) = self.app.client_certificate_requests.take()
+            && let Ok(mut requests) = requests.lock()
+        {
+            for callback in requests.take_pending() {
+                callback.select(None);
             }
         }
 
+        // Snapshots still being captured never get their result
+        if let Ok(mut page_saves) = self.app.page_saves.lock() {
+            page_saves.clear();
+        }
+        self.app.devtools_registration = None;
         if let Some(browser) = self.app.browser.take()
             && let Some(host) = browser.host()
         {
             host.close_browser(true as _);
         }
+        if let Some(group) = self.app.renderer_group.take() {
+            cef_init::release_request_context(&group);
+        }
 
         self.app.render_mode = None;
         self.app.render_size = None;
         self.app.device_scale_factor = None;
+        self.app.display_scale = None;
         self.app.cursor_type = None;
         self.app.popup_state = None;
         self.app.event_queues = None;
@@ -79,21 +172,59 @@ impl CefTexture {
         self.app.audio_params = None;
         self.app.audio_sample_rate = None;
         self.app.audio_shutdown_flag = None;
+        self.app.render_stats = None;
+        self.app.frame_pacer = None;
+        self.app.renderer_process_id = None;
+        self.renderer_startup_deadline = None;
+        self.native_drag = Default::default();
+        self.cleanup_drag_files();
+        self.cancel_load_timeout();
+        self.abort_paginated_capture("the browser was released", false);
+        self.app.audio_capture_enabled = false;
+        self.app.external_begin_frames = false;
+        self.accessibility_tree.clear();
+    }
 
-        self.ime_active = false;
-        self.ime_proxy = None;
+    /// Recreates the browser with software rendering at its current URL,
+    /// after accelerated rendering could not recover from a GPU device loss.
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    pub(super) fn fall_back_to_software_rendering(&mut self) {
+        godot::global::godot_warn!(
+            "[CefTexture] GPU device did not recover, falling back to software rendering"
+        );
+        self.url = self.get_url_property();
+        self.force_software_rendering = true;
+        self.release_browser();
+        self.popup_texture_2d_rd = None;
+        self.create_browser();
+    }
 
-        if let Some(mut overlay) = self.popup_overlay.take() {
-            overlay.queue_free();
+    /// Emits `browser_startup_timeout` once if the renderer process of a new
+    /// browser has not reported in time. The browser stays blank in that
+    /// case, usually because the helper cannot start.
+    pub(super) fn check_renderer_startup(&mut self) {
+        let Some(deadline) = self.renderer_startup_deadline else {
+            return;
+        };
+        if self.app.renderer_process_id.is_some() {
+            self.renderer_startup_deadline = None;
+            return;
         }
-        self.popup_texture = None;
-
-        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
-        {
-            self.popup_texture_2d_rd = None;
+        if Instant::now() < deadline {
+            return;
         }
 
-        crate::cef_init::cef_release();
+        self.renderer_startup_deadline = None;
+        let helper = crate::utils::get_subprocess_path()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| "gdcef_helper".to_string());
+        godot::global::godot_warn!(
+            "[CefTexture] The browser's renderer process did not start ({} helper processes \
+             launched so far); check that {} runs",
+            cef_app::child_process_launch_count(),
+            helper
+        );
+        self.emit_browser_signal("browser_startup_timeout", &[]);
     }
 
     pub(super) fn create_browser(&mut self) {
@@ -109,7 +240,8 @@ impl CefTexture {
             return Ok(());
         }
 
-        let logical_size = self.base().get_size();
+        self.apply_render_target();
+        let logical_size = self.view_size();
 
         // Validate size before attempting to create browser.
         // A zero or negative size will crash CEF subprocess.
@@ -125,6 +257,8 @@ impl CefTexture {
         let pixel_height = (logical_size.y * dpi) as i32;
 
         let use_accelerated = self.should_use_accelerated_osr();
+        self.app.external_begin_frames =
+            self.frame_scheduling == crate::frame_scheduler::FrameScheduling::ExternalBeginFrame;
 
         let window_info = WindowInfo {
             bounds: cef::Rect {
@@ -135,68 +269,166 @@ impl CefTexture {
             },
             windowless_rendering_enabled: true as _,
             shared_texture_enabled: use_accelerated as _,
-            external_begin_frame_enabled: true as _,
+            external_begin_frame_enabled: self.app.external_begin_frames as _,
             ..Default::default()
         };
 
+        let font_families = crate::settings::get_font_families();
         let browser_settings = BrowserSettings {
             windowless_frame_rate: self.get_max_fps(),
             background_color: color_to_cef_color(self.background_color),
+            minimum_font_size: self.minimum_font_size.max(0),
+            default_font_size: self.default_font_size.max(0),
+            standard_font_family: font_families.standard.as_str().into(),
+            serif_font_family: font_families.serif.as_str().into(),
+            sans_serif_font_family: font_families.sans_serif.as_str().into(),
+            fixed_font_family: font_families.fixed.as_str().into(),
             ..Default::default()
         };
 
-        let mut context = cef::request_context_create_context(
-            Some(&RequestContextSettings::default()),
-            Some(&mut webrender::RequestContextHandlerImpl::build(
-                webrender::OsrRequestContextHandler {},
-            )),
+        let sample_rate = get_godot_audio_sample_rate();
+        let enable_audio_capture = self.effective_audio_capture();
+        let queues = webrender::ClientQueues::new(
+            sample_rate,
+            enable_audio_capture,
+            crate::settings::get_client_certificate_auto_select(),
+            self.app.navigation_allowlist.clone(),
+            self.app.extra_headers.clone(),
+            self.app.geolocation_override.clone(),
+            self.app.browser_shortcuts.clone(),
         );
 
-        // Register the res:// and user:// scheme handlers on this specific request context
-        if let Some(ctx) = context.as_mut() {
-            godot_protocol::register_res_scheme_handler_on_context(ctx);
-            godot_protocol::register_user_scheme_handler_on_context(ctx);
-        }
+        // Scheme requests are only recorded when request logging is enabled
+        let scheme_request_log = crate::settings::is_scheme_request_logging_enabled()
+            .then(|| queues.event_queues.clone());
+
+        let renderer_group = self.renderer_group.to_string();
+        let schemes = cef_init::ContextSchemes {
+            allow_res: self.allow_res_scheme,
+            allow_user: self.allow_user_scheme,
+            restrict_res_origin: self.restrict_res_scheme_origin,
+        };
+        let mut context =
+            cef_init::acquire_request_context(&renderer_group, schemes, scheme_request_log);
 
         let browser = if use_accelerated {
             self.create_accelerated_browser(
                 &window_info,
                 &browser_settings,
                 context.as_mut(),
+                queues,
                 dpi,
-                pixel_width,
-                pixel_height,
-            )?
+            )
         } else {
             self.create_software_browser(
                 &window_info,
                 &browser_settings,
                 context.as_mut(),
+                queues,
                 dpi,
-                pixel_width,
-                pixel_height,
-            )?
+            )
         };
 
+        let browser = match browser {
+            Ok(browser) => browser,
+            Err(e) => {
+                cef_init::release_request_context(&renderer_group);
+                return Err(e);
+            }
+        };
+
+        // The browser's own GPU info is always available; other results and
+        // the events only reach GDScript with allow_devtools_protocol
+        if let Some(event_queues) = &self.app.event_queues
+            && let Some(host) = browser.host()
+        {
+            self.app.devtools_registration = host.add_dev_tools_message_observer(Some(
+                &mut webrender::DevToolsMessageObserverImpl::build(
+                    event_queues.clone(),
+                    self.app.page_saves.clone(),
+                    self.app.gpu_info_requests.clone(),
+                    crate::settings::is_devtools_protocol_allowed(),
+                ),
+            ));
+        }
+
         self.app.browser = Some(browser);
+        self.app.renderer_group = Some(renderer_group);
+        self.app.audio_capture_enabled = enable_audio_capture;
         self.last_size = logical_size;
         self.last_dpi = dpi;
+        self.last_display_scale = self.get_device_scale_factor();
+        self.resize_debouncer = ResizeDebouncer::new(Duration::from_millis(
+            crate::settings::get_resize_debounce_ms(),
+        ));
+        self.click_counter = ClickCounter::new(Duration::from_millis(
+            crate::settings::get_double_click_time_ms(),
+        ));
+        self.emit_render_stats = crate::settings::is_render_stats_signal_enabled();
+        self.last_render_stats = Default::default();
+        self.renderer_startup_deadline =
+            crate::settings::get_browser_startup_timeout().map(|timeout| Instant::now() + timeout);
+
+        // Visibility may have been set before the browser existed
+        if !self.is_page_visible() {
+            self.apply_page_visibility();
+        }
+        // So is the emulation; sent before the first page commits
+        self.apply_emulation_overrides();
+        self.apply_user_scripts();
+        self.apply_zoom();
+        self.enable_accessibility();
+        // Recorded for the debug report
+        self.request_browser_gpu_info();
         Ok(())
     }
 
+    /// Creates the browser at `url`, or with `attach_dev_tools` the DevTools
+    /// front-end of the inspected browser.
+    fn open_browser(
+        &mut self,
+        window_info: &WindowInfo,
+        client: &mut cef::Client,
+        created_browser: &webrender::CreatedBrowserHandle,
+        browser_settings: &BrowserSettings,
+        context: Option<&mut cef::RequestContext>,
+    ) -> Option<cef::Browser> {
+        if let Some(host) = self.dev_tools_front_end.inspected_host() {
+            // The front-end shares the inspected browser's request context
+            return self.open_dev_tools_front_end(
+                &host,
+                window_info,
+                client,
+                created_browser,
+                browser_settings,
+            );
+        }
+        cef::browser_host_create_browser_sync(
+            Some(window_info),
+            Some(client),
+            Some(&self.url.to_string().as_str().into()),
+            Some(browser_settings),
+            self.browser_extra_info().as_mut(),
+            context,
+        )
+    }
+
     fn should_use_accelerated_osr(&self) -> bool {
-        self.enable_accelerated_osr && accelerated_osr::is_accelerated_osr_supported()
+        self.enable_accelerated_osr
+            && !self.force_software_rendering
+            && accelerated_osr::is_accelerated_osr_supported()
     }
 
     fn create_software_browser(
         &mut self,
-        _window_info: &WindowInfo,
+        window_info: &WindowInfo,
         browser_settings: &BrowserSettings,
         context: Option<&mut cef::RequestContext>,
+        queues: webrender::ClientQueues,
         dpi: f32,
-        pixel_width: i32,
-        pixel_height: i32,
     ) -> Result<cef::Browser, CefError> {
+        let pixel_width = window_info.bounds.width;
+        let pixel_height = window_info.bounds.height;
         let window_info = WindowInfo {
             bounds: cef::Rect {
                 x: 0,
@@ -206,59 +438,71 @@ impl CefTexture {
             },
             windowless_rendering_enabled: true as _,
             shared_texture_enabled: false as _,
-            external_begin_frame_enabled: true as _,
+            external_begin_frame_enabled: self.app.external_begin_frames as _,
             ..Default::default()
         };
 
         let render_handler = cef_app::OsrRenderHandler::new(
             dpi,
+            self.get_device_scale_factor(),
             PhysicalSize::new(pixel_width as f32, pixel_height as f32),
         );
 
         let frame_buffer = render_handler.get_frame_buffer();
         let render_size = render_handler.get_size();
         let device_scale_factor = render_handler.get_device_scale_factor();
+        let display_scale = render_handler.get_display_scale();
         let cursor_type = render_handler.get_cursor_type();
         let popup_state: PopupStateQueue = render_handler.get_popup_state();
-        let sample_rate = get_godot_audio_sample_rate();
-        let enable_audio_capture = crate::settings::is_audio_capture_enabled();
-        let queues = webrender::ClientQueues::new(sample_rate, enable_audio_capture);
 
         let texture = ImageTexture::new_gd();
 
-        let mut client = webrender::SoftwareClientImpl::build(
-            render_handler,
-            webrender::ClientQueues {
-                event_queues: queues.event_queues.clone(),
-                audio_packet_queue: queues.audio_packet_queue.clone(),
-                audio_params: queues.audio_params.clone(),
-                audio_sample_rate: queues.audio_sample_rate.clone(),
-                audio_shutdown_flag: queues.audio_shutdown_flag.clone(),
-                enable_audio_capture,
-            },
-        );
+        let mut client = webrender::SoftwareClientImpl::build(render_handler, queues.clone());
 
         // Attempt browser creation first, before updating any app state
-        let browser = cef::browser_host_create_browser_sync(
-            Some(&window_info),
-            Some(&mut client),
-            Some(&self.url.to_string().as_str().into()),
-            Some(browser_settings),
-            None,
-            context,
-        )
-        .ok_or_else(|| {
-            CefError::BrowserCreationFailed("browser_host_create_browser_sync returned None".into())
-        })?;
+        let browser = self
+            .open_browser(
+                &window_info,
+                &mut client,
+                &queues.created_browser,
+                browser_settings,
+                context,
+            )
+            .ok_or_else(|| {
+                CefError::BrowserCreationFailed(
+                    "browser_host_create_browser_sync returned None".into(),
+                )
+            })?;
 
         // Browser created successfully - now update app state
-        self.base_mut().set_texture(&texture);
+        self.set_browser_texture(texture.clone().upcast());
+        let uploader = if crate::settings::is_threaded_software_upload_enabled() {
+            // The placeholder's RID is kept when the first frame creates the
+            // texture
+            crate::software_upload::SoftwareUploader::spawn(
+                frame_buffer.clone(),
+                Some(popup_state.clone()),
+                texture.get_rid(),
+                Some(queues.render_stats.clone()),
+            )
+            .inspect_err(|err| {
+                godot::global::godot_warn!(
+                    "[CefTexture] Uploading frames on the main thread: {}",
+                    err
+                );
+            })
+            .ok()
+        } else {
+            None
+        };
         self.app.render_mode = Some(RenderMode::Software {
+            uploader,
             frame_buffer,
             texture,
         });
         self.app.render_size = Some(render_size);
         self.app.device_scale_factor = Some(device_scale_factor);
+        self.app.display_scale = Some(display_scale);
         self.app.cursor_type = Some(cursor_type);
         self.app.popup_state = Some(popup_state);
         self.app.event_queues = Some(queues.event_queues);
@@ -266,6 +510,10 @@ impl CefTexture {
         self.app.audio_params = Some(queues.audio_params);
         self.app.audio_sample_rate = Some(queues.audio_sample_rate);
         self.app.audio_shutdown_flag = Some(queues.audio_shutdown_flag);
+        self.app.certificate_decisions = Some(queues.certificate_decisions);
+        self.app.client_certificate_requests = Some(queues.client_certificate_requests);
+        self.app.render_stats = Some(queues.render_stats);
+        self.app.frame_pacer = Some(queues.frame_pacer);
 
         Ok(browser)
     }
@@ -276,10 +524,11 @@ impl CefTexture {
         window_info: &WindowInfo,
         browser_settings: &BrowserSettings,
         context: Option<&mut cef::RequestContext>,
+        queues: webrender::ClientQueues,
         dpi: f32,
-        pixel_width: i32,
-        pixel_height: i32,
     ) -> Result<cef::Browser, CefError> {
+        let pixel_width = window_info.bounds.width;
+        let pixel_height = window_info.bounds.height;
         let importer = match GodotTextureImporter::new() {
             Some(imp) => imp,
             None => {
@@ -290,15 +539,15 @@ impl CefTexture {
                     window_info,
                     browser_settings,
                     context,
+                    queues,
                     dpi,
-                    pixel_width,
-                    pixel_height,
                 );
             }
         };
 
         // Create the RD texture first
-        let (rd_texture_rid, texture_2d_rd) = render::create_rd_texture(pixel_width, pixel_height)?;
+        let (rd_texture_rid, texture_2d_rd) =
+            render::create_rd_texture(pixel_width, pixel_height, self.generate_mipmaps)?;
 
         // Create shared render state with the importer and destination texture
         let render_state = Arc::new(Mutex::new(AcceleratedRenderState::new(
@@ -306,49 +555,47 @@ impl CefTexture {
             rd_texture_rid,
             pixel_width as u32,
             pixel_height as u32,
+            self.generate_mipmaps
+                .then(accelerated_osr::MipmapGenerator::default),
         )));
 
         // Create render handler and give it the shared state
         let mut render_handler = PlatformAcceleratedRenderHandler::new(
             dpi,
+            self.get_device_scale_factor(),
             PhysicalSize::new(pixel_width as f32, pixel_height as f32),
         );
         render_handler.set_render_state(render_state.clone());
+        render_handler.set_render_stats(queues.render_stats.clone());
+        render_handler.set_frame_pacer(queues.frame_pacer.clone());
 
         let render_size = render_handler.get_size();
         let device_scale_factor = render_handler.get_device_scale_factor();
+        let display_scale = render_handler.get_display_scale();
         let cursor_type = render_handler.get_cursor_type();
         let popup_state: PopupStateQueue = render_handler.get_popup_state();
-        let sample_rate = get_godot_audio_sample_rate();
-        let enable_audio_capture = crate::settings::is_audio_capture_enabled();
-        let queues = webrender::ClientQueues::new(sample_rate, enable_audio_capture);
 
         let mut client = webrender::AcceleratedClientImpl::build(
             render_handler,
             cursor_type.clone(),
-            webrender::ClientQueues {
-                event_queues: queues.event_queues.clone(),
-                audio_packet_queue: queues.audio_packet_queue.clone(),
-                audio_params: queues.audio_params.clone(),
-                audio_sample_rate: queues.audio_sample_rate.clone(),
-                audio_shutdown_flag: queues.audio_shutdown_flag.clone(),
-                enable_audio_capture,
-            },
+            queues.clone(),
         );
 
         // Attempt browser creation first, before updating any app state
-        let browser = match cef::browser_host_create_browser_sync(
-            Some(window_info),
-            Some(&mut client),
-            Some(&self.url.to_string().as_str().into()),
-            Some(browser_settings),
-            None,
+        let browser = match self.open_browser(
+            window_info,
+            &mut client,
+            &queues.created_browser,
+            browser_settings,
             context,
         ) {
             Some(browser) => browser,
             None => {
-                // Browser creation failed - clean up the RD texture to prevent leak
-                render::free_rd_texture(rd_texture_rid);
+                // Browser creation failed - free the importer and the RD texture
+                render_state
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .teardown();
                 return Err(CefError::BrowserCreationFailed(
                     "browser_host_create_browser_sync returned None (accelerated)".into(),
                 ));
@@ -356,13 +603,14 @@ impl CefTexture {
         };
 
         // Browser created successfully - now update app state
-        self.base_mut().set_texture(&texture_2d_rd);
+        self.set_browser_texture(texture_2d_rd.clone().upcast());
         self.app.render_mode = Some(RenderMode::Accelerated {
             render_state,
             texture_2d_rd,
         });
         self.app.render_size = Some(render_size);
         self.app.device_scale_factor = Some(device_scale_factor);
+        self.app.display_scale = Some(display_scale);
         self.app.cursor_type = Some(cursor_type);
         self.app.popup_state = Some(popup_state);
         self.app.event_queues = Some(queues.event_queues);
@@ -370,6 +618,10 @@ impl CefTexture {
         self.app.audio_params = Some(queues.audio_params);
         self.app.audio_sample_rate = Some(queues.audio_sample_rate);
         self.app.audio_shutdown_flag = Some(queues.audio_shutdown_flag);
+        self.app.certificate_decisions = Some(queues.certificate_decisions);
+        self.app.client_certificate_requests = Some(queues.client_certificate_requests);
+        self.app.render_stats = Some(queues.render_stats);
+        self.app.frame_pacer = Some(queues.frame_pacer);
 
         Ok(browser)
     }
@@ -380,17 +632,9 @@ impl CefTexture {
         window_info: &WindowInfo,
         browser_settings: &BrowserSettings,
         context: Option<&mut cef::RequestContext>,
+        queues: webrender::ClientQueues,
         dpi: f32,
-        pixel_width: i32,
-        pixel_height: i32,
     ) -> Result<cef::Browser, CefError> {
-        self.create_software_browser(
-            window_info,
-            browser_settings,
-            context,
-            dpi,
-            pixel_width,
-            pixel_height,
-        )
+        self.create_software_browser(window_info, browser_settings, context, queues, dpi)
     }
 }
@@ -0,0 +1,209 @@
+//! Configuration warnings shown for `CefTexture` nodes in the editor's scene tree.
+
+use godot::prelude::*;
+use std::sync::OnceLock;
+
+use super::CefTexture;
+use crate::accelerated_osr::{self, RenderBackend};
+use crate::{godot_protocol, settings, utils};
+
+/// Schemes CEF loads without a registered handler.
+const BUILTIN_SCHEMES: &[&str] = &[
+    "http",
+    "https",
+    "file",
+    "data",
+    "about",
+    "blob",
+    "chrome",
+    "devtools",
+    "view-source",
+];
+
+/// Switches that open the DevTools port regardless of the build type.
+const REMOTE_DEBUGGING_SWITCHES: &[&str] = &["remote-debugging-port", "remote-debugging-pipe"];
+
+/// Results of the checks that do not depend on the node. Probing the GPU
+/// creates texture importers, far too slow for every inspector repaint, and
+/// neither the installation nor the rendering driver change while the editor
+/// runs.
+struct EnvironmentChecks {
+    missing_files: Vec<String>,
+    backend: RenderBackend,
+    accelerated_osr_supported: bool,
+}
+
+static ENVIRONMENT_CHECKS: OnceLock<EnvironmentChecks> = OnceLock::new();
+
+fn environment_checks() -> &'static EnvironmentChecks {
+    ENVIRONMENT_CHECKS.get_or_init(|| {
+        let missing_files = match utils::installation_files() {
+            Ok(files) => files
+                .into_iter()
+                .filter(|(_, path)| !path.exists())
+                .map(|(_, path)| path.display().to_string())
+                .collect(),
+            Err(e) => vec![e.to_string()],
+        };
+
+        EnvironmentChecks {
+            missing_files,
+            backend: RenderBackend::detect(),
+            accelerated_osr_supported: accelerated_osr::is_accelerated_osr_supported(),
+        }
+    })
+}
+
+/// Returns the lowercase scheme of `url`, or `None` for relative or empty URLs.
+fn url_scheme(url: &str) -> Option<String> {
+    let (scheme, _) = url.split_once(':')?;
+    let valid = scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    valid.then(|| scheme.to_ascii_lowercase())
+}
+
+/// Returns a warning when the browser would not be able to load `url`.
+fn url_scheme_warning(
+    url: &str,
+    allow_res_scheme: bool,
+    allow_user_scheme: bool,
+    custom_schemes: &[String],
+) -> Option<String> {
+    let scheme = url_scheme(url)?;
+    match scheme.as_str() {
+        "res" if !allow_res_scheme => {
+            Some("The URL uses res://, but allow_res_scheme is disabled.".to_string())
+        }
+        "user" if !allow_user_scheme => {
+            Some("The URL uses user://, but allow_user_scheme is disabled.".to_string())
+        }
+        "res" | "user" => None,
+        scheme
+            if BUILTIN_SCHEMES.contains(&scheme)
+                || custom_schemes
+                    .iter()
+                    .any(|custom| custom.eq_ignore_ascii_case(scheme)) =>
+        {
+            None
+        }
+        scheme => Some(format!(
+            "The URL scheme \"{scheme}\" is not built in. Make sure it is registered \
+             with CefTexture.register_scheme_folder() or register_scheme_callable() \
+             before the first CefTexture enters the tree."
+        )),
+    }
+}
+
+/// Returns whether the custom switches open the DevTools port, which bypasses
+/// the debug-build-only `remote_devtools_port` setting.
+fn enables_remote_debugging(switches: &[String]) -> bool {
+    switches.iter().any(|switch| {
+        let name = switch.trim_start_matches('-');
+        let name = name.split_once('=').map_or(name, |(name, _)| name);
+        REMOTE_DEBUGGING_SWITCHES.contains(&name)
+    })
+}
+
+impl CefTexture {
+    pub(super) fn configuration_warnings(&self) -> PackedStringArray {
+        let mut warnings = Vec::new();
+        let checks = environment_checks();
+
+        if !checks.missing_files.is_empty() {
+            warnings.push(format!(
+                "The CEF installation is incomplete. Missing: {}",
+                checks.missing_files.join(", ")
+            ));
+        }
+
+        if self.enable_accelerated_osr && !checks.accelerated_osr_supported {
+            warnings.push(format!(
+                "enable_accelerated_osr is set, but the {:?} rendering driver does not \
+                 support accelerated rendering here; the browser will fall back to software \
+                 rendering.",
+                checks.backend
+            ));
+        }
+
+        if let Some(warning) = url_scheme_warning(
+            &self.url.to_string(),
+            self.allow_res_scheme,
+            self.allow_user_scheme,
+            &godot_protocol::registered_custom_scheme_names(),
+        ) {
+            warnings.push(warning);
+        }
+
+        if enables_remote_debugging(&settings::get_custom_switches()) {
+            warnings.push(
+                "godot_cef/advanced/custom_command_line_switches enables remote debugging, \
+                 which stays enabled in release exports."
+                    .to_string(),
+            );
+        }
+
+        warnings
+            .iter()
+            .map(|warning| GString::from(warning.as_str()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_scheme() {
+        assert_eq!(url_scheme("https://example.com"), Some("https".to_string()));
+        assert_eq!(url_scheme("RES://ui/index.html"), Some("res".to_string()));
+        assert_eq!(url_scheme("about:blank"), Some("about".to_string()));
+        assert_eq!(
+            url_scheme("view-source:https://a"),
+            Some("view-source".to_string())
+        );
+        assert_eq!(url_scheme("index.html"), None);
+        assert_eq!(url_scheme(""), None);
+        assert_eq!(url_scheme("1http://a"), None);
+    }
+
+    #[test]
+    fn test_url_scheme_warning() {
+        let custom = vec!["app".to_string()];
+        assert_eq!(
+            url_scheme_warning("https://example.com", false, false, &custom),
+            None
+        );
+        assert_eq!(
+            url_scheme_warning("res://index.html", true, false, &custom),
+            None
+        );
+        assert!(url_scheme_warning("res://index.html", false, false, &custom).is_some());
+        assert!(url_scheme_warning("user://page.html", true, false, &custom).is_some());
+        assert_eq!(
+            url_scheme_warning("user://page.html", true, true, &custom),
+            None
+        );
+        assert_eq!(url_scheme_warning("APP://ui/", true, false, &custom), None);
+        assert!(url_scheme_warning("game://ui/", true, false, &custom).is_some());
+        assert_eq!(url_scheme_warning("", true, false, &custom), None);
+    }
+
+    #[test]
+    fn test_enables_remote_debugging() {
+        let switches = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(enables_remote_debugging(&switches(&[
+            "--remote-debugging-port=9222"
+        ])));
+        assert!(enables_remote_debugging(&switches(&[
+            "remote-debugging-pipe"
+        ])));
+        assert!(!enables_remote_debugging(&switches(&[
+            "--remote-allow-origins=*",
+            "--disable-gpu"
+        ])));
+        assert!(!enables_remote_debugging(&[]));
+    }
+}
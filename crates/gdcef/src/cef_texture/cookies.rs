@@ -0,0 +1,249 @@
+//! Cookie export and import: `export_cookies` and `import_cookies`.
+//!
+//! Cookies are read and set through the cookie manager of the browser's
+//! request context, which also reaches HttpOnly cookies that scripts cannot
+//! see. Both run asynchronously and report through `cookies_exported` and
+//! `cookies_imported`. A snapshot that cannot be read is rejected before any
+//! cookie is set; a cookie that cannot be set is reported and skipped. The
+//! snapshot format is described in `crate::cookies`.
+
+use super::CefTexture;
+use crate::browser::{CookieImportFailure, CookiesImportedEvent, EventKind, EventQueuesHandle};
+use crate::cookies::{CookieRecord, SNAPSHOT_FORMAT, SNAPSHOT_VERSION, SameSite};
+use crate::webrender::{CookieExport, CookieImport, CookieVisitorImpl, SetCookieCallbackImpl};
+use cef::{CefString, ImplBrowser, ImplBrowserHost, ImplCookieManager, ImplRequestContext};
+use godot::classes::Json;
+use godot::prelude::*;
+
+fn string_at(cookie: &VarDictionary, key: &str) -> Result<String, String> {
+    cookie
+        .get(key)
+        .and_then(|value| value.try_to::<GString>().ok())
+        .map(|value| value.to_string())
+        .ok_or_else(|| format!("missing or invalid '{key}'"))
+}
+
+/// Returns the value of an optional key, `default` if it is absent.
+fn optional_at<T: FromGodot>(cookie: &VarDictionary, key: &str, default: T) -> Result<T, String> {
+    match cookie.get(key) {
+        None => Ok(default),
+        Some(value) => value.try_to::<T>().map_err(|_| format!("invalid '{key}'")),
+    }
+}
+
+fn cookie_from_dictionary(cookie: &VarDictionary) -> Result<CookieRecord, String> {
+    let same_site = optional_at(cookie, "samesite", GString::from("unspecified"))?.to_string();
+    // JSON numbers are floats; null marks a session cookie
+    let expires = match cookie.get("expires") {
+        None => None,
+        Some(value) if value.is_nil() => None,
+        Some(value) => match value.try_to::<f64>() {
+            Ok(expires) if expires.is_finite() => Some(expires as i64),
+            _ => return Err("invalid 'expires'".to_string()),
+        },
+    };
+    Ok(CookieRecord {
+        name: string_at(cookie, "name")?,
+        value: string_at(cookie, "value")?,
+        domain: string_at(cookie, "domain")?,
+        path: string_at(cookie, "path")?,
+        expires,
+        secure: optional_at(cookie, "secure", false)?,
+        httponly: optional_at(cookie, "httponly", false)?,
+        same_site: SameSite::parse(&same_site)
+            .ok_or_else(|| format!("invalid 'samesite' '{same_site}'"))?,
+    })
+}
+
+fn cookie_to_dictionary(cookie: &CookieRecord) -> VarDictionary {
+    let mut dictionary = VarDictionary::new();
+    dictionary.set("name", cookie.name.as_str());
+    dictionary.set("value", cookie.value.as_str());
+    dictionary.set("domain", cookie.domain.as_str());
+    dictionary.set("path", cookie.path.as_str());
+    match cookie.expires {
+        Some(expires) => dictionary.set("expires", expires),
+        None => dictionary.set("expires", Variant::nil()),
+    }
+    dictionary.set("secure", cookie.secure);
+    dictionary.set("httponly", cookie.httponly);
+    dictionary.set("samesite", cookie.same_site.as_str());
+    dictionary
+}
+
+/// Reads the cookies of a snapshot, each one or why it cannot be set.
+/// Fails if the snapshot as a whole cannot be read.
+fn parse_snapshot(data: &PackedByteArray) -> Result<Vec<Result<CookieRecord, String>>, String> {
+    let text = std::str::from_utf8(data.as_slice())
+        .map_err(|_| "the snapshot is not UTF-8 text".to_string())?;
+    let snapshot = Json::parse_string(text)
+        .try_to::<VarDictionary>()
+        .map_err(|_| "the snapshot is not a JSON object".to_string())?;
+    if optional_at(&snapshot, "format", GString::new())?.to_string() != SNAPSHOT_FORMAT {
+        return Err(format!("the snapshot's format is not '{SNAPSHOT_FORMAT}'"));
+    }
+    let version = optional_at(&snapshot, "version", 0.0)?;
+    if version != SNAPSHOT_VERSION as f64 {
+        return Err(format!("unsupported snapshot version {version}"));
+    }
+    let cookies = optional_at(&snapshot, "cookies", VarArray::new())?;
+    Ok(cookies
+        .iter_shared()
+        .map(|cookie| {
+            cookie
+                .try_to::<VarDictionary>()
+                .map_err(|_| "the cookie is not a JSON object".to_string())
+                .and_then(|cookie| cookie_from_dictionary(&cookie))
+        })
+        .collect())
+}
+
+fn failure_to_dictionary(failure: &CookieImportFailure) -> VarDictionary {
+    let mut dictionary = VarDictionary::new();
+    dictionary.set("index", failure.index);
+    dictionary.set("name", failure.name.as_str());
+    dictionary.set("domain", failure.domain.as_str());
+    dictionary.set("error", failure.error.as_str());
+    dictionary
+}
+
+/// Queues the outcome of an import that set no cookie.
+fn queue_cookies_imported(event_queues: &EventQueuesHandle, event: CookiesImportedEvent) {
+    if let Ok(mut queues) = event_queues.lock() {
+        let outcome = queues.cookies_imported.push_back(event);
+        queues.record(EventKind::CookiesImported, outcome);
+    }
+}
+
+impl CefTexture {
+    fn cookie_manager(&self) -> Option<cef::CookieManager> {
+        self.app
+            .browser
+            .as_ref()?
+            .host()?
+            .request_context()?
+            .cookie_manager(None)
+    }
+
+    pub(super) fn export_cookies_impl(&mut self, url_filter: &str) {
+        let (Some(manager), Some(event_queues)) = (self.cookie_manager(), &self.app.event_queues)
+        else {
+            godot::global::godot_warn!("[CefTexture] Cannot export cookies: no browser");
+            return;
+        };
+        // If the cookies cannot be read, the visitor is released unused and
+        // an empty snapshot is exported
+        let mut visitor = CookieVisitorImpl::build(CookieExport::new(event_queues.clone()));
+        let started = if url_filter.is_empty() {
+            manager.visit_all_cookies(Some(&mut visitor))
+        } else {
+            manager.visit_url_cookies(
+                Some(&CefString::from(url_filter)),
+                true as _,
+                Some(&mut visitor),
+            )
+        };
+        if started == 0 {
+            godot::global::godot_error!(
+                "[CefTexture] Cannot read the cookies for '{}'",
+                url_filter
+            );
+        }
+    }
+
+    pub(super) fn import_cookies_impl(&mut self, data: &PackedByteArray) {
+        let Some(event_queues) = self.app.event_queues.clone() else {
+            godot::global::godot_warn!("[CefTexture] Cannot import cookies: no browser");
+            return;
+        };
+        let rejected = |error: String| CookiesImportedEvent {
+            ok_count: 0,
+            failed: vec![CookieImportFailure {
+                index: -1,
+                error,
+                ..Default::default()
+            }],
+        };
+        let snapshot = match (parse_snapshot(data), self.cookie_manager()) {
+            (Ok(cookies), Some(manager)) => Some((cookies, manager)),
+            (Err(error), _) => {
+                queue_cookies_imported(&event_queues, rejected(error));
+                None
+            }
+            (Ok(_), None) => {
+                queue_cookies_imported(&event_queues, rejected("no cookie manager".to_string()));
+                None
+            }
+        };
+        let Some((cookies, manager)) = snapshot else {
+            return;
+        };
+
+        // Queued once the last callback is released
+        let import = CookieImport::new(event_queues);
+        for (index, cookie) in cookies.into_iter().enumerate() {
+            let cookie = cookie.and_then(|cookie| cookie.validate().map(|()| cookie));
+            let cookie = match cookie {
+                Ok(cookie) => cookie,
+                Err(error) => {
+                    import.fail(CookieImportFailure {
+                        index: index as i64,
+                        error,
+                        ..Default::default()
+                    });
+                    continue;
+                }
+            };
+            let failure = CookieImportFailure {
+                index: index as i64,
+                name: cookie.name.clone(),
+                domain: cookie.domain.clone(),
+                error: String::new(),
+            };
+            let mut callback = SetCookieCallbackImpl::build(import.clone(), failure.clone());
+            let set = manager.set_cookie(
+                Some(&CefString::from(cookie.url().as_str())),
+                Some(&cookie.to_cef()),
+                Some(&mut callback),
+            );
+            if set == 0 {
+                import.fail(CookieImportFailure {
+                    error: format!("invalid URL {}", cookie.url()),
+                    ..failure
+                });
+            }
+        }
+        // Write them to disk soon, a launcher may quit right after
+        manager.flush_store(None);
+    }
+
+    pub(super) fn emit_cookies_exported_signal(&mut self, cookies: &[CookieRecord]) {
+        let mut snapshot = VarDictionary::new();
+        snapshot.set("format", SNAPSHOT_FORMAT);
+        snapshot.set("version", SNAPSHOT_VERSION);
+        snapshot.set(
+            "cookies",
+            cookies
+                .iter()
+                .map(|cookie| cookie_to_dictionary(cookie).to_variant())
+                .collect::<VarArray>(),
+        );
+        let data = Json::stringify(&snapshot.to_variant()).to_utf8_buffer();
+        self.emit_browser_signal("cookies_exported", &[("data", data.to_variant())]);
+    }
+
+    pub(super) fn emit_cookies_imported_signal(&mut self, event: &CookiesImportedEvent) {
+        let failed: VarArray = event
+            .failed
+            .iter()
+            .map(|failure| failure_to_dictionary(failure).to_variant())
+            .collect();
+        self.emit_browser_signal(
+            "cookies_imported",
+            &[
+                ("ok_count", event.ok_count.to_variant()),
+                ("failed", failed.to_variant()),
+            ],
+        );
+    }
+}
@@ -0,0 +1,433 @@
+//! Developer overlay for CefTexture.
+//!
+//! `show_debug_overlay` adds a child Control drawn over the page with live
+//! diagnostics; `copy_debug_report` puts the same data and the platform
+//! details on the clipboard for bug reports. The overlay is only updated
+//! and drawn while visible. In release exports both stay inert unless
+//! `godot_cef/debug/allow_debug_overlay_in_release` is enabled.
+
+use std::time::{Duration, Instant};
+
+use super::CefTexture;
+use godot::classes::control::{LayoutPreset, MouseFilter};
+use godot::classes::{
+    Control, DisplayServer, Engine, Input, InputMap, Os, RenderingServer, ThemeDb,
+};
+use godot::prelude::*;
+
+use crate::render_stats::RenderStatsSnapshot;
+
+/// How often the paint rate and helper processes are refreshed.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(1);
+
+const FONT_SIZE: i32 = 14;
+const LINE_HEIGHT: f32 = 18.0;
+const PADDING: f32 = 8.0;
+
+/// The diagnostics shown by the overlay and included in reports.
+#[derive(Debug, Default, Clone, PartialEq)]
+struct DebugInfo {
+    url: String,
+    loading: bool,
+    render_path: &'static str,
+    paints_per_second: f64,
+    pending_events: usize,
+    pending_audio_packets: usize,
+    texture_size: (u32, u32),
+    screen: i32,
+    device_scale: f32,
+    helper_pids: Vec<u32>,
+    renderer_pid: Option<u32>,
+    gpu_pid: Option<u32>,
+    last_error: Option<String>,
+}
+
+impl DebugInfo {
+    fn lines(&self) -> Vec<String> {
+        let helper_pids = if self.helper_pids.is_empty() {
+            "none".to_string()
+        } else {
+            self.helper_pids
+                .iter()
+                .map(u32::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let pid = |pid: Option<u32>| pid.map_or("unknown".to_string(), |pid| pid.to_string());
+        vec![
+            format!("URL: {}", self.url),
+            format!(
+                "Load state: {}",
+                if self.loading { "loading" } else { "idle" }
+            ),
+            format!("Render path: {}", self.render_path),
+            format!("Paints/s: {:.1}", self.paints_per_second),
+            format!(
+                "Queue backlog: {} events, {} audio packets",
+                self.pending_events, self.pending_audio_packets
+            ),
+            format!("Texture: {}x{}", self.texture_size.0, self.texture_size.1),
+            format!("Screen: {} @ {:.2}x", self.screen, self.device_scale),
+            format!("Helper processes: {helper_pids}"),
+            format!("Renderer process: {}", pid(self.renderer_pid)),
+            format!("GPU process: {}", pid(self.gpu_pid)),
+            format!(
+                "Last error: {}",
+                self.last_error.as_deref().unwrap_or("none")
+            ),
+        ]
+    }
+}
+
+/// Joins the diagnostics and platform details into a report.
+fn report_text(info: &DebugInfo, platform: &[(&str, String)]) -> String {
+    let mut report = String::from("Godot CEF debug report\n");
+    for line in info.lines() {
+        report.push_str(&line);
+        report.push('\n');
+    }
+    for (name, value) in platform {
+        report.push_str(&format!("{name}: {value}\n"));
+    }
+    report
+}
+
+/// Returns whether the overlay and reports are available: always in debug
+/// builds and the editor, in release exports only when the project allows.
+fn is_debug_overlay_allowed() -> bool {
+    Os::singleton().is_debug_build()
+        || Engine::singleton().is_editor_hint()
+        || crate::settings::is_debug_overlay_allowed_in_release()
+}
+
+#[derive(Default)]
+pub(super) struct DebugOverlay {
+    control: Option<Gd<Control>>,
+    /// Input action toggling the overlay, if one is set and exists.
+    action: Option<StringName>,
+    info: DebugInfo,
+    last_refresh: Option<(Instant, RenderStatsSnapshot)>,
+    last_error: Option<String>,
+}
+
+impl CefTexture {
+    /// Reads the toggle action from the project settings.
+    pub(super) fn init_debug_overlay(&mut self) {
+        let action = crate::settings::get_debug_overlay_action();
+        self.debug_overlay.action = (!action.is_empty() && is_debug_overlay_allowed())
+            .then(|| StringName::from(action.as_str()))
+            .filter(|action| InputMap::singleton().has_action(action));
+    }
+
+    pub(super) fn show_debug_overlay_impl(&mut self, visible: bool) {
+        if !visible {
+            if let Some(control) = self.debug_overlay.control.as_mut() {
+                control.set_visible(false);
+            }
+            return;
+        }
+        if !is_debug_overlay_allowed() {
+            godot::global::godot_warn!(
+                "[CefTexture] The debug overlay is disabled in release exports by godot_cef/debug/allow_debug_overlay_in_release"
+            );
+            return;
+        }
+
+        let mut control = match self.debug_overlay.control.clone() {
+            Some(control) => control,
+            None => {
+                let mut control = Control::new_alloc();
+                control.set_mouse_filter(MouseFilter::IGNORE);
+                control.set_anchors_and_offsets_preset(LayoutPreset::FULL_RECT);
+                let callable = self.base().callable("_on_debug_overlay_draw");
+                control.connect("draw", &callable);
+                self.base_mut().add_child(&control);
+                self.debug_overlay.control = Some(control.clone());
+                control
+            }
+        };
+        control.set_visible(true);
+        self.debug_overlay.last_refresh = None;
+        self.refresh_debug_overlay();
+    }
+
+    fn is_debug_overlay_visible(&self) -> bool {
+        self.debug_overlay
+            .control
+            .as_ref()
+            .is_some_and(|control| control.is_visible())
+    }
+
+    /// Toggles the overlay on its input action, and refreshes it while it is
+    /// visible. Does nothing otherwise.
+    pub(super) fn update_debug_overlay(&mut self) {
+        if let Some(action) = &self.debug_overlay.action
+            && Input::singleton().is_action_just_pressed(action)
+        {
+            let visible = !self.is_debug_overlay_visible();
+            self.show_debug_overlay_impl(visible);
+            return;
+        }
+        if self.is_debug_overlay_visible() {
+            self.refresh_debug_overlay();
+        }
+    }
+
+    fn refresh_debug_overlay(&mut self) {
+        self.debug_overlay.info = self.debug_info();
+        if let Some(control) = self.debug_overlay.control.as_mut() {
+            control.queue_redraw();
+        }
+    }
+
+    /// Draws the overlay; connected to the overlay's `draw` signal.
+    pub(super) fn draw_debug_overlay(&mut self) {
+        let Some(mut control) = self.debug_overlay.control.clone() else {
+            return;
+        };
+        let Some(font) = ThemeDb::singleton().get_fallback_font() else {
+            return;
+        };
+        let lines = self.debug_overlay.info.lines();
+
+        let width = lines
+            .iter()
+            .map(|line| {
+                font.get_string_size_ex(line.as_str())
+                    .font_size(FONT_SIZE)
+                    .done()
+                    .x
+            })
+            .fold(0.0, f32::max);
+        let size = Vector2::new(
+            width + PADDING * 2.0,
+            lines.len() as f32 * LINE_HEIGHT + PADDING * 2.0,
+        );
+        control.draw_rect(
+            Rect2::new(Vector2::ZERO, size),
+            Color::from_rgba(0.0, 0.0, 0.0, 0.7),
+        );
+        for (index, line) in lines.iter().enumerate() {
+            let position = Vector2::new(PADDING, PADDING + (index as f32 + 0.8) * LINE_HEIGHT);
+            control
+                .draw_string_ex(&font, position, line.as_str())
+                .font_size(FONT_SIZE)
+                .modulate(Color::WHITE)
+                .done();
+        }
+    }
+
+    /// Returns the diagnostics and platform details as text and copies it to
+    /// the clipboard.
+    pub(super) fn copy_debug_report_impl(&mut self) -> String {
+        if !is_debug_overlay_allowed() {
+            godot::global::godot_warn!(
+                "[CefTexture] Debug reports are disabled in release exports by godot_cef/debug/allow_debug_overlay_in_release"
+            );
+            return String::new();
+        }
+        let info = self.debug_info();
+
+        let os = Os::singleton();
+        let rendering_server = RenderingServer::singleton();
+        let interop = crate::vulkan_hook::status();
+        let version_info = Engine::singleton().get_version_info();
+        let godot_version = version_info
+            .get("string")
+            .map(|version| version.to_string())
+            .unwrap_or_default();
+        let platform = [
+            ("OS", format!("{} {}", os.get_name(), os.get_version())),
+            ("Godot", godot_version),
+            ("godot-cef", env!("CARGO_PKG_VERSION").to_string()),
+            (
+                "CEF",
+                format!(
+                    "{}.{}.{} (Chromium {}.{}.{}.{})",
+                    cef::sys::CEF_VERSION_MAJOR,
+                    cef::sys::CEF_VERSION_MINOR,
+                    cef::sys::CEF_VERSION_PATCH,
+                    cef::sys::CHROME_VERSION_MAJOR,
+                    cef::sys::CHROME_VERSION_MINOR,
+                    cef::sys::CHROME_VERSION_BUILD,
+                    cef::sys::CHROME_VERSION_PATCH
+                ),
+            ),
+            ("Browser GPU", self.browser_gpu_report()),
+            (
+                "Rendering driver",
+                format!(
+                    "{} {}",
+                    rendering_server.get_current_rendering_driver_name(),
+                    rendering_server.get_video_adapter_api_version()
+                ),
+            ),
+            (
+                "GPU",
+                format!(
+                    "{} ({})",
+                    rendering_server.get_video_adapter_name(),
+                    rendering_server.get_video_adapter_vendor()
+                ),
+            ),
+            (
+                "Vulkan hook",
+                if interop.installed {
+                    "installed"
+                } else if interop.disabled {
+                    "disabled"
+                } else {
+                    "not installed"
+                }
+                .to_string(),
+            ),
+        ];
+
+        let report = report_text(&info, &platform);
+        DisplayServer::singleton().clipboard_set(report.as_str());
+        report
+    }
+
+    /// Records a main-frame load error for the overlay and reports.
+    pub(super) fn record_debug_error(&mut self, url: &str, error_code: i32, error_text: &str) {
+        self.debug_overlay.last_error = Some(format!("{error_text} ({error_code}) at {url}"));
+    }
+
+    pub(super) fn release_debug_overlay(&mut self) {
+        if let Some(mut control) = self.debug_overlay.control.take() {
+            control.queue_free();
+        }
+    }
+
+    fn debug_info(&mut self) -> DebugInfo {
+        let now = Instant::now();
+        let snapshot = self
+            .app
+            .render_stats
+            .as_ref()
+            .map(|stats| stats.snapshot())
+            .unwrap_or_default();
+
+        // The paint rate and processes change slowly and the latter are
+        // costly to read, so they are refreshed once per interval
+        let (paints_per_second, helper_pids, gpu_pid) = match self.debug_overlay.last_refresh {
+            Some((refreshed_at, _)) if now.duration_since(refreshed_at) < REFRESH_INTERVAL => (
+                self.debug_overlay.info.paints_per_second,
+                self.debug_overlay.info.helper_pids.clone(),
+                self.debug_overlay.info.gpu_pid,
+            ),
+            last_refresh => {
+                let paints_per_second = match last_refresh {
+                    Some((_, last_snapshot)) => snapshot.since(&last_snapshot).paints_per_second(),
+                    None => snapshot.paints_per_second(),
+                };
+                self.debug_overlay.last_refresh = Some((now, snapshot));
+                (
+                    paints_per_second,
+                    crate::utils::helper_process_ids(),
+                    crate::utils::gpu_process_id(),
+                )
+            }
+        };
+
+        let (render_path, width, height) = self.render_path_and_size();
+        let (pending_events, pending_audio_packets) = self.queue_backlog();
+        DebugInfo {
+            url: self.get_url_property().to_string(),
+            loading: self.is_loading(),
+            render_path,
+            paints_per_second,
+            pending_events,
+            pending_audio_packets,
+            texture_size: (width, height),
+            screen: self.current_screen(),
+            device_scale: self.get_device_scale_factor(),
+            helper_pids,
+            renderer_pid: self.app.renderer_process_id,
+            gpu_pid,
+            last_error: self.debug_overlay.last_error.clone(),
+        }
+    }
+
+    /// Returns the number of browser events and audio packets waiting to be
+    /// processed.
+    pub(super) fn queue_backlog(&self) -> (usize, usize) {
+        let pending_events = self
+            .app
+            .event_queues
+            .as_ref()
+            .and_then(|queues| queues.lock().ok().map(|q| q.pending_len()))
+            .unwrap_or(0);
+        let pending_audio_packets = self
+            .app
+            .audio_packet_queue
+            .as_ref()
+            .and_then(|queue| queue.lock().ok().map(|q| q.len()))
+            .unwrap_or(0);
+        (pending_events, pending_audio_packets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> DebugInfo {
+        DebugInfo {
+            url: "https://example.com/".to_string(),
+            loading: true,
+            render_path: "accelerated",
+            paints_per_second: 59.94,
+            pending_events: 3,
+            pending_audio_packets: 0,
+            texture_size: (1920, 1080),
+            screen: 1,
+            device_scale: 1.5,
+            helper_pids: vec![1201, 1202],
+            renderer_pid: Some(1202),
+            gpu_pid: None,
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn test_debug_info_lines() {
+        assert_eq!(
+            info().lines(),
+            vec![
+                "URL: https://example.com/",
+                "Load state: loading",
+                "Render path: accelerated",
+                "Paints/s: 59.9",
+                "Queue backlog: 3 events, 0 audio packets",
+                "Texture: 1920x1080",
+                "Screen: 1 @ 1.50x",
+                "Helper processes: 1201, 1202",
+                "Renderer process: 1202",
+                "GPU process: unknown",
+                "Last error: none",
+            ]
+        );
+
+        let idle = DebugInfo {
+            loading: false,
+            helper_pids: Vec::new(),
+            last_error: Some("ERR_NAME_NOT_RESOLVED (-105) at https://x.invalid/".to_string()),
+            ..info()
+        };
+        let lines = idle.lines();
+        assert_eq!(lines[1], "Load state: idle");
+        assert_eq!(lines[7], "Helper processes: none");
+        assert_eq!(
+            lines[10],
+            "Last error: ERR_NAME_NOT_RESOLVED (-105) at https://x.invalid/"
+        );
+    }
+
+    #[test]
+    fn test_report_text() {
+        let report = report_text(&info(), &[("OS", "Linux 6.8".to_string())]);
+        assert!(report.starts_with("Godot CEF debug report\nURL: https://example.com/\n"));
+        assert!(report.ends_with("Last error: none\nOS: Linux 6.8\n"));
+    }
+}
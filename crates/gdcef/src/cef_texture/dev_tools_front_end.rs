@@ -0,0 +1,163 @@
+//! DevTools front-end in a CefTexture: `attach_dev_tools`.
+//!
+//! The node calling `attach_dev_tools` replaces its page with the inspector
+//! of the target's browser. The front-end is a windowless browser created by
+//! `show_dev_tools` with this node's client, so it renders into this node's
+//! texture and takes its input like any page. Closing either browser
+//! detaches the pair: CEF closes the front-end with the inspected browser,
+//! which the front-end node notices on its next frame, and the node then
+//! loads its `url` again. Only one browser of the pair has keyboard focus:
+//! the node gaining focus takes it from the other one.
+
+use super::CefTexture;
+use crate::webrender::CreatedBrowserHandle;
+use cef::{BrowserSettings, ImplBrowser, ImplBrowserHost, WindowInfo};
+use godot::classes::{Engine, Os};
+use godot::prelude::*;
+
+/// Browser hosts of the other end of an attached front-end.
+#[derive(Default)]
+pub(super) struct DevToolsFrontEnd {
+    /// On the front-end node: the inspected node and its browser host.
+    inspected: Option<(InstanceId, cef::BrowserHost)>,
+    /// On the inspected node: the front-end's browser host. It is not
+    /// cleared when the front-end detaches, which only costs a redundant
+    /// `set_focus(false)`.
+    front_end_host: Option<cef::BrowserHost>,
+}
+
+impl DevToolsFrontEnd {
+    pub(super) fn inspected_host(&self) -> Option<cef::BrowserHost> {
+        self.inspected.as_ref().map(|(_, host)| host.clone())
+    }
+
+    fn is_inspecting(&self, id: InstanceId) -> bool {
+        self.inspected
+            .as_ref()
+            .is_some_and(|(inspected, _)| *inspected == id)
+    }
+}
+
+/// Returns whether DevTools front-ends may be shown, under the same
+/// conditions as remote debugging: the DevTools protocol is allowed, and
+/// this is a debug build or the editor.
+fn is_dev_tools_front_end_allowed() -> bool {
+    crate::settings::is_devtools_protocol_allowed()
+        && (Os::singleton().is_debug_build() || Engine::singleton().is_editor_hint())
+}
+
+fn browser_host(texture: &CefTexture) -> Option<cef::BrowserHost> {
+    texture
+        .app
+        .browser
+        .as_ref()
+        .and_then(|browser| browser.host())
+}
+
+impl CefTexture {
+    pub(super) fn attach_dev_tools_impl(&mut self, mut target: Gd<CefTexture>) -> bool {
+        if !is_dev_tools_front_end_allowed() {
+            godot::global::godot_warn!(
+                "[CefTexture] DevTools front-ends are only available in debug builds, with \
+                 godot_cef/security/allow_devtools_protocol enabled"
+            );
+            return false;
+        }
+        let target_id = target.instance_id();
+        if target_id == self.base().instance_id() {
+            godot::global::godot_warn!("[CefTexture] A browser cannot show its own DevTools");
+            return false;
+        }
+        if self.dev_tools_front_end.is_inspecting(target_id) {
+            return true;
+        }
+
+        let Some(target_host) = browser_host(&target.bind()) else {
+            godot::global::godot_warn!(
+                "[CefTexture] The target of attach_dev_tools has no browser"
+            );
+            return false;
+        };
+        if target_host.has_dev_tools() != 0 {
+            godot::global::godot_warn!(
+                "[CefTexture] The target of attach_dev_tools is already inspected"
+            );
+            return false;
+        }
+
+        // The front-end replaces this node's page
+        self.release_browser();
+        self.dev_tools_front_end.inspected = Some((target_id, target_host));
+        if let Err(e) = self.try_create_browser() {
+            godot::global::godot_error!("[CefTexture] {}", e);
+            self.dev_tools_front_end.inspected = None;
+            return false;
+        }
+        target.bind_mut().dev_tools_front_end.front_end_host = browser_host(self);
+        true
+    }
+
+    /// Shows the inspected browser's DevTools with this node's client and
+    /// returns the front-end browser, which CEF creates before
+    /// `show_dev_tools` returns.
+    pub(super) fn open_dev_tools_front_end(
+        &mut self,
+        inspected_host: &cef::BrowserHost,
+        window_info: &WindowInfo,
+        client: &mut cef::Client,
+        created_browser: &CreatedBrowserHandle,
+        browser_settings: &BrowserSettings,
+    ) -> Option<cef::Browser> {
+        inspected_host.show_dev_tools(
+            Some(window_info),
+            Some(client),
+            Some(browser_settings),
+            None,
+        );
+        let browser = created_browser
+            .lock()
+            .ok()
+            .and_then(|mut browser| browser.take());
+        if browser.is_none() {
+            inspected_host.close_dev_tools();
+        }
+        browser
+    }
+
+    /// Detaches the front-end once the inspected browser closed it. Called
+    /// once per frame.
+    pub(super) fn check_dev_tools_front_end(&mut self) {
+        let closed = self
+            .dev_tools_front_end
+            .inspected
+            .as_ref()
+            .is_some_and(|(_, host)| host.has_dev_tools() == 0);
+        if closed {
+            self.dev_tools_front_end.inspected = None;
+            self.release_browser();
+        }
+    }
+
+    /// Takes CEF keyboard focus from the other end of an attached front-end,
+    /// when this node gains focus.
+    pub(super) fn unfocus_dev_tools_peer(&self) {
+        let inspected_host = self
+            .dev_tools_front_end
+            .inspected
+            .as_ref()
+            .map(|(_, host)| host);
+        for host in inspected_host
+            .into_iter()
+            .chain(self.dev_tools_front_end.front_end_host.as_ref())
+        {
+            host.set_focus(false as _);
+        }
+    }
+
+    /// Closes the front-end this node shows, before its browser is closed.
+    pub(super) fn release_dev_tools_front_end(&mut self) {
+        if let Some((_, inspected_host)) = self.dev_tools_front_end.inspected.take() {
+            inspected_host.close_dev_tools();
+        }
+    }
+}
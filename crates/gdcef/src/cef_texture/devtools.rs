@@ -0,0 +1,259 @@
+//! Chrome DevTools Protocol access for CefTexture.
+//!
+//! Methods are sent with `execute_dev_tools_method`; their results and the
+//! events of enabled domains come back through the observer registered when
+//! the browser is created, and are emitted as `devtools_result` and
+//! `devtools_event`.
+
+use super::CefTexture;
+use crate::browser::GeolocationOverride;
+use cef::{CefString, ImplBrowser, ImplBrowserHost, ImplValue, JsonParserOptions};
+use godot::classes::Json;
+use godot::prelude::*;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Value of the `prefers-color-scheme` media feature reported to the pages.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum PreferredColorScheme {
+    /// Follow the OS setting.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+static NEXT_MESSAGE_ID: AtomicI32 = AtomicI32::new(1);
+
+/// Returns a message id that is unique for the session, so methods sent by
+/// different nodes and by `purge_memory` never collide.
+pub(super) fn next_message_id() -> i32 {
+    NEXT_MESSAGE_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Returns whether `method` has the `Domain.method` form of the protocol,
+/// e.g. `Emulation.setGeolocationOverride`.
+fn is_valid_method(method: &str) -> bool {
+    let is_name = |name: &str| !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric());
+    method
+        .split_once('.')
+        .is_some_and(|(domain, name)| is_name(domain) && is_name(name))
+}
+
+/// Returns whether the coordinates are a valid position, as checked by
+/// `Emulation.setGeolocationOverride`.
+fn is_valid_geolocation(geolocation: &GeolocationOverride) -> bool {
+    (-90.0..=90.0).contains(&geolocation.latitude)
+        && (-180.0..=180.0).contains(&geolocation.longitude)
+        && geolocation.accuracy >= 0.0
+        && geolocation.accuracy.is_finite()
+}
+
+fn geolocation_override_params(geolocation: &GeolocationOverride) -> String {
+    format!(
+        r#"{{"latitude":{},"longitude":{},"accuracy":{}}}"#,
+        geolocation.latitude, geolocation.longitude, geolocation.accuracy
+    )
+}
+
+fn emulated_media_params(scheme: PreferredColorScheme) -> String {
+    // An empty value removes the emulation
+    let value = match scheme {
+        PreferredColorScheme::System => "",
+        PreferredColorScheme::Light => "light",
+        PreferredColorScheme::Dark => "dark",
+    };
+    format!(r#"{{"features":[{{"name":"prefers-color-scheme","value":"{value}"}}]}}"#)
+}
+
+/// Parses a raw protocol payload with Godot's JSON, so GDScript gets the
+/// same types as from `JSON.parse_string`. Returns an empty dictionary for
+/// payloads that are not JSON objects.
+pub(super) fn parse_json_dictionary(json: &str) -> VarDictionary {
+    if json.is_empty() {
+        return VarDictionary::new();
+    }
+    Json::parse_string(json)
+        .try_to::<VarDictionary>()
+        .unwrap_or_default()
+}
+
+impl CefTexture {
+    /// Sends `method` to the browser on behalf of GDScript. Returns the
+    /// message id, or `-1` when the method could not be sent.
+    pub(super) fn send_devtools_method(&mut self, method: &str, params: &VarDictionary) -> i64 {
+        if !crate::settings::is_devtools_protocol_allowed() {
+            godot::global::godot_warn!(
+                "[CefTexture] DevTools protocol messages are disabled by godot_cef/security/allow_devtools_protocol"
+            );
+            return -1;
+        }
+        if !is_valid_method(method) {
+            godot::global::godot_error!(
+                "[CefTexture] Invalid DevTools protocol method '{}', expected Domain.method",
+                method
+            );
+            return -1;
+        }
+
+        let json = Json::stringify(&params.to_variant()).to_string();
+        self.execute_devtools_method(method, &json)
+    }
+
+    /// Sends `method` with the JSON object `params`, regardless of
+    /// `allow_devtools_protocol`. Returns the message id, or `-1`.
+    pub(super) fn execute_devtools_method(&mut self, method: &str, params: &str) -> i64 {
+        let Some(browser) = self.app.browser.as_mut() else {
+            return -1;
+        };
+        let Some(host) = browser.host() else {
+            return -1;
+        };
+
+        let mut params =
+            cef::parse_json(Some(&CefString::from(params)), JsonParserOptions::default())
+                .and_then(|value| value.dictionary());
+
+        let message_id = host.execute_dev_tools_method(
+            next_message_id(),
+            Some(&CefString::from(method)),
+            params.as_mut(),
+        );
+        if message_id == 0 {
+            godot::global::godot_error!("[CefTexture] Failed to send DevTools method {}", method);
+            return -1;
+        }
+        message_id as i64
+    }
+
+    /// Sets the position reported to the pages and grants their geolocation
+    /// permission. Out-of-range coordinates are rejected.
+    pub(super) fn override_geolocation(&mut self, geolocation: GeolocationOverride) {
+        if !is_valid_geolocation(&geolocation) {
+            godot::global::godot_error!(
+                "[CefTexture] Invalid geolocation override: latitude {}, longitude {}, accuracy {}",
+                geolocation.latitude,
+                geolocation.longitude,
+                geolocation.accuracy
+            );
+            return;
+        }
+        if let Ok(mut current) = self.app.geolocation_override.write() {
+            *current = Some(geolocation);
+        }
+        self.apply_geolocation_override();
+    }
+
+    pub(super) fn clear_geolocation(&mut self) {
+        let was_active = self
+            .app
+            .geolocation_override
+            .write()
+            .is_ok_and(|mut current| current.take().is_some());
+        if was_active {
+            self.execute_devtools_method("Emulation.clearGeolocationOverride", "{}");
+        }
+    }
+
+    /// Sends the emulation state to the browser. Called when the browser is
+    /// created and whenever a main-frame load starts, so it also holds after
+    /// navigations.
+    pub(super) fn apply_emulation_overrides(&mut self) {
+        self.apply_geolocation_override();
+        if self.preferred_color_scheme != PreferredColorScheme::System {
+            self.apply_color_scheme();
+        }
+        if self.allow_autoplay {
+            self.apply_autoplay_activation();
+        }
+    }
+
+    /// Gives the page a user activation, as a click would, so its media can
+    /// start playing. Activation belongs to the document, so it is given
+    /// again on every navigation. `Runtime.evaluate` runs in the main frame,
+    /// so cross-origin iframes only benefit through `allow="autoplay"`.
+    pub(super) fn apply_autoplay_activation(&mut self) {
+        self.execute_devtools_method(
+            "Runtime.evaluate",
+            r#"{"expression":"void 0","userGesture":true}"#,
+        );
+    }
+
+    /// Sends `preferred_color_scheme` to the browser; `System` removes the
+    /// emulation.
+    pub(super) fn apply_color_scheme(&mut self) {
+        self.execute_devtools_method(
+            "Emulation.setEmulatedMedia",
+            &emulated_media_params(self.preferred_color_scheme),
+        );
+    }
+
+    fn apply_geolocation_override(&mut self) {
+        let geolocation = self
+            .app
+            .geolocation_override
+            .read()
+            .ok()
+            .and_then(|current| *current);
+        if let Some(geolocation) = geolocation {
+            self.execute_devtools_method(
+                "Emulation.setGeolocationOverride",
+                &geolocation_override_params(&geolocation),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_valid_method() {
+        assert!(is_valid_method("Emulation.setGeolocationOverride"));
+        assert!(is_valid_method("DOM.enable"));
+        assert!(!is_valid_method("Emulation"));
+        assert!(!is_valid_method(".enable"));
+        assert!(!is_valid_method("DOM."));
+        assert!(!is_valid_method("DOM.enable.now"));
+        assert!(!is_valid_method("DOM enable"));
+        assert!(!is_valid_method(""));
+    }
+
+    #[test]
+    fn test_geolocation_override() {
+        let position = |latitude, longitude, accuracy| GeolocationOverride {
+            latitude,
+            longitude,
+            accuracy,
+        };
+        assert!(is_valid_geolocation(&position(35.6812, 139.7671, 10.0)));
+        assert!(is_valid_geolocation(&position(-90.0, 180.0, 0.0)));
+        assert!(!is_valid_geolocation(&position(90.5, 0.0, 10.0)));
+        assert!(!is_valid_geolocation(&position(0.0, -180.5, 10.0)));
+        assert!(!is_valid_geolocation(&position(0.0, 0.0, -1.0)));
+        assert!(!is_valid_geolocation(&position(f64::NAN, 0.0, 10.0)));
+        assert!(!is_valid_geolocation(&position(0.0, 0.0, f64::INFINITY)));
+
+        assert_eq!(
+            geolocation_override_params(&position(35.5, -120.25, 5.0)),
+            r#"{"latitude":35.5,"longitude":-120.25,"accuracy":5}"#
+        );
+    }
+
+    #[test]
+    fn test_emulated_media_params() {
+        assert_eq!(
+            emulated_media_params(PreferredColorScheme::Dark),
+            r#"{"features":[{"name":"prefers-color-scheme","value":"dark"}]}"#
+        );
+        assert_eq!(
+            emulated_media_params(PreferredColorScheme::Light),
+            r#"{"features":[{"name":"prefers-color-scheme","value":"light"}]}"#
+        );
+        assert_eq!(
+            emulated_media_params(PreferredColorScheme::System),
+            r#"{"features":[{"name":"prefers-color-scheme","value":""}]}"#
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! Screen capture offered from Godot for CefTexture.
+//!
+//! A page's `getDisplayMedia()` never reaches the OS picker: the renderer
+//! helper turns it into `display_media_requested`, and the game answers with
+//! `offer_display_media`. An offered game viewport is streamed to the page
+//! by drawing its frames into a canvas whose `captureStream()` the page gets,
+//! at the browser frame rate.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use super::CefTexture;
+use cef::{ImplBrowser, ImplFrame, ImplListValue, ImplProcessMessage};
+use godot::classes::image::Format;
+use godot::prelude::*;
+
+use crate::browser::DisplayMediaEvent;
+
+/// Longest side of the frames streamed to the page, in pixels. Frames are
+/// copied through the renderer process, so larger viewports are scaled down.
+const MAX_FRAME_SIZE: i32 = 1280;
+
+/// What the game offers for a `getDisplayMedia()` call.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum DisplayMediaSource {
+    /// Reject the call with `NotAllowedError`.
+    #[default]
+    Deny,
+    /// Stream `display_media_viewport`, or the node's viewport.
+    GameViewport,
+}
+
+/// A `getDisplayMedia()` call, identified within its frame.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct PageRequest {
+    frame_id: String,
+    page_request_id: i32,
+}
+
+/// Requests waiting for an answer and the captures being streamed, by the
+/// request id given to GDScript.
+#[derive(Default)]
+pub(super) struct DisplayMediaState {
+    next_request_id: i64,
+    pending: HashMap<i64, PageRequest>,
+    captures: HashMap<i64, PageRequest>,
+    last_frame: Option<Instant>,
+}
+
+impl DisplayMediaState {
+    /// Records a request and returns its id.
+    fn request(&mut self, request: PageRequest) -> i64 {
+        self.next_request_id += 1;
+        self.pending.insert(self.next_request_id, request);
+        self.next_request_id
+    }
+
+    /// Removes a pending request to answer it.
+    fn take_pending(&mut self, request_id: i64) -> Option<PageRequest> {
+        self.pending.remove(&request_id)
+    }
+
+    /// Forgets the capture the page stopped.
+    fn stop(&mut self, request: &PageRequest) {
+        self.captures.retain(|_, capture| capture != request);
+    }
+
+    /// Forgets the requests and captures of a frame whose document went away.
+    fn release_frame(&mut self, frame_id: &str) {
+        self.pending
+            .retain(|_, request| request.frame_id != frame_id);
+        self.captures
+            .retain(|_, capture| capture.frame_id != frame_id);
+    }
+
+    /// Returns whether a frame is due at `now` for a frame rate of `max_fps`,
+    /// and if so records it as sent.
+    fn frame_due(&mut self, now: Instant, max_fps: i32) -> bool {
+        let interval = Duration::from_secs_f64(1.0 / max_fps.max(1) as f64);
+        if self
+            .last_frame
+            .is_some_and(|last| now.duration_since(last) < interval)
+        {
+            return false;
+        }
+        self.last_frame = Some(now);
+        true
+    }
+}
+
+/// Returns the size of the frames streamed for a viewport of `size`: the
+/// same aspect ratio, with the longest side at most `max_size`.
+fn frame_size(size: Vector2i, max_size: i32) -> Vector2i {
+    let longest = size.x.max(size.y);
+    if longest <= max_size {
+        return size;
+    }
+    let scale = max_size as f32 / longest as f32;
+    Vector2i::new(
+        ((size.x as f32 * scale).round() as i32).max(1),
+        ((size.y as f32 * scale).round() as i32).max(1),
+    )
+}
+
+impl CefTexture {
+    pub(super) fn process_display_media_event(&mut self, event: &DisplayMediaEvent) {
+        match event {
+            DisplayMediaEvent::Requested {
+                frame_id,
+                page_request_id,
+            } => {
+                let request_id = self.display_media.request(PageRequest {
+                    frame_id: frame_id.clone(),
+                    page_request_id: *page_request_id,
+                });
+                self.emit_browser_signal(
+                    "display_media_requested",
+                    &[("request_id", request_id.to_variant())],
+                );
+            }
+            DisplayMediaEvent::Stopped {
+                frame_id,
+                page_request_id,
+            } => self.display_media.stop(&PageRequest {
+                frame_id: frame_id.clone(),
+                page_request_id: *page_request_id,
+            }),
+            DisplayMediaEvent::FrameReleased { frame_id } => {
+                self.display_media.release_frame(frame_id)
+            }
+        }
+    }
+
+    pub(super) fn offer_display_media_impl(
+        &mut self,
+        request_id: i64,
+        source: DisplayMediaSource,
+    ) -> bool {
+        let Some(request) = self.display_media.take_pending(request_id) else {
+            godot::global::godot_warn!(
+                "[CefTexture] No pending display media request with id {}",
+                request_id
+            );
+            return false;
+        };
+
+        let size = match source {
+            DisplayMediaSource::Deny => None,
+            DisplayMediaSource::GameViewport => {
+                let size = self.display_media_image().map(|image| image.get_size());
+                if size.is_none() {
+                    godot::global::godot_warn!(
+                        "[CefTexture] Cannot offer the game viewport: it has no texture"
+                    );
+                }
+                size.map(|size| frame_size(size, MAX_FRAME_SIZE))
+            }
+        };
+
+        let sent = self.send_display_media_message(&request, "displayMediaResponse", |args| {
+            args.set_int(0, request.page_request_id);
+            args.set_bool(1, size.is_some() as _);
+            let size = size.unwrap_or_default();
+            args.set_int(2, size.x);
+            args.set_int(3, size.y);
+            args.set_int(4, self.get_max_fps());
+        });
+        if sent && size.is_some() {
+            self.display_media.captures.insert(request_id, request);
+        }
+        sent && size.is_some()
+    }
+
+    /// Sends the offered viewport to the pages capturing it, at the browser
+    /// frame rate.
+    pub(super) fn stream_display_media(&mut self) {
+        if self.display_media.captures.is_empty()
+            || !self
+                .display_media
+                .frame_due(Instant::now(), self.get_max_fps())
+        {
+            return;
+        }
+        let Some(mut image) = self.display_media_image() else {
+            return;
+        };
+
+        let size = frame_size(image.get_size(), MAX_FRAME_SIZE);
+        if image.get_format() != Format::RGBA8 {
+            image.convert(Format::RGBA8);
+        }
+        if size != image.get_size() {
+            image.resize(size.x, size.y);
+        }
+        let pixels = image.get_data();
+
+        let captures: Vec<PageRequest> = self.display_media.captures.values().cloned().collect();
+        for capture in captures {
+            // A binary value is moved into the message it is set on
+            let Some(mut binary_value) = cef::binary_value_create(Some(pixels.as_slice())) else {
+                return;
+            };
+            self.send_display_media_message(&capture, "displayMediaFrame", |args| {
+                args.set_int(0, capture.page_request_id);
+                args.set_int(1, size.x);
+                args.set_int(2, size.y);
+                args.set_binary(3, Some(&mut binary_value));
+            });
+        }
+    }
+
+    /// Forgets all requests and captures, e.g. when the browser is released.
+    pub(super) fn release_display_media(&mut self) {
+        self.display_media = DisplayMediaState::default();
+    }
+
+    /// Returns the current image of the viewport offered to pages.
+    fn display_media_image(&self) -> Option<Gd<godot::classes::Image>> {
+        let viewport = self
+            .display_media_viewport
+            .clone()
+            .or_else(|| self.base().get_viewport())?;
+        viewport.get_texture()?.get_image()
+    }
+
+    /// Sends a message to the frame that made `request`. Returns `false` if
+    /// the frame is gone.
+    fn send_display_media_message(
+        &self,
+        request: &PageRequest,
+        route: &str,
+        fill: impl FnOnce(&mut cef::ListValue),
+    ) -> bool {
+        let Some(browser) = self.app.browser.as_ref() else {
+            return false;
+        };
+        let frame_id = cef::CefString::from(request.frame_id.as_str());
+        let Some(frame) = browser.frame_by_identifier(Some(&frame_id)) else {
+            return false;
+        };
+        let route = cef::CefStringUtf16::from(route);
+        let Some(mut process_message) = cef::process_message_create(Some(&route)) else {
+            return false;
+        };
+        let Some(mut argument_list) = process_message.argument_list() else {
+            return false;
+        };
+        fill(&mut argument_list);
+        frame.send_process_message(cef::ProcessId::RENDERER, Some(&mut process_message));
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page_request(frame_id: &str, page_request_id: i32) -> PageRequest {
+        PageRequest {
+            frame_id: frame_id.to_string(),
+            page_request_id,
+        }
+    }
+
+    #[test]
+    fn test_requests_and_captures() {
+        let mut state = DisplayMediaState::default();
+        let first = state.request(page_request("main", 1));
+        let second = state.request(page_request("child", 1));
+        assert_ne!(first, second);
+
+        assert_eq!(state.take_pending(first), Some(page_request("main", 1)));
+        assert_eq!(state.take_pending(first), None);
+        state.captures.insert(first, page_request("main", 1));
+
+        // Stopping matches the frame as well as the page's id
+        state.stop(&page_request("child", 1));
+        assert!(state.captures.contains_key(&first));
+        state.stop(&page_request("main", 1));
+        assert!(state.captures.is_empty());
+
+        state.captures.insert(first, page_request("main", 1));
+        state.release_frame("child");
+        assert!(state.pending.is_empty());
+        assert_eq!(state.captures.len(), 1);
+        state.release_frame("main");
+        assert!(state.captures.is_empty());
+    }
+
+    #[test]
+    fn test_frame_due() {
+        let mut state = DisplayMediaState::default();
+        let start = Instant::now();
+        assert!(state.frame_due(start, 30));
+        assert!(!state.frame_due(start + Duration::from_millis(20), 30));
+        assert!(state.frame_due(start + Duration::from_millis(34), 30));
+        // Follows frame rate changes, e.g. background mode
+        assert!(!state.frame_due(start + Duration::from_millis(500), 1));
+        assert!(state.frame_due(start + Duration::from_millis(1034), 1));
+    }
+
+    #[test]
+    fn test_frame_size() {
+        assert_eq!(
+            frame_size(Vector2i::new(800, 600), 1280),
+            Vector2i::new(800, 600)
+        );
+        assert_eq!(
+            frame_size(Vector2i::new(1920, 1080), 1280),
+            Vector2i::new(1280, 720)
+        );
+        assert_eq!(
+            frame_size(Vector2i::new(1000, 4000), 1280),
+            Vector2i::new(320, 1280)
+        );
+        assert_eq!(
+            frame_size(Vector2i::new(4000, 1), 1280),
+            Vector2i::new(1280, 1)
+        );
+    }
+}
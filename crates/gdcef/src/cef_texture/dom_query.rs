@@ -0,0 +1,35 @@
+//! DOM inspection without DevTools: `dump_dom_async` and `count_elements`.
+//!
+//! The query is sent to the main frame's render process, which passes the
+//! selector to the page helper as a string argument, so selectors with
+//! quotes or backslashes never end up inside a script. The answer comes
+//! back as a `domQueryResult` message and is emitted as `dom_dumped` or
+//! `elements_counted`.
+
+use super::CefTexture;
+use cef::{CefStringUtf16, ImplBrowser, ImplFrame, ImplListValue, ImplProcessMessage};
+use cef_app::DomQueryKind;
+
+impl CefTexture {
+    /// Sends a DOM query for `selector` to the page. Returns `false` if
+    /// there is no page to ask.
+    pub(super) fn send_dom_query(&mut self, kind: DomQueryKind, selector: &str) -> bool {
+        let Some(frame) = self.app.browser.as_ref().and_then(|b| b.main_frame()) else {
+            godot::global::godot_warn!("[CefTexture] Cannot query the DOM: no main frame");
+            return false;
+        };
+
+        let route = CefStringUtf16::from("domQuery");
+        let Some(mut process_message) = cef::process_message_create(Some(&route)) else {
+            return false;
+        };
+        let Some(argument_list) = process_message.argument_list() else {
+            return false;
+        };
+        argument_list.set_int(0, kind as i32);
+        argument_list.set_string(1, Some(&CefStringUtf16::from(selector)));
+        argument_list.set_int(2, crate::settings::get_dom_dump_max_length());
+        frame.send_process_message(cef::ProcessId::RENDERER, Some(&mut process_message));
+        true
+    }
+}
@@ -0,0 +1,240 @@
+//! Files dragged out of the page.
+//!
+//! A drag of an `<a download>` link or a file from a web app only carries
+//! the file's contents inside CEF. They are written to
+//! `user://godot_cef_dragtmp/` on a worker thread, capped by
+//! `godot_cef/input/max_drag_file_size_mb`, and reported with
+//! `drag_files_ready` once written. The files are deleted when the drag ends.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use cef::{CefStringUtf16, ImplDragData};
+use godot::classes::ProjectSettings;
+use godot::prelude::*;
+
+use super::CefTexture;
+use crate::browser::{DragEvent, DragFileSource, EventKind, EventQueuesHandle, ExtractedDragFiles};
+use crate::drag::DragDataInfo;
+use crate::settings;
+
+/// Folder the dragged files are written to, one subfolder per drag.
+const DRAG_TMP_DIR: &str = "user://godot_cef_dragtmp";
+
+/// Name used when the page gives the dragged file none.
+const FALLBACK_FILE_NAME: &str = "download";
+
+#[derive(Default)]
+pub(super) struct DragFilesState {
+    next_id: u64,
+    current: Option<DragFileExtraction>,
+}
+
+struct DragFileExtraction {
+    id: u64,
+    dir: PathBuf,
+    /// Set when the drag ended, so a worker still writing removes its files.
+    cancelled: Arc<AtomicBool>,
+    /// Payload of the drag, completed with the paths once written.
+    drag_info: Gd<DragDataInfo>,
+}
+
+impl CefTexture {
+    /// Starts writing the files of a drag started in the page.
+    pub(super) fn start_drag_file_extraction(
+        &mut self,
+        drag_info: &Gd<DragDataInfo>,
+        source: DragFileSource,
+        file_names: Vec<String>,
+    ) {
+        self.cleanup_drag_files();
+        let Some(event_queues) = self.app.event_queues.clone() else {
+            return;
+        };
+        let max_size = settings::get_max_drag_file_size();
+
+        let id = self.drag_files.next_id;
+        self.drag_files.next_id += 1;
+        let folder = format!(
+            "{}/{}-{}",
+            DRAG_TMP_DIR,
+            self.base().instance_id().to_i64(),
+            id
+        );
+        let dir = PathBuf::from(
+            ProjectSettings::singleton()
+                .globalize_path(&folder)
+                .to_string(),
+        );
+        let cancelled = Arc::new(AtomicBool::new(false));
+
+        self.drag_files.current = Some(DragFileExtraction {
+            id,
+            dir: dir.clone(),
+            cancelled: cancelled.clone(),
+            drag_info: drag_info.clone(),
+        });
+
+        let spawned = std::thread::Builder::new()
+            .name("godot-cef-drag-files".into())
+            .spawn(move || {
+                let files = extract_drag_files(&source, &file_names, &dir, max_size);
+                if cancelled.load(Ordering::Acquire) {
+                    let _ = std::fs::remove_dir_all(&dir);
+                    return;
+                }
+                push_files_ready(&event_queues, id, files);
+            });
+        if let Err(err) = spawned {
+            godot::global::godot_warn!("[CefTexture] Could not extract dragged files: {}", err);
+            self.drag_files.current = None;
+        }
+    }
+
+    /// Completes the drag payload and emits `drag_files_ready` once the
+    /// files of the current drag are written.
+    pub(super) fn finish_drag_file_extraction(&mut self, id: u64, files: &ExtractedDragFiles) {
+        let Some(extraction) = self.drag_files.current.as_mut() else {
+            return;
+        };
+        if extraction.id != id {
+            return;
+        }
+
+        if let Some(size) = files.oversized {
+            godot::global::godot_warn!(
+                "[CefTexture] Dragged file of {} bytes exceeds godot_cef/input/max_drag_file_size_mb, not extracted",
+                size
+            );
+        }
+
+        let paths: PackedStringArray = files.paths.iter().map(GString::from).collect();
+        extraction.drag_info.bind_mut().file_paths = paths.clone();
+        self.emit_browser_signal("drag_files_ready", &[("paths", paths.to_variant())]);
+    }
+
+    /// Deletes the files of the current drag, also when they are still being
+    /// written.
+    pub(super) fn cleanup_drag_files(&mut self) {
+        let Some(extraction) = self.drag_files.current.take() else {
+            return;
+        };
+        extraction.cancelled.store(true, Ordering::Release);
+        if extraction.dir.exists() {
+            let _ = std::fs::remove_dir_all(&extraction.dir);
+        }
+    }
+}
+
+fn push_files_ready(event_queues: &EventQueuesHandle, id: u64, files: ExtractedDragFiles) {
+    if let Ok(mut queues) = event_queues.lock() {
+        let outcome = queues
+            .drag_events
+            .push_back(DragEvent::FilesReady { id, files });
+        queues.record(EventKind::Drag, outcome);
+    }
+}
+
+/// Writes the contents carried by the drag to `dir` and collects the paths
+/// of the dragged files. Files that already exist on disk are reported where
+/// they are. Runs on a worker thread.
+fn extract_drag_files(
+    source: &DragFileSource,
+    file_names: &[String],
+    dir: &Path,
+    max_size: u64,
+) -> ExtractedDragFiles {
+    let mut files = ExtractedDragFiles::default();
+    let (on_disk, virtual_names): (Vec<&String>, Vec<&String>) =
+        file_names.iter().partition(|name| {
+            Path::new(name.as_str()).is_absolute() && Path::new(name.as_str()).is_file()
+        });
+    files.paths.extend(on_disk.into_iter().cloned());
+
+    // Content a page drags out, e.g. an `<a download>` link
+    let size = source.0.file_contents(None) as u64;
+    if size == 0 {
+        return files;
+    }
+    if !fits_size_limit(size, max_size) {
+        files.oversized = Some(size);
+        return files;
+    }
+
+    let name = sanitize_file_name(virtual_names.first().map_or("", |name| name.as_str()));
+    let path = dir.join(name);
+    if std::fs::create_dir_all(dir).is_err() {
+        return files;
+    }
+    let path_string = path.to_string_lossy().into_owned();
+    let Some(mut writer) =
+        cef::stream_writer_create_for_file(Some(&CefStringUtf16::from(path_string.as_str())))
+    else {
+        return files;
+    };
+    let written = source.0.file_contents(Some(&mut writer));
+    drop(writer);
+
+    if written as u64 == size {
+        files.paths.push(path_string);
+    } else {
+        let _ = std::fs::remove_file(&path);
+    }
+    files
+}
+
+fn fits_size_limit(size: u64, max_size: u64) -> bool {
+    max_size > 0 && size <= max_size
+}
+
+/// Turns a file name chosen by the page into one safe to create in the
+/// drag folder: no folders, no characters reserved on any platform.
+fn sanitize_file_name(name: &str) -> String {
+    let base = name.rsplit(['/', '\\']).next().unwrap_or("");
+    let cleaned: String = base
+        .chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*') {
+                '_'
+            } else {
+                c
+            }
+        })
+        .collect();
+    let cleaned = cleaned.trim().trim_end_matches('.').trim_start_matches('.');
+
+    if cleaned.is_empty() {
+        FALLBACK_FILE_NAME.to_string()
+    } else {
+        cleaned.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("report.pdf"), "report.pdf");
+        assert_eq!(sanitize_file_name("../../secret.txt"), "secret.txt");
+        assert_eq!(sanitize_file_name("C:\\Users\\me\\a.txt"), "a.txt");
+        assert_eq!(sanitize_file_name("what?.txt"), "what_.txt");
+        assert_eq!(sanitize_file_name("line\nbreak.txt"), "line_break.txt");
+        assert_eq!(sanitize_file_name(".hidden"), "hidden");
+        assert_eq!(sanitize_file_name(".."), FALLBACK_FILE_NAME);
+        assert_eq!(sanitize_file_name(""), FALLBACK_FILE_NAME);
+        assert_eq!(sanitize_file_name("folder/"), FALLBACK_FILE_NAME);
+    }
+
+    #[test]
+    fn test_fits_size_limit() {
+        let limit = 100 * 1024 * 1024;
+        assert!(fits_size_limit(1, limit));
+        assert!(fits_size_limit(limit, limit));
+        assert!(!fits_size_limit(limit + 1, limit));
+        // 0 disables extraction
+        assert!(!fits_size_limit(1, 0));
+    }
+}
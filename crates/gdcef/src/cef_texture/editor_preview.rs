@@ -0,0 +1,169 @@
+//! Previewing pages in the editor.
+//!
+//! In the editor a `CefTexture` draws a placeholder with its URL. With
+//! `godot_cef/editor/enable_preview` it shows the page instead, through a
+//! browser created like at runtime but limited to
+//! `EDITOR_PREVIEW_FRAME_RATE` frames per second and to local pages. The
+//! editor has its own CEF, with its own data directory, so it does not get
+//! in the way of the game launched from it. Input is never forwarded.
+
+use super::CefTexture;
+use godot::classes::notify::ControlNotification;
+use godot::classes::texture_rect::ExpandMode;
+use godot::classes::{ProjectSettings, Texture2D, ThemeDb};
+use godot::global::HorizontalAlignment;
+use godot::prelude::*;
+use std::f32::consts::TAU;
+
+use crate::security::NavigationAllowlist;
+use crate::{cef_init, frame_scheduler, godot_protocol};
+
+/// Frame rate of previews, which only need to show the layout.
+pub(super) const EDITOR_PREVIEW_FRAME_RATE: i32 = 5;
+
+/// Pages a preview may navigate to.
+const EDITOR_PREVIEW_ALLOWLIST: [&str; 3] = ["res://*", "user://*", "about:*"];
+
+const PLACEHOLDER_BACKGROUND: Color = Color::from_rgba(0.13, 0.14, 0.16, 1.0);
+const PLACEHOLDER_FOREGROUND: Color = Color::from_rgba(0.62, 0.65, 0.7, 1.0);
+
+impl CefTexture {
+    pub(super) fn on_editor_notification(&mut self, what: ControlNotification) {
+        match what {
+            ControlNotification::READY => {
+                let callable = self.base().callable("_on_project_settings_changed");
+                ProjectSettings::singleton().connect("settings_changed", &callable);
+            }
+            ControlNotification::ENTER_TREE => self.update_editor_preview(),
+            ControlNotification::EXIT_TREE | ControlNotification::PREDELETE => {
+                self.stop_editor_preview();
+            }
+            ControlNotification::PROCESS if self.editor_preview => {
+                self.process_editor_preview();
+            }
+            ControlNotification::DRAW if !self.editor_preview => {
+                self.draw_editor_placeholder();
+            }
+            // The page's texture must not end up in the scene file
+            ControlNotification::EDITOR_PRE_SAVE if self.editor_preview => {
+                self.editor_saved_texture = self.base().get_texture();
+                self.base_mut().set_texture(Gd::<Texture2D>::null_arg());
+            }
+            ControlNotification::EDITOR_POST_SAVE => {
+                if let Some(texture) = self.editor_saved_texture.take() {
+                    self.base_mut().set_texture(&texture);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Starts or stops the preview to follow the project setting.
+    pub(super) fn update_editor_preview(&mut self) {
+        let enabled = self.base().is_inside_tree() && crate::settings::is_editor_preview_enabled();
+        if enabled == self.editor_preview {
+            return;
+        }
+        if enabled {
+            self.start_editor_preview();
+        } else {
+            self.stop_editor_preview();
+        }
+    }
+
+    /// Takes a hold on CEF. The browser is created on the next process
+    /// notification with a valid size.
+    fn start_editor_preview(&mut self) {
+        if let Err(e) = cef_init::cef_retain(self.base().instance_id()) {
+            godot::global::godot_error!("[CefTexture] {}", e);
+            return;
+        }
+        self.editor_preview = true;
+        if let Ok(mut allowlist) = self.app.navigation_allowlist.write() {
+            *allowlist = NavigationAllowlist::new(EDITOR_PREVIEW_ALLOWLIST);
+        }
+        self.base_mut().set_expand_mode(ExpandMode::IGNORE_SIZE);
+        self.base_mut().set_process(true);
+        self.base_mut().queue_redraw();
+    }
+
+    fn stop_editor_preview(&mut self) {
+        if !std::mem::take(&mut self.editor_preview) {
+            return;
+        }
+        self.release_browser();
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+        {
+            self.popup_texture_2d_rd = None;
+        }
+        self.base_mut().set_texture(Gd::<Texture2D>::null_arg());
+        self.base_mut().set_process(false);
+        self.release_cef();
+        self.base_mut().queue_redraw();
+    }
+
+    /// The part of `on_process` a page without input needs.
+    fn process_editor_preview(&mut self) {
+        if self.app.browser.is_none() {
+            let size = self.base().get_size();
+            if size.x > 0.0 && size.y > 0.0 {
+                self.create_browser();
+            }
+        }
+
+        self.handle_max_fps_change();
+        _ = self.handle_size_change();
+        self.update_texture();
+        frame_scheduler::pump_message_loop();
+        self.request_external_begin_frame();
+        self.process_all_event_queues();
+        godot_protocol::process_pending_scheme_requests();
+    }
+
+    /// Draws a globe and the URL in place of the page.
+    fn draw_editor_placeholder(&mut self) {
+        let size = self.base().get_size();
+        if size.x <= 0.0 || size.y <= 0.0 {
+            return;
+        }
+        let url = self.url.clone();
+        let mut base = self.base_mut();
+        base.draw_rect(Rect2::new(Vector2::ZERO, size), PLACEHOLDER_BACKGROUND);
+
+        let radius = (size.x.min(size.y) * 0.15).clamp(8.0, 48.0);
+        let center = Vector2::new(size.x / 2.0, size.y / 2.0 - radius * 0.5);
+        base.draw_arc_ex(center, radius, 0.0, TAU, 48, PLACEHOLDER_FOREGROUND)
+            .width(2.0)
+            .antialiased(true)
+            .done();
+        let meridian: PackedVector2Array = (0..=48)
+            .map(|i| {
+                let angle = i as f32 / 48.0 * TAU;
+                center + Vector2::new(radius * 0.45 * angle.cos(), radius * angle.sin())
+            })
+            .collect();
+        base.draw_polyline_ex(&meridian, PLACEHOLDER_FOREGROUND)
+            .width(2.0)
+            .antialiased(true)
+            .done();
+        base.draw_line_ex(
+            center - Vector2::new(radius, 0.0),
+            center + Vector2::new(radius, 0.0),
+            PLACEHOLDER_FOREGROUND,
+        )
+        .width(2.0)
+        .done();
+
+        let mut theme = ThemeDb::singleton();
+        if let Some(font) = theme.get_fallback_font() {
+            let font_size = theme.get_fallback_font_size();
+            let baseline = center.y + radius + 8.0 + font_size as f32;
+            base.draw_string_ex(&font, Vector2::new(4.0, baseline), &url)
+                .alignment(HorizontalAlignment::CENTER)
+                .width(size.x - 8.0)
+                .font_size(font_size)
+                .modulate(PLACEHOLDER_FOREGROUND)
+                .done();
+        }
+    }
+}
@@ -0,0 +1,435 @@
+//! Gamepad navigation for CefTexture.
+//!
+//! With `gamepad_navigation`, the focused node browses the page with a
+//! controller, through the input actions of the `godot_cef/gamepad/*`
+//! project settings. Only joypad events trigger them, so keyboard keys bound
+//! to the same actions still reach the page as keys.
+//!
+//! - Spatial navigation sends the directions as arrow keys, repeated while
+//!   held, accept as Enter and cancel as Escape. Arrow keys move the focus
+//!   between elements when `godot_cef/input/enable_spatial_navigation` is
+//!   enabled; otherwise they scroll.
+//! - The virtual cursor is moved by the directions each frame, faster the
+//!   longer they are held, and clicks with accept like a mouse.
+//!
+//! In both modes the scroll actions send wheel events.
+
+use std::time::{Duration, Instant};
+
+use super::CefTexture;
+use cef::ImplBrowserHost;
+use godot::classes::control::MouseFilter;
+use godot::classes::{InputEvent, InputEventJoypadMotion, InputMap, TextureRect};
+use godot::global::{JoyAxis, Key, MouseButton};
+use godot::prelude::*;
+
+use crate::input::{self, KeyInput};
+
+/// How the gamepad browses the page.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum GamepadNavigationMode {
+    /// Directions move between elements like arrow keys.
+    #[default]
+    SpatialNavigation,
+    /// Directions move a cursor that clicks like a mouse.
+    VirtualCursor,
+}
+
+/// Delay before a held direction repeats, and the interval of the repeats.
+const REPEAT_DELAY: Duration = Duration::from_millis(400);
+const REPEAT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The cursor speeds up to this multiple of `gamepad_cursor_speed` over
+/// `CURSOR_ACCELERATION_TIME` of continuous movement.
+const CURSOR_MAX_ACCELERATION: f32 = 3.0;
+const CURSOR_ACCELERATION_TIME: Duration = Duration::from_secs(1);
+
+/// Wheel notches per second at full deflection of the scroll actions.
+const SCROLL_NOTCHES_PER_SECOND: f32 = 15.0;
+
+// Indices into the actions, in the order of `settings::GAMEPAD_ACTIONS`
+const UP: usize = 0;
+const DOWN: usize = 1;
+const LEFT: usize = 2;
+const RIGHT: usize = 3;
+const ACCEPT: usize = 4;
+const CANCEL: usize = 5;
+const SCROLL_UP: usize = 6;
+const SCROLL_DOWN: usize = 7;
+const SCROLL_LEFT: usize = 8;
+const SCROLL_RIGHT: usize = 9;
+const ACTION_COUNT: usize = 10;
+
+/// Returns the arrow key of a direction action.
+fn direction_key(action: usize) -> Key {
+    match action {
+        UP => Key::UP,
+        DOWN => Key::DOWN,
+        LEFT => Key::LEFT,
+        _ => Key::RIGHT,
+    }
+}
+
+/// Repeats the arrow key of a held direction.
+#[derive(Default)]
+struct DirectionRepeat {
+    /// The held direction and when it next repeats.
+    held: Option<(usize, Instant)>,
+}
+
+impl DirectionRepeat {
+    fn press(&mut self, direction: usize, now: Instant) {
+        self.held = Some((direction, now + REPEAT_DELAY));
+    }
+
+    fn release(&mut self, direction: usize) {
+        if self.held.is_some_and(|(held, _)| held == direction) {
+            self.held = None;
+        }
+    }
+
+    /// Returns the direction to repeat now, if any.
+    fn poll(&mut self, now: Instant) -> Option<usize> {
+        let (direction, next) = self.held?;
+        if now < next {
+            return None;
+        }
+        self.held = Some((direction, next + REPEAT_INTERVAL));
+        Some(direction)
+    }
+}
+
+/// Returns the cursor speed in pixels per second after moving for
+/// `moving_for`, easing in to the maximum acceleration.
+fn cursor_speed(base_speed: f32, moving_for: Duration) -> f32 {
+    let t = (moving_for.as_secs_f32() / CURSOR_ACCELERATION_TIME.as_secs_f32()).min(1.0);
+    base_speed * (1.0 + (CURSOR_MAX_ACCELERATION - 1.0) * t * t)
+}
+
+/// Returns the vector of four action strengths, at most of length 1.
+fn stick_vector(up: f32, down: f32, left: f32, right: f32) -> Vector2 {
+    Vector2::new(right - left, down - up).limit_length(Some(1.0))
+}
+
+#[derive(Default)]
+pub(super) struct GamepadState {
+    /// Actions by index, `None` for actions missing from the input map.
+    /// Resolved on first use.
+    actions: Option<Vec<Option<StringName>>>,
+    strengths: [f32; ACTION_COUNT],
+    pressed: [bool; ACTION_COUNT],
+    repeat: DirectionRepeat,
+    /// Virtual cursor position in local coordinates.
+    cursor: Option<Vector2>,
+    moving_since: Option<Instant>,
+    cursor_sprite: Option<Gd<TextureRect>>,
+}
+
+/// Returns the input actions, creating the default scroll actions with the
+/// right stick if the project does not define them.
+fn resolve_actions() -> Vec<Option<StringName>> {
+    let mut input_map = InputMap::singleton();
+    crate::settings::get_gamepad_actions()
+        .into_iter()
+        .zip(crate::settings::GAMEPAD_ACTIONS)
+        .enumerate()
+        .map(|(index, (name, (_, _, default)))| {
+            if name.is_empty() {
+                return None;
+            }
+            let action = StringName::from(name.as_str());
+            if !input_map.has_action(&action) {
+                let (axis, value) = match index {
+                    SCROLL_UP if name == default => (JoyAxis::RIGHT_Y, -1.0),
+                    SCROLL_DOWN if name == default => (JoyAxis::RIGHT_Y, 1.0),
+                    SCROLL_LEFT if name == default => (JoyAxis::RIGHT_X, -1.0),
+                    SCROLL_RIGHT if name == default => (JoyAxis::RIGHT_X, 1.0),
+                    _ => {
+                        godot::global::godot_warn!(
+                            "[CefTexture] Gamepad navigation action '{}' is not in the input map",
+                            name
+                        );
+                        return None;
+                    }
+                };
+                input_map.add_action(&action);
+                let mut event = InputEventJoypadMotion::new_gd();
+                // All devices
+                event.set_device(-1);
+                event.set_axis(axis);
+                event.set_axis_value(value);
+                input_map.action_add_event(&action, &event);
+            }
+            Some(action)
+        })
+        .collect()
+}
+
+impl CefTexture {
+    /// Handles a joypad event of a gamepad navigation action. Returns true
+    /// if it was consumed.
+    pub(super) fn handle_gamepad_event(&mut self, event: &Gd<InputEvent>) -> bool {
+        if !self.gamepad_navigation
+            || !(event.is_class("InputEventJoypadButton")
+                || event.is_class("InputEventJoypadMotion"))
+            || !self.base().has_focus()
+        {
+            return false;
+        }
+        let actions = self
+            .gamepad
+            .actions
+            .get_or_insert_with(resolve_actions)
+            .clone();
+
+        let now = Instant::now();
+        let mut consumed = false;
+        for (index, action) in actions.iter().enumerate() {
+            let Some(action) = action else {
+                continue;
+            };
+            if !event.is_action(action) {
+                continue;
+            }
+            consumed = true;
+            self.gamepad.strengths[index] = event.get_action_strength(action);
+            let pressed = event.is_action_pressed_ex(action).allow_echo(true).done();
+            if pressed == std::mem::replace(&mut self.gamepad.pressed[index], pressed) {
+                continue;
+            }
+            self.on_gamepad_action(index, pressed, now);
+        }
+
+        if consumed && let Some(mut viewport) = self.base().get_viewport() {
+            viewport.set_input_as_handled();
+        }
+        consumed
+    }
+
+    /// Acts on an action that was just pressed or released.
+    fn on_gamepad_action(&mut self, action: usize, pressed: bool, now: Instant) {
+        let cursor_mode = self.gamepad_navigation_mode == GamepadNavigationMode::VirtualCursor;
+        match action {
+            UP | DOWN | LEFT | RIGHT if !cursor_mode => {
+                if pressed {
+                    self.send_gamepad_key(direction_key(action), true);
+                    self.gamepad.repeat.press(action, now);
+                } else {
+                    self.gamepad.repeat.release(action);
+                }
+            }
+            ACCEPT if cursor_mode => self.send_gamepad_click(pressed, now),
+            ACCEPT => self.send_gamepad_key(Key::ENTER, pressed),
+            CANCEL => self.send_gamepad_key(Key::ESCAPE, pressed),
+            _ => {}
+        }
+    }
+
+    /// Repeats held directions, moves the virtual cursor and scrolls. Called
+    /// every frame.
+    pub(super) fn update_gamepad_navigation(&mut self) {
+        let cursor_mode = self.gamepad_navigation_mode == GamepadNavigationMode::VirtualCursor;
+        if !self.gamepad_navigation || !self.base().has_focus() {
+            // Held actions are forgotten, as their releases go elsewhere
+            self.gamepad.strengths = Default::default();
+            self.gamepad.pressed = Default::default();
+            self.gamepad.repeat = Default::default();
+            self.gamepad.moving_since = None;
+            self.update_gamepad_cursor_sprite(false);
+            return;
+        }
+        let now = Instant::now();
+        let delta = self.base().get_process_delta_time() as f32;
+        let strengths = self.gamepad.strengths;
+
+        if cursor_mode {
+            let direction = stick_vector(
+                strengths[UP],
+                strengths[DOWN],
+                strengths[LEFT],
+                strengths[RIGHT],
+            );
+            if direction == Vector2::ZERO {
+                self.gamepad.moving_since = None;
+            } else {
+                let moving_for = now - *self.gamepad.moving_since.get_or_insert(now);
+                let speed = cursor_speed(self.gamepad_cursor_speed, moving_for);
+                let size = self.base().get_size();
+                let cursor = self.gamepad_cursor() + direction * speed * delta;
+                self.gamepad.cursor = Some(cursor.clamp(Vector2::ZERO, size));
+                self.send_gamepad_move();
+            }
+        } else if let Some(direction) = self.gamepad.repeat.poll(now) {
+            self.send_gamepad_key(direction_key(direction), true);
+        }
+
+        let scroll = stick_vector(
+            strengths[SCROLL_UP],
+            strengths[SCROLL_DOWN],
+            strengths[SCROLL_LEFT],
+            strengths[SCROLL_RIGHT],
+        );
+        if scroll != Vector2::ZERO {
+            let position = if cursor_mode {
+                self.gamepad_cursor()
+            } else {
+                self.base().get_size() / 2.0
+            };
+            self.inject_wheel(position, scroll * SCROLL_NOTCHES_PER_SECOND * delta);
+        }
+
+        self.update_gamepad_cursor_sprite(cursor_mode);
+    }
+
+    /// Frees the cursor sprite with the node's other children.
+    pub(super) fn release_gamepad_navigation(&mut self) {
+        if let Some(mut sprite) = self.gamepad.cursor_sprite.take() {
+            sprite.queue_free();
+        }
+    }
+
+    /// Returns the virtual cursor position, starting at the center.
+    fn gamepad_cursor(&mut self) -> Vector2 {
+        let center = self.base().get_size() / 2.0;
+        *self.gamepad.cursor.get_or_insert(center)
+    }
+
+    fn send_gamepad_key(&mut self, keycode: Key, pressed: bool) {
+        let Some(host) = self.injection_host() else {
+            return;
+        };
+        host.set_focus(true as _);
+        let key = KeyInput {
+            keycode,
+            unicode: 0,
+            pressed,
+            echo: false,
+            modifiers: 0,
+        };
+        input::send_key_inputs(&host, [key], false);
+    }
+
+    /// Moves the mouse to the virtual cursor, dragging while accept is held.
+    fn send_gamepad_move(&mut self) {
+        let position = self.gamepad_cursor();
+        let buttons = if self.gamepad.pressed[ACCEPT] {
+            input::mouse_button_type(MouseButton::LEFT).map_or(0, |(_, flag)| flag)
+        } else {
+            0
+        };
+        let Some(mouse_event) = self.injected_mouse_event(position, buttons) else {
+            return;
+        };
+        if let Some(host) = self.injection_host() {
+            input::send_mouse_move(&host, &mouse_event);
+        }
+    }
+
+    fn send_gamepad_click(&mut self, pressed: bool, now: Instant) {
+        let position = self.gamepad_cursor();
+        let Some((button_type, flag)) = input::mouse_button_type(MouseButton::LEFT) else {
+            return;
+        };
+        let Some(mouse_event) = self.injected_mouse_event(position, if pressed { flag } else { 0 })
+        else {
+            return;
+        };
+        let click_count = if pressed {
+            self.click_counter.press(MouseButton::LEFT, position, now)
+        } else {
+            self.click_counter.release(MouseButton::LEFT)
+        };
+        let mouse_up = !pressed;
+        if let Some(host) = self.injection_host() {
+            host.send_mouse_click_event(
+                Some(&mouse_event),
+                button_type,
+                mouse_up as i32,
+                click_count,
+            );
+        }
+    }
+
+    /// Shows `gamepad_cursor_texture` at the virtual cursor while it is in
+    /// use.
+    fn update_gamepad_cursor_sprite(&mut self, visible: bool) {
+        let texture = self.gamepad_cursor_texture.clone().filter(|_| visible);
+        let Some(texture) = texture else {
+            if let Some(sprite) = self.gamepad.cursor_sprite.as_mut() {
+                sprite.set_visible(false);
+            }
+            return;
+        };
+
+        let position = self.gamepad_cursor();
+        let mut sprite = match self.gamepad.cursor_sprite.clone() {
+            Some(sprite) => sprite,
+            None => {
+                let mut sprite = TextureRect::new_alloc();
+                sprite.set_mouse_filter(MouseFilter::IGNORE);
+                self.base_mut().add_child(&sprite);
+                self.gamepad.cursor_sprite = Some(sprite.clone());
+                sprite
+            }
+        };
+        sprite.set_texture(&texture);
+        sprite.set_position(position);
+        sprite.set_visible(true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_direction_repeat() {
+        let mut repeat = DirectionRepeat::default();
+        let start = Instant::now();
+        assert_eq!(repeat.poll(start), None);
+
+        repeat.press(DOWN, start);
+        assert_eq!(repeat.poll(start + Duration::from_millis(399)), None);
+        assert_eq!(repeat.poll(start + REPEAT_DELAY), Some(DOWN));
+        assert_eq!(repeat.poll(start + REPEAT_DELAY), None);
+        let next = start + REPEAT_DELAY + REPEAT_INTERVAL;
+        assert_eq!(repeat.poll(next), Some(DOWN));
+
+        // Releasing another direction keeps the repeat
+        repeat.release(UP);
+        assert_eq!(repeat.poll(next + REPEAT_INTERVAL), Some(DOWN));
+        repeat.release(DOWN);
+        assert_eq!(repeat.poll(next + REPEAT_INTERVAL * 2), None);
+
+        // A new direction replaces the held one
+        repeat.press(LEFT, start);
+        repeat.press(RIGHT, start);
+        assert_eq!(repeat.poll(start + REPEAT_DELAY), Some(RIGHT));
+    }
+
+    #[test]
+    fn test_cursor_speed() {
+        assert_eq!(cursor_speed(400.0, Duration::ZERO), 400.0);
+        let half = cursor_speed(400.0, CURSOR_ACCELERATION_TIME / 2);
+        assert!(half > 400.0 && half < 400.0 * CURSOR_MAX_ACCELERATION);
+        assert_eq!(
+            cursor_speed(400.0, CURSOR_ACCELERATION_TIME),
+            400.0 * CURSOR_MAX_ACCELERATION
+        );
+        assert_eq!(
+            cursor_speed(400.0, Duration::from_secs(10)),
+            400.0 * CURSOR_MAX_ACCELERATION
+        );
+    }
+
+    #[test]
+    fn test_stick_vector() {
+        assert_eq!(stick_vector(0.0, 0.0, 0.0, 0.0), Vector2::ZERO);
+        assert_eq!(stick_vector(1.0, 0.0, 0.0, 0.0), Vector2::new(0.0, -1.0));
+        assert_eq!(stick_vector(0.0, 0.5, 0.0, 0.25), Vector2::new(0.25, 0.5));
+        // Diagonals are no faster than straight moves
+        let diagonal = stick_vector(0.0, 1.0, 0.0, 1.0);
+        assert!((diagonal.length() - 1.0).abs() < 1e-6);
+    }
+}
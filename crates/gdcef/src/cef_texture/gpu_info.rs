@@ -0,0 +1,220 @@
+//! The browser's GPU state: `get_browser_gpu_info_async`.
+//!
+//! Chromium falls back to software rendering (SwiftShader) when the GPU or
+//! its driver is blocklisted or crashed, which leaves WebGL content black
+//! or slow. `SystemInfo.getInfo` reports the renderer the GPU process ended
+//! up with and the status of each feature. It is sent once when the browser
+//! is created, for the debug report, and on request. Its results bypass
+//! `allow_devtools_protocol`, see `DevToolsMessageObserverImpl`.
+
+use super::CefTexture;
+use super::devtools::{next_message_id, parse_json_dictionary};
+use crate::browser::DevToolsResultEvent;
+use cef::{CefString, ImplBrowser, ImplBrowserHost};
+use godot::prelude::*;
+use std::collections::HashSet;
+
+/// Renderers of the software rasterizers Chromium and the drivers fall back
+/// to.
+const SOFTWARE_RENDERERS: [&str; 4] = [
+    "SwiftShader",
+    "llvmpipe",
+    "softpipe",
+    "Microsoft Basic Render Driver",
+];
+
+fn is_software_renderer(gl_renderer: &str) -> bool {
+    SOFTWARE_RENDERERS
+        .iter()
+        .any(|renderer| gl_renderer.contains(renderer))
+}
+
+/// Returns whether pages are composited on the GPU with a hardware
+/// renderer, from the `gpu_compositing` feature status (`enabled`,
+/// `disabled_software`, ...).
+fn is_hardware_accelerated(gl_renderer: &str, gpu_compositing: &str) -> bool {
+    gpu_compositing.starts_with("enabled") && !is_software_renderer(gl_renderer)
+}
+
+/// Returns the line of the debug report.
+fn report_line(gl_renderer: &str, hardware_accelerated: bool, webgl: &str) -> String {
+    let renderer = if gl_renderer.is_empty() {
+        "unknown renderer"
+    } else {
+        gl_renderer
+    };
+    let acceleration = if hardware_accelerated {
+        "hardware"
+    } else {
+        "software"
+    };
+    let webgl = if webgl.is_empty() { "unknown" } else { webgl };
+    format!("{renderer} ({acceleration}, WebGL {webgl})")
+}
+
+fn string_at(dictionary: &VarDictionary, key: &str) -> String {
+    dictionary
+        .get(key)
+        .and_then(|value| value.try_to::<GString>().ok())
+        .map(|value| value.to_string())
+        .unwrap_or_default()
+}
+
+fn dictionary_at(dictionary: &VarDictionary, key: &str) -> VarDictionary {
+    dictionary
+        .get(key)
+        .and_then(|value| value.try_to::<VarDictionary>().ok())
+        .unwrap_or_default()
+}
+
+/// `SystemInfo.getInfo` requests of a browser.
+#[derive(Default)]
+pub(super) struct BrowserGpuInfo {
+    /// Message ids of the requests from `get_browser_gpu_info_async`.
+    requested: HashSet<i64>,
+    /// Line of the debug report, once the first result arrived.
+    report: Option<String>,
+}
+
+impl CefTexture {
+    pub(super) fn get_browser_gpu_info_async_impl(&mut self) -> bool {
+        let Some(message_id) = self.request_browser_gpu_info() else {
+            return false;
+        };
+        self.browser_gpu_info.requested.insert(message_id as i64);
+        true
+    }
+
+    /// Sends `SystemInfo.getInfo`, registering it with the observer first.
+    /// Returns the message id, or `None` when it could not be sent.
+    pub(super) fn request_browser_gpu_info(&mut self) -> Option<i32> {
+        let host = self.app.browser.as_ref().and_then(|b| b.host())?;
+        let message_id = next_message_id();
+        if let Ok(mut requests) = self.app.gpu_info_requests.lock() {
+            requests.insert(message_id);
+        }
+        let sent = host.execute_dev_tools_method(
+            message_id,
+            Some(&CefString::from("SystemInfo.getInfo")),
+            None,
+        );
+        if sent == 0 {
+            if let Ok(mut requests) = self.app.gpu_info_requests.lock() {
+                requests.remove(&message_id);
+            }
+            godot::global::godot_error!("[CefTexture] Failed to request the browser's GPU info");
+            return None;
+        }
+        Some(message_id)
+    }
+
+    /// Returns the line of the debug report.
+    pub(super) fn browser_gpu_report(&self) -> String {
+        self.browser_gpu_info
+            .report
+            .clone()
+            .unwrap_or_else(|| "not reported yet".to_string())
+    }
+
+    /// Records a `SystemInfo.getInfo` result and emits `browser_gpu_info`
+    /// if GDScript asked for it.
+    pub(super) fn handle_browser_gpu_info(&mut self, event: &DevToolsResultEvent) {
+        let result = parse_json_dictionary(&event.result);
+        let info = if event.success {
+            let gpu = dictionary_at(&result, "gpu");
+            let aux_attributes = dictionary_at(&gpu, "auxAttributes");
+            let feature_status = dictionary_at(&gpu, "featureStatus");
+            let gl_renderer = string_at(&aux_attributes, "glRenderer");
+            let webgl = string_at(&feature_status, "webgl");
+            let hardware_accelerated = is_hardware_accelerated(
+                &gl_renderer,
+                &string_at(&feature_status, "gpu_compositing"),
+            );
+            // The first device is the one in use
+            let gpu_device = gpu
+                .get("devices")
+                .and_then(|devices| devices.try_to::<VarArray>().ok())
+                .and_then(|devices| devices.get(0))
+                .and_then(|device| device.try_to::<VarDictionary>().ok())
+                .map(|device| string_at(&device, "deviceString"))
+                .unwrap_or_default();
+            self.browser_gpu_info.report =
+                Some(report_line(&gl_renderer, hardware_accelerated, &webgl));
+
+            let mut info = VarDictionary::new();
+            info.set("supported", true);
+            info.set("gl_renderer", gl_renderer);
+            info.set("gl_vendor", string_at(&aux_attributes, "glVendor"));
+            info.set("gpu_device", gpu_device);
+            info.set("hardware_accelerated", hardware_accelerated);
+            info.set("webgl", webgl);
+            info.set("feature_status", feature_status);
+            info
+        } else {
+            // CEF builds without the method answer with a protocol error
+            let error = string_at(&result, "message");
+            self.browser_gpu_info.report = Some(format!("unsupported ({error})"));
+
+            let mut info = VarDictionary::new();
+            info.set("supported", false);
+            info.set("error", error);
+            info
+        };
+
+        if self
+            .browser_gpu_info
+            .requested
+            .remove(&(event.message_id as i64))
+        {
+            self.emit_browser_signal("browser_gpu_info", &[("info", info.to_variant())]);
+        }
+    }
+
+    /// Forgets the requests of a released browser, whose results never
+    /// arrive.
+    pub(super) fn release_browser_gpu_info(&mut self) {
+        self.browser_gpu_info = BrowserGpuInfo::default();
+        if let Ok(mut requests) = self.app.gpu_info_requests.lock() {
+            requests.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_hardware_accelerated() {
+        assert!(is_hardware_accelerated(
+            "ANGLE (NVIDIA, NVIDIA GeForce RTX 3070 Direct3D11 vs_5_0 ps_5_0, D3D11)",
+            "enabled"
+        ));
+        // SwiftShader runs behind ANGLE with GPU compositing still enabled
+        assert!(!is_hardware_accelerated(
+            "ANGLE (Google, Vulkan 1.3.0 (SwiftShader Device (Subzero) (0x0000C0DE)), SwiftShader driver)",
+            "enabled"
+        ));
+        assert!(!is_hardware_accelerated(
+            "llvmpipe (LLVM 15.0.7, 256 bits)",
+            "enabled"
+        ));
+        assert!(!is_hardware_accelerated(
+            "ANGLE (Intel, Mesa Intel(R) UHD Graphics 620, OpenGL 4.6)",
+            "disabled_software"
+        ));
+        assert!(!is_hardware_accelerated("", ""));
+    }
+
+    #[test]
+    fn test_report_line() {
+        assert_eq!(
+            report_line("ANGLE (Apple, Apple M1, OpenGL 4.1)", true, "enabled_on"),
+            "ANGLE (Apple, Apple M1, OpenGL 4.1) (hardware, WebGL enabled_on)"
+        );
+        assert_eq!(
+            report_line("", false, ""),
+            "unknown renderer (software, WebGL unknown)"
+        );
+    }
+}
@@ -0,0 +1,299 @@
+//! Click-through on transparent pixels for CefTexture.
+//!
+//! With `click_through_transparent` enabled, `_has_point` reports the node as
+//! absent where the page is transparent, so Godot delivers those clicks to
+//! whatever is underneath. Software rendering samples the frame buffer
+//! directly. In accelerated mode the frame only lives on the GPU, so a small
+//! region around the mouse is copied and read back asynchronously every few
+//! frames; hit tests use that mask and are a few frames behind the page.
+
+use super::CefTexture;
+use godot::classes::RenderingServer;
+use godot::prelude::*;
+
+use crate::browser::RenderMode;
+use crate::render;
+
+/// Side of the square region around the mouse read back in accelerated mode,
+/// in physical pixels.
+const MASK_REGION_SIZE: u32 = 64;
+
+/// Frames between two readbacks of the alpha mask.
+const MASK_READBACK_INTERVAL: u32 = 4;
+
+/// Returns the alpha of pixel `(x, y)` of a 4-byte-per-pixel buffer with the
+/// alpha last (RGBA or BGRA), or `None` outside of it.
+fn alpha_at(data: &[u8], width: u32, height: u32, x: i32, y: i32) -> Option<u8> {
+    if x < 0 || y < 0 || x as u32 >= width || y as u32 >= height {
+        return None;
+    }
+    let index = (y as usize * width as usize + x as usize) * 4 + 3;
+    data.get(index).copied()
+}
+
+/// Returns whether an alpha value is below `threshold` (0 to 1).
+fn is_transparent(alpha: u8, threshold: f32) -> bool {
+    (alpha as f32) < threshold * 255.0
+}
+
+/// Returns the region of at most `region_size` pixels per side centered on
+/// `center`, kept inside a texture of `texture_size`, as `(x, y, width,
+/// height)`.
+fn mask_region(
+    center: (i32, i32),
+    texture_size: (u32, u32),
+    region_size: u32,
+) -> (i32, i32, u32, u32) {
+    let width = region_size.min(texture_size.0);
+    let height = region_size.min(texture_size.1);
+    let x = (center.0 - width as i32 / 2).clamp(0, (texture_size.0 - width) as i32);
+    let y = (center.1 - height as i32 / 2).clamp(0, (texture_size.1 - height) as i32);
+    (x, y, width, height)
+}
+
+/// Alpha of a region of the page, in physical pixels.
+#[derive(Debug)]
+struct AlphaMask {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+    alpha: Vec<u8>,
+}
+
+impl AlphaMask {
+    /// Builds the mask of `region` from the BGRA pixels read back from a
+    /// readback texture `stride` pixels wide.
+    fn from_pixels(region: (i32, i32, u32, u32), pixels: &[u8], stride: u32) -> Self {
+        let (x, y, width, height) = region;
+        let alpha = (0..height as i32)
+            .flat_map(|row| {
+                (0..width as i32).map(move |column| {
+                    alpha_at(pixels, stride, height, column, row).unwrap_or(u8::MAX)
+                })
+            })
+            .collect();
+        Self {
+            x,
+            y,
+            width,
+            height,
+            alpha,
+        }
+    }
+
+    /// Returns the alpha at a physical pixel, or `None` outside the mask.
+    fn alpha(&self, x: i32, y: i32) -> Option<u8> {
+        let (x, y) = (x - self.x, y - self.y);
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height {
+            return None;
+        }
+        self.alpha
+            .get(y as usize * self.width as usize + x as usize)
+            .copied()
+    }
+}
+
+/// Alpha mask kept up to date around the mouse in accelerated mode.
+#[derive(Default)]
+pub(super) struct AlphaMaskReadback {
+    /// Texture regions are copied into to be read back.
+    texture: Option<Rid>,
+    frames_until_readback: u32,
+    /// Region whose readback is in flight.
+    pending_region: Option<(i32, i32, u32, u32)>,
+    mask: Option<AlphaMask>,
+}
+
+impl CefTexture {
+    /// Returns whether the page is transparent at a local position, so a
+    /// click there should go to what is underneath the node.
+    pub(super) fn is_click_through(&self, position: Vector2) -> bool {
+        if !self.click_through_transparent {
+            return false;
+        }
+
+        // Popups (e.g. `<select>` dropdowns) are drawn over the page and
+        // always take clicks
+        let pixel_scale = self.get_pixel_scale_factor();
+        let view_position = position * pixel_scale / self.get_device_scale_factor();
+        if let Some(popup_state) = &self.app.popup_state
+            && let Ok(popup) = popup_state.lock()
+            && popup.visible
+        {
+            let rect = Rect2::new(
+                Vector2::new(popup.rect.x as f32, popup.rect.y as f32),
+                Vector2::new(popup.rect.width as f32, popup.rect.height as f32),
+            );
+            if rect.contains_point(view_position) {
+                return false;
+            }
+        }
+
+        let pixel = position * pixel_scale;
+        let (x, y) = (pixel.x as i32, pixel.y as i32);
+        let alpha = match &self.app.render_mode {
+            Some(RenderMode::Software { frame_buffer, .. }) => frame_buffer
+                .lock()
+                .ok()
+                .and_then(|fb| alpha_at(&fb.data, fb.width, fb.height, x, y)),
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            Some(RenderMode::Accelerated { .. }) => self
+                .alpha_mask
+                .mask
+                .as_ref()
+                .and_then(|mask| mask.alpha(x, y)),
+            None => None,
+        };
+        // Without pixels to test, the node takes the click as usual
+        alpha.is_some_and(|alpha| is_transparent(alpha, self.click_through_alpha_threshold))
+    }
+
+    /// Reads back the alpha around the mouse every few frames while
+    /// click-through is enabled in accelerated mode.
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    pub(super) fn update_alpha_mask(&mut self) {
+        if !self.click_through_transparent || self.alpha_mask.pending_region.is_some() {
+            return;
+        }
+        if self.alpha_mask.frames_until_readback > 0 {
+            self.alpha_mask.frames_until_readback -= 1;
+            return;
+        }
+        let Some(RenderMode::Accelerated { render_state, .. }) = &self.app.render_mode else {
+            return;
+        };
+
+        let mouse = self.base().get_local_mouse_position();
+        if !Rect2::new(Vector2::ZERO, self.base().get_size()).contains_point(mouse) {
+            return;
+        }
+        let pixel = self.local_to_view(mouse) * self.get_pixel_scale_factor();
+        let Ok(state) = render_state.lock() else {
+            return;
+        };
+        let (source, texture_size) = (state.dst_rd_rid, (state.dst_width, state.dst_height));
+        drop(state);
+        if texture_size.0 == 0 || texture_size.1 == 0 {
+            return;
+        }
+        let region = mask_region(
+            (pixel.x as i32, pixel.y as i32),
+            texture_size,
+            MASK_REGION_SIZE,
+        );
+
+        let Some(mut rd) = RenderingServer::singleton().get_rendering_device() else {
+            return;
+        };
+        let texture = match self.alpha_mask.texture {
+            Some(texture) => texture,
+            None => {
+                let size = MASK_REGION_SIZE as i32;
+                match render::create_rd_readback_texture(size, size) {
+                    Ok(texture) => *self.alpha_mask.texture.insert(texture),
+                    Err(e) => {
+                        godot::global::godot_error!(
+                            "[CefTexture] Failed to create alpha mask texture: {}",
+                            e
+                        );
+                        self.click_through_transparent = false;
+                        return;
+                    }
+                }
+            }
+        };
+
+        let (x, y, width, height) = region;
+        let error = rd.texture_copy(
+            source,
+            texture,
+            Vector3::new(x as f32, y as f32, 0.0),
+            Vector3::ZERO,
+            Vector3::new(width as f32, height as f32, 1.0),
+            0,
+            0,
+            0,
+            0,
+        );
+        if error != godot::global::Error::OK {
+            return;
+        }
+        let callback = self.base().callable("_on_alpha_mask_readback");
+        if rd.texture_get_data_async(texture, 0, &callback) == godot::global::Error::OK {
+            self.alpha_mask.pending_region = Some(region);
+            self.alpha_mask.frames_until_readback = MASK_READBACK_INTERVAL;
+        }
+    }
+
+    /// Stores the alpha of a finished readback.
+    pub(super) fn on_alpha_mask_readback_impl(&mut self, pixels: PackedByteArray) {
+        let Some(region) = self.alpha_mask.pending_region.take() else {
+            return;
+        };
+        self.alpha_mask.mask = Some(AlphaMask::from_pixels(
+            region,
+            pixels.as_slice(),
+            MASK_REGION_SIZE,
+        ));
+    }
+
+    /// Frees the readback texture and forgets the mask.
+    pub(super) fn release_alpha_mask(&mut self) {
+        if let Some(texture) = self.alpha_mask.texture.take() {
+            render::free_rd_texture(texture);
+        }
+        self.alpha_mask = AlphaMaskReadback::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alpha_at() {
+        // 2x2 pixels, alpha 10, 20, 30, 40
+        let data = [0, 0, 0, 10, 0, 0, 0, 20, 0, 0, 0, 30, 0, 0, 0, 40];
+        assert_eq!(alpha_at(&data, 2, 2, 0, 0), Some(10));
+        assert_eq!(alpha_at(&data, 2, 2, 1, 1), Some(40));
+        assert_eq!(alpha_at(&data, 2, 2, 2, 0), None);
+        assert_eq!(alpha_at(&data, 2, 2, -1, 0), None);
+        // Buffers shorter than their size, e.g. mid-resize
+        assert_eq!(alpha_at(&data, 4, 4, 3, 3), None);
+    }
+
+    #[test]
+    fn test_is_transparent() {
+        assert!(is_transparent(0, 0.1));
+        assert!(!is_transparent(26, 0.1));
+        assert!(!is_transparent(0, 0.0));
+        assert!(is_transparent(254, 1.0));
+    }
+
+    #[test]
+    fn test_mask_region() {
+        assert_eq!(mask_region((100, 100), (800, 600), 64), (68, 68, 64, 64));
+        // Kept inside the texture
+        assert_eq!(mask_region((5, 590), (800, 600), 64), (0, 536, 64, 64));
+        // Textures smaller than the region
+        assert_eq!(mask_region((10, 10), (32, 600), 64), (0, 0, 32, 64));
+    }
+
+    #[test]
+    fn test_alpha_mask() {
+        // A 2x2 region read into a 4 pixel wide readback texture
+        let mut pixels = vec![0; 4 * 4 * 2];
+        pixels[3] = 0;
+        pixels[7] = 255;
+        pixels[16 + 3] = 128;
+        pixels[16 + 7] = 64;
+        let mask = AlphaMask::from_pixels((10, 20, 2, 2), &pixels, 4);
+        assert_eq!(mask.alpha(10, 20), Some(0));
+        assert_eq!(mask.alpha(11, 20), Some(255));
+        assert_eq!(mask.alpha(10, 21), Some(128));
+        assert_eq!(mask.alpha(11, 21), Some(64));
+        assert_eq!(mask.alpha(12, 20), None);
+        assert_eq!(mask.alpha(9, 20), None);
+    }
+}
@@ -0,0 +1,208 @@
+//! Idle mode: `idle_timeout_seconds`, `is_idle` and `idle_state_changed`.
+//!
+//! A browser that has not painted, played audio, queued an event or
+//! received input for `idle_timeout_seconds` goes idle: it gets no begin
+//! frames and no texture checks, and CEF's message loop is pumped at a low
+//! rate unless another browser needs it every frame. Input, a visibility
+//! change, a resize or a message sent to the page wakes it at once, with a
+//! repaint so the first frame is current. While idle a page only updates the
+//! texture by waking, so content changed by the page's own timers waits for
+//! the next wake.
+
+use std::time::{Duration, Instant};
+
+use super::CefTexture;
+use cef::{ImplBrowser, ImplBrowserHost};
+use godot::prelude::*;
+
+#[derive(Default)]
+pub(super) struct IdleDetector {
+    last_activity: Option<Instant>,
+    last_paint_count: u64,
+    idle: bool,
+}
+
+impl IdleDetector {
+    /// Returns the new state if it changed. The browser is active while
+    /// `busy`, while `paint_count` changes and until `timeout` passed since;
+    /// without a timeout it never goes idle.
+    fn update(
+        &mut self,
+        now: Instant,
+        paint_count: u64,
+        busy: bool,
+        timeout: Option<Duration>,
+    ) -> Option<bool> {
+        let painted = std::mem::replace(&mut self.last_paint_count, paint_count) != paint_count;
+        if busy || painted || self.last_activity.is_none() {
+            self.last_activity = Some(now);
+        }
+        let idle = timeout.is_some_and(|timeout| {
+            self.last_activity
+                .is_some_and(|last| now.duration_since(last) >= timeout)
+        });
+        (idle != std::mem::replace(&mut self.idle, idle)).then_some(idle)
+    }
+
+    /// Records activity. Returns `true` if the browser was idle.
+    fn wake(&mut self, now: Instant) -> bool {
+        self.last_activity = Some(now);
+        std::mem::take(&mut self.idle)
+    }
+}
+
+impl CefTexture {
+    fn idle_timeout(&self) -> Option<Duration> {
+        let seconds = self.idle_timeout_seconds;
+        (seconds.is_finite() && seconds > 0.0).then(|| Duration::from_secs_f32(seconds))
+    }
+
+    /// Updates the idle state from this frame's activity and returns whether
+    /// the browser is idle. Called once per frame.
+    pub(super) fn update_idle_state(&mut self) -> bool {
+        if self.app.browser.is_none() {
+            return false;
+        }
+        let paint_count = self
+            .app
+            .render_stats
+            .as_ref()
+            .map_or(0, |stats| stats.paint_sequence());
+        // Events queued since the last drain, and audio being captured
+        let busy = self
+            .app
+            .event_queues
+            .as_ref()
+            .and_then(|queues| queues.lock().ok())
+            .is_some_and(|queues| queues.pending_len() > 0)
+            || self
+                .app
+                .audio_params
+                .as_ref()
+                .and_then(|params| params.lock().ok())
+                .is_some_and(|params| params.is_some());
+
+        let timeout = self.idle_timeout();
+        match self.idle.update(Instant::now(), paint_count, busy, timeout) {
+            Some(true) => self.emit_idle_state_changed(true),
+            Some(false) => self.leave_idle(),
+            None => {}
+        }
+        self.idle.idle
+    }
+
+    /// Marks the page as active: a due begin frame is sent right away and an
+    /// idle browser wakes.
+    pub(super) fn wake_page(&mut self) {
+        self.begin_frame_scheduler.wake();
+        if self.idle.wake(Instant::now()) {
+            self.leave_idle();
+        }
+    }
+
+    pub(super) fn is_idle_impl(&self) -> bool {
+        self.idle.idle
+    }
+
+    fn leave_idle(&mut self) {
+        self.begin_frame_scheduler.wake();
+        // Whatever changed while idle was never painted
+        if let Some(browser) = self.app.browser.as_mut()
+            && let Some(host) = browser.host()
+        {
+            host.invalidate(cef::PaintElementType::VIEW);
+        }
+        self.emit_idle_state_changed(false);
+    }
+
+    fn emit_idle_state_changed(&mut self, idle: bool) {
+        self.emit_browser_signal("idle_state_changed", &[("idle", idle.to_variant())]);
+    }
+
+    pub(super) fn reset_idle_state(&mut self) {
+        if self.idle.wake(Instant::now()) {
+            self.emit_idle_state_changed(false);
+        }
+        self.idle = IdleDetector::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Option<Duration> = Some(Duration::from_secs(5));
+
+    #[test]
+    fn test_goes_idle_after_timeout() {
+        let start = Instant::now();
+        let mut detector = IdleDetector::default();
+        assert_eq!(detector.update(start, 0, false, TIMEOUT), None);
+        assert_eq!(
+            detector.update(start + Duration::from_secs(4), 0, false, TIMEOUT),
+            None
+        );
+        assert_eq!(
+            detector.update(start + Duration::from_secs(5), 0, false, TIMEOUT),
+            Some(true)
+        );
+        assert_eq!(
+            detector.update(start + Duration::from_secs(6), 0, false, TIMEOUT),
+            None
+        );
+    }
+
+    #[test]
+    fn test_activity_restarts_timeout() {
+        let start = Instant::now();
+        let mut detector = IdleDetector::default();
+        detector.update(start, 0, false, TIMEOUT);
+        // A paint, then a queued event
+        detector.update(start + Duration::from_secs(3), 1, false, TIMEOUT);
+        detector.update(start + Duration::from_secs(6), 1, true, TIMEOUT);
+        assert_eq!(
+            detector.update(start + Duration::from_secs(10), 1, false, TIMEOUT),
+            None
+        );
+        assert_eq!(
+            detector.update(start + Duration::from_secs(11), 1, false, TIMEOUT),
+            Some(true)
+        );
+        // A paint while idle wakes the browser
+        assert_eq!(
+            detector.update(start + Duration::from_secs(12), 2, false, TIMEOUT),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_wake() {
+        let start = Instant::now();
+        let mut detector = IdleDetector::default();
+        detector.update(start, 0, false, TIMEOUT);
+        detector.update(start + Duration::from_secs(5), 0, false, TIMEOUT);
+        assert!(detector.wake(start + Duration::from_secs(7)));
+        assert!(!detector.wake(start + Duration::from_secs(7)));
+        // Input restarted the timeout
+        assert_eq!(
+            detector.update(start + Duration::from_secs(11), 0, false, TIMEOUT),
+            None
+        );
+    }
+
+    #[test]
+    fn test_no_timeout_never_idles() {
+        let start = Instant::now();
+        let mut detector = IdleDetector::default();
+        detector.update(start, 0, false, TIMEOUT);
+        detector.update(start + Duration::from_secs(5), 0, false, TIMEOUT);
+        assert_eq!(
+            detector.update(start + Duration::from_secs(6), 0, false, None),
+            Some(false)
+        );
+        assert_eq!(
+            detector.update(start + Duration::from_secs(600), 0, false, None),
+            None
+        );
+    }
+}
@@ -5,12 +5,88 @@
 
 use super::CefTexture;
 use cef::{ImplBrowser, ImplBrowserHost};
+use cef_app::TextInputType;
 use godot::classes::control::{FocusMode, MouseFilter};
+use godot::classes::line_edit::VirtualKeyboardType;
 use godot::classes::{Control, DisplayServer, LineEdit};
 use godot::prelude::*;
 
+use super::zoom::zoom_factor;
+use crate::browser::{CharacterBounds, ImeCompositionRange};
 use crate::input;
-use crate::utils::get_display_scale_factor;
+
+/// Composition in progress, as reported by the OS input method.
+#[derive(Clone, Debug, Default)]
+pub(super) struct ImeComposition {
+    text: String,
+    /// Caret position in characters.
+    cursor: u32,
+    /// Length of the clause being converted, which starts at the cursor.
+    selection_length: u32,
+    /// Bounds of each UTF-16 code unit in view coordinates, as last laid out
+    /// by the browser.
+    character_bounds: Vec<CharacterBounds>,
+}
+
+impl ImeComposition {
+    fn clauses(&self) -> Vec<input::ImeClause> {
+        input::ime_clauses(
+            self.text.chars().count() as u32,
+            self.cursor,
+            self.selection_length,
+        )
+    }
+
+    /// Returns the range of UTF-16 code units of the clause being converted.
+    fn selected_range(&self) -> Option<(usize, usize)> {
+        let clause = self.clauses().into_iter().find(|clause| clause.selected)?;
+        Some((
+            input::utf16_offset(&self.text, clause.start) as usize,
+            input::utf16_offset(&self.text, clause.end) as usize,
+        ))
+    }
+}
+
+/// Caret of the focused text field in CSS pixels, which stay valid across
+/// zoom changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct ImeCaret {
+    position: Vector2,
+    height: f32,
+}
+
+impl ImeCaret {
+    fn from_range(range: &ImeCompositionRange, zoom_factor: f32) -> Self {
+        let scale = if range.css_pixels {
+            1.0
+        } else {
+            1.0 / zoom_factor
+        };
+        Self {
+            position: Vector2::new(range.caret_x as f32, range.caret_y as f32) * scale,
+            height: range.caret_height as f32 * scale,
+        }
+    }
+
+    /// Returns the caret's top-left corner and height in local coordinates:
+    /// CSS pixels are scaled by the zoom to view coordinates, then by the
+    /// inverse of the input transform.
+    fn to_local(self, zoom_factor: f32, device_scale: f32, pixel_scale: f32) -> (Vector2, f32) {
+        let scale = zoom_factor * device_scale / pixel_scale;
+        (self.position * scale, self.height * scale)
+    }
+}
+
+/// Everything the IME position depends on; it is recomputed when any of
+/// them changes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct ImePlacement {
+    caret: ImeCaret,
+    zoom_factor: f32,
+    device_scale: f32,
+    pixel_scale: f32,
+    global_position: Vector2,
+}
 
 impl CefTexture {
     /// Creates a hidden LineEdit to act as an IME input proxy.
@@ -20,6 +96,8 @@ impl CefTexture {
         line_edit.set_size(Vector2::new(200.0, 30.0));
         line_edit.set_mouse_filter(MouseFilter::IGNORE);
         line_edit.set_focus_mode(FocusMode::ALL);
+        // Moved over the caret while composing, so keep it invisible
+        line_edit.set_modulate(Color::from_rgba(1.0, 1.0, 1.0, 0.0));
         let callable_changed = self.base().callable("on_ime_proxy_text_changed");
         line_edit.connect("text_changed", &callable_changed);
 
@@ -30,10 +108,85 @@ impl CefTexture {
         self.ime_proxy = Some(line_edit);
     }
 
+    /// Configures the proxy for the focused text field: password fields make
+    /// it secret, and the virtual keyboard it shows matches the field.
+    pub(super) fn set_ime_input_type(&mut self, input_type: TextInputType) {
+        if self.ime_input_type == input_type {
+            return;
+        }
+        if self.ime_input_type == TextInputType::Password {
+            // Nothing typed into the password field may carry over
+            self.cancel_pending_ime_composition();
+        }
+        self.ime_input_type = input_type;
+
+        if let Some(proxy) = self.ime_proxy.as_mut() {
+            proxy.set_secret(input_type == TextInputType::Password);
+            proxy.set_virtual_keyboard_type(virtual_keyboard_type(input_type));
+        }
+    }
+
+    /// Stores a caret reported by the browser and places the IME there,
+    /// under the clause being converted when its layout is known.
+    pub(super) fn set_ime_caret(&mut self, range: ImeCompositionRange) {
+        let range = match self.ime_composition.selected_range() {
+            Some((start, end)) => range.anchored_to(start, end),
+            None => range,
+        };
+        if !range.css_pixels {
+            self.ime_composition
+                .character_bounds
+                .clone_from(&range.character_bounds);
+        }
+        let zoom_factor = zoom_factor(self.host_zoom_level());
+        self.ime_caret = Some(ImeCaret::from_range(&range, zoom_factor));
+        self.update_ime_placement();
+    }
+
+    /// Moves the IME candidate window and the proxy to the caret when the
+    /// caret, the zoom level, the scale factors or the node's position
+    /// changed. Called every frame while IME is active.
+    pub(super) fn update_ime_placement(&mut self) {
+        if !self.ime_active {
+            return;
+        }
+        let Some(caret) = self.ime_caret else {
+            return;
+        };
+        let placement = ImePlacement {
+            caret,
+            zoom_factor: zoom_factor(self.host_zoom_level()),
+            device_scale: self.get_device_scale_factor(),
+            pixel_scale: self.get_pixel_scale_factor(),
+            global_position: self.base().get_global_position(),
+        };
+        if self.ime_placement == Some(placement) {
+            return;
+        }
+        self.ime_placement = Some(placement);
+
+        let (position, height) = caret.to_local(
+            placement.zoom_factor,
+            placement.device_scale,
+            placement.pixel_scale,
+        );
+        // The page may be laid out at another size than the node's
+        let bottom = self.view_to_local(position + Vector2::new(0.0, height));
+        let position = self.view_to_local(position);
+        let height = bottom.y - position.y;
+        // The candidate window opens below the caret
+        self.ime_position = Vector2i::new(position.x as i32, (position.y + height) as i32);
+        // LineEdit places the IME at its own caret when it redraws, so keep
+        // the proxy over the page's caret
+        if let Some(proxy) = self.ime_proxy.as_mut() {
+            proxy.set_position(position);
+        }
+        self.process_ime_position();
+    }
+
     pub(super) fn process_ime_position(&mut self) {
         if self.ime_active {
             let mut ds: Gd<DisplayServer> = DisplayServer::singleton();
-            let display_scale = get_display_scale_factor();
             let pixel_scale = self.get_pixel_scale_factor();
 
             let rect = self.base().get_viewport_rect();
@@ -54,10 +207,10 @@ impl CefTexture {
             );
 
             let final_ime_position = Vector2i::new(
-                (self.ime_position.x as f32 * display_scale
-                    + (viewport_offset.x + node_offset.x) * pixel_scale) as i32,
-                (self.ime_position.y as f32 * display_scale
-                    + (viewport_offset.y + node_offset.y) * pixel_scale) as i32,
+                ((self.ime_position.x as f32 + viewport_offset.x + node_offset.x) * pixel_scale)
+                    as i32,
+                ((self.ime_position.y as f32 + viewport_offset.y + node_offset.y) * pixel_scale)
+                    as i32,
             );
 
             ds.window_set_ime_position(final_ime_position);
@@ -74,11 +227,67 @@ impl CefTexture {
             return;
         };
 
-        input::ime_commit_text(&host, &new_text.to_string());
+        // Keys typed straight into the proxy reach the page before their text
+        let text = new_text.to_string();
+        input::send_key_inputs(&host, self.ime_keys.committed(&text), true);
+        input::ime_commit_text(&host, &text);
+        self.set_ime_composition(String::new(), 0, 0);
+
+        if let Some(proxy) = self.ime_proxy.as_mut() {
+            proxy.set_text("");
+        }
+    }
+
+    /// Discards the composition in the page and in the OS input method.
+    pub(super) fn cancel_ime_composition_impl(&mut self) {
+        self.set_ime_composition(String::new(), 0, 0);
+        self.ime_keys.reset();
+        self.reset_os_ime();
+
+        if let Some(browser) = self.app.browser.as_mut()
+            && let Some(host) = browser.host()
+        {
+            host.ime_cancel_composition();
+        }
+    }
+
+    /// Cancels the composition in progress, if any, so it cannot be
+    /// committed later into whatever element has focus by then.
+    pub(super) fn cancel_pending_ime_composition(&mut self) {
+        if !self.ime_composition.text.is_empty() {
+            self.cancel_ime_composition_impl();
+        }
+    }
+
+    /// Commits the composition in progress as it currently reads.
+    pub(super) fn commit_ime_composition_impl(&mut self) {
+        let text = self.ime_composition.text.clone();
+        if text.is_empty() {
+            return;
+        }
+        self.set_ime_composition(String::new(), 0, 0);
+        self.ime_keys.committed(&text);
+        // Otherwise the OS would commit the same text again later
+        self.reset_os_ime();
+
+        if let Some(browser) = self.app.browser.as_mut()
+            && let Some(host) = browser.host()
+        {
+            input::ime_commit_text(&host, &text);
+        }
+    }
 
+    /// Drops the OS input method's pending composition by turning IME off
+    /// and on again while the proxy has it.
+    fn reset_os_ime(&mut self) {
         if let Some(proxy) = self.ime_proxy.as_mut() {
             proxy.set_text("");
         }
+        if self.ime_active {
+            let mut display_server = DisplayServer::singleton();
+            display_server.window_set_ime_active(false);
+            display_server.window_set_ime_active(true);
+        }
     }
 
     pub(super) fn on_ime_proxy_focus_exited_impl(&mut self) {
@@ -137,11 +346,15 @@ impl CefTexture {
         self.ime_active = true;
     }
 
-    /// Deactivates IME and commits any pending text.
+    /// Deactivates IME, cancelling a composition left in progress (e.g. when
+    /// the page's text field lost focus or was removed mid-composition).
     pub(super) fn deactivate_ime(&mut self) {
         if !self.ime_active {
             return;
         }
+        self.cancel_pending_ime_composition();
+        self.ime_placement = None;
+        self.ime_keys.reset();
 
         // Clear the proxy
         if let Some(proxy) = self.ime_proxy.as_mut() {
@@ -161,14 +374,185 @@ impl CefTexture {
 
         let ime_text = DisplayServer::singleton().ime_get_text().to_string();
         let ime_selection = DisplayServer::singleton().ime_get_selection();
-        let start = ime_selection.x.max(0) as u32;
-        let end = ime_selection.y.max(0) as u32;
+        if !self.ime_keys.composition_updated(&ime_text) {
+            return;
+        }
+        // Godot reports the selection as a start and a length
+        let cursor = ime_selection.x.max(0) as u32;
+        let selection_length = ime_selection.y.max(0) as u32;
+        self.set_ime_composition(ime_text.clone(), cursor, selection_length);
+        let clauses = self.ime_composition.clauses();
 
         // Update the IME composition text
         if let Some(browser) = self.app.browser.as_mut()
             && let Some(host) = browser.host()
         {
-            input::ime_set_composition(&host, &ime_text, start, end);
+            input::ime_set_composition(&host, &ime_text, &clauses, cursor);
+        }
+    }
+
+    /// Sends the key events held back this frame that the IME did not
+    /// consume, and an Enter pressed right after a commit when
+    /// `forward_enter_after_commit` is set.
+    pub(super) fn flush_ime_keys(&mut self) {
+        let mut enter = self.ime_keys.take_enter_after_commit();
+        if !self.forward_enter_after_commit {
+            enter.clear();
+        }
+        let inputs = self.ime_keys.end_frame();
+        if inputs.is_empty() && enter.is_empty() {
+            return;
+        }
+        if let Some(browser) = self.app.browser.as_mut()
+            && let Some(host) = browser.host()
+        {
+            input::send_key_inputs(&host, inputs, true);
+            // With its character, which is what submits forms and inserts
+            // line breaks
+            input::send_key_inputs(&host, enter, false);
+        }
+    }
+
+    /// Stores the composition and emits `ime_composition_changed` when its
+    /// text or cursor changed. Compositions in password fields are never
+    /// reported.
+    fn set_ime_composition(&mut self, text: String, cursor: u32, selection_length: u32) {
+        let reported = self.ime_input_type != TextInputType::Password;
+        let composition = &mut self.ime_composition;
+        let changed = reported && (composition.text != text || composition.cursor != cursor);
+        if text.is_empty() {
+            composition.character_bounds.clear();
+        }
+        composition.text = text;
+        composition.cursor = cursor;
+        composition.selection_length = selection_length;
+
+        if changed {
+            let text = GString::from(&self.ime_composition.text);
+            self.emit_browser_signal(
+                "ime_composition_changed",
+                &[
+                    ("text", text.to_variant()),
+                    ("cursor", (cursor as i64).to_variant()),
+                ],
+            );
+        }
+    }
+
+    /// Returns the composition with its clauses and the local bounds of its
+    /// characters, for drawing it over the page.
+    pub(super) fn ime_composition_dictionary(&self) -> VarDictionary {
+        let empty = ImeComposition::default();
+        let composition = if self.ime_input_type == TextInputType::Password {
+            &empty
+        } else {
+            &self.ime_composition
+        };
+        let clauses: Array<VarDictionary> = composition
+            .clauses()
+            .iter()
+            .map(|clause| {
+                vdict! {
+                    "start": clause.start as i64,
+                    "end": clause.end as i64,
+                    "selected": clause.selected,
+                }
+            })
+            .collect();
+        // View coordinates to local, the inverse of the input transform
+        let scale = self.get_device_scale_factor() / self.get_pixel_scale_factor();
+        let character_bounds: Array<Rect2> = composition
+            .character_bounds
+            .iter()
+            .map(|bounds| {
+                Rect2::new(
+                    Vector2::new(bounds.x as f32, bounds.y as f32) * scale,
+                    Vector2::new(bounds.width as f32, bounds.height as f32) * scale,
+                )
+            })
+            .collect();
+        vdict! {
+            "text": GString::from(&composition.text),
+            "cursor": composition.cursor as i64,
+            "clauses": clauses,
+            "character_bounds": character_bounds,
+        }
+    }
+}
+
+/// Returns the virtual keyboard the proxy shows for a kind of text field.
+fn virtual_keyboard_type(input_type: TextInputType) -> VirtualKeyboardType {
+    match input_type {
+        TextInputType::Text => VirtualKeyboardType::DEFAULT,
+        TextInputType::Multiline => VirtualKeyboardType::MULTILINE,
+        TextInputType::Password => VirtualKeyboardType::PASSWORD,
+        TextInputType::Email => VirtualKeyboardType::EMAIL_ADDRESS,
+        TextInputType::Number => VirtualKeyboardType::NUMBER,
+        TextInputType::Decimal => VirtualKeyboardType::NUMBER_DECIMAL,
+        TextInputType::Phone => VirtualKeyboardType::PHONE,
+        TextInputType::Url => VirtualKeyboardType::URL,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(x: i32, y: i32, height: i32, css_pixels: bool) -> ImeCompositionRange {
+        ImeCompositionRange {
+            caret_x: x,
+            caret_y: y,
+            caret_height: height,
+            css_pixels,
+            character_bounds: Vec::new(),
         }
     }
+
+    #[test]
+    fn test_selected_range() {
+        let mut composition = ImeComposition::default();
+        assert_eq!(composition.selected_range(), None);
+
+        composition.text = "😀日本".to_string();
+        composition.cursor = 1;
+        composition.selection_length = 2;
+        // The emoji takes two UTF-16 code units
+        assert_eq!(composition.selected_range(), Some((2, 4)));
+
+        composition.selection_length = 0;
+        assert_eq!(composition.selected_range(), None);
+    }
+
+    #[test]
+    fn test_virtual_keyboard_type() {
+        assert_eq!(
+            virtual_keyboard_type(TextInputType::Password),
+            VirtualKeyboardType::PASSWORD
+        );
+        assert_eq!(
+            virtual_keyboard_type(TextInputType::Phone),
+            VirtualKeyboardType::PHONE
+        );
+        assert_eq!(
+            virtual_keyboard_type(TextInputType::Text),
+            VirtualKeyboardType::DEFAULT
+        );
+    }
+
+    #[test]
+    fn test_caret_to_local() {
+        // At 150% zoom, 2x device scale and a 2x stretched viewport
+        let zoom = 1.5;
+        let caret = ImeCaret::from_range(&range(100, 40, 20, true), zoom);
+        let (position, height) = caret.to_local(zoom, 2.0, 2.0);
+        assert_eq!(position, Vector2::new(150.0, 60.0));
+        assert_eq!(height, 30.0);
+
+        // View coordinates already include the zoom
+        let caret = ImeCaret::from_range(&range(150, 60, 30, false), zoom);
+        assert_eq!(
+            caret.to_local(zoom, 1.0, 1.0),
+            (Vector2::new(150.0, 60.0), 30.0)
+        );
+    }
 }
@@ -0,0 +1,182 @@
+//! Scripted input for CefTexture, for automated UI tests.
+//!
+//! Injected events go straight to the browser host with the same conversion
+//! and DPI scaling as Godot input, so they work without focus and wherever
+//! the real mouse is.
+
+use super::CefTexture;
+use cef::{ImplBrowser, ImplBrowserHost};
+use godot::global::{Key, MouseButton};
+use godot::prelude::*;
+
+use crate::input::{self, KeyInput};
+
+/// Returns `position` if it lies within a node of `size`; otherwise the
+/// nearest point inside when `clamp` is set, or `None`.
+fn position_in_bounds(position: Vector2, size: Vector2, clamp: bool) -> Option<Vector2> {
+    let inside = (0.0..=size.x).contains(&position.x) && (0.0..=size.y).contains(&position.y);
+    if inside {
+        Some(position)
+    } else if clamp && position.is_finite() {
+        Some(position.clamp(Vector2::ZERO, size))
+    } else {
+        None
+    }
+}
+
+impl CefTexture {
+    /// Returns the host to inject into, after delivering a held-back mouse
+    /// move so events stay in order.
+    pub(super) fn injection_host(&mut self) -> Option<cef::BrowserHost> {
+        self.wake_page();
+        self.flush_mouse_move();
+        self.app.browser.as_mut().and_then(|browser| browser.host())
+    }
+
+    /// Converts a position in local coordinates to a CEF mouse event, or
+    /// returns `None` if it is out of bounds and `clamp_injected_positions`
+    /// is off.
+    pub(super) fn injected_mouse_event(
+        &self,
+        position: Vector2,
+        modifiers: u32,
+    ) -> Option<cef::MouseEvent> {
+        let Some(position) = position_in_bounds(
+            position,
+            self.base().get_size(),
+            self.clamp_injected_positions,
+        ) else {
+            godot::global::godot_warn!(
+                "[CefTexture] Injected position {} is outside the node",
+                position
+            );
+            return None;
+        };
+        Some(input::create_mouse_event(
+            self.local_to_view(position),
+            self.get_pixel_scale_factor(),
+            self.get_device_scale_factor(),
+            modifiers as i32,
+        ))
+    }
+
+    pub(super) fn inject_click(&mut self, position: Vector2, button: i64, double: bool) -> bool {
+        let Some((button_type, button_flag)) =
+            MouseButton::try_from_ord(button as i32).and_then(input::mouse_button_type)
+        else {
+            godot::global::godot_error!(
+                "[CefTexture] Cannot inject a click of mouse button {}",
+                button
+            );
+            return false;
+        };
+        let (Some(hover), Some(pressed)) = (
+            self.injected_mouse_event(position, 0),
+            self.injected_mouse_event(position, button_flag),
+        ) else {
+            return false;
+        };
+        let Some(host) = self.injection_host() else {
+            return false;
+        };
+
+        // Hover first, as a real pointer would
+        host.send_mouse_move_event(Some(&hover), false as _);
+        let click_counts = if double { 1..=2 } else { 1..=1 };
+        for click_count in click_counts {
+            host.send_mouse_click_event(Some(&pressed), button_type, false as _, click_count);
+            host.send_mouse_click_event(Some(&hover), button_type, true as _, click_count);
+        }
+        true
+    }
+
+    pub(super) fn inject_move(&mut self, position: Vector2) -> bool {
+        let Some(mouse_event) = self.injected_mouse_event(position, 0) else {
+            return false;
+        };
+        let Some(host) = self.injection_host() else {
+            return false;
+        };
+        input::send_mouse_move(&host, &mouse_event);
+        true
+    }
+
+    pub(super) fn inject_wheel(&mut self, position: Vector2, notches: Vector2) -> bool {
+        let Some(mouse_event) = self.injected_mouse_event(position, 0) else {
+            return false;
+        };
+        let Some(host) = self.injection_host() else {
+            return false;
+        };
+        let (delta_x, delta_y) = input::wheel_deltas(notches);
+        host.send_mouse_wheel_event(Some(&mouse_event), delta_x, delta_y);
+        true
+    }
+
+    pub(super) fn inject_key_event(
+        &mut self,
+        keycode: i64,
+        unicode: i64,
+        pressed: bool,
+        modifiers: i64,
+    ) -> bool {
+        let Some(keycode) = Key::try_from_ord(keycode as i32) else {
+            godot::global::godot_error!("[CefTexture] Cannot inject unknown keycode {}", keycode);
+            return false;
+        };
+        let Some(host) = self.injection_host() else {
+            return false;
+        };
+
+        let key = KeyInput {
+            keycode,
+            unicode: unicode as u32,
+            pressed,
+            echo: false,
+            modifiers: input::key_modifier_mask_flags(modifiers as u32),
+        };
+        host.set_focus(true as _);
+        // Injected keys bypass the IME proxy, so characters are always typed
+        // with CHAR events
+        for key_event in input::key_events(&key, false) {
+            host.send_key_event(Some(&key_event));
+        }
+        true
+    }
+
+    pub(super) fn inject_text_events(&mut self, text: &str) -> bool {
+        let Some(host) = self.injection_host() else {
+            return false;
+        };
+        host.set_focus(true as _);
+        for char_event in input::text_char_events(text) {
+            host.send_key_event(Some(&char_event));
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_in_bounds() {
+        let size = Vector2::new(200.0, 100.0);
+        let inside = Vector2::new(50.0, 25.0);
+        assert_eq!(position_in_bounds(inside, size, false), Some(inside));
+        assert_eq!(position_in_bounds(size, size, false), Some(size));
+        assert_eq!(
+            position_in_bounds(Vector2::new(250.0, 50.0), size, false),
+            None
+        );
+        assert_eq!(
+            position_in_bounds(Vector2::new(250.0, -10.0), size, true),
+            Some(Vector2::new(200.0, 0.0))
+        );
+        assert_eq!(
+            position_in_bounds(Vector2::new(f32::NAN, 10.0), size, true),
+            None
+        );
+    }
+}
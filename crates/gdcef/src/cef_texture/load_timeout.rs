@@ -0,0 +1,195 @@
+//! Load timeout: `load_timeout_seconds` and `fallback_url`.
+//!
+//! A main-frame load that neither finishes nor fails in time is stopped and
+//! reported with `load_timed_out`, and the browser goes to `fallback_url`
+//! if one is set. The timer starts when the browser starts loading, before
+//! the navigation commits, so an unreachable host is caught too. Progress
+//! of the load restarts it, so a slow page that is still receiving data is
+//! not cut off. The timer is checked while the node processes, so it ends
+//! with the node.
+
+use std::time::{Duration, Instant};
+
+use godot::prelude::*;
+
+use super::CefTexture;
+use crate::browser::{LoadingStateChangeEvent, LoadingStateEvent};
+
+#[derive(Default)]
+pub(super) struct LoadTimeout {
+    deadline: Option<Instant>,
+    /// URL of the load once it committed.
+    url: Option<String>,
+    /// Highest progress reported for the load.
+    progress: f64,
+}
+
+impl LoadTimeout {
+    fn start(&mut self, now: Instant, timeout: Duration) {
+        self.deadline = Some(now + timeout);
+        self.url = None;
+        self.progress = 0.0;
+    }
+
+    /// Restarts the timer of a running load that made progress.
+    fn progress(&mut self, progress: f64, now: Instant, timeout: Duration) {
+        if self.deadline.is_some() && progress > self.progress {
+            self.progress = progress;
+            self.deadline = Some(now + timeout);
+        }
+    }
+
+    fn stop(&mut self) {
+        self.deadline = None;
+        self.url = None;
+    }
+
+    /// Stops the timer if it ran out, returning the URL of the load if it
+    /// committed.
+    fn expire(&mut self, now: Instant) -> Option<Option<String>> {
+        let deadline = self.deadline?;
+        if now < deadline {
+            return None;
+        }
+        let url = self.url.take();
+        self.stop();
+        Some(url)
+    }
+}
+
+impl CefTexture {
+    fn load_timeout_duration(&self) -> Option<Duration> {
+        let seconds = self.load_timeout_seconds;
+        (seconds.is_finite() && seconds > 0.0).then(|| Duration::from_secs_f32(seconds))
+    }
+
+    /// Starts, restarts and stops the timer from the loading events.
+    pub(super) fn update_load_timeout(
+        &mut self,
+        loading_states: &[LoadingStateEvent],
+        loading_state_changes: &[LoadingStateChangeEvent],
+        loading_progress: Option<f64>,
+    ) {
+        let Some(timeout) = self.load_timeout_duration() else {
+            self.load_timeout.stop();
+            return;
+        };
+        let now = Instant::now();
+
+        for change in loading_state_changes {
+            match (change.is_loading, self.load_timeout.deadline.is_some()) {
+                (true, false) => self.load_timeout.start(now, timeout),
+                (false, true) => self.load_timeout.stop(),
+                _ => {}
+            }
+        }
+        for event in loading_states.iter().filter(|event| event.is_main()) {
+            match event {
+                LoadingStateEvent::Started { url, .. } => {
+                    if self.load_timeout.deadline.is_none() {
+                        self.load_timeout.start(now, timeout);
+                    }
+                    self.load_timeout.url = Some(url.clone());
+                }
+                LoadingStateEvent::Finished { .. } | LoadingStateEvent::Error { .. } => {
+                    self.load_timeout.stop();
+                }
+            }
+        }
+        if let Some(progress) = loading_progress {
+            self.load_timeout.progress(progress, now, timeout);
+        }
+    }
+
+    /// Stops a load that ran out of time. Called once per frame.
+    pub(super) fn check_load_timeout(&mut self) {
+        let Some(url) = self.load_timeout.expire(Instant::now()) else {
+            return;
+        };
+        // Before the navigation commits only the requested URL is known
+        let url = url.unwrap_or_else(|| self.url.to_string());
+
+        self.stop_loading();
+        godot::global::godot_warn!(
+            "[CefTexture] Loading {} timed out after {} seconds",
+            url,
+            self.load_timeout_seconds
+        );
+        self.emit_browser_signal(
+            "load_timed_out",
+            &[("url", GString::from(url.as_str()).to_variant())],
+        );
+
+        // The fallback timing out too must not load it over and over
+        let fallback = self.fallback_url.clone();
+        if !fallback.is_empty() && fallback.to_string() != url {
+            self.navigate(fallback);
+        }
+    }
+
+    pub(super) fn cancel_load_timeout(&mut self) {
+        self.load_timeout.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TIMEOUT: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn test_load_timeout_expires_once() {
+        let start = Instant::now();
+        let mut timeout = LoadTimeout::default();
+        assert_eq!(timeout.expire(start + TIMEOUT), None);
+
+        timeout.start(start, TIMEOUT);
+        assert_eq!(timeout.expire(start + Duration::from_secs(9)), None);
+        assert_eq!(timeout.expire(start + TIMEOUT), Some(None));
+        assert_eq!(timeout.expire(start + TIMEOUT * 2), None);
+    }
+
+    #[test]
+    fn test_load_timeout_reports_committed_url() {
+        let start = Instant::now();
+        let mut timeout = LoadTimeout::default();
+        timeout.start(start, TIMEOUT);
+        timeout.url = Some("https://example.com/".to_string());
+        assert_eq!(
+            timeout.expire(start + TIMEOUT),
+            Some(Some("https://example.com/".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_progress_restarts_timer() {
+        let start = Instant::now();
+        let mut timeout = LoadTimeout::default();
+        timeout.start(start, TIMEOUT);
+
+        let later = start + Duration::from_secs(8);
+        timeout.progress(0.3, later, TIMEOUT);
+        assert_eq!(timeout.expire(start + TIMEOUT), None);
+        // The same progress again is no progress
+        timeout.progress(0.3, later + Duration::from_secs(8), TIMEOUT);
+        assert_eq!(timeout.expire(later + TIMEOUT), Some(None));
+    }
+
+    #[test]
+    fn test_progress_without_load_does_not_start_timer() {
+        let start = Instant::now();
+        let mut timeout = LoadTimeout::default();
+        timeout.progress(0.5, start, TIMEOUT);
+        assert_eq!(timeout.expire(start + TIMEOUT), None);
+    }
+
+    #[test]
+    fn test_stop_cancels_timer() {
+        let start = Instant::now();
+        let mut timeout = LoadTimeout::default();
+        timeout.start(start, TIMEOUT);
+        timeout.stop();
+        assert_eq!(timeout.expire(start + TIMEOUT), None);
+    }
+}
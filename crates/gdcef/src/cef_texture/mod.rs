@@ -1,25 +1,55 @@
+mod accessibility;
+mod audio;
 mod browser_lifecycle;
+mod configuration;
+mod cookies;
+mod debug_overlay;
+mod dev_tools_front_end;
+mod devtools;
+mod display_media;
+mod dom_query;
+mod drag_files;
+mod editor_preview;
+mod gamepad;
+mod gpu_info;
+mod hit_test;
+mod idle;
 mod ime;
+mod injection;
+mod load_timeout;
+mod native_drag;
+mod navigation;
+mod page_save;
+mod paginated_capture;
+mod pause;
+mod render_target;
 mod rendering;
+mod session;
 mod signals;
+mod user_gesture;
+mod user_scripts;
+mod web_notifications;
+mod zoom;
 
 use cef::{
-    self, ImplBrowser, ImplBrowserHost, ImplDragData, ImplFrame, ImplListValue, ImplProcessMessage,
-    do_message_loop_work,
+    self, ImplBrowser, ImplBrowserHost, ImplCallback, ImplDragData, ImplFrame, ImplListValue,
+    ImplProcessMessage, ImplSelectClientCertificateCallback,
 };
 use godot::classes::notify::ControlNotification;
 use godot::classes::texture_rect::ExpandMode;
 use godot::classes::{
-    ITextureRect, ImageTexture, InputEvent, InputEventKey, InputEventMouseButton,
+    Engine, ITextureRect, ImageTexture, InputEvent, InputEventKey, InputEventMouseButton,
     InputEventMouseMotion, InputEventPanGesture, LineEdit, TextureRect,
 };
 use godot::prelude::*;
 
-use crate::browser::App;
-use crate::{cef_init, input};
+use crate::browser::{App, RenderMode};
+use crate::{cef_init, frame_scheduler, godot_protocol, input};
 
 #[derive(GodotClass)]
-#[class(base=TextureRect)]
+// `tool` so the editor shows configuration warnings; everything else is
+// skipped in the editor
+#[class(tool, base=TextureRect)]
 pub struct CefTexture {
     base: Base<TextureRect>,
     app: App,
@@ -29,11 +59,214 @@ pub struct CefTexture {
     url: GString,
 
     #[export]
+    /// Assigning the URL the browser is already at does not load it again.
+    /// `navigate` always loads.
+    ignore_same_url_loads: bool,
+
+    #[export]
+    #[var(get, set = set_enable_accelerated_osr)]
     enable_accelerated_osr: bool,
 
     #[export]
     background_color: Color,
 
+    #[export]
+    /// Draw the page in an internal `SubViewport` the node shows, for
+    /// post-processing; see `get_output_viewport`. Takes effect when the
+    /// browser is created.
+    render_target: render_target::RenderTarget,
+
+    #[export]
+    #[var(get, set = set_generate_mipmaps)]
+    /// Give the browser texture mipmaps, so it does not shimmer when drawn
+    /// scaled down. Takes about a third more texture memory, and a
+    /// `texture_filter_override` with mipmaps to be used. Software rendering
+    /// generates them on the CPU for each frame, skipping frames over
+    /// 2560×1600 unless `force_mipmaps` is set. Accelerated rendering draws
+    /// them on the GPU after each copy, waiting for the copy to finish first.
+    generate_mipmaps: bool,
+
+    #[export]
+    /// Generate software-rendered mipmaps for frames over 2560×1600 too.
+    force_mipmaps: bool,
+
+    #[export]
+    #[var(get, set = set_texture_filter_override)]
+    /// Sets the node's `texture_filter`, which the popup overlay follows.
+    /// `Inherit` leaves it as it is.
+    texture_filter_override: rendering::TextureFilterOverride,
+
+    #[export]
+    /// Remember certificate errors allowed with `resolve_certificate_error` for the
+    /// rest of the session, so the same certificate does not prompt again.
+    remember_certificate_decisions: bool,
+
+    #[export]
+    /// Open external protocol links (`mailto:`, `steam://`, ...) with `OS.shell_open`
+    /// when their scheme is listed in `godot_cef/security/auto_open_protocols`.
+    auto_open_external_protocols: bool,
+
+    #[export]
+    #[var(get, set = set_allow_res_scheme)]
+    /// Serve `res://` URLs from the project's resources in this browser.
+    allow_res_scheme: bool,
+
+    #[export]
+    #[var(get, set = set_allow_user_scheme)]
+    /// Serve `user://` URLs from the user data directory in this browser.
+    allow_user_scheme: bool,
+
+    #[export]
+    /// Only serve `res://` requests made by `res://` or `user://` pages, so
+    /// remote pages cannot read project files. Top-level navigations without
+    /// a referrer (e.g. setting `url`) are still allowed.
+    restrict_res_scheme_origin: bool,
+
+    #[export]
+    #[var(get, set = set_preferred_color_scheme)]
+    /// Value of the CSS `prefers-color-scheme` media feature for the pages.
+    /// `System` follows the OS setting.
+    preferred_color_scheme: devtools::PreferredColorScheme,
+
+    #[export(range = (0.5, 3.0, 0.05))]
+    #[var(get, set = set_text_scale)]
+    /// Scale of the pages on top of the zoom level, for a game-wide text
+    /// size setting. The two multiply.
+    text_scale: f32,
+
+    #[export(range = (0.0, 72.0))]
+    /// Smallest font size, in CSS pixels, the pages may use; smaller text is
+    /// enlarged. 0 keeps Chromium's default. Takes effect when the browser is
+    /// created.
+    minimum_font_size: i32,
+
+    #[export(range = (0.0, 72.0))]
+    /// Font size, in CSS pixels, of text without a `font-size`. 0 keeps
+    /// Chromium's default of 16. Takes effect when the browser is created.
+    default_font_size: i32,
+
+    #[export]
+    #[var(get, set = set_allow_autoplay)]
+    /// Let the pages play media, including sound, without a click first.
+    /// Only the main frame is activated; cross-origin iframes need
+    /// `allow="autoplay"`.
+    allow_autoplay: bool,
+
+    #[export]
+    /// Browsers of the `CefTexture`s with the same non-empty group share one
+    /// request context, so Chromium can run their same-site pages in one
+    /// renderer process. Takes effect when the browser is created.
+    renderer_group: GString,
+
+    #[export]
+    /// Capture the audio of the pages for `create_audio_stream` instead of
+    /// playing it. Takes effect when the browser is created.
+    audio_capture: audio::AudioCapture,
+
+    #[export]
+    /// Enter background mode when the application loses focus and leave it
+    /// when focus returns. See `set_background_mode`.
+    auto_background_mode: bool,
+
+    #[export]
+    /// Mute the page while the node is paused with the scene tree. The page
+    /// is hidden while paused either way; set `process_mode` to `Always` to
+    /// keep it running.
+    mute_when_paused: bool,
+
+    #[export]
+    /// Skip paints Godot would never show, or lower the frame rate CEF paints
+    /// at to the one Godot reaches.
+    frame_pacing: crate::frame_pacing::FramePacing,
+
+    #[export]
+    /// Drive the page's frames from Godot's process loop, or let CEF schedule
+    /// them so the page keeps running while the game stalls. Applied when
+    /// the browser is created.
+    frame_scheduling: crate::frame_scheduler::FrameScheduling,
+
+    #[export]
+    /// Seconds without paints, audio, page events or input after which the
+    /// browser goes idle: it gets no frames until input, a message sent to
+    /// the page or a visibility change wakes it. `0` never goes idle.
+    idle_timeout_seconds: f32,
+
+    #[export]
+    /// Also report the loads of iframes, with the `subframe_load_*` signals.
+    include_subframe_loads: bool,
+
+    #[export]
+    /// Seconds a main-frame load may take before it is stopped and
+    /// `load_timed_out` is emitted. Progress of the load restarts the
+    /// timer. `0` disables the timeout.
+    load_timeout_seconds: f32,
+
+    #[export]
+    /// URL loaded after a load timed out. Empty to stay on the stopped page.
+    fallback_url: GString,
+
+    #[export]
+    /// Run drags started in the page as Godot drags, and drop Godot drags
+    /// carrying a `DragDataInfo`, Dictionary or file list onto the page.
+    native_drag_integration: bool,
+
+    #[export]
+    /// Only emit `browser_event` instead of both it and the individual
+    /// signals. While enabled, connections to the individual signals never
+    /// fire; only `painted`, which backs `wait_until_painted()` and has no
+    /// `browser_event` counterpart, is still emitted.
+    use_unified_events: bool,
+
+    #[export]
+    /// Let clicks on transparent parts of the page through to what is
+    /// underneath the node.
+    click_through_transparent: bool,
+
+    #[export]
+    /// Alpha (0 to 1) below which a pixel counts as transparent for
+    /// `click_through_transparent`.
+    click_through_alpha_threshold: f32,
+
+    #[export]
+    /// Send pen strokes to the page as pen pointer events with pressure,
+    /// instead of mouse events. Disable for pages that only handle mice.
+    pen_as_touch: bool,
+
+    #[export]
+    /// Send an Enter pressed right after an IME commit to the page when the
+    /// input method did not use it, so forms submitting on Enter see it.
+    forward_enter_after_commit: bool,
+
+    #[export]
+    /// Browse the page with a gamepad while the node has focus, using the
+    /// actions of the `godot_cef/gamepad/*` project settings.
+    gamepad_navigation: bool,
+
+    #[export]
+    /// Whether the gamepad moves between elements or a virtual cursor.
+    gamepad_navigation_mode: gamepad::GamepadNavigationMode,
+
+    #[export]
+    /// Speed of the virtual cursor in pixels per second, before it
+    /// accelerates.
+    gamepad_cursor_speed: f32,
+
+    #[export]
+    /// Drawn at the virtual cursor, with its top-left corner at the hotspot.
+    /// No cursor is drawn when unset.
+    gamepad_cursor_texture: Option<Gd<godot::classes::Texture2D>>,
+
+    #[export]
+    /// Viewport streamed to pages offered the game viewport with
+    /// `offer_display_media`.
+    /// Defaults to the viewport the node is in.
+    display_media_viewport: Option<Gd<godot::classes::Viewport>>,
+
+    #[var]
+    /// Move positions of the `inject_*` methods that fall outside the node to
+    /// its nearest edge instead of rejecting the event.
+    clamp_injected_positions: bool,
+
     #[var]
     /// Stores the IME cursor position in local coordinates (relative to this `CefTexture` node),
     /// automatically updated from the browser's caret position.
@@ -42,12 +275,79 @@ pub struct CefTexture {
     // Change detection state
     last_size: Vector2,
     last_dpi: f32,
+    last_display_scale: f32,
+    resize_debouncer: crate::resize_debounce::ResizeDebouncer,
     last_cursor: cef_app::CursorType,
     last_max_fps: i32,
+    /// Zoom level set with `set_zoom_level`, without `text_scale`.
+    user_zoom_level: f64,
+    /// The main frame's document reported its first contentful paint.
+    page_painted: bool,
+    /// Time by which a new browser's renderer process must have reported,
+    /// see `browser_startup_timeout`.
+    renderer_startup_deadline: Option<std::time::Instant>,
+    native_drag: native_drag::NativeDragState,
+    drag_files: drag_files::DragFilesState,
+    load_timeout: load_timeout::LoadTimeout,
+
+    // Page visibility state
+    page_hidden: bool,
+    page_occluded: bool,
+    background_mode: bool,
+    /// The node cannot process because the scene tree is paused.
+    paused: bool,
+    /// Audio was muted by the pause, so leaving it unmutes.
+    muted_by_pause: bool,
+    /// `CefManager.set_global_mute()` state applied to the browser.
+    global_mute: bool,
+    /// Audio was muted by the global mute, so lifting it unmutes.
+    muted_by_global_mute: bool,
+
+    // Editor preview state
+    /// The page is previewed in the editor.
+    editor_preview: bool,
+    /// Texture taken off the node while the scene is saved.
+    editor_saved_texture: Option<Gd<godot::classes::Texture2D>>,
+
+    // Render statistics state
+    emit_render_stats: bool,
+    last_render_stats: crate::render_stats::RenderStatsSnapshot,
+
+    // Frame scheduling state
+    begin_frame_scheduler: frame_scheduler::BeginFrameScheduler,
+    idle: idle::IdleDetector,
+
+    // Input state
+    mouse_move_coalescer: input::MouseMoveCoalescer<cef::MouseEvent>,
+    click_counter: input::ClickCounter<godot::global::MouseButton>,
+    pen: input::PenTracker,
+    gamepad: gamepad::GamepadState,
+    alpha_mask: hit_test::AlphaMaskReadback,
+    display_media: display_media::DisplayMediaState,
+    web_notifications: web_notifications::WebNotifications,
+    dev_tools_front_end: dev_tools_front_end::DevToolsFrontEnd,
+    browser_gpu_info: gpu_info::BrowserGpuInfo,
+    user_scripts: user_scripts::UserScripts,
+    user_gesture_evals: user_gesture::UserGestureEvals,
+    output_viewport: render_target::OutputViewport,
+    startup_scripts: Vec<String>,
+    session: session::SessionState,
+    paginated_capture: paginated_capture::PaginatedCaptureState,
+    debug_overlay: debug_overlay::DebugOverlay,
+    ime_keys: input::ImeKeyFilter,
+    accessibility_tree: crate::accessibility::AccessibilityTree,
 
     // IME state
     ime_active: bool,
     ime_proxy: Option<Gd<LineEdit>>,
+    /// Kind of the focused text field, which the proxy is configured for.
+    ime_input_type: cef_app::TextInputType,
+    /// IME composition in progress, with empty text when not composing.
+    ime_composition: ime::ImeComposition,
+    /// Last caret reported by the page.
+    ime_caret: Option<ime::ImeCaret>,
+    /// Inputs of the last IME placement, to redo it only when they change.
+    ime_placement: Option<ime::ImePlacement>,
     ime_focus_regrab_pending: bool,
 
     // Popup state
@@ -55,6 +355,20 @@ pub struct CefTexture {
     popup_texture: Option<Gd<ImageTexture>>,
     #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
     popup_texture_2d_rd: Option<Gd<godot::classes::Texture2Drd>>,
+    /// Texture recreated at a new size, shown once a frame at that size has
+    /// been copied into it, and the RID of the texture still on display.
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    resized_texture: Option<(Gd<godot::classes::Texture2Drd>, Rid)>,
+
+    // GPU device loss state
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    device_recovery: Option<crate::accelerated_osr::DeviceLossRecovery>,
+    /// Set once accelerated rendering was given up after a GPU device loss.
+    force_software_rendering: bool,
+
+    /// Set under headless Godot and after `CefManager.shutdown()`, where the
+    /// node never creates a browser.
+    disabled: bool,
 }
 
 #[godot_api]
@@ -64,24 +378,118 @@ impl ITextureRect for CefTexture {
             base,
             app: App::default(),
             url: "https://google.com".into(),
+            ignore_same_url_loads: true,
             enable_accelerated_osr: true,
             background_color: Color::from_rgba(0.0, 0.0, 0.0, 0.0),
+            render_target: render_target::RenderTarget::TextureRect,
+            generate_mipmaps: false,
+            force_mipmaps: false,
+            texture_filter_override: rendering::TextureFilterOverride::Inherit,
+            remember_certificate_decisions: false,
+            auto_open_external_protocols: false,
+            allow_res_scheme: true,
+            allow_user_scheme: false,
+            restrict_res_scheme_origin: false,
+            preferred_color_scheme: devtools::PreferredColorScheme::System,
+            text_scale: 1.0,
+            minimum_font_size: 0,
+            default_font_size: 0,
+            allow_autoplay: false,
+            audio_capture: audio::AudioCapture::ProjectDefault,
+            renderer_group: GString::new(),
+            auto_background_mode: false,
+            mute_when_paused: false,
+            frame_pacing: crate::frame_pacing::FramePacing::Off,
+            frame_scheduling: crate::frame_scheduler::FrameScheduling::ExternalBeginFrame,
+            idle_timeout_seconds: 0.0,
+            include_subframe_loads: false,
+            load_timeout_seconds: 0.0,
+            fallback_url: GString::new(),
+            native_drag_integration: false,
+            use_unified_events: false,
+            click_through_transparent: false,
+            pen_as_touch: true,
+            forward_enter_after_commit: true,
+            gamepad_navigation: false,
+            gamepad_navigation_mode: gamepad::GamepadNavigationMode::SpatialNavigation,
+            gamepad_cursor_speed: 400.0,
+            gamepad_cursor_texture: None,
+            click_through_alpha_threshold: 0.1,
+            display_media_viewport: None,
+            clamp_injected_positions: false,
             ime_position: Vector2i::new(0, 0),
             last_size: Vector2::ZERO,
             last_dpi: 1.0,
+            last_display_scale: 1.0,
+            resize_debouncer: Default::default(),
             last_cursor: cef_app::CursorType::Arrow,
             last_max_fps: 0,
+            user_zoom_level: 0.0,
+            page_painted: false,
+            renderer_startup_deadline: None,
+            native_drag: Default::default(),
+            drag_files: Default::default(),
+            load_timeout: Default::default(),
+            page_hidden: false,
+            page_occluded: false,
+            background_mode: false,
+            paused: false,
+            muted_by_pause: false,
+            editor_preview: false,
+            editor_saved_texture: None,
+            emit_render_stats: false,
+            last_render_stats: Default::default(),
+            begin_frame_scheduler: Default::default(),
+            idle: Default::default(),
+            mouse_move_coalescer: Default::default(),
+            click_counter: Default::default(),
+            pen: Default::default(),
+            gamepad: Default::default(),
+            alpha_mask: Default::default(),
+            display_media: Default::default(),
+            web_notifications: Default::default(),
+            dev_tools_front_end: Default::default(),
+            browser_gpu_info: Default::default(),
+            user_scripts: Default::default(),
+            user_gesture_evals: Default::default(),
+            output_viewport: Default::default(),
+            startup_scripts: Vec::new(),
+            session: Default::default(),
+            paginated_capture: Default::default(),
+            debug_overlay: Default::default(),
+            ime_keys: Default::default(),
+            accessibility_tree: Default::default(),
             ime_active: false,
             ime_proxy: None,
+            ime_input_type: Default::default(),
+            ime_composition: ime::ImeComposition::default(),
+            ime_caret: None,
+            ime_placement: None,
             ime_focus_regrab_pending: false,
             popup_overlay: None,
             popup_texture: None,
             #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
             popup_texture_2d_rd: None,
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            resized_texture: None,
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            device_recovery: None,
+            force_software_rendering: false,
+            disabled: false,
+            global_mute: false,
+            muted_by_global_mute: false,
         }
     }
 
     fn on_notification(&mut self, what: ControlNotification) {
+        if self.disabled {
+            return;
+        }
+        if Engine::singleton().is_editor_hint() {
+            self.on_editor_notification(what);
+            return;
+        }
+
         match what {
             ControlNotification::READY => {
                 self.on_ready();
@@ -95,16 +503,52 @@ impl ITextureRect for CefTexture {
             ControlNotification::FOCUS_ENTER => {
                 self.on_focus_enter();
             }
+            ControlNotification::FOCUS_EXIT => {
+                self.cancel_pending_ime_composition();
+            }
             ControlNotification::OS_IME_UPDATE => {
                 self.handle_os_ime_update();
             }
+            ControlNotification::DRAG_END => {
+                self.end_native_drag();
+            }
+            ControlNotification::WM_MOUSE_EXIT => {
+                self.leave_native_drag_target();
+            }
+            ControlNotification::APPLICATION_FOCUS_OUT if self.auto_background_mode => {
+                self.set_background_mode(true);
+            }
+            ControlNotification::APPLICATION_FOCUS_IN if self.auto_background_mode => {
+                self.set_background_mode(false);
+            }
             _ => {}
         }
     }
 
     fn input(&mut self, event: Gd<InputEvent>) {
+        if Engine::singleton().is_editor_hint() {
+            return;
+        }
+        let event = self.input_event_to_view(event);
         self.handle_input_event(event);
     }
+
+    fn can_drop_data(&self, at_position: Vector2, data: Variant) -> bool {
+        self.can_drop_native_drag(at_position, &data)
+    }
+
+    fn drop_data(&mut self, at_position: Vector2, data: Variant) {
+        self.drop_native_drag(at_position, &data);
+    }
+
+    fn get_configuration_warnings(&self) -> PackedStringArray {
+        self.configuration_warnings()
+    }
+
+    fn has_point(&self, point: Vector2) -> bool {
+        Rect2::new(Vector2::ZERO, self.base().get_size()).contains_point(point)
+            && !self.is_click_through(self.local_to_view(point))
+    }
 }
 
 #[godot_api]
@@ -121,6 +565,61 @@ impl CefTexture {
     #[signal]
     fn title_changed(title: GString);
 
+    #[signal]
+    fn security_state_changed(info: VarDictionary);
+
+    #[signal]
+    fn certificate_error(error_id: i64, url: GString, status: i64, cert_info: VarDictionary);
+
+    #[signal]
+    fn client_certificate_requested(
+        request_id: i64,
+        host: GString,
+        certificates: Array<VarDictionary>,
+    );
+
+    #[signal]
+    fn navigation_blocked(url: GString, user_gesture: bool);
+
+    #[signal]
+    fn external_protocol_requested(url: GString);
+
+    #[signal]
+    fn devtools_result(id: i64, success: bool, result: VarDictionary);
+
+    #[signal]
+    fn devtools_event(method: GString, params: VarDictionary);
+
+    #[signal]
+    fn page_saved(path: GString, ok: bool);
+
+    #[signal]
+    fn scrolled(position: Vector2, max: Vector2);
+
+    #[signal]
+    fn page_captured_sequence(images: Array<Gd<godot::classes::Image>>);
+
+    #[signal]
+    fn first_contentful_paint(url: GString, time_ms: f64);
+
+    #[signal]
+    fn painted();
+
+    #[signal]
+    fn browser_startup_timeout();
+
+    #[signal]
+    fn dom_dumped(selector: GString, html: GString);
+
+    #[signal]
+    fn elements_counted(selector: GString, count: i32);
+
+    #[signal]
+    fn accessibility_tree_changed(updated: Array<VarDictionary>, removed: PackedInt32Array);
+
+    #[signal]
+    fn accessibility_focus_changed(node: VarDictionary);
+
     #[signal]
     fn load_started(url: GString);
 
@@ -130,6 +629,21 @@ impl CefTexture {
     #[signal]
     fn load_error(url: GString, error_code: i32, error_text: GString);
 
+    #[signal]
+    fn load_timed_out(url: GString);
+
+    #[signal]
+    fn subframe_load_started(url: GString, frame_name: GString);
+
+    #[signal]
+    fn subframe_load_finished(url: GString, http_status_code: i32, frame_name: GString);
+
+    #[signal]
+    fn subframe_load_error(url: GString, error_code: i32, error_text: GString, frame_name: GString);
+
+    #[signal]
+    fn loading_state_changed(is_loading: bool, can_go_back: bool, can_go_forward: bool);
+
     #[signal]
     fn console_message(level: u32, message: GString, source: GString, line: i32);
 
@@ -142,28 +656,90 @@ impl CefTexture {
     #[signal]
     fn drag_entered(drag_data: Gd<crate::drag::DragDataInfo>, mask: i32);
 
+    #[signal]
+    fn drag_files_ready(paths: PackedStringArray);
+
     #[signal]
     fn download_requested(download_info: Gd<crate::cef_texture::signals::DownloadRequestInfo>);
 
     #[signal]
     fn download_updated(download_info: Gd<crate::cef_texture::signals::DownloadUpdateInfo>);
 
+    #[signal]
+    fn scheme_request_completed(info: VarDictionary);
+
+    #[signal]
+    fn render_stats_updated(stats: VarDictionary);
+
+    #[signal]
+    fn gpu_device_lost();
+
+    #[signal]
+    fn ime_composition_changed(text: GString, cursor: i64);
+
+    #[signal]
+    fn display_media_requested(request_id: i64);
+
+    #[signal]
+    fn web_notification(title: GString, body: GString, icon_url: GString, tag: GString);
+
+    #[signal]
+    fn web_notification_closed(tag: GString);
+
+    #[signal]
+    fn idle_state_changed(idle: bool);
+
+    #[signal]
+    fn browser_shortcut(action_name: StringName);
+
+    #[signal]
+    fn browser_gpu_info(info: VarDictionary);
+
+    #[signal]
+    fn cookies_exported(data: PackedByteArray);
+
+    #[signal]
+    fn cookies_imported(ok_count: i64, failed: VarArray);
+
+    #[signal]
+    fn browser_event(event_type: StringName, data: VarDictionary);
+
     #[func]
     fn on_ready(&mut self) {
         use godot::classes::control::FocusMode;
+
+        if cef_init::is_headless() {
+            self.disabled = true;
+            static HEADLESS_WARNING: std::sync::Once = std::sync::Once::new();
+            HEADLESS_WARNING.call_once(|| {
+                godot::global::godot_warn!(
+                    "[CefTexture] Godot is running headless; CefTexture nodes are disabled"
+                );
+            });
+            return;
+        }
+        if cef_init::is_shut_down() {
+            self.disabled = true;
+            return;
+        }
+
         self.base_mut().set_expand_mode(ExpandMode::IGNORE_SIZE);
         // Must explicitly enable processing when using on_notification instead of fn process()
         self.base_mut().set_process(true);
         // Enable focus so we receive FOCUS_ENTER/EXIT notifications and can forward to CEF
         self.base_mut().set_focus_mode(FocusMode::CLICK);
 
-        if let Err(e) = cef_init::cef_retain() {
+        if let Err(e) = cef_init::cef_retain(self.base().instance_id()) {
             godot::global::godot_error!("[CefTexture] {}", e);
             return;
         }
+        // A new foreground browser needs the helpers at normal priority
+        crate::helper_priority::refresh();
 
         // Create hidden LineEdit for IME proxy
         self.create_ime_proxy();
+        self.init_debug_overlay();
+        self.init_pause_tracking();
 
         // Only create browser if we have a valid size.
         // If size is 0 (e.g., inside a Container that hasn't laid out yet),
@@ -186,19 +762,53 @@ impl CefTexture {
         }
 
         self.handle_max_fps_change();
+        self.update_output_viewport();
         _ = self.handle_size_change();
-        self.update_texture();
-
-        do_message_loop_work();
-
-        self.request_external_begin_frame();
+        let idle = self.update_idle_state();
+        if !idle {
+            self.update_texture();
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            self.update_alpha_mask();
+            self.stream_display_media();
+        }
+        self.flush_mouse_move();
+        self.flush_ime_keys();
+        self.update_gamepad_navigation();
+        self.update_native_drag();
+
+        if idle {
+            frame_scheduler::pump_message_loop_idle();
+        } else {
+            frame_scheduler::pump_message_loop();
+            self.request_external_begin_frame();
+        }
         self.update_cursor();
+        self.update_ime_placement();
+        self.emit_render_stats_if_due();
+        self.update_debug_overlay();
 
         // Process all event queues with a single lock (more efficient than per-queue locks)
         self.process_all_event_queues();
+        self.update_paginated_capture();
+        self.check_load_timeout();
+        self.check_renderer_startup();
+        self.check_dev_tools_front_end();
+
+        // Answer requests for custom schemes backed by GDScript callables
+        godot_protocol::process_pending_scheme_requests();
     }
 
     fn handle_input_event(&mut self, event: Gd<InputEvent>) {
+        self.wake_page();
+        if self.handle_gamepad_event(&event) {
+            return;
+        }
+
+        // Deliver a held-back move first so CEF sees events in order
+        if !event.is_class("InputEventMouseMotion") {
+            self.flush_mouse_move();
+        }
+
         let Some(browser) = self.app.browser.as_mut() else {
             return;
         };
@@ -207,19 +817,50 @@ impl CefTexture {
         };
 
         if let Ok(mouse_button) = event.clone().try_cast::<InputEventMouseButton>() {
+            // Clicks on transparent pixels belong to what is underneath
+            if mouse_button.is_pressed() && self.is_click_through(mouse_button.get_position()) {
+                return;
+            }
+            let pixel_scale_factor = self.get_pixel_scale_factor();
+            let device_scale_factor = self.get_device_scale_factor();
+            if input::handle_pen_button(
+                &host,
+                &mouse_button,
+                &mut self.pen,
+                self.pen_as_touch,
+                pixel_scale_factor,
+                device_scale_factor,
+            ) {
+                return;
+            }
             input::handle_mouse_button(
                 &host,
                 &mouse_button,
-                self.get_pixel_scale_factor(),
-                self.get_device_scale_factor(),
+                &mut self.click_counter,
+                pixel_scale_factor,
+                device_scale_factor,
             );
         } else if let Ok(mouse_motion) = event.clone().try_cast::<InputEventMouseMotion>() {
-            input::handle_mouse_motion(
+            let pixel_scale_factor = self.get_pixel_scale_factor();
+            let device_scale_factor = self.get_device_scale_factor();
+            if input::handle_pen_motion(
                 &host,
                 &mouse_motion,
-                self.get_pixel_scale_factor(),
-                self.get_device_scale_factor(),
-            );
+                &mut self.pen,
+                pixel_scale_factor,
+                device_scale_factor,
+            ) {
+                return;
+            }
+            let (mouse_event, button_pressed) =
+                input::mouse_motion_event(&mouse_motion, pixel_scale_factor, device_scale_factor);
+            if let Some(mouse_event) = self.mouse_move_coalescer.push(
+                mouse_event,
+                button_pressed,
+                std::time::Instant::now(),
+            ) {
+                input::send_mouse_move(&host, &mouse_event);
+            }
         } else if let Ok(pan_gesture) = event.clone().try_cast::<InputEventPanGesture>() {
             input::handle_pan_gesture(
                 &host,
@@ -232,233 +873,1096 @@ impl CefTexture {
                 &host,
                 browser.main_frame().as_ref(),
                 &key_event,
-                self.ime_active,
+                self.ime_active.then_some(&mut self.ime_keys),
             );
         }
     }
 
+    /// Returns the `SubViewport` the page is drawn in when `render_target`
+    /// is `SubViewport`, e.g. to use its texture in a material. `null` in
+    /// the default mode or before the browser is created.
+    #[func]
+    pub fn get_output_viewport(&self) -> Option<Gd<godot::classes::SubViewport>> {
+        self.output_viewport()
+    }
+
+    /// Runs `code` in the main frame. With `isolated`, it runs in the
+    /// isolated world of isolated user scripts instead of the page's.
+    #[func]
+    pub fn eval(&mut self, code: GString, #[opt(default = false)] isolated: bool) {
+        self.wake_page();
+        if isolated {
+            if self.app.browser.is_none() {
+                godot::global::godot_warn!("[CefTexture] Cannot execute JS: no browser");
+                return;
+            }
+            self.eval_isolated(&code.to_string());
+            return;
+        }
+        let Some(browser) = self.app.browser.as_ref() else {
+            godot::global::godot_warn!("[CefTexture] Cannot execute JS: no browser");
+            return;
+        };
+        let Some(frame) = browser.main_frame() else {
+            godot::global::godot_warn!("[CefTexture] Cannot execute JS: no main frame");
+            return;
+        };
+
+        let code_str: cef::CefStringUtf16 = code.to_string().as_str().into();
+        frame.execute_java_script(Some(&code_str), None, 0);
+    }
+
+    /// Runs `code` in the main frame as if the user had just clicked the
+    /// page, so it may write to the clipboard, enter fullscreen or start
+    /// audio. The activation lasts a few seconds, like after a real click.
+    /// Use plain `eval` for everything else.
+    #[func]
+    pub fn eval_with_user_gesture(&mut self, code: GString) {
+        self.wake_page();
+        if !self.eval_with_user_gesture_impl(&code.to_string()) {
+            godot::global::godot_warn!("[CefTexture] Cannot execute JS: no browser");
+        }
+    }
+
+    #[func]
+    fn set_url_property(&mut self, url: GString) {
+        self.load_url_property(url, false);
+    }
+
+    /// Loads `url` in the main frame, also when the browser is already there.
+    #[func]
+    pub fn navigate(&mut self, url: GString) {
+        self.load_url_property(url, true);
+    }
+
+    #[func]
+    /// Sends a message into the page via `window.onIpcMessage`.
+    ///
+    /// This is intentionally separate from [`eval`]: callers could achieve a
+    /// similar effect with `eval("window.onIpcMessage(...);")`, but this
+    /// helper enforces a consistent IPC pattern (`window.onIpcMessage(message)`).
+    ///
+    /// Uses native CEF process messaging for efficient transfer without
+    /// script injection overhead.
+    ///
+    /// Use this when you want structured IPC into the page, and `eval` when
+    /// you truly need arbitrary JavaScript execution.
+    ///
+    /// With `user_gesture`, the handler runs with user activation like
+    /// `eval_with_user_gesture`; the message is then dropped if the page has
+    /// no handler yet instead of waiting for one.
+    pub fn send_ipc_message(
+        &mut self,
+        message: GString,
+        #[opt(default = false)] user_gesture: bool,
+    ) {
+        self.wake_page();
+        if user_gesture {
+            if !self.send_ipc_message_with_user_gesture(&message.to_string()) {
+                godot::global::godot_warn!("[CefTexture] Cannot send IPC message: no browser");
+            }
+            return;
+        }
+        let Some(browser) = self.app.browser.as_ref() else {
+            godot::global::godot_warn!("[CefTexture] Cannot send IPC message: no browser");
+            return;
+        };
+        let Some(frame) = browser.main_frame() else {
+            godot::global::godot_warn!("[CefTexture] Cannot send IPC message: no main frame");
+            return;
+        };
+
+        let route = cef::CefStringUtf16::from("ipcGodotToRenderer");
+        let msg_str: cef::CefStringUtf16 = message.to_string().as_str().into();
+
+        if let Some(mut process_message) = cef::process_message_create(Some(&route)) {
+            if let Some(argument_list) = process_message.argument_list() {
+                argument_list.set_string(0, Some(&msg_str));
+            }
+            frame.send_process_message(cef::ProcessId::RENDERER, Some(&mut process_message));
+        }
+    }
+
+    #[func]
+    /// Sends binary data into the page via `window.onIpcBinaryMessage`.
+    ///
+    /// The data will be delivered as an ArrayBuffer to the JavaScript callback
+    /// `window.onIpcBinaryMessage(arrayBuffer)` if it is registered.
+    ///
+    /// Uses native CEF process messaging with BinaryValue for zero-copy
+    /// binary transfer without encoding overhead.
+    pub fn send_ipc_binary_message(&mut self, data: PackedByteArray) {
+        self.wake_page();
+        let Some(browser) = self.app.browser.as_ref() else {
+            godot::global::godot_warn!("[CefTexture] Cannot send binary IPC message: no browser");
+            return;
+        };
+        let Some(frame) = browser.main_frame() else {
+            godot::global::godot_warn!(
+                "[CefTexture] Cannot send binary IPC message: no main frame"
+            );
+            return;
+        };
+
+        let route = cef::CefStringUtf16::from("ipcBinaryGodotToRenderer");
+        let bytes = data.to_vec();
+
+        let Some(mut binary_value) = cef::binary_value_create(Some(&bytes)) else {
+            godot::global::godot_warn!(
+                "[CefTexture] Cannot send binary IPC message: failed to create BinaryValue"
+            );
+            return;
+        };
+
+        let Some(mut process_message) = cef::process_message_create(Some(&route)) else {
+            godot::global::godot_warn!(
+                "[CefTexture] Cannot send binary IPC message: failed to create process message"
+            );
+            return;
+        };
+
+        let Some(argument_list) = process_message.argument_list() else {
+            godot::global::godot_warn!(
+                "[CefTexture] Cannot send binary IPC message: failed to get argument list"
+            );
+            return;
+        };
+
+        argument_list.set_binary(0, Some(&mut binary_value));
+        frame.send_process_message(cef::ProcessId::RENDERER, Some(&mut process_message));
+    }
+
+    #[func]
+    /// Registers a custom URL scheme served from a `res://` or `user://` folder.
+    ///
+    /// Must be called before the first `CefTexture` enters the scene tree,
+    /// because CEF only accepts new schemes before it initializes.
+    pub fn register_scheme_folder(scheme: GString, root: GString) -> bool {
+        let scheme = scheme.to_string();
+        if !Self::can_register_scheme(&scheme) {
+            return false;
+        }
+
+        match godot_protocol::register_scheme_folder(&scheme, &root.to_string()) {
+            Ok(()) => true,
+            Err(e) => {
+                godot::global::godot_warn!("[CefTexture] Cannot register scheme: {}", e);
+                false
+            }
+        }
+    }
+
+    #[func]
+    /// Registers a custom URL scheme whose requests are answered by `handler`.
+    ///
+    /// The handler is called on the main thread with `(method, url, headers, body)`
+    /// and must return a Dictionary with optional `status`, `headers` and `body` keys.
+    /// Must be called before the first `CefTexture` enters the scene tree.
+    pub fn register_scheme_callable(scheme: GString, handler: Callable) -> bool {
+        let scheme = scheme.to_string();
+        if !Self::can_register_scheme(&scheme) {
+            return false;
+        }
+
+        match godot_protocol::register_scheme_callable(&scheme, handler) {
+            Ok(()) => true,
+            Err(e) => {
+                godot::global::godot_warn!("[CefTexture] Cannot register scheme: {}", e);
+                false
+            }
+        }
+    }
+
+    /// New schemes can only be declared before CEF initializes; already declared
+    /// schemes may still be remapped afterwards.
+    fn can_register_scheme(scheme: &str) -> bool {
+        if cef_init::is_cef_initialized()
+            && !godot_protocol::registered_custom_scheme_names()
+                .iter()
+                .any(|name| name == scheme)
+        {
+            godot::global::godot_warn!(
+                "[CefTexture] Cannot register scheme '{}': CEF is already initialized. \
+                 Register custom schemes before the first CefTexture is ready.",
+                scheme
+            );
+            return false;
+        }
+        true
+    }
+
+    /// Allows or denies a request that failed with a certificate error.
+    /// `error_id` comes from the `certificate_error` signal.
+    #[func]
+    pub fn resolve_certificate_error(&mut self, error_id: i64, allow: bool) {
+        let callback = self
+            .app
+            .certificate_decisions
+            .as_ref()
+            .and_then(|decisions| decisions.lock().ok())
+            .and_then(|mut decisions| {
+                decisions.resolve(error_id, allow, self.remember_certificate_decisions)
+            });
+
+        match callback {
+            Some(callback) if allow => callback.cont(),
+            Some(callback) => callback.cancel(),
+            None => godot::global::godot_warn!(
+                "[CefTexture] No pending certificate error with id {}",
+                error_id
+            ),
+        }
+    }
+
+    /// Answers a server's request for a client certificate with the
+    /// certificate at `index` in the `certificates` of the
+    /// `client_certificate_requested` signal.
+    #[func]
+    pub fn select_client_certificate(&mut self, request_id: i64, index: i64) {
+        let selected = {
+            let Some(Ok(mut requests)) = self
+                .app
+                .client_certificate_requests
+                .as_ref()
+                .map(|requests| requests.lock())
+            else {
+                return;
+            };
+            match requests.certificate_count(request_id) {
+                None => Err(format!(
+                    "No pending client certificate request with id {}",
+                    request_id
+                )),
+                Some(count) if index < 0 || index as usize >= count => Err(format!(
+                    "Client certificate index {} is out of range (0..{})",
+                    index, count
+                )),
+                Some(_) => Ok(requests.resolve(request_id, Some(index as usize))),
+            }
+        };
+
+        match selected {
+            Ok(Some((callback, Some(mut certificate)))) => {
+                callback.select(Some(&mut certificate));
+            }
+            Ok(_) => {}
+            Err(message) => godot::global::godot_warn!("[CefTexture] {}", message),
+        }
+    }
+
+    /// Answers a server's request for a client certificate without one,
+    /// which usually fails the connection.
+    #[func]
+    pub fn decline_client_certificate(&mut self, request_id: i64) {
+        let callback = self
+            .app
+            .client_certificate_requests
+            .as_ref()
+            .and_then(|requests| requests.lock().ok())
+            .and_then(|mut requests| requests.resolve(request_id, None));
+
+        match callback {
+            Some((callback, _)) => callback.select(None),
+            None => godot::global::godot_warn!(
+                "[CefTexture] No pending client certificate request with id {}",
+                request_id
+            ),
+        }
+    }
+
+    /// Restricts main-frame navigations to URLs matching one of `patterns`
+    /// (`scheme://host[:port][/path]`, each part matched on its own). An
+    /// empty array allows everything.
+    #[func]
+    pub fn set_navigation_allowlist(&mut self, patterns: PackedStringArray) {
+        let allowlist = crate::security::NavigationAllowlist::new(
+            patterns.as_slice().iter().map(|p| p.to_string()),
+        );
+        if let Ok(mut current) = self.app.navigation_allowlist.write() {
+            *current = allowlist;
+        }
+    }
+
+    /// Adds `headers` (name to value) to every request of the browser,
+    /// replacing those set before. Takes effect on the next request, without
+    /// reloading. An empty dictionary removes them.
+    #[func]
+    pub fn set_extra_headers(&mut self, headers: VarDictionary) {
+        let headers = extra_header_list(&headers);
+        if let Ok(mut extra_headers) = self.app.extra_headers.write() {
+            extra_headers.set_global(headers);
+        }
+    }
+
+    /// Adds `headers` to the requests sent to hosts matching `host_pattern`
+    /// (`api.example.com`, or `*.example.com` for its subdomains), replacing
+    /// those set before for the pattern. They are removed again when a
+    /// request is redirected to another host. An empty dictionary removes the
+    /// pattern.
+    #[func]
+    pub fn set_extra_headers_for_host(&mut self, host_pattern: GString, headers: VarDictionary) {
+        let headers = extra_header_list(&headers);
+        if let Ok(mut extra_headers) = self.app.extra_headers.write() {
+            extra_headers.set_for_host(&host_pattern.to_string(), headers);
+        }
+    }
+
+    /// Takes the key chord `keycode` with the `KeyModifierMask` flags in
+    /// `modifiers` from the page: it no longer reaches the page, and pressing
+    /// it emits `browser_shortcut` with `action_name`. Registering a chord
+    /// again replaces its action. Returns `false` for an invalid keycode.
+    #[func]
+    pub fn register_browser_shortcut(
+        &mut self,
+        action_name: StringName,
+        keycode: i64,
+        modifiers: i64,
+    ) -> bool {
+        let registered = self
+            .app
+            .browser_shortcuts
+            .write()
+            .is_ok_and(|mut shortcuts| {
+                shortcuts.register(&action_name.to_string(), keycode, modifiers)
+            });
+        if !registered {
+            godot::global::godot_warn!(
+                "[CefTexture] Invalid keycode {} for browser shortcut '{}'",
+                keycode,
+                action_name
+            );
+        }
+        registered
+    }
+
+    /// Gives the chords of `action_name` back to the page. Returns `false`
+    /// if it had none.
+    #[func]
+    pub fn unregister_browser_shortcut(&mut self, action_name: StringName) -> bool {
+        self.app
+            .browser_shortcuts
+            .write()
+            .is_ok_and(|mut shortcuts| shortcuts.unregister(&action_name.to_string()))
+    }
+
+    #[func]
+    pub fn go_back(&mut self) {
+        if let Some(browser) = self.app.browser.as_mut() {
+            browser.go_back();
+        }
+    }
+
+    #[func]
+    pub fn go_forward(&mut self) {
+        if let Some(browser) = self.app.browser.as_mut() {
+            browser.go_forward();
+        }
+    }
+
+    #[func]
+    pub fn can_go_back(&self) -> bool {
+        self.app
+            .browser
+            .as_ref()
+            .map(|b| b.can_go_back() != 0)
+            .unwrap_or(false)
+    }
+
+    #[func]
+    pub fn can_go_forward(&self) -> bool {
+        self.app
+            .browser
+            .as_ref()
+            .map(|b| b.can_go_forward() != 0)
+            .unwrap_or(false)
+    }
+
+    #[func]
+    pub fn reload(&mut self) {
+        if let Some(browser) = self.app.browser.as_mut() {
+            browser.reload();
+        }
+    }
+
+    #[func]
+    pub fn reload_ignore_cache(&mut self) {
+        if let Some(browser) = self.app.browser.as_mut() {
+            browser.reload_ignore_cache();
+        }
+    }
+
+    #[func]
+    pub fn stop_loading(&mut self) {
+        if let Some(browser) = self.app.browser.as_mut() {
+            browser.stop_load();
+        }
+    }
+
+    #[func]
+    pub fn is_loading(&self) -> bool {
+        self.app
+            .browser
+            .as_ref()
+            .map(|b| b.is_loading() != 0)
+            .unwrap_or(false)
+    }
+
+    #[func]
+    fn set_enable_accelerated_osr(&mut self, enable: bool) {
+        self.enable_accelerated_osr = enable;
+        self.base_mut().update_configuration_warnings();
+    }
+
+    #[func]
+    fn set_generate_mipmaps(&mut self, enable: bool) {
+        if self.generate_mipmaps != enable {
+            self.generate_mipmaps = enable;
+            self.apply_generate_mipmaps();
+        }
+    }
+
+    #[func]
+    fn set_texture_filter_override(&mut self, filter: rendering::TextureFilterOverride) {
+        self.texture_filter_override = filter;
+        if let Some(filter) = filter.texture_filter() {
+            self.base_mut().set_texture_filter(filter);
+        }
+    }
+
+    #[func]
+    fn set_preferred_color_scheme(&mut self, scheme: devtools::PreferredColorScheme) {
+        if self.preferred_color_scheme != scheme {
+            self.preferred_color_scheme = scheme;
+            self.apply_color_scheme();
+        }
+    }
+
+    #[func]
+    fn set_text_scale(&mut self, scale: f32) {
+        self.text_scale = scale.clamp(zoom::MIN_TEXT_SCALE, zoom::MAX_TEXT_SCALE);
+        self.apply_zoom();
+    }
+
+    #[func]
+    fn set_allow_autoplay(&mut self, allow: bool) {
+        if allow && !self.allow_autoplay {
+            self.apply_autoplay_activation();
+        }
+        self.allow_autoplay = allow;
+    }
+
+    #[func]
+    fn set_allow_res_scheme(&mut self, allow: bool) {
+        self.allow_res_scheme = allow;
+        self.base_mut().update_configuration_warnings();
+    }
+
+    #[func]
+    fn set_allow_user_scheme(&mut self, allow: bool) {
+        self.allow_user_scheme = allow;
+        self.base_mut().update_configuration_warnings();
+    }
+
+    #[func]
+    fn get_url_property(&self) -> GString {
+        // A preview may be redirected or blocked; the scene keeps the URL set
+        if self.editor_preview {
+            return self.url.clone();
+        }
+        if let Some(url) = self.main_frame_url() {
+            return GString::from(url.as_str());
+        }
+        self.url.clone()
+    }
+
+    /// Returns a signal to `await` until the current page has painted its
+    /// first content. Emitted on the next frame if it already has.
+    #[func]
+    pub fn wait_until_painted(&mut self) -> Signal {
+        if self.page_painted {
+            self.base_mut()
+                .call_deferred("emit_signal", &["painted".to_variant()]);
+        }
+        Signal::from_object_signal(&self.to_gd(), "painted")
+    }
+
+    /// Requests the HTML of the first element matching `selector`, or of the
+    /// whole document if it is empty. `dom_dumped` delivers it, cut off after
+    /// `godot_cef/debug/dom_dump_max_length` characters.
+    #[func]
+    pub fn dump_dom_async(&mut self, selector: GString) -> bool {
+        self.send_dom_query(cef_app::DomQueryKind::Dump, &selector.to_string())
+    }
+
+    /// Requests the number of elements matching `selector`, delivered by
+    /// `elements_counted` (-1 for an invalid selector).
+    #[func]
+    pub fn count_elements(&mut self, selector: GString) -> bool {
+        self.send_dom_query(cef_app::DomQueryKind::Count, &selector.to_string())
+    }
+
+    /// Returns the main frame's scroll offset in CSS pixels, as last
+    /// reported by the page.
+    #[func]
+    pub fn get_scroll_position(&self) -> Vector2 {
+        let scroll = self.last_scroll_position();
+        Vector2::new(scroll.x as f32, scroll.y as f32)
+    }
+
+    /// Scrolls the main frame to `position`, in CSS pixels. With `smooth`,
+    /// the page animates the scroll.
+    #[func]
+    pub fn set_scroll_position(&mut self, position: Vector2, #[opt(default = false)] smooth: bool) {
+        self.scroll_main_frame_to(position.x as f64, position.y as f64, smooth);
+    }
+
+    /// Captures the page as a sequence of images of `page_size` texture
+    /// pixels, scrolling down one page per frame, and emits them with
+    /// `page_captured_sequence`. Consecutive pages overlap by `bleed`
+    /// pixels. The scroll position is restored afterwards. Returns `false`
+    /// if the capture could not start.
+    #[func]
+    pub fn capture_paginated(
+        &mut self,
+        page_size: Vector2i,
+        #[opt(default = 0)] bleed: i32,
+    ) -> bool {
+        self.capture_paginated_impl(page_size, bleed)
+    }
+
+    #[func]
+    pub fn set_zoom_level(&mut self, level: f64) {
+        self.user_zoom_level = level;
+        self.apply_zoom();
+    }
+
+    #[func]
+    pub fn get_zoom_level(&self) -> f64 {
+        self.user_zoom_level
+    }
+
+    /// Returns the zoom the pages are shown at, in percent: the zoom level
+    /// combined with `text_scale`.
+    #[func]
+    pub fn get_effective_zoom_percent(&self) -> f64 {
+        self.effective_zoom_percent()
+    }
+
+    #[func]
+    pub fn set_audio_muted(&mut self, muted: bool) {
+        if let Some(browser) = self.app.browser.as_mut()
+            && let Some(host) = browser.host()
+        {
+            host.set_audio_muted(muted as i32);
+        }
+    }
+
+    #[func]
+    pub fn is_audio_muted(&self) -> bool {
+        self.app
+            .browser
+            .as_ref()
+            .and_then(|b| b.host())
+            .map(|h| h.is_audio_muted() != 0)
+            .unwrap_or(false)
+    }
+
+    /// Tells the page whether it is visible. While hidden, `document.visibilityState`
+    /// is `"hidden"`, the page is throttled and no frames are rendered.
+    #[func]
+    pub fn set_page_visibility(&mut self, visible: bool) {
+        self.page_hidden = !visible;
+        self.apply_page_visibility();
+    }
+
+    #[func]
+    pub fn is_page_visible(&self) -> bool {
+        !self.page_hidden && !self.page_occluded
+    }
+
+    /// Marks the page as fully covered by other UI. CEF has no separate occlusion
+    /// state for windowless browsers, so an occluded page is reported as hidden.
+    #[func]
+    pub fn set_occluded(&mut self, occluded: bool) {
+        self.page_occluded = occluded;
+        self.apply_page_visibility();
+    }
+
+    #[func]
+    pub fn is_occluded(&self) -> bool {
+        self.page_occluded
+    }
+
+    /// Throttles the browser while the game is in the background: the frame
+    /// rate drops to `godot_cef/performance/background_frame_rate`, and once
+    /// every browser is in background mode the helper processes run at a
+    /// lower priority.
+    #[func]
+    pub fn set_background_mode(&mut self, enabled: bool) {
+        if enabled == self.background_mode {
+            return;
+        }
+
+        self.background_mode = enabled;
+        if enabled {
+            crate::helper_priority::enter_background();
+        } else {
+            crate::helper_priority::leave_background();
+        }
+        self.handle_max_fps_change();
+    }
+
+    #[func]
+    pub fn is_background_mode(&self) -> bool {
+        self.background_mode
+    }
+
+    /// Returns frame render statistics accumulated since the browser was
+    /// created or `reset_render_stats()` was last called.
+    #[func]
+    pub fn get_render_stats(&self) -> VarDictionary {
+        let snapshot = self
+            .app
+            .render_stats
+            .as_ref()
+            .map(|stats| stats.snapshot())
+            .unwrap_or_default();
+        self.render_stats_dictionary(&snapshot)
+    }
+
+    #[func]
+    pub fn reset_render_stats(&mut self) {
+        if let Some(stats) = &self.app.render_stats {
+            stats.reset();
+        }
+        self.last_render_stats = Default::default();
+    }
+
+    /// Returns the accessibility tree of the page, flattened in document
+    /// order. Empty unless `godot_cef/accessibility/enable_accessibility` is
+    /// enabled.
+    #[func]
+    pub fn get_accessibility_tree(&self) -> VarDictionary {
+        self.accessibility_tree_dictionary()
+    }
+
+    /// Shows or hides an overlay with live diagnostics: URL, load state,
+    /// render path, paint rate, queue backlog, texture size and scale, helper
+    /// processes and the last load error. In release exports it only works
+    /// when `godot_cef/debug/allow_debug_overlay_in_release` is enabled.
     #[func]
-    pub fn eval(&mut self, code: GString) {
-        let Some(browser) = self.app.browser.as_ref() else {
-            godot::global::godot_warn!("[CefTexture] Cannot execute JS: no browser");
-            return;
-        };
-        let Some(frame) = browser.main_frame() else {
-            godot::global::godot_warn!("[CefTexture] Cannot execute JS: no main frame");
-            return;
-        };
+    pub fn show_debug_overlay(&mut self, visible: bool) {
+        self.show_debug_overlay_impl(visible);
+    }
 
-        let code_str: cef::CefStringUtf16 = code.to_string().as_str().into();
-        frame.execute_java_script(Some(&code_str), None, 0);
+    /// Returns the overlay's diagnostics with the OS, Godot, CEF and GPU
+    /// details as text for bug reports, and copies it to the clipboard.
+    #[func]
+    pub fn copy_debug_report(&mut self) -> GString {
+        GString::from(self.copy_debug_report_impl().as_str())
     }
 
     #[func]
-    fn set_url_property(&mut self, url: GString) {
-        self.url = url.clone();
+    fn _on_debug_overlay_draw(&mut self) {
+        self.draw_debug_overlay();
+    }
 
-        if let Some(browser) = self.app.browser.as_ref()
-            && let Some(frame) = browser.main_frame()
-        {
-            let url_str: cef::CefStringUtf16 = url.to_string().as_str().into();
-            frame.load_url(Some(&url_str));
-        }
+    #[func]
+    fn _on_tree_process_frame(&mut self) {
+        self.update_pause_state();
+        self.update_global_mute();
     }
 
     #[func]
-    /// Sends a message into the page via `window.onIpcMessage`.
-    ///
-    /// This is intentionally separate from [`eval`]: callers could achieve a
-    /// similar effect with `eval("window.onIpcMessage(...);")`, but this
-    /// helper enforces a consistent IPC pattern (`window.onIpcMessage(message)`).
-    ///
-    /// Uses native CEF process messaging for efficient transfer without
-    /// script injection overhead.
-    ///
-    /// Use this when you want structured IPC into the page, and `eval` when
-    /// you truly need arbitrary JavaScript execution.
-    pub fn send_ipc_message(&mut self, message: GString) {
-        let Some(browser) = self.app.browser.as_ref() else {
-            godot::global::godot_warn!("[CefTexture] Cannot send IPC message: no browser");
-            return;
-        };
-        let Some(frame) = browser.main_frame() else {
-            godot::global::godot_warn!("[CefTexture] Cannot send IPC message: no main frame");
-            return;
-        };
+    fn _on_project_settings_changed(&mut self) {
+        self.update_editor_preview();
+    }
 
-        let route = cef::CefStringUtf16::from("ipcGodotToRenderer");
-        let msg_str: cef::CefStringUtf16 = message.to_string().as_str().into();
+    /// Returns whether the browser is idle, see `idle_timeout_seconds`.
+    #[func]
+    pub fn is_idle(&self) -> bool {
+        self.is_idle_impl()
+    }
 
-        if let Some(mut process_message) = cef::process_message_create(Some(&route)) {
-            if let Some(argument_list) = process_message.argument_list() {
-                argument_list.set_string(0, Some(&msg_str));
-            }
-            frame.send_process_message(cef::ProcessId::RENDERER, Some(&mut process_message));
+    /// Reports, for each category of event queues, the events waiting to be
+    /// emitted, the capacity of each queue and the events dropped or
+    /// coalesced because a queue was full.
+    #[func]
+    pub fn get_queue_backlog(&self) -> VarDictionary {
+        let queues = self
+            .app
+            .event_queues
+            .as_ref()
+            .and_then(|queues| queues.lock().ok());
+        let mut backlog = VarDictionary::new();
+        for category in crate::event_queue::QueueCategory::ALL {
+            let stats = queues
+                .as_ref()
+                .map(|queues| queues.backlog(category))
+                .unwrap_or_default();
+            backlog.set(
+                category.name(),
+                vdict! {
+                    "pending": stats.pending as i64,
+                    "capacity": stats.capacity as i64,
+                    "overflowed": stats.overflowed as i64,
+                },
+            );
         }
+        backlog
     }
 
+    /// Reports the resident memory of the CEF helper processes and the size
+    /// of the buffers this node keeps on the Godot side.
     #[func]
-    /// Sends binary data into the page via `window.onIpcBinaryMessage`.
-    ///
-    /// The data will be delivered as an ArrayBuffer to the JavaScript callback
-    /// `window.onIpcBinaryMessage(arrayBuffer)` if it is registered.
-    ///
-    /// Uses native CEF process messaging with BinaryValue for zero-copy
-    /// binary transfer without encoding overhead.
-    pub fn send_ipc_binary_message(&mut self, data: PackedByteArray) {
-        let Some(browser) = self.app.browser.as_ref() else {
-            godot::global::godot_warn!("[CefTexture] Cannot send binary IPC message: no browser");
-            return;
-        };
-        let Some(frame) = browser.main_frame() else {
-            godot::global::godot_warn!(
-                "[CefTexture] Cannot send binary IPC message: no main frame"
-            );
-            return;
+    pub fn get_memory_usage(&self) -> VarDictionary {
+        let helpers = crate::process_memory::helper_memory_usage();
+        let mut helper_processes: Array<VarDictionary> = Array::new();
+        for helper in &helpers {
+            helper_processes.push(&vdict! {
+                "pid": helper.pid as i64,
+                "resident_bytes": helper.resident_bytes as i64,
+            });
+        }
+        let helper_resident_bytes: u64 = helpers.iter().map(|h| h.resident_bytes).sum();
+
+        let frame_buffer_bytes = match &self.app.render_mode {
+            Some(RenderMode::Software { frame_buffer, .. }) => frame_buffer
+                .lock()
+                .map(|fb| fb.data.len() as u64)
+                .unwrap_or(0),
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            Some(RenderMode::Accelerated { render_state, .. }) => render_state
+                .lock()
+                .map(|state| state.dst_width as u64 * state.dst_height as u64 * 4)
+                .unwrap_or(0),
+            None => 0,
         };
+        let popup_buffer_bytes = self
+            .app
+            .popup_state
+            .as_ref()
+            .and_then(|ps| ps.lock().ok().map(|popup| popup.buffer.len() as u64))
+            .unwrap_or(0);
+        let (pending_events, pending_audio_packets) = self.queue_backlog();
+
+        vdict! {
+            "helper_processes": helper_processes,
+            "helper_resident_bytes": helper_resident_bytes as i64,
+            "frame_buffer_bytes": frame_buffer_bytes as i64,
+            "popup_buffer_bytes": popup_buffer_bytes as i64,
+            "pending_events": pending_events as i64,
+            "pending_audio_packets": pending_audio_packets as i64,
+        }
+    }
 
-        let route = cef::CefStringUtf16::from("ipcBinaryGodotToRenderer");
-        let bytes = data.to_vec();
+    /// Lists the crash dumps written by the CEF helper processes to
+    /// `user://godot_cef_crashes`, oldest first. Delete a file once it has been
+    /// handled (e.g. uploaded) so it is not reported again.
+    #[func]
+    pub fn get_pending_crash_reports() -> Array<VarDictionary> {
+        let mut reports: Array<VarDictionary> = Array::new();
+        for dump in crate::crash_reports::pending_crash_reports() {
+            let file_name = dump
+                .path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            reports.push(&vdict! {
+                "path": dump.path.to_string_lossy().into_owned(),
+                "file_name": file_name,
+                "size_bytes": dump.size_bytes as i64,
+                "modified_unix": dump.modified_unix as i64,
+            });
+        }
+        reports
+    }
 
-        let Some(mut binary_value) = cef::binary_value_create(Some(&bytes)) else {
-            godot::global::godot_warn!(
-                "[CefTexture] Cannot send binary IPC message: failed to create BinaryValue"
-            );
-            return;
+    /// Checks that the files CEF needs are installed, without loading
+    /// anything. Editor plugins can use it to show a setup checklist.
+    ///
+    /// Returns `ok`, an `error` message when the install directory cannot be
+    /// resolved, and `files`: one dictionary per file with `name`, `path` and
+    /// `found`.
+    #[func]
+    pub fn verify_installation() -> VarDictionary {
+        let mut files: Array<VarDictionary> = Array::new();
+        let (ok, error) = match crate::utils::installation_files() {
+            Ok(expected) => {
+                let mut ok = true;
+                for (name, path) in expected {
+                    let found = path.exists();
+                    ok &= found;
+                    files.push(&vdict! {
+                        "name": name,
+                        "path": path.to_string_lossy().into_owned(),
+                        "found": found,
+                    });
+                }
+                (ok, String::new())
+            }
+            Err(e) => (false, e.to_string()),
         };
 
-        let Some(mut process_message) = cef::process_message_create(Some(&route)) else {
-            godot::global::godot_warn!(
-                "[CefTexture] Cannot send binary IPC message: failed to create process message"
-            );
-            return;
-        };
+        vdict! {
+            "ok": ok,
+            "error": error,
+            "files": files,
+        }
+    }
 
-        let Some(argument_list) = process_message.argument_list() else {
-            godot::global::godot_warn!(
-                "[CefTexture] Cannot send binary IPC message: failed to get argument list"
-            );
-            return;
-        };
+    /// Returns the number of running CEF helper processes (renderer, GPU,
+    /// utility, ...) started by the game. Useful to check what `renderer_group`
+    /// saves.
+    #[func]
+    pub fn get_process_count() -> i64 {
+        crate::utils::helper_process_ids().len() as i64
+    }
 
-        argument_list.set_binary(0, Some(&mut binary_value));
-        frame.send_process_message(cef::ProcessId::RENDERER, Some(&mut process_message));
+    /// Returns the pid of the renderer process showing the page, for
+    /// attaching a debugger or matching CPU usage to this node. Returns -1
+    /// until the page reported it and after the process crashed; a
+    /// navigation to another process updates it.
+    #[func]
+    pub fn get_renderer_process_id(&self) -> i64 {
+        self.app.renderer_process_id.map_or(-1, i64::from)
     }
 
+    /// Returns the pid of the shared GPU process, or -1 if it is not running
+    /// or cannot be found on this platform. Scans the process table, so
+    /// avoid calling it every frame.
     #[func]
-    pub fn go_back(&mut self) {
-        if let Some(browser) = self.app.browser.as_mut() {
-            browser.go_back();
-        }
+    pub fn get_gpu_process_id() -> i64 {
+        crate::utils::gpu_process_id().map_or(-1, i64::from)
     }
 
+    /// Returns up to `max_lines` of the last lines of CEF's log, oldest
+    /// first, for bug reports. Empty unless
+    /// `godot_cef/logging/forward_to_output` is enabled.
     #[func]
-    pub fn go_forward(&mut self) {
-        if let Some(browser) = self.app.browser.as_mut() {
-            browser.go_forward();
+    pub fn get_recent_cef_logs(max_lines: i64) -> PackedStringArray {
+        crate::cef_log::recent_lines(max_lines.max(0) as usize)
+            .iter()
+            .map(|line| GString::from(line.as_str()))
+            .collect()
+    }
+
+    /// Returns whether this node can show a browser. Under headless Godot
+    /// (`--headless`, dedicated server exports) and after
+    /// `CefManager.shutdown()` it returns `false`: no browser is created,
+    /// methods do nothing and no signals are emitted.
+    #[func]
+    pub fn is_available(&self) -> bool {
+        !self.disabled && !cef_init::is_headless()
+    }
+
+    /// Reports whether the Vulkan layer that enables GPU texture sharing was
+    /// registered and which extensions it added to Godot's device.
+    #[func]
+    pub fn get_gpu_interop_status() -> VarDictionary {
+        let status = crate::vulkan_hook::status();
+        let injected_extensions: PackedStringArray = status
+            .injected_extensions
+            .iter()
+            .map(|name| GString::from(name.as_str()))
+            .collect();
+
+        vdict! {
+            "vulkan_hook_installed": status.installed,
+            "vulkan_hook_disabled": status.disabled,
+            "injected_extensions": injected_extensions,
+            "external_memory_enabled": status.external_memory_enabled,
+            "error": status.error.unwrap_or_default(),
         }
     }
 
+    /// Sends a Chrome DevTools Protocol method, e.g.
+    /// `Emulation.setGeolocationOverride`, with `params` as its parameters.
+    /// Returns the message id that `devtools_result` reports the outcome
+    /// with, or `-1` if the method could not be sent.
     #[func]
-    pub fn can_go_back(&self) -> bool {
-        self.app
-            .browser
-            .as_ref()
-            .map(|b| b.can_go_back() != 0)
-            .unwrap_or(false)
+    pub fn send_devtools_message(&mut self, method: GString, params: VarDictionary) -> i64 {
+        self.send_devtools_method(&method.to_string(), &params)
     }
 
+    /// Shows the DevTools of `target`'s browser in this node instead of its
+    /// page, taking this node's input. Closing either browser detaches the
+    /// DevTools, after which this node loads its `url` again. Only available
+    /// in debug builds with `godot_cef/security/allow_devtools_protocol`.
+    /// Returns `false` if the DevTools could not be shown, e.g. because
+    /// `target` is already inspected.
     #[func]
-    pub fn can_go_forward(&self) -> bool {
-        self.app
-            .browser
-            .as_ref()
-            .map(|b| b.can_go_forward() != 0)
-            .unwrap_or(false)
+    pub fn attach_dev_tools(&mut self, target: Gd<CefTexture>) -> bool {
+        self.attach_dev_tools_impl(target)
     }
 
+    /// Requests the GPU state of the browser: its renderer, whether it is
+    /// hardware accelerated and the status of each feature, delivered by
+    /// `browser_gpu_info`. Returns `false` if there is no browser.
     #[func]
-    pub fn reload(&mut self) {
-        if let Some(browser) = self.app.browser.as_mut() {
-            browser.reload();
-        }
+    pub fn get_browser_gpu_info_async(&mut self) -> bool {
+        self.get_browser_gpu_info_async_impl()
     }
 
+    /// Exports the cookies of the browser's profile, HttpOnly ones included,
+    /// as a JSON snapshot delivered by `cookies_exported`. An empty
+    /// `url_filter` exports every cookie, otherwise only those sent to that
+    /// URL. The snapshot holds session credentials; store it accordingly.
     #[func]
-    pub fn reload_ignore_cache(&mut self) {
-        if let Some(browser) = self.app.browser.as_mut() {
-            browser.reload_ignore_cache();
-        }
+    pub fn export_cookies(&mut self, url_filter: GString) {
+        self.export_cookies_impl(&url_filter.to_string());
     }
 
+    /// Sets the cookies of a snapshot from `export_cookies` in the browser's
+    /// profile. `cookies_imported` reports how many were set and why the
+    /// others were not; a snapshot that cannot be read sets none.
     #[func]
-    pub fn stop_loading(&mut self) {
-        if let Some(browser) = self.app.browser.as_mut() {
-            browser.stop_load();
-        }
+    pub fn import_cookies(&mut self, data: PackedByteArray) {
+        self.import_cookies_impl(&data);
     }
 
+    /// Saves the current page to `path`, as MHTML (`0`) with its loaded
+    /// resources or as the main frame's HTML (`1`). Returns `false` if the
+    /// save could not start; otherwise `page_saved` reports the outcome.
     #[func]
-    pub fn is_loading(&self) -> bool {
-        self.app
-            .browser
-            .as_ref()
-            .map(|b| b.is_loading() != 0)
-            .unwrap_or(false)
+    pub fn save_page(&mut self, path: GString, format: page_save::PageSaveFormat) -> bool {
+        self.save_page_impl(&path.to_string(), format)
     }
 
+    /// Reports the given position to `navigator.geolocation` instead of the
+    /// OS location, and grants the geolocation permission to the main
+    /// frame's origin. The override holds across navigations until
+    /// `clear_geolocation_override`.
     #[func]
-    fn get_url_property(&self) -> GString {
-        if let Some(browser) = self.app.browser.as_ref()
-            && let Some(frame) = browser.main_frame()
-        {
-            let frame_url = frame.url();
-            let url_string = cef::CefStringUtf16::from(&frame_url).to_string();
-            return GString::from(url_string.as_str());
-        }
-        self.url.clone()
+    pub fn set_geolocation_override(&mut self, latitude: f64, longitude: f64, accuracy: f64) {
+        self.override_geolocation(crate::browser::GeolocationOverride {
+            latitude,
+            longitude,
+            accuracy,
+        });
     }
 
+    /// Removes the position set with `set_geolocation_override`; the
+    /// geolocation permission is no longer granted.
     #[func]
-    pub fn set_zoom_level(&mut self, level: f64) {
-        if let Some(browser) = self.app.browser.as_mut()
-            && let Some(host) = browser.host()
-        {
-            host.set_zoom_level(level);
-        }
+    pub fn clear_geolocation_override(&mut self) {
+        self.clear_geolocation();
     }
 
+    /// Answers a `getDisplayMedia()` call reported by
+    /// `display_media_requested`. Mode `1` (`GameViewport`) streams
+    /// `display_media_viewport` to the page at the browser frame rate; `0`
+    /// (`Deny`) rejects the call. Returns `true` if a stream was offered.
     #[func]
-    pub fn get_zoom_level(&self) -> f64 {
-        self.app
-            .browser
-            .as_ref()
-            .and_then(|b| b.host())
-            .map(|h| h.zoom_level())
-            .unwrap_or(0.0)
+    pub fn offer_display_media(
+        &mut self,
+        request_id: i64,
+        mode: display_media::DisplayMediaSource,
+    ) -> bool {
+        self.offer_display_media_impl(request_id, mode)
     }
 
+    /// Fires the `click` event of the notification reported by
+    /// `web_notification` with `tag`, for when the player clicks the game's
+    /// toast. Returns `false` if the notification was closed or its page is
+    /// gone.
     #[func]
-    pub fn set_audio_muted(&mut self, muted: bool) {
+    pub fn activate_web_notification(&mut self, tag: GString) -> bool {
+        self.activate_web_notification_impl(&tag.to_string())
+    }
+
+    /// Adds a script run in every document the browser loads, including
+    /// iframes. `injection_time` `0` (`DocumentStart`) runs it before the
+    /// page's scripts, `1` (`DocumentEnd`) at `DOMContentLoaded`. With
+    /// `isolated`, it runs in an isolated world: it shares the DOM with the
+    /// page but not its JavaScript globals. Returns the id to remove it with.
+    #[func]
+    pub fn add_user_script(
+        &mut self,
+        code: GString,
+        injection_time: user_scripts::ScriptInjectionTime,
+        isolated: bool,
+    ) -> i64 {
+        self.add_user_script_impl(&code.to_string(), injection_time, isolated)
+    }
+
+    /// Removes a script added with `add_user_script`, from the next
+    /// navigation on. Returns `false` if there is no script with this id.
+    #[func]
+    pub fn remove_user_script(&mut self, id: i64) -> bool {
+        self.remove_user_script_impl(id)
+    }
+
+    /// Sets scripts run in every new document of the main world before any
+    /// of the page's scripts, e.g. to patch `window.fetch`. Replaces the
+    /// previous list; documents loaded from then on run the new one. Set
+    /// them before the browser is created to cover the first page.
+    #[func]
+    pub fn set_startup_scripts(&mut self, scripts: PackedStringArray) {
+        let scripts = scripts.as_slice().iter().map(|s| s.to_string()).collect();
+        self.set_startup_scripts_impl(scripts);
+    }
+
+    #[func]
+    pub fn get_startup_scripts(&self) -> PackedStringArray {
+        self.startup_scripts
+            .iter()
+            .map(|script| GString::from(script.as_str()))
+            .collect()
+    }
+
+    /// Captures the current URL, navigation history, zoom level and
+    /// main-frame scroll position, for `restore_session`.
+    #[func]
+    pub fn save_session(&self) -> VarDictionary {
+        self.save_session_impl()
+    }
+
+    /// Loads the URL of a session from `save_session`, applies its zoom
+    /// level and scrolls once the page has loaded. Only the current history
+    /// entry is restored. Returns what was restored.
+    #[func]
+    pub fn restore_session(&mut self, session: VarDictionary) -> VarDictionary {
+        self.restore_session_impl(&session)
+    }
+
+    /// Asks Chromium to release memory as if the system were under critical
+    /// memory pressure, and releases the spare capacity of internal buffers.
+    #[func]
+    pub fn purge_memory(&mut self) {
         if let Some(browser) = self.app.browser.as_mut()
             && let Some(host) = browser.host()
         {
-            host.set_audio_muted(muted as i32);
+            let message = format!(
+                r#"{{"id":{},"method":"Memory.simulatePressureNotification","params":{{"level":"critical"}}}}"#,
+                devtools::next_message_id()
+            );
+            host.send_dev_tools_message(Some(message.as_bytes()));
         }
-    }
 
-    #[func]
-    pub fn is_audio_muted(&self) -> bool {
-        self.app
-            .browser
-            .as_ref()
-            .and_then(|b| b.host())
-            .map(|h| h.is_audio_muted() != 0)
-            .unwrap_or(false)
+        // Emit whatever is still queued so nothing is lost, then drop the
+        // capacity the queues reserved.
+        self.process_all_event_queues();
+        if let Some(queues) = &self.app.event_queues
+            && let Ok(mut queues) = queues.lock()
+        {
+            queues.shrink_to_fit();
+        }
+        if let Some(queue) = &self.app.audio_packet_queue
+            && let Ok(mut queue) = queue.lock()
+        {
+            queue.shrink_to_fit();
+        }
+        if let Some(ps) = &self.app.popup_state
+            && let Ok(mut popup) = ps.lock()
+            && !popup.visible
+        {
+            popup.buffer = Vec::new();
+        }
     }
 
     /// Creates an AudioStreamGenerator configured for this browser's audio.
-    /// Only works when `godot_cef/audio/enable_audio_capture` is enabled.
+    /// Only works when the browser captures audio, see `audio_capture`.
     #[func]
     pub fn create_audio_stream(&self) -> Gd<godot::classes::AudioStreamGenerator> {
         use godot::classes::AudioStreamGenerator;
@@ -541,10 +2045,12 @@ impl CefTexture {
             .unwrap_or(0)
     }
 
-    /// Returns true if audio capture mode is enabled in project settings.
+    /// Returns true if the browser captures its audio, from `audio_capture`
+    /// and the project setting. Before the browser exists, returns whether
+    /// it would.
     #[func]
     pub fn is_audio_capture_enabled(&self) -> bool {
-        crate::settings::is_audio_capture_enabled()
+        self.effective_audio_capture()
     }
 
     /// Called when the IME proxy LineEdit text changes during composition.
@@ -558,11 +2064,37 @@ impl CefTexture {
         self.on_ime_proxy_focus_exited_impl();
     }
 
+    #[func]
+    fn _on_alpha_mask_readback(&mut self, pixels: PackedByteArray) {
+        self.on_alpha_mask_readback_impl(pixels);
+    }
+
     #[func]
     fn _check_ime_focus_after_exit(&mut self) {
         self.check_ime_focus_after_exit_impl();
     }
 
+    /// Cancels the IME composition in progress, discarding its text. Done
+    /// automatically when the node loses focus or the page's text field goes
+    /// away.
+    #[func]
+    pub fn cancel_ime_composition(&mut self) {
+        self.cancel_ime_composition_impl();
+    }
+
+    /// Commits the IME composition in progress to the page as typed text.
+    #[func]
+    pub fn commit_ime_composition(&mut self) {
+        self.commit_ime_composition_impl();
+    }
+
+    /// Returns the IME composition in progress: its `text`, `cursor`,
+    /// `clauses` and the local `character_bounds` of each UTF-16 code unit.
+    #[func]
+    pub fn get_ime_composition(&self) -> VarDictionary {
+        self.ime_composition_dictionary()
+    }
+
     fn on_focus_enter(&mut self) {
         let Some(browser) = self.app.browser.as_mut() else {
             return;
@@ -572,6 +2104,76 @@ impl CefTexture {
         };
 
         host.set_focus(true as _);
+        self.unfocus_dev_tools_peer();
+    }
+
+    /// Clicks `button` (a `MouseButton`) at `position` in local coordinates,
+    /// twice with `double`. Like the other `inject_*` methods, it works without
+    /// focus and returns `false` if nothing was sent.
+    #[func]
+    pub fn inject_mouse_click(&mut self, position: Vector2, button: i64, double: bool) -> bool {
+        self.inject_click(position, button, double)
+    }
+
+    /// Moves the pointer to `position` in local coordinates.
+    #[func]
+    pub fn inject_mouse_move(&mut self, position: Vector2) -> bool {
+        self.inject_move(position)
+    }
+
+    /// Scrolls at `position` by `delta` wheel notches; positive `y` scrolls
+    /// down and positive `x` right.
+    #[func]
+    pub fn inject_scroll(&mut self, position: Vector2, delta: Vector2) -> bool {
+        self.inject_wheel(position, delta)
+    }
+
+    /// Presses or releases `keycode` (a `Key`). `unicode` is the character
+    /// the key types, 0 for none, and `modifiers` a `KeyModifierMask`.
+    #[func]
+    pub fn inject_key(
+        &mut self,
+        keycode: i64,
+        unicode: i64,
+        pressed: bool,
+        modifiers: i64,
+    ) -> bool {
+        self.inject_key_event(keycode, unicode, pressed, modifiers)
+    }
+
+    /// Types `text` into the focused element of the page.
+    #[func]
+    pub fn inject_text(&mut self, text: GString) -> bool {
+        self.inject_text_events(&text.to_string())
+    }
+
+    /// Enables or disables mouse move coalescing. When enabled (the default),
+    /// only the latest hover position per frame is sent to the browser, and
+    /// moves while a button is held are capped at 250 Hz.
+    #[func]
+    pub fn set_mouse_move_coalescing(&mut self, enabled: bool) {
+        if let Some(mouse_event) = self.mouse_move_coalescer.set_enabled(enabled) {
+            self.send_mouse_move(&mouse_event);
+        }
+    }
+
+    #[func]
+    pub fn is_mouse_move_coalescing(&self) -> bool {
+        self.mouse_move_coalescer.is_enabled()
+    }
+
+    fn flush_mouse_move(&mut self) {
+        if let Some(mouse_event) = self.mouse_move_coalescer.take_pending() {
+            self.send_mouse_move(&mouse_event);
+        }
+    }
+
+    fn send_mouse_move(&mut self, mouse_event: &cef::MouseEvent) {
+        if let Some(browser) = self.app.browser.as_mut()
+            && let Some(host) = browser.host()
+        {
+            input::send_mouse_move(&host, mouse_event);
+        }
     }
 
     fn get_pixel_scale_factor(&self) -> f32 {
@@ -583,8 +2185,17 @@ impl CefTexture {
             .x
     }
 
+    /// Scale factor of the screen the node's window is on. Checked every
+    /// frame, so moving the window to another monitor rescales the page.
     fn get_device_scale_factor(&self) -> f32 {
-        crate::utils::get_display_scale_factor()
+        crate::utils::get_screen_scale_factor(self.current_screen())
+    }
+
+    fn current_screen(&self) -> i32 {
+        self.base().get_window().map_or(
+            godot::classes::DisplayServer::SCREEN_OF_MAIN_WINDOW,
+            |window| window.get_current_screen(),
+        )
     }
 
     #[func]
@@ -606,7 +2217,7 @@ impl CefTexture {
         }
 
         let mouse_event = input::create_mouse_event(
-            position,
+            self.local_to_view(position),
             self.get_pixel_scale_factor(),
             self.get_device_scale_factor(),
             0,
@@ -634,7 +2245,7 @@ impl CefTexture {
         };
 
         let mouse_event = input::create_mouse_event(
-            position,
+            self.local_to_view(position),
             self.get_pixel_scale_factor(),
             self.get_device_scale_factor(),
             0,
@@ -673,7 +2284,7 @@ impl CefTexture {
         };
 
         let mouse_event = input::create_mouse_event(
-            position,
+            self.local_to_view(position),
             self.get_pixel_scale_factor(),
             self.get_device_scale_factor(),
             0,
@@ -702,6 +2313,7 @@ impl CefTexture {
         host.drag_source_ended_at(position.x as i32, position.y as i32, op);
 
         self.app.drag_state.is_dragging_from_browser = false;
+        self.cleanup_drag_files();
     }
 
     #[func]
@@ -726,3 +2338,24 @@ impl CefTexture {
         self.app.drag_state.is_drag_over
     }
 }
+
+/// Converts a header dictionary from GDScript, skipping the entries that are
+/// not valid headers. Values are not printed since they often hold tokens.
+fn extra_header_list(headers: &VarDictionary) -> Vec<(String, String)> {
+    headers
+        .iter_shared()
+        .filter_map(|(name, value)| {
+            let name = name.stringify().to_string();
+            let value = value.stringify().to_string();
+            let valid = godot_protocol::is_valid_header_name(&name)
+                && godot_protocol::is_valid_header_value(&value);
+            if !valid {
+                godot::global::godot_warn!(
+                    "[CefTexture] Ignoring invalid extra header '{}'",
+                    name.escape_debug()
+                );
+            }
+            valid.then_some((name, value))
+        })
+        .collect()
+}
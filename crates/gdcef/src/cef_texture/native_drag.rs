@@ -0,0 +1,437 @@
+//! `native_drag_integration`: browser drags as Godot drags, and Godot drags
+//! as drops onto the page.
+//!
+//! A drag started in the page becomes a Godot drag carrying its
+//! `DragDataInfo`, and CEF is told how it ended when Godot ends the drag.
+//! Godot drags carrying a `DragDataInfo`, a Dictionary or a file list are
+//! sent to the page while they hover the node. `_can_drop_data` cannot change
+//! the node, so it only records the hover, which `_process` forwards to CEF.
+
+use std::cell::RefCell;
+
+use cef::{CefStringUtf16, ImplBrowser, ImplBrowserHost, ImplDragData};
+use godot::classes::{Control, Label, ProjectSettings};
+use godot::prelude::*;
+
+use super::CefTexture;
+use crate::browser::DragDataInfo as InternalDragDataInfo;
+use crate::drag::DragDataInfo;
+use crate::input;
+
+// `DragOperation` values
+const OP_NONE: u32 = 0;
+const OP_COPY: u32 = 1;
+const OP_LINK: u32 = 2;
+const OP_MOVE: u32 = 16;
+
+/// Operations offered to the page for Godot drags, which have none of their
+/// own.
+const GODOT_DRAG_OPS: u32 = OP_COPY | OP_LINK | OP_MOVE;
+
+/// Characters of text shown in the generated drag preview.
+const PREVIEW_MAX_CHARS: usize = 48;
+
+#[derive(Default)]
+pub(super) struct NativeDragState {
+    /// A drag started in the page is running as a Godot drag.
+    source_active: bool,
+    /// Operations the page allows for that drag.
+    source_ops: u32,
+    /// That drag was dropped back onto the page.
+    dropped_on_self: bool,
+    /// The page was sent a drag enter that has not been left or dropped.
+    target_entered: bool,
+    /// Operation the page last reported for the drag over it.
+    cursor_operation: u32,
+    /// Hover recorded by `_can_drop_data`, forwarded in `_process`.
+    pending_hover: RefCell<Option<PendingHover>>,
+}
+
+struct PendingHover {
+    position: Vector2,
+    /// Only set while the page has not been entered yet.
+    drag_data: Option<InternalDragDataInfo>,
+}
+
+impl CefTexture {
+    /// Turns a drag started in the page into a Godot drag.
+    pub(super) fn begin_native_drag(
+        &mut self,
+        drag_info: &Gd<DragDataInfo>,
+        drag_data: &InternalDragDataInfo,
+        allowed_ops: u32,
+    ) {
+        self.native_drag.source_active = true;
+        self.native_drag.source_ops = allowed_ops;
+        self.native_drag.dropped_on_self = false;
+
+        let mut preview = Label::new_alloc();
+        preview.set_text(&preview_text(drag_data));
+        preview.set_modulate(Color::from_rgba(1.0, 1.0, 1.0, 0.8));
+        let preview = preview.upcast::<Control>();
+        self.base_mut()
+            .force_drag(&drag_info.to_variant(), &preview);
+    }
+
+    /// Backs `_can_drop_data`: whether `data` can be dropped onto the page.
+    pub(super) fn can_drop_native_drag(&self, position: Vector2, data: &Variant) -> bool {
+        if !self.native_drag_integration || self.app.browser.is_none() {
+            return false;
+        }
+        let Some(drag_data) = drag_data_from_payload(data) else {
+            return false;
+        };
+
+        let entered = self.native_drag.target_entered;
+        *self.native_drag.pending_hover.borrow_mut() = Some(PendingHover {
+            position,
+            drag_data: (!entered).then_some(drag_data),
+        });
+        !entered || self.native_drag.cursor_operation != OP_NONE
+    }
+
+    /// Backs `_drop_data`.
+    pub(super) fn drop_native_drag(&mut self, position: Vector2, data: &Variant) {
+        if !self.can_drop_native_drag(position, data) {
+            return;
+        }
+        self.flush_native_drag_hover();
+        if !self.native_drag.target_entered {
+            return;
+        }
+
+        if let Some(host) = self.app.browser.as_ref().and_then(|b| b.host()) {
+            let mouse_event = input::create_mouse_event(
+                self.local_to_view(position),
+                self.get_pixel_scale_factor(),
+                self.get_device_scale_factor(),
+                0,
+            );
+            host.drag_target_drop(Some(&mouse_event));
+        }
+        self.native_drag.target_entered = false;
+        self.native_drag.dropped_on_self = self.native_drag.source_active;
+        self.app.drag_state.is_drag_over = false;
+    }
+
+    /// Records the operation the page reported for the drag over it.
+    pub(super) fn set_native_drag_cursor(&mut self, operation: u32) {
+        self.native_drag.cursor_operation = operation;
+    }
+
+    /// Forwards the hover recorded by `_can_drop_data`, and leaves the page
+    /// once the drag is no longer over the node.
+    pub(super) fn update_native_drag(&mut self) {
+        self.flush_native_drag_hover();
+
+        let dragging = self
+            .base()
+            .get_viewport()
+            .is_some_and(|viewport| viewport.gui_is_dragging());
+        if self.native_drag.target_entered {
+            let size = self.base().get_size();
+            let mouse = self.base().get_local_mouse_position();
+            if !dragging || !Rect2::new(Vector2::ZERO, size).contains_point(mouse) {
+                self.leave_native_drag_target();
+            }
+        }
+        // Drags cancelled without a drag end notification
+        if self.native_drag.source_active && !dragging {
+            self.end_native_drag();
+        }
+    }
+
+    /// Tells the page the drag has left it, for example because the mouse
+    /// left the window.
+    pub(super) fn leave_native_drag_target(&mut self) {
+        self.native_drag.pending_hover.get_mut().take();
+        if !self.native_drag.target_entered {
+            return;
+        }
+        self.native_drag.target_entered = false;
+        self.app.drag_state.is_drag_over = false;
+        if let Some(host) = self.app.browser.as_ref().and_then(|b| b.host()) {
+            host.drag_target_drag_leave();
+        }
+    }
+
+    /// Handles the end of any Godot drag: leaves the page if the drag was
+    /// over it, and tells CEF how a drag started in the page ended.
+    pub(super) fn end_native_drag(&mut self) {
+        self.leave_native_drag_target();
+        if !self.native_drag.source_active {
+            return;
+        }
+
+        let successful = self
+            .base()
+            .get_viewport()
+            .is_some_and(|viewport| viewport.gui_is_drag_successful());
+        let operation = drop_operation(
+            successful,
+            self.native_drag.dropped_on_self,
+            self.native_drag.cursor_operation,
+            self.native_drag.source_ops,
+        );
+        let position = self.base().get_local_mouse_position();
+        self.native_drag.source_active = false;
+        self.native_drag.dropped_on_self = false;
+
+        self.drag_source_ended(position, operation as i32);
+        self.drag_source_system_ended();
+    }
+
+    fn flush_native_drag_hover(&mut self) {
+        let Some(hover) = self.native_drag.pending_hover.get_mut().take() else {
+            return;
+        };
+        let Some(host) = self.app.browser.as_ref().and_then(|b| b.host()) else {
+            return;
+        };
+
+        let mouse_event = input::create_mouse_event(
+            self.local_to_view(hover.position),
+            self.get_pixel_scale_factor(),
+            self.get_device_scale_factor(),
+            0,
+        );
+        let ops = if self.native_drag.source_active {
+            self.native_drag.source_ops
+        } else {
+            GODOT_DRAG_OPS
+        };
+
+        match hover.drag_data {
+            Some(drag_data) if !self.native_drag.target_entered => {
+                let Some(mut cef_drag_data) = create_cef_drag_data(&drag_data) else {
+                    return;
+                };
+                host.drag_target_drag_enter(
+                    Some(&mut cef_drag_data),
+                    Some(&mouse_event),
+                    drag_operations_mask(ops),
+                );
+                self.native_drag.target_entered = true;
+                self.native_drag.cursor_operation = ops;
+                self.app.drag_state.is_drag_over = true;
+                self.app.drag_state.allowed_ops = ops;
+            }
+            _ => host.drag_target_drag_over(Some(&mouse_event), drag_operations_mask(ops)),
+        }
+    }
+}
+
+fn drag_operations_mask(ops: u32) -> cef::DragOperationsMask {
+    cef::DragOperationsMask::from(cef::sys::cef_drag_operations_mask_t(ops as _))
+}
+
+/// Reads a Godot drag payload: a `DragDataInfo`, a list of file paths, or a
+/// Dictionary with `files`, `url`, `title`, `text` and `html` entries.
+fn drag_data_from_payload(data: &Variant) -> Option<InternalDragDataInfo> {
+    if let Ok(info) = data.try_to::<Gd<DragDataInfo>>() {
+        let info = info.bind();
+        return Some(InternalDragDataInfo {
+            is_link: info.is_link,
+            is_file: info.is_file,
+            is_fragment: info.is_fragment,
+            link_url: info.link_url.to_string(),
+            link_title: info.link_title.to_string(),
+            fragment_text: info.fragment_text.to_string(),
+            fragment_html: info.fragment_html.to_string(),
+            file_names: info
+                .file_names
+                .iter_shared()
+                .map(|s| s.to_string())
+                .collect(),
+        });
+    }
+
+    if let Ok(dict) = data.try_to::<VarDictionary>() {
+        let string = |key: &str| {
+            dict.get(key)
+                .and_then(|value| value.try_to::<GString>().ok())
+                .map(|value| value.to_string())
+                .unwrap_or_default()
+        };
+        let file_names = match dict.get("files") {
+            Some(files) => file_list(&files)?,
+            None => Vec::new(),
+        };
+        let drag_data = InternalDragDataInfo {
+            is_link: false,
+            is_file: !file_names.is_empty(),
+            is_fragment: false,
+            link_url: string("url"),
+            link_title: string("title"),
+            fragment_text: string("text"),
+            fragment_html: string("html"),
+            file_names,
+        };
+        return with_kinds(drag_data);
+    }
+
+    let file_names = file_list(data)?;
+    with_kinds(InternalDragDataInfo {
+        is_file: !file_names.is_empty(),
+        file_names,
+        ..Default::default()
+    })
+}
+
+/// Sets the link and fragment flags from the fields, or returns `None` if
+/// there is nothing to drop.
+fn with_kinds(mut drag_data: InternalDragDataInfo) -> Option<InternalDragDataInfo> {
+    drag_data.is_link = !drag_data.link_url.is_empty();
+    drag_data.is_fragment =
+        !drag_data.fragment_text.is_empty() || !drag_data.fragment_html.is_empty();
+    (drag_data.is_file || drag_data.is_link || drag_data.is_fragment).then_some(drag_data)
+}
+
+/// Reads a list of file paths, turning `res://` and `user://` paths into
+/// absolute ones the page can open.
+fn file_list(data: &Variant) -> Option<Vec<String>> {
+    let paths: Vec<GString> = if let Ok(paths) = data.try_to::<PackedStringArray>() {
+        paths.as_slice().to_vec()
+    } else {
+        data.try_to::<Array<Variant>>()
+            .ok()?
+            .iter_shared()
+            .map(|path| path.try_to::<GString>().ok())
+            .collect::<Option<_>>()?
+    };
+
+    let settings = ProjectSettings::singleton();
+    Some(
+        paths
+            .iter()
+            .map(|path| settings.globalize_path(path).to_string())
+            .collect(),
+    )
+}
+
+fn create_cef_drag_data(drag_data: &InternalDragDataInfo) -> Option<cef::DragData> {
+    let cef_drag_data = cef::drag_data_create()?;
+    for path in &drag_data.file_names {
+        cef_drag_data.add_file(Some(&CefStringUtf16::from(path.as_str())), None);
+    }
+    if drag_data.is_link {
+        cef_drag_data.set_link_url(Some(&CefStringUtf16::from(drag_data.link_url.as_str())));
+        cef_drag_data.set_link_title(Some(&CefStringUtf16::from(drag_data.link_title.as_str())));
+    }
+    if drag_data.is_fragment {
+        cef_drag_data.set_fragment_text(Some(&CefStringUtf16::from(
+            drag_data.fragment_text.as_str(),
+        )));
+        cef_drag_data.set_fragment_html(Some(&CefStringUtf16::from(
+            drag_data.fragment_html.as_str(),
+        )));
+    }
+    Some(cef_drag_data)
+}
+
+/// Operation reported to CEF when a drag started in the page ends. Drops
+/// elsewhere in Godot prefer copying, as the page keeps its content.
+fn drop_operation(
+    successful: bool,
+    dropped_on_self: bool,
+    page_operation: u32,
+    allowed_ops: u32,
+) -> u32 {
+    if !successful {
+        return OP_NONE;
+    }
+    if dropped_on_self {
+        return page_operation & allowed_ops;
+    }
+    [OP_COPY, OP_MOVE, OP_LINK]
+        .into_iter()
+        .find(|op| allowed_ops & op != 0)
+        .unwrap_or(OP_NONE)
+}
+
+/// Text of the generated drag preview: the link title or URL, the file
+/// names, or the first line of the dragged text.
+fn preview_text(drag_data: &InternalDragDataInfo) -> String {
+    let text = if drag_data.is_link {
+        if drag_data.link_title.trim().is_empty() {
+            drag_data.link_url.clone()
+        } else {
+            drag_data.link_title.clone()
+        }
+    } else if drag_data.is_file {
+        drag_data
+            .file_names
+            .iter()
+            .map(|path| path.rsplit(['/', '\\']).next().unwrap_or(path))
+            .collect::<Vec<_>>()
+            .join(", ")
+    } else {
+        drag_data.fragment_text.clone()
+    };
+
+    let line = text.trim().lines().next().unwrap_or_default();
+    if line.chars().count() <= PREVIEW_MAX_CHARS {
+        line.to_string()
+    } else {
+        let shortened: String = line.chars().take(PREVIEW_MAX_CHARS).collect();
+        format!("{}…", shortened.trim_end())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drop_operation() {
+        let all = OP_COPY | OP_LINK | OP_MOVE;
+        assert_eq!(drop_operation(false, false, OP_COPY, all), OP_NONE);
+        assert_eq!(drop_operation(true, false, OP_NONE, all), OP_COPY);
+        assert_eq!(
+            drop_operation(true, false, OP_NONE, OP_MOVE | OP_LINK),
+            OP_MOVE
+        );
+        assert_eq!(drop_operation(true, false, OP_NONE, OP_NONE), OP_NONE);
+        assert_eq!(drop_operation(true, true, OP_MOVE, all), OP_MOVE);
+        assert_eq!(drop_operation(true, true, OP_MOVE, OP_COPY), OP_NONE);
+    }
+
+    #[test]
+    fn test_preview_text() {
+        let link = InternalDragDataInfo {
+            is_link: true,
+            link_url: "https://example.com".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(preview_text(&link), "https://example.com");
+
+        let files = InternalDragDataInfo {
+            is_file: true,
+            file_names: vec!["/tmp/a.png".to_string(), "C:\\b.txt".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(preview_text(&files), "a.png, b.txt");
+
+        let text = InternalDragDataInfo {
+            is_fragment: true,
+            fragment_text: format!("  {}\nsecond line", "x".repeat(60)),
+            ..Default::default()
+        };
+        assert_eq!(preview_text(&text), format!("{}…", "x".repeat(48)));
+    }
+
+    #[test]
+    fn test_with_kinds() {
+        assert!(with_kinds(InternalDragDataInfo::default()).is_none());
+
+        let drag_data = with_kinds(InternalDragDataInfo {
+            link_url: "https://example.com".to_string(),
+            fragment_html: "<b>hi</b>".to_string(),
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(drag_data.is_link);
+        assert!(drag_data.is_fragment);
+        assert!(!drag_data.is_file);
+    }
+}
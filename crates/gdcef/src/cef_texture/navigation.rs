@@ -0,0 +1,98 @@
+//! The `url` property and `navigate`.
+//!
+//! `url` works both ways: setting it loads the URL, and the main frame's
+//! address changes (redirects, fragment and pushState navigations) are
+//! written back to it so the inspector shows where the browser is. With
+//! `ignore_same_url_loads`, assigning the URL the browser is already at
+//! does nothing; `navigate` always loads.
+
+use super::CefTexture;
+use cef::{ImplBrowser, ImplFrame};
+use godot::prelude::*;
+
+use crate::browser::UrlChangeEvent;
+
+/// Returns `url` in the form Chromium reports it, so equal addresses compare
+/// equal however they were written (`HTTP://Example.com:80` and
+/// `http://example.com/`).
+fn normalize_url(url: &str) -> String {
+    let url = url.trim();
+    url::Url::parse(url).map_or_else(|_| url.to_string(), String::from)
+}
+
+fn is_same_url(a: &str, b: &str) -> bool {
+    normalize_url(a) == normalize_url(b)
+}
+
+impl CefTexture {
+    /// Stores `url` and loads it in the main frame. Unless `force`, a URL the
+    /// browser is already at is not loaded again when `ignore_same_url_loads`
+    /// is set.
+    pub(super) fn load_url_property(&mut self, url: GString, force: bool) {
+        let current_url = self.main_frame_url();
+        self.url = url.clone();
+        self.base_mut().update_configuration_warnings();
+        // The editor placeholder shows the URL
+        self.base_mut().queue_redraw();
+
+        if !force
+            && self.ignore_same_url_loads
+            && current_url.is_some_and(|current| is_same_url(&current, &url.to_string()))
+        {
+            return;
+        }
+
+        if let Some(browser) = self.app.browser.as_ref()
+            && let Some(frame) = browser.main_frame()
+        {
+            let url_str: cef::CefStringUtf16 = url.to_string().as_str().into();
+            frame.load_url(Some(&url_str));
+            // `wait_until_painted` now waits for the new page
+            self.page_painted = false;
+        }
+    }
+
+    /// Writes the main frame's new address back to `url`, without loading it.
+    pub(super) fn sync_url_property(&mut self, event: &UrlChangeEvent) {
+        // A preview may be redirected or blocked; the scene keeps the URL set
+        if event.is_main && !self.editor_preview {
+            self.url = GString::from(event.url.as_str());
+        }
+    }
+
+    /// Returns the main frame's address, or `None` without a browser.
+    pub(super) fn main_frame_url(&self) -> Option<String> {
+        let frame = self.app.browser.as_ref()?.main_frame()?;
+        Some(cef::CefStringUtf16::from(&frame.url()).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_same_url() {
+        assert!(is_same_url("https://example.com", "https://example.com/"));
+        assert!(is_same_url(
+            "HTTPS://Example.com:443/a",
+            "https://example.com/a"
+        ));
+        assert!(is_same_url(" res://ui/menu.html", "res://ui/menu.html"));
+        assert!(!is_same_url(
+            "https://example.com/a",
+            "https://example.com/b"
+        ));
+        // A fragment is a different address, loaded as a same-document navigation
+        assert!(!is_same_url(
+            "https://example.com/#top",
+            "https://example.com/"
+        ));
+        assert!(!is_same_url("http://example.com", "https://example.com"));
+        // Paths are case sensitive
+        assert!(!is_same_url(
+            "https://example.com/A",
+            "https://example.com/a"
+        ));
+    }
+}
@@ -0,0 +1,102 @@
+//! Saving the current page with `save_page`.
+//!
+//! MHTML snapshots are captured with `Page.captureSnapshot` and written by
+//! the DevTools message observer as the result arrives; HTML snapshots are
+//! the main frame's source, written by a string visitor. Both report
+//! through `page_saved`.
+
+use super::CefTexture;
+use super::devtools::next_message_id;
+use crate::webrender;
+use cef::{CefString, ImplBrowser, ImplBrowserHost, ImplFrame, ImplValue, JsonParserOptions};
+use godot::classes::ProjectSettings;
+use godot::prelude::*;
+use std::path::PathBuf;
+
+/// Format of a page saved with `save_page`.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum PageSaveFormat {
+    /// A single file with the document and its loaded resources.
+    #[default]
+    Mhtml,
+    /// The main frame's HTML only.
+    Html,
+}
+
+impl CefTexture {
+    /// Starts saving the current page to `path`. Returns `false` if it
+    /// could not start; otherwise `page_saved` reports the outcome.
+    pub(super) fn save_page_impl(&mut self, path: &str, format: PageSaveFormat) -> bool {
+        if path.starts_with("res://") {
+            godot::global::godot_error!(
+                "[CefTexture] Cannot save the page to {}: res:// is read-only in exported games",
+                path
+            );
+            return false;
+        }
+        let Some(event_queues) = self.app.event_queues.clone() else {
+            godot::global::godot_error!("[CefTexture] Cannot save the page: no browser");
+            return false;
+        };
+        let godot_path: GString = path.into();
+        let file = PathBuf::from(
+            ProjectSettings::singleton()
+                .globalize_path(&godot_path)
+                .to_string(),
+        );
+
+        match format {
+            PageSaveFormat::Html => {
+                let Some(frame) = self.app.browser.as_ref().and_then(|b| b.main_frame()) else {
+                    godot::global::godot_error!("[CefTexture] Cannot save the page: no main frame");
+                    return false;
+                };
+                frame.source(Some(&mut webrender::PageSourceVisitorImpl::build(
+                    event_queues,
+                    path.to_string(),
+                    file,
+                )));
+                true
+            }
+            PageSaveFormat::Mhtml => self.capture_snapshot(path, file),
+        }
+    }
+
+    /// Sends `Page.captureSnapshot`, registering the file to write before
+    /// the result can arrive.
+    fn capture_snapshot(&mut self, path: &str, file: PathBuf) -> bool {
+        if !crate::settings::is_devtools_protocol_allowed() {
+            godot::global::godot_error!(
+                "[CefTexture] MHTML snapshots need godot_cef/security/allow_devtools_protocol"
+            );
+            return false;
+        }
+        let Some(host) = self.app.browser.as_ref().and_then(|b| b.host()) else {
+            return false;
+        };
+
+        let message_id = next_message_id();
+        if let Ok(mut page_saves) = self.app.page_saves.lock() {
+            page_saves.insert(message_id, (path.to_string(), file));
+        }
+        let mut params = cef::parse_json(
+            Some(&CefString::from(r#"{"format":"mhtml"}"#)),
+            JsonParserOptions::default(),
+        )
+        .and_then(|value| value.dictionary());
+        let sent = host.execute_dev_tools_method(
+            message_id,
+            Some(&CefString::from("Page.captureSnapshot")),
+            params.as_mut(),
+        );
+        if sent == 0 {
+            if let Ok(mut page_saves) = self.app.page_saves.lock() {
+                page_saves.remove(&message_id);
+            }
+            godot::global::godot_error!("[CefTexture] Failed to request a snapshot of the page");
+            return false;
+        }
+        true
+    }
+}
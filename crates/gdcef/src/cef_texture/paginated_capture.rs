@@ -0,0 +1,325 @@
+//! Paginated capture of the page: `capture_paginated`.
+//!
+//! The main frame is scrolled down one page at a time, each page is cut out
+//! of the texture once the scrolled frame is on it, and the pages are
+//! emitted together with `page_captured_sequence`. One page is captured per
+//! frame at most, so a long page does not stall the game. Pages overlap by
+//! `bleed` pixels so content cut at a page boundary shows on both sheets.
+//!
+//! Sizes are in texture pixels, the page's scroll positions in CSS pixels.
+//! A navigation, a resize or releasing the browser aborts the capture,
+//! which then emits an empty array.
+
+use godot::classes::Image;
+use godot::prelude::*;
+
+use super::CefTexture;
+use crate::browser::ScrollPosition;
+
+/// Frames waited for the page to report the scroll before giving up.
+const SCROLL_TIMEOUT_FRAMES: u32 = 60;
+
+/// Frames waited after the page reported the scroll, for the scrolled frame
+/// to be painted and uploaded to the texture.
+const SETTLE_FRAMES: u32 = 2;
+
+/// Most pages a capture produces, against pages that keep growing.
+const MAX_PAGES: usize = 500;
+
+#[derive(Default)]
+pub(super) struct PaginatedCaptureState {
+    current: Option<PaginatedCapture>,
+}
+
+struct PaginatedCapture {
+    page_width: i32,
+    page_height: i32,
+    bleed: i32,
+    /// Texture size the capture started with.
+    texture_size: (i32, i32),
+    /// Texture pixels per CSS pixel.
+    scale: f64,
+    /// Scroll position to restore once done.
+    original_scroll: (f64, f64),
+    /// Top of the next page in the document, in texture pixels.
+    offset: i32,
+    /// CSS scroll position the page was asked to scroll to.
+    target_y: f64,
+    frames_waited: u32,
+    /// Frames since the page reported the target position.
+    settled_frames: Option<u32>,
+    images: Array<Gd<Image>>,
+}
+
+enum Poll {
+    Waiting,
+    TimedOut,
+    /// The scrolled frame is on the texture.
+    Ready,
+}
+
+impl PaginatedCapture {
+    /// Counts a frame of waiting for the page to scroll to the target and
+    /// for the scrolled frame to reach the texture.
+    fn poll(&mut self, scroll: ScrollPosition) -> Poll {
+        match self.settled_frames.as_mut() {
+            Some(frames) if *frames >= SETTLE_FRAMES => Poll::Ready,
+            Some(frames) => {
+                *frames += 1;
+                Poll::Waiting
+            }
+            None if (scroll.y - self.target_y).abs() < 1.0 => {
+                self.settled_frames = Some(0);
+                Poll::Waiting
+            }
+            None => {
+                self.frames_waited += 1;
+                if self.frames_waited > SCROLL_TIMEOUT_FRAMES {
+                    Poll::TimedOut
+                } else {
+                    Poll::Waiting
+                }
+            }
+        }
+    }
+}
+
+/// Height of the document in texture pixels.
+fn document_height(texture_height: i32, max_scroll_y: f64, scale: f64) -> i32 {
+    texture_height + (max_scroll_y * scale).round() as i32
+}
+
+/// Returns the rows of the texture holding the page at `offset` while the
+/// view is scrolled to `scroll_px`, as `(top, height)`, or `None` if the
+/// page is not in view.
+fn page_rows(
+    offset: i32,
+    page_height: i32,
+    scroll_px: i32,
+    texture_height: i32,
+    document_height: i32,
+) -> Option<(i32, i32)> {
+    let top = offset - scroll_px;
+    if top < 0 || top >= texture_height {
+        return None;
+    }
+    let height = page_height
+        .min(document_height - offset)
+        .min(texture_height - top);
+    (height > 0).then_some((top, height))
+}
+
+/// Returns whether the page at `offset` reaches the end of the document.
+fn is_last_page(offset: i32, page_height: i32, document_height: i32) -> bool {
+    offset + page_height >= document_height
+}
+
+fn scroll_script(x: f64, y: f64) -> String {
+    // `instant` overrides a page's `scroll-behavior: smooth`
+    format!("window.scrollTo({{left: {x}, top: {y}, behavior: 'instant'}});")
+}
+
+impl CefTexture {
+    pub(super) fn capture_paginated_impl(&mut self, page_size: Vector2i, bleed: i32) -> bool {
+        if self.paginated_capture.current.is_some() {
+            godot::global::godot_warn!("[CefTexture] A paginated capture is already running");
+            return false;
+        }
+        if self.app.browser.is_none() {
+            godot::global::godot_warn!("[CefTexture] Cannot capture the page: no browser");
+            return false;
+        }
+        let Some(texture) = self.base().get_texture() else {
+            godot::global::godot_warn!("[CefTexture] Cannot capture the page: no frame yet");
+            return false;
+        };
+        let texture_size = (texture.get_width(), texture.get_height());
+        if page_size.x <= 0 || page_size.y <= 0 {
+            godot::global::godot_error!("[CefTexture] Invalid page size {}", page_size);
+            return false;
+        }
+        if page_size.x > texture_size.0 || page_size.y > texture_size.1 {
+            godot::global::godot_warn!(
+                "[CefTexture] Page size {} is clamped to the view size {}x{}",
+                page_size,
+                texture_size.0,
+                texture_size.1
+            );
+        }
+        let page_width = page_size.x.min(texture_size.0);
+        let page_height = page_size.y.min(texture_size.1);
+        if bleed < 0 || bleed >= page_height {
+            godot::global::godot_error!(
+                "[CefTexture] Bleed must be at least 0 and less than the page height {}",
+                page_height
+            );
+            return false;
+        }
+
+        let device_scale = self
+            .app
+            .device_scale_factor
+            .as_ref()
+            .and_then(|scale| scale.lock().ok().map(|scale| *scale))
+            .unwrap_or_else(|| self.get_device_scale_factor());
+        let scale = device_scale as f64 * super::zoom::zoom_factor(self.host_zoom_level()) as f64;
+        let scroll = self.last_scroll_position();
+
+        self.paginated_capture.current = Some(PaginatedCapture {
+            page_width,
+            page_height,
+            bleed,
+            texture_size,
+            scale,
+            original_scroll: (scroll.x, scroll.y),
+            offset: 0,
+            target_y: 0.0,
+            frames_waited: 0,
+            settled_frames: None,
+            images: Array::new(),
+        });
+        self.scroll_to_next_page(scroll);
+        true
+    }
+
+    /// Advances the capture by at most one page. Called once per frame.
+    pub(super) fn update_paginated_capture(&mut self) {
+        let scroll = self.last_scroll_position();
+        let Some(capture) = self.paginated_capture.current.as_mut() else {
+            return;
+        };
+        match capture.poll(scroll) {
+            Poll::Waiting => return,
+            Poll::TimedOut => {
+                self.abort_paginated_capture("the page did not scroll", true);
+                return;
+            }
+            Poll::Ready => {}
+        }
+
+        let texture = self.base().get_texture();
+        let texture_size = texture
+            .as_ref()
+            .map(|texture| (texture.get_width(), texture.get_height()));
+        let Some(capture) = self.paginated_capture.current.as_mut() else {
+            return;
+        };
+        if texture_size != Some(capture.texture_size) {
+            self.abort_paginated_capture("the view was resized", true);
+            return;
+        }
+        let Some(image) = texture.and_then(|texture| texture.get_image()) else {
+            self.abort_paginated_capture("the frame could not be read", true);
+            return;
+        };
+
+        let document_height = document_height(capture.texture_size.1, scroll.max_y, capture.scale);
+        let scroll_px = (scroll.y * capture.scale).round() as i32;
+        if let Some((top, height)) = page_rows(
+            capture.offset,
+            capture.page_height,
+            scroll_px,
+            capture.texture_size.1,
+            document_height,
+        ) && let Some(page) = image.get_region(Rect2i::new(
+            Vector2i::new(0, top),
+            Vector2i::new(capture.page_width, height),
+        )) {
+            capture.images.push(&page);
+        }
+
+        if is_last_page(capture.offset, capture.page_height, document_height)
+            || capture.images.len() >= MAX_PAGES
+        {
+            self.finish_paginated_capture();
+        } else {
+            capture.offset += capture.page_height - capture.bleed;
+            self.scroll_to_next_page(scroll);
+        }
+    }
+
+    /// Ends a running capture and emits an empty `page_captured_sequence`.
+    /// The scroll position is restored unless the page it belongs to is gone.
+    pub(super) fn abort_paginated_capture(&mut self, reason: &str, restore_scroll: bool) {
+        let Some(capture) = self.paginated_capture.current.take() else {
+            return;
+        };
+        godot::global::godot_warn!("[CefTexture] Paginated capture aborted: {}", reason);
+        if restore_scroll {
+            self.restore_capture_scroll(&capture);
+        }
+        self.emit_browser_signal(
+            "page_captured_sequence",
+            &[("images", Array::<Gd<Image>>::new().to_variant())],
+        );
+    }
+
+    fn finish_paginated_capture(&mut self) {
+        let Some(capture) = self.paginated_capture.current.take() else {
+            return;
+        };
+        self.restore_capture_scroll(&capture);
+        self.emit_browser_signal(
+            "page_captured_sequence",
+            &[("images", capture.images.to_variant())],
+        );
+    }
+
+    /// Asks the page to scroll to the page at the capture's offset, or as
+    /// far as it scrolls.
+    fn scroll_to_next_page(&mut self, scroll: ScrollPosition) {
+        let Some(capture) = self.paginated_capture.current.as_mut() else {
+            return;
+        };
+        let target = (capture.offset as f64 / capture.scale).min(scroll.max_y);
+        capture.target_y = target;
+        capture.frames_waited = 0;
+        capture.settled_frames = None;
+        let script = scroll_script(capture.original_scroll.0, target);
+        self.eval(GString::from(script.as_str()), false);
+    }
+
+    fn restore_capture_scroll(&mut self, capture: &PaginatedCapture) {
+        let (x, y) = capture.original_scroll;
+        self.eval(GString::from(scroll_script(x, y).as_str()), false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_rows() {
+        // 1000 px view over a 2500 px document, 800 px pages
+        assert_eq!(page_rows(0, 800, 0, 1000, 2500), Some((0, 800)));
+        assert_eq!(page_rows(800, 800, 800, 1000, 2500), Some((0, 800)));
+        // Scrolling stops at 1500, so the last page starts lower in the view
+        // and ends with the document
+        assert_eq!(page_rows(2300, 800, 1500, 1000, 2500), Some((800, 200)));
+        assert_eq!(page_rows(0, 800, 100, 1000, 2500), None);
+        assert_eq!(page_rows(1600, 800, 500, 1000, 2500), None);
+    }
+
+    #[test]
+    fn test_is_last_page() {
+        assert!(!is_last_page(0, 800, 2500));
+        assert!(is_last_page(1700, 800, 2500));
+        assert!(is_last_page(0, 800, 800));
+    }
+
+    #[test]
+    fn test_document_height() {
+        assert_eq!(document_height(1000, 0.0, 2.0), 1000);
+        assert_eq!(document_height(1000, 750.0, 2.0), 2500);
+        assert_eq!(document_height(1000, 100.4, 1.25), 1126);
+    }
+
+    #[test]
+    fn test_scroll_script() {
+        assert_eq!(
+            scroll_script(0.0, 640.5),
+            "window.scrollTo({left: 0, top: 640.5, behavior: 'instant'});"
+        );
+    }
+}
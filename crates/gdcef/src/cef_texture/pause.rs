@@ -0,0 +1,98 @@
+//! Pausing the browser with the scene tree.
+//!
+//! A paused node gets no process notifications, so the pause is detected
+//! from the tree's `process_frame` signal, which is emitted regardless. The
+//! node counts as paused when it cannot process, which follows its
+//! `process_mode`: nodes set to `Always` keep their browser running. While
+//! paused the page is hidden, so CEF throttles timers and
+//! `requestAnimationFrame` and stops painting, and with `mute_when_paused`
+//! its audio is muted.
+//!
+//! `CefManager.set_global_mute()` is followed from the same signal, so it
+//! reaches paused nodes too. A browser muted by both the pause and the
+//! global mute stays muted until both are lifted.
+
+use super::CefTexture;
+use cef::{ImplBrowser, ImplBrowserHost};
+use godot::classes::{Engine, SceneTree};
+use godot::prelude::*;
+
+use crate::frame_scheduler;
+
+impl CefTexture {
+    /// Connects to the tree's `process_frame` signal.
+    pub(super) fn init_pause_tracking(&mut self) {
+        let callable = self.base().callable("_on_tree_process_frame");
+        if let Some(mut tree) = Engine::singleton()
+            .get_main_loop()
+            .and_then(|main_loop| main_loop.try_cast::<SceneTree>().ok())
+        {
+            tree.connect("process_frame", &callable);
+        }
+    }
+
+    /// Called every frame, paused or not.
+    pub(super) fn update_pause_state(&mut self) {
+        let paused = self.base().is_inside_tree() && !self.base().can_process();
+        if paused != self.paused {
+            self.paused = paused;
+            if paused {
+                self.enter_pause();
+            } else {
+                self.leave_pause();
+            }
+        }
+        // Other browsers may not process either, so keep CEF's message loop
+        // running for the hide and mute to take effect
+        if self.paused {
+            frame_scheduler::pump_message_loop();
+        }
+    }
+
+    /// Follows `CefManager.set_global_mute()`. Called every frame, paused or
+    /// not.
+    pub(super) fn update_global_mute(&mut self) {
+        let muted = crate::cef_manager::is_globally_muted();
+        if muted == self.global_mute || self.app.browser.is_none() {
+            return;
+        }
+        self.global_mute = muted;
+        if muted {
+            if !self.is_audio_muted() {
+                self.set_audio_muted(true);
+                self.muted_by_global_mute = true;
+            }
+        } else if std::mem::take(&mut self.muted_by_global_mute) {
+            if self.paused && self.mute_when_paused {
+                self.muted_by_pause = true;
+            } else {
+                self.set_audio_muted(false);
+            }
+        }
+    }
+
+    fn enter_pause(&mut self) {
+        self.apply_page_visibility();
+        if self.mute_when_paused && !self.is_audio_muted() {
+            self.set_audio_muted(true);
+            self.muted_by_pause = true;
+        }
+    }
+
+    fn leave_pause(&mut self) {
+        self.apply_page_visibility();
+        if std::mem::take(&mut self.muted_by_pause) {
+            if self.global_mute {
+                self.muted_by_global_mute = true;
+            } else {
+                self.set_audio_muted(false);
+            }
+        }
+        // The last frame before the pause may be stale
+        if let Some(browser) = self.app.browser.as_mut()
+            && let Some(host) = browser.host()
+        {
+            host.invalidate(cef::PaintElementType::VIEW);
+        }
+    }
+}
@@ -0,0 +1,230 @@
+//! `render_target`: where the browser texture is drawn.
+//!
+//! With `SubViewport`, a `TextureRect` in an internal `SubViewport` draws the
+//! browser texture, popup overlay included, and the node shows the viewport's
+//! texture. A material on the node, or the viewport's own environment, then
+//! applies to the page like to any other viewport, and `get_output_viewport`
+//! hands the viewport out for materials elsewhere.
+//!
+//! The page is laid out at the viewport's size. It follows the node's size
+//! in pixels whenever the node is resized, and a size set in between is kept,
+//! e.g. a fixed resolution for a screen in 3D. Positions of input are scaled
+//! from the node to the viewport.
+
+use super::CefTexture;
+use godot::classes::control::{LayoutPreset, MouseFilter};
+use godot::classes::node::InternalMode;
+use godot::classes::sub_viewport::UpdateMode;
+use godot::classes::texture_rect::ExpandMode;
+use godot::classes::{InputEvent, SubViewport, Texture2D, TextureRect};
+use godot::prelude::*;
+
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum RenderTarget {
+    /// Draw the page on the node itself.
+    #[default]
+    TextureRect,
+    /// Draw the page in an internal `SubViewport` the node shows.
+    SubViewport,
+}
+
+/// The internal viewport of `RenderTarget::SubViewport`.
+#[derive(Default)]
+pub(super) struct OutputViewport {
+    viewport: Option<Gd<SubViewport>>,
+    /// Draws the browser texture in the viewport.
+    rect: Option<Gd<TextureRect>>,
+    /// Viewport size the node's size was last converted to.
+    synced_size: Vector2i,
+}
+
+/// Returns the viewport size in pixels for a node of `size`.
+fn viewport_size_for(size: Vector2, pixel_scale: f32) -> Vector2i {
+    Vector2i::new(
+        ((size.x * pixel_scale).round() as i32).max(1),
+        ((size.y * pixel_scale).round() as i32).max(1),
+    )
+}
+
+/// Returns the scale from positions on a node of `node_size` to positions on
+/// a page laid out in a viewport of `viewport_size` pixels.
+fn local_to_view_scale(node_size: Vector2, viewport_size: Vector2i, pixel_scale: f32) -> Vector2 {
+    if node_size.x <= 0.0 || node_size.y <= 0.0 || pixel_scale <= 0.0 {
+        return Vector2::ONE;
+    }
+    Vector2::new(
+        viewport_size.x as f32 / pixel_scale / node_size.x,
+        viewport_size.y as f32 / pixel_scale / node_size.y,
+    )
+}
+
+impl CefTexture {
+    /// Creates the output viewport for `RenderTarget::SubViewport`, or frees
+    /// the one of a previous browser for `RenderTarget::TextureRect`.
+    pub(super) fn apply_render_target(&mut self) {
+        if self.render_target == RenderTarget::TextureRect {
+            if let Some(mut viewport) = self.output_viewport.viewport.take() {
+                viewport.queue_free();
+                self.output_viewport = OutputViewport::default();
+            }
+            return;
+        }
+        if self.output_viewport.viewport.is_some() {
+            return;
+        }
+
+        let size = viewport_size_for(self.base().get_size(), self.get_pixel_scale_factor());
+        let mut viewport = SubViewport::new_alloc();
+        viewport.set_name("CefOutputViewport");
+        viewport.set_size(size);
+        viewport.set_transparent_background(true);
+        viewport.set_disable_3d(true);
+        viewport.set_update_mode(UpdateMode::ALWAYS);
+
+        let mut rect = TextureRect::new_alloc();
+        rect.set_expand_mode(ExpandMode::IGNORE_SIZE);
+        rect.set_mouse_filter(MouseFilter::IGNORE);
+        viewport.add_child(&rect);
+        // Follows the viewport's size
+        rect.set_anchors_and_offsets_preset(LayoutPreset::FULL_RECT);
+
+        self.base_mut()
+            .add_child_ex(&viewport)
+            .internal(InternalMode::FRONT)
+            .done();
+        if let Some(texture) = viewport.get_texture() {
+            self.base_mut().set_texture(&texture);
+        }
+        self.output_viewport = OutputViewport {
+            viewport: Some(viewport),
+            rect: Some(rect),
+            synced_size: size,
+        };
+    }
+
+    pub(super) fn output_viewport(&self) -> Option<Gd<SubViewport>> {
+        self.output_viewport.viewport.clone()
+    }
+
+    /// Resizes the output viewport when the node was resized.
+    pub(super) fn update_output_viewport(&mut self) {
+        if self.output_viewport.viewport.is_none() {
+            return;
+        }
+        let size = viewport_size_for(self.base().get_size(), self.get_pixel_scale_factor());
+        if size == self.output_viewport.synced_size {
+            return;
+        }
+        self.output_viewport.synced_size = size;
+        if let Some(viewport) = self.output_viewport.viewport.as_mut() {
+            viewport.set_size(size);
+        }
+    }
+
+    /// Draws `texture`, the browser's, on the node or in the output viewport.
+    pub(super) fn set_browser_texture(&mut self, texture: Gd<Texture2D>) {
+        match self.output_viewport.rect.as_mut() {
+            Some(rect) => rect.set_texture(&texture),
+            None => self.base_mut().set_texture(&texture),
+        }
+    }
+
+    /// Adds the popup overlay over the browser texture.
+    pub(super) fn add_popup_overlay(&mut self, overlay: &Gd<TextureRect>) {
+        match self.output_viewport.rect.as_mut() {
+            Some(rect) => rect.add_child(overlay),
+            None => self.base_mut().add_child(overlay),
+        }
+    }
+
+    /// Size of the rect drawing the browser texture, in its own units.
+    pub(super) fn browser_rect_size(&self) -> Vector2 {
+        match &self.output_viewport.rect {
+            Some(rect) => rect.get_size(),
+            None => self.base().get_size(),
+        }
+    }
+
+    /// Size the page is laid out at, in the node's units: the node's size,
+    /// or the output viewport's.
+    pub(super) fn view_size(&self) -> Vector2 {
+        match &self.output_viewport.viewport {
+            Some(viewport) => {
+                let size = viewport.get_size();
+                Vector2::new(size.x as f32, size.y as f32) / self.get_pixel_scale_factor()
+            }
+            None => self.base().get_size(),
+        }
+    }
+
+    fn local_to_view_scale(&self) -> Vector2 {
+        match &self.output_viewport.viewport {
+            Some(viewport) => local_to_view_scale(
+                self.base().get_size(),
+                viewport.get_size(),
+                self.get_pixel_scale_factor(),
+            ),
+            None => Vector2::ONE,
+        }
+    }
+
+    /// Converts a position on the node to one on the page.
+    pub(super) fn local_to_view(&self, position: Vector2) -> Vector2 {
+        position * self.local_to_view_scale()
+    }
+
+    /// Converts a position on the page to one on the node.
+    pub(super) fn view_to_local(&self, position: Vector2) -> Vector2 {
+        position / self.local_to_view_scale()
+    }
+
+    /// Returns `event` with its positions converted to the page's.
+    pub(super) fn input_event_to_view(&self, event: Gd<InputEvent>) -> Gd<InputEvent> {
+        let scale = self.local_to_view_scale();
+        if scale == Vector2::ONE {
+            return event;
+        }
+        let xform = Transform2D::from_cols(
+            Vector2::new(scale.x, 0.0),
+            Vector2::new(0.0, scale.y),
+            Vector2::ZERO,
+        );
+        event.xformed_by(xform).unwrap_or(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_viewport_size_for() {
+        assert_eq!(
+            viewport_size_for(Vector2::new(640.0, 360.0), 1.0),
+            Vector2i::new(640, 360)
+        );
+        assert_eq!(
+            viewport_size_for(Vector2::new(640.0, 360.5), 2.0),
+            Vector2i::new(1280, 721)
+        );
+        assert_eq!(viewport_size_for(Vector2::ZERO, 1.0), Vector2i::new(1, 1));
+    }
+
+    #[test]
+    fn test_local_to_view_scale() {
+        let node = Vector2::new(640.0, 360.0);
+        assert_eq!(
+            local_to_view_scale(node, Vector2i::new(1280, 720), 2.0),
+            Vector2::ONE
+        );
+        assert_eq!(
+            local_to_view_scale(node, Vector2i::new(1920, 360), 1.0),
+            Vector2::new(3.0, 1.0)
+        );
+        assert_eq!(
+            local_to_view_scale(Vector2::ZERO, Vector2i::new(1920, 1080), 1.0),
+            Vector2::ONE
+        );
+    }
+}
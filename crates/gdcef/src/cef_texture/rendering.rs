@@ -1,18 +1,96 @@
 use super::CefTexture;
 use cef::{ImplBrowser, ImplBrowserHost};
+use godot::classes::canvas_item::TextureFilter;
 use godot::classes::control::MouseFilter;
 use godot::classes::image::Format as ImageFormat;
 use godot::classes::texture_rect::ExpandMode;
 use godot::classes::{DisplayServer, Engine, Image, TextureRect};
 use godot::prelude::*;
-use software_render::{DestBuffer, PopupBuffer, composite_popup};
+use std::time::{Duration, Instant};
 
 use crate::browser::RenderMode;
-use crate::utils::get_display_scale_factor;
+use crate::frame_pacing::{self, FramePacerHandle, FramePacing};
+use crate::render_stats::RenderStatsSnapshot;
 use crate::{cursor, render};
 
+/// Filter of the browser texture set with `texture_filter_override`.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum TextureFilterOverride {
+    /// Keep the node's own `texture_filter`.
+    #[default]
+    Inherit,
+    Nearest,
+    Linear,
+    /// Needs `generate_mipmaps`.
+    LinearMipmaps,
+    /// Needs `generate_mipmaps`.
+    LinearMipmapsAnisotropic,
+}
+
+impl TextureFilterOverride {
+    /// Returns the node's `texture_filter` for this override, `None` to keep
+    /// the current one.
+    pub fn texture_filter(self) -> Option<TextureFilter> {
+        match self {
+            Self::Inherit => None,
+            Self::Nearest => Some(TextureFilter::NEAREST),
+            Self::Linear => Some(TextureFilter::LINEAR),
+            Self::LinearMipmaps => Some(TextureFilter::LINEAR_WITH_MIPMAPS),
+            Self::LinearMipmapsAnisotropic => Some(TextureFilter::LINEAR_WITH_MIPMAPS_ANISOTROPIC),
+        }
+    }
+}
+
 impl CefTexture {
+    /// Recreates the browser texture with or without mip levels after
+    /// `generate_mipmaps` changed.
+    pub(super) fn apply_generate_mipmaps(&mut self) {
+        if let Some(RenderMode::Software { frame_buffer, .. }) = &self.app.render_mode {
+            // The texture is only recreated for a dirty frame
+            if let Ok(mut fb) = frame_buffer.lock()
+                && !fb.data.is_empty()
+            {
+                fb.dirty = true;
+            }
+            return;
+        }
+
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+        if let Some(RenderMode::Accelerated { render_state, .. }) = &self.app.render_mode {
+            let Ok(mut state) = render_state.lock() else {
+                return;
+            };
+            if state.mipmap_generator.is_some() == self.generate_mipmaps {
+                return;
+            }
+            if let Some(mut generator) = state.mipmap_generator.take() {
+                generator.free();
+            }
+            state.mipmap_generator = self
+                .generate_mipmaps
+                .then(crate::accelerated_osr::MipmapGenerator::default);
+            // Recreated at the same size like on a resize, and shown once a
+            // frame was copied into it
+            state.needs_resize = Some((state.dst_width, state.dst_height));
+            drop(state);
+            if let Some(host) = self.app.browser.as_ref().and_then(|browser| browser.host()) {
+                host.invalidate(cef::PaintElementType::VIEW);
+            }
+        }
+    }
+
     pub(super) fn get_max_fps(&self) -> i32 {
+        if self.editor_preview {
+            return super::editor_preview::EDITOR_PREVIEW_FRAME_RATE;
+        }
+        if self.background_mode {
+            let background_fps = crate::settings::get_background_frame_rate();
+            if background_fps > 0 {
+                return background_fps;
+            }
+        }
+
         // Check project setting first
         let setting_fps = crate::settings::get_max_frame_rate();
         if setting_fps > 0 {
@@ -31,8 +109,29 @@ impl CefTexture {
         }
     }
 
-    pub(super) fn handle_max_fps_change(&mut self) {
+    /// `get_max_fps`, lowered to the frame rate Godot reaches when
+    /// `frame_pacing` is `MatchEngineFps`.
+    fn paced_max_fps(&self) -> i32 {
         let max_fps = self.get_max_fps();
+        if self.frame_pacing != FramePacing::MatchEngineFps
+            || self.background_mode
+            || self.editor_preview
+        {
+            return max_fps;
+        }
+        frame_pacing::paced_frame_rate(
+            self.last_max_fps,
+            max_fps,
+            Engine::singleton().get_frames_per_second(),
+        )
+    }
+
+    pub(super) fn handle_max_fps_change(&mut self) {
+        if let Some(pacer) = &self.app.frame_pacer {
+            pacer.set_drop_stale(self.frame_pacing == FramePacing::DropStale);
+        }
+
+        let max_fps = self.paced_max_fps();
         if max_fps == self.last_max_fps {
             return;
         }
@@ -47,14 +146,23 @@ impl CefTexture {
 
     pub(super) fn handle_size_change(&mut self) -> bool {
         let current_dpi = self.get_pixel_scale_factor();
-        let logical_size = self.base().get_size();
+        let logical_size = self.view_size();
         if logical_size.x <= 0.0 || logical_size.y <= 0.0 {
             return false;
         }
 
-        let size_diff = (logical_size - self.last_size).abs();
-        let dpi_diff = (current_dpi - self.last_dpi).abs();
-        if size_diff.x < 1e-6 && size_diff.y < 1e-6 && dpi_diff < 1e-6 {
+        let display_scale = self.get_device_scale_factor();
+        let dpi_changed = (current_dpi - self.last_dpi).abs() >= 1e-6;
+        let display_scale_changed = (display_scale - self.last_display_scale).abs() >= 1e-6;
+
+        // Scale changes are forwarded right away; plain size changes wait
+        // until the size settles. Meanwhile the last frame is stretched.
+        let size_ready = self.resize_debouncer.should_apply(
+            (self.last_size.x, self.last_size.y),
+            (logical_size.x, logical_size.y),
+            Instant::now(),
+        );
+        if !size_ready && !dpi_changed && !display_scale_changed {
             return false;
         }
 
@@ -74,6 +182,20 @@ impl CefTexture {
             *dpi = current_dpi;
         }
 
+        if let Some(shared_display_scale) = &self.app.display_scale
+            && let Ok(mut scale) = shared_display_scale.lock()
+        {
+            *scale = display_scale;
+        }
+
+        // Moving between monitors changes the device scale factor. The pixel
+        // size CEF paints at can stay the same, so the resize check in
+        // `on_accelerated_paint` may never fire; reallocate up front instead.
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+        if display_scale_changed {
+            self.resize_accelerated_textures(pixel_width, pixel_height, display_scale);
+        }
+
         if let Some(browser) = self.app.browser.as_mut()
             && let Some(host) = browser.host()
         {
@@ -83,99 +205,133 @@ impl CefTexture {
 
         self.last_size = logical_size;
         self.last_dpi = current_dpi;
+        self.last_display_scale = display_scale;
+        self.wake_page();
         true
     }
 
+    /// Forces the accelerated destination texture to the size CEF will paint
+    /// at after a device scale factor change, and recreates the popup texture
+    /// so it is not shown at the old scale.
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    fn resize_accelerated_textures(
+        &mut self,
+        pixel_width: f32,
+        pixel_height: f32,
+        display_scale: f32,
+    ) {
+        let Some(RenderMode::Accelerated { render_state, .. }) = &self.app.render_mode else {
+            return;
+        };
+        let Ok(mut state) = render_state.lock() else {
+            return;
+        };
+
+        let (view_width, view_height) =
+            render::view_size_for(pixel_width, pixel_height, display_scale);
+        let (new_w, new_h) = render::paint_size_for(view_width, view_height, display_scale);
+        if (new_w, new_h) != (state.dst_width, state.dst_height) {
+            state.needs_resize = Some((new_w, new_h));
+        }
+
+        if state.popup_rd_rid.is_some() {
+            let ratio = display_scale / self.last_display_scale;
+            let popup_w = (state.popup_width as f32 * ratio).ceil() as u32;
+            let popup_h = (state.popup_height as f32 * ratio).ceil() as u32;
+            state.needs_popup_texture = Some((popup_w.max(1), popup_h.max(1)));
+            state.popup_has_content = false;
+        }
+    }
+
     pub(super) fn update_texture(&mut self) {
         if let Some(RenderMode::Software {
+            uploader,
             frame_buffer,
             texture,
         }) = &mut self.app.render_mode
         {
-            let Ok(mut fb) = frame_buffer.lock() else {
-                return;
-            };
-
-            let popup_metadata = self.app.popup_state.as_ref().and_then(|ps| {
-                ps.lock().ok().and_then(|popup| {
-                    if popup.visible && !popup.buffer.is_empty() {
-                        Some((
-                            popup.width,
-                            popup.height,
-                            popup.rect.x,
-                            popup.rect.y,
-                            popup.dirty,
-                        ))
-                    } else {
-                        None
-                    }
-                })
-            });
+            if uploader.as_ref().is_some_and(|u| u.take_uploads()) {
+                frame_consumed(self.app.frame_pacer.as_ref(), self.app.browser.as_ref());
+            }
 
-            let popup_dirty = popup_metadata
+            let popup_dirty = self
+                .app
+                .popup_state
                 .as_ref()
-                .is_some_and(|(_, _, _, _, dirty)| *dirty);
-
-            if !fb.dirty && !popup_dirty {
+                .and_then(|ps| ps.lock().ok())
+                .is_some_and(|popup| popup.visible && !popup.buffer.is_empty() && popup.dirty);
+            let Some((dirty, width, height)) = frame_buffer
+                .lock()
+                .ok()
+                .filter(|fb| !fb.data.is_empty())
+                .map(|fb| (fb.dirty, fb.width, fb.height))
+            else {
+                return;
+            };
+            if !dirty && !popup_dirty {
                 return;
             }
 
+            let display_scale = self.last_display_scale;
+            let mipmaps = crate::software_upload::software_mipmaps(
+                self.generate_mipmaps,
+                self.force_mipmaps,
+                width,
+                height,
+            );
+            if let Some(uploader) = uploader.as_mut() {
+                if uploader.size() == (width, height) && uploader.mipmaps() == mipmaps {
+                    uploader.request(display_scale);
+                    return;
+                }
+                // The texture is recreated below at the new size or with the
+                // new mipmaps; no update of the old one may land after that
+                uploader.flush();
+            }
+
+            let Ok(mut fb) = frame_buffer.lock() else {
+                return;
+            };
             if fb.data.is_empty() {
                 return;
             }
+            let final_data = crate::software_upload::composite_frame(
+                &fb,
+                self.app.popup_state.as_ref(),
+                display_scale,
+            );
 
-            let width = fb.width as i32;
-            let height = fb.height as i32;
-            let display_scale = get_display_scale_factor();
-
-            let final_data =
-                if let Some((popup_width, popup_height, popup_x, popup_y, _)) = popup_metadata {
-                    let popup_buffer = self
-                        .app
-                        .popup_state
-                        .as_ref()
-                        .and_then(|ps| ps.lock().ok().map(|popup| popup.buffer.clone()));
-
-                    if let Some(popup_buffer) = popup_buffer {
-                        let mut composited = fb.data.clone();
-                        let scaled_x = (popup_x as f32 * display_scale) as i32;
-                        let scaled_y = (popup_y as f32 * display_scale) as i32;
-                        composite_popup(
-                            &mut DestBuffer {
-                                data: &mut composited,
-                                width: fb.width,
-                                height: fb.height,
-                            },
-                            &PopupBuffer {
-                                data: &popup_buffer,
-                                width: popup_width,
-                                height: popup_height,
-                                x: scaled_x,
-                                y: scaled_y,
-                            },
-                        );
-                        if let Some(ps) = &self.app.popup_state
-                            && let Ok(mut popup) = ps.lock()
-                        {
-                            popup.mark_clean();
-                        }
-                        composited
-                    } else {
-                        fb.data.clone()
-                    }
-                } else {
-                    fb.data.clone()
-                };
-
+            let upload_start = Instant::now();
             let byte_array = PackedByteArray::from(final_data.as_slice());
 
-            let image: Option<Gd<Image>> =
-                Image::create_from_data(width, height, false, ImageFormat::RGBA8, &byte_array);
-            if let Some(image) = image {
+            let image: Option<Gd<Image>> = Image::create_from_data(
+                fb.width as i32,
+                fb.height as i32,
+                false,
+                ImageFormat::RGBA8,
+                &byte_array,
+            );
+            if let Some(mut image) = image {
+                if mipmaps {
+                    image.generate_mipmaps();
+                }
                 texture.set_image(&image);
+                if let Some(uploader) = uploader.as_mut() {
+                    uploader.set_format(fb.width, fb.height, mipmaps);
+                }
+            }
+            if let Some(stats) = &self.app.render_stats {
+                stats.record_upload(upload_start.elapsed());
             }
 
             fb.mark_clean();
+            frame_consumed(self.app.frame_pacer.as_ref(), self.app.browser.as_ref());
+            return;
+        }
+
+        #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+        if self.device_recovery.is_some() {
+            self.recover_gpu_device();
             return;
         }
 
@@ -189,14 +345,13 @@ impl CefTexture {
                 return;
             };
 
-            let texture_to_set = if let Some((new_w, new_h)) = state.needs_resize.take()
+            if let Some((new_w, new_h)) = state.needs_resize.take()
                 && new_w > 0
                 && new_h > 0
             {
-                render::free_rd_texture(state.dst_rd_rid);
-
+                let mipmaps = state.mipmap_generator.is_some();
                 let (new_rd_rid, new_texture_2d_rd) =
-                    match render::create_rd_texture(new_w as i32, new_h as i32) {
+                    match render::create_rd_texture(new_w as i32, new_h as i32, mipmaps) {
                         Ok(result) => result,
                         Err(e) => {
                             godot::global::godot_error!("[CefTexture] {}", e);
@@ -204,26 +359,76 @@ impl CefTexture {
                         }
                     };
 
-                state.dst_rd_rid = new_rd_rid;
-                state.dst_width = new_w;
-                state.dst_height = new_h;
+                // Keep displaying the current texture until a frame at the new
+                // size has been copied, instead of showing an empty texture.
+                let replaced_rid = state.replace_destination(new_rd_rid, new_w, new_h);
+                let displayed_rid = match self.resized_texture.take() {
+                    // The previous replacement never received a frame
+                    Some((_, displayed_rid)) => {
+                        render::free_rd_texture(replaced_rid);
+                        displayed_rid
+                    }
+                    None => replaced_rid,
+                };
+                self.resized_texture = Some((new_texture_2d_rd, displayed_rid));
+            }
 
-                *texture_2d_rd = new_texture_2d_rd.clone();
-                Some(new_texture_2d_rd)
+            let mut copied_size = None;
+            let mut device_lost = false;
+            if state.has_pending_copy {
+                let copy_start = Instant::now();
+                let copy_size = state.pending_copy_size;
+                match state.process_pending_copy() {
+                    Ok(crate::accelerated_osr::CopyOutcome::Copied) => {
+                        copied_size = Some(copy_size);
+                        if let Some(stats) = &self.app.render_stats {
+                            stats.record_upload(copy_start.elapsed());
+                        }
+                    }
+                    // Keep showing the last frame until CEF paints at the new size
+                    Ok(crate::accelerated_osr::CopyOutcome::Stale) => {
+                        if let Some(stats) = &self.app.render_stats {
+                            stats.record_stale_frame();
+                        }
+                    }
+                    Err(e) => {
+                        device_lost = state
+                            .importer_mut()
+                            .is_ok_and(|importer| importer.is_device_lost());
+                        if !device_lost {
+                            godot::global::godot_error!(
+                                "[CefTexture] Failed to process pending copy: {}",
+                                e
+                            );
+                        }
+                    }
+                }
+                frame_consumed(self.app.frame_pacer.as_ref(), self.app.browser.as_ref());
+            }
+
+            let texture_to_set = if copied_size == Some((state.dst_width, state.dst_height)) {
+                self.resized_texture.take()
             } else {
                 None
             };
 
-            if state.has_pending_copy
-                && let Err(e) = state.process_pending_copy()
-            {
-                godot::global::godot_error!("[CefTexture] Failed to process pending copy: {}", e);
-            }
-
             drop(state);
 
-            if let Some(tex) = texture_to_set {
-                self.base_mut().set_texture(&tex);
+            if let Some((tex, old_rid)) = texture_to_set {
+                *texture_2d_rd = tex.clone();
+                self.set_browser_texture(tex.upcast());
+                render::free_rd_texture(old_rid);
+            }
+
+            if device_lost {
+                godot::global::godot_warn!(
+                    "[CefTexture] GPU device lost, recreating accelerated rendering resources"
+                );
+                self.device_recovery = Some(crate::accelerated_osr::DeviceLossRecovery::new(
+                    Instant::now(),
+                ));
+                self.emit_browser_signal("gpu_device_lost", &[]);
+                return;
             }
         }
 
@@ -236,7 +441,7 @@ impl CefTexture {
                     render::free_rd_texture(old_rid);
                 }
 
-                match render::create_rd_texture(new_w as i32, new_h as i32) {
+                match render::create_rd_texture(new_w as i32, new_h as i32, false) {
                     Ok((new_rid, new_texture_2d_rd)) => {
                         state.popup_rd_rid = Some(new_rid);
                         state.popup_width = new_w;
@@ -298,16 +503,18 @@ impl CefTexture {
                 Some((popup_dirty, popup_has_content, tex_width, tex_height)),
             ) => {
                 if self.popup_overlay.is_none() {
+                    // Keeps the default `TEXTURE_FILTER_PARENT_NODE`, so it
+                    // is filtered like the browser texture it is drawn over,
+                    // `texture_filter_override` included
                     let mut overlay = TextureRect::new_alloc();
                     overlay.set_expand_mode(ExpandMode::IGNORE_SIZE);
                     overlay.set_mouse_filter(MouseFilter::IGNORE);
-                    let overlay_node: Gd<godot::classes::Node> = overlay.clone().upcast();
-                    self.base_mut().add_child(&overlay_node);
+                    self.add_popup_overlay(&overlay);
                     self.popup_overlay = Some(overlay);
                 }
 
-                let display_scale = get_display_scale_factor();
-                let cef_texture_size = self.base().get_size();
+                let display_scale = self.last_display_scale;
+                let cef_texture_size = self.browser_rect_size();
                 let render_size = self
                     .app
                     .render_size
@@ -362,7 +569,189 @@ impl CefTexture {
         }
     }
 
+    /// Builds the dictionary returned by `get_render_stats`.
+    pub(super) fn render_stats_dictionary(&self, snapshot: &RenderStatsSnapshot) -> VarDictionary {
+        let (render_path, width, height) = self.render_path_and_size();
+        vdict! {
+            "paints_per_second": snapshot.paints_per_second(),
+            "paint_count": snapshot.paints as i64,
+            "average_convert_ms": snapshot.average_convert_ms(),
+            "average_upload_ms": snapshot.average_upload_ms(),
+            "dropped_frames": snapshot.dropped_frames as i64,
+            "stale_frames": snapshot.stale_frames as i64,
+            "skipped_frames": snapshot.skipped_frames as i64,
+            "render_path": render_path,
+            "texture_width": width as i64,
+            "texture_height": height as i64,
+        }
+    }
+
+    /// Returns the render path (`software`, `accelerated` or `none`) and the
+    /// size of the browser texture in physical pixels.
+    pub(super) fn render_path_and_size(&self) -> (&'static str, u32, u32) {
+        match &self.app.render_mode {
+            Some(RenderMode::Software { frame_buffer, .. }) => {
+                let (width, height) = frame_buffer
+                    .lock()
+                    .map(|fb| (fb.width, fb.height))
+                    .unwrap_or_default();
+                ("software", width, height)
+            }
+            #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+            Some(RenderMode::Accelerated { render_state, .. }) => {
+                let (width, height) = render_state
+                    .lock()
+                    .map(|state| (state.dst_width, state.dst_height))
+                    .unwrap_or_default();
+                ("accelerated", width, height)
+            }
+            None => ("none", 0, 0),
+        }
+    }
+
+    /// Emits `render_stats_updated` about once per second when enabled by
+    /// the `godot_cef/debug/emit_render_stats` project setting.
+    pub(super) fn emit_render_stats_if_due(&mut self) {
+        if !self.emit_render_stats {
+            return;
+        }
+        let Some(stats) = &self.app.render_stats else {
+            return;
+        };
+
+        let snapshot = stats.snapshot();
+        let interval = snapshot.since(&self.last_render_stats);
+        if interval.elapsed < Duration::from_secs(1) {
+            return;
+        }
+        self.last_render_stats = snapshot;
+
+        let info = self.render_stats_dictionary(&interval);
+        self.emit_browser_signal("render_stats_updated", &[("stats", info.to_variant())]);
+    }
+
+    /// Retries rebuilding the accelerated rendering resources after a GPU
+    /// device loss, falling back to software rendering if that keeps failing.
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    fn recover_gpu_device(&mut self) {
+        let now = Instant::now();
+        if !self
+            .device_recovery
+            .as_ref()
+            .is_some_and(|recovery| recovery.is_due(now))
+        {
+            return;
+        }
+
+        match self.recreate_accelerated_resources() {
+            Ok(()) => {
+                self.device_recovery = None;
+                godot::global::godot_print!("[CefTexture] Recovered from GPU device loss");
+                if let Some(browser) = self.app.browser.as_mut()
+                    && let Some(host) = browser.host()
+                {
+                    host.invalidate(cef::PaintElementType::VIEW);
+                }
+            }
+            Err(e) => {
+                godot::global::godot_warn!("[CefTexture] GPU device recovery failed: {}", e);
+                let give_up = self
+                    .device_recovery
+                    .as_mut()
+                    .is_none_or(|recovery| recovery.attempt_failed(now));
+                if give_up {
+                    self.device_recovery = None;
+                    self.fall_back_to_software_rendering();
+                }
+            }
+        }
+    }
+
+    /// Replaces the texture importer and destination textures, which belong
+    /// to the lost device, with new ones.
+    #[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+    fn recreate_accelerated_resources(&mut self) -> Result<(), String> {
+        let Some(RenderMode::Accelerated {
+            render_state,
+            texture_2d_rd,
+        }) = &mut self.app.render_mode
+        else {
+            return Err("Browser is not using accelerated rendering".into());
+        };
+
+        let mut importer = crate::accelerated_osr::GodotTextureImporter::new()
+            .ok_or("Failed to recreate the GPU texture importer")?;
+        if importer.is_device_lost() {
+            return Err("Godot's rendering device has not recovered".into());
+        }
+
+        let mut state = render_state
+            .lock()
+            .map_err(|_| "Failed to lock render state".to_string())?;
+        let (width, height) = (state.dst_width, state.dst_height);
+        let (new_rid, new_texture_2d_rd) = render::create_rd_texture(
+            width as i32,
+            height as i32,
+            state.mipmap_generator.is_some(),
+        )
+        .map_err(|e| e.to_string())?;
+
+        state.importer = Some(importer);
+        // Its shader and sampler belonged to the lost device
+        if let Some(generator) = &mut state.mipmap_generator {
+            generator.free();
+        }
+        let old_rid = state.replace_destination(new_rid, width, height);
+        state.has_pending_copy = false;
+        if state.popup_rd_rid.is_some() {
+            state.needs_popup_texture = Some((state.popup_width, state.popup_height));
+            state.popup_has_content = false;
+        }
+        drop(state);
+
+        *texture_2d_rd = new_texture_2d_rd.clone();
+        self.set_browser_texture(new_texture_2d_rd.upcast());
+        render::free_rd_texture(old_rid);
+        if let Some((_, displayed_rid)) = self.resized_texture.take() {
+            render::free_rd_texture(displayed_rid);
+        }
+        Ok(())
+    }
+
+    /// Forwards the combined hidden/occluded/paused state to CEF.
+    pub(super) fn apply_page_visibility(&mut self) {
+        let hidden = !self.is_page_visible() || self.paused;
+        self.wake_page();
+        if let Some(browser) = self.app.browser.as_mut()
+            && let Some(host) = browser.host()
+        {
+            host.was_hidden(hidden as _);
+        }
+    }
+
     pub(super) fn request_external_begin_frame(&mut self) {
+        // CEF schedules the frames of a browser created without them
+        if !self.app.external_begin_frames {
+            return;
+        }
+        // A hidden page must not be driven, or CEF keeps compositing anyway
+        if !self.is_page_visible() {
+            return;
+        }
+
+        let paint_sequence = self
+            .app
+            .render_stats
+            .as_ref()
+            .map_or(0, |stats| stats.paint_sequence());
+        let max_fps = self.last_max_fps;
+        if !self
+            .begin_frame_scheduler
+            .should_begin_frame(Instant::now(), paint_sequence, max_fps)
+        {
+            return;
+        }
+
         if let Some(browser) = self.app.browser.as_mut()
             && let Some(host) = browser.host()
         {
@@ -389,3 +778,14 @@ impl CefTexture {
         self.base_mut().set_default_cursor_shape(shape);
     }
 }
+
+/// Tells the frame pacer Godot took the waiting frame, and asks CEF for a
+/// fresh paint if one was skipped meanwhile.
+fn frame_consumed(pacer: Option<&FramePacerHandle>, browser: Option<&cef::Browser>) {
+    if let Some(pacer) = pacer
+        && pacer.frame_consumed()
+        && let Some(host) = browser.and_then(|browser| browser.host())
+    {
+        host.invalidate(cef::PaintElementType::VIEW);
+    }
+}
@@ -0,0 +1,239 @@
+//! Session save and restore for CefTexture.
+//!
+//! `save_session` captures what brings the browser back where the user left
+//! it: the URL, the navigation history, the zoom level and the main-frame
+//! scroll position, which the page reports as it scrolls so it can be read
+//! without waiting for a script. `restore_session` loads the URL and scrolls
+//! once the page has finished loading. CEF cannot rebuild a navigation
+//! history, so restoring only loads the current entry. Cookies and
+//! localStorage are not part of a session; they persist in the cache path,
+//! and `export_cookies` carries cookies to another profile.
+//!
+//! The reported scroll position is also what `get_scroll_position` returns
+//! and `scrolled` carries, so reading it never waits for the page.
+
+use std::sync::{Arc, Mutex};
+
+use super::CefTexture;
+use cef::{ImplBrowser, ImplBrowserHost, ImplFrame};
+use godot::prelude::*;
+
+use crate::browser::{LoadingStateEvent, ScrollPosition};
+use crate::webrender::{NavigationEntryVisitorImpl, NavigationHistory};
+
+/// Version of the dictionaries returned by `save_session`.
+const SESSION_VERSION: i64 = 1;
+
+/// Frames the scroll restore is retried for while the page is too short to
+/// scroll to the position, e.g. while images are still loading.
+const SCROLL_RESTORE_FRAMES: u32 = 60;
+
+#[derive(Default)]
+pub(super) struct SessionState {
+    /// Last reported scroll position of the main frame.
+    scroll_position: ScrollPosition,
+    /// Scroll position to restore once the restored page has loaded.
+    pending_scroll: Option<(f64, f64)>,
+}
+
+/// Returns the script that scrolls the main frame to a restored position,
+/// or `None` if there is nothing to restore.
+fn scroll_restore_script(x: f64, y: f64) -> Option<String> {
+    if !x.is_finite() || !y.is_finite() {
+        return None;
+    }
+    let (x, y) = (x.max(0.0), y.max(0.0));
+    if x == 0.0 && y == 0.0 {
+        return None;
+    }
+    Some(format!(
+        "if (window.__restoreScrollPosition) window.__restoreScrollPosition({x}, {y}, {SCROLL_RESTORE_FRAMES});"
+    ))
+}
+
+/// Returns the script that scrolls the main frame to `(x, y)`, or `None`
+/// for a position that is not a number.
+fn scroll_to_script(x: f64, y: f64, smooth: bool) -> Option<String> {
+    if !x.is_finite() || !y.is_finite() {
+        return None;
+    }
+    let behavior = if smooth { "smooth" } else { "auto" };
+    Some(format!(
+        "window.scrollTo({{left: {}, top: {}, behavior: '{behavior}'}});",
+        x.max(0.0),
+        y.max(0.0)
+    ))
+}
+
+/// Reads a number from a session, which may have turned into a float or an
+/// int on its way through JSON or a resource file.
+fn session_number(session: &VarDictionary, key: &str) -> Option<f64> {
+    let value = session.get(key)?;
+    value
+        .try_to::<f64>()
+        .ok()
+        .or_else(|| value.try_to::<i64>().ok().map(|value| value as f64))
+}
+
+impl CefTexture {
+    pub(super) fn save_session_impl(&self) -> VarDictionary {
+        let url = self
+            .app
+            .browser
+            .as_ref()
+            .and_then(|browser| browser.main_frame())
+            .map(|frame| cef::CefStringUtf16::from(&frame.url()).to_string())
+            .filter(|url| !url.is_empty())
+            .unwrap_or_else(|| self.url.to_string());
+
+        let history = self.navigation_history();
+        let entries: Array<VarDictionary> = history
+            .entries
+            .iter()
+            .map(|entry| {
+                vdict! {
+                    "url": GString::from(entry.url.as_str()),
+                    "title": GString::from(entry.title.as_str()),
+                }
+            })
+            .collect();
+
+        let scroll = self.session.scroll_position;
+        vdict! {
+            "version": SESSION_VERSION,
+            "url": GString::from(url.as_str()),
+            "zoom_level": self.user_zoom_level,
+            "scroll_x": scroll.x,
+            "scroll_y": scroll.y,
+            "history": entries,
+            "history_index": history.current_index as i64,
+        }
+    }
+
+    pub(super) fn restore_session_impl(&mut self, session: &VarDictionary) -> VarDictionary {
+        let url = session
+            .get("url")
+            .and_then(|url| url.try_to::<GString>().ok())
+            .unwrap_or_default();
+        if url.is_empty() {
+            godot::global::godot_error!("[CefTexture] Cannot restore a session without a URL");
+            return VarDictionary::new();
+        }
+
+        if let Some(zoom_level) = session_number(session, "zoom_level") {
+            self.user_zoom_level = zoom_level;
+            self.apply_zoom();
+        }
+        let scroll = (
+            session_number(session, "scroll_x").unwrap_or(0.0),
+            session_number(session, "scroll_y").unwrap_or(0.0),
+        );
+        let scroll_pending = scroll_restore_script(scroll.0, scroll.1).is_some();
+        self.session.pending_scroll = scroll_pending.then_some(scroll);
+        self.set_url_property(url.clone());
+
+        vdict! {
+            "url": url,
+            "zoom_level": self.user_zoom_level,
+            "scroll_pending": scroll_pending,
+            // Only the current entry is loaded; back and forward start empty
+            "history_restored": false,
+        }
+    }
+
+    /// Tracks the scroll position reported by the page, and scrolls a
+    /// restored page once it has finished loading.
+    pub(super) fn update_session_state(
+        &mut self,
+        loading_states: &[LoadingStateEvent],
+        scroll_position: Option<ScrollPosition>,
+    ) {
+        for event in loading_states {
+            match event {
+                LoadingStateEvent::Started { is_main: true, .. } => {
+                    self.session.scroll_position = ScrollPosition::default();
+                }
+                LoadingStateEvent::Finished { is_main: true, .. } => {
+                    if let Some((x, y)) = self.session.pending_scroll.take()
+                        && let Some(script) = scroll_restore_script(x, y)
+                    {
+                        self.eval(GString::from(script.as_str()), false);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(position) = scroll_position {
+            self.session.scroll_position = position;
+        }
+    }
+
+    /// Returns the last scroll position the page reported.
+    pub(super) fn last_scroll_position(&self) -> ScrollPosition {
+        self.session.scroll_position
+    }
+
+    /// Scrolls the main frame; the page reports the new position once it
+    /// got there.
+    pub(super) fn scroll_main_frame_to(&mut self, x: f64, y: f64, smooth: bool) {
+        match scroll_to_script(x, y, smooth) {
+            Some(script) => self.eval(GString::from(script.as_str()), false),
+            None => {
+                godot::global::godot_error!("[CefTexture] Invalid scroll position ({}, {})", x, y)
+            }
+        }
+    }
+
+    fn navigation_history(&self) -> NavigationHistory {
+        let history = Arc::new(Mutex::new(NavigationHistory::default()));
+        if let Some(host) = self.app.browser.as_ref().and_then(|b| b.host()) {
+            // The visitor runs synchronously on the UI thread
+            let mut visitor = NavigationEntryVisitorImpl::build(history.clone());
+            host.navigation_entries(Some(&mut visitor), false as _);
+        }
+        history
+            .lock()
+            .map(|mut history| std::mem::take(&mut *history))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_restore_script() {
+        assert_eq!(scroll_restore_script(0.0, 0.0), None);
+        assert_eq!(scroll_restore_script(f64::NAN, 100.0), None);
+        assert_eq!(
+            scroll_restore_script(0.0, 1250.5),
+            Some(
+                "if (window.__restoreScrollPosition) window.__restoreScrollPosition(0, 1250.5, 60);"
+                    .to_string()
+            )
+        );
+        // Negative positions (overscroll) are clamped
+        assert_eq!(scroll_restore_script(-5.0, 0.0), None);
+        assert_eq!(
+            scroll_restore_script(-5.0, 10.0),
+            Some(
+                "if (window.__restoreScrollPosition) window.__restoreScrollPosition(0, 10, 60);"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_scroll_to_script() {
+        assert_eq!(
+            scroll_to_script(0.0, 480.5, false).as_deref(),
+            Some("window.scrollTo({left: 0, top: 480.5, behavior: 'auto'});")
+        );
+        assert_eq!(
+            scroll_to_script(-10.0, 20.0, true).as_deref(),
+            Some("window.scrollTo({left: 0, top: 20, behavior: 'smooth'});")
+        );
+        assert_eq!(scroll_to_script(f64::INFINITY, 0.0, false), None);
+    }
+}
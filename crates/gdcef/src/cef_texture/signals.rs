@@ -3,9 +3,12 @@
 //! This module handles draining event queues and emitting Godot signals.
 
 use super::CefTexture;
+use cef_app::TextInputType;
 use godot::prelude::*;
 
-use crate::browser::{DragEvent, EventQueues, LoadingStateEvent};
+use crate::browser::{
+    DragEvent, EventKind, EventQueues, LoadingStateEvent, RendererProcessEvent, emission_order,
+};
 use crate::drag::DragDataInfo;
 
 #[derive(GodotClass)]
@@ -140,15 +143,40 @@ impl DownloadUpdateInfo {
 pub(super) struct DrainedEvents {
     pub messages: Vec<String>,
     pub binary_messages: Vec<Vec<u8>>,
-    pub url_changes: Vec<String>,
+    pub url_changes: Vec<crate::browser::UrlChangeEvent>,
     pub title_changes: Vec<String>,
     pub loading_states: Vec<LoadingStateEvent>,
-    pub ime_enables: Vec<bool>,
+    pub loading_state_changes: Vec<crate::browser::LoadingStateChangeEvent>,
+    pub ime_enables: Vec<Option<TextInputType>>,
     pub ime_composition_range: Option<crate::browser::ImeCompositionRange>,
+    pub loading_progress: Option<f64>,
+    pub scroll_position: Option<crate::browser::ScrollPosition>,
+    pub renderer_process: Option<crate::browser::RendererProcessEvent>,
     pub console_messages: Vec<crate::browser::ConsoleMessageEvent>,
     pub drag_events: Vec<DragEvent>,
     pub download_requests: Vec<crate::browser::DownloadRequestEvent>,
     pub download_updates: Vec<crate::browser::DownloadUpdateEvent>,
+    pub scheme_requests: Vec<crate::browser::SchemeRequestEvent>,
+    pub security_states: Vec<crate::browser::SecurityStateEvent>,
+    pub certificate_errors: Vec<crate::browser::CertificateErrorEvent>,
+    pub client_certificates: Vec<crate::browser::ClientCertificateEvent>,
+    pub navigation_blocked: Vec<crate::browser::NavigationBlockedEvent>,
+    pub external_protocols: Vec<String>,
+    pub devtools_results: Vec<crate::browser::DevToolsResultEvent>,
+    pub devtools_events: Vec<crate::browser::DevToolsEvent>,
+    pub accessibility_events: Vec<crate::accessibility::AccessibilityEvent>,
+    pub display_media_events: Vec<crate::browser::DisplayMediaEvent>,
+    pub pages_saved: Vec<crate::browser::PageSavedEvent>,
+    pub first_paints: Vec<crate::browser::FirstPaintEvent>,
+    pub dom_queries: Vec<crate::browser::DomQueryEvent>,
+    pub web_notifications: Vec<crate::browser::WebNotificationEvent>,
+    pub browser_shortcuts: Vec<String>,
+    pub browser_gpu_info: Vec<crate::browser::DevToolsResultEvent>,
+    pub cookies_exported: Vec<Vec<crate::cookies::CookieRecord>>,
+    pub cookies_imported: Vec<crate::browser::CookiesImportedEvent>,
+    pub event_order: Vec<EventKind>,
+    /// IPC messages dropped since the last drain because the queue was full.
+    pub dropped_ipc_messages: u64,
 }
 
 impl DrainedEvents {
@@ -160,12 +188,70 @@ impl DrainedEvents {
             url_changes: queues.url_changes.drain(..).collect(),
             title_changes: queues.title_changes.drain(..).collect(),
             loading_states: queues.loading_states.drain(..).collect(),
+            loading_state_changes: queues.loading_state_changes.drain(..).collect(),
             ime_enables: queues.ime_enables.drain(..).collect(),
             ime_composition_range: queues.ime_composition_range.take(),
+            loading_progress: queues.loading_progress.take(),
+            scroll_position: queues.scroll_position.take(),
+            renderer_process: queues.renderer_process.take(),
             console_messages: queues.console_messages.drain(..).collect(),
             drag_events: queues.drag_events.drain(..).collect(),
             download_requests: queues.download_requests.drain(..).collect(),
             download_updates: queues.download_updates.drain(..).collect(),
+            scheme_requests: queues.scheme_requests.drain(..).collect(),
+            security_states: queues.security_states.drain(..).collect(),
+            certificate_errors: queues.certificate_errors.drain(..).collect(),
+            client_certificates: queues.client_certificates.drain(..).collect(),
+            navigation_blocked: queues.navigation_blocked.drain(..).collect(),
+            external_protocols: queues.external_protocols.drain(..).collect(),
+            devtools_results: queues.devtools_results.drain(..).collect(),
+            devtools_events: queues.devtools_events.drain(..).collect(),
+            accessibility_events: queues.accessibility_events.drain(..).collect(),
+            display_media_events: queues.display_media_events.drain(..).collect(),
+            pages_saved: queues.pages_saved.drain(..).collect(),
+            first_paints: queues.first_paints.drain(..).collect(),
+            dom_queries: queues.dom_queries.drain(..).collect(),
+            web_notifications: queues.web_notifications.drain(..).collect(),
+            browser_shortcuts: queues.browser_shortcuts.drain(..).collect(),
+            browser_gpu_info: queues.browser_gpu_info.drain(..).collect(),
+            cookies_exported: queues.cookies_exported.drain(..).collect(),
+            cookies_imported: queues.cookies_imported.drain(..).collect(),
+            event_order: queues.drain_event_order(),
+            dropped_ipc_messages: queues.messages.take_new_overflows()
+                + queues.binary_messages.take_new_overflows(),
+        }
+    }
+
+    /// Returns the number of drained events of `kind`.
+    fn count(&self, kind: EventKind) -> usize {
+        match kind {
+            EventKind::Message => self.messages.len(),
+            EventKind::BinaryMessage => self.binary_messages.len(),
+            EventKind::UrlChange => self.url_changes.len(),
+            EventKind::SecurityState => self.security_states.len(),
+            EventKind::CertificateError => self.certificate_errors.len(),
+            EventKind::ClientCertificate => self.client_certificates.len(),
+            EventKind::NavigationBlocked => self.navigation_blocked.len(),
+            EventKind::ExternalProtocol => self.external_protocols.len(),
+            EventKind::TitleChange => self.title_changes.len(),
+            EventKind::LoadingState => self.loading_states.len(),
+            EventKind::LoadingStateChange => self.loading_state_changes.len(),
+            EventKind::ConsoleMessage => self.console_messages.len(),
+            EventKind::Drag => self.drag_events.len(),
+            EventKind::DownloadRequest => self.download_requests.len(),
+            EventKind::DownloadUpdate => self.download_updates.len(),
+            EventKind::SchemeRequest => self.scheme_requests.len(),
+            EventKind::DevToolsResult => self.devtools_results.len(),
+            EventKind::DevToolsEvent => self.devtools_events.len(),
+            EventKind::DisplayMedia => self.display_media_events.len(),
+            EventKind::PageSaved => self.pages_saved.len(),
+            EventKind::FirstPaint => self.first_paints.len(),
+            EventKind::DomQuery => self.dom_queries.len(),
+            EventKind::WebNotification => self.web_notifications.len(),
+            EventKind::BrowserShortcut => self.browser_shortcuts.len(),
+            EventKind::BrowserGpuInfo => self.browser_gpu_info.len(),
+            EventKind::CookiesExported => self.cookies_exported.len(),
+            EventKind::CookiesImported => self.cookies_imported.len(),
         }
     }
 }
@@ -186,16 +272,126 @@ impl CefTexture {
             DrainedEvents::drain_from(&mut queues)
         };
 
-        // Now process events without holding the lock
-        self.emit_message_signals(&events.messages);
-        self.emit_binary_message_signals(&events.binary_messages);
-        self.emit_url_change_signals(&events.url_changes);
-        self.emit_title_change_signals(&events.title_changes);
-        self.emit_loading_state_signals(&events.loading_states);
-        self.emit_console_message_signals(&events.console_messages);
-        self.emit_drag_event_signals(&events.drag_events);
-        self.emit_download_request_signals(&events.download_requests);
-        self.emit_download_update_signals(&events.download_updates);
+        if events.dropped_ipc_messages > 0 {
+            godot::global::godot_warn!(
+                "[CefTexture] Dropped {} IPC messages from the page: the queue is full, see godot_cef/queues/ipc_capacity",
+                events.dropped_ipc_messages
+            );
+        }
+
+        // Re-apply the emulation before the new page can ask for its position
+        if events
+            .loading_states
+            .iter()
+            .any(|event| matches!(event, LoadingStateEvent::Started { is_main: true, .. }))
+        {
+            self.apply_emulation_overrides();
+            self.abort_paginated_capture("the page navigated", false);
+        }
+        // Chromium keeps zoom per host, so a new host starts at its own level
+        if events.loading_states.iter().any(|event| {
+            matches!(
+                event,
+                LoadingStateEvent::Started { is_main: true, .. }
+                    | LoadingStateEvent::Finished { is_main: true, .. }
+            )
+        }) {
+            self.apply_zoom();
+        }
+        self.update_session_state(&events.loading_states, events.scroll_position);
+        self.update_load_timeout(
+            &events.loading_states,
+            &events.loading_state_changes,
+            events.loading_progress,
+        );
+        match events.renderer_process {
+            Some(RendererProcessEvent::Started(pid)) => self.app.renderer_process_id = Some(pid),
+            Some(RendererProcessEvent::Terminated) => self.app.renderer_process_id = None,
+            None => {}
+        }
+
+        // Now process events without holding the lock, in arrival order
+        let mut next = [0; EventKind::ALL.len()];
+        for kind in emission_order(&events.event_order, |kind| events.count(kind)) {
+            let index = next[kind as usize];
+            next[kind as usize] += 1;
+            match kind {
+                EventKind::Message => self.emit_message_signal(&events.messages[index]),
+                EventKind::BinaryMessage => {
+                    self.emit_binary_message_signal(&events.binary_messages[index])
+                }
+                EventKind::UrlChange => self.emit_url_change_signal(&events.url_changes[index]),
+                EventKind::SecurityState => {
+                    self.emit_security_state_signal(&events.security_states[index])
+                }
+                EventKind::CertificateError => {
+                    self.emit_certificate_error_signal(&events.certificate_errors[index])
+                }
+                EventKind::ClientCertificate => {
+                    self.emit_client_certificate_signal(&events.client_certificates[index])
+                }
+                EventKind::NavigationBlocked => {
+                    self.emit_navigation_blocked_signal(&events.navigation_blocked[index])
+                }
+                EventKind::ExternalProtocol => {
+                    self.emit_external_protocol_signal(&events.external_protocols[index])
+                }
+                EventKind::TitleChange => {
+                    self.emit_title_change_signal(&events.title_changes[index])
+                }
+                EventKind::LoadingState => {
+                    self.emit_loading_state_signal(&events.loading_states[index])
+                }
+                EventKind::LoadingStateChange => {
+                    self.emit_loading_state_change_signal(&events.loading_state_changes[index])
+                }
+                EventKind::ConsoleMessage => {
+                    self.emit_console_message_signal(&events.console_messages[index])
+                }
+                EventKind::Drag => self.emit_drag_event_signal(&events.drag_events[index]),
+                EventKind::DownloadRequest => {
+                    self.emit_download_request_signal(&events.download_requests[index])
+                }
+                EventKind::DownloadUpdate => {
+                    self.emit_download_update_signal(&events.download_updates[index])
+                }
+                EventKind::SchemeRequest => {
+                    self.emit_scheme_request_signal(&events.scheme_requests[index])
+                }
+                EventKind::DevToolsResult => {
+                    self.emit_devtools_result_signal(&events.devtools_results[index])
+                }
+                EventKind::DevToolsEvent => {
+                    self.emit_devtools_event_signal(&events.devtools_events[index])
+                }
+                EventKind::DisplayMedia => {
+                    self.process_display_media_event(&events.display_media_events[index])
+                }
+                EventKind::PageSaved => self.emit_page_saved_signal(&events.pages_saved[index]),
+                EventKind::FirstPaint => self.emit_first_paint_signal(&events.first_paints[index]),
+                EventKind::DomQuery => self.emit_dom_query_signal(&events.dom_queries[index]),
+                EventKind::WebNotification => {
+                    self.process_web_notification_event(&events.web_notifications[index])
+                }
+                EventKind::BrowserShortcut => {
+                    self.emit_browser_shortcut_signal(&events.browser_shortcuts[index])
+                }
+                EventKind::BrowserGpuInfo => {
+                    self.handle_browser_gpu_info(&events.browser_gpu_info[index])
+                }
+                EventKind::CookiesExported => {
+                    self.emit_cookies_exported_signal(&events.cookies_exported[index])
+                }
+                EventKind::CookiesImported => {
+                    self.emit_cookies_imported_signal(&events.cookies_imported[index])
+                }
+            }
+        }
+        self.process_accessibility_events(&events.accessibility_events);
+        // The page reports at most once per frame, and only the latest is kept
+        if let Some(scroll) = events.scroll_position {
+            self.emit_scrolled_signal(scroll);
+        }
 
         // Handle IME events (these may modify self state)
         self.process_ime_enable_events(&events.ime_enables);
@@ -204,157 +400,436 @@ impl CefTexture {
         }
     }
 
-    fn emit_message_signals(&mut self, messages: &[String]) {
-        for message in messages {
-            self.base_mut()
-                .emit_signal("ipc_message", &[GString::from(message).to_variant()]);
+    /// Emits `signal` with `args`, unless `use_unified_events` is set, and
+    /// `browser_event` with the same arguments keyed by parameter name.
+    /// `painted` does not go through here and is emitted either way.
+    pub(super) fn emit_browser_signal(&mut self, signal: &str, args: &[(&str, Variant)]) {
+        if !self.use_unified_events {
+            let values: Vec<Variant> = args.iter().map(|(_, value)| value.clone()).collect();
+            self.base_mut().emit_signal(signal, &values);
         }
-    }
 
-    fn emit_binary_message_signals(&mut self, messages: &[Vec<u8>]) {
-        for data in messages {
-            let byte_array = PackedByteArray::from(data.as_slice());
-            self.base_mut()
-                .emit_signal("ipc_binary_message", &[byte_array.to_variant()]);
+        let mut data = VarDictionary::new();
+        for (key, value) in args {
+            data.set(*key, value.clone());
         }
+        self.base_mut().emit_signal(
+            "browser_event",
+            &[StringName::from(signal).to_variant(), data.to_variant()],
+        );
+    }
+
+    fn emit_message_signal(&mut self, message: &str) {
+        self.emit_browser_signal(
+            "ipc_message",
+            &[("message", GString::from(message).to_variant())],
+        );
+    }
+
+    fn emit_binary_message_signal(&mut self, data: &[u8]) {
+        let byte_array = PackedByteArray::from(data);
+        self.emit_browser_signal("ipc_binary_message", &[("data", byte_array.to_variant())]);
+    }
+
+    fn emit_url_change_signal(&mut self, event: &crate::browser::UrlChangeEvent) {
+        self.sync_url_property(event);
+        self.emit_browser_signal(
+            "url_changed",
+            &[("url", GString::from(event.url.as_str()).to_variant())],
+        );
+    }
+
+    fn emit_security_state_signal(&mut self, event: &crate::browser::SecurityStateEvent) {
+        let certificate = event.certificate.clone().unwrap_or_default();
+        let info = vdict! {
+            "url": GString::from(&event.url),
+            "applicable": event.applicable,
+            "is_secure_connection": event.is_secure_connection,
+            "cert_status": event.cert_status as i64,
+            "has_certificate": event.certificate.is_some(),
+            "subject": GString::from(&certificate.subject),
+            "issuer": GString::from(&certificate.issuer),
+            "valid_from": certificate.valid_from,
+            "valid_until": certificate.valid_until,
+            "fingerprint_sha256": GString::from(&certificate.fingerprint_sha256),
+        };
+        self.emit_browser_signal("security_state_changed", &[("info", info.to_variant())]);
+    }
+
+    fn emit_certificate_error_signal(&mut self, event: &crate::browser::CertificateErrorEvent) {
+        let cert_info = vdict! {
+            "error_code": event.error_code,
+            "subject": GString::from(&event.certificate.subject),
+            "issuer": GString::from(&event.certificate.issuer),
+            "valid_from": event.certificate.valid_from,
+            "valid_until": event.certificate.valid_until,
+            "fingerprint_sha256": GString::from(&event.certificate.fingerprint_sha256),
+        };
+        self.emit_browser_signal(
+            "certificate_error",
+            &[
+                ("error_id", event.error_id.to_variant()),
+                ("url", GString::from(&event.url).to_variant()),
+                ("status", (event.cert_status as i64).to_variant()),
+                ("cert_info", cert_info.to_variant()),
+            ],
+        );
     }
 
-    fn emit_url_change_signals(&mut self, urls: &[String]) {
-        for url in urls {
-            self.base_mut()
-                .emit_signal("url_changed", &[GString::from(url).to_variant()]);
+    fn emit_client_certificate_signal(&mut self, event: &crate::browser::ClientCertificateEvent) {
+        let certificates: Array<VarDictionary> = event
+            .certificates
+            .iter()
+            .map(|certificate| {
+                vdict! {
+                    "subject": GString::from(&certificate.subject),
+                    "issuer": GString::from(&certificate.issuer),
+                    "valid_from": certificate.valid_from,
+                    "valid_until": certificate.valid_until,
+                    "fingerprint_sha256": GString::from(&certificate.fingerprint_sha256),
+                }
+            })
+            .collect();
+        self.emit_browser_signal(
+            "client_certificate_requested",
+            &[
+                ("request_id", event.request_id.to_variant()),
+                ("host", GString::from(&event.host).to_variant()),
+                ("certificates", certificates.to_variant()),
+            ],
+        );
+    }
+
+    fn emit_navigation_blocked_signal(&mut self, event: &crate::browser::NavigationBlockedEvent) {
+        self.emit_browser_signal(
+            "navigation_blocked",
+            &[
+                ("url", GString::from(&event.url).to_variant()),
+                ("user_gesture", event.user_gesture.to_variant()),
+            ],
+        );
+    }
+
+    fn emit_devtools_result_signal(&mut self, event: &crate::browser::DevToolsResultEvent) {
+        if self.handle_user_script_result(event) || self.handle_user_gesture_result(event) {
+            return;
         }
+        let result = super::devtools::parse_json_dictionary(&event.result);
+        self.emit_browser_signal(
+            "devtools_result",
+            &[
+                ("id", (event.message_id as i64).to_variant()),
+                ("success", event.success.to_variant()),
+                ("result", result.to_variant()),
+            ],
+        );
     }
 
-    fn emit_title_change_signals(&mut self, titles: &[String]) {
-        for title in titles {
-            self.base_mut()
-                .emit_signal("title_changed", &[GString::from(title).to_variant()]);
+    fn emit_scrolled_signal(&mut self, scroll: crate::browser::ScrollPosition) {
+        self.emit_browser_signal(
+            "scrolled",
+            &[
+                (
+                    "position",
+                    Vector2::new(scroll.x as f32, scroll.y as f32).to_variant(),
+                ),
+                (
+                    "max",
+                    Vector2::new(scroll.max_x as f32, scroll.max_y as f32).to_variant(),
+                ),
+            ],
+        );
+    }
+
+    fn emit_first_paint_signal(&mut self, event: &crate::browser::FirstPaintEvent) {
+        self.page_painted = true;
+        self.emit_browser_signal(
+            "first_contentful_paint",
+            &[
+                ("url", GString::from(&event.url).to_variant()),
+                ("time_ms", event.time_ms.to_variant()),
+            ],
+        );
+        self.base_mut().emit_signal("painted", &[]);
+    }
+
+    fn emit_dom_query_signal(&mut self, event: &crate::browser::DomQueryEvent) {
+        match event {
+            crate::browser::DomQueryEvent::Dumped { selector, html } => self.emit_browser_signal(
+                "dom_dumped",
+                &[
+                    ("selector", GString::from(selector).to_variant()),
+                    ("html", GString::from(html).to_variant()),
+                ],
+            ),
+            crate::browser::DomQueryEvent::Counted { selector, count } => self.emit_browser_signal(
+                "elements_counted",
+                &[
+                    ("selector", GString::from(selector).to_variant()),
+                    ("count", count.to_variant()),
+                ],
+            ),
         }
     }
 
-    fn emit_loading_state_signals(&mut self, events: &[LoadingStateEvent]) {
-        for event in events {
-            match event {
-                LoadingStateEvent::Started { url } => {
-                    self.base_mut()
-                        .emit_signal("load_started", &[GString::from(url).to_variant()]);
-                }
-                LoadingStateEvent::Finished {
-                    url,
-                    http_status_code,
-                } => {
-                    self.base_mut().emit_signal(
-                        "load_finished",
-                        &[
-                            GString::from(url).to_variant(),
-                            http_status_code.to_variant(),
-                        ],
-                    );
-                }
-                LoadingStateEvent::Error {
+    fn emit_page_saved_signal(&mut self, event: &crate::browser::PageSavedEvent) {
+        self.emit_browser_signal(
+            "page_saved",
+            &[
+                ("path", GString::from(&event.path).to_variant()),
+                ("ok", event.ok.to_variant()),
+            ],
+        );
+    }
+
+    fn emit_devtools_event_signal(&mut self, event: &crate::browser::DevToolsEvent) {
+        let params = super::devtools::parse_json_dictionary(&event.params);
+        self.emit_browser_signal(
+            "devtools_event",
+            &[
+                ("method", GString::from(&event.method).to_variant()),
+                ("params", params.to_variant()),
+            ],
+        );
+    }
+
+    fn emit_browser_shortcut_signal(&mut self, action_name: &str) {
+        self.emit_browser_signal(
+            "browser_shortcut",
+            &[("action_name", StringName::from(action_name).to_variant())],
+        );
+    }
+
+    fn emit_external_protocol_signal(&mut self, url: &str) {
+        self.emit_browser_signal(
+            "external_protocol_requested",
+            &[("url", GString::from(url).to_variant())],
+        );
+
+        if !self.auto_open_external_protocols {
+            return;
+        }
+        let should_open = crate::security::external_protocol_scheme(url)
+            .is_some_and(|scheme| crate::settings::get_auto_open_protocols().contains(&scheme));
+        if should_open {
+            let error = godot::classes::Os::singleton().shell_open(url);
+            if error != godot::global::Error::OK {
+                godot::global::godot_warn!(
+                    "[CefTexture] Failed to open external protocol URL {}: {:?}",
                     url,
-                    error_code,
-                    error_text,
-                } => {
-                    self.base_mut().emit_signal(
-                        "load_error",
-                        &[
-                            GString::from(url).to_variant(),
-                            error_code.to_variant(),
-                            GString::from(error_text).to_variant(),
-                        ],
-                    );
-                }
+                    error
+                );
             }
         }
     }
 
-    fn emit_console_message_signals(&mut self, events: &[crate::browser::ConsoleMessageEvent]) {
-        for event in events {
-            self.base_mut().emit_signal(
-                "console_message",
-                &[
-                    event.level.to_variant(),
-                    GString::from(&event.message).to_variant(),
-                    GString::from(&event.source).to_variant(),
-                    event.line.to_variant(),
+    fn emit_title_change_signal(&mut self, title: &str) {
+        self.emit_browser_signal(
+            "title_changed",
+            &[("title", GString::from(title).to_variant())],
+        );
+    }
+
+    fn emit_loading_state_signal(&mut self, event: &LoadingStateEvent) {
+        if let LoadingStateEvent::Error {
+            url,
+            error_code,
+            error_text,
+            is_main: true,
+            ..
+        } = event
+        {
+            self.record_debug_error(url, *error_code, error_text);
+        }
+        if let LoadingStateEvent::Started { is_main: true, .. } = event {
+            self.page_painted = false;
+        }
+        if !event.is_main() && !self.include_subframe_loads {
+            return;
+        }
+
+        let (signal, mut args) = match event {
+            LoadingStateEvent::Started { url, .. } => (
+                "load_started",
+                vec![("url", GString::from(url).to_variant())],
+            ),
+            LoadingStateEvent::Finished {
+                url,
+                http_status_code,
+                ..
+            } => (
+                "load_finished",
+                vec![
+                    ("url", GString::from(url).to_variant()),
+                    ("http_status_code", http_status_code.to_variant()),
                 ],
-            );
+            ),
+            LoadingStateEvent::Error {
+                url,
+                error_code,
+                error_text,
+                ..
+            } => (
+                "load_error",
+                vec![
+                    ("url", GString::from(url).to_variant()),
+                    ("error_code", error_code.to_variant()),
+                    ("error_text", GString::from(error_text).to_variant()),
+                ],
+            ),
+        };
+
+        if event.is_main() {
+            self.emit_browser_signal(signal, &args);
+            return;
         }
+        // Iframes get their own signals, so the main-frame ones keep their
+        // signatures
+        args.push(("frame_name", GString::from(event.frame_name()).to_variant()));
+        self.emit_browser_signal(&format!("subframe_{signal}"), &args);
+    }
+
+    fn emit_loading_state_change_signal(
+        &mut self,
+        event: &crate::browser::LoadingStateChangeEvent,
+    ) {
+        self.emit_browser_signal(
+            "loading_state_changed",
+            &[
+                ("is_loading", event.is_loading.to_variant()),
+                ("can_go_back", event.can_go_back.to_variant()),
+                ("can_go_forward", event.can_go_forward.to_variant()),
+            ],
+        );
+    }
+
+    fn emit_console_message_signal(&mut self, event: &crate::browser::ConsoleMessageEvent) {
+        self.emit_browser_signal(
+            "console_message",
+            &[
+                ("level", event.level.to_variant()),
+                ("message", GString::from(&event.message).to_variant()),
+                ("source", GString::from(&event.source).to_variant()),
+                ("line", event.line.to_variant()),
+            ],
+        );
     }
 
-    fn emit_drag_event_signals(&mut self, events: &[DragEvent]) {
-        for event in events {
-            match event {
-                DragEvent::Started {
-                    drag_data,
-                    x,
-                    y,
-                    allowed_ops,
-                } => {
-                    let drag_info = DragDataInfo::from_internal(drag_data);
-                    let position = Vector2::new(*x as f32, *y as f32);
-                    self.base_mut().emit_signal(
-                        "drag_started",
-                        &[
-                            drag_info.to_variant(),
-                            position.to_variant(),
-                            (*allowed_ops as i32).to_variant(),
-                        ],
+    fn emit_drag_event_signal(&mut self, event: &DragEvent) {
+        match event {
+            DragEvent::Started {
+                drag_data,
+                x,
+                y,
+                allowed_ops,
+                file_source,
+            } => {
+                let drag_info = DragDataInfo::from_internal(drag_data);
+                let position = Vector2::new(*x as f32, *y as f32);
+                self.emit_browser_signal(
+                    "drag_started",
+                    &[
+                        ("drag_data", drag_info.to_variant()),
+                        ("position", position.to_variant()),
+                        ("allowed_ops", (*allowed_ops as i32).to_variant()),
+                    ],
+                );
+                self.app.drag_state.is_dragging_from_browser = true;
+                self.app.drag_state.allowed_ops = *allowed_ops;
+                if let Some(source) = file_source {
+                    self.start_drag_file_extraction(
+                        &drag_info,
+                        source.clone(),
+                        drag_data.file_names.clone(),
                     );
-                    self.app.drag_state.is_dragging_from_browser = true;
-                    self.app.drag_state.allowed_ops = *allowed_ops;
-                }
-                DragEvent::UpdateCursor { operation } => {
-                    self.base_mut()
-                        .emit_signal("drag_cursor_updated", &[(*operation as i32).to_variant()]);
                 }
-                DragEvent::Entered { drag_data, mask } => {
-                    let drag_info = DragDataInfo::from_internal(drag_data);
-                    self.base_mut().emit_signal(
-                        "drag_entered",
-                        &[drag_info.to_variant(), (*mask as i32).to_variant()],
-                    );
-                    self.app.drag_state.is_drag_over = true;
+                if self.native_drag_integration {
+                    self.begin_native_drag(&drag_info, drag_data, *allowed_ops);
                 }
             }
+            DragEvent::UpdateCursor { operation } => {
+                self.set_native_drag_cursor(*operation);
+                self.emit_browser_signal(
+                    "drag_cursor_updated",
+                    &[("operation", (*operation as i32).to_variant())],
+                );
+            }
+            DragEvent::Entered { drag_data, mask } => {
+                let drag_info = DragDataInfo::from_internal(drag_data);
+                self.emit_browser_signal(
+                    "drag_entered",
+                    &[
+                        ("drag_data", drag_info.to_variant()),
+                        ("mask", (*mask as i32).to_variant()),
+                    ],
+                );
+                self.app.drag_state.is_drag_over = true;
+            }
+            DragEvent::FilesReady { id, files } => {
+                self.finish_drag_file_extraction(*id, files);
+            }
         }
     }
 
-    fn emit_download_request_signals(&mut self, events: &[crate::browser::DownloadRequestEvent]) {
-        for event in events {
-            let download_info = DownloadRequestInfo::from_event(event);
-            self.base_mut()
-                .emit_signal("download_requested", &[download_info.to_variant()]);
-        }
+    fn emit_download_request_signal(&mut self, event: &crate::browser::DownloadRequestEvent) {
+        let download_info = DownloadRequestInfo::from_event(event);
+        self.emit_browser_signal(
+            "download_requested",
+            &[("download_info", download_info.to_variant())],
+        );
     }
 
-    fn emit_download_update_signals(&mut self, events: &[crate::browser::DownloadUpdateEvent]) {
-        for event in events {
-            let download_info = DownloadUpdateInfo::from_event(event);
-            self.base_mut()
-                .emit_signal("download_updated", &[download_info.to_variant()]);
+    fn emit_download_update_signal(&mut self, event: &crate::browser::DownloadUpdateEvent) {
+        let download_info = DownloadUpdateInfo::from_event(event);
+        self.emit_browser_signal(
+            "download_updated",
+            &[("download_info", download_info.to_variant())],
+        );
+    }
+
+    fn emit_scheme_request_signal(&mut self, event: &crate::browser::SchemeRequestEvent) {
+        if godot::classes::Os::singleton().is_stdout_verbose() {
+            godot::global::godot_print!(
+                "[CefTexture] {} {} -> {} ({} bytes, {:.2} ms)",
+                event.method,
+                event.url,
+                event.status_code,
+                event.bytes_served,
+                event.duration_ms
+            );
         }
+
+        let info = vdict! {
+            "url": GString::from(&event.url),
+            "method": GString::from(&event.method),
+            "resolved_path": GString::from(&event.resolved_path),
+            "status_code": event.status_code,
+            "bytes_served": event.bytes_served as i64,
+            "duration_ms": event.duration_ms,
+            "is_range": event.is_range,
+            "is_multipart": event.is_multipart,
+        };
+        self.emit_browser_signal("scheme_request_completed", &[("info", info.to_variant())]);
     }
 
-    fn process_ime_enable_events(&mut self, events: &[bool]) {
+    fn process_ime_enable_events(&mut self, events: &[Option<TextInputType>]) {
         // Take the last event (latest wins)
-        if let Some(&enable) = events.last() {
-            if enable && !self.ime_active {
-                self.activate_ime();
-            } else if !enable && self.ime_active {
-                self.deactivate_ime();
+        match events.last() {
+            Some(&Some(input_type)) => {
+                // Focus can move between fields without disabling IME
+                self.set_ime_input_type(input_type);
+                if !self.ime_active {
+                    self.activate_ime();
+                }
             }
+            Some(None) if self.ime_active => self.deactivate_ime(),
+            _ => {}
         }
     }
 
     fn process_ime_composition_event(&mut self, range: crate::browser::ImeCompositionRange) {
         if self.ime_active {
-            // Directly assign to ime_position field instead of using setter
-            // to avoid conflict with GodotClass-generated setter
-            self.ime_position = Vector2i::new(range.caret_x, range.caret_y + range.caret_height);
-            self.process_ime_position();
+            self.set_ime_caret(range);
         }
     }
 }
@@ -0,0 +1,148 @@
+//! Scripts run as if the user had clicked: `eval_with_user_gesture` and
+//! `send_ipc_message` with `user_gesture`.
+//!
+//! Clipboard writes, fullscreen, popups and audio playback need transient
+//! user activation, which `Frame::ExecuteJavaScript` never grants. CEF has
+//! no flag for it, and a synthesized key event would also reach the focused
+//! element. DevTools' `Runtime.evaluate` with `userGesture` grants
+//! activation to the main frame before running the script, without input.
+//! Activation lasts a few seconds, so code the script schedules can use it
+//! too. Plain `eval` and IPC are left without activation.
+//!
+//! The results only arrive with `allow_devtools_protocol`, which is when
+//! exceptions are logged; they are never emitted as `devtools_result`.
+
+use super::CefTexture;
+use super::devtools::parse_json_dictionary;
+use crate::browser::DevToolsResultEvent;
+use godot::classes::Json;
+use godot::prelude::*;
+use std::collections::HashSet;
+
+/// Returns `value` as a JavaScript string literal.
+fn js_string_literal(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if (c as u32) < 0x20 || c == '\u{2028}' || c == '\u{2029}' => {
+                literal.push_str(&format!("\\u{:04x}", c as u32));
+            }
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Returns the script that hands `message` to `window.onIpcMessage`.
+/// Unlike process messages it is not buffered until the page installs one.
+fn ipc_message_source(message: &str) -> String {
+    format!(
+        "if (typeof window.onIpcMessage === \"function\") {{ window.onIpcMessage({}); }}",
+        js_string_literal(message)
+    )
+}
+
+/// Message ids of the `Runtime.evaluate` calls sent by this module.
+#[derive(Default)]
+pub(super) struct UserGestureEvals {
+    pending: HashSet<i64>,
+}
+
+impl CefTexture {
+    /// Runs `source` in the main frame with transient user activation.
+    /// Returns `false` if it could not be sent.
+    pub(super) fn eval_with_user_gesture_impl(&mut self, source: &str) -> bool {
+        let mut params = VarDictionary::new();
+        params.set("expression", source);
+        params.set("userGesture", true);
+        let params = Json::stringify(&params.to_variant()).to_string();
+        let message_id = self.execute_devtools_method("Runtime.evaluate", &params);
+        if message_id < 0 {
+            return false;
+        }
+        // Without the protocol the result never arrives to be forgotten
+        if crate::settings::is_devtools_protocol_allowed() {
+            self.user_gesture_evals.pending.insert(message_id);
+        }
+        true
+    }
+
+    /// Delivers `message` to `window.onIpcMessage` with transient user
+    /// activation.
+    pub(super) fn send_ipc_message_with_user_gesture(&mut self, message: &str) -> bool {
+        self.eval_with_user_gesture_impl(&ipc_message_source(message))
+    }
+
+    /// Handles the result of an evaluation sent by this module. Returns
+    /// `false` for other messages.
+    pub(super) fn handle_user_gesture_result(&mut self, event: &DevToolsResultEvent) -> bool {
+        if !self
+            .user_gesture_evals
+            .pending
+            .remove(&(event.message_id as i64))
+        {
+            return false;
+        }
+        let result = parse_json_dictionary(&event.result);
+        if !event.success {
+            godot::global::godot_error!(
+                "[CefTexture] Failed to run script with user gesture: {}",
+                event.result
+            );
+        } else if let Some(details) = result
+            .get("exceptionDetails")
+            .and_then(|details| details.try_to::<VarDictionary>().ok())
+        {
+            // `text` is only "Uncaught", the exception says what went wrong
+            let exception = details
+                .get("exception")
+                .and_then(|exception| exception.try_to::<VarDictionary>().ok())
+                .and_then(|exception| exception.get("description"))
+                .unwrap_or_else(|| details.get("text").unwrap_or_default());
+            godot::global::godot_error!(
+                "[CefTexture] Script with user gesture threw: {}",
+                exception
+            );
+        }
+        true
+    }
+
+    /// Forgets the evaluations of a released browser, whose results never
+    /// arrive.
+    pub(super) fn release_user_gesture_evals(&mut self) {
+        self.user_gesture_evals = UserGestureEvals::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_js_string_literal() {
+        assert_eq!(js_string_literal("hello"), r#""hello""#);
+        assert_eq!(js_string_literal(r#"say "hi""#), r#""say \"hi\"""#);
+        assert_eq!(js_string_literal(r"C:\path"), r#""C:\\path""#);
+        assert_eq!(js_string_literal("a\nb\tc\r"), r#""a\nb\tc\r""#);
+        assert_eq!(
+            js_string_literal("\u{0}\u{2028}\u{2029}"),
+            r#""\u0000\u2028\u2029""#
+        );
+        assert_eq!(js_string_literal("日本語"), "\"日本語\"");
+    }
+
+    #[test]
+    fn test_ipc_message_source() {
+        assert_eq!(
+            ipc_message_source("\"); alert(1); (\""),
+            r#"if (typeof window.onIpcMessage === "function") { window.onIpcMessage("\"); alert(1); (\""); }"#
+        );
+    }
+}
@@ -0,0 +1,365 @@
+//! User scripts for CefTexture.
+//!
+//! Scripts added with `add_user_script` run in every document the browser
+//! loads, including iframes. They are registered with the DevTools protocol
+//! (`Page.addScriptToEvaluateOnNewDocument`), so they survive navigations
+//! and renderer process swaps. CEF cannot create V8 contexts for isolated
+//! worlds itself, so isolated scripts and `eval(code, true)` run in an
+//! isolated world the protocol creates per frame, where `sendIpcMessage` is
+//! installed as a protocol binding.
+//!
+//! Startup scripts set with `set_startup_scripts` are run by the render
+//! process itself when a document's context is created, which is guaranteed
+//! to be before the page's scripts. They only run in the main world.
+
+use std::collections::HashMap;
+
+use super::CefTexture;
+use crate::webrender::ISOLATED_IPC_BINDING;
+use cef::{ImplBrowser, ImplDictionaryValue, ImplFrame, ImplListValue, ImplProcessMessage};
+use godot::classes::Json;
+use godot::prelude::*;
+
+/// Name of the isolated world scripts run in. Isolated scripts share it, so
+/// they can see each other's globals but not the page's.
+const ISOLATED_WORLD_NAME: &str = "godot_cef";
+
+/// When a user script runs in a document.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum ScriptInjectionTime {
+    /// Before any of the page's scripts.
+    #[default]
+    DocumentStart,
+    /// Once the document is parsed, at `DOMContentLoaded`.
+    DocumentEnd,
+}
+
+/// Returns the source registered for a user script. Document-end scripts
+/// are wrapped in a function run at `DOMContentLoaded`, so their top-level
+/// declarations are local to them.
+fn script_source(code: &str, injection_time: ScriptInjectionTime) -> String {
+    match injection_time {
+        ScriptInjectionTime::DocumentStart => code.to_string(),
+        ScriptInjectionTime::DocumentEnd => format!(
+            "(function() {{\n\
+             const run = function() {{\n{code}\n}};\n\
+             if (document.readyState === 'loading') {{\n\
+             document.addEventListener('DOMContentLoaded', run, {{ once: true }});\n\
+             }} else {{\n\
+             run();\n\
+             }}\n\
+             }})();"
+        ),
+    }
+}
+
+/// Returns the source that runs `code` once in the main frame's isolated
+/// world. It is registered for all frames, so the others skip it.
+fn isolated_eval_source(code: &str) -> String {
+    format!("if (window === window.top) {{\n{code}\n}}")
+}
+
+/// A registration waiting for the identifier the protocol assigns to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Registration {
+    Script(i64),
+    /// An isolated `eval`, removed as soon as it ran.
+    Eval,
+}
+
+struct UserScript {
+    id: i64,
+    source: String,
+    isolated: bool,
+    /// Identifier of the registration, once the protocol answered.
+    identifier: Option<String>,
+}
+
+/// User scripts and their registrations with the current browser.
+#[derive(Default)]
+pub(super) struct UserScripts {
+    next_id: i64,
+    scripts: Vec<UserScript>,
+    /// Registrations waiting for their identifier, by message id.
+    pending: HashMap<i64, Registration>,
+    /// Whether the isolated world's `sendIpcMessage` binding was added.
+    binding_added: bool,
+}
+
+impl UserScripts {
+    fn add(&mut self, source: String, isolated: bool) -> i64 {
+        self.next_id += 1;
+        self.scripts.push(UserScript {
+            id: self.next_id,
+            source,
+            isolated,
+            identifier: None,
+        });
+        self.next_id
+    }
+
+    /// Removes a script. Returns `None` if there is no such script, or the
+    /// identifier of its registration if it has one.
+    fn remove(&mut self, id: i64) -> Option<Option<String>> {
+        let index = self.scripts.iter().position(|script| script.id == id)?;
+        Some(self.scripts.remove(index).identifier)
+    }
+
+    /// Records the identifier of a finished registration. Returns the
+    /// identifier if the registration must be removed right away, because
+    /// it was an `eval` or its script was removed in the meantime.
+    fn registered(&mut self, registration: Registration, identifier: String) -> Option<String> {
+        let script = match registration {
+            Registration::Script(id) => self.scripts.iter_mut().find(|script| script.id == id),
+            Registration::Eval => None,
+        };
+        match script {
+            Some(script) => {
+                script.identifier = Some(identifier);
+                None
+            }
+            None => Some(identifier),
+        }
+    }
+
+    /// Forgets the registrations, which belong to the browser, keeping the
+    /// scripts.
+    fn reset_registrations(&mut self) {
+        self.pending.clear();
+        self.binding_added = false;
+        for script in &mut self.scripts {
+            script.identifier = None;
+        }
+    }
+}
+
+impl CefTexture {
+    pub(super) fn add_user_script_impl(
+        &mut self,
+        code: &str,
+        injection_time: ScriptInjectionTime,
+        isolated: bool,
+    ) -> i64 {
+        let id = self
+            .user_scripts
+            .add(script_source(code, injection_time), isolated);
+        if self.app.browser.is_some() {
+            self.register_user_script(id);
+        }
+        id
+    }
+
+    /// Removes a user script. Documents that already ran it keep its
+    /// effects; the next navigation no longer runs it.
+    pub(super) fn remove_user_script_impl(&mut self, id: i64) -> bool {
+        let Some(identifier) = self.user_scripts.remove(id) else {
+            return false;
+        };
+        // A pending registration is removed once its identifier arrives
+        if let Some(identifier) = identifier {
+            self.unregister_script(&identifier);
+        }
+        true
+    }
+
+    /// Runs `code` once in the main frame's isolated world.
+    pub(super) fn eval_isolated(&mut self, code: &str) {
+        self.ensure_isolated_ipc_binding();
+        let message_id = self.register_source(&isolated_eval_source(code), true, true);
+        if message_id >= 0 {
+            self.user_scripts
+                .pending
+                .insert(message_id, Registration::Eval);
+        }
+    }
+
+    /// Registers all user scripts with a new browser.
+    pub(super) fn apply_user_scripts(&mut self) {
+        self.user_scripts.reset_registrations();
+        let ids: Vec<i64> = self.user_scripts.scripts.iter().map(|s| s.id).collect();
+        for id in ids {
+            self.register_user_script(id);
+        }
+    }
+
+    /// Forgets the registrations of a released browser.
+    pub(super) fn release_user_scripts(&mut self) {
+        self.user_scripts.reset_registrations();
+    }
+
+    /// Handles the result of a registration sent by this module. Returns
+    /// `false` for other messages, which are emitted as `devtools_result`.
+    pub(super) fn handle_user_script_result(
+        &mut self,
+        event: &crate::browser::DevToolsResultEvent,
+    ) -> bool {
+        let Some(registration) = self.user_scripts.pending.remove(&(event.message_id as i64))
+        else {
+            return false;
+        };
+        if !event.success {
+            godot::global::godot_error!(
+                "[CefTexture] Failed to register user script: {}",
+                event.result
+            );
+            return true;
+        }
+        let identifier = super::devtools::parse_json_dictionary(&event.result)
+            .get("identifier")
+            .map(|identifier| identifier.to_string())
+            .unwrap_or_default();
+        if let Some(identifier) = self.user_scripts.registered(registration, identifier) {
+            self.unregister_script(&identifier);
+        }
+        true
+    }
+
+    /// Replaces the startup scripts. The render process of the main frame
+    /// gets them right away; later documents run the new list.
+    pub(super) fn set_startup_scripts_impl(&mut self, scripts: Vec<String>) {
+        self.startup_scripts = scripts;
+        let Some(frame) = self.app.browser.as_ref().and_then(|b| b.main_frame()) else {
+            return;
+        };
+        let route = cef::CefStringUtf16::from(cef_app::STARTUP_SCRIPTS_KEY);
+        let Some(mut process_message) = cef::process_message_create(Some(&route)) else {
+            return;
+        };
+        if let Some(argument_list) = process_message.argument_list()
+            && let Some(mut list) = cef_app::startup_scripts_list(&self.startup_scripts)
+        {
+            argument_list.set_list(0, Some(&mut list));
+        }
+        frame.send_process_message(cef::ProcessId::RENDERER, Some(&mut process_message));
+    }
+
+    /// Returns the `extra_info` a browser is created with, which carries the
+    /// startup scripts to its render processes before the first navigation
+    /// commits.
+    pub(super) fn browser_extra_info(&self) -> Option<cef::DictionaryValue> {
+        if self.startup_scripts.is_empty() {
+            return None;
+        }
+        let extra_info = cef::dictionary_value_create()?;
+        let mut list = cef_app::startup_scripts_list(&self.startup_scripts)?;
+        extra_info.set_list(
+            Some(&cef::CefString::from(cef_app::STARTUP_SCRIPTS_KEY)),
+            Some(&mut list),
+        );
+        Some(extra_info)
+    }
+
+    fn register_user_script(&mut self, id: i64) {
+        let Some((source, isolated)) = self
+            .user_scripts
+            .scripts
+            .iter()
+            .find(|script| script.id == id)
+            .map(|script| (script.source.clone(), script.isolated))
+        else {
+            return;
+        };
+        if isolated {
+            self.ensure_isolated_ipc_binding();
+        }
+        let message_id = self.register_source(&source, isolated, false);
+        if message_id >= 0 {
+            self.user_scripts
+                .pending
+                .insert(message_id, Registration::Script(id));
+        }
+    }
+
+    /// Sends `Page.addScriptToEvaluateOnNewDocument`. Returns the message id,
+    /// or `-1`.
+    fn register_source(&mut self, source: &str, isolated: bool, run_immediately: bool) -> i64 {
+        let mut params = VarDictionary::new();
+        params.set("source", source);
+        if isolated {
+            params.set("worldName", ISOLATED_WORLD_NAME);
+        }
+        if run_immediately {
+            params.set("runImmediately", true);
+        }
+        let params = Json::stringify(&params.to_variant()).to_string();
+        self.execute_devtools_method("Page.addScriptToEvaluateOnNewDocument", &params)
+    }
+
+    fn unregister_script(&mut self, identifier: &str) {
+        let mut params = VarDictionary::new();
+        params.set("identifier", identifier);
+        let params = Json::stringify(&params.to_variant()).to_string();
+        self.execute_devtools_method("Page.removeScriptToEvaluateOnNewDocument", &params);
+    }
+
+    /// Installs `sendIpcMessage` in the isolated world. Its calls come back
+    /// as `Runtime.bindingCalled` events, which are emitted as
+    /// `ipc_message`.
+    fn ensure_isolated_ipc_binding(&mut self) {
+        if self.user_scripts.binding_added {
+            return;
+        }
+        let params = format!(
+            r#"{{"name":"{ISOLATED_IPC_BINDING}","executionContextName":"{ISOLATED_WORLD_NAME}"}}"#
+        );
+        self.user_scripts.binding_added =
+            self.execute_devtools_method("Runtime.addBinding", &params) >= 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_source() {
+        assert_eq!(
+            script_source("let a = 1;", ScriptInjectionTime::DocumentStart),
+            "let a = 1;"
+        );
+        let source = script_source("let a = 1;", ScriptInjectionTime::DocumentEnd);
+        assert!(source.contains("const run = function() {\nlet a = 1;\n};"));
+        assert!(source.contains("addEventListener('DOMContentLoaded', run, { once: true })"));
+        // A trailing line comment cannot swallow the wrapper
+        let source = script_source("go(); // done", ScriptInjectionTime::DocumentEnd);
+        assert!(source.contains("go(); // done\n};"));
+        assert_eq!(
+            isolated_eval_source("go() // done"),
+            "if (window === window.top) {\ngo() // done\n}"
+        );
+    }
+
+    #[test]
+    fn test_registrations() {
+        let mut scripts = UserScripts::default();
+        let first = scripts.add("a".into(), false);
+        let second = scripts.add("b".into(), true);
+        assert_ne!(first, second);
+
+        assert_eq!(
+            scripts.registered(Registration::Script(first), "1".into()),
+            None
+        );
+        assert_eq!(scripts.remove(first), Some(Some("1".to_string())));
+        assert_eq!(scripts.remove(first), None);
+
+        // Removed before the protocol answered: unregistered once it does
+        assert_eq!(scripts.remove(second), Some(None));
+        assert_eq!(
+            scripts.registered(Registration::Script(second), "2".into()),
+            Some("2".to_string())
+        );
+        assert_eq!(
+            scripts.registered(Registration::Eval, "3".into()),
+            Some("3".to_string())
+        );
+
+        let third = scripts.add("c".into(), true);
+        scripts.registered(Registration::Script(third), "4".into());
+        scripts.binding_added = true;
+        scripts.reset_registrations();
+        assert!(!scripts.binding_added);
+        assert_eq!(scripts.remove(third), Some(None));
+    }
+}
@@ -0,0 +1,126 @@
+//! Web notifications bridged to Godot for CefTexture.
+//!
+//! An off-screen browser has no notification UI, so with
+//! `godot_cef/security/allow_web_notifications` the renderer helper replaces
+//! `window.Notification` with a shim that reports the permission as granted
+//! and forwards each notification to `web_notification`. The game shows its
+//! own toast, and reports a click on it with `activate_web_notification`,
+//! which fires the notification's `click` event in the page. Notifications
+//! the page closes are reported with `web_notification_closed`.
+
+use std::collections::HashMap;
+
+use super::CefTexture;
+use cef::{ImplBrowser, ImplFrame, ImplListValue, ImplProcessMessage};
+use godot::prelude::*;
+
+use crate::browser::WebNotificationEvent;
+
+/// Frames of the notifications shown and not closed yet, by tag.
+#[derive(Default)]
+pub(super) struct WebNotifications {
+    frames: HashMap<String, String>,
+}
+
+impl WebNotifications {
+    /// Records a shown notification. One with the same tag replaces it.
+    fn show(&mut self, tag: &str, frame_id: &str) {
+        self.frames.insert(tag.to_string(), frame_id.to_string());
+    }
+
+    /// Forgets a notification its frame closed. Returns `false` if it is
+    /// not shown, or was replaced by one from another frame.
+    fn close(&mut self, tag: &str, frame_id: &str) -> bool {
+        if self.frames.get(tag).is_some_and(|shown| shown == frame_id) {
+            self.frames.remove(tag);
+            return true;
+        }
+        false
+    }
+
+    fn frame(&self, tag: &str) -> Option<&str> {
+        self.frames.get(tag).map(String::as_str)
+    }
+}
+
+impl CefTexture {
+    pub(super) fn process_web_notification_event(&mut self, event: &WebNotificationEvent) {
+        match event {
+            WebNotificationEvent::Shown {
+                frame_id,
+                title,
+                body,
+                icon_url,
+                tag,
+            } => {
+                self.web_notifications.show(tag, frame_id);
+                self.emit_browser_signal(
+                    "web_notification",
+                    &[
+                        ("title", GString::from(title).to_variant()),
+                        ("body", GString::from(body).to_variant()),
+                        ("icon_url", GString::from(icon_url).to_variant()),
+                        ("tag", GString::from(tag).to_variant()),
+                    ],
+                );
+            }
+            WebNotificationEvent::Closed { frame_id, tag } => {
+                if self.web_notifications.close(tag, frame_id) {
+                    self.emit_browser_signal(
+                        "web_notification_closed",
+                        &[("tag", GString::from(tag).to_variant())],
+                    );
+                }
+            }
+        }
+    }
+
+    pub(super) fn activate_web_notification_impl(&mut self, tag: &str) -> bool {
+        let Some(frame_id) = self.web_notifications.frame(tag) else {
+            godot::global::godot_warn!("[CefTexture] No web notification is shown with tag {tag}");
+            return false;
+        };
+        let Some(browser) = self.app.browser.as_ref() else {
+            return false;
+        };
+        let frame_id = cef::CefString::from(frame_id);
+        let Some(frame) = browser.frame_by_identifier(Some(&frame_id)) else {
+            return false;
+        };
+        let route = cef::CefStringUtf16::from("notificationActivate");
+        let Some(mut process_message) = cef::process_message_create(Some(&route)) else {
+            return false;
+        };
+        if let Some(argument_list) = process_message.argument_list() {
+            argument_list.set_string(0, Some(&cef::CefStringUtf16::from(tag)));
+        }
+        frame.send_process_message(cef::ProcessId::RENDERER, Some(&mut process_message));
+        true
+    }
+
+    pub(super) fn release_web_notifications(&mut self) {
+        self.web_notifications = WebNotifications::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shown_notifications() {
+        let mut notifications = WebNotifications::default();
+        notifications.show("chat", "main");
+        assert_eq!(notifications.frame("chat"), Some("main"));
+
+        // The same tag from another frame replaces the notification, which
+        // the first frame can no longer close
+        notifications.show("chat", "child");
+        assert!(!notifications.close("chat", "main"));
+        assert_eq!(notifications.frame("chat"), Some("child"));
+
+        assert!(notifications.close("chat", "child"));
+        assert!(!notifications.close("chat", "child"));
+        assert_eq!(notifications.frame("chat"), None);
+    }
+}
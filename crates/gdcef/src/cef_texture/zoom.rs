@@ -0,0 +1,76 @@
+//! Zoom for CefTexture.
+//!
+//! The browser renders at the zoom level set with `set_zoom_level` combined
+//! with the `text_scale` accessibility setting, so a game-wide text size
+//! and the player's zoom of a page compose instead of overwriting each
+//! other.
+
+use super::CefTexture;
+use cef::{ImplBrowser, ImplBrowserHost};
+
+pub(super) const MIN_TEXT_SCALE: f32 = 0.5;
+pub(super) const MAX_TEXT_SCALE: f32 = 3.0;
+
+/// Chromium's zoom factor for a zoom level; each level is 20% larger.
+pub(super) fn zoom_factor(zoom_level: f64) -> f32 {
+    1.2_f64.powf(zoom_level) as f32
+}
+
+/// Returns the zoom level that scales pages by `text_scale` on top of the
+/// user zoom level.
+fn effective_zoom_level(user_level: f64, text_scale: f32) -> f64 {
+    user_level + (text_scale as f64).ln() / 1.2_f64.ln()
+}
+
+impl CefTexture {
+    /// Sends the combined zoom level to the browser. Chromium remembers zoom
+    /// per host, so it is sent again after each navigation.
+    pub(super) fn apply_zoom(&mut self) {
+        let level = effective_zoom_level(self.user_zoom_level, self.text_scale);
+        if let Some(browser) = self.app.browser.as_mut()
+            && let Some(host) = browser.host()
+            && (host.zoom_level() - level).abs() > 1e-6
+        {
+            host.set_zoom_level(level);
+        }
+    }
+
+    /// Returns the zoom the pages are shown at, in percent.
+    pub(super) fn effective_zoom_percent(&self) -> f64 {
+        1.2_f64.powf(effective_zoom_level(self.user_zoom_level, self.text_scale)) * 100.0
+    }
+
+    /// Returns the zoom level the browser currently renders at.
+    pub(super) fn host_zoom_level(&self) -> f64 {
+        self.app
+            .browser
+            .as_ref()
+            .and_then(|b| b.host())
+            .map(|h| h.zoom_level())
+            .unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_zoom_factor() {
+        assert_eq!(zoom_factor(0.0), 1.0);
+        assert!((zoom_factor(1.0) - 1.2).abs() < 1e-6);
+        assert!((zoom_factor(-1.0) - 1.0 / 1.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_effective_zoom_level() {
+        assert_eq!(effective_zoom_level(0.0, 1.0), 0.0);
+        assert_eq!(effective_zoom_level(-1.0, 1.0), -1.0);
+        assert!((effective_zoom_level(0.0, 1.2) - 1.0).abs() < 1e-6);
+        // The scales multiply: 150% text scale at user zoom level 1 is 180%
+        let level = effective_zoom_level(1.0, 1.5);
+        assert!((zoom_factor(level) - 1.8).abs() < 1e-5);
+        let level = effective_zoom_level(2.0, 0.5);
+        assert!((zoom_factor(level) - 0.72).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,263 @@
+//! Cookie snapshots for `export_cookies` and `import_cookies`.
+//!
+//! A snapshot is a UTF-8 JSON document,
+//! `{"format": "godot-cef-cookies", "version": 1, "cookies": [...]}`, whose
+//! cookies have a `name`, `value`, `domain`, `path`, `expires` (Unix
+//! seconds, `null` for session cookies), `secure`, `httponly` and `samesite`
+//! (`unspecified`, `none`, `lax` or `strict`). Godot's JSON reads and writes
+//! the document on the main thread; this module converts the cookies from
+//! and to CEF and checks them before they are set.
+//!
+//! Host-only cookies keep a domain without a leading dot and are set again
+//! as host-only cookies, domain cookies keep theirs.
+
+use cef::{CookieSameSite, sys};
+
+use crate::security::{basetime_to_unix_seconds, unix_seconds_to_basetime};
+
+/// Value of the `format` key of a snapshot.
+pub const SNAPSHOT_FORMAT: &str = "godot-cef-cookies";
+
+/// Version of the snapshots written by `export_cookies`; `import_cookies`
+/// reads this version only.
+pub const SNAPSHOT_VERSION: i64 = 1;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SameSite {
+    #[default]
+    Unspecified,
+    None,
+    Lax,
+    Strict,
+}
+
+impl SameSite {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Unspecified => "unspecified",
+            Self::None => "none",
+            Self::Lax => "lax",
+            Self::Strict => "strict",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "unspecified" => Some(Self::Unspecified),
+            "none" => Some(Self::None),
+            "lax" => Some(Self::Lax),
+            "strict" => Some(Self::Strict),
+            _ => None,
+        }
+    }
+
+    fn from_cef(same_site: CookieSameSite) -> Self {
+        let is = |value| same_site == CookieSameSite::from(value);
+        if is(sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_NO_RESTRICTION) {
+            Self::None
+        } else if is(sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_LAX_MODE) {
+            Self::Lax
+        } else if is(sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_STRICT_MODE) {
+            Self::Strict
+        } else {
+            Self::Unspecified
+        }
+    }
+
+    fn to_cef(self) -> CookieSameSite {
+        CookieSameSite::from(match self {
+            Self::Unspecified => sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_UNSPECIFIED,
+            Self::None => sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_NO_RESTRICTION,
+            Self::Lax => sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_LAX_MODE,
+            Self::Strict => sys::cef_cookie_same_site_t::CEF_COOKIE_SAME_SITE_STRICT_MODE,
+        })
+    }
+}
+
+/// A cookie of a snapshot.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CookieRecord {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    /// Expiry in Unix seconds; `None` for session cookies.
+    pub expires: Option<i64>,
+    pub secure: bool,
+    pub httponly: bool,
+    pub same_site: SameSite,
+}
+
+fn is_host_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '.' || c == '_'
+}
+
+impl CookieRecord {
+    pub fn from_cef(cookie: &cef::Cookie) -> Self {
+        Self {
+            name: cookie.name.to_string(),
+            value: cookie.value.to_string(),
+            domain: cookie.domain.to_string(),
+            path: cookie.path.to_string(),
+            expires: (cookie.has_expires != 0)
+                .then(|| basetime_to_unix_seconds(cookie.expires.val)),
+            secure: cookie.secure != 0,
+            httponly: cookie.httponly != 0,
+            same_site: SameSite::from_cef(cookie.same_site),
+        }
+    }
+
+    /// Returns the cookie to set for `url`. An empty domain makes CEF set a
+    /// host-only cookie for the host of the URL.
+    pub fn to_cef(&self) -> cef::Cookie {
+        let domain = if self.is_host_only() {
+            ""
+        } else {
+            self.domain.as_str()
+        };
+        cef::Cookie {
+            name: self.name.as_str().into(),
+            value: self.value.as_str().into(),
+            domain: domain.into(),
+            path: self.path.as_str().into(),
+            secure: self.secure as _,
+            httponly: self.httponly as _,
+            has_expires: self.expires.is_some() as _,
+            expires: cef::Basetime {
+                val: self.expires.map_or(0, unix_seconds_to_basetime),
+            },
+            same_site: self.same_site.to_cef(),
+            ..Default::default()
+        }
+    }
+
+    fn is_host_only(&self) -> bool {
+        !self.domain.starts_with('.')
+    }
+
+    /// Returns the URL the cookie is set for: its host, its path and the
+    /// scheme it is sent over.
+    pub fn url(&self) -> String {
+        let scheme = if self.secure { "https" } else { "http" };
+        format!(
+            "{scheme}://{}{}",
+            self.domain.trim_start_matches('.'),
+            self.path
+        )
+    }
+
+    /// Checks that the cookie can be set, returning why not otherwise.
+    pub fn validate(&self) -> Result<(), String> {
+        let is_forbidden = |c: char| c.is_control() || c == ';';
+        if self.name.is_empty() {
+            return Err("the cookie has no name".to_string());
+        }
+        if self.name.contains(|c| is_forbidden(c) || c == '=') {
+            return Err("the name contains a forbidden character".to_string());
+        }
+        if self.value.contains(is_forbidden) {
+            return Err("the value contains a forbidden character".to_string());
+        }
+        let host = self.domain.trim_start_matches('.');
+        if host.is_empty() || !host.chars().all(is_host_char) {
+            return Err(format!("invalid domain '{}'", self.domain));
+        }
+        if !self.path.starts_with('/') || self.path.contains(is_forbidden) {
+            return Err(format!("invalid path '{}'", self.path));
+        }
+        // Chromium rejects cross-site cookies that are not secure
+        if self.same_site == SameSite::None && !self.secure {
+            return Err("samesite 'none' requires secure".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> CookieRecord {
+        CookieRecord {
+            name: "session".to_string(),
+            value: "abc123".to_string(),
+            domain: ".example.com".to_string(),
+            path: "/".to_string(),
+            expires: Some(1_800_000_000),
+            secure: true,
+            httponly: true,
+            same_site: SameSite::Lax,
+        }
+    }
+
+    #[test]
+    fn test_same_site_names() {
+        for same_site in [
+            SameSite::Unspecified,
+            SameSite::None,
+            SameSite::Lax,
+            SameSite::Strict,
+        ] {
+            assert_eq!(SameSite::parse(same_site.as_str()), Some(same_site));
+        }
+        assert_eq!(SameSite::parse("Lax"), None);
+        assert_eq!(SameSite::parse(""), None);
+    }
+
+    #[test]
+    fn test_cookie_url() {
+        assert_eq!(record().url(), "https://example.com/");
+        let host_only = CookieRecord {
+            domain: "accounts.example.com".to_string(),
+            path: "/login".to_string(),
+            secure: false,
+            ..record()
+        };
+        assert_eq!(host_only.url(), "http://accounts.example.com/login");
+        assert!(host_only.is_host_only());
+        assert!(!record().is_host_only());
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(record().validate(), Ok(()));
+        let invalid = [
+            CookieRecord {
+                name: String::new(),
+                ..record()
+            },
+            CookieRecord {
+                name: "a=b".to_string(),
+                ..record()
+            },
+            CookieRecord {
+                value: "a;b".to_string(),
+                ..record()
+            },
+            CookieRecord {
+                value: "line\nbreak".to_string(),
+                ..record()
+            },
+            CookieRecord {
+                domain: ".".to_string(),
+                ..record()
+            },
+            CookieRecord {
+                domain: "example.com/evil".to_string(),
+                ..record()
+            },
+            CookieRecord {
+                path: "relative".to_string(),
+                ..record()
+            },
+            CookieRecord {
+                same_site: SameSite::None,
+                secure: false,
+                ..record()
+            },
+        ];
+        for cookie in invalid {
+            assert!(cookie.validate().is_err(), "{cookie:?}");
+        }
+    }
+}
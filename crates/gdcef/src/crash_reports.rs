@@ -0,0 +1,86 @@
+//! Crash dump collection for the CEF helper processes.
+//!
+//! Before CEF is initialized, a `crash_reporter.cfg` is generated where CEF
+//! looks for it (next to the helper executable, or inside `Godot CEF.app` on
+//! macOS) and the dump directory under `user://` is passed to the helpers
+//! through the environment. CEF reads the file from nowhere else, so it is
+//! only written from here, once per launch; the helpers never write it. In a
+//! read-only install the file has to be shipped with the addon. Dumps are
+//! only stored locally; games decide whether to upload them.
+
+use godot::classes::ProjectSettings;
+use godot::global::godot_warn;
+use godot::prelude::*;
+use std::path::PathBuf;
+
+use crate::settings;
+use crate::utils::get_subprocess_path;
+
+const CRASH_DUMPS_DIR: &str = "user://godot_cef_crashes";
+
+/// Absolute path of the directory crash dumps are written to.
+pub fn crash_dumps_dir() -> PathBuf {
+    let dir: GString = CRASH_DUMPS_DIR.into();
+    let path = ProjectSettings::singleton().globalize_path(&dir);
+    PathBuf::from(path.to_string())
+}
+
+/// Enables or disables crash dump collection according to the
+/// `godot_cef/debug/collect_crash_dumps` project setting. Must run before CEF
+/// starts any process.
+pub fn configure_crash_reporting() {
+    let config_dir = match get_subprocess_path() {
+        Ok(path) => cef_app::crash_reporter_config_dir(&path),
+        Err(e) => {
+            godot_warn!(
+                "[CrashReports] Failed to locate the helper executable: {}",
+                e
+            );
+            None
+        }
+    };
+    let Some(config_dir) = config_dir else {
+        return;
+    };
+
+    if !settings::is_crash_dump_collection_enabled() {
+        if let Err(e) = cef_app::remove_crash_reporter_config(&config_dir) {
+            godot_warn!(
+                "[CrashReports] Failed to remove crash reporter config: {}",
+                e
+            );
+        }
+        // SAFETY: called from the main thread before CEF is initialized.
+        unsafe { std::env::remove_var(cef_app::CRASH_DUMP_LOCATION_ENV) };
+        return;
+    }
+
+    let dumps_dir = crash_dumps_dir();
+    if let Err(e) = std::fs::create_dir_all(&dumps_dir) {
+        godot_warn!(
+            "[CrashReports] Failed to create {}: {}",
+            dumps_dir.display(),
+            e
+        );
+        return;
+    }
+    if let Err(e) = cef_app::write_crash_reporter_config(&config_dir) {
+        godot_warn!(
+            "[CrashReports] Crash dumps are not collected: failed to write crash reporter \
+             config to {}: {}. CEF only reads it from there; in a read-only install, ship \
+             the file with the addon.",
+            config_dir.display(),
+            e
+        );
+        return;
+    }
+
+    // SAFETY: called from the main thread before CEF is initialized, so no
+    // CEF thread reads the environment concurrently.
+    unsafe { std::env::set_var(cef_app::CRASH_DUMP_LOCATION_ENV, &dumps_dir) };
+}
+
+/// Lists the crash dumps that have not been deleted yet, oldest first.
+pub fn pending_crash_reports() -> Vec<cef_app::CrashDump> {
+    cef_app::find_crash_dumps(&crash_dumps_dir())
+}
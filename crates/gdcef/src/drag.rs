@@ -30,6 +30,11 @@ pub struct DragDataInfo {
 
     #[var]
     pub file_names: Array<GString>,
+
+    /// Paths of the dragged files on disk, filled in once they are written
+    /// (see `drag_files_ready`).
+    #[var]
+    pub file_paths: PackedStringArray,
 }
 
 #[godot_api]
@@ -46,6 +51,7 @@ impl DragDataInfo {
             fragment_text: GString::new(),
             fragment_html: GString::new(),
             file_names: Array::new(),
+            file_paths: PackedStringArray::new(),
         })
     }
 }
@@ -68,6 +74,7 @@ impl DragDataInfo {
             fragment_text: GString::from(&data.fragment_text),
             fragment_html: GString::from(&data.fragment_html),
             file_names,
+            file_paths: PackedStringArray::new(),
         })
     }
 }
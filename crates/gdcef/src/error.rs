@@ -5,6 +5,7 @@
 
 use std::fmt;
 use std::io;
+use std::path::PathBuf;
 
 /// Main error type for CEF operations.
 #[derive(Debug)]
@@ -26,6 +27,8 @@ pub enum CefError {
     PathError(io::Error),
     /// A required resource was not found.
     ResourceNotFound(String),
+    /// The helper subprocess is missing, not executable or does not run.
+    HelperUnavailable { path: PathBuf, reason: String },
     /// GPU device access failed.
     GpuDeviceError(String),
     /// Invalid texture size (zero or negative dimensions).
@@ -53,6 +56,9 @@ impl fmt::Display for CefError {
             CefError::ResourceNotFound(resource) => {
                 write!(f, "Resource not found: {}", resource)
             }
+            CefError::HelperUnavailable { path, reason } => {
+                write!(f, "Helper process {} {}", path.display(), reason)
+            }
             CefError::GpuDeviceError(msg) => {
                 write!(f, "GPU device error: {}", msg)
             }
@@ -72,6 +78,7 @@ impl std::error::Error for CefError {
             | CefError::BrowserCreationFailed(_)
             | CefError::TextureOperationFailed(_)
             | CefError::ResourceNotFound(_)
+            | CefError::HelperUnavailable { .. }
             | CefError::GpuDeviceError(_)
             | CefError::InvalidSize { .. } => None,
         }
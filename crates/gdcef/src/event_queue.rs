@@ -0,0 +1,320 @@
+//! Bounded event queues.
+//!
+//! CEF threads queue events for the main thread, which drains them once per
+//! frame. A page sending console messages or IPC in a tight loop would grow
+//! an unbounded queue without limit and then stall the frame that drains
+//! it, so each queue holds at most the capacity of its category
+//! (`godot_cef/queues/*_capacity`) and applies the category's
+//! [`OverflowPolicy`] when full. Each queue allocates its capacity up front
+//! and applying the policy never allocates: a full queue drops an event or
+//! overwrites one in place.
+
+use std::collections::VecDeque;
+use std::collections::vec_deque::Drain;
+use std::ops::RangeFull;
+
+/// What a full queue does with a new event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Drop the oldest queued event to make room; the latest events matter.
+    DropOldest,
+    /// Drop the new event; what was queued first is delivered.
+    DropNewest,
+    /// Replace the newest queued event, which the new one supersedes.
+    Coalesce,
+}
+
+/// What `push_back` did with an event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PushOutcome {
+    /// The event was appended.
+    Queued,
+    /// The event was appended after the oldest queued event was dropped.
+    DroppedOldest,
+    /// The event replaced the newest queued event.
+    Coalesced,
+    /// The event was dropped.
+    Dropped,
+}
+
+/// Groups of queues sharing a capacity setting and an overflow policy.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueueCategory {
+    /// String and binary IPC messages.
+    Ipc,
+    Console,
+    /// URL and title changes.
+    Navigation,
+    /// Load events and loading state changes.
+    Loading,
+    /// All other events.
+    Events,
+}
+
+impl QueueCategory {
+    pub const ALL: [Self; 5] = [
+        Self::Ipc,
+        Self::Console,
+        Self::Navigation,
+        Self::Loading,
+        Self::Events,
+    ];
+
+    pub fn policy(self) -> OverflowPolicy {
+        match self {
+            Self::Ipc => OverflowPolicy::DropNewest,
+            Self::Loading => OverflowPolicy::Coalesce,
+            Self::Console | Self::Navigation | Self::Events => OverflowPolicy::DropOldest,
+        }
+    }
+
+    /// Name used in the settings and in `get_queue_backlog`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Ipc => "ipc",
+            Self::Console => "console",
+            Self::Navigation => "navigation",
+            Self::Loading => "loading",
+            Self::Events => "events",
+        }
+    }
+
+    pub fn default_capacity(self) -> usize {
+        match self {
+            Self::Ipc => 4096,
+            Self::Console => 1000,
+            Self::Navigation | Self::Loading => 256,
+            Self::Events => 4096,
+        }
+    }
+}
+
+/// Capacity of each queue, by category.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueueLimits([usize; QueueCategory::ALL.len()]);
+
+impl QueueLimits {
+    pub fn capacity(&self, category: QueueCategory) -> usize {
+        self.0[category as usize]
+    }
+
+    pub fn set_capacity(&mut self, category: QueueCategory, capacity: usize) {
+        self.0[category as usize] = capacity.max(1);
+    }
+}
+
+impl Default for QueueLimits {
+    fn default() -> Self {
+        Self(QueueCategory::ALL.map(QueueCategory::default_capacity))
+    }
+}
+
+/// FIFO queue holding at most `capacity` events.
+#[derive(Debug)]
+pub struct BoundedQueue<T> {
+    items: VecDeque<T>,
+    capacity: usize,
+    policy: OverflowPolicy,
+    /// Category the capacity and policy come from, if any.
+    category: Option<QueueCategory>,
+    /// Events dropped or coalesced since the queue was created.
+    overflowed: u64,
+    /// Overflows not yet taken with `take_new_overflows`.
+    new_overflows: u64,
+}
+
+impl<T> BoundedQueue<T> {
+    pub fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            items: VecDeque::with_capacity(capacity),
+            capacity,
+            policy,
+            category: None,
+            overflowed: 0,
+            new_overflows: 0,
+        }
+    }
+
+    /// Queue with the capacity and policy of `category`.
+    pub fn for_category(limits: &QueueLimits, category: QueueCategory) -> Self {
+        Self {
+            category: Some(category),
+            ..Self::new(limits.capacity(category), category.policy())
+        }
+    }
+
+    /// Queues `item`, applying the overflow policy if the queue is full.
+    pub fn push_back(&mut self, item: T) -> PushOutcome {
+        if self.items.len() < self.capacity {
+            self.items.push_back(item);
+            return PushOutcome::Queued;
+        }
+
+        self.overflowed += 1;
+        self.new_overflows += 1;
+        match self.policy {
+            OverflowPolicy::DropOldest => {
+                self.items.pop_front();
+                self.items.push_back(item);
+                PushOutcome::DroppedOldest
+            }
+            OverflowPolicy::DropNewest => PushOutcome::Dropped,
+            OverflowPolicy::Coalesce => {
+                if let Some(last) = self.items.back_mut() {
+                    *last = item;
+                }
+                PushOutcome::Coalesced
+            }
+        }
+    }
+
+    pub fn pop_front(&mut self) -> Option<T> {
+        self.items.pop_front()
+    }
+
+    pub fn extend(&mut self, items: impl IntoIterator<Item = T>) {
+        for item in items {
+            self.push_back(item);
+        }
+    }
+
+    pub fn drain(&mut self, range: RangeFull) -> Drain<'_, T> {
+        self.items.drain(range)
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn category(&self) -> Option<QueueCategory> {
+        self.category
+    }
+
+    pub fn overflowed(&self) -> u64 {
+        self.overflowed
+    }
+
+    /// Returns the overflows since the last call.
+    pub fn take_new_overflows(&mut self) -> u64 {
+        std::mem::take(&mut self.new_overflows)
+    }
+
+    /// Releases memory beyond the capacity allocated up front, which is kept
+    /// so pushing after a purge does not allocate.
+    pub fn shrink_to_fit(&mut self) {
+        self.items.shrink_to(self.capacity);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EVENTS: usize = 1_000_000;
+    const CAPACITY: usize = 1000;
+
+    /// Pushes `EVENTS` numbered events, checking the queue never outgrows
+    /// its capacity, and returns what is drained.
+    fn push_all(policy: OverflowPolicy) -> (Vec<usize>, u64) {
+        let mut queue = BoundedQueue::new(CAPACITY, policy);
+        let allocated = queue.items.capacity();
+        for i in 0..EVENTS {
+            queue.push_back(i);
+            assert!(queue.len() <= CAPACITY);
+        }
+        // The buffer is allocated up front and never grows
+        assert_eq!(queue.items.capacity(), allocated);
+        let overflowed = queue.overflowed();
+        (queue.drain(..).collect(), overflowed)
+    }
+
+    #[test]
+    fn test_drop_oldest_keeps_latest_events() {
+        let (drained, overflowed) = push_all(OverflowPolicy::DropOldest);
+        let expected: Vec<usize> = (EVENTS - CAPACITY..EVENTS).collect();
+        assert_eq!(drained, expected);
+        assert_eq!(overflowed, (EVENTS - CAPACITY) as u64);
+    }
+
+    #[test]
+    fn test_drop_newest_keeps_first_events() {
+        let (drained, overflowed) = push_all(OverflowPolicy::DropNewest);
+        let expected: Vec<usize> = (0..CAPACITY).collect();
+        assert_eq!(drained, expected);
+        assert_eq!(overflowed, (EVENTS - CAPACITY) as u64);
+    }
+
+    #[test]
+    fn test_coalesce_keeps_first_events_and_latest_state() {
+        let (drained, overflowed) = push_all(OverflowPolicy::Coalesce);
+        let mut expected: Vec<usize> = (0..CAPACITY - 1).collect();
+        expected.push(EVENTS - 1);
+        assert_eq!(drained, expected);
+        assert_eq!(overflowed, (EVENTS - CAPACITY) as u64);
+    }
+
+    #[test]
+    fn test_push_outcomes() {
+        let mut queue = BoundedQueue::new(1, OverflowPolicy::DropOldest);
+        assert_eq!(queue.push_back(1), PushOutcome::Queued);
+        assert_eq!(queue.push_back(2), PushOutcome::DroppedOldest);
+        let mut queue = BoundedQueue::new(1, OverflowPolicy::Coalesce);
+        assert_eq!(queue.push_back(1), PushOutcome::Queued);
+        assert_eq!(queue.push_back(2), PushOutcome::Coalesced);
+    }
+
+    #[test]
+    fn test_shrink_to_fit_keeps_allocated_capacity() {
+        let mut queue = BoundedQueue::new(CAPACITY, OverflowPolicy::DropOldest);
+        let allocated = queue.items.capacity();
+        queue.extend(0..CAPACITY);
+        queue.drain(..);
+        queue.shrink_to_fit();
+        queue.extend(0..CAPACITY);
+        assert_eq!(queue.items.capacity(), allocated);
+        assert_eq!(queue.pop_front(), Some(0));
+    }
+
+    #[test]
+    fn test_drop_newest_reports_dropped_push() {
+        let mut queue = BoundedQueue::new(1, OverflowPolicy::DropNewest);
+        assert_eq!(queue.push_back(1), PushOutcome::Queued);
+        assert_eq!(queue.push_back(2), PushOutcome::Dropped);
+        assert_eq!(queue.take_new_overflows(), 1);
+        assert_eq!(queue.take_new_overflows(), 0);
+        assert_eq!(queue.overflowed(), 1);
+    }
+
+    #[test]
+    fn test_queue_accepts_events_again_after_drain() {
+        let mut queue = BoundedQueue::new(2, OverflowPolicy::DropNewest);
+        queue.extend([1, 2, 3]);
+        assert_eq!(queue.drain(..).collect::<Vec<_>>(), vec![1, 2]);
+        queue.extend([4]);
+        assert_eq!(queue.drain(..).collect::<Vec<_>>(), vec![4]);
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut limits = QueueLimits::default();
+        assert_eq!(limits.capacity(QueueCategory::Console), 1000);
+        limits.set_capacity(QueueCategory::Ipc, 0);
+        assert_eq!(limits.capacity(QueueCategory::Ipc), 1);
+        assert_eq!(
+            BoundedQueue::<()>::new(0, OverflowPolicy::DropOldest).capacity(),
+            1
+        );
+        let queue = BoundedQueue::<()>::for_category(&limits, QueueCategory::Loading);
+        assert_eq!(queue.capacity(), 256);
+        assert_eq!(queue.category(), Some(QueueCategory::Loading));
+        assert_eq!(
+            BoundedQueue::<()>::new(8, OverflowPolicy::DropOldest).category(),
+            None
+        );
+    }
+}
@@ -0,0 +1,398 @@
+//! Fonts bundled with the game, made available to the pages.
+//!
+//! The font resources listed in `godot_cef/fonts/font_files` are loaded as
+//! `FontFile`s, which keep the font data in exports, and written to
+//! `user://godot_cef_fonts/` under names derived from a hash of their data,
+//! so unchanged fonts are not written again. Before CEF initializes they are
+//! registered with the platform: through a fontconfig configuration adding
+//! the directory on Linux, `AddFontResourceExW` on Windows and
+//! `CTFontManagerRegisterFontsForURL` on macOS. Pages use them through the
+//! `godot_cef/fonts/*_family` settings or by family name.
+
+use godot::classes::{FontFile, ProjectSettings};
+use godot::global::godot_warn;
+use godot::prelude::*;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::settings;
+
+const FONTS_DIR: &str = "user://godot_cef_fonts";
+
+/// Extensions of the files written to the fonts directory. Other files are
+/// left alone when stale fonts are removed.
+const FONT_EXTENSIONS: [&str; 5] = ["ttf", "otf", "ttc", "woff", "woff2"];
+
+/// Fonts registered with the platform, unregistered when CEF shuts down.
+static REGISTERED: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// 64-bit FNV-1a, stable across builds unlike `DefaultHasher`.
+fn fnv1a(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Name of the cached copy of a font: its file stem and a hash of its data,
+/// e.g. `NotoSansCJK-0123456789abcdef.otf`.
+fn cached_file_name(source: &str, data: &[u8]) -> String {
+    let file_name = source.rsplit(['/', '\\']).next().unwrap_or(source);
+    let (stem, extension) = file_name.rsplit_once('.').unwrap_or((file_name, "ttf"));
+    format!(
+        "{}-{:016x}.{}",
+        stem,
+        fnv1a(data),
+        extension.to_ascii_lowercase()
+    )
+}
+
+fn is_font_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| FONT_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str()))
+}
+
+/// Writes the fonts that are not in `dir` yet and removes the fonts of
+/// earlier runs that are no longer listed. Returns the paths of `fonts`.
+fn sync_font_cache(dir: &Path, fonts: &[(String, Vec<u8>)]) -> io::Result<Vec<PathBuf>> {
+    std::fs::create_dir_all(dir)?;
+    let mut paths = Vec::with_capacity(fonts.len());
+    for (name, data) in fonts {
+        let path = dir.join(name);
+        if !path.exists() {
+            // Written under another name first, so an interrupted write is
+            // never taken for a cached font
+            let partial = dir.join(format!("{}.partial", name));
+            std::fs::write(&partial, data)?;
+            std::fs::rename(&partial, &path)?;
+        }
+        paths.push(path);
+    }
+
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if is_font_file(&path) && !paths.contains(&path) {
+            // Fails while another instance of the game still uses the font
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+    Ok(paths)
+}
+
+/// Copies the bundled fonts out of the project and registers them. Returns
+/// the environment variables the subprocesses need to find them.
+pub fn install_bundled_fonts() -> Vec<(String, String)> {
+    let sources = settings::get_font_files();
+    if sources.is_empty() {
+        return Vec::new();
+    }
+
+    let fonts: Vec<(String, Vec<u8>)> = sources
+        .iter()
+        .filter_map(|source| {
+            let font = match godot::tools::try_load::<FontFile>(source.as_str()) {
+                Ok(font) => font,
+                Err(e) => {
+                    godot_warn!("[Fonts] Failed to load font '{}': {}", source, e);
+                    return None;
+                }
+            };
+            let data = font.get_data().to_vec();
+            if data.is_empty() {
+                godot_warn!("[Fonts] Font '{}' has no font data", source);
+                return None;
+            }
+            Some((cached_file_name(source, &data), data))
+        })
+        .collect();
+
+    let dir: GString = FONTS_DIR.into();
+    let dir = PathBuf::from(
+        ProjectSettings::singleton()
+            .globalize_path(&dir)
+            .to_string(),
+    );
+    let paths = match sync_font_cache(&dir, &fonts) {
+        Ok(paths) => paths,
+        Err(e) => {
+            godot_warn!(
+                "[Fonts] Failed to copy the bundled fonts to '{}': {}",
+                dir.display(),
+                e
+            );
+            return Vec::new();
+        }
+    };
+
+    platform::register(&dir, &paths)
+}
+
+/// Unregisters the fonts registered by `install_bundled_fonts`.
+pub fn uninstall_bundled_fonts() {
+    let paths = REGISTERED
+        .lock()
+        .map(|mut registered| std::mem::take(&mut *registered))
+        .unwrap_or_default();
+    for path in paths {
+        platform::unregister(&path);
+    }
+}
+
+/// Returns a fontconfig configuration that loads `include`, the
+/// configuration fontconfig would otherwise use, and adds `font_dir`.
+#[cfg(any(target_os = "linux", test))]
+fn fontconfig_config(include: &str, font_dir: &Path) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    format!(
+        "<?xml version=\"1.0\"?>\n\
+         <!DOCTYPE fontconfig SYSTEM \"fonts.dtd\">\n\
+         <fontconfig>\n\
+         \x20 <include ignore_missing=\"yes\">{}</include>\n\
+         \x20 <dir>{}</dir>\n\
+         </fontconfig>\n",
+        escape(include),
+        escape(&font_dir.to_string_lossy())
+    )
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::fontconfig_config;
+    use godot::global::godot_warn;
+    use std::path::{Path, PathBuf};
+
+    /// Points fontconfig at a configuration adding the fonts directory.
+    /// Chromium's renderers ask the browser process for fonts, which reads
+    /// the environment when CEF initializes.
+    pub fn register(dir: &Path, _fonts: &[PathBuf]) -> Vec<(String, String)> {
+        let config_path = dir.join("fonts.conf");
+        // A game launched from the editor inherits the editor's configuration
+        let include = std::env::var("FONTCONFIG_FILE")
+            .ok()
+            .filter(|file| Path::new(file) != config_path)
+            .unwrap_or_else(|| {
+                std::env::var("FONTCONFIG_PATH")
+                    .map(|path| format!("{}/fonts.conf", path.trim_end_matches('/')))
+                    .unwrap_or_else(|_| "/etc/fonts/fonts.conf".to_string())
+            });
+        if let Err(e) = std::fs::write(&config_path, fontconfig_config(&include, dir)) {
+            godot_warn!(
+                "[Fonts] Failed to write the fontconfig configuration to '{}': {}",
+                config_path.display(),
+                e
+            );
+            return Vec::new();
+        }
+
+        let config_path = config_path.to_string_lossy().into_owned();
+        // SAFETY: called from the main thread before CEF is initialized, so no
+        // CEF thread reads the environment concurrently.
+        unsafe { std::env::set_var("FONTCONFIG_FILE", &config_path) };
+        vec![("FONTCONFIG_FILE".to_string(), config_path)]
+    }
+
+    pub fn unregister(_font: &Path) {}
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::REGISTERED;
+    use godot::global::godot_warn;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+    use windows::Win32::Graphics::Gdi::{
+        AddFontResourceExW, FONT_RESOURCE_CHARACTERISTICS, RemoveFontResourceExW,
+    };
+    use windows::core::PCWSTR;
+
+    fn wide(path: &Path) -> Vec<u16> {
+        path.as_os_str().encode_wide().chain(Some(0)).collect()
+    }
+
+    /// Adds the fonts for the session rather than privately, as the
+    /// subprocesses enumerate fonts on their own.
+    pub fn register(_dir: &Path, fonts: &[PathBuf]) -> Vec<(String, String)> {
+        let mut registered = REGISTERED.lock().unwrap_or_else(|e| e.into_inner());
+        for font in fonts {
+            let name = wide(font);
+            let added = unsafe {
+                AddFontResourceExW(
+                    PCWSTR(name.as_ptr()),
+                    FONT_RESOURCE_CHARACTERISTICS(0),
+                    None,
+                )
+            };
+            if added == 0 {
+                godot_warn!("[Fonts] Failed to register font '{}'", font.display());
+            } else {
+                registered.push(font.clone());
+            }
+        }
+        Vec::new()
+    }
+
+    pub fn unregister(font: &Path) {
+        let name = wide(font);
+        let _ = unsafe { RemoveFontResourceExW(PCWSTR(name.as_ptr()), 0, None) };
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::REGISTERED;
+    use godot::global::godot_warn;
+    use std::ffi::c_void;
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::{Path, PathBuf};
+
+    /// Fonts are available to every process of the login session, including
+    /// the helpers, until unregistered or logout.
+    const K_CT_FONT_MANAGER_SCOPE_SESSION: u32 = 3;
+
+    #[link(name = "CoreFoundation", kind = "framework")]
+    unsafe extern "C" {
+        fn CFURLCreateFromFileSystemRepresentation(
+            allocator: *const c_void,
+            buffer: *const u8,
+            buf_len: isize,
+            is_directory: u8,
+        ) -> *const c_void;
+        fn CFRelease(cf: *const c_void);
+    }
+
+    #[link(name = "CoreText", kind = "framework")]
+    unsafe extern "C" {
+        fn CTFontManagerRegisterFontsForURL(
+            font_url: *const c_void,
+            scope: u32,
+            error: *mut *const c_void,
+        ) -> u8;
+        fn CTFontManagerUnregisterFontsForURL(
+            font_url: *const c_void,
+            scope: u32,
+            error: *mut *const c_void,
+        ) -> u8;
+    }
+
+    /// Calls `f` with a `CFURLRef` for `path`.
+    fn with_url<T>(path: &Path, f: impl FnOnce(*const c_void) -> T) -> Option<T> {
+        let bytes = path.as_os_str().as_bytes();
+        let url = unsafe {
+            CFURLCreateFromFileSystemRepresentation(
+                std::ptr::null(),
+                bytes.as_ptr(),
+                bytes.len() as isize,
+                0,
+            )
+        };
+        if url.is_null() {
+            return None;
+        }
+        let result = f(url);
+        unsafe { CFRelease(url) };
+        Some(result)
+    }
+
+    pub fn register(_dir: &Path, fonts: &[PathBuf]) -> Vec<(String, String)> {
+        let mut registered = REGISTERED.lock().unwrap_or_else(|e| e.into_inner());
+        for font in fonts {
+            let added = with_url(font, |url| {
+                let mut error = std::ptr::null();
+                let added = unsafe {
+                    CTFontManagerRegisterFontsForURL(
+                        url,
+                        K_CT_FONT_MANAGER_SCOPE_SESSION,
+                        &mut error,
+                    )
+                };
+                if !error.is_null() {
+                    unsafe { CFRelease(error) };
+                }
+                added != 0
+            });
+            // Fails harmlessly when an earlier run left the font registered
+            if added == Some(true) {
+                registered.push(font.clone());
+            } else if added.is_none() {
+                godot_warn!("[Fonts] Failed to register font '{}'", font.display());
+            }
+        }
+        Vec::new()
+    }
+
+    pub fn unregister(font: &Path) {
+        with_url(font, |url| {
+            let mut error = std::ptr::null();
+            unsafe {
+                CTFontManagerUnregisterFontsForURL(url, K_CT_FONT_MANAGER_SCOPE_SESSION, &mut error)
+            };
+            if !error.is_null() {
+                unsafe { CFRelease(error) };
+            }
+        });
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod platform {
+    use std::path::{Path, PathBuf};
+
+    pub fn register(_dir: &Path, _fonts: &[PathBuf]) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
+    pub fn unregister(_font: &Path) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_file_name() {
+        let name = cached_file_name("res://fonts/NotoSansCJK.OTF", b"font data");
+        assert_eq!(
+            name,
+            format!("NotoSansCJK-{:016x}.otf", fnv1a(b"font data"))
+        );
+        assert_ne!(
+            cached_file_name("res://fonts/NotoSansCJK.OTF", b"other data"),
+            name
+        );
+        assert_eq!(
+            cached_file_name("user://Emoji", b""),
+            format!("Emoji-{:016x}.ttf", fnv1a(b""))
+        );
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_sync_font_cache() {
+        let dir = std::env::temp_dir().join(format!("godot-cef-fonts-{}", std::process::id()));
+        let old = ("Old-1.ttf".to_string(), b"old".to_vec());
+        let new = ("New-2.otf".to_string(), b"new".to_vec());
+        sync_font_cache(&dir, std::slice::from_ref(&old)).unwrap();
+        std::fs::write(dir.join("fonts.conf"), "").unwrap();
+
+        let paths = sync_font_cache(&dir, std::slice::from_ref(&new)).unwrap();
+        assert_eq!(paths, vec![dir.join("New-2.otf")]);
+        assert_eq!(std::fs::read(&paths[0]).unwrap(), b"new");
+        assert!(!dir.join("Old-1.ttf").exists());
+        assert!(dir.join("fonts.conf").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_fontconfig_config() {
+        let config = fontconfig_config("/etc/fonts/fonts.conf", Path::new("/data/R&D fonts"));
+        assert!(config.contains("<include ignore_missing=\"yes\">/etc/fonts/fonts.conf</include>"));
+        assert!(config.contains("<dir>/data/R&amp;D fonts</dir>"));
+    }
+}
@@ -0,0 +1,149 @@
+//! Frame pacing between CEF's paints and Godot's frames.
+//!
+//! When CEF paints faster than Godot shows frames, paints are converted or
+//! copied and then replaced before they are ever shown. With
+//! [`FramePacing::DropStale`] the render handlers skip a paint that arrives
+//! while the previous frame is still waiting for Godot, and the view is
+//! repainted once Godot has taken that frame, so the page shows its latest
+//! state one frame later at most. [`FramePacing::MatchEngineFps`] instead
+//! lowers the frame rate CEF paints at to the rate Godot actually reaches.
+
+use godot::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Lowest frame rate `MatchEngineFps` lowers CEF to.
+const MIN_PACED_FRAME_RATE: i32 = 10;
+
+/// Granularity of the matched frame rate, so the small swings of the
+/// measured rate do not change CEF's frame rate every second.
+const PACED_FRAME_RATE_STEP: i32 = 5;
+
+/// How paints are paced against the frames Godot shows.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum FramePacing {
+    /// Every paint is converted or copied.
+    #[default]
+    Off,
+    /// Paints arriving while a frame is still waiting for Godot are skipped.
+    DropStale,
+    /// CEF paints at the frame rate Godot reaches.
+    MatchEngineFps,
+}
+
+/// Shared handle to the frame pacing state of one browser.
+pub type FramePacerHandle = Arc<FramePacer>;
+
+/// Frames handed over by the render handler and taken by Godot, shared
+/// without locks.
+#[derive(Default)]
+pub struct FramePacer {
+    drop_stale: AtomicBool,
+    queued: AtomicU64,
+    consumed: AtomicU64,
+    repaint_needed: AtomicBool,
+}
+
+impl FramePacer {
+    pub fn new() -> FramePacerHandle {
+        Arc::new(Self::default())
+    }
+
+    pub fn set_drop_stale(&self, drop_stale: bool) {
+        self.drop_stale.store(drop_stale, Ordering::Relaxed);
+    }
+
+    /// Called by the render handler for each view paint, before converting
+    /// or copying it. Returns true if the paint should be skipped because a
+    /// frame is still waiting for Godot.
+    pub fn should_skip_paint(&self) -> bool {
+        if !self.drop_stale.load(Ordering::Relaxed)
+            || self.queued.load(Ordering::Acquire) <= self.consumed.load(Ordering::Acquire)
+        {
+            return false;
+        }
+        self.repaint_needed.store(true, Ordering::Relaxed);
+        true
+    }
+
+    /// Called by the render handler once a frame waits for Godot.
+    pub fn frame_queued(&self) {
+        self.queued.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Called by Godot after taking the waiting frame. Returns true if a
+    /// paint was skipped meanwhile, so the view should be repainted.
+    pub fn frame_consumed(&self) -> bool {
+        self.consumed
+            .store(self.queued.load(Ordering::Acquire), Ordering::Release);
+        self.repaint_needed.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// Frame rate for `MatchEngineFps`: `engine_fps` rounded up to the next step
+/// and capped at `max_fps`. The `current` rate is kept while it is within a
+/// step of that, so the rate only changes when Godot clearly slows down or
+/// speeds up.
+pub fn paced_frame_rate(current: i32, max_fps: i32, engine_fps: f64) -> i32 {
+    if max_fps <= 0 || engine_fps <= 0.0 {
+        return max_fps;
+    }
+
+    let step = PACED_FRAME_RATE_STEP;
+    let measured = engine_fps.ceil() as i32;
+    let target =
+        ((measured + step - 1) / step * step).clamp(MIN_PACED_FRAME_RATE.min(max_fps), max_fps);
+    if current > 0 && current <= max_fps && (target - current).abs() <= step {
+        current
+    } else {
+        target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pacer_skips_paints_while_a_frame_waits() {
+        let pacer = FramePacer::default();
+
+        // Off: nothing is skipped
+        pacer.frame_queued();
+        assert!(!pacer.should_skip_paint());
+
+        pacer.set_drop_stale(true);
+        assert!(pacer.should_skip_paint());
+        assert!(pacer.frame_consumed(), "a skipped paint asks for a repaint");
+        assert!(!pacer.should_skip_paint());
+
+        pacer.frame_queued();
+        assert!(!pacer.frame_consumed(), "nothing was skipped");
+        assert!(!pacer.should_skip_paint());
+    }
+
+    #[test]
+    fn test_paced_frame_rate() {
+        // Not measured yet, or no cap
+        assert_eq!(paced_frame_rate(60, 60, 0.0), 60);
+        assert_eq!(paced_frame_rate(60, 0, 30.0), 0);
+
+        // Godot slows down to 30 fps, then jitters around it
+        assert_eq!(paced_frame_rate(60, 60, 30.0), 30);
+        assert_eq!(paced_frame_rate(30, 60, 31.0), 30);
+        assert_eq!(paced_frame_rate(30, 60, 29.0), 30);
+
+        // And speeds up again
+        assert_eq!(paced_frame_rate(30, 60, 42.0), 45);
+        assert_eq!(paced_frame_rate(45, 60, 60.0), 60);
+        assert_eq!(paced_frame_rate(60, 60, 144.0), 60);
+
+        // Never below the minimum, unless the cap is lower
+        assert_eq!(paced_frame_rate(60, 60, 2.0), MIN_PACED_FRAME_RATE);
+        assert_eq!(paced_frame_rate(60, 5, 2.0), 5);
+
+        // A lowered cap takes over
+        assert_eq!(paced_frame_rate(60, 30, 60.0), 30);
+    }
+}
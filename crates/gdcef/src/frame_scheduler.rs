@@ -0,0 +1,207 @@
+//! Shared frame scheduling for all `CefTexture` instances.
+//!
+//! CEF's message loop is pumped once per Godot frame no matter how many
+//! browsers exist, and each browser only receives an external begin frame
+//! when it is due at its target frame rate. Pages that have stopped painting
+//! are driven at a low idle rate until they paint or receive input again, so
+//! idle panels cost little. While every browser is in idle mode, the
+//! message loop itself is only pumped at a low rate.
+
+use godot::classes::Engine;
+use godot::prelude::*;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Begin frames without a paint after which a page is considered idle.
+const IDLE_AFTER_BEGIN_FRAMES: u32 = 30;
+
+/// Interval between begin frames for an idle page. Content changed by
+/// timers on an idle page shows up within this delay.
+const IDLE_BEGIN_FRAME_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Interval between message loop pumps while all browsers are idle.
+const IDLE_MESSAGE_LOOP_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Godot process frame in which the message loop was last pumped.
+static LAST_MESSAGE_LOOP_FRAME: AtomicU64 = AtomicU64::new(u64::MAX);
+
+/// Time the message loop was last pumped.
+static LAST_MESSAGE_LOOP_PUMP: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Runs `cef::do_message_loop_work` unless it already ran this Godot frame.
+pub fn pump_message_loop() {
+    let frame = Engine::singleton().get_process_frames();
+    if LAST_MESSAGE_LOOP_FRAME.swap(frame, Ordering::Relaxed) != frame {
+        if let Ok(mut last_pump) = LAST_MESSAGE_LOOP_PUMP.lock() {
+            *last_pump = Some(Instant::now());
+        }
+        cef::do_message_loop_work();
+    }
+}
+
+/// Pumps the message loop for an idle browser: only once per idle
+/// interval. A busy browser processed later in the frame still pumps it.
+pub fn pump_message_loop_idle() {
+    let last_pump = LAST_MESSAGE_LOOP_PUMP
+        .lock()
+        .ok()
+        .and_then(|last_pump| *last_pump);
+    if idle_pump_due(last_pump, Instant::now()) {
+        pump_message_loop();
+    }
+}
+
+fn idle_pump_due(last_pump: Option<Instant>, now: Instant) -> bool {
+    last_pump.is_none_or(|last| now.duration_since(last) >= IDLE_MESSAGE_LOOP_INTERVAL)
+}
+
+/// What drives a browser's frames.
+#[derive(GodotConvert, Var, Export, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[godot(via = i64)]
+pub enum FrameScheduling {
+    /// Godot sends a begin frame from its process loop when one is due, so
+    /// pages render in step with the game and idle pages are slowed down.
+    /// Rendering stops while the main thread is blocked.
+    #[default]
+    ExternalBeginFrame,
+    /// CEF schedules frames itself at the texture's frame rate. Pages keep
+    /// rendering, and their timers keep running, while the game stalls, at
+    /// the cost of a frame of latency and of frames Godot may not show.
+    CefInternal,
+}
+
+/// Decides when one browser receives an external begin frame.
+#[derive(Clone, Debug, Default)]
+pub struct BeginFrameScheduler {
+    last_begin_frame: Option<Instant>,
+    last_paint_sequence: u64,
+    idle_begin_frames: u32,
+}
+
+impl BeginFrameScheduler {
+    /// Marks the page as active again (input, resize, visibility change).
+    pub fn wake(&mut self) {
+        self.idle_begin_frames = 0;
+    }
+
+    /// Returns true if a begin frame should be sent now.
+    ///
+    /// `paint_sequence` changes with every view paint; any change since the
+    /// previous call means the page is still active.
+    /// `max_fps` of zero or less means no frame rate limit.
+    pub fn should_begin_frame(&mut self, now: Instant, paint_sequence: u64, max_fps: i32) -> bool {
+        if paint_sequence != self.last_paint_sequence {
+            self.last_paint_sequence = paint_sequence;
+            self.idle_begin_frames = 0;
+        }
+
+        let interval = if self.idle_begin_frames >= IDLE_AFTER_BEGIN_FRAMES {
+            IDLE_BEGIN_FRAME_INTERVAL
+        } else {
+            frame_interval(max_fps)
+        };
+        if let Some(last) = self.last_begin_frame
+            && now.duration_since(last) < interval
+        {
+            return false;
+        }
+
+        self.last_begin_frame = Some(now);
+        self.idle_begin_frames = self.idle_begin_frames.saturating_add(1);
+        true
+    }
+}
+
+/// Minimum time between begin frames for a target frame rate. A quarter of
+/// the period is allowed as slack so Godot's frame timing jitter does not
+/// skip frames when both run at the same rate.
+fn frame_interval(max_fps: i32) -> Duration {
+    if max_fps <= 0 {
+        return Duration::ZERO;
+    }
+    Duration::from_secs(1) * 3 / (4 * max_fps as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: Duration = Duration::from_micros(16_667);
+
+    #[test]
+    fn test_active_page_gets_a_begin_frame_every_frame() {
+        let mut scheduler = BeginFrameScheduler::default();
+        let start = Instant::now();
+        for frame in 0..120u32 {
+            // The page paints in response to every begin frame.
+            assert!(scheduler.should_begin_frame(start + FRAME * frame, frame as u64, 60));
+        }
+    }
+
+    #[test]
+    fn test_frame_rate_limit_skips_frames() {
+        let mut scheduler = BeginFrameScheduler::default();
+        let start = Instant::now();
+        let sent = (0..60u32)
+            .filter(|&frame| scheduler.should_begin_frame(start + FRAME * frame, frame as u64, 30))
+            .count();
+        assert_eq!(sent, 30);
+    }
+
+    #[test]
+    fn test_idle_page_is_throttled_until_it_paints_or_wakes() {
+        let mut scheduler = BeginFrameScheduler::default();
+        let start = Instant::now();
+        let mut now = start;
+        for _ in 0..IDLE_AFTER_BEGIN_FRAMES {
+            assert!(scheduler.should_begin_frame(now, 0, 60));
+            now += FRAME;
+        }
+
+        // Idle: roughly one begin frame per idle interval.
+        let sent = (0..60)
+            .filter(|_| {
+                now += FRAME;
+                scheduler.should_begin_frame(now, 0, 60)
+            })
+            .count();
+        assert!(
+            (9..=11).contains(&sent),
+            "sent {sent} begin frames while idle"
+        );
+
+        // A paint (e.g. from a timer) makes the page active again.
+        now += IDLE_BEGIN_FRAME_INTERVAL;
+        assert!(scheduler.should_begin_frame(now, 1, 60));
+        assert!(scheduler.should_begin_frame(now + FRAME, 1, 60));
+
+        // So does input.
+        let mut scheduler = BeginFrameScheduler {
+            idle_begin_frames: IDLE_AFTER_BEGIN_FRAMES,
+            last_begin_frame: Some(now),
+            ..Default::default()
+        };
+        assert!(!scheduler.should_begin_frame(now + FRAME, 0, 60));
+        scheduler.wake();
+        assert!(scheduler.should_begin_frame(now + FRAME, 0, 60));
+    }
+
+    #[test]
+    fn test_idle_pump_due() {
+        let start = Instant::now();
+        assert!(idle_pump_due(None, start));
+        assert!(!idle_pump_due(Some(start), start + FRAME * 5));
+        assert!(idle_pump_due(
+            Some(start),
+            start + IDLE_MESSAGE_LOOP_INTERVAL
+        ));
+    }
+
+    #[test]
+    fn test_frame_interval() {
+        assert_eq!(frame_interval(0), Duration::ZERO);
+        assert_eq!(frame_interval(-1), Duration::ZERO);
+        assert_eq!(frame_interval(60), Duration::from_nanos(12_500_000));
+    }
+}
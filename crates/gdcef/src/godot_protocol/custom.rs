@@ -0,0 +1,561 @@
+//! Application-defined schemes registered from GDScript.
+//!
+//! Custom schemes must be declared before CEF initializes (they are added in
+//! `on_register_custom_schemes` of every process), so registrations are kept
+//! in a static registry that `cef_init` consumes when building the app.
+//!
+//! A scheme is either mapped onto a folder in Godot's filesystem, in which
+//! case it is served exactly like `res://`, or answered by a GDScript
+//! `Callable`. Callables can only run on the main thread, so requests for
+//! callable schemes are queued from CEF's IO thread and completed
+//! asynchronously from [`process_pending_scheme_requests`] once per frame.
+
+use cef::{
+    CefStringUtf16, ImplCallback, ImplPostData, ImplPostDataElement, ImplRequest,
+    ImplResourceHandler, ImplResponse, ResourceHandler, WrapResourceHandler, rc::Rc,
+    wrap_resource_handler,
+};
+use godot::prelude::*;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use super::handler::{SchemeSource, register_scheme_source_on_context, status_text};
+use super::headers::{is_valid_header_name, is_valid_header_value};
+use super::mime::get_mime_type;
+use super::{GodotScheme, SchemeMapping};
+use crate::browser::EventQueuesHandle;
+
+/// Scheme names that are handled by Chromium itself or by the built-in handlers.
+const RESERVED_SCHEMES: &[&str] = &[
+    "about",
+    "blob",
+    "chrome",
+    "chrome-extension",
+    "data",
+    "devtools",
+    "file",
+    "filesystem",
+    "ftp",
+    "http",
+    "https",
+    "javascript",
+    "res",
+    "user",
+    "ws",
+    "wss",
+];
+
+/// Request headers forwarded to callable scheme handlers.
+const FORWARDED_REQUEST_HEADERS: &[&str] = &[
+    "Accept",
+    "Accept-Language",
+    "Content-Type",
+    "Origin",
+    "Range",
+    "Referer",
+    "User-Agent",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum CustomSchemeKind {
+    Folder(String),
+    Callable,
+}
+
+#[derive(Clone, Debug)]
+struct CustomScheme {
+    name: String,
+    kind: CustomSchemeKind,
+}
+
+static CUSTOM_SCHEMES: Mutex<Vec<CustomScheme>> = Mutex::new(Vec::new());
+
+static PENDING_REQUESTS: Mutex<VecDeque<PendingSchemeRequest>> = Mutex::new(VecDeque::new());
+
+thread_local! {
+    /// Callables are not thread-safe, so they stay on the main thread and only
+    /// the scheme name is shared with CEF's IO thread.
+    static SCHEME_CALLABLES: RefCell<HashMap<String, Callable>> = RefCell::new(HashMap::new());
+}
+
+/// Validates a scheme name according to RFC 3986 and rejects reserved names.
+fn validate_scheme_name(name: &str) -> Result<(), String> {
+    let mut chars = name.chars();
+    let Some(first) = chars.next() else {
+        return Err("scheme name is empty".to_string());
+    };
+
+    if !first.is_ascii_lowercase() {
+        return Err(format!(
+            "scheme '{}' must start with a lowercase ASCII letter",
+            name
+        ));
+    }
+
+    if !chars.all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || matches!(c, '+' | '-' | '.'))
+    {
+        return Err(format!(
+            "scheme '{}' may only contain lowercase letters, digits, '+', '-' and '.'",
+            name
+        ));
+    }
+
+    if RESERVED_SCHEMES.contains(&name) {
+        return Err(format!("scheme '{}' is reserved", name));
+    }
+
+    Ok(())
+}
+
+/// Validates the root folder of a folder-mapped scheme.
+fn validate_scheme_root(root: &str) -> Result<(), String> {
+    let scheme = if root.starts_with(GodotScheme::Res.prefix()) {
+        GodotScheme::Res
+    } else if root.starts_with(GodotScheme::User.prefix()) {
+        GodotScheme::User
+    } else {
+        return Err(format!(
+            "root '{}' must be a res:// or user:// folder",
+            root
+        ));
+    };
+
+    let relative = &root[scheme.prefix().len()..];
+    if relative
+        .replace('\\', "/")
+        .split('/')
+        .any(|component| component == "..")
+    {
+        return Err(format!("root '{}' must not contain '..'", root));
+    }
+
+    Ok(())
+}
+
+fn upsert_scheme(name: &str, kind: CustomSchemeKind) {
+    let mut schemes = CUSTOM_SCHEMES.lock().unwrap();
+    if let Some(existing) = schemes.iter_mut().find(|s| s.name == name) {
+        existing.kind = kind;
+    } else {
+        schemes.push(CustomScheme {
+            name: name.to_string(),
+            kind,
+        });
+    }
+}
+
+/// Registers a scheme whose URLs are served from `root` (a `res://` or `user://` folder).
+pub fn register_scheme_folder(scheme: &str, root: &str) -> Result<(), String> {
+    validate_scheme_name(scheme)?;
+    validate_scheme_root(root)?;
+
+    SCHEME_CALLABLES.with_borrow_mut(|callables| callables.remove(scheme));
+    upsert_scheme(scheme, CustomSchemeKind::Folder(root.to_string()));
+    Ok(())
+}
+
+/// Registers a scheme whose requests are answered by a GDScript `Callable`.
+///
+/// The callable receives `(method: String, url: String, headers: Dictionary,
+/// body: PackedByteArray)` and returns a Dictionary with optional `status`,
+/// `headers` and `body` (PackedByteArray or String) keys.
+pub fn register_scheme_callable(scheme: &str, handler: Callable) -> Result<(), String> {
+    validate_scheme_name(scheme)?;
+
+    if !handler.is_valid() {
+        return Err(format!(
+            "handler for scheme '{}' is not a valid Callable",
+            scheme
+        ));
+    }
+
+    SCHEME_CALLABLES.with_borrow_mut(|callables| {
+        callables.insert(scheme.to_string(), handler);
+    });
+    upsert_scheme(scheme, CustomSchemeKind::Callable);
+    Ok(())
+}
+
+/// Returns the names of all registered custom schemes.
+pub fn registered_custom_scheme_names() -> Vec<String> {
+    CUSTOM_SCHEMES
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|s| s.name.clone())
+        .collect()
+}
+
+/// Registers handler factories for all custom schemes on the given request context.
+pub fn register_custom_scheme_handlers_on_context(
+    context: &mut cef::RequestContext,
+    request_log: Option<EventQueuesHandle>,
+) {
+    let schemes = CUSTOM_SCHEMES.lock().unwrap().clone();
+
+    for scheme in schemes {
+        let source = match scheme.kind {
+            CustomSchemeKind::Folder(root) => {
+                SchemeSource::Files(SchemeMapping::new(&scheme.name, &root))
+            }
+            CustomSchemeKind::Callable => SchemeSource::Callable(scheme.name.clone()),
+        };
+        register_scheme_source_on_context(context, &scheme.name, source, request_log.clone());
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+struct CallableResponse {
+    status_code: i32,
+    mime_type: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl CallableResponse {
+    fn error(status_code: i32, message: String) -> Self {
+        Self {
+            status_code,
+            mime_type: "text/plain".to_string(),
+            headers: Vec::new(),
+            body: message.into_bytes(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct CallableExchange {
+    response: Option<CallableResponse>,
+    offset: usize,
+    canceled: bool,
+}
+
+/// A CEF callback that is sent to the main thread.
+///
+/// CEF callbacks are reference counted and thread-safe; `cont()` may be
+/// called from any thread.
+struct PendingCallback(cef::Callback);
+
+unsafe impl Send for PendingCallback {}
+
+struct PendingSchemeRequest {
+    scheme: String,
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    exchange: Arc<Mutex<CallableExchange>>,
+    callback: PendingCallback,
+}
+
+fn read_request_headers(request: &cef::Request) -> Vec<(String, String)> {
+    FORWARDED_REQUEST_HEADERS
+        .iter()
+        .filter_map(|name| {
+            let value = request.header_by_name(Some(&(*name).into()));
+            let value = CefStringUtf16::from(&value).to_string();
+            (!value.is_empty()).then(|| (name.to_string(), value))
+        })
+        .collect()
+}
+
+fn read_request_body(request: &cef::Request) -> Vec<u8> {
+    let Some(post_data) = request.post_data() else {
+        return Vec::new();
+    };
+
+    let mut elements = Vec::with_capacity(post_data.element_count());
+    post_data.elements(Some(&mut elements));
+
+    let mut body = Vec::new();
+    for element in elements.into_iter().flatten() {
+        let size = element.bytes_count();
+        if size == 0 {
+            continue;
+        }
+        let start = body.len();
+        body.resize(start + size, 0);
+        let read = element.bytes(size, body[start..].as_mut_ptr() as *mut _);
+        body.truncate(start + read);
+    }
+    body
+}
+
+#[derive(Clone)]
+pub struct CallableResourceHandler {
+    scheme: String,
+    exchange: Arc<Mutex<CallableExchange>>,
+}
+
+impl CallableResourceHandler {
+    pub fn new(scheme: String) -> Self {
+        Self {
+            scheme,
+            exchange: Arc::new(Mutex::new(CallableExchange::default())),
+        }
+    }
+}
+
+wrap_resource_handler! {
+    pub struct CallableResourceHandlerImpl {
+        handler: CallableResourceHandler,
+    }
+
+    impl ResourceHandler {
+        fn open(
+            &self,
+            request: Option<&mut cef::Request>,
+            handle_request: Option<&mut ::std::os::raw::c_int>,
+            callback: Option<&mut cef::Callback>,
+        ) -> ::std::os::raw::c_int {
+            let (Some(request), Some(callback)) = (request, callback) else {
+                return false as _;
+            };
+
+            let pending = PendingSchemeRequest {
+                scheme: self.handler.scheme.clone(),
+                method: CefStringUtf16::from(&request.method()).to_string(),
+                url: CefStringUtf16::from(&request.url()).to_string(),
+                headers: read_request_headers(request),
+                body: read_request_body(request),
+                exchange: self.handler.exchange.clone(),
+                callback: PendingCallback(callback.clone()),
+            };
+
+            if let Ok(mut queue) = PENDING_REQUESTS.lock() {
+                queue.push_back(pending);
+            }
+
+            // Handled asynchronously: the callback is continued from the main thread.
+            if let Some(handle_request) = handle_request {
+                *handle_request = false as _;
+            }
+
+            true as _
+        }
+
+        fn response_headers(
+            &self,
+            response: Option<&mut cef::Response>,
+            response_length: Option<&mut i64>,
+            _redirect_url: Option<&mut cef::CefStringUtf16>,
+        ) {
+            let Ok(exchange) = self.handler.exchange.lock() else {
+                return;
+            };
+            let fallback = CallableResponse::error(500, "No response".to_string());
+            let result = exchange.response.as_ref().unwrap_or(&fallback);
+
+            if let Some(response) = response {
+                response.set_status(result.status_code);
+                response.set_status_text(Some(&status_text(result.status_code).into()));
+                response.set_mime_type(Some(&result.mime_type.as_str().into()));
+
+                for (name, value) in &result.headers {
+                    response.set_header_by_name(
+                        Some(&name.as_str().into()),
+                        Some(&value.as_str().into()),
+                        true as _,
+                    );
+                }
+            }
+
+            if let Some(response_length) = response_length {
+                *response_length = result.body.len() as i64;
+            }
+        }
+
+        fn read(
+            &self,
+            data_out: *mut u8,
+            bytes_to_read: ::std::os::raw::c_int,
+            bytes_read: Option<&mut ::std::os::raw::c_int>,
+            _callback: Option<&mut cef::ResourceReadCallback>,
+        ) -> ::std::os::raw::c_int {
+            if data_out.is_null() {
+                return false as _;
+            }
+
+            let Ok(mut exchange) = self.handler.exchange.lock() else {
+                return false as _;
+            };
+            let offset = exchange.offset;
+            let Some(result) = exchange.response.as_ref() else {
+                return false as _;
+            };
+
+            let remaining = result.body.len().saturating_sub(offset);
+            if remaining == 0 {
+                if let Some(bytes_read) = bytes_read {
+                    *bytes_read = 0;
+                }
+                return false as _;
+            }
+
+            let to_copy = remaining.min(bytes_to_read.max(0) as usize);
+            unsafe {
+                std::ptr::copy_nonoverlapping(result.body.as_ptr().add(offset), data_out, to_copy);
+            }
+            exchange.offset += to_copy;
+
+            if let Some(bytes_read) = bytes_read {
+                *bytes_read = to_copy as _;
+            }
+
+            true as _
+        }
+
+        fn cancel(&self) {
+            if let Ok(mut exchange) = self.handler.exchange.lock() {
+                exchange.canceled = true;
+            }
+        }
+    }
+}
+
+impl CallableResourceHandlerImpl {
+    pub fn build(handler: CallableResourceHandler) -> ResourceHandler {
+        Self::new(handler)
+    }
+}
+
+fn headers_to_dictionary(headers: &[(String, String)]) -> VarDictionary {
+    let mut dict = VarDictionary::new();
+    for (name, value) in headers {
+        dict.set(name.as_str(), value.as_str());
+    }
+    dict
+}
+
+fn response_from_variant(result: &Variant, url: &str) -> CallableResponse {
+    let Ok(dict) = result.try_to::<VarDictionary>() else {
+        return CallableResponse::error(500, "Scheme handler must return a Dictionary".to_string());
+    };
+
+    let status_code = dict
+        .get("status")
+        .and_then(|v| v.try_to::<i64>().ok())
+        .unwrap_or(200) as i32;
+
+    let body = match dict.get("body") {
+        Some(v) if v.get_type() == VariantType::PACKED_BYTE_ARRAY => {
+            v.to::<PackedByteArray>().to_vec()
+        }
+        Some(v) if !v.is_nil() => v.stringify().to_string().into_bytes(),
+        _ => Vec::new(),
+    };
+
+    let mut headers = Vec::new();
+    let mut mime_type = None;
+    if let Some(header_dict) = dict
+        .get("headers")
+        .and_then(|v| v.try_to::<VarDictionary>().ok())
+    {
+        for (name, value) in header_dict.iter_shared() {
+            let name = name.stringify().to_string();
+            let value = value.stringify().to_string();
+            if !is_valid_header_name(&name) || !is_valid_header_value(&value) {
+                godot::global::godot_warn!(
+                    "[GodotCef] Ignoring invalid response header '{}' from scheme handler for {}",
+                    name.escape_debug(),
+                    url
+                );
+                continue;
+            }
+            if name.eq_ignore_ascii_case("Content-Type") {
+                mime_type = Some(value.clone());
+            }
+            headers.push((name, value));
+        }
+    }
+
+    let mime_type = mime_type.unwrap_or_else(|| {
+        let path = url.split(['?', '#']).next().unwrap_or(url);
+        let extension = path.rsplit_once('.').map(|(_, ext)| ext).unwrap_or("");
+        get_mime_type(extension)
+    });
+
+    CallableResponse {
+        status_code,
+        mime_type,
+        headers,
+        body,
+    }
+}
+
+/// Answers queued requests for callable schemes. Must be called on the main thread.
+pub fn process_pending_scheme_requests() {
+    let pending: Vec<PendingSchemeRequest> = match PENDING_REQUESTS.lock() {
+        Ok(mut queue) => queue.drain(..).collect(),
+        Err(_) => return,
+    };
+
+    for request in pending {
+        let callable =
+            SCHEME_CALLABLES.with_borrow(|callables| callables.get(&request.scheme).cloned());
+
+        let response = match callable {
+            Some(callable) => {
+                let args = [
+                    request.method.to_variant(),
+                    request.url.to_variant(),
+                    headers_to_dictionary(&request.headers).to_variant(),
+                    PackedByteArray::from(request.body.as_slice()).to_variant(),
+                ];
+                response_from_variant(&callable.call(&args), &request.url)
+            }
+            None => CallableResponse::error(
+                404,
+                format!("No handler registered for scheme: {}", request.scheme),
+            ),
+        };
+
+        let Ok(mut exchange) = request.exchange.lock() else {
+            continue;
+        };
+        if exchange.canceled {
+            continue;
+        }
+        exchange.response = Some(response);
+        drop(exchange);
+
+        request.callback.0.cont();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_scheme_name() {
+        assert!(validate_scheme_name("app").is_ok());
+        assert!(validate_scheme_name("game-data").is_ok());
+        assert!(validate_scheme_name("mods.v2+local").is_ok());
+
+        assert!(validate_scheme_name("").is_err());
+        assert!(validate_scheme_name("App").is_err());
+        assert!(validate_scheme_name("1app").is_err());
+        assert!(validate_scheme_name("my app").is_err());
+        assert!(validate_scheme_name("app://").is_err());
+    }
+
+    #[test]
+    fn test_rejects_reserved_schemes() {
+        for scheme in ["http", "https", "file", "res", "user", "javascript", "data"] {
+            assert!(validate_scheme_name(scheme).is_err(), "{scheme}");
+        }
+    }
+
+    #[test]
+    fn test_validate_scheme_root() {
+        assert!(validate_scheme_root("res://web").is_ok());
+        assert!(validate_scheme_root("user://mods/").is_ok());
+
+        assert!(validate_scheme_root("/etc").is_err());
+        assert!(validate_scheme_root("C:\\games").is_err());
+        assert!(validate_scheme_root("res://web/../secrets").is_err());
+        assert!(validate_scheme_root("user://..\\outside").is_err());
+    }
+}
@@ -5,8 +5,8 @@
 
 use cef::{
     CefStringUtf16, ImplRequest, ImplResourceHandler, ImplResponse, ImplSchemeHandlerFactory,
-    ResourceHandler, SchemeHandlerFactory, WrapResourceHandler, WrapSchemeHandlerFactory, rc::Rc,
-    wrap_resource_handler, wrap_scheme_handler_factory,
+    ResourceHandler, ResourceType, SchemeHandlerFactory, WrapResourceHandler,
+    WrapSchemeHandlerFactory, rc::Rc, wrap_resource_handler, wrap_scheme_handler_factory,
 };
 use godot::classes::FileAccess;
 use godot::classes::file_access::ModeFlags;
@@ -14,14 +14,21 @@ use godot::prelude::*;
 use percent_encoding::percent_decode_str;
 use std::cell::RefCell;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 use url::Url;
 
-use super::GodotScheme;
+use super::custom::{CallableResourceHandler, CallableResourceHandlerImpl};
+use super::headers::response_header_config;
+use super::imported::{IMPORT_HINT_HEADER, ImportedResource, load_imported_resource};
 use super::mime::get_mime_type;
 use super::multipart::{
     MULTIPART_BOUNDARY, MultipartStreamState, read_multipart_streaming, skip_multipart_streaming,
 };
 use super::range::{ParsedRanges, parse_range_header};
+use super::stream::{FileStreamState, read_file_streaming, should_stream};
+use super::{GodotScheme, SchemeMapping};
+use crate::browser::{EventKind, EventQueuesHandle, SchemeRequestEvent};
 
 /// Validate that a string contains only valid percent-encoded sequences.
 ///
@@ -76,7 +83,8 @@ fn contains_path_traversal(decoded_path: &str) -> bool {
     false
 }
 
-/// Parse a URL into a Godot filesystem path.
+/// Validate a URL of a folder-mapped scheme and join its path onto the
+/// mapping's root folder, keeping any trailing `/`.
 ///
 /// Uses the `percent-encoding` crate for robust percent-decoding. Returns `None`
 /// if the URL contains path traversal patterns, invalid encoding, or other
@@ -86,7 +94,7 @@ fn contains_path_traversal(decoded_path: &str) -> bool {
 /// standard URL authority rules - everything after `://` is treated as the path,
 /// not as host + path. We intentionally do NOT use URL normalization to avoid
 /// silently resolving `..` traversal patterns.
-pub(crate) fn parse_godot_url(url_str: &str, scheme: GodotScheme) -> Option<String> {
+fn parse_mapped_path(url_str: &str, mapping: &SchemeMapping) -> Option<String> {
     if contains_path_traversal_encoded(url_str) {
         return None;
     }
@@ -94,7 +102,7 @@ pub(crate) fn parse_godot_url(url_str: &str, scheme: GodotScheme) -> Option<Stri
     // For custom schemes like res://, the url crate treats the first path
     // component as a host, so we reconstruct the full path ourselves.
     let path_encoded = if let Ok(parsed_url) = Url::parse(url_str) {
-        if parsed_url.scheme() == scheme.name() {
+        if parsed_url.scheme() == mapping.name() {
             let host = parsed_url.host_str().unwrap_or("");
             let url_path = parsed_url.path();
 
@@ -104,13 +112,12 @@ pub(crate) fn parse_godot_url(url_str: &str, scheme: GodotScheme) -> Option<Stri
                 // When the URL is of the form `res://file.html?v=1` or `user://data.json#frag`,
                 // `Url::parse` will treat `file.html`/`data.json` as the host and `/` as the path.
                 // In those cases, we must *not* append a trailing slash, otherwise we end up with
-                // `file.html/` which `finalize_godot_path` then treats as a directory.
+                // `file.html/` which `resolve_read_path` then treats as a directory.
                 if url_path == "/" {
                     // Reconstruct what was after `scheme://` up to any `?` or `#` to see if there
                     // was an explicit trailing slash in the original URL.
-                    let scheme_prefix = format!("{}://", scheme.name());
                     let had_trailing_slash = url_str
-                        .strip_prefix(&scheme_prefix)
+                        .strip_prefix(&mapping.prefix())
                         .map(|rest| {
                             let end = rest.find(['?', '#']).unwrap_or(rest.len());
                             rest[..end].ends_with('/')
@@ -128,10 +135,10 @@ pub(crate) fn parse_godot_url(url_str: &str, scheme: GodotScheme) -> Option<Stri
                 }
             }
         } else {
-            return parse_godot_url_manual(url_str, scheme);
+            return parse_godot_url_manual(url_str, mapping);
         }
     } else {
-        return parse_godot_url_manual(url_str, scheme);
+        return parse_godot_url_manual(url_str, mapping);
     };
 
     if !has_valid_percent_encoding(&path_encoded) {
@@ -151,11 +158,11 @@ pub(crate) fn parse_godot_url(url_str: &str, scheme: GodotScheme) -> Option<Stri
         return None;
     }
 
-    finalize_godot_path(&path, scheme)
+    join_mapped_path(&path, mapping)
 }
 
 /// Manual URL parsing fallback for relative paths or when URL crate fails.
-fn parse_godot_url_manual(url_str: &str, scheme: GodotScheme) -> Option<String> {
+fn parse_godot_url_manual(url_str: &str, mapping: &SchemeMapping) -> Option<String> {
     if contains_path_traversal_encoded(url_str) {
         return None;
     }
@@ -171,8 +178,8 @@ fn parse_godot_url_manual(url_str: &str, scheme: GodotScheme) -> Option<String>
         .unwrap_or(url_without_query);
 
     let path_encoded = url_clean
-        .strip_prefix(scheme.prefix())
-        .or_else(|| url_clean.strip_prefix(scheme.short_prefix()))
+        .strip_prefix(&mapping.prefix())
+        .or_else(|| url_clean.strip_prefix(&mapping.short_prefix()))
         .unwrap_or(url_clean);
 
     if !has_valid_percent_encoding(path_encoded) {
@@ -192,29 +199,127 @@ fn parse_godot_url_manual(url_str: &str, scheme: GodotScheme) -> Option<String>
         return None;
     }
 
-    finalize_godot_path(&path, scheme)
+    join_mapped_path(&path, mapping)
 }
 
-/// Finalize a decoded path into a full Godot path, adding index.html if needed.
-fn finalize_godot_path(path: &str, scheme: GodotScheme) -> Option<String> {
+/// Join a decoded path onto the mapping's root folder, without any rewriting.
+fn join_mapped_path(path: &str, mapping: &SchemeMapping) -> Option<String> {
     let path = path.strip_prefix('/').unwrap_or(path);
-    let mut full_path = format!("{}{}", scheme.prefix(), path);
+    Some(format!("{}{}", mapping.root(), path))
+}
+
+/// Whether requests for a folder without a trailing `/` are served its
+/// `index.html`. Off keeps the historical behaviour of treating every
+/// extensionless path as a folder.
+static DIRECTORY_INDEX_REQUIRES_TRAILING_SLASH: AtomicBool = AtomicBool::new(false);
+
+/// Sets whether only URLs ending in `/` are served the folder's `index.html`.
+pub fn set_directory_index_requires_trailing_slash(required: bool) {
+    DIRECTORY_INDEX_REQUIRES_TRAILING_SLASH.store(required, Ordering::Relaxed);
+}
+
+/// Returns true for the methods that only read, which are the only ones
+/// rewritten to a folder's `index.html`.
+fn is_read_method(method: &str) -> bool {
+    method.is_empty() || method.eq_ignore_ascii_case("GET") || method.eq_ignore_ascii_case("HEAD")
+}
+
+/// Resolve a read request for a Godot path, appending `index.html` for
+/// folders.
+///
+/// A path that exists as a file is served as is, so a file named like a
+/// folder is never shadowed by an `index.html` next to it. Otherwise paths
+/// ending in `/` and the scheme root get the index; extensionless paths only
+/// do when `require_trailing_slash` is off.
+fn resolve_read_path(
+    full_path: &str,
+    mapping: &SchemeMapping,
+    require_trailing_slash: bool,
+    is_file: impl Fn(&str) -> bool,
+) -> String {
+    let explicit_folder = full_path.ends_with('/') || full_path == mapping.root();
+    if !explicit_folder && is_file(full_path) {
+        return full_path.to_string();
+    }
 
-    // Determine whether the last path component (ignoring trailing '/')
-    // has an extension (i.e., contains a dot). This avoids treating dots
-    // in parent directory names as file extensions.
-    let trimmed = full_path.trim_end_matches('/');
-    let last_segment = trimmed.rsplit('/').next().unwrap_or("");
+    // Only the last path component counts, so dots in parent folder names
+    // are not mistaken for file extensions.
+    let last_segment = full_path.rsplit('/').next().unwrap_or("");
     let has_extension = last_segment.contains('.');
 
-    if full_path.ends_with('/') || !has_extension || full_path.ends_with(scheme.prefix()) {
-        if !full_path.ends_with('/') {
-            full_path.push('/');
+    if explicit_folder || (!require_trailing_slash && !has_extension) {
+        let mut index_path = full_path.to_string();
+        if !index_path.ends_with('/') {
+            index_path.push('/');
         }
-        full_path.push_str("index.html");
+        index_path.push_str("index.html");
+        return index_path;
+    }
+
+    full_path.to_string()
+}
+
+/// Returns the first parent of `full_path` below the mapping's root that
+/// exists as a file, which makes writing `full_path` impossible.
+fn find_file_parent(
+    full_path: &str,
+    mapping: &SchemeMapping,
+    is_file: impl Fn(&str) -> bool,
+) -> Option<String> {
+    let relative = full_path.strip_prefix(mapping.root())?;
+    let relative = relative.trim_end_matches('/');
+
+    relative
+        .match_indices('/')
+        .map(|(index, _)| format!("{}{}", mapping.root(), &relative[..index]))
+        .find(|parent| is_file(parent))
+}
+
+/// Resolve the Godot path a request with `method` should use.
+///
+/// Reads go through [`resolve_read_path`]; any other method uses the exact
+/// path, and fails with the conflicting parent when a parent component is
+/// an existing file (answered with 409 Conflict).
+fn resolve_request_path(
+    full_path: &str,
+    mapping: &SchemeMapping,
+    method: &str,
+    require_trailing_slash: bool,
+    is_file: impl Fn(&str) -> bool,
+) -> Result<String, String> {
+    if is_read_method(method) {
+        return Ok(resolve_read_path(
+            full_path,
+            mapping,
+            require_trailing_slash,
+            is_file,
+        ));
     }
 
-    Some(full_path)
+    match find_file_parent(full_path, mapping, is_file) {
+        Some(parent) => Err(parent),
+        None => Ok(full_path.to_string()),
+    }
+}
+
+/// Returns the reason phrase for the status codes produced by the scheme handlers.
+pub(super) fn status_text(status_code: i32) -> &'static str {
+    match status_code {
+        200 => "OK",
+        204 => "No Content",
+        206 => "Partial Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        416 => "Range Not Satisfiable",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
 }
 
 #[derive(Clone, Default)]
@@ -232,19 +337,123 @@ struct ResourceState {
     multipart_stream: Option<MultipartStreamState>,
     file_path: Option<String>,
     open_file: Option<Gd<FileAccess>>,
+    file_stream: Option<FileStreamState>,
+    request_url: String,
+    request_method: String,
+    started_at: Option<Instant>,
+    bytes_served: u64,
+    request_logged: bool,
+    is_head: bool,
+    /// Length a GET would have returned, reported for HEAD requests.
+    head_content_length: Option<u64>,
+    /// Value of the `X-Godot-Import-Hint` header.
+    import_hint: Option<String>,
+}
+
+fn set_error_response(state: &mut ResourceState, status_code: i32, message: String) {
+    state.status_code = status_code;
+    state.mime_type = "text/plain".to_string();
+    state.response_content_type = "text/plain".to_string();
+    state.data = message.as_bytes().to_vec();
+    state.error_message = Some(message);
+}
+
+/// Returns true if `url` belongs to a page served from Godot's filesystem.
+fn is_local_origin(url: &str) -> bool {
+    let url = url.trim_start();
+    ["res:", "user:"].iter().any(|scheme| {
+        url.get(..scheme.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(scheme))
+    })
+}
+
+/// Decides whether a request may be served by a scheme restricted to local
+/// origins.
+///
+/// The initiator is taken from the `Origin` header, falling back to the
+/// referrer. Requests without any initiator are only allowed for top-level
+/// navigations, which is how the application itself loads pages; a
+/// subresource without a referrer may come from a page that suppressed it.
+fn is_allowed_initiator(origin: &str, referrer: &str, is_main_frame: bool) -> bool {
+    let initiator = if origin.is_empty() || origin == "null" {
+        referrer
+    } else {
+        origin
+    };
+    if initiator.is_empty() {
+        is_main_frame
+    } else {
+        is_local_origin(initiator)
+    }
+}
+
+/// Number of body bytes the prepared response will deliver.
+fn body_length(state: &ResourceState) -> u64 {
+    // For streaming multipart responses, use pre-calculated total size
+    if let Some(ref stream) = state.multipart_stream {
+        stream.total_size
+    } else if let Some(ref stream) = state.file_stream {
+        stream.remaining()
+    } else {
+        state.data.len() as u64
+    }
+}
+
+/// Turns a prepared GET response into its HEAD equivalent: the headers are
+/// kept and the GET length is remembered for `Content-Length`, while the body
+/// and any open file are dropped, so the response length is 0.
+fn finish_head_response(state: &mut ResourceState) {
+    state.head_content_length = Some(body_length(state));
+    state.data = Vec::new();
+    state.offset = 0;
+    state.multipart_stream = None;
+    state.file_stream = None;
+    state.open_file = None;
 }
 
 #[derive(Clone)]
 pub struct GodotResourceHandler {
     state: RefCell<ResourceState>,
-    scheme: GodotScheme,
+    mapping: SchemeMapping,
+    request_log: Option<EventQueuesHandle>,
 }
 
 impl GodotResourceHandler {
-    pub fn new(scheme: GodotScheme) -> Self {
+    pub fn new(mapping: SchemeMapping, request_log: Option<EventQueuesHandle>) -> Self {
         Self {
             state: RefCell::new(ResourceState::default()),
-            scheme,
+            mapping,
+            request_log,
+        }
+    }
+
+    /// Records the finished request once, if request logging is enabled.
+    fn log_completed_request(&self, state: &mut ResourceState) {
+        let Some(request_log) = &self.request_log else {
+            return;
+        };
+        if state.request_logged {
+            return;
+        }
+        state.request_logged = true;
+
+        let event = SchemeRequestEvent {
+            url: state.request_url.clone(),
+            method: state.request_method.clone(),
+            resolved_path: state.file_path.clone().unwrap_or_default(),
+            status_code: state.status_code,
+            bytes_served: state.bytes_served,
+            duration_ms: state
+                .started_at
+                .map(|started| started.elapsed().as_secs_f64() * 1000.0)
+                .unwrap_or(0.0),
+            is_range: state.status_code == 206,
+            is_multipart: state.is_multipart,
+        };
+
+        if let Ok(mut queues) = request_log.lock() {
+            let outcome = queues.scheme_requests.push_back(event);
+            queues.record(EventKind::SchemeRequest, outcome);
         }
     }
 }
@@ -270,21 +479,65 @@ wrap_resource_handler! {
 
             let mut state = self.handler.state.borrow_mut();
 
+            state.request_method = CefStringUtf16::from(&request.method()).to_string();
+            state.is_head = state.request_method.eq_ignore_ascii_case("HEAD");
+            if self.handler.request_log.is_some() {
+                state.started_at = Some(Instant::now());
+                state.request_url = url.clone();
+            }
+
+            if self.handler.mapping.local_origin_only() {
+                let origin = request.header_by_name(Some(&"Origin".into()));
+                let referrer = request.referrer_url();
+                let allowed = is_allowed_initiator(
+                    &CefStringUtf16::from(&origin).to_string(),
+                    &CefStringUtf16::from(&referrer).to_string(),
+                    request.resource_type() == ResourceType::MAIN_FRAME,
+                );
+                if !allowed {
+                    set_error_response(&mut state, 403, "Forbidden: Request from a non-local origin".to_string());
+
+                    if state.is_head {
+                        finish_head_response(&mut state);
+                    }
+                    if let Some(handle_request) = handle_request {
+                        *handle_request = true as _;
+                    }
+                    return true as _;
+                }
+            }
+
             // Reject paths with traversal patterns (returns 403 Forbidden)
-            let godot_path = match parse_godot_url(&url, self.handler.scheme) {
+            let requested_path = match parse_mapped_path(&url, &self.handler.mapping) {
                 Some(path) => path,
                 None => {
-                    state.status_code = 403;
-                    state.mime_type = "text/plain".to_string();
-                    state.response_content_type = "text/plain".to_string();
-                    state.error_message = Some("Forbidden: Invalid path".to_string());
-                    state.data = state
-                        .error_message
-                        .as_ref()
-                        .unwrap()
-                        .as_bytes()
-                        .to_vec();
+                    set_error_response(&mut state, 403, "Forbidden: Invalid path".to_string());
+
+                    if state.is_head {
+                        finish_head_response(&mut state);
+                    }
+                    if let Some(handle_request) = handle_request {
+                        *handle_request = true as _;
+                    }
+                    return true as _;
+                }
+            };
 
+            let godot_path = match resolve_request_path(
+                &requested_path,
+                &self.handler.mapping,
+                &state.request_method,
+                DIRECTORY_INDEX_REQUIRES_TRAILING_SLASH.load(Ordering::Relaxed),
+                |path| FileAccess::file_exists(&GString::from(path)),
+            ) {
+                Ok(path) => path,
+                Err(parent) => {
+                    state.file_path = Some(requested_path.clone());
+                    set_error_response(
+                        &mut state,
+                        409,
+                        format!("Conflict: {} is a file", parent),
+                    );
                     if let Some(handle_request) = handle_request {
                         *handle_request = true as _;
                     }
@@ -292,19 +545,34 @@ wrap_resource_handler! {
                 }
             };
             let gstring_path = GString::from(&godot_path);
+            state.file_path = Some(godot_path.clone());
 
             if !FileAccess::file_exists(&gstring_path) {
-                state.status_code = 404;
-                state.mime_type = "text/plain".to_string();
-                state.response_content_type = "text/plain".to_string();
-                state.error_message = Some(format!("File not found: {}", godot_path));
-                state.data = state
-                    .error_message
-                    .as_ref()
-                    .unwrap()
-                    .as_bytes()
-                    .to_vec();
+                let imported = (is_read_method(&state.request_method)
+                    && godot_path.starts_with(GodotScheme::Res.prefix()))
+                .then(|| load_imported_resource(&godot_path))
+                .flatten();
+                match imported {
+                    Some(ImportedResource::Png(data)) => {
+                        // Ranges are ignored, the whole image is sent with 200
+                        state.mime_type = get_mime_type("png");
+                        state.response_content_type = state.mime_type.clone();
+                        state.total_file_size = data.len() as u64;
+                        state.data = data;
+                        state.status_code = 200;
+                    }
+                    Some(ImportedResource::Unavailable(hint)) => {
+                        set_error_response(&mut state, 404, format!("File not found: {}", godot_path));
+                        state.import_hint = Some(hint);
+                    }
+                    None => {
+                        set_error_response(&mut state, 404, format!("File not found: {}", godot_path));
+                    }
+                }
 
+                if state.is_head {
+                    finish_head_response(&mut state);
+                }
                 if let Some(handle_request) = handle_request {
                     *handle_request = true as _;
                 }
@@ -324,7 +592,7 @@ wrap_resource_handler! {
                         .extension()
                         .and_then(|e| e.to_str())
                         .unwrap_or("");
-                    state.mime_type = get_mime_type(extension).to_string();
+                    state.mime_type = get_mime_type(extension);
                     state.response_content_type = state.mime_type.clone();
 
                     // Parse `Range` header. Supports single ranges ("bytes=start-end",
@@ -340,10 +608,17 @@ wrap_resource_handler! {
                                 state.is_multipart = false;
                             } else {
                                 let content_size_u64 = range.end.saturating_sub(range.start).saturating_add(1);
-                                let content_size = i64::try_from(content_size_u64).unwrap_or(i64::MAX);
-                                file.seek(range.start);
-                                let buffer = file.get_buffer(content_size);
-                                state.data = buffer.as_slice().to_vec();
+                                if state.is_head || should_stream(content_size_u64) {
+                                    // Large ranges (e.g. `bytes=0-` from media elements) are streamed
+                                    state.data = Vec::new();
+                                    state.file_stream = Some(FileStreamState::new(range.start, content_size_u64));
+                                    state.open_file = Some(file);
+                                } else {
+                                    let content_size = i64::try_from(content_size_u64).unwrap_or(i64::MAX);
+                                    file.seek(range.start);
+                                    let buffer = file.get_buffer(content_size);
+                                    state.data = buffer.as_slice().to_vec();
+                                }
                                 state.status_code = 206;
                                 state.range_start = Some(range.start);
                                 state.range_end = Some(range.end);
@@ -366,15 +641,21 @@ wrap_resource_handler! {
                             state.range_start = None;
                             state.range_end = None;
                             state.is_multipart = true;
-                            state.file_path = Some(godot_path.clone());
                             state.multipart_stream = Some(stream_state);
                             state.data = Vec::new(); // Data will be streamed, not buffered
                             state.offset = 0;
                         }
                         None => {
-                            let buffer_size = i64::try_from(file_size).unwrap_or(i64::MAX);
-                            let buffer = file.get_buffer(buffer_size);
-                            state.data = buffer.as_slice().to_vec();
+                            if state.is_head || should_stream(file_size) {
+                                // Keep the file open and stream it from `read()`
+                                state.data = Vec::new();
+                                state.file_stream = Some(FileStreamState::new(0, file_size));
+                                state.open_file = Some(file);
+                            } else {
+                                let buffer_size = i64::try_from(file_size).unwrap_or(i64::MAX);
+                                let buffer = file.get_buffer(buffer_size);
+                                state.data = buffer.as_slice().to_vec();
+                            }
                             state.status_code = 200;
                             state.range_start = None;
                             state.range_end = None;
@@ -384,19 +665,15 @@ wrap_resource_handler! {
                     }
                 }
                 None => {
-                    state.status_code = 500;
-                    state.mime_type = "text/plain".to_string();
-                    state.response_content_type = "text/plain".to_string();
-                    state.error_message = Some(format!("Failed to open file: {}", godot_path));
-                    state.data = state
-                        .error_message
-                        .as_ref()
-                        .unwrap()
-                        .as_bytes()
-                        .to_vec();
+                    set_error_response(&mut state, 500, format!("Failed to open file: {}", godot_path));
                 }
             }
 
+            // HEAD responses report the GET length but never send a body
+            if state.is_head {
+                finish_head_response(&mut state);
+            }
+
             if let Some(handle_request) = handle_request {
                 *handle_request = true as _;
             }
@@ -410,28 +687,33 @@ wrap_resource_handler! {
             response_length: Option<&mut i64>,
             _redirect_url: Option<&mut cef::CefStringUtf16>,
         ) {
-            let state = self.handler.state.borrow();
+            let mut state = self.handler.state.borrow_mut();
 
             if let Some(response) = response {
                 response.set_status(state.status_code);
 
-                let status_text = match state.status_code {
-                    200 => "OK",
-                    206 => "Partial Content",
-                    403 => "Forbidden",
-                    404 => "Not Found",
-                    416 => "Range Not Satisfiable",
-                    500 => "Internal Server Error",
-                    _ => "Unknown",
-                };
-                response.set_status_text(Some(&status_text.into()));
+                response.set_status_text(Some(&status_text(state.status_code).into()));
 
                 response.set_mime_type(Some(&state.response_content_type.as_str().into()));
 
                 response.set_header_by_name(Some(&"Content-Type".into()), Some(&state.response_content_type.as_str().into()), true as _);
-                response.set_header_by_name(Some(&"Access-Control-Allow-Origin".into()), Some(&"*".into()), true as _);
                 response.set_header_by_name(Some(&"Accept-Ranges".into()), Some(&"bytes".into()), true as _);
 
+                let header_config = response_header_config();
+                if let Some(origin) = header_config.allow_origin_for(self.handler.mapping.root()) {
+                    response.set_header_by_name(Some(&"Access-Control-Allow-Origin".into()), Some(&origin.into()), true as _);
+                }
+                for (name, value) in &header_config.extra_headers {
+                    response.set_header_by_name(Some(&name.as_str().into()), Some(&value.as_str().into()), true as _);
+                }
+                if let Some(hint) = &state.import_hint {
+                    response.set_header_by_name(Some(&IMPORT_HINT_HEADER.into()), Some(&hint.as_str().into()), true as _);
+                }
+                // HEAD reports the GET length as a header; the response itself is empty
+                if let Some(length) = state.head_content_length {
+                    response.set_header_by_name(Some(&"Content-Length".into()), Some(&length.to_string().as_str().into()), true as _);
+                }
+
                 if state.status_code == 206 && !state.is_multipart {
                     if let (Some(start), Some(end)) = (state.range_start, state.range_end) {
                         let value: CefStringUtf16 = format!("bytes {}-{}/{}", start, end, state.total_file_size).as_str().into();
@@ -444,11 +726,11 @@ wrap_resource_handler! {
             }
 
             if let Some(response_length) = response_length {
-                // For streaming multipart responses, use pre-calculated total size
-                if let Some(ref stream) = state.multipart_stream {
-                    *response_length = stream.total_size as i64;
-                } else {
-                    *response_length = state.data.len() as i64;
+                *response_length = body_length(&state) as i64;
+
+                // Empty responses are never read, so they complete here
+                if *response_length == 0 {
+                    self.handler.log_completed_request(&mut state);
                 }
             }
         }
@@ -490,6 +772,43 @@ wrap_resource_handler! {
                     bytes_to_read,
                 );
 
+                state.bytes_served += written as u64;
+                if written == 0 {
+                    self.handler.log_completed_request(&mut state);
+                }
+
+                if let Some(bytes_read) = bytes_read {
+                    *bytes_read = written as _;
+                }
+
+                return (written > 0) as _;
+            }
+
+            // Handle streaming single-body responses
+            if state.file_stream.is_some() {
+                let ResourceState {
+                    file_stream,
+                    open_file,
+                    ..
+                } = &mut *state;
+
+                let written = match (file_stream.as_mut(), open_file.as_mut()) {
+                    (Some(stream), Some(file)) => {
+                        read_file_streaming(stream, file, data_out, bytes_to_read)
+                    }
+                    _ => 0,
+                };
+
+                if written == 0 {
+                    // Stream complete: release the file handle as early as possible
+                    *open_file = None;
+                }
+
+                state.bytes_served += written as u64;
+                if written == 0 {
+                    self.handler.log_completed_request(&mut state);
+                }
+
                 if let Some(bytes_read) = bytes_read {
                     *bytes_read = written as _;
                 }
@@ -501,6 +820,7 @@ wrap_resource_handler! {
             let remaining = state.data.len().saturating_sub(state.offset);
 
             if remaining == 0 {
+                self.handler.log_completed_request(&mut state);
                 if let Some(bytes_read) = bytes_read {
                     *bytes_read = 0;
                 }
@@ -518,6 +838,7 @@ wrap_resource_handler! {
             }
 
             state.offset += to_copy;
+            state.bytes_served += to_copy as u64;
 
             if let Some(bytes_read) = bytes_read {
                 *bytes_read = to_copy as _;
@@ -556,6 +877,17 @@ wrap_resource_handler! {
                 return true as _;
             }
 
+            // Handle streaming single-body responses; `read()` seeks lazily
+            if let Some(stream) = state.file_stream.as_mut() {
+                let skipped = stream.advance(bytes_to_skip as u64);
+
+                if let Some(bytes_skipped) = bytes_skipped {
+                    *bytes_skipped = skipped as i64;
+                }
+
+                return true as _;
+            }
+
             // Handle buffered (non-streaming) responses
             let remaining = state.data.len().saturating_sub(state.offset);
             let to_skip = remaining.min(bytes_to_skip);
@@ -572,11 +904,12 @@ wrap_resource_handler! {
         fn cancel(&self) {
             let mut state = self.handler.state.borrow_mut();
 
-            // If a multipart stream is active, explicitly release its resources
-            if state.multipart_stream.is_some() {
-                state.multipart_stream = None;
-                state.open_file = None;
-            }
+            // If a stream is active, explicitly release its resources and close the file
+            state.multipart_stream = None;
+            state.file_stream = None;
+            state.open_file = None;
+
+            self.handler.log_completed_request(&mut state);
         }
     }
 }
@@ -587,14 +920,27 @@ impl GodotResourceHandlerImpl {
     }
 }
 
+/// Where the responses of a registered scheme come from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum SchemeSource {
+    /// Files served from a folder in Godot's filesystem.
+    Files(SchemeMapping),
+    /// Responses produced by a GDScript `Callable` registered under this scheme name.
+    Callable(String),
+}
+
 #[derive(Clone)]
 pub struct GodotSchemeHandler {
-    scheme: GodotScheme,
+    source: SchemeSource,
+    request_log: Option<EventQueuesHandle>,
 }
 
 impl GodotSchemeHandler {
-    pub fn new(scheme: GodotScheme) -> Self {
-        Self { scheme }
+    pub(crate) fn new(source: SchemeSource, request_log: Option<EventQueuesHandle>) -> Self {
+        Self {
+            source,
+            request_log,
+        }
     }
 }
 
@@ -611,7 +957,14 @@ wrap_scheme_handler_factory! {
             _scheme_name: Option<&cef::CefString>,
             _request: Option<&mut cef::Request>,
         ) -> Option<ResourceHandler> {
-            Some(GodotResourceHandlerImpl::build(GodotResourceHandler::new(self.handler.scheme)))
+            match &self.handler.source {
+                SchemeSource::Files(mapping) => Some(GodotResourceHandlerImpl::build(
+                    GodotResourceHandler::new(mapping.clone(), self.handler.request_log.clone()),
+                )),
+                SchemeSource::Callable(scheme) => Some(CallableResourceHandlerImpl::build(
+                    CallableResourceHandler::new(scheme.clone()),
+                )),
+            }
         }
     }
 }
@@ -622,28 +975,68 @@ impl GodotSchemeHandlerFactory {
     }
 }
 
-fn register_scheme_handler_on_context(context: &mut cef::RequestContext, scheme: GodotScheme) {
+pub(crate) fn register_scheme_source_on_context(
+    context: &mut cef::RequestContext,
+    scheme_name: &str,
+    source: SchemeSource,
+    request_log: Option<EventQueuesHandle>,
+) {
     use cef::ImplRequestContext;
-    let mut factory = GodotSchemeHandlerFactory::build(GodotSchemeHandler::new(scheme));
+    let mut factory =
+        GodotSchemeHandlerFactory::build(GodotSchemeHandler::new(source, request_log));
     context.register_scheme_handler_factory(
-        Some(&scheme.name().into()),
+        Some(&scheme_name.into()),
         Some(&"".into()),
         Some(&mut factory),
     );
 }
 
-pub fn register_res_scheme_handler_on_context(context: &mut cef::RequestContext) {
-    register_scheme_handler_on_context(context, GodotScheme::Res);
+fn register_scheme_handler_on_context(
+    context: &mut cef::RequestContext,
+    mapping: SchemeMapping,
+    request_log: Option<EventQueuesHandle>,
+) {
+    let name = mapping.name().to_string();
+    register_scheme_source_on_context(context, &name, SchemeSource::Files(mapping), request_log);
 }
 
-pub fn register_user_scheme_handler_on_context(context: &mut cef::RequestContext) {
-    register_scheme_handler_on_context(context, GodotScheme::User);
+/// Registers the `res://` handler. With `local_origin_only`, only requests
+/// from `res://` or `user://` pages (and top-level navigations) are served;
+/// everything else gets a 403.
+pub fn register_res_scheme_handler_on_context(
+    context: &mut cef::RequestContext,
+    request_log: Option<EventQueuesHandle>,
+    local_origin_only: bool,
+) {
+    let mapping = SchemeMapping::from(GodotScheme::Res).with_local_origin_only(local_origin_only);
+    register_scheme_handler_on_context(context, mapping, request_log);
+}
+
+pub fn register_user_scheme_handler_on_context(
+    context: &mut cef::RequestContext,
+    request_log: Option<EventQueuesHandle>,
+) {
+    register_scheme_handler_on_context(
+        context,
+        SchemeMapping::from(GodotScheme::User),
+        request_log,
+    );
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// Resolves `url_str` like a GET request for which no file exists.
+    fn parse_mapped_url(url_str: &str, mapping: &SchemeMapping) -> Option<String> {
+        let full_path = parse_mapped_path(url_str, mapping)?;
+        Some(resolve_read_path(&full_path, mapping, false, |_| false))
+    }
+
+    fn parse_godot_url(url_str: &str, scheme: GodotScheme) -> Option<String> {
+        parse_mapped_url(url_str, &SchemeMapping::from(scheme))
+    }
+
     #[test]
     fn test_parse_res_url() {
         assert_eq!(
@@ -911,4 +1304,326 @@ mod tests {
         // Invalid hex characters
         assert_eq!(parse_godot_url("res://file%GG.txt", GodotScheme::Res), None);
     }
+
+    #[test]
+    fn test_parse_mapped_url() {
+        let mapping = SchemeMapping::new("app", "res://web");
+        assert_eq!(
+            parse_mapped_url("app://index.html", &mapping),
+            Some("res://web/index.html".to_string())
+        );
+        assert_eq!(
+            parse_mapped_url("app://assets/logo.png?v=2", &mapping),
+            Some("res://web/assets/logo.png".to_string())
+        );
+        assert_eq!(
+            parse_mapped_url("app://", &mapping),
+            Some("res://web/index.html".to_string())
+        );
+        assert_eq!(parse_mapped_url("app://../project.godot", &mapping), None);
+        assert_eq!(parse_mapped_url("app://%2e%2e/secret.txt", &mapping), None);
+    }
+
+    #[test]
+    fn test_head_missing_file_reports_error_length() {
+        let mut state = ResourceState {
+            is_head: true,
+            ..Default::default()
+        };
+        let message = "File not found: res://missing.html".to_string();
+        set_error_response(&mut state, 404, message.clone());
+        finish_head_response(&mut state);
+
+        assert_eq!(state.status_code, 404);
+        assert_eq!(state.head_content_length, Some(message.len() as u64));
+        assert!(state.data.is_empty());
+        assert_eq!(body_length(&state), 0);
+    }
+
+    #[test]
+    fn test_head_range_reports_range_length() {
+        let mut state = ResourceState {
+            is_head: true,
+            status_code: 206,
+            total_file_size: 1000,
+            range_start: Some(100),
+            range_end: Some(149),
+            file_stream: Some(FileStreamState::new(100, 50)),
+            ..Default::default()
+        };
+        finish_head_response(&mut state);
+
+        assert_eq!(state.status_code, 206);
+        assert_eq!(state.range_start, Some(100));
+        assert_eq!(state.range_end, Some(149));
+        assert_eq!(state.head_content_length, Some(50));
+        assert!(state.file_stream.is_none());
+        assert_eq!(body_length(&state), 0);
+    }
+
+    #[test]
+    fn test_head_directory_index_rewrite() {
+        let mapping = SchemeMapping::from(GodotScheme::Res);
+        assert_eq!(
+            parse_mapped_url("res://ui/", &mapping),
+            Some("res://ui/index.html".to_string())
+        );
+
+        let mut state = ResourceState {
+            is_head: true,
+            status_code: 200,
+            data: b"<html></html>".to_vec(),
+            ..Default::default()
+        };
+        finish_head_response(&mut state);
+
+        assert_eq!(state.head_content_length, Some(13));
+        assert!(state.data.is_empty());
+    }
+
+    #[test]
+    fn test_is_allowed_initiator() {
+        // Requests from local pages
+        assert!(is_allowed_initiator("", "res://ui/index.html", false));
+        assert!(is_allowed_initiator("", "USER://save/page.html", false));
+        assert!(is_allowed_initiator("res://", "", false));
+
+        // Requests from remote pages, by Origin or by referrer
+        assert!(!is_allowed_initiator("https://example.com", "", false));
+        assert!(!is_allowed_initiator("", "https://example.com/page", false));
+        assert!(!is_allowed_initiator(
+            "https://example.com",
+            "res://ui/",
+            false
+        ));
+        assert!(!is_allowed_initiator("null", "https://example.com/", true));
+
+        // No initiator: only top-level navigations
+        assert!(is_allowed_initiator("", "", true));
+        assert!(is_allowed_initiator("null", "", true));
+        assert!(!is_allowed_initiator("", "", false));
+        assert!(!is_allowed_initiator("", "resource://x", false));
+    }
+
+    fn files(paths: &'static [&'static str]) -> impl Fn(&str) -> bool {
+        move |path| paths.contains(&path)
+    }
+
+    #[test]
+    fn test_parse_mapped_path_keeps_request_shape() {
+        let user = SchemeMapping::from(GodotScheme::User);
+        assert_eq!(
+            parse_mapped_path("user://saves", &user),
+            Some("user://saves".to_string())
+        );
+        assert_eq!(
+            parse_mapped_path("user://saves/", &user),
+            Some("user://saves/".to_string())
+        );
+        assert_eq!(
+            parse_mapped_path("user:saves/slot1.json", &user),
+            Some("user://saves/slot1.json".to_string())
+        );
+        assert_eq!(
+            parse_mapped_path("user://saves?x=1#top", &user),
+            Some("user://saves".to_string())
+        );
+        assert_eq!(
+            parse_mapped_path("user://", &user),
+            Some("user://".to_string())
+        );
+        assert_eq!(parse_mapped_path("user://../etc/passwd", &user), None);
+        assert_eq!(parse_mapped_path("user://saves/%2e%2e/x", &user), None);
+    }
+
+    #[test]
+    fn test_read_appends_index_for_folders() {
+        let user = SchemeMapping::from(GodotScheme::User);
+        let none = |_: &str| false;
+
+        assert_eq!(
+            resolve_read_path("user://saves/", &user, false, none),
+            "user://saves/index.html"
+        );
+        assert_eq!(
+            resolve_read_path("user://saves", &user, false, none),
+            "user://saves/index.html"
+        );
+        assert_eq!(
+            resolve_read_path("user://", &user, false, none),
+            "user://index.html"
+        );
+        assert_eq!(
+            resolve_read_path("user://saves/slot1.json", &user, false, none),
+            "user://saves/slot1.json"
+        );
+        // Dots in parent folders are not extensions
+        assert_eq!(
+            resolve_read_path("user://v1.2/docs", &user, false, none),
+            "user://v1.2/docs/index.html"
+        );
+    }
+
+    #[test]
+    fn test_read_never_rewrites_existing_file() {
+        let user = SchemeMapping::from(GodotScheme::User);
+        let is_file = files(&["user://saves", "user://LICENSE", "user://saves/index.html"]);
+
+        assert_eq!(
+            resolve_read_path("user://saves", &user, false, &is_file),
+            "user://saves"
+        );
+        assert_eq!(
+            resolve_read_path("user://LICENSE", &user, false, &is_file),
+            "user://LICENSE"
+        );
+        // An explicit trailing slash still asks for the folder
+        assert_eq!(
+            resolve_read_path("user://saves/", &user, false, &is_file),
+            "user://saves/index.html"
+        );
+    }
+
+    #[test]
+    fn test_read_requires_trailing_slash_when_configured() {
+        let res = SchemeMapping::from(GodotScheme::Res);
+        let none = |_: &str| false;
+
+        assert_eq!(resolve_read_path("res://ui", &res, true, none), "res://ui");
+        assert_eq!(
+            resolve_read_path("res://ui/", &res, true, none),
+            "res://ui/index.html"
+        );
+        assert_eq!(
+            resolve_read_path("res://", &res, true, none),
+            "res://index.html"
+        );
+        assert_eq!(
+            resolve_read_path("res://ui/app.js", &res, true, none),
+            "res://ui/app.js"
+        );
+    }
+
+    #[test]
+    fn test_read_methods() {
+        assert!(is_read_method("GET"));
+        assert!(is_read_method("get"));
+        assert!(is_read_method("HEAD"));
+        assert!(is_read_method(""));
+        assert!(!is_read_method("POST"));
+        assert!(!is_read_method("PUT"));
+        assert!(!is_read_method("DELETE"));
+        assert!(!is_read_method("OPTIONS"));
+    }
+
+    #[test]
+    fn test_only_reads_append_index() {
+        let user = SchemeMapping::from(GodotScheme::User);
+        let none = |_: &str| false;
+
+        assert_eq!(
+            resolve_request_path("user://saves", &user, "GET", false, none),
+            Ok("user://saves/index.html".to_string())
+        );
+        assert_eq!(
+            resolve_request_path("user://saves", &user, "HEAD", false, none),
+            Ok("user://saves/index.html".to_string())
+        );
+        assert_eq!(
+            resolve_request_path("user://saves", &user, "PUT", false, none),
+            Ok("user://saves".to_string())
+        );
+        assert_eq!(
+            resolve_request_path("user://saves/", &user, "POST", false, none),
+            Ok("user://saves/".to_string())
+        );
+        assert_eq!(
+            resolve_request_path("user://", &user, "DELETE", false, none),
+            Ok("user://".to_string())
+        );
+    }
+
+    #[test]
+    fn test_write_under_file_conflicts() {
+        let user = SchemeMapping::from(GodotScheme::User);
+        let is_file = files(&["user://saves", "user://data/profile.json"]);
+
+        assert_eq!(
+            resolve_request_path("user://saves/slot1.json", &user, "PUT", false, &is_file),
+            Err("user://saves".to_string())
+        );
+        assert_eq!(
+            resolve_request_path(
+                "user://data/profile.json/backup",
+                &user,
+                "POST",
+                false,
+                &is_file
+            ),
+            Err("user://data/profile.json".to_string())
+        );
+        // Overwriting the file itself is not a conflict
+        assert_eq!(
+            resolve_request_path("user://saves", &user, "PUT", false, &is_file),
+            Ok("user://saves".to_string())
+        );
+        assert_eq!(
+            resolve_request_path("user://data/new.json", &user, "PUT", false, &is_file),
+            Ok("user://data/new.json".to_string())
+        );
+        // Reads below a file are not conflicts, they just miss
+        assert_eq!(
+            resolve_request_path("user://saves/slot1.json", &user, "GET", false, &is_file),
+            Ok("user://saves/slot1.json".to_string())
+        );
+    }
+
+    #[test]
+    fn test_find_file_parent() {
+        let web = SchemeMapping::new("app", "user://web");
+        let is_file = files(&["user://web/a", "user://web/a/b"]);
+
+        assert_eq!(
+            find_file_parent("user://web/a/b/c", &web, &is_file),
+            Some("user://web/a".to_string())
+        );
+        assert_eq!(find_file_parent("user://web/a", &web, &is_file), None);
+        assert_eq!(find_file_parent("user://web/x/y/", &web, &is_file), None);
+        // The root itself is never checked
+        let root_is_file = files(&["user://web"]);
+        assert_eq!(find_file_parent("user://web/a", &web, root_is_file), None);
+        // Paths outside the root are not resolved here
+        assert_eq!(find_file_parent("res://a/b", &web, &is_file), None);
+    }
+
+    #[test]
+    fn test_mapped_scheme_resolution() {
+        let web = SchemeMapping::new("app", "res://web");
+        let is_file = files(&["res://web/about"]);
+
+        let full = parse_mapped_path("app://about", &web).unwrap();
+        assert_eq!(
+            resolve_request_path(&full, &web, "GET", false, &is_file),
+            Ok("res://web/about".to_string())
+        );
+        let full = parse_mapped_path("app://docs", &web).unwrap();
+        assert_eq!(
+            resolve_request_path(&full, &web, "GET", false, &is_file),
+            Ok("res://web/docs/index.html".to_string())
+        );
+        assert_eq!(
+            resolve_request_path(&full, &web, "GET", true, &is_file),
+            Ok("res://web/docs".to_string())
+        );
+        let full = parse_mapped_path("app://about/comments.json", &web).unwrap();
+        assert_eq!(
+            resolve_request_path(&full, &web, "POST", false, &is_file),
+            Err("res://web/about".to_string())
+        );
+    }
+
+    #[test]
+    fn test_conflict_status_text() {
+        assert_eq!(status_text(409), "Conflict");
+    }
 }
@@ -0,0 +1,204 @@
+//! Configurable response headers for the Godot scheme handlers.
+//!
+//! The configuration is read from project settings once when CEF initializes
+//! and then shared with the IO thread, so no Godot APIs are touched while
+//! responses are being built.
+
+use std::sync::{LazyLock, RwLock};
+
+use super::GodotScheme;
+
+/// Header configuration applied to every file-backed scheme response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResponseHeaderConfig {
+    /// Value of `Access-Control-Allow-Origin` (empty = header omitted).
+    pub allow_origin: String,
+    /// Drop a wildcard `Access-Control-Allow-Origin` for responses served
+    /// from `user://`, including custom schemes mapped onto it.
+    pub strict_user_origin: bool,
+    /// Additional headers appended to every response.
+    pub extra_headers: Vec<(String, String)>,
+}
+
+impl Default for ResponseHeaderConfig {
+    fn default() -> Self {
+        Self {
+            allow_origin: "*".to_string(),
+            strict_user_origin: false,
+            extra_headers: Vec::new(),
+        }
+    }
+}
+
+impl ResponseHeaderConfig {
+    /// Returns the `Access-Control-Allow-Origin` value for responses served
+    /// from the `root` folder of a scheme mapping, if any.
+    pub fn allow_origin_for(&self, root: &str) -> Option<&str> {
+        let origin = self.allow_origin.trim();
+        if origin.is_empty() || !is_valid_header_value(origin) {
+            return None;
+        }
+        if self.strict_user_origin && root.starts_with(GodotScheme::User.prefix()) && origin == "*"
+        {
+            return None;
+        }
+        Some(origin)
+    }
+}
+
+static RESPONSE_HEADERS: LazyLock<RwLock<ResponseHeaderConfig>> =
+    LazyLock::new(|| RwLock::new(ResponseHeaderConfig::default()));
+
+/// Replaces the header configuration used by the scheme handlers.
+pub fn set_response_header_config(config: ResponseHeaderConfig) {
+    if let Ok(mut current) = RESPONSE_HEADERS.write() {
+        *current = config;
+    }
+}
+
+pub(crate) fn response_header_config() -> ResponseHeaderConfig {
+    RESPONSE_HEADERS
+        .read()
+        .map(|config| config.clone())
+        .unwrap_or_default()
+}
+
+/// Returns `true` if `name` is a valid HTTP header field name (RFC 9110 token).
+pub fn is_valid_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.bytes().all(|b| {
+            b.is_ascii_alphanumeric()
+                || matches!(
+                    b,
+                    b'!' | b'#'
+                        | b'$'
+                        | b'%'
+                        | b'&'
+                        | b'\''
+                        | b'*'
+                        | b'+'
+                        | b'-'
+                        | b'.'
+                        | b'^'
+                        | b'_'
+                        | b'`'
+                        | b'|'
+                        | b'~'
+                )
+        })
+}
+
+/// Returns `true` if `value` cannot break out of its header line.
+pub fn is_valid_header_value(value: &str) -> bool {
+    !value.bytes().any(|b| b == b'\r' || b == b'\n' || b == 0)
+}
+
+/// Parses a multiline `Header: value` list.
+///
+/// Blank lines and lines starting with `#` are ignored. Malformed or unsafe
+/// lines are returned separately so the caller can report them.
+pub fn parse_extra_headers(input: &str) -> (Vec<(String, String)>, Vec<String>) {
+    let mut headers = Vec::new();
+    let mut rejected = Vec::new();
+
+    for line in input.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match line.split_once(':') {
+            Some((name, value))
+                if is_valid_header_name(name.trim()) && is_valid_header_value(value.trim()) =>
+            {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+            _ => rejected.push(line.to_string()),
+        }
+    }
+
+    (headers, rejected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::godot_protocol::SchemeMapping;
+
+    #[test]
+    fn test_header_name_validation() {
+        assert!(is_valid_header_name("Content-Security-Policy"));
+        assert!(is_valid_header_name("X-Custom_Header.1"));
+        assert!(!is_valid_header_name(""));
+        assert!(!is_valid_header_name("Bad Header"));
+        assert!(!is_valid_header_name("X-Test\r\nSet-Cookie"));
+        assert!(!is_valid_header_name("X:Y"));
+    }
+
+    #[test]
+    fn test_header_value_validation() {
+        assert!(is_valid_header_value("default-src 'self'"));
+        assert!(is_valid_header_value(""));
+        assert!(!is_valid_header_value("a\r\nSet-Cookie: x=1"));
+        assert!(!is_valid_header_value("a\nb"));
+        assert!(!is_valid_header_value("a\0b"));
+    }
+
+    #[test]
+    fn test_parse_extra_headers() {
+        let (headers, rejected) = parse_extra_headers(
+            "Content-Security-Policy: default-src 'self'\n\
+             \n\
+             # comment\n\
+             X-Frame-Options:DENY\n\
+             not a header\n\
+             Bad Name: value",
+        );
+        assert_eq!(
+            headers,
+            vec![
+                (
+                    "Content-Security-Policy".to_string(),
+                    "default-src 'self'".to_string()
+                ),
+                ("X-Frame-Options".to_string(), "DENY".to_string()),
+            ]
+        );
+        assert_eq!(
+            rejected,
+            vec!["not a header".to_string(), "Bad Name: value".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_allow_origin_for() {
+        let config = ResponseHeaderConfig::default();
+        assert_eq!(config.allow_origin_for("res://"), Some("*"));
+        assert_eq!(config.allow_origin_for("user://"), Some("*"));
+
+        let strict = ResponseHeaderConfig {
+            strict_user_origin: true,
+            ..Default::default()
+        };
+        assert_eq!(strict.allow_origin_for("res://"), Some("*"));
+        assert_eq!(strict.allow_origin_for("user://"), None);
+        // Custom schemes follow the folder they are mapped onto
+        let saves = SchemeMapping::new("saves", "user://saves");
+        assert_eq!(strict.allow_origin_for(saves.root()), None);
+        let app = SchemeMapping::new("app", "res://ui");
+        assert_eq!(strict.allow_origin_for(app.root()), Some("*"));
+
+        let specific = ResponseHeaderConfig {
+            allow_origin: "res://app".to_string(),
+            strict_user_origin: true,
+            extra_headers: Vec::new(),
+        };
+        assert_eq!(specific.allow_origin_for("user://"), Some("res://app"));
+
+        let disabled = ResponseHeaderConfig {
+            allow_origin: String::new(),
+            ..Default::default()
+        };
+        assert_eq!(disabled.allow_origin_for("res://"), None);
+    }
+}
@@ -0,0 +1,103 @@
+//! Fallback for resources that only exist in their imported form.
+//!
+//! Exported builds ship the result of the import instead of the source
+//! file: `icon.png` becomes a `.ctex` under `res://.godot/imported/`, found
+//! through `icon.png.import`, and scenes or scripts may be replaced by a
+//! `.remap`. `FileAccess` cannot open the original path there, but
+//! `ResourceLoader` can. Imported textures are loaded and served re-encoded
+//! as PNG; other imported resources have no file form to serve and get a
+//! 404 with a hint header instead. When running from the editor the source
+//! files exist and are served as they are.
+
+use godot::classes::{ResourceLoader, Texture2D};
+use godot::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Header of the 404 responses for imported resources, explaining why the
+/// resource was not served.
+pub(super) const IMPORT_HINT_HEADER: &str = "X-Godot-Import-Hint";
+
+const REENCODE_SETTING: &str = "godot_cef/protocol/reencode_imported_textures";
+
+static REENCODE_IMPORTED_TEXTURES: AtomicBool = AtomicBool::new(true);
+
+/// Sets whether imported textures are served re-encoded as PNG.
+pub fn set_reencode_imported_textures(enabled: bool) {
+    REENCODE_IMPORTED_TEXTURES.store(enabled, Ordering::Relaxed);
+}
+
+pub(super) enum ImportedResource {
+    /// The texture, re-encoded as PNG.
+    Png(Vec<u8>),
+    /// The resource exists but is not served; the hint explains why.
+    Unavailable(String),
+}
+
+/// Returns the hint for an imported resource that is not served, where
+/// `class` is the class it loaded as, or `None` if it was not loaded because
+/// re-encoding is disabled.
+fn unavailable_hint(class: Option<&str>) -> String {
+    const EXPORT_AS_FILE: &str = "list the file in the export preset's non-resource filters to \
+                                  ship it as is";
+    match class {
+        None => format!(
+            "Only the imported resource exists in this build; enable {REENCODE_SETTING} to \
+             serve imported textures as PNG, or {EXPORT_AS_FILE}"
+        ),
+        Some(class) => format!(
+            "Only the imported {class} exists in this build, which has no file form; \
+             {EXPORT_AS_FILE}"
+        ),
+    }
+}
+
+/// Looks up `path` as an imported or remapped resource, for a read of a
+/// file `FileAccess` does not find. Returns `None` if no resource exists
+/// there either.
+pub(super) fn load_imported_resource(path: &str) -> Option<ImportedResource> {
+    let mut loader = ResourceLoader::singleton();
+    let path = GString::from(path);
+    if !loader.exists(&path) {
+        return None;
+    }
+    if !REENCODE_IMPORTED_TEXTURES.load(Ordering::Relaxed) {
+        return Some(ImportedResource::Unavailable(unavailable_hint(None)));
+    }
+
+    let resource = loader.load(&path)?;
+    let class = resource.get_class().to_string();
+    let png = resource
+        .try_cast::<Texture2D>()
+        .ok()
+        .and_then(|texture| texture.get_image())
+        .and_then(|mut image| {
+            // VRAM-compressed textures must be decompressed before saving
+            if image.is_compressed() && image.decompress() != godot::global::Error::OK {
+                return None;
+            }
+            Some(image.save_png_to_buffer().as_slice().to_vec())
+        });
+    Some(match png {
+        Some(png) => ImportedResource::Png(png),
+        None => ImportedResource::Unavailable(unavailable_hint(Some(&class))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unavailable_hint() {
+        let disabled = unavailable_hint(None);
+        assert!(disabled.contains(REENCODE_SETTING));
+        assert!(disabled.contains("non-resource filters"));
+
+        let unsupported = unavailable_hint(Some("PackedScene"));
+        assert!(unsupported.contains("imported PackedScene"));
+        assert!(!unsupported.contains(REENCODE_SETTING));
+
+        // Header values cannot contain line breaks
+        assert!(!disabled.contains('\n') && !unsupported.contains('\n'));
+    }
+}
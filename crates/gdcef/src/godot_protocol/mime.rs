@@ -3,21 +3,34 @@
 //! Reference: https://developer.mozilla.org/en-US/docs/Web/HTTP/Guides/MIME_types/Common_types
 
 use std::collections::HashMap;
-use std::sync::LazyLock;
+use std::sync::{LazyLock, RwLock};
+
+/// MIME type used for extensions that are neither overridden nor known.
+pub(crate) const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+
+/// User-provided extension to MIME type overrides, consulted before [`MIME_TYPES`].
+///
+/// Keys are stored lowercase without a leading dot.
+static MIME_OVERRIDES: LazyLock<RwLock<HashMap<String, String>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
 
 pub(crate) static MIME_TYPES: LazyLock<HashMap<&'static str, &'static str>> = LazyLock::new(|| {
     HashMap::from([
         // Audio
         ("aac", "audio/aac"),
+        ("flac", "audio/flac"),
+        ("m4a", "audio/mp4"),
         ("midi", "audio/midi"),
         ("mid", "audio/midi"),
         ("mp3", "audio/mpeg"),
         ("oga", "audio/ogg"),
+        ("ogg", "audio/ogg"),
         ("opus", "audio/ogg"),
         ("wav", "audio/wav"),
         ("weba", "audio/webm"),
         // Video
         ("avi", "video/x-msvideo"),
+        ("m4v", "video/mp4"),
         ("mp4", "video/mp4"),
         ("mpeg", "video/mpeg"),
         ("ogv", "video/ogg"),
@@ -31,6 +44,7 @@ pub(crate) static MIME_TYPES: LazyLock<HashMap<&'static str, &'static str>> = La
         ("bmp", "image/bmp"),
         ("gif", "image/gif"),
         ("ico", "image/vnd.microsoft.icon"),
+        ("jfif", "image/jpeg"),
         ("jpeg", "image/jpeg"),
         ("jpg", "image/jpeg"),
         ("png", "image/png"),
@@ -53,14 +67,19 @@ pub(crate) static MIME_TYPES: LazyLock<HashMap<&'static str, &'static str>> = La
         ("js", "text/javascript"),
         ("cjs", "text/javascript"),
         ("mjs", "text/javascript"),
+        ("md", "text/markdown"),
         ("txt", "text/plain"),
         ("xml", "application/xml"),
         // Application
         ("json", "application/json"),
         ("jsonld", "application/ld+json"),
+        ("map", "application/json"),
+        ("webmanifest", "application/manifest+json"),
         ("pdf", "application/pdf"),
         ("wasm", "application/wasm"),
         ("xhtml", "application/xhtml+xml"),
+        ("glb", "model/gltf-binary"),
+        ("gltf", "model/gltf+json"),
         ("zip", "application/zip"),
         ("7z", "application/x-7z-compressed"),
         ("gz", "application/gzip"),
@@ -106,10 +125,47 @@ pub(crate) static MIME_TYPES: LazyLock<HashMap<&'static str, &'static str>> = La
     ])
 });
 
-pub(crate) fn get_mime_type(extension: &str) -> &'static str {
+/// Normalizes an extension for lookup: lowercase, without a leading dot.
+fn normalize_extension(extension: &str) -> String {
+    extension.trim().trim_start_matches('.').to_lowercase()
+}
+
+/// Replaces the MIME override table.
+pub fn set_mime_overrides(overrides: HashMap<String, String>) {
+    let normalized = overrides
+        .into_iter()
+        .map(|(ext, mime)| (normalize_extension(&ext), mime.trim().to_string()))
+        .filter(|(ext, mime)| !ext.is_empty() && !mime.is_empty())
+        .collect();
+
+    if let Ok(mut table) = MIME_OVERRIDES.write() {
+        *table = normalized;
+    }
+}
+
+fn lookup_mime_type(extension: &str, overrides: &HashMap<String, String>) -> String {
+    let extension = normalize_extension(extension);
+
+    if let Some(mime) = overrides.get(&extension) {
+        return mime.clone();
+    }
+
     MIME_TYPES
-        .get(extension.to_lowercase().as_str())
-        .unwrap_or(&"application/octet-stream")
+        .get(extension.as_str())
+        .copied()
+        .unwrap_or(DEFAULT_MIME_TYPE)
+        .to_string()
+}
+
+/// Returns the MIME type for a file extension (case-insensitive).
+///
+/// Project overrides take precedence over the built-in table; unknown
+/// extensions map to `application/octet-stream`.
+pub(crate) fn get_mime_type(extension: &str) -> String {
+    match MIME_OVERRIDES.read() {
+        Ok(overrides) => lookup_mime_type(extension, &overrides),
+        Err(_) => lookup_mime_type(extension, &HashMap::new()),
+    }
 }
 
 #[cfg(test)]
@@ -126,4 +182,44 @@ mod tests {
         assert_eq!(get_mime_type("png"), "image/png");
         assert_eq!(get_mime_type("unknown"), "application/octet-stream");
     }
+
+    #[test]
+    fn test_modern_web_types() {
+        let overrides = HashMap::new();
+        assert_eq!(lookup_mime_type("wasm", &overrides), "application/wasm");
+        assert_eq!(lookup_mime_type("mjs", &overrides), "text/javascript");
+        assert_eq!(lookup_mime_type("map", &overrides), "application/json");
+        assert_eq!(lookup_mime_type("woff2", &overrides), "font/woff2");
+        assert_eq!(lookup_mime_type("avif", &overrides), "image/avif");
+        assert_eq!(
+            lookup_mime_type("webmanifest", &overrides),
+            "application/manifest+json"
+        );
+    }
+
+    #[test]
+    fn test_lookup_is_case_insensitive() {
+        let overrides = HashMap::new();
+        assert_eq!(lookup_mime_type("WASM", &overrides), "application/wasm");
+        assert_eq!(lookup_mime_type("Mjs", &overrides), "text/javascript");
+        assert_eq!(lookup_mime_type(".PNG", &overrides), "image/png");
+    }
+
+    #[test]
+    fn test_overrides_take_precedence() {
+        let overrides = HashMap::from([
+            ("glbin".to_string(), "application/x-glbin".to_string()),
+            ("txt".to_string(), "text/x-custom".to_string()),
+        ]);
+        assert_eq!(lookup_mime_type("glbin", &overrides), "application/x-glbin");
+        assert_eq!(lookup_mime_type("txt", &overrides), "text/x-custom");
+        assert_eq!(lookup_mime_type("html", &overrides), "text/html");
+    }
+
+    #[test]
+    fn test_unknown_extension_is_octet_stream() {
+        let overrides = HashMap::new();
+        assert_eq!(lookup_mime_type("glbin", &overrides), DEFAULT_MIME_TYPE);
+        assert_eq!(lookup_mime_type("", &overrides), DEFAULT_MIME_TYPE);
+    }
 }
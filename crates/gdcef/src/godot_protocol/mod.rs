@@ -8,15 +8,33 @@
 //!
 //! - `res://` - Access resources from Godot's packed resource system
 //! - `user://` - Access files from Godot's user data directory
+//!
+//! Applications can additionally register their own schemes (see [`custom`]),
+//! either mapped onto a Godot folder or answered by a GDScript `Callable`.
 
+mod custom;
 mod handler;
+mod headers;
+mod imported;
 mod mime;
 mod multipart;
 mod range;
+mod stream;
 
+pub use custom::{
+    process_pending_scheme_requests, register_custom_scheme_handlers_on_context,
+    register_scheme_callable, register_scheme_folder, registered_custom_scheme_names,
+};
 pub use handler::{
     register_res_scheme_handler_on_context, register_user_scheme_handler_on_context,
+    set_directory_index_requires_trailing_slash,
 };
+pub use headers::{
+    ResponseHeaderConfig, is_valid_header_name, is_valid_header_value, parse_extra_headers,
+    set_response_header_config,
+};
+pub use imported::set_reencode_imported_textures;
+pub use mime::set_mime_overrides;
 
 /// Represents the Godot filesystem scheme type.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -35,13 +53,6 @@ impl GodotScheme {
         }
     }
 
-    pub(crate) fn short_prefix(&self) -> &'static str {
-        match self {
-            GodotScheme::Res => "res:",
-            GodotScheme::User => "user:",
-        }
-    }
-
     pub(crate) fn name(&self) -> &'static str {
         match self {
             GodotScheme::Res => "res",
@@ -49,3 +60,60 @@ impl GodotScheme {
         }
     }
 }
+
+/// Maps a URL scheme onto a root folder in Godot's filesystem.
+///
+/// `res://` and `user://` map onto themselves; custom schemes registered with
+/// [`register_scheme_folder`] map onto an arbitrary `res://` or `user://` folder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SchemeMapping {
+    name: String,
+    root: String,
+    local_origin_only: bool,
+}
+
+impl SchemeMapping {
+    pub(crate) fn new(name: &str, root: &str) -> Self {
+        let mut root = root.to_string();
+        if !root.ends_with('/') {
+            root.push('/');
+        }
+        Self {
+            name: name.to_string(),
+            root,
+            local_origin_only: false,
+        }
+    }
+
+    /// Only serve requests initiated by `res://` or `user://` pages.
+    pub(crate) fn with_local_origin_only(mut self, local_origin_only: bool) -> Self {
+        self.local_origin_only = local_origin_only;
+        self
+    }
+
+    pub(crate) fn local_origin_only(&self) -> bool {
+        self.local_origin_only
+    }
+
+    pub(crate) fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub(crate) fn root(&self) -> &str {
+        &self.root
+    }
+
+    pub(crate) fn prefix(&self) -> String {
+        format!("{}://", self.name)
+    }
+
+    pub(crate) fn short_prefix(&self) -> String {
+        format!("{}:", self.name)
+    }
+}
+
+impl From<GodotScheme> for SchemeMapping {
+    fn from(scheme: GodotScheme) -> Self {
+        Self::new(scheme.name(), scheme.prefix())
+    }
+}
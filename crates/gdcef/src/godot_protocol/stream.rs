@@ -0,0 +1,146 @@
+//! Streaming of single-body responses directly from an open file.
+//!
+//! Large files are not buffered in memory; instead the `FileAccess` handle is
+//! kept open and each `read()` call pulls at most the requested number of bytes.
+
+use godot::classes::FileAccess;
+use godot::prelude::*;
+
+/// Files up to this size are read into memory in one go.
+pub(crate) const STREAMING_THRESHOLD: u64 = 64 * 1024;
+
+/// Returns `true` if a body of `length` bytes should be streamed from the file.
+pub(crate) fn should_stream(length: u64) -> bool {
+    length > STREAMING_THRESHOLD
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct FileStreamState {
+    /// Absolute file position of the next byte to send.
+    pub position: u64,
+    /// Absolute file position one past the last byte to send.
+    pub end: u64,
+}
+
+impl FileStreamState {
+    /// Creates a stream over `length` bytes starting at `start`.
+    pub fn new(start: u64, length: u64) -> Self {
+        Self {
+            position: start,
+            end: start.saturating_add(length),
+        }
+    }
+
+    pub fn remaining(&self) -> u64 {
+        self.end.saturating_sub(self.position)
+    }
+
+    /// Number of bytes the next read should pull from the file.
+    pub fn next_chunk_len(&self, bytes_to_read: usize) -> usize {
+        self.remaining().min(bytes_to_read as u64) as usize
+    }
+
+    /// Advances the stream by up to `bytes`, returning how far it actually moved.
+    pub fn advance(&mut self, bytes: u64) -> u64 {
+        let moved = bytes.min(self.remaining());
+        self.position += moved;
+        moved
+    }
+}
+
+/// Reads the next chunk of `stream` from `file` into `data_out`.
+///
+/// Returns the number of bytes written. A return value of 0 means the stream
+/// is complete or the file could not be read any further.
+pub(crate) fn read_file_streaming(
+    stream: &mut FileStreamState,
+    file: &mut Gd<FileAccess>,
+    data_out: *mut u8,
+    bytes_to_read: usize,
+) -> usize {
+    let to_read = stream.next_chunk_len(bytes_to_read);
+    if to_read == 0 {
+        return 0;
+    }
+
+    if file.get_position() != stream.position {
+        file.seek(stream.position);
+    }
+
+    let buffer = file.get_buffer(to_read as i64);
+    let actual_read = buffer.len().min(to_read);
+    if actual_read == 0 {
+        // EOF or read error - end the stream early rather than looping forever
+        stream.position = stream.end;
+        return 0;
+    }
+
+    unsafe {
+        std::ptr::copy_nonoverlapping(buffer.as_slice().as_ptr(), data_out, actual_read);
+    }
+    stream.advance(actual_read as u64);
+
+    actual_read
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_stream() {
+        assert!(!should_stream(0));
+        assert!(!should_stream(STREAMING_THRESHOLD));
+        assert!(should_stream(STREAMING_THRESHOLD + 1));
+        assert!(should_stream(700 * 1024 * 1024));
+    }
+
+    #[test]
+    fn test_stream_range_bounds() {
+        let stream = FileStreamState::new(100, 50);
+        assert_eq!(stream.position, 100);
+        assert_eq!(stream.end, 150);
+        assert_eq!(stream.remaining(), 50);
+        assert_eq!(stream.next_chunk_len(64), 50);
+        assert_eq!(stream.next_chunk_len(10), 10);
+    }
+
+    #[test]
+    fn test_advance_is_clamped() {
+        let mut stream = FileStreamState::new(0, 10);
+        assert_eq!(stream.advance(4), 4);
+        assert_eq!(stream.advance(100), 6);
+        assert_eq!(stream.remaining(), 0);
+        assert_eq!(stream.next_chunk_len(64), 0);
+    }
+
+    #[test]
+    fn test_simulated_1gb_read_is_chunked() {
+        const FILE_SIZE: u64 = 1024 * 1024 * 1024;
+        const CHUNK: usize = 64 * 1024;
+
+        let mut stream = FileStreamState::new(0, FILE_SIZE);
+        let mut total = 0u64;
+        let mut largest_chunk = 0usize;
+
+        loop {
+            let chunk = stream.next_chunk_len(CHUNK);
+            if chunk == 0 {
+                break;
+            }
+            largest_chunk = largest_chunk.max(chunk);
+            total += stream.advance(chunk as u64);
+        }
+
+        assert_eq!(total, FILE_SIZE);
+        assert!(largest_chunk <= CHUNK);
+    }
+
+    #[test]
+    fn test_skip_then_read_tail() {
+        let mut stream = FileStreamState::new(0, 1000);
+        assert_eq!(stream.advance(990), 990);
+        assert_eq!(stream.position, 990);
+        assert_eq!(stream.next_chunk_len(64), 10);
+    }
+}
@@ -0,0 +1,155 @@
+//! Checks that the helper subprocess can run before CEF is initialized.
+//!
+//! CEF initializes fine with a missing or broken helper, but every browser
+//! then stays blank and the only trace is a line on stderr. These checks
+//! turn that into an initialization error naming the path that was checked.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::error::{CefError, CefResult};
+
+/// Time the helper gets to answer the preflight check.
+const PREFLIGHT_TIMEOUT: Duration = Duration::from_secs(10);
+
+fn unavailable(path: &Path, reason: impl Into<String>) -> CefError {
+    CefError::HelperUnavailable {
+        path: path.to_path_buf(),
+        reason: reason.into(),
+    }
+}
+
+/// Checks that the helper at `path` exists and is executable.
+pub fn verify_helper(path: &Path) -> CefResult<()> {
+    let metadata = std::fs::metadata(path).map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => unavailable(path, "does not exist"),
+        _ => unavailable(path, format!("cannot be read: {}", e)),
+    })?;
+    if !metadata.is_file() {
+        return Err(unavailable(path, "is not a file"));
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(unavailable(path, "is not executable"));
+        }
+    }
+
+    Ok(())
+}
+
+/// Starts the helper as a preflight-check process, which exits right after
+/// loading CEF, and checks its exit code.
+pub fn run_preflight(path: &Path) -> CefResult<()> {
+    let mut child = Command::new(path)
+        .arg(format!("--type={}", cef_app::PREFLIGHT_PROCESS_TYPE))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .spawn()
+        .map_err(|e| unavailable(path, format!("could not be started: {}", e)))?;
+
+    let deadline = Instant::now() + PREFLIGHT_TIMEOUT;
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return check_preflight_exit(path, status.code()),
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(Duration::from_millis(20)),
+            Ok(None) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(unavailable(
+                    path,
+                    format!(
+                        "did not exit within {} s of the preflight check",
+                        PREFLIGHT_TIMEOUT.as_secs()
+                    ),
+                ));
+            }
+            Err(e) => return Err(unavailable(path, format!("could not be waited for: {}", e))),
+        }
+    }
+}
+
+fn check_preflight_exit(path: &Path, code: Option<i32>) -> CefResult<()> {
+    let expected = i32::from(cef_app::PREFLIGHT_EXIT_CODE);
+    match code {
+        Some(code) if code == expected => Ok(()),
+        Some(code) => Err(unavailable(
+            path,
+            format!(
+                "exited with code {} instead of {} during the preflight check",
+                code, expected
+            ),
+        )),
+        None => Err(unavailable(
+            path,
+            "was terminated by a signal during the preflight check",
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::io::Write;
+
+    fn reason(result: CefResult<()>) -> String {
+        match result {
+            Err(CefError::HelperUnavailable { reason, .. }) => reason,
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    /// Writes `path` and closes it before returning, so running it does not
+    /// fail with "text file busy".
+    fn write_script(path: &Path, script: &str) {
+        let mut file = fs::File::create(path).unwrap();
+        file.write_all(script.as_bytes()).unwrap();
+        file.sync_all().unwrap();
+    }
+
+    #[test]
+    fn test_verify_helper() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let dir = temp_dir.path();
+
+        assert_eq!(
+            reason(verify_helper(&dir.join("missing"))),
+            "does not exist"
+        );
+        assert_eq!(reason(verify_helper(dir)), "is not a file");
+
+        let helper = dir.join("helper");
+        write_script(&helper, "#!/bin/sh\nexit 73\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&helper, fs::Permissions::from_mode(0o644)).unwrap();
+            assert_eq!(reason(verify_helper(&helper)), "is not executable");
+            fs::set_permissions(&helper, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        assert!(verify_helper(&helper).is_ok());
+
+        // A script stands in for the helper answering the preflight check
+        #[cfg(unix)]
+        {
+            assert!(run_preflight(&helper).is_ok());
+            write_script(&helper, "#!/bin/sh\nexit 0\n");
+            assert_eq!(
+                reason(run_preflight(&helper)),
+                "exited with code 0 instead of 73 during the preflight check"
+            );
+        }
+    }
+
+    #[test]
+    fn test_check_preflight_exit() {
+        let path = Path::new("gdcef_helper");
+        assert!(check_preflight_exit(path, Some(73)).is_ok());
+        assert!(check_preflight_exit(path, Some(1)).is_err());
+        assert!(check_preflight_exit(path, None).is_err());
+    }
+}
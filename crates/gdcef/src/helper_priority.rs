@@ -0,0 +1,90 @@
+//! Helper process priority while browsers are in background mode.
+//!
+//! The renderer, GPU and utility processes are shared by every `CefTexture`,
+//! so their priority is only lowered once all of them are in background mode,
+//! and restored as soon as one leaves it or a new one is created. Where the
+//! priority could not be restored (an unprivileged process on Linux without
+//! `RLIMIT_NICE`), it is never lowered.
+
+use godot::global::godot_warn;
+use std::sync::Mutex;
+
+use crate::utils::{ProcessPriority, can_restore_helper_priority, set_helper_process_priority};
+
+struct PriorityState {
+    background_browsers: usize,
+    /// Priority every helper has, or `None` after a partial failure.
+    applied: Option<ProcessPriority>,
+}
+
+static STATE: Mutex<PriorityState> = Mutex::new(PriorityState {
+    background_browsers: 0,
+    applied: Some(ProcessPriority::Normal),
+});
+
+/// Records that a `CefTexture` entered background mode.
+pub fn enter_background() {
+    if let Ok(mut state) = STATE.lock() {
+        state.background_browsers += 1;
+        apply(&mut state);
+    }
+}
+
+/// Records that a `CefTexture` left background mode or was freed while in it.
+pub fn leave_background() {
+    if let Ok(mut state) = STATE.lock() {
+        state.background_browsers = state.background_browsers.saturating_sub(1);
+        apply(&mut state);
+    }
+}
+
+/// Re-evaluates the helper priority after the number of browsers changed.
+pub fn refresh() {
+    if let Ok(mut state) = STATE.lock() {
+        apply(&mut state);
+    }
+}
+
+fn target_priority(background_browsers: usize, browsers: usize) -> ProcessPriority {
+    if background_browsers > 0 && background_browsers >= browsers {
+        ProcessPriority::Background
+    } else {
+        ProcessPriority::Normal
+    }
+}
+
+fn apply(state: &mut PriorityState) {
+    let priority = target_priority(state.background_browsers, crate::cef_init::browser_count());
+    if state.applied == Some(priority) {
+        return;
+    }
+    if priority == ProcessPriority::Background && !can_restore_helper_priority() {
+        return;
+    }
+
+    // Unknown after a failure, so the next change applies it again
+    let failed = set_helper_process_priority(priority);
+    if failed == 0 {
+        state.applied = Some(priority);
+    } else {
+        state.applied = None;
+        godot_warn!(
+            "[HelperPriority] Failed to set {:?} priority on {} helper process(es)",
+            priority,
+            failed
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_target_priority() {
+        assert_eq!(target_priority(0, 0), ProcessPriority::Normal);
+        assert_eq!(target_priority(0, 2), ProcessPriority::Normal);
+        assert_eq!(target_priority(1, 2), ProcessPriority::Normal);
+        assert_eq!(target_priority(2, 2), ProcessPriority::Background);
+    }
+}
@@ -0,0 +1,146 @@
+//! Click counting for mouse presses.
+//!
+//! Godot only flags double clicks, and some platforms flag presses far apart
+//! as double clicks. The count CEF needs (2 selects a word, 3 a paragraph)
+//! is computed here instead: a press continues the sequence of the previous
+//! one if it is the same button, within the double-click time and within a
+//! few pixels of it. Releases report the count of their press, so a drag
+//! after a double click keeps extending the selection by words.
+
+use std::time::{Duration, Instant};
+
+use godot::prelude::Vector2;
+
+/// Default maximum time between the presses of a multi-click.
+pub const DEFAULT_DOUBLE_CLICK_TIME: Duration = Duration::from_millis(500);
+
+/// Maximum distance in pixels between the presses of a multi-click.
+const DOUBLE_CLICK_RADIUS: f32 = 4.0;
+
+/// Longest click sequence; the next press starts a new one.
+const MAX_CLICK_COUNT: i32 = 3;
+
+#[derive(Clone, Copy, Debug)]
+struct Click<B> {
+    button: B,
+    position: Vector2,
+    at: Instant,
+    count: i32,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClickCounter<B> {
+    double_click_time: Duration,
+    last: Option<Click<B>>,
+}
+
+impl<B> Default for ClickCounter<B> {
+    fn default() -> Self {
+        Self::new(DEFAULT_DOUBLE_CLICK_TIME)
+    }
+}
+
+impl<B> ClickCounter<B> {
+    pub fn new(double_click_time: Duration) -> Self {
+        Self {
+            double_click_time,
+            last: None,
+        }
+    }
+}
+
+impl<B: Copy + PartialEq> ClickCounter<B> {
+    /// Records a press and returns its click count.
+    pub fn press(&mut self, button: B, position: Vector2, now: Instant) -> i32 {
+        let count = match self.last {
+            Some(last)
+                if last.button == button
+                    && last.count < MAX_CLICK_COUNT
+                    && now.duration_since(last.at) <= self.double_click_time
+                    && last.position.distance_to(position) <= DOUBLE_CLICK_RADIUS =>
+            {
+                last.count + 1
+            }
+            _ => 1,
+        };
+        self.last = Some(Click {
+            button,
+            position,
+            at: now,
+            count,
+        });
+        count
+    }
+
+    /// Returns the click count of a release, which is that of its press.
+    pub fn release(&self, button: B) -> i32 {
+        self.last
+            .filter(|last| last.button == button)
+            .map_or(1, |last| last.count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LEFT: u8 = 0;
+    const RIGHT: u8 = 1;
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn test_double_and_triple_click() {
+        let mut counter = ClickCounter::default();
+        let start = Instant::now();
+        let position = Vector2::new(100.0, 100.0);
+        assert_eq!(counter.press(LEFT, position, start), 1);
+        assert_eq!(counter.release(LEFT), 1);
+        assert_eq!(counter.press(LEFT, position, start + ms(200)), 2);
+        assert_eq!(counter.release(LEFT), 2);
+        // Small jitter between presses is allowed
+        let jittered = Vector2::new(102.0, 103.0);
+        assert_eq!(counter.press(LEFT, jittered, start + ms(400)), 3);
+        assert_eq!(counter.release(LEFT), 3);
+        // A fourth press starts over
+        assert_eq!(counter.press(LEFT, position, start + ms(600)), 1);
+    }
+
+    #[test]
+    fn test_sequence_resets() {
+        let mut counter = ClickCounter::new(ms(300));
+        let start = Instant::now();
+        let position = Vector2::new(10.0, 10.0);
+
+        // Too slow
+        assert_eq!(counter.press(LEFT, position, start), 1);
+        assert_eq!(counter.press(LEFT, position, start + ms(301)), 1);
+        assert_eq!(counter.press(LEFT, position, start + ms(601)), 2);
+
+        // Too far: rapid clicks in different places stay single clicks
+        let far = Vector2::new(15.0, 10.0);
+        assert_eq!(counter.press(LEFT, far, start + ms(700)), 1);
+
+        // Another button
+        assert_eq!(counter.press(RIGHT, far, start + ms(800)), 1);
+        assert_eq!(counter.release(LEFT), 1);
+        assert_eq!(counter.press(LEFT, far, start + ms(900)), 1);
+    }
+
+    #[test]
+    fn test_drag_release_keeps_press_count() {
+        let mut counter = ClickCounter::default();
+        let start = Instant::now();
+        let position = Vector2::new(50.0, 50.0);
+        counter.press(LEFT, position, start);
+        assert_eq!(counter.press(LEFT, position, start + ms(100)), 2);
+        // Dragging to select by words releases well away from the press,
+        // much later
+        assert_eq!(counter.release(LEFT), 2);
+        // The next press near the drag's end is a new sequence
+        let dragged = Vector2::new(300.0, 50.0);
+        assert_eq!(counter.press(LEFT, dragged, start + ms(200)), 1);
+    }
+}
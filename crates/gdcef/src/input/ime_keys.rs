@@ -0,0 +1,497 @@
+//! Suppression of keys consumed by the IME.
+//!
+//! While IME is active, composed text reaches the page through the IME
+//! proxy, so a key the input method consumed must not also reach the page
+//! as a raw key event, or Korean input ends up with doubled jamo. Whether
+//! the input method consumed a key is only known once it reports a
+//! composition change, which depending on the platform arrives before or
+//! after the key event. Key presses outside a composition are therefore
+//! held back until the end of the frame and dropped if the IME reacted to
+//! them.
+//!
+//! An Enter pressed after a composition ended in the same frame is not part
+//! of it when the proxy text stays the same until the end of the frame, as
+//! happens on Windows when the candidate list was just closed. It is then
+//! forwarded on its own so that pages submitting on Enter see it.
+
+use godot::global::Key;
+use godot::obj::EngineEnum;
+
+use super::KeyInput;
+
+/// Returns whether a key reaches the page even during a composition. Input
+/// methods use arrows, Home/End, Enter, Escape, Space and Backspace to edit
+/// and convert the composition, but leave function keys and paging alone.
+fn is_passthrough_key(key: Key) -> bool {
+    (Key::F1.ord()..=Key::F35.ord()).contains(&key.ord())
+        || matches!(key, Key::PAGEUP | Key::PAGEDOWN)
+}
+
+fn is_enter_key(key: Key) -> bool {
+    matches!(key, Key::ENTER | Key::KP_ENTER)
+}
+
+#[derive(Default)]
+pub struct ImeKeyFilter {
+    composing: bool,
+    /// Whether the IME changed or ended a composition this frame.
+    consumed: bool,
+    /// Key events held back until the frame shows whether the IME took them.
+    pending: Vec<KeyInput>,
+    /// Keys whose press was dropped, so their release is dropped too.
+    suppressed: Vec<Key>,
+    /// Text committed this frame, which a late composition update may repeat.
+    last_commit: Option<String>,
+    /// Enter pressed after the composition ended this frame, dropped if the
+    /// proxy text changes before the frame ends.
+    enter_after_commit: Option<KeyInput>,
+}
+
+impl ImeKeyFilter {
+    /// Records a key event. Returns the events to send to the page now.
+    pub fn key(&mut self, input: KeyInput) -> Vec<KeyInput> {
+        if !input.pressed {
+            if let Some(index) = self.suppressed.iter().position(|&key| key == input.keycode) {
+                self.suppressed.swap_remove(index);
+                return Vec::new();
+            }
+            if self.is_pending(input.keycode) {
+                self.pending.push(input);
+                return Vec::new();
+            }
+            return vec![input];
+        }
+
+        if is_passthrough_key(input.keycode) {
+            return vec![input];
+        }
+        if self.composing || self.consumed {
+            self.suppress(input.keycode);
+            if !self.composing && !input.echo && is_enter_key(input.keycode) {
+                self.enter_after_commit = Some(input);
+            }
+            return Vec::new();
+        }
+        self.pending.push(input);
+        Vec::new()
+    }
+
+    /// Records a composition update from the OS input method. Returns `false`
+    /// if it repeats the text just committed and must be ignored.
+    pub fn composition_updated(&mut self, text: &str) -> bool {
+        if !text.is_empty() && self.last_commit.as_deref() == Some(text) {
+            return false;
+        }
+        if self.composing || !text.is_empty() {
+            self.consumed = true;
+            self.enter_after_commit = None;
+        }
+        self.composing = !text.is_empty();
+        true
+    }
+
+    /// Records text committed through the IME proxy, which must be sent to
+    /// the page exactly once. Returns the held-back key events to send
+    /// before it: text committed outside a composition was typed directly.
+    pub fn committed(&mut self, text: &str) -> Vec<KeyInput> {
+        self.last_commit = Some(text.to_string());
+        self.enter_after_commit = None;
+        if self.composing {
+            self.composing = false;
+            self.consumed = true;
+            return Vec::new();
+        }
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Returns the Enter pressed after the composition ended this frame if
+    /// the IME did not take it, as a press and a release typing a carriage
+    /// return. Its modifiers are kept, so Shift+Enter still inserts a line
+    /// break in text areas instead of submitting. Must be called before
+    /// [`Self::end_frame`].
+    pub fn take_enter_after_commit(&mut self) -> Vec<KeyInput> {
+        let Some(press) = self.enter_after_commit.take() else {
+            return Vec::new();
+        };
+        let release = KeyInput {
+            keycode: press.keycode,
+            unicode: 0,
+            pressed: false,
+            echo: false,
+            modifiers: press.modifiers,
+        };
+        vec![
+            KeyInput {
+                unicode: '\r' as u32,
+                ..press
+            },
+            release,
+        ]
+    }
+
+    /// Ends the frame. Returns the held-back key events the IME did not
+    /// take, which are sent to the page.
+    pub fn end_frame(&mut self) -> Vec<KeyInput> {
+        let pending = std::mem::take(&mut self.pending);
+        let consumed = std::mem::take(&mut self.consumed);
+        self.last_commit = None;
+        self.enter_after_commit = None;
+        if !consumed {
+            return pending;
+        }
+        for input in &pending {
+            if input.pressed {
+                self.suppress(input.keycode);
+            } else if let Some(index) = self.suppressed.iter().position(|&key| key == input.keycode)
+            {
+                self.suppressed.swap_remove(index);
+            }
+        }
+        Vec::new()
+    }
+
+    /// Forgets all state, e.g. when IME is deactivated.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn is_pending(&self, key: Key) -> bool {
+        self.pending
+            .iter()
+            .any(|input| input.pressed && input.keycode == key)
+    }
+
+    fn suppress(&mut self, key: Key) {
+        if !self.suppressed.contains(&key) {
+            self.suppressed.push(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An input event or OS input method callback, in the order the
+    /// platform delivers them.
+    enum Step {
+        Press(Key, char),
+        /// A press with CEF modifier flags.
+        PressWith(Key, char, u32),
+        Release(Key),
+        Update(&'static str),
+        Commit(&'static str),
+        EndFrame,
+    }
+
+    /// What reaches CEF.
+    #[derive(Debug, PartialEq)]
+    enum Call {
+        KeyDown(Key),
+        KeyUp(Key),
+        SetComposition(&'static str),
+        CommitText(&'static str),
+        /// An Enter forwarded after a commit, with its modifiers.
+        ForwardedEnter(u32),
+    }
+
+    use Call::*;
+    use Step::*;
+
+    fn run(steps: &[Step]) -> Vec<Call> {
+        let mut filter = ImeKeyFilter::default();
+        let mut calls = Vec::new();
+        let send = |calls: &mut Vec<Call>, inputs: Vec<KeyInput>| {
+            calls.extend(inputs.into_iter().map(|input| {
+                if input.pressed {
+                    KeyDown(input.keycode)
+                } else {
+                    KeyUp(input.keycode)
+                }
+            }));
+        };
+        for step in steps {
+            match *step {
+                Press(keycode, character) | PressWith(keycode, character, _) => {
+                    let modifiers = match *step {
+                        PressWith(_, _, modifiers) => modifiers,
+                        _ => 0,
+                    };
+                    let inputs = filter.key(KeyInput {
+                        keycode,
+                        unicode: character as u32,
+                        pressed: true,
+                        echo: false,
+                        modifiers,
+                    });
+                    send(&mut calls, inputs);
+                }
+                Release(keycode) => {
+                    let inputs = filter.key(KeyInput {
+                        keycode,
+                        unicode: 0,
+                        pressed: false,
+                        echo: false,
+                        modifiers: 0,
+                    });
+                    send(&mut calls, inputs);
+                }
+                Update(text) => {
+                    if filter.composition_updated(text) {
+                        calls.push(SetComposition(text));
+                    }
+                }
+                Commit(text) => {
+                    let inputs = filter.committed(text);
+                    send(&mut calls, inputs);
+                    calls.push(CommitText(text));
+                }
+                EndFrame => {
+                    let enter = filter.take_enter_after_commit();
+                    if let [press, release] = enter.as_slice() {
+                        assert_eq!(press.unicode, '\r' as u32);
+                        assert!(press.pressed && !release.pressed);
+                        calls.push(ForwardedEnter(press.modifiers));
+                    }
+                    let inputs = filter.end_frame();
+                    send(&mut calls, inputs);
+                }
+            }
+        }
+        calls
+    }
+
+    #[test]
+    fn test_macos_japanese() {
+        // macOS reports the composition after the key event, and repeats the
+        // committed text as a final update
+        let calls = run(&[
+            Press(Key::K, 'k'),
+            Update("ｋ"),
+            Release(Key::K),
+            EndFrame,
+            Press(Key::A, 'a'),
+            Update("か"),
+            EndFrame,
+            Release(Key::A),
+            EndFrame,
+            Press(Key::ENTER, '\0'),
+            Commit("か"),
+            Update("か"),
+            Update(""),
+            Release(Key::ENTER),
+            EndFrame,
+        ]);
+        assert_eq!(
+            calls,
+            vec![
+                SetComposition("ｋ"),
+                SetComposition("か"),
+                CommitText("か"),
+                SetComposition(""),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_windows_korean() {
+        // Windows reports the composition before the key event; a jamo that
+        // cannot join the syllable commits it and starts a new one
+        let calls = run(&[
+            Update("ㅎ"),
+            Press(Key::G, 'ㅎ'),
+            Release(Key::G),
+            EndFrame,
+            Update("하"),
+            Press(Key::K, 'ㅏ'),
+            Release(Key::K),
+            EndFrame,
+            Update("한"),
+            Press(Key::S, 'ㄴ'),
+            Release(Key::S),
+            EndFrame,
+            Commit("한"),
+            Update("ㄱ"),
+            Press(Key::R, 'ㄱ'),
+            Release(Key::R),
+            EndFrame,
+            // Space ends the composition and is typed through the proxy
+            Commit("ㄱ"),
+            Update(""),
+            Press(Key::SPACE, ' '),
+            Commit(" "),
+            Release(Key::SPACE),
+            EndFrame,
+            // Digits are typed directly
+            Press(Key::KEY_1, '1'),
+            Commit("1"),
+            Release(Key::KEY_1),
+            EndFrame,
+        ]);
+        assert_eq!(
+            calls,
+            vec![
+                SetComposition("ㅎ"),
+                SetComposition("하"),
+                SetComposition("한"),
+                CommitText("한"),
+                SetComposition("ㄱ"),
+                CommitText("ㄱ"),
+                SetComposition(""),
+                CommitText(" "),
+                KeyDown(Key::KEY_1),
+                CommitText("1"),
+                KeyUp(Key::KEY_1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_linux_ibus_pinyin() {
+        let calls = run(&[
+            Press(Key::N, 'n'),
+            Update("n"),
+            Release(Key::N),
+            EndFrame,
+            Press(Key::I, 'i'),
+            Update("ni"),
+            Release(Key::I),
+            EndFrame,
+            // Arrows pick candidates; function keys still reach the page
+            Press(Key::DOWN, '\0'),
+            Release(Key::DOWN),
+            Press(Key::F5, '\0'),
+            Release(Key::F5),
+            EndFrame,
+            Press(Key::KEY_1, '1'),
+            Commit("你"),
+            Update(""),
+            Release(Key::KEY_1),
+            EndFrame,
+            // Backspace that empties the composition stays with the IME
+            Press(Key::N, 'n'),
+            Update("n"),
+            Release(Key::N),
+            EndFrame,
+            Press(Key::BACKSPACE, '\0'),
+            Update(""),
+            EndFrame,
+            Release(Key::BACKSPACE),
+            EndFrame,
+            // Outside a composition it edits the page
+            Press(Key::BACKSPACE, '\0'),
+            EndFrame,
+            Release(Key::BACKSPACE),
+            EndFrame,
+        ]);
+        assert_eq!(
+            calls,
+            vec![
+                SetComposition("n"),
+                SetComposition("ni"),
+                KeyDown(Key::F5),
+                KeyUp(Key::F5),
+                CommitText("你"),
+                SetComposition(""),
+                SetComposition("n"),
+                SetComposition(""),
+                KeyDown(Key::BACKSPACE),
+                KeyUp(Key::BACKSPACE),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dead_keys() {
+        // A dead key composes with the next key into one character
+        let calls = run(&[
+            Press(Key::APOSTROPHE, '\''),
+            Update("´"),
+            Release(Key::APOSTROPHE),
+            EndFrame,
+            Press(Key::E, 'e'),
+            Commit("é"),
+            Update(""),
+            Release(Key::E),
+            EndFrame,
+        ]);
+        assert_eq!(
+            calls,
+            vec![SetComposition("´"), CommitText("é"), SetComposition("")]
+        );
+    }
+
+    #[test]
+    fn test_windows_enter_after_commit() {
+        // The composition is committed when the candidate list closes, then
+        // Enter arrives in the same frame without changing the proxy text
+        let calls = run(&[
+            Update("か"),
+            Press(Key::K, 'k'),
+            Release(Key::K),
+            EndFrame,
+            Commit("か"),
+            Update(""),
+            Press(Key::ENTER, '\0'),
+            Release(Key::ENTER),
+            EndFrame,
+            // A later Enter is an ordinary key
+            Press(Key::ENTER, '\0'),
+            Release(Key::ENTER),
+            EndFrame,
+        ]);
+        assert_eq!(
+            calls,
+            vec![
+                SetComposition("か"),
+                CommitText("か"),
+                SetComposition(""),
+                ForwardedEnter(0),
+                KeyDown(Key::ENTER),
+                KeyUp(Key::ENTER),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enter_consumed_after_composition_ends() {
+        // The composition ends before the Enter that commits it, which the
+        // proxy text then shows
+        let calls = run(&[
+            Update("か"),
+            EndFrame,
+            Update(""),
+            Press(Key::ENTER, '\0'),
+            Commit("か"),
+            Release(Key::ENTER),
+            EndFrame,
+        ]);
+        assert_eq!(
+            calls,
+            vec![SetComposition("か"), SetComposition(""), CommitText("か"),]
+        );
+    }
+
+    #[test]
+    fn test_shift_enter_after_commit() {
+        let shift = crate::input::keyboard_modifier_flags(true, false, false, false);
+        let calls = run(&[
+            Update("한"),
+            EndFrame,
+            Commit("한"),
+            Update(""),
+            PressWith(Key::ENTER, '\0', shift),
+            Release(Key::ENTER),
+            EndFrame,
+        ]);
+        assert_eq!(
+            calls,
+            vec![
+                SetComposition("한"),
+                CommitText("한"),
+                SetComposition(""),
+                ForwardedEnter(shift),
+            ]
+        );
+    }
+}
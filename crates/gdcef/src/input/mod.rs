@@ -1,12 +1,26 @@
 use cef::sys::cef_event_flags_t;
-use cef::{ImplBrowserHost, ImplFrame, KeyEvent, KeyEventType, MouseButtonType, MouseEvent};
+use cef::{
+    ImplBrowserHost, ImplFrame, KeyEvent, KeyEventType, MouseButtonType, MouseEvent, PointerType,
+    TouchEvent, TouchEventType,
+};
 use godot::classes::{
     InputEvent, InputEventKey, InputEventMouseButton, InputEventMouseMotion, InputEventPanGesture,
 };
-use godot::global::{Key, MouseButton, MouseButtonMask};
+use godot::global::{Key, KeyModifierMask, MouseButton, MouseButtonMask};
 use godot::prelude::*;
 
+mod click;
+mod ime_keys;
 mod keycode;
+mod motion;
+mod pen;
+mod shortcuts;
+
+pub use click::{ClickCounter, DEFAULT_DOUBLE_CLICK_TIME};
+pub use ime_keys::ImeKeyFilter;
+pub use motion::MouseMoveCoalescer;
+pub use pen::PenTracker;
+pub use shortcuts::BrowserShortcuts;
 
 /// Standard wheel delta for one scroll "notch" (Windows convention used by CEF).
 const WHEEL_DELTA: f32 = 120.0;
@@ -53,53 +67,113 @@ fn create_shortcut(key: Key, with_command_or_ctrl: bool, with_shift: bool) -> Gd
     key_event.to_variant().to()
 }
 
+/// Converts CEF event flags to the `u32` modifiers of CEF events.
+fn event_flags(flags: cef_event_flags_t) -> u32 {
+    // cef_event_flags_t returns u32 on linux and macOS, but i32 on Windows,
+    // so we need to cast to u32 to avoid type mismatch.
+    #[cfg(target_os = "windows")]
+    return flags.0 as u32;
+    #[cfg(not(target_os = "windows"))]
+    return flags.0;
+}
+
+/// Returns the CEF flags of the held keyboard modifiers.
+pub fn keyboard_modifier_flags(shift: bool, ctrl: bool, alt: bool, meta: bool) -> u32 {
+    let mut modifiers = cef_event_flags_t::EVENTFLAG_NONE;
+    if shift {
+        modifiers |= cef_event_flags_t::EVENTFLAG_SHIFT_DOWN;
+    }
+    if ctrl {
+        modifiers |= cef_event_flags_t::EVENTFLAG_CONTROL_DOWN;
+    }
+    if alt {
+        modifiers |= cef_event_flags_t::EVENTFLAG_ALT_DOWN;
+    }
+    if meta {
+        modifiers |= cef_event_flags_t::EVENTFLAG_COMMAND_DOWN;
+    }
+    event_flags(modifiers)
+}
+
 /// Macro to extract keyboard modifier flags from any event with modifier methods
 macro_rules! keyboard_modifiers {
-    ($event:expr) => {{
-        let mut modifiers = cef_event_flags_t::EVENTFLAG_NONE;
-        if $event.is_shift_pressed() {
-            modifiers |= cef_event_flags_t::EVENTFLAG_SHIFT_DOWN;
-        }
-        if $event.is_ctrl_pressed() {
-            modifiers |= cef_event_flags_t::EVENTFLAG_CONTROL_DOWN;
-        }
-        if $event.is_alt_pressed() {
-            modifiers |= cef_event_flags_t::EVENTFLAG_ALT_DOWN;
-        }
-        if $event.is_meta_pressed() {
-            modifiers |= cef_event_flags_t::EVENTFLAG_COMMAND_DOWN;
-        }
+    ($event:expr) => {
+        keyboard_modifier_flags(
+            $event.is_shift_pressed(),
+            $event.is_ctrl_pressed(),
+            $event.is_alt_pressed(),
+            $event.is_meta_pressed(),
+        )
+    };
+}
 
-        // cef_event_flags_t returns u32 on linux and macOS, but i32 on Windows,
-        // so we need to cast to u32 to avoid type mismatch.
-        #[cfg(target_os = "windows")]
-        let ret = modifiers.0 as u32;
-        #[cfg(not(target_os = "windows"))]
-        let ret = modifiers.0;
-        ret
-    }};
+/// Returns the CEF flags of the modifiers in a Godot `KeyModifierMask`.
+/// `CMD_OR_CTRL` maps to Command on macOS and Control elsewhere.
+pub fn key_modifier_mask_flags(mask: u32) -> u32 {
+    let has = |modifier: KeyModifierMask| mask & modifier.ord() as u32 != 0;
+    let cmd_or_ctrl = has(KeyModifierMask::CMD_OR_CTRL);
+    let mut modifiers = keyboard_modifier_flags(
+        has(KeyModifierMask::SHIFT),
+        has(KeyModifierMask::CTRL) || (cmd_or_ctrl && !cfg!(target_os = "macos")),
+        has(KeyModifierMask::ALT),
+        has(KeyModifierMask::META) || (cmd_or_ctrl && cfg!(target_os = "macos")),
+    );
+    if has(KeyModifierMask::KPAD) {
+        modifiers |= event_flags(cef_event_flags_t::EVENTFLAG_IS_KEY_PAD);
+    }
+    modifiers
 }
 
-/// Extracts mouse button modifier flags from a button mask
-fn mouse_button_modifiers(button_mask: MouseButtonMask) -> u32 {
+/// Returns the CEF flags of the held mouse buttons.
+fn mouse_button_flags(left: bool, middle: bool, right: bool) -> u32 {
     let mut modifiers = cef_event_flags_t::EVENTFLAG_NONE;
-
-    if button_mask.is_set(MouseButtonMask::LEFT) {
+    if left {
         modifiers |= cef_event_flags_t::EVENTFLAG_LEFT_MOUSE_BUTTON;
     }
-    if button_mask.is_set(MouseButtonMask::MIDDLE) {
+    if middle {
         modifiers |= cef_event_flags_t::EVENTFLAG_MIDDLE_MOUSE_BUTTON;
     }
-    if button_mask.is_set(MouseButtonMask::RIGHT) {
+    if right {
         modifiers |= cef_event_flags_t::EVENTFLAG_RIGHT_MOUSE_BUTTON;
     }
+    event_flags(modifiers)
+}
 
-    // cef_event_flags_t returns u32 on linux and macOS, but i32 on Windows,
-    // so we need to cast to u32 to avoid type mismatch.
-    #[cfg(target_os = "windows")]
-    return modifiers.0 as u32;
-    #[cfg(not(target_os = "windows"))]
-    return modifiers.0;
+/// Extracts mouse button modifier flags from a button mask
+fn mouse_button_modifiers(button_mask: MouseButtonMask) -> u32 {
+    mouse_button_flags(
+        button_mask.is_set(MouseButtonMask::LEFT),
+        button_mask.is_set(MouseButtonMask::MIDDLE),
+        button_mask.is_set(MouseButtonMask::RIGHT),
+    )
+}
+
+/// Returns the CEF button type and held-button flag of a clickable button.
+pub fn mouse_button_type(button: MouseButton) -> Option<(MouseButtonType, u32)> {
+    match button {
+        MouseButton::LEFT => Some((
+            MouseButtonType::LEFT,
+            mouse_button_flags(true, false, false),
+        )),
+        MouseButton::MIDDLE => Some((
+            MouseButtonType::MIDDLE,
+            mouse_button_flags(false, true, false),
+        )),
+        MouseButton::RIGHT => Some((
+            MouseButtonType::RIGHT,
+            mouse_button_flags(false, false, true),
+        )),
+        _ => None,
+    }
+}
+
+/// Converts a scroll in wheel notches (positive `y` scrolls down, positive
+/// `x` right) to CEF wheel deltas.
+pub fn wheel_deltas(notches: Vector2) -> (i32, i32) {
+    (
+        (notches.x * WHEEL_DELTA) as i32,
+        (-notches.y * WHEEL_DELTA) as i32,
+    )
 }
 
 /// Creates a CEF mouse event from Godot position and DPI scale
@@ -123,6 +197,7 @@ pub fn create_mouse_event(
 pub fn handle_mouse_button(
     host: &impl ImplBrowserHost,
     event: &Gd<InputEventMouseButton>,
+    clicks: &mut ClickCounter<MouseButton>,
     pixel_scale_factor: f32,
     device_scale_factor: f32,
 ) {
@@ -132,23 +207,24 @@ pub fn handle_mouse_button(
     let mouse_event =
         create_mouse_event(position, pixel_scale_factor, device_scale_factor, modifiers);
 
+    if let Some((button_type, _)) = mouse_button_type(event.get_button_index()) {
+        let button = event.get_button_index();
+        let mouse_up = !event.is_pressed();
+        let click_count = if mouse_up {
+            clicks.release(button)
+        } else {
+            clicks.press(button, position, std::time::Instant::now())
+        };
+        host.send_mouse_click_event(
+            Some(&mouse_event),
+            button_type,
+            mouse_up as i32,
+            click_count,
+        );
+        return;
+    }
+
     match event.get_button_index() {
-        MouseButton::LEFT | MouseButton::MIDDLE | MouseButton::RIGHT => {
-            let button_type = match event.get_button_index() {
-                MouseButton::LEFT => MouseButtonType::LEFT,
-                MouseButton::MIDDLE => MouseButtonType::MIDDLE,
-                MouseButton::RIGHT => MouseButtonType::RIGHT,
-                _ => unreachable!(),
-            };
-            let mouse_up = !event.is_pressed();
-            let click_count = if event.is_double_click() { 2 } else { 1 };
-            host.send_mouse_click_event(
-                Some(&mouse_event),
-                button_type,
-                mouse_up as i32,
-                click_count,
-            );
-        }
         MouseButton::WHEEL_UP => {
             let delta = (WHEEL_DELTA * event.get_factor()) as i32;
             host.send_mouse_wheel_event(Some(&mouse_event), 0, delta);
@@ -169,14 +245,15 @@ pub fn handle_mouse_button(
     }
 }
 
-/// Handles mouse motion events and sends them to CEF browser host
-pub fn handle_mouse_motion(
-    host: &impl ImplBrowserHost,
+/// Converts a mouse motion event into a CEF mouse event.
+/// Also returns whether a mouse button is held during the move.
+pub fn mouse_motion_event(
     event: &Gd<InputEventMouseMotion>,
     pixel_scale_factor: f32,
     device_scale_factor: f32,
-) {
-    let modifiers = keyboard_modifiers!(event) | mouse_button_modifiers(event.get_button_mask());
+) -> (MouseEvent, bool) {
+    let button_modifiers = mouse_button_modifiers(event.get_button_mask());
+    let modifiers = keyboard_modifiers!(event) | button_modifiers;
     let position = event.get_position();
     let mouse_event = create_mouse_event(
         position,
@@ -184,7 +261,98 @@ pub fn handle_mouse_motion(
         device_scale_factor,
         modifiers as i32,
     );
-    host.send_mouse_move_event(Some(&mouse_event), false as i32);
+    (mouse_event, button_modifiers != 0)
+}
+
+/// Sends a mouse move event to CEF browser host
+pub fn send_mouse_move(host: &impl ImplBrowserHost, mouse_event: &MouseEvent) {
+    host.send_mouse_move_event(Some(mouse_event), false as i32);
+}
+
+/// Sends a pen event as a CEF touch event.
+fn send_pen_touch(
+    host: &impl ImplBrowserHost,
+    touch: &pen::PenTouch,
+    position: Vector2,
+    modifiers: u32,
+    pixel_scale_factor: f32,
+    device_scale_factor: f32,
+) {
+    let type_ = match touch.phase {
+        pen::PenPhase::Pressed => TouchEventType::PRESSED,
+        pen::PenPhase::Moved => TouchEventType::MOVED,
+        pen::PenPhase::Released => TouchEventType::RELEASED,
+    };
+    let touch_event = TouchEvent {
+        id: pen::PEN_TOUCH_ID,
+        x: position.x * pixel_scale_factor / device_scale_factor,
+        y: position.y * pixel_scale_factor / device_scale_factor,
+        rotation_angle: touch.rotation,
+        pressure: touch.pressure,
+        type_,
+        modifiers,
+        pointer_type: if touch.eraser {
+            PointerType::ERASER
+        } else {
+            PointerType::PEN
+        },
+        ..Default::default()
+    };
+    host.send_touch_event(Some(&touch_event));
+}
+
+/// Sends a press or release of the primary button as a pen touch when it
+/// starts or ends a pen stroke. Returns false if it is a mouse click.
+pub fn handle_pen_button(
+    host: &impl ImplBrowserHost,
+    event: &Gd<InputEventMouseButton>,
+    pen: &mut PenTracker,
+    pen_as_touch: bool,
+    pixel_scale_factor: f32,
+    device_scale_factor: f32,
+) -> bool {
+    if event.get_button_index() != MouseButton::LEFT {
+        return false;
+    }
+    let Some(touch) = pen.button(event.is_pressed(), pen_as_touch) else {
+        return false;
+    };
+    send_pen_touch(
+        host,
+        &touch,
+        event.get_position(),
+        keyboard_modifiers!(event),
+        pixel_scale_factor,
+        device_scale_factor,
+    );
+    true
+}
+
+/// Sends a motion as a pen touch while a pen stroke is in progress. Returns
+/// false if it is a mouse move.
+pub fn handle_pen_motion(
+    host: &impl ImplBrowserHost,
+    event: &Gd<InputEventMouseMotion>,
+    pen: &mut PenTracker,
+    pixel_scale_factor: f32,
+    device_scale_factor: f32,
+) -> bool {
+    let Some(touch) = pen.motion(
+        event.get_pressure(),
+        event.get_tilt(),
+        event.get_pen_inverted(),
+    ) else {
+        return false;
+    };
+    send_pen_touch(
+        host,
+        &touch,
+        event.get_position(),
+        keyboard_modifiers!(event),
+        pixel_scale_factor,
+        device_scale_factor,
+    );
+    true
 }
 
 /// Handles pan gesture events (trackpad scrolling) and sends them to CEF browser host
@@ -215,36 +383,40 @@ pub fn handle_pan_gesture(
     }
 }
 
-/// Handles keyboard events and sends them to CEF browser host
+/// A key press or release, from a Godot event or injected by script.
+pub struct KeyInput {
+    pub keycode: Key,
+    /// Unicode character the key produces, 0 for none.
+    pub unicode: u32,
+    pub pressed: bool,
+    /// Whether this is an auto-repeat of a held key.
+    pub echo: bool,
+    /// CEF modifier flags.
+    pub modifiers: u32,
+}
+
+/// Handles keyboard events and sends them to CEF browser host. While IME is
+/// active, `ime_keys` filters out the keys the input method consumes.
 pub fn handle_key_event(
     host: &impl ImplBrowserHost,
     frame: Option<&impl ImplFrame>,
     event: &Gd<InputEventKey>,
-    focus_on_editable_field: bool,
+    ime_keys: Option<&mut ImeKeyFilter>,
 ) {
     let mut modifiers = keyboard_modifiers!(event);
-    #[cfg(target_os = "windows")]
-    let keypad_key_modifier = cef_event_flags_t::EVENTFLAG_IS_KEY_PAD.0 as u32;
-    #[cfg(not(target_os = "windows"))]
-    let keypad_key_modifier = cef_event_flags_t::EVENTFLAG_IS_KEY_PAD.0;
 
     // Check if it's from the keypad
     if is_keypad_key(event.get_physical_keycode()) {
-        modifiers |= keypad_key_modifier;
+        modifiers |= event_flags(cef_event_flags_t::EVENTFLAG_IS_KEY_PAD);
     }
 
     let is_pressed = event.is_pressed();
     let is_echo = event.is_echo();
-    let keycode = event.get_keycode();
-
-    // Godot also sends a KEY event for the NONE key for characters, which we don't want to process.
-    if keycode == Key::NONE {
-        return;
-    }
 
     // Handle shortcuts using pre-cached Shortcut objects
     if is_pressed
         && !is_echo
+        && event.get_keycode() != Key::NONE
         && let Some(frame) = frame
     {
         let input_event: Gd<InputEvent> = event.to_variant().to();
@@ -271,6 +443,41 @@ pub fn handle_key_event(
         }
     }
 
+    let input = KeyInput {
+        keycode: event.get_keycode(),
+        unicode: event.get_unicode(),
+        pressed: is_pressed,
+        echo: is_echo,
+        modifiers,
+    };
+    match ime_keys {
+        Some(filter) => send_key_inputs(host, filter.key(input), true),
+        None => send_key_inputs(host, [input], false),
+    }
+}
+
+/// Sends key presses and releases to CEF browser host.
+pub fn send_key_inputs(
+    host: &impl ImplBrowserHost,
+    inputs: impl IntoIterator<Item = KeyInput>,
+    focus_on_editable_field: bool,
+) {
+    for input in inputs {
+        for key_event in key_events(&input, focus_on_editable_field) {
+            host.send_key_event(Some(&key_event));
+        }
+    }
+}
+
+/// Builds the CEF key events for a key press or release.
+pub fn key_events(input: &KeyInput, focus_on_editable_field: bool) -> Vec<KeyEvent> {
+    let keycode = input.keycode;
+
+    // Godot also sends a KEY event for the NONE key for characters, which we don't want to process.
+    if keycode == Key::NONE {
+        return Vec::new();
+    }
+
     // Get the Windows virtual key code from Godot key (CEF expects this on all platforms)
     let windows_key_code = keycode::godot_key_to_windows_keycode(keycode);
 
@@ -279,73 +486,72 @@ pub fn handle_key_event(
 
     // Get the character code - for printable keys use unicode,
     // for control characters use their ASCII codes
-    let unicode = event.get_unicode();
-
-    let character = if unicode != 0 {
-        unicode as u16
+    let character = if input.unicode != 0 {
+        input.unicode as u16
     } else {
         // Use ASCII codes for control characters
         get_control_char_code(keycode)
     };
 
-    // For key press events, send RAWKEYDOWN for initial press, KEYDOWN for repeat
-    if is_pressed {
-        let key_event = KeyEvent {
-            type_: if is_echo {
-                KeyEventType::KEYDOWN
-            } else {
-                KeyEventType::RAWKEYDOWN
-            },
-            modifiers,
-            windows_key_code,
-            native_key_code,
-            is_system_key: 0,
-            character,
-            unmodified_character: character,
-            focus_on_editable_field: focus_on_editable_field as _,
-            ..Default::default()
-        };
-        host.send_key_event(Some(&key_event));
+    let key_event = |type_| KeyEvent {
+        type_,
+        modifiers: input.modifiers,
+        windows_key_code,
+        native_key_code,
+        is_system_key: 0,
+        character,
+        unmodified_character: character,
+        focus_on_editable_field: focus_on_editable_field as _,
+        ..Default::default()
+    };
+
+    let mut events = Vec::new();
+    if input.pressed {
+        // For key press events, send RAWKEYDOWN for initial press, KEYDOWN for repeat
+        events.push(key_event(if input.echo {
+            KeyEventType::KEYDOWN
+        } else {
+            KeyEventType::RAWKEYDOWN
+        }));
 
         // Send a CHAR event for printable characters AND control characters that need it
         // (Backspace, Tab, Enter need CHAR events for text input to work)
         // When focus is on an editable field, we don't need to send CHAR events.
-        if should_send_char_event(keycode, unicode) && !focus_on_editable_field {
-            let char_event = KeyEvent {
-                type_: KeyEventType::CHAR,
-                modifiers,
-                // For CHAR events, use the character code (not the virtual key code)
-                // for windows_key_code and native_key_code, matching Windows WM_CHAR
-                // behavior where wParam contains the character value.
-                windows_key_code: character as i32,
-                native_key_code: character as i32,
-                is_system_key: 0,
-                character,
-                unmodified_character: character,
-                focus_on_editable_field: focus_on_editable_field as _,
-                ..Default::default()
-            };
-            host.send_key_event(Some(&char_event));
+        if should_send_char_event(keycode, input.unicode) && !focus_on_editable_field {
+            events.push(char_event(character, input.modifiers));
         }
-    } else {
+    } else if !is_navigation_key(keycode) {
         // Key release event
         // Skip KEYUP for navigation keys - works around a CEF issue where KEYUP
         // triggers arrow key actions on macOS
-        if !is_navigation_key(keycode) {
-            let key_event = KeyEvent {
-                type_: KeyEventType::KEYUP,
-                modifiers,
-                windows_key_code,
-                native_key_code,
-                is_system_key: 0,
-                character,
-                unmodified_character: character,
-                focus_on_editable_field: focus_on_editable_field as _,
-                ..Default::default()
-            };
-            host.send_key_event(Some(&key_event));
-        }
+        events.push(key_event(KeyEventType::KEYUP));
     }
+    events
+}
+
+/// Builds a CHAR event typing the UTF-16 code unit `character`.
+fn char_event(character: u16, modifiers: u32) -> KeyEvent {
+    KeyEvent {
+        type_: KeyEventType::CHAR,
+        modifiers,
+        // For CHAR events, use the character code (not the virtual key code)
+        // for windows_key_code and native_key_code, matching Windows WM_CHAR
+        // behavior where wParam contains the character value.
+        windows_key_code: character as i32,
+        native_key_code: character as i32,
+        is_system_key: 0,
+        character,
+        unmodified_character: character,
+        ..Default::default()
+    }
+}
+
+/// Builds the CHAR events that type `text`, one per UTF-16 code unit as
+/// Windows delivers characters outside the BMP.
+pub fn text_char_events(text: &str) -> Vec<KeyEvent> {
+    text.encode_utf16()
+        .map(|character| char_event(character, 0))
+        .collect()
 }
 
 /// Returns the ASCII control character code for special keys
@@ -438,40 +644,77 @@ pub fn ime_commit_text(host: &impl ImplBrowserHost, text: &str) {
     host.ime_commit_text(Some(&cef_text), Some(&invalid_range), 0);
 }
 
+/// A clause of an IME composition, in characters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImeClause {
+    pub start: u32,
+    pub end: u32,
+    /// Whether this is the clause being converted.
+    pub selected: bool,
+}
+
+/// Splits a composition of `len` characters around the selection reported
+/// by the OS input method, which starts at `cursor` and spans
+/// `selection_length` characters.
+pub fn ime_clauses(len: u32, cursor: u32, selection_length: u32) -> Vec<ImeClause> {
+    let start = cursor.min(len);
+    let end = cursor.saturating_add(selection_length).min(len);
+    [(0, start, false), (start, end, true), (end, len, false)]
+        .into_iter()
+        .filter(|(start, end, _)| start < end)
+        .map(|(start, end, selected)| ImeClause {
+            start,
+            end,
+            selected,
+        })
+        .collect()
+}
+
+/// Converts a character index in `text` to the UTF-16 offset CEF expects.
+pub fn utf16_offset(text: &str, char_index: u32) -> u32 {
+    text.chars()
+        .take(char_index as usize)
+        .map(char::len_utf16)
+        .sum::<usize>() as u32
+}
+
 pub fn ime_set_composition(
     host: &impl ImplBrowserHost,
     text: &str,
-    selection_start: u32,
-    selection_end: u32,
+    clauses: &[ImeClause],
+    cursor: u32,
 ) {
     let cef_text: cef::CefString = text.into();
-    let text_len = text.chars().count() as u32;
-
-    // Create an underline for the entire composition text
-    let underline = cef::CompositionUnderline {
-        size: std::mem::size_of::<cef::CompositionUnderline>(),
-        range: cef::Range {
-            from: 0,
-            to: text_len,
-        },
-        // Use default/system IME underline color (0 lets CEF choose an appropriate color)
-        color: 0,
-        background_color: 0,
-        thick: 0, // thin underline
-        style: cef::CompositionUnderlineStyle::SOLID,
+    let utf16_range = |start: u32, end: u32| cef::Range {
+        from: utf16_offset(text, start),
+        to: utf16_offset(text, end),
     };
-    let underlines = [underline];
+
+    // One underline per clause, thick for the clause being converted
+    let underlines: Vec<_> = clauses
+        .iter()
+        .map(|clause| cef::CompositionUnderline {
+            size: std::mem::size_of::<cef::CompositionUnderline>(),
+            range: utf16_range(clause.start, clause.end),
+            // Use default/system IME underline color (0 lets CEF choose an appropriate color)
+            color: 0,
+            background_color: 0,
+            thick: clause.selected as _,
+            style: cef::CompositionUnderlineStyle::SOLID,
+        })
+        .collect();
 
     let invalid_range = cef::Range {
         from: u32::MAX,
         to: u32::MAX,
     };
 
-    // Selection range is at the cursor position
-    let selection_range = cef::Range {
-        from: selection_start,
-        to: selection_end,
-    };
+    // Selection range is the clause being converted, or the cursor position
+    let selection_range = clauses
+        .iter()
+        .find(|clause| clause.selected)
+        .map(|clause| utf16_range(clause.start, clause.end))
+        .unwrap_or_else(|| utf16_range(cursor, cursor));
 
     host.ime_set_composition(
         Some(&cef_text),
@@ -480,3 +723,132 @@ pub fn ime_set_composition(
         Some(&selection_range),
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cef::sys::{cef_key_event_type_t, cef_mouse_button_type_t};
+
+    fn key_input(keycode: Key, unicode: u32, pressed: bool) -> KeyInput {
+        KeyInput {
+            keycode,
+            unicode,
+            pressed,
+            echo: false,
+            modifiers: 0,
+        }
+    }
+
+    fn event_types(events: &[KeyEvent]) -> Vec<cef_key_event_type_t> {
+        events.iter().map(|event| *event.type_.as_ref()).collect()
+    }
+
+    #[test]
+    fn test_key_modifier_mask_flags() {
+        assert_eq!(key_modifier_mask_flags(0), 0);
+        let shift_alt = KeyModifierMask::SHIFT.ord() as u32 | KeyModifierMask::ALT.ord() as u32;
+        assert_eq!(
+            key_modifier_mask_flags(shift_alt),
+            keyboard_modifier_flags(true, false, true, false)
+        );
+        let cmd_or_ctrl = key_modifier_mask_flags(KeyModifierMask::CMD_OR_CTRL.ord() as u32);
+        if cfg!(target_os = "macos") {
+            assert_eq!(
+                cmd_or_ctrl,
+                keyboard_modifier_flags(false, false, false, true)
+            );
+        } else {
+            assert_eq!(
+                cmd_or_ctrl,
+                keyboard_modifier_flags(false, true, false, false)
+            );
+        }
+    }
+
+    #[test]
+    fn test_mouse_button_type() {
+        let (button_type, flags) = mouse_button_type(MouseButton::RIGHT).unwrap();
+        assert_eq!(*button_type.as_ref(), cef_mouse_button_type_t::MBT_RIGHT);
+        assert_eq!(flags, mouse_button_flags(false, false, true));
+        assert!(mouse_button_type(MouseButton::WHEEL_UP).is_none());
+    }
+
+    #[test]
+    fn test_wheel_deltas() {
+        assert_eq!(wheel_deltas(Vector2::new(0.0, 1.0)), (0, -120));
+        assert_eq!(wheel_deltas(Vector2::new(-0.5, 0.0)), (-60, 0));
+    }
+
+    #[test]
+    fn test_key_events_printable() {
+        let events = key_events(&key_input(Key::A, 'a' as u32, true), false);
+        assert_eq!(
+            event_types(&events),
+            vec![
+                cef_key_event_type_t::KEYEVENT_RAWKEYDOWN,
+                cef_key_event_type_t::KEYEVENT_CHAR
+            ]
+        );
+        assert_eq!(events[1].character, 'a' as u16);
+
+        // Editable fields receive characters through the IME instead
+        let events = key_events(&key_input(Key::A, 'a' as u32, true), true);
+        assert_eq!(
+            event_types(&events),
+            vec![cef_key_event_type_t::KEYEVENT_RAWKEYDOWN]
+        );
+
+        let events = key_events(&key_input(Key::A, 'a' as u32, false), false);
+        assert_eq!(
+            event_types(&events),
+            vec![cef_key_event_type_t::KEYEVENT_KEYUP]
+        );
+    }
+
+    #[test]
+    fn test_key_events_skipped() {
+        assert!(key_events(&key_input(Key::NONE, 'a' as u32, true), false).is_empty());
+        assert!(key_events(&key_input(Key::LEFT, 0, false), false).is_empty());
+    }
+
+    #[test]
+    fn test_text_char_events() {
+        let events = text_char_events("a😀");
+        assert_eq!(
+            event_types(&events),
+            vec![cef_key_event_type_t::KEYEVENT_CHAR; 3]
+        );
+        assert_eq!(events[0].character, 'a' as u16);
+        assert_eq!(events[1].character, 0xD83D);
+        assert_eq!(events[2].character, 0xDE00);
+    }
+
+    #[test]
+    fn test_ime_clauses() {
+        let clause = |start, end, selected| ImeClause {
+            start,
+            end,
+            selected,
+        };
+        assert_eq!(ime_clauses(0, 0, 0), vec![]);
+        assert_eq!(ime_clauses(5, 5, 0), vec![clause(0, 5, false)]);
+        assert_eq!(
+            ime_clauses(6, 2, 2),
+            vec![clause(0, 2, false), clause(2, 4, true), clause(4, 6, false)]
+        );
+        assert_eq!(ime_clauses(4, 0, 4), vec![clause(0, 4, true)]);
+        // Selections past the end are clamped
+        assert_eq!(
+            ime_clauses(3, 2, 10),
+            vec![clause(0, 2, false), clause(2, 3, true)]
+        );
+    }
+
+    #[test]
+    fn test_utf16_offset() {
+        assert_eq!(utf16_offset("abc", 2), 2);
+        assert_eq!(utf16_offset("日本語", 2), 2);
+        assert_eq!(utf16_offset("😀a😀", 2), 3);
+        assert_eq!(utf16_offset("ab", 10), 2);
+    }
+}
@@ -0,0 +1,119 @@
+//! Coalescing of mouse move events.
+//!
+//! High polling rate mice deliver many motion events per rendered frame.
+//! Only the latest hover position per frame is forwarded to CEF; while a
+//! button is held (dragging, text selection) intermediate points matter, so
+//! moves are forwarded at a capped rate instead.
+
+use std::time::{Duration, Instant};
+
+/// Minimum interval between forwarded moves while a button is held (250 Hz).
+const DRAG_MOVE_INTERVAL: Duration = Duration::from_millis(4);
+
+pub struct MouseMoveCoalescer<T> {
+    enabled: bool,
+    pending: Option<T>,
+    last_drag_move: Option<Instant>,
+}
+
+impl<T> Default for MouseMoveCoalescer<T> {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            pending: None,
+            last_drag_move: None,
+        }
+    }
+}
+
+impl<T> MouseMoveCoalescer<T> {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Enables or disables coalescing. Returns a held-back move that should
+    /// be sent now when coalescing is turned off.
+    pub fn set_enabled(&mut self, enabled: bool) -> Option<T> {
+        self.enabled = enabled;
+        if enabled { None } else { self.take_pending() }
+    }
+
+    /// Records a move. Returns the move if it should be sent right away;
+    /// otherwise it replaces any held-back move until [`Self::take_pending`].
+    pub fn push(&mut self, event: T, button_pressed: bool, now: Instant) -> Option<T> {
+        if !self.enabled {
+            return Some(event);
+        }
+
+        if !button_pressed {
+            self.last_drag_move = None;
+            self.pending = Some(event);
+            return None;
+        }
+
+        let due = self
+            .last_drag_move
+            .is_none_or(|last| now.duration_since(last) >= DRAG_MOVE_INTERVAL);
+        if due {
+            self.last_drag_move = Some(now);
+            self.pending = None;
+            Some(event)
+        } else {
+            self.pending = Some(event);
+            None
+        }
+    }
+
+    /// Takes the held-back move, if any. Called once per frame and before
+    /// any other input event so CEF sees events in order.
+    pub fn take_pending(&mut self) -> Option<T> {
+        self.pending.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hover_moves_keep_only_latest() {
+        let mut coalescer = MouseMoveCoalescer::default();
+        let now = Instant::now();
+        assert_eq!(coalescer.push(1, false, now), None);
+        assert_eq!(coalescer.push(2, false, now), None);
+        assert_eq!(coalescer.push(3, false, now), None);
+        assert_eq!(coalescer.take_pending(), Some(3));
+        assert_eq!(coalescer.take_pending(), None);
+    }
+
+    #[test]
+    fn test_drag_moves_are_rate_limited() {
+        let mut coalescer = MouseMoveCoalescer::default();
+        let start = Instant::now();
+        assert_eq!(coalescer.push(1, true, start), Some(1));
+        assert_eq!(
+            coalescer.push(2, true, start + Duration::from_millis(1)),
+            None
+        );
+        assert_eq!(
+            coalescer.push(3, true, start + Duration::from_millis(2)),
+            None
+        );
+        assert_eq!(coalescer.push(4, true, start + DRAG_MOVE_INTERVAL), Some(4));
+        assert_eq!(coalescer.take_pending(), None);
+
+        assert_eq!(coalescer.push(5, true, start + DRAG_MOVE_INTERVAL), None);
+        assert_eq!(coalescer.take_pending(), Some(5));
+    }
+
+    #[test]
+    fn test_disabled_forwards_every_move() {
+        let mut coalescer = MouseMoveCoalescer::default();
+        let now = Instant::now();
+        assert_eq!(coalescer.push(1, false, now), None);
+        assert_eq!(coalescer.set_enabled(false), Some(1));
+        assert_eq!(coalescer.push(2, false, now), Some(2));
+        assert_eq!(coalescer.push(3, true, now), Some(3));
+        assert_eq!(coalescer.take_pending(), None);
+    }
+}
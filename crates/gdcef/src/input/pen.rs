@@ -0,0 +1,165 @@
+//! Pen input forwarded as touch events.
+//!
+//! Godot delivers pens as mouse events, with pressure and tilt on the
+//! motions. With `pen_as_touch`, a stroke whose press follows a pen motion
+//! is sent to CEF as pen touch events instead, so `PointerEvent.pressure`
+//! follows the stylus. Strokes that cannot be told apart from a mouse, e.g.
+//! because the pen reported no pressure or tilt before touching down, stay
+//! mouse events from press to release.
+
+use godot::prelude::Vector2;
+
+/// Touch id of the pen, far from the indices Godot gives finger touches
+/// (0, 1, ...), so a pen and fingers never share a pointer id.
+pub const PEN_TOUCH_ID: i32 = i32::MAX;
+
+/// Pressure of a press, which Godot reports without one.
+const DEFAULT_PRESSURE: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PenPhase {
+    Pressed,
+    Moved,
+    Released,
+}
+
+/// A pen event to send as a touch.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PenTouch {
+    pub phase: PenPhase,
+    pub pressure: f32,
+    /// Direction the pen leans towards, clockwise from the x axis in
+    /// radians. CEF touches have no tilt, so the page gets it as the
+    /// rotation of the contact.
+    pub rotation: f32,
+    /// The pen is upside down (eraser end).
+    pub eraser: bool,
+}
+
+/// Returns whether a motion comes from a pen rather than a mouse. Mice have
+/// no tilt and report a pressure of exactly 0 or 1 on some platforms.
+fn is_pen_motion(pressure: f32, tilt: Vector2, inverted: bool) -> bool {
+    inverted || tilt != Vector2::ZERO || (pressure > 0.0 && pressure < 1.0)
+}
+
+fn tilt_rotation(tilt: Vector2) -> f32 {
+    if tilt == Vector2::ZERO {
+        0.0
+    } else {
+        tilt.y.atan2(tilt.x)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PenTracker {
+    /// The last motion came from a pen.
+    pen_hovering: bool,
+    last_pressure: f32,
+    rotation: f32,
+    eraser: bool,
+    in_stroke: bool,
+}
+
+impl PenTracker {
+    /// Records a motion. Returns the touch to send instead of a mouse move
+    /// while a pen stroke is in progress.
+    pub fn motion(&mut self, pressure: f32, tilt: Vector2, inverted: bool) -> Option<PenTouch> {
+        self.pen_hovering = is_pen_motion(pressure, tilt, inverted);
+        if self.pen_hovering {
+            self.rotation = tilt_rotation(tilt);
+            self.eraser = inverted;
+        }
+        if !self.in_stroke {
+            return None;
+        }
+        if pressure > 0.0 {
+            self.last_pressure = pressure;
+        }
+        Some(self.touch(PenPhase::Moved))
+    }
+
+    /// Records a press or release of the primary button. Returns the touch
+    /// to send instead of the mouse click when it starts or ends a pen
+    /// stroke; `enabled` only matters when a stroke starts.
+    pub fn button(&mut self, pressed: bool, enabled: bool) -> Option<PenTouch> {
+        match (pressed, self.in_stroke) {
+            (true, false) if enabled && self.pen_hovering => {
+                self.in_stroke = true;
+                self.last_pressure = DEFAULT_PRESSURE;
+                Some(self.touch(PenPhase::Pressed))
+            }
+            (false, true) => {
+                self.in_stroke = false;
+                Some(self.touch(PenPhase::Released))
+            }
+            _ => None,
+        }
+    }
+
+    fn touch(&self, phase: PenPhase) -> PenTouch {
+        PenTouch {
+            phase,
+            pressure: if phase == PenPhase::Released {
+                0.0
+            } else {
+                self.last_pressure
+            },
+            rotation: self.rotation,
+            eraser: self.eraser,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NO_TILT: Vector2 = Vector2::ZERO;
+
+    #[test]
+    fn test_pen_stroke() {
+        let mut pen = PenTracker::default();
+        let tilt = Vector2::new(0.0, 0.5);
+        assert_eq!(pen.motion(0.0, tilt, false), None);
+
+        let pressed = pen.button(true, true).unwrap();
+        assert_eq!(pressed.phase, PenPhase::Pressed);
+        assert_eq!(pressed.pressure, DEFAULT_PRESSURE);
+        assert!((pressed.rotation - std::f32::consts::FRAC_PI_2).abs() < 1e-6);
+
+        let moved = pen.motion(0.8, tilt, false).unwrap();
+        assert_eq!(moved.phase, PenPhase::Moved);
+        assert_eq!(moved.pressure, 0.8);
+        // Full pressure without tilt is still part of the stroke
+        assert_eq!(pen.motion(1.0, NO_TILT, false).unwrap().pressure, 1.0);
+
+        let released = pen.button(false, true).unwrap();
+        assert_eq!(released.phase, PenPhase::Released);
+        assert_eq!(released.pressure, 0.0);
+        assert_eq!(pen.motion(0.0, tilt, false), None);
+    }
+
+    #[test]
+    fn test_mouse_falls_back() {
+        let mut pen = PenTracker::default();
+        // Some platforms report full pressure for mice with a button held
+        assert_eq!(pen.motion(0.0, NO_TILT, false), None);
+        assert_eq!(pen.button(true, true), None);
+        assert_eq!(pen.motion(1.0, NO_TILT, false), None);
+        assert_eq!(pen.button(false, true), None);
+
+        // A pen stroke while disabled stays a mouse stroke
+        pen.motion(0.3, NO_TILT, false);
+        assert_eq!(pen.button(true, false), None);
+        assert_eq!(pen.motion(0.6, NO_TILT, false), None);
+        assert_eq!(pen.button(false, false), None);
+    }
+
+    #[test]
+    fn test_eraser() {
+        let mut pen = PenTracker::default();
+        pen.motion(0.0, NO_TILT, true);
+        assert!(pen.button(true, true).unwrap().eraser);
+        assert!(pen.motion(0.4, NO_TILT, true).unwrap().eraser);
+    }
+}
@@ -0,0 +1,129 @@
+//! Key chords taken from the page and reported to Godot instead, for
+//! browser-like shortcuts such as Ctrl+L or F5.
+
+use std::collections::HashMap;
+
+use godot::global::{Key, KeyModifierMask};
+use godot::obj::{EngineBitfield, EngineEnum};
+
+use super::keycode::godot_key_to_windows_keycode;
+use super::{key_modifier_mask_flags, keyboard_modifier_flags};
+
+/// Keeps the modifiers a chord is matched on, dropping lock keys, held mouse
+/// buttons and the keypad flag.
+fn chord_modifiers(flags: u32) -> u32 {
+    flags & keyboard_modifier_flags(true, true, true, true)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct Chord {
+    windows_key_code: i32,
+    modifiers: u32,
+}
+
+/// Shortcuts registered with `register_browser_shortcut`, by chord.
+#[derive(Debug, Default)]
+pub struct BrowserShortcuts {
+    actions: HashMap<Chord, String>,
+}
+
+impl BrowserShortcuts {
+    /// Registers `action` for the Godot `keycode` with the `KeyModifierMask`
+    /// flags in `modifiers`. Flags combined into `keycode`, as in
+    /// `KEY_MASK_CTRL | KEY_R`, count too. Registering a chord again
+    /// replaces its action. Returns `false` for an invalid keycode.
+    pub fn register(&mut self, action: &str, keycode: i64, modifiers: i64) -> bool {
+        let code_mask = KeyModifierMask::CODE_MASK.ord() as i64;
+        let code = keycode & code_mask;
+        let mask = (keycode & !code_mask) | modifiers;
+        let Some(key) = i32::try_from(code).ok().and_then(Key::try_from_ord) else {
+            return false;
+        };
+        if key == Key::NONE {
+            return false;
+        }
+
+        let chord = Chord {
+            windows_key_code: godot_key_to_windows_keycode(key),
+            modifiers: chord_modifiers(key_modifier_mask_flags(mask as u32)),
+        };
+        self.actions.insert(chord, action.to_string());
+        true
+    }
+
+    /// Removes every chord of `action`. Returns `false` if it had none.
+    pub fn unregister(&mut self, action: &str) -> bool {
+        let count = self.actions.len();
+        self.actions.retain(|_, registered| registered != action);
+        self.actions.len() != count
+    }
+
+    /// Returns the action of a CEF key event's key code and modifiers.
+    pub fn action(&self, windows_key_code: i32, modifiers: u32) -> Option<&str> {
+        let chord = Chord {
+            windows_key_code,
+            modifiers: chord_modifiers(modifiers),
+        };
+        self.actions.get(&chord).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cef::sys::cef_event_flags_t;
+
+    fn flags(flags: cef_event_flags_t) -> u32 {
+        crate::input::event_flags(flags)
+    }
+
+    #[test]
+    fn test_shortcut_matching() {
+        let mut shortcuts = BrowserShortcuts::default();
+        let ctrl = KeyModifierMask::CTRL.ord() as i64;
+        assert!(shortcuts.register("reload", Key::R.ord() as i64, ctrl));
+        assert!(shortcuts.register("refresh", Key::F5.ord() as i64, 0));
+
+        let control_down = flags(cef_event_flags_t::EVENTFLAG_CONTROL_DOWN);
+        assert_eq!(shortcuts.action(0x52, control_down), Some("reload"));
+        assert_eq!(shortcuts.action(0x74, 0), Some("refresh"));
+
+        // Lock keys do not change the chord, other modifiers do
+        let caps_lock = flags(cef_event_flags_t::EVENTFLAG_CAPS_LOCK_ON);
+        assert_eq!(
+            shortcuts.action(0x52, control_down | caps_lock),
+            Some("reload")
+        );
+        assert_eq!(shortcuts.action(0x52, 0), None);
+        let shift_down = flags(cef_event_flags_t::EVENTFLAG_SHIFT_DOWN);
+        assert_eq!(shortcuts.action(0x52, control_down | shift_down), None);
+        assert_eq!(shortcuts.action(0x74, shift_down), None);
+    }
+
+    #[test]
+    fn test_modifiers_in_keycode() {
+        let mut shortcuts = BrowserShortcuts::default();
+        let keycode = KeyModifierMask::CTRL.ord() as i64 | Key::L.ord() as i64;
+        assert!(shortcuts.register("focus_url", keycode, 0));
+        let control_down = flags(cef_event_flags_t::EVENTFLAG_CONTROL_DOWN);
+        assert_eq!(shortcuts.action(0x4C, control_down), Some("focus_url"));
+    }
+
+    #[test]
+    fn test_register_and_unregister() {
+        let mut shortcuts = BrowserShortcuts::default();
+        assert!(!shortcuts.register("nothing", Key::NONE.ord() as i64, 0));
+        assert!(shortcuts.actions.is_empty());
+
+        assert!(shortcuts.register("find", Key::F.ord() as i64, 0));
+        assert!(shortcuts.register("find", Key::F3.ord() as i64, 0));
+        // The same chord again replaces the action
+        assert!(shortcuts.register("search", Key::F.ord() as i64, 0));
+        assert_eq!(shortcuts.action(0x46, 0), Some("search"));
+
+        assert!(shortcuts.unregister("find"));
+        assert!(!shortcuts.unregister("find"));
+        assert_eq!(shortcuts.action(0x72, 0), None);
+        assert_eq!(shortcuts.action(0x46, 0), Some("search"));
+    }
+}
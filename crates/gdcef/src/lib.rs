@@ -1,14 +1,31 @@
 mod accelerated_osr;
+mod accessibility;
 mod browser;
 mod cef_init;
+mod cef_log;
+mod cef_manager;
 mod cef_texture;
+mod cookies;
+mod crash_reports;
 mod cursor;
 mod drag;
 mod error;
+mod event_queue;
+mod fonts;
+mod frame_pacing;
+mod frame_scheduler;
 mod godot_protocol;
+mod helper_check;
+mod helper_priority;
 mod input;
+mod page_snapshot;
+mod process_memory;
 mod render;
+mod render_stats;
+mod resize_debounce;
+mod security;
 mod settings;
+mod software_upload;
 mod utils;
 mod vulkan_hook;
 mod webrender;
@@ -35,12 +52,24 @@ unsafe impl ExtensionLibrary for GodotCef {
                 }
             }
             InitStage::Scene => {
+                // Godot's Vulkan device exists by now; keep CEF's subprocesses from
+                // inheriting the layer registration.
+                vulkan_hook::restore_environment();
+                vulkan_hook::log_status();
                 settings::register_project_settings();
+                cef_manager::register_singleton();
             }
             _ => {}
         }
     }
+
+    fn on_stage_deinit(level: InitStage) {
+        if matches!(level, InitStage::Scene) {
+            cef_manager::unregister_singleton();
+        }
+    }
 }
 
-// Re-export CefTexture for convenience
+// Re-export the classes for convenience
+pub use cef_manager::CefManager;
 pub use cef_texture::CefTexture;
@@ -0,0 +1,250 @@
+//! Writing page snapshots saved with `CefTexture.save_page` to disk.
+//!
+//! An MHTML snapshot comes back from `Page.captureSnapshot` as a JSON
+//! result whose `data` string can be tens of megabytes. It is unescaped
+//! straight from the raw result into the file, in chunks, so it is never
+//! held as a decoded string.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+/// Size of the chunks written to the file.
+const CHUNK_SIZE: usize = 256 * 1024;
+
+/// Writes the MHTML of a `Page.captureSnapshot` result to `path`.
+pub fn save_snapshot(result: &[u8], path: &Path) -> io::Result<()> {
+    write_file(path, |out| {
+        if write_json_string_field(result, "data", out)? {
+            Ok(())
+        } else {
+            Err(invalid_data("the snapshot result has no data"))
+        }
+    })
+}
+
+/// Writes the HTML source of a page to `path`.
+pub fn save_source(source: &str, path: &Path) -> io::Result<()> {
+    write_file(path, |out| out.write_all(source.as_bytes()))
+}
+
+/// Creates `path` and its directory and writes it with `write`. A partly
+/// written file is removed on failure.
+fn write_file(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> io::Result<()>,
+) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let result = File::create(path).and_then(|file| {
+        let mut out = BufWriter::with_capacity(CHUNK_SIZE, file);
+        write(&mut out)?;
+        out.flush()
+    });
+    if result.is_err() {
+        let _ = fs::remove_file(path);
+    }
+    result
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Writes the unescaped value of the string member `field` of the JSON
+/// object `json` to `out`. Returns `false` if the object has no such member.
+fn write_json_string_field(json: &[u8], field: &str, out: &mut impl Write) -> io::Result<bool> {
+    let mut pos = skip_whitespace(json, 0);
+    if json.get(pos) != Some(&b'{') {
+        return Err(invalid_data("expected a JSON object"));
+    }
+    pos = skip_whitespace(json, pos + 1);
+    if json.get(pos) == Some(&b'}') {
+        return Ok(false);
+    }
+
+    loop {
+        if json.get(pos) != Some(&b'"') {
+            return Err(invalid_data("expected a member name"));
+        }
+        let mut name = Vec::new();
+        pos = skip_whitespace(json, write_json_string(json, pos, &mut name)?);
+        if json.get(pos) != Some(&b':') {
+            return Err(invalid_data("expected ':'"));
+        }
+        pos = skip_whitespace(json, pos + 1);
+
+        if name == field.as_bytes() {
+            if json.get(pos) != Some(&b'"') {
+                return Err(invalid_data("the member is not a string"));
+            }
+            write_json_string(json, pos, out)?;
+            return Ok(true);
+        }
+
+        pos = skip_whitespace(json, skip_json_value(json, pos)?);
+        match json.get(pos) {
+            Some(b',') => pos = skip_whitespace(json, pos + 1),
+            Some(b'}') => return Ok(false),
+            _ => return Err(invalid_data("expected ',' or '}'")),
+        }
+    }
+}
+
+fn skip_whitespace(json: &[u8], mut pos: usize) -> usize {
+    while json.get(pos).is_some_and(|b| b.is_ascii_whitespace()) {
+        pos += 1;
+    }
+    pos
+}
+
+/// Returns the position after the value starting at `pos`, which ends
+/// before the `,` or closing bracket that follows it.
+fn skip_json_value(json: &[u8], mut pos: usize) -> io::Result<usize> {
+    let mut depth = 0usize;
+    while let Some(&byte) = json.get(pos) {
+        match byte {
+            b'"' => {
+                pos = write_json_string(json, pos, &mut io::sink())?;
+                continue;
+            }
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' if depth == 0 => return Ok(pos),
+            b'}' | b']' => depth -= 1,
+            b',' if depth == 0 => return Ok(pos),
+            _ => {}
+        }
+        pos += 1;
+    }
+    Err(invalid_data("unexpected end of JSON"))
+}
+
+/// Unescapes the JSON string starting with the quote at `start` into `out`
+/// and returns the position after its closing quote. Runs of bytes without
+/// escapes are written as they are.
+fn write_json_string(json: &[u8], start: usize, out: &mut impl Write) -> io::Result<usize> {
+    let mut pos = start + 1;
+    let mut run_start = pos;
+    loop {
+        match json.get(pos) {
+            None => return Err(invalid_data("unterminated JSON string")),
+            Some(b'"') => {
+                out.write_all(&json[run_start..pos])?;
+                return Ok(pos + 1);
+            }
+            Some(b'\\') => {
+                out.write_all(&json[run_start..pos])?;
+                let escape = *json
+                    .get(pos + 1)
+                    .ok_or_else(|| invalid_data("unterminated JSON string"))?;
+                pos += 2;
+                let byte = match escape {
+                    b'"' | b'\\' | b'/' => escape,
+                    b'b' => 0x08,
+                    b'f' => 0x0c,
+                    b'n' => b'\n',
+                    b'r' => b'\r',
+                    b't' => b'\t',
+                    b'u' => {
+                        let (c, end) = read_unicode_escape(json, pos)?;
+                        pos = end;
+                        out.write_all(c.encode_utf8(&mut [0; 4]).as_bytes())?;
+                        run_start = pos;
+                        continue;
+                    }
+                    _ => return Err(invalid_data("invalid JSON escape")),
+                };
+                out.write_all(&[byte])?;
+                run_start = pos;
+            }
+            Some(_) => pos += 1,
+        }
+    }
+}
+
+/// Reads the hex digits of a `\u` escape at `pos`, combining a surrogate
+/// pair. Lone surrogates become U+FFFD.
+fn read_unicode_escape(json: &[u8], pos: usize) -> io::Result<(char, usize)> {
+    let hex = |pos: usize| -> Option<u32> {
+        let digits = std::str::from_utf8(json.get(pos..pos + 4)?).ok()?;
+        u32::from_str_radix(digits, 16).ok()
+    };
+    let unit = hex(pos).ok_or_else(|| invalid_data("invalid \\u escape"))?;
+    let end = pos + 4;
+
+    if (0xD800..0xDC00).contains(&unit)
+        && json.get(end..end + 2) == Some(b"\\u")
+        && let Some(low) = hex(end + 2).filter(|low| (0xDC00..0xE000).contains(low))
+    {
+        let c = char::from_u32(0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00));
+        return Ok((c.unwrap_or(char::REPLACEMENT_CHARACTER), end + 6));
+    }
+    Ok((
+        char::from_u32(unit).unwrap_or(char::REPLACEMENT_CHARACTER),
+        end,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(json: &str, name: &str) -> io::Result<Option<String>> {
+        let mut out = Vec::new();
+        let found = write_json_string_field(json.as_bytes(), name, &mut out)?;
+        Ok(found.then(|| String::from_utf8(out).unwrap()))
+    }
+
+    #[test]
+    fn test_write_json_string_field() {
+        assert_eq!(
+            field(r#"{"data":"MIME-Version: 1.0\r\n\"x\" \/ é😀"}"#, "data")
+                .unwrap()
+                .as_deref(),
+            Some("MIME-Version: 1.0\r\n\"x\" / é😀")
+        );
+        assert_eq!(
+            field(
+                r#" { "other" : {"data": [1, "}"]}, "n": -1.5, "data" : "ok" } "#,
+                "data"
+            )
+            .unwrap()
+            .as_deref(),
+            Some("ok")
+        );
+        assert_eq!(field(r#"{"other":"data"}"#, "data").unwrap(), None);
+        assert_eq!(field("{}", "data").unwrap(), None);
+        // Lone surrogates are replaced rather than rejected
+        assert_eq!(
+            field(r#"{"data":"a\ud800b"}"#, "data").unwrap().as_deref(),
+            Some("a\u{fffd}b")
+        );
+    }
+
+    #[test]
+    fn test_write_json_string_field_invalid() {
+        assert!(field(r#"{"data":"unterminated"#, "data").is_err());
+        assert!(field(r#"{"data":42}"#, "data").is_err());
+        assert!(field(r#"{"data":"\x"}"#, "data").is_err());
+        assert!(field(r#"["data"]"#, "data").is_err());
+    }
+
+    #[test]
+    fn test_save_snapshot() {
+        let dir = std::env::temp_dir().join(format!("godot-cef-snapshot-{}", std::process::id()));
+        let path = dir.join("saved").join("page.mhtml");
+
+        save_snapshot(br#"{"data":"line 1\r\nline 2"}"#, &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "line 1\r\nline 2");
+
+        // A failed save leaves no file behind
+        assert!(save_snapshot(br#"{"data":"cut"#, &path).is_err());
+        assert!(!path.exists());
+
+        save_source("<html></html>", &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "<html></html>");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+//! Resident memory of the CEF helper processes.
+//!
+//! The helpers are found with [`helper_process_ids`], then their resident
+//! memory is read with the platform API.
+
+use crate::utils::helper_process_ids;
+
+/// Memory usage of one helper process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HelperProcessMemory {
+    pub pid: u32,
+    pub resident_bytes: u64,
+}
+
+/// Returns the resident memory of every helper process started by this
+/// process. Processes whose memory cannot be read are skipped.
+pub fn helper_memory_usage() -> Vec<HelperProcessMemory> {
+    helper_process_ids()
+        .into_iter()
+        .filter_map(|pid| {
+            platform::resident_bytes(pid).map(|resident_bytes| HelperProcessMemory {
+                pid,
+                resident_bytes,
+            })
+        })
+        .collect()
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use std::fs;
+
+    pub fn resident_bytes(pid: u32) -> Option<u64> {
+        let statm = fs::read_to_string(format!("/proc/{pid}/statm")).ok()?;
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+        if page_size <= 0 {
+            return None;
+        }
+        Some(parse_statm_resident_pages(&statm)? * page_size as u64)
+    }
+
+    /// Parses the resident page count (second field) of `/proc/<pid>/statm`.
+    pub(super) fn parse_statm_resident_pages(statm: &str) -> Option<u64> {
+        statm.split_whitespace().nth(1)?.parse().ok()
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use windows::Win32::Foundation::CloseHandle;
+    use windows::Win32::System::ProcessStatus::{GetProcessMemoryInfo, PROCESS_MEMORY_COUNTERS};
+    use windows::Win32::System::Threading::{OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION};
+
+    pub fn resident_bytes(pid: u32) -> Option<u64> {
+        let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+        let mut counters = PROCESS_MEMORY_COUNTERS::default();
+        let result = unsafe {
+            GetProcessMemoryInfo(
+                process,
+                &mut counters,
+                std::mem::size_of::<PROCESS_MEMORY_COUNTERS>() as u32,
+            )
+        };
+        let _ = unsafe { CloseHandle(process) };
+        result.ok()?;
+        Some(counters.WorkingSetSize as u64)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use std::ffi::c_void;
+
+    pub fn resident_bytes(pid: u32) -> Option<u64> {
+        let mut info: libc::proc_taskinfo = unsafe { std::mem::zeroed() };
+        let size = std::mem::size_of::<libc::proc_taskinfo>() as libc::c_int;
+        let written = unsafe {
+            libc::proc_pidinfo(
+                pid as libc::c_int,
+                libc::PROC_PIDTASKINFO,
+                0,
+                &mut info as *mut _ as *mut c_void,
+                size,
+            )
+        };
+        (written == size).then_some(info.pti_resident_size)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod platform {
+    pub fn resident_bytes(_pid: u32) -> Option<u64> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_statm_resident_pages() {
+        use super::platform::parse_statm_resident_pages;
+
+        assert_eq!(
+            parse_statm_resident_pages("5000 1200 300 10 0 900 0"),
+            Some(1200)
+        );
+        assert_eq!(parse_statm_resident_pages(""), None);
+    }
+}
@@ -10,10 +10,21 @@ use godot::classes::rendering_device::{
 use godot::classes::{RenderingServer, Texture2Drd};
 use godot::prelude::*;
 
-/// Creates a RenderingDevice texture for CEF rendering.
-pub fn create_rd_texture(width: i32, height: i32) -> CefResult<(Rid, Gd<Texture2Drd>)> {
-    let width = width.max(1) as i64;
-    let height = height.max(1) as i64;
+/// Returns the number of mip levels of a full mip chain for a texture of
+/// the given size, down to 1x1.
+pub fn mipmap_count(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+/// Creates a BGRA RenderingDevice texture with the given usage.
+fn create_bgra_texture(
+    width: i32,
+    height: i32,
+    mipmaps: u32,
+    usage: TextureUsageBits,
+) -> CefResult<Rid> {
+    let width = width.max(1) as u32;
+    let height = height.max(1) as u32;
 
     let mut rd = RenderingServer::singleton()
         .get_rendering_device()
@@ -23,14 +34,14 @@ pub fn create_rd_texture(width: i32, height: i32) -> CefResult<(Rid, Gd<Texture2
     format.add_shareable_format(DataFormat::B8G8R8A8_UNORM);
     format.add_shareable_format(DataFormat::B8G8R8A8_SRGB);
     format.set_format(DataFormat::B8G8R8A8_SRGB);
-    format.set_width(width as u32);
-    format.set_height(height as u32);
+    format.set_width(width);
+    format.set_height(height);
     format.set_depth(1);
     format.set_array_layers(1);
-    format.set_mipmaps(1);
+    format.set_mipmaps(mipmaps);
     format.set_texture_type(RdTextureType::TYPE_2D);
     format.set_samples(TextureSamples::SAMPLES_1);
-    format.set_usage_bits(TextureUsageBits::SAMPLING_BIT | TextureUsageBits::CAN_COPY_TO_BIT);
+    format.set_usage_bits(usage);
 
     let rd_texture_rid = rd.texture_create(&format, &godot::classes::RdTextureView::new_gd());
 
@@ -40,6 +51,27 @@ pub fn create_rd_texture(width: i32, height: i32) -> CefResult<(Rid, Gd<Texture2
             width, height
         )));
     }
+    Ok(rd_texture_rid)
+}
+
+/// Creates a RenderingDevice texture for CEF rendering. With `mipmaps`, it
+/// has a full mip chain that `MipmapGenerator` can draw into.
+pub fn create_rd_texture(
+    width: i32,
+    height: i32,
+    mipmaps: bool,
+) -> CefResult<(Rid, Gd<Texture2Drd>)> {
+    // Copies from it feed the alpha mask used for click-through
+    let mut usage = TextureUsageBits::SAMPLING_BIT
+        | TextureUsageBits::CAN_COPY_TO_BIT
+        | TextureUsageBits::CAN_COPY_FROM_BIT;
+    let mipmaps = if mipmaps {
+        usage |= TextureUsageBits::COLOR_ATTACHMENT_BIT;
+        mipmap_count(width.max(1) as u32, height.max(1) as u32)
+    } else {
+        1
+    };
+    let rd_texture_rid = create_bgra_texture(width, height, mipmaps, usage)?;
 
     let mut texture_2d_rd = Texture2Drd::new_gd();
     texture_2d_rd.set_texture_rd_rid(rd_texture_rid);
@@ -47,6 +79,17 @@ pub fn create_rd_texture(width: i32, height: i32) -> CefResult<(Rid, Gd<Texture2
     Ok((rd_texture_rid, texture_2d_rd))
 }
 
+/// Creates a small RenderingDevice texture that regions of a CEF texture
+/// are copied into to be read back on the CPU.
+pub fn create_rd_readback_texture(width: i32, height: i32) -> CefResult<Rid> {
+    create_bgra_texture(
+        width,
+        height,
+        1,
+        TextureUsageBits::CAN_COPY_TO_BIT | TextureUsageBits::CAN_COPY_FROM_BIT,
+    )
+}
+
 pub fn free_rd_texture(rd_texture_rid: Rid) {
     if rd_texture_rid.is_valid()
         && let Some(mut rd) = RenderingServer::singleton().get_rendering_device()
@@ -54,3 +97,69 @@ pub fn free_rd_texture(rd_texture_rid: Rid) {
         rd.free_rid(rd_texture_rid);
     }
 }
+
+/// Returns the view rect size in DIPs that CEF is given for a render size in
+/// physical pixels.
+pub fn view_size_for(pixel_width: f32, pixel_height: f32, device_scale_factor: f32) -> (i32, i32) {
+    (
+        (pixel_width / device_scale_factor) as i32,
+        (pixel_height / device_scale_factor) as i32,
+    )
+}
+
+/// Returns the size in pixels that CEF paints a view of the given DIP size
+/// at. Chromium rounds the scaled size up to whole pixels.
+pub fn paint_size_for(view_width: i32, view_height: i32, device_scale_factor: f32) -> (u32, u32) {
+    let scale = |dips: i32| {
+        (dips.max(0) as f32 * device_scale_factor - 1e-3)
+            .ceil()
+            .max(0.0) as u32
+    };
+    (scale(view_width), scale(view_height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mipmap_count() {
+        assert_eq!(mipmap_count(1, 1), 1);
+        assert_eq!(mipmap_count(2, 1), 2);
+        assert_eq!(mipmap_count(1920, 1080), 11);
+        assert_eq!(mipmap_count(2048, 2048), 12);
+        assert_eq!(mipmap_count(300, 2049), 12);
+        assert_eq!(mipmap_count(0, 0), 1);
+    }
+
+    #[test]
+    fn test_view_and_paint_size_at_100_percent() {
+        let view = view_size_for(1280.0, 720.0, 1.0);
+        assert_eq!(view, (1280, 720));
+        assert_eq!(paint_size_for(view.0, view.1, 1.0), (1280, 720));
+    }
+
+    #[test]
+    fn test_paint_size_follows_dpi_transition() {
+        // The window keeps its pixel size when moved from a 100% to a 150%
+        // monitor, so the view shrinks in DIPs and is painted at 1.5x.
+        for (from, to) in [(1.0, 1.5), (1.5, 1.0), (1.25, 2.0)] {
+            let (w, h) = view_size_for(1280.0, 720.0, from);
+            let before = paint_size_for(w, h, from);
+
+            let (w, h) = view_size_for(1280.0, 720.0, to);
+            let after = paint_size_for(w, h, to);
+            assert_eq!(
+                after,
+                ((w as f32 * to).ceil() as u32, (h as f32 * to).ceil() as u32)
+            );
+            assert!(after.0.abs_diff(before.0) <= 2 && after.1.abs_diff(before.1) <= 2);
+        }
+    }
+
+    #[test]
+    fn test_paint_size_does_not_round_up_exact_sizes() {
+        assert_eq!(paint_size_for(800, 600, 2.0), (1600, 1200));
+        assert_eq!(paint_size_for(0, -5, 1.5), (0, 0));
+    }
+}
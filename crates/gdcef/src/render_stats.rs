@@ -0,0 +1,269 @@
+//! Frame render statistics for profiling.
+//!
+//! The render handlers record samples from CEF's paint callbacks, so the
+//! counters are plain atomics and recording never takes a lock. Only reading
+//! a snapshot or resetting (both done from the Godot main thread) touch the
+//! reset timestamp.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shared handle to the render statistics of one browser.
+pub type RenderStatsHandle = Arc<RenderStats>;
+
+pub struct RenderStats {
+    /// Main view paints since the browser was created; never reset, so
+    /// schedulers can tell whether a page painted since they last looked.
+    paint_sequence: AtomicU64,
+    paints: AtomicU64,
+    dropped_frames: AtomicU64,
+    stale_frames: AtomicU64,
+    skipped_frames: AtomicU64,
+    convert_count: AtomicU64,
+    convert_nanos: AtomicU64,
+    upload_count: AtomicU64,
+    upload_nanos: AtomicU64,
+    reset_at: Mutex<Instant>,
+}
+
+impl Default for RenderStats {
+    fn default() -> Self {
+        Self {
+            paint_sequence: AtomicU64::new(0),
+            paints: AtomicU64::new(0),
+            dropped_frames: AtomicU64::new(0),
+            stale_frames: AtomicU64::new(0),
+            skipped_frames: AtomicU64::new(0),
+            convert_count: AtomicU64::new(0),
+            convert_nanos: AtomicU64::new(0),
+            upload_count: AtomicU64::new(0),
+            upload_nanos: AtomicU64::new(0),
+            reset_at: Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl RenderStats {
+    pub fn new() -> RenderStatsHandle {
+        Arc::new(Self::default())
+    }
+
+    /// Records a main view paint delivered by CEF.
+    pub fn record_paint(&self) {
+        self.paint_sequence.fetch_add(1, Ordering::Relaxed);
+        self.paints.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a frame that was replaced before Godot consumed it.
+    pub fn record_dropped_frame(&self) {
+        self.dropped_frames.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a frame skipped because it was painted at a different size
+    /// than the texture, e.g. just before a resize.
+    pub fn record_stale_frame(&self) {
+        self.stale_frames.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a paint skipped by frame pacing without being converted or
+    /// copied.
+    pub fn record_skipped_frame(&self) {
+        self.skipped_frames.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records the time spent converting a frame (BGRA to RGBA).
+    pub fn record_convert(&self, elapsed: Duration) {
+        self.convert_count.fetch_add(1, Ordering::Relaxed);
+        self.convert_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Records the time spent uploading or copying a frame into the texture.
+    pub fn record_upload(&self, elapsed: Duration) {
+        self.upload_count.fetch_add(1, Ordering::Relaxed);
+        self.upload_nanos
+            .fetch_add(elapsed.as_nanos() as u64, Ordering::Relaxed);
+    }
+
+    /// Returns a number that changes with every main view paint and is
+    /// not affected by [`reset`](Self::reset).
+    pub fn paint_sequence(&self) -> u64 {
+        self.paint_sequence.load(Ordering::Relaxed)
+    }
+
+    /// Returns the counters accumulated since the last reset.
+    pub fn snapshot(&self) -> RenderStatsSnapshot {
+        let elapsed = self
+            .reset_at
+            .lock()
+            .map(|reset_at| reset_at.elapsed())
+            .unwrap_or_default();
+
+        RenderStatsSnapshot {
+            paints: self.paints.load(Ordering::Relaxed),
+            dropped_frames: self.dropped_frames.load(Ordering::Relaxed),
+            stale_frames: self.stale_frames.load(Ordering::Relaxed),
+            skipped_frames: self.skipped_frames.load(Ordering::Relaxed),
+            convert_count: self.convert_count.load(Ordering::Relaxed),
+            convert_nanos: self.convert_nanos.load(Ordering::Relaxed),
+            upload_count: self.upload_count.load(Ordering::Relaxed),
+            upload_nanos: self.upload_nanos.load(Ordering::Relaxed),
+            elapsed,
+        }
+    }
+
+    pub fn reset(&self) {
+        self.paints.store(0, Ordering::Relaxed);
+        self.dropped_frames.store(0, Ordering::Relaxed);
+        self.stale_frames.store(0, Ordering::Relaxed);
+        self.skipped_frames.store(0, Ordering::Relaxed);
+        self.convert_count.store(0, Ordering::Relaxed);
+        self.convert_nanos.store(0, Ordering::Relaxed);
+        self.upload_count.store(0, Ordering::Relaxed);
+        self.upload_nanos.store(0, Ordering::Relaxed);
+        if let Ok(mut reset_at) = self.reset_at.lock() {
+            *reset_at = Instant::now();
+        }
+    }
+}
+
+/// Point-in-time copy of [`RenderStats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStatsSnapshot {
+    pub paints: u64,
+    pub dropped_frames: u64,
+    pub stale_frames: u64,
+    pub skipped_frames: u64,
+    pub convert_count: u64,
+    pub convert_nanos: u64,
+    pub upload_count: u64,
+    pub upload_nanos: u64,
+    /// Time since the counters were last reset.
+    pub elapsed: Duration,
+}
+
+impl RenderStatsSnapshot {
+    /// Returns the activity between `earlier` and this snapshot.
+    ///
+    /// If the counters were reset in between, this snapshot is returned as-is.
+    pub fn since(&self, earlier: &Self) -> Self {
+        if self.elapsed < earlier.elapsed {
+            return *self;
+        }
+
+        Self {
+            paints: self.paints.saturating_sub(earlier.paints),
+            dropped_frames: self.dropped_frames.saturating_sub(earlier.dropped_frames),
+            stale_frames: self.stale_frames.saturating_sub(earlier.stale_frames),
+            skipped_frames: self.skipped_frames.saturating_sub(earlier.skipped_frames),
+            convert_count: self.convert_count.saturating_sub(earlier.convert_count),
+            convert_nanos: self.convert_nanos.saturating_sub(earlier.convert_nanos),
+            upload_count: self.upload_count.saturating_sub(earlier.upload_count),
+            upload_nanos: self.upload_nanos.saturating_sub(earlier.upload_nanos),
+            elapsed: self.elapsed - earlier.elapsed,
+        }
+    }
+
+    pub fn paints_per_second(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.paints as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    pub fn average_convert_ms(&self) -> f64 {
+        average_ms(self.convert_nanos, self.convert_count)
+    }
+
+    pub fn average_upload_ms(&self) -> f64 {
+        average_ms(self.upload_nanos, self.upload_count)
+    }
+}
+
+fn average_ms(total_nanos: u64, count: u64) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+    total_nanos as f64 / count as f64 / 1_000_000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_and_reset() {
+        let stats = RenderStats::new();
+        stats.record_paint();
+        stats.record_paint();
+        stats.record_dropped_frame();
+        stats.record_stale_frame();
+        stats.record_skipped_frame();
+        stats.record_convert(Duration::from_millis(2));
+        stats.record_convert(Duration::from_millis(4));
+        stats.record_upload(Duration::from_millis(1));
+
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.paints, 2);
+        assert_eq!(stats.paint_sequence(), 2);
+        assert_eq!(snapshot.dropped_frames, 1);
+        assert_eq!(snapshot.stale_frames, 1);
+        assert_eq!(snapshot.skipped_frames, 1);
+        assert_eq!(snapshot.average_convert_ms(), 3.0);
+        assert_eq!(snapshot.average_upload_ms(), 1.0);
+
+        stats.reset();
+        let snapshot = stats.snapshot();
+        assert_eq!(snapshot.paints, 0);
+        assert_eq!(stats.paint_sequence(), 2);
+        assert_eq!(snapshot.stale_frames, 0);
+        assert_eq!(snapshot.skipped_frames, 0);
+        assert_eq!(snapshot.average_convert_ms(), 0.0);
+    }
+
+    #[test]
+    fn test_snapshot_since() {
+        let earlier = RenderStatsSnapshot {
+            paints: 10,
+            elapsed: Duration::from_secs(1),
+            ..Default::default()
+        };
+        let later = RenderStatsSnapshot {
+            paints: 70,
+            elapsed: Duration::from_secs(2),
+            ..Default::default()
+        };
+
+        let interval = later.since(&earlier);
+        assert_eq!(interval.paints, 60);
+        assert_eq!(interval.elapsed, Duration::from_secs(1));
+        assert_eq!(interval.paints_per_second(), 60.0);
+    }
+
+    #[test]
+    fn test_snapshot_since_reset() {
+        let earlier = RenderStatsSnapshot {
+            paints: 100,
+            elapsed: Duration::from_secs(5),
+            ..Default::default()
+        };
+        let after_reset = RenderStatsSnapshot {
+            paints: 3,
+            elapsed: Duration::from_millis(500),
+            ..Default::default()
+        };
+        assert_eq!(after_reset.since(&earlier), after_reset);
+    }
+
+    #[test]
+    fn test_paints_per_second_without_elapsed_time() {
+        let snapshot = RenderStatsSnapshot {
+            paints: 5,
+            ..Default::default()
+        };
+        assert_eq!(snapshot.paints_per_second(), 0.0);
+    }
+}
@@ -0,0 +1,127 @@
+//! Debouncing of view resizes.
+//!
+//! CEF repaints the whole page for every size it is given, so forwarding each
+//! intermediate size while a window edge is dragged causes flashes and
+//! stalls. Sizes are forwarded once they have been stable for a short delay;
+//! until then the previous frame is stretched to the new size.
+
+use std::time::{Duration, Instant};
+
+/// Relative change in either dimension above which a resize is forwarded
+/// immediately (e.g. maximizing or toggling fullscreen).
+const IMMEDIATE_RESIZE_RATIO: f32 = 0.5;
+
+#[derive(Clone, Debug)]
+pub struct ResizeDebouncer {
+    delay: Duration,
+    /// Size waiting to be forwarded and when it was first seen.
+    pending: Option<((f32, f32), Instant)>,
+}
+
+impl Default for ResizeDebouncer {
+    fn default() -> Self {
+        Self::new(Duration::ZERO)
+    }
+}
+
+impl ResizeDebouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: None,
+        }
+    }
+
+    /// Returns true if `size` should be forwarded to CEF now, given the size
+    /// that was last forwarded.
+    pub fn should_apply(&mut self, applied: (f32, f32), size: (f32, f32), now: Instant) -> bool {
+        if same_size(applied, size) {
+            self.pending = None;
+            return false;
+        }
+
+        if self.delay.is_zero() || exceeds_immediate_ratio(applied, size) {
+            self.pending = None;
+            return true;
+        }
+
+        match self.pending {
+            Some((pending_size, since)) if same_size(pending_size, size) => {
+                if now.duration_since(since) >= self.delay {
+                    self.pending = None;
+                    true
+                } else {
+                    false
+                }
+            }
+            _ => {
+                self.pending = Some((size, now));
+                false
+            }
+        }
+    }
+}
+
+fn same_size(a: (f32, f32), b: (f32, f32)) -> bool {
+    (a.0 - b.0).abs() < 1e-6 && (a.1 - b.1).abs() < 1e-6
+}
+
+fn exceeds_immediate_ratio(applied: (f32, f32), size: (f32, f32)) -> bool {
+    let exceeds =
+        |from: f32, to: f32| from <= 0.0 || (to - from).abs() / from > IMMEDIATE_RESIZE_RATIO;
+    exceeds(applied.0, size.0) || exceeds(applied.1, size.1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DELAY: Duration = Duration::from_millis(100);
+
+    #[test]
+    fn test_unchanged_size_is_not_applied() {
+        let mut debouncer = ResizeDebouncer::new(DELAY);
+        assert!(!debouncer.should_apply((800.0, 600.0), (800.0, 600.0), Instant::now()));
+    }
+
+    #[test]
+    fn test_resize_waits_until_size_is_stable() {
+        let mut debouncer = ResizeDebouncer::new(DELAY);
+        let start = Instant::now();
+        let applied = (800.0, 600.0);
+
+        // Dragging an edge: the size changes every frame.
+        assert!(!debouncer.should_apply(applied, (801.0, 600.0), start));
+        assert!(!debouncer.should_apply(applied, (805.0, 600.0), start + DELAY / 2));
+        assert!(!debouncer.should_apply(applied, (810.0, 600.0), start + DELAY));
+
+        // The last size has not been stable for the full delay yet.
+        assert!(!debouncer.should_apply(applied, (810.0, 600.0), start + DELAY * 3 / 2));
+        assert!(debouncer.should_apply(applied, (810.0, 600.0), start + DELAY * 2));
+    }
+
+    #[test]
+    fn test_large_change_is_applied_immediately() {
+        let mut debouncer = ResizeDebouncer::new(DELAY);
+        let now = Instant::now();
+        assert!(debouncer.should_apply((800.0, 600.0), (1920.0, 1080.0), now));
+        assert!(debouncer.should_apply((0.0, 0.0), (800.0, 600.0), now));
+    }
+
+    #[test]
+    fn test_zero_delay_disables_debouncing() {
+        let mut debouncer = ResizeDebouncer::new(Duration::ZERO);
+        assert!(debouncer.should_apply((800.0, 600.0), (801.0, 600.0), Instant::now()));
+    }
+
+    #[test]
+    fn test_returning_to_applied_size_cancels_pending_resize() {
+        let mut debouncer = ResizeDebouncer::new(DELAY);
+        let start = Instant::now();
+        let applied = (800.0, 600.0);
+
+        assert!(!debouncer.should_apply(applied, (820.0, 600.0), start));
+        assert!(!debouncer.should_apply(applied, applied, start + DELAY));
+        assert!(!debouncer.should_apply(applied, (820.0, 600.0), start + DELAY * 2));
+    }
+}
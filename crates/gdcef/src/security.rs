@@ -0,0 +1,784 @@
+//! Helpers for reporting the connection security of the current page.
+//!
+//! CEF exposes certificates as DER blobs and CEF `basetime` values; these
+//! helpers turn them into the fingerprint strings and Unix timestamps that are
+//! handed to GDScript. [`CertificateDecisions`] tracks certificate errors that
+//! are waiting for GDScript to allow or deny them, [`ClientCertificateRequests`]
+//! the servers waiting for a client certificate, and [`NavigationAllowlist`]
+//! decides which main-frame navigations may proceed. [`ExtraHeaders`] holds
+//! the headers added to outgoing requests. External protocol links
+//! (`mailto:`, `steam://`, ...) are filtered with [`external_protocol_scheme`].
+
+use std::collections::{HashMap, HashSet};
+
+use sha2::{Digest, Sha256};
+
+/// Microseconds between the Windows epoch (1601-01-01) used by CEF `basetime`
+/// and the Unix epoch.
+const WINDOWS_TO_UNIX_EPOCH_MICROS: i64 = 11_644_473_600 * 1_000_000;
+
+/// Converts a CEF `basetime` value (microseconds since 1601-01-01 UTC) into
+/// seconds since the Unix epoch. Returns 0 for an unset time.
+pub fn basetime_to_unix_seconds(val: i64) -> i64 {
+    if val == 0 {
+        return 0;
+    }
+    (val - WINDOWS_TO_UNIX_EPOCH_MICROS).div_euclid(1_000_000)
+}
+
+/// Converts seconds since the Unix epoch into a CEF `basetime` value.
+pub fn unix_seconds_to_basetime(seconds: i64) -> i64 {
+    seconds
+        .saturating_mul(1_000_000)
+        .saturating_add(WINDOWS_TO_UNIX_EPOCH_MICROS)
+}
+
+/// Returns `true` if certificate information is meaningful for `url`.
+///
+/// Only `https://` and `wss://` pages carry a certificate; plain HTTP, the
+/// Godot schemes, `data:` URLs and so on report a "not applicable" state.
+pub fn is_certificate_applicable(url: &str) -> bool {
+    let Some((scheme, _)) = url.split_once(':') else {
+        return false;
+    };
+    scheme.eq_ignore_ascii_case("https") || scheme.eq_ignore_ascii_case("wss")
+}
+
+/// Formats a SHA-256 fingerprint of `der` as colon-separated uppercase hex
+/// (the format browsers show in their certificate viewers).
+pub fn certificate_fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Schemes that must never be reported as external protocols or handed to
+/// the OS, since they would execute or embed content.
+const NEVER_EXTERNAL_SCHEMES: &[&str] = &["javascript", "data", "vbscript"];
+
+/// Returns the lowercase scheme of an external protocol URL.
+///
+/// Returns `None` if the URL has no valid scheme or uses a scheme that must
+/// never leave the browser (`javascript:`, `data:`, ...).
+pub fn external_protocol_scheme(url: &str) -> Option<String> {
+    let (scheme, _) = url.trim_start().split_once(':')?;
+    let mut chars = scheme.chars();
+    let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'));
+    if !valid {
+        return None;
+    }
+
+    let scheme = scheme.to_ascii_lowercase();
+    (!NEVER_EXTERNAL_SCHEMES.contains(&scheme.as_str())).then_some(scheme)
+}
+
+/// Parses a comma-separated list of scheme names (e.g. `"mailto, steam"`).
+pub fn parse_scheme_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|s| s.trim().trim_end_matches("://").trim_end_matches(':'))
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_ascii_lowercase())
+        .collect()
+}
+
+struct PendingCertificateError<C> {
+    callback: C,
+    fingerprint: String,
+}
+
+/// Certificate errors waiting for a decision, plus the fingerprints that were
+/// allowed earlier in this session.
+///
+/// Generic over the callback type so the bookkeeping can be tested without CEF.
+pub struct CertificateDecisions<C> {
+    next_id: i64,
+    pending: HashMap<i64, PendingCertificateError<C>>,
+    allowed_fingerprints: HashSet<String>,
+}
+
+impl<C> Default for CertificateDecisions<C> {
+    fn default() -> Self {
+        Self {
+            next_id: 1,
+            pending: HashMap::new(),
+            allowed_fingerprints: HashSet::new(),
+        }
+    }
+}
+
+impl<C> CertificateDecisions<C> {
+    /// Returns `true` if a certificate with this fingerprint was already allowed.
+    pub fn is_allowed(&self, fingerprint: &str) -> bool {
+        !fingerprint.is_empty() && self.allowed_fingerprints.contains(fingerprint)
+    }
+
+    /// Stores the callback of a new certificate error and returns its id.
+    pub fn add_pending(&mut self, callback: C, fingerprint: String) -> i64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.insert(
+            id,
+            PendingCertificateError {
+                callback,
+                fingerprint,
+            },
+        );
+        id
+    }
+
+    /// Removes a pending error and returns its callback.
+    ///
+    /// When `allow` and `remember` are both set, the certificate's fingerprint
+    /// is allowed for the rest of the session.
+    pub fn resolve(&mut self, id: i64, allow: bool, remember: bool) -> Option<C> {
+        let pending = self.pending.remove(&id)?;
+        if allow && remember && !pending.fingerprint.is_empty() {
+            self.allowed_fingerprints.insert(pending.fingerprint);
+        }
+        Some(pending.callback)
+    }
+
+    /// Removes all pending errors (e.g. when the browser closes) and returns
+    /// their callbacks.
+    pub fn take_pending(&mut self) -> Vec<C> {
+        self.pending.drain().map(|(_, p)| p.callback).collect()
+    }
+}
+
+/// Client certificate requests waiting for GDScript to pick one of the
+/// offered certificates, with the certificates kept to hand back to CEF.
+///
+/// Generic over the callback and certificate types so the bookkeeping can be
+/// tested without CEF.
+pub struct ClientCertificateRequests<C, X> {
+    next_id: i64,
+    pending: HashMap<i64, (C, Vec<X>)>,
+}
+
+impl<C, X> Default for ClientCertificateRequests<C, X> {
+    fn default() -> Self {
+        Self {
+            next_id: 1,
+            pending: HashMap::new(),
+        }
+    }
+}
+
+impl<C, X> ClientCertificateRequests<C, X> {
+    /// Stores the callback and certificates of a new request and returns its id.
+    pub fn add_pending(&mut self, callback: C, certificates: Vec<X>) -> i64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.insert(id, (callback, certificates));
+        id
+    }
+
+    /// Removes a pending request and returns its callback, with the
+    /// certificate at `index` when there is one.
+    pub fn resolve(&mut self, id: i64, index: Option<usize>) -> Option<(C, Option<X>)> {
+        let (callback, mut certificates) = self.pending.remove(&id)?;
+        let certificate = index
+            .filter(|&index| index < certificates.len())
+            .map(|index| certificates.swap_remove(index));
+        Some((callback, certificate))
+    }
+
+    /// Returns the number of certificates offered by a pending request.
+    pub fn certificate_count(&self, id: i64) -> Option<usize> {
+        self.pending
+            .get(&id)
+            .map(|(_, certificates)| certificates.len())
+    }
+
+    /// Removes all pending requests (e.g. when the browser closes) and
+    /// returns their callbacks.
+    pub fn take_pending(&mut self) -> Vec<C> {
+        self.pending
+            .drain()
+            .map(|(_, (callback, _))| callback)
+            .collect()
+    }
+}
+
+/// Returns the index of the certificate to select without asking GDScript:
+/// the only one whose subject matches `pattern` (case-insensitively, `*`
+/// matching any characters). Returns `None` for an empty pattern or when
+/// no certificate or several match.
+pub fn auto_select_client_certificate<'a>(
+    pattern: &str,
+    subjects: impl IntoIterator<Item = &'a str>,
+) -> Option<usize> {
+    let pattern = pattern.trim().to_ascii_lowercase();
+    if pattern.is_empty() {
+        return None;
+    }
+    let mut matching = subjects.into_iter().enumerate().filter(|(_, subject)| {
+        wildcard_match(pattern.as_bytes(), subject.to_ascii_lowercase().as_bytes())
+    });
+    match (matching.next(), matching.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None,
+    }
+}
+
+/// URL patterns that main-frame navigations must match.
+///
+/// A pattern `scheme://host[:port][/path]` is matched part by part, so a
+/// `*` never reaches from one part into another, case-insensitively:
+///
+/// - the scheme with `*` matching any characters,
+/// - the host like an [`ExtraHeaders`] host pattern, or `*` for any host,
+/// - the port, if given and not `*`, against the URL's port or the scheme's
+///   default one,
+/// - the path, if given, against the URL's path and query, with `*`
+///   matching any characters including `/`.
+///
+/// A pattern without `://` (`about:blank`) is matched against the whole URL,
+/// and only against URLs without `://`. An empty list allows every
+/// navigation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NavigationAllowlist {
+    patterns: Vec<UrlPattern>,
+}
+
+impl NavigationAllowlist {
+    pub fn new<I, S>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        Self {
+            patterns: patterns
+                .into_iter()
+                .map(|p| p.as_ref().trim().to_ascii_lowercase())
+                .filter(|p| !p.is_empty())
+                .map(|p| UrlPattern::parse(&p))
+                .collect(),
+        }
+    }
+
+    /// Returns `true` if a navigation to `url` may proceed.
+    pub fn allows(&self, url: &str) -> bool {
+        if self.patterns.is_empty() {
+            return true;
+        }
+        let url = url.to_ascii_lowercase();
+        let parsed = url::Url::parse(&url).ok();
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches(&url, parsed.as_ref()))
+    }
+}
+
+/// A lowercase [`NavigationAllowlist`] pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum UrlPattern {
+    Hierarchical {
+        scheme: String,
+        host: String,
+        /// `None` matches any port.
+        port: Option<String>,
+        /// `None` matches any path.
+        path: Option<String>,
+    },
+    /// A pattern without an authority, matched against the whole URL.
+    Opaque(String),
+}
+
+impl UrlPattern {
+    fn parse(pattern: &str) -> Self {
+        let Some((scheme, rest)) = pattern.split_once("://") else {
+            return Self::Opaque(pattern.to_string());
+        };
+        let (authority, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], Some(rest[index..].to_string())),
+            None => (rest, None),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) if !authority.ends_with(']') => {
+                (host, (port != "*").then(|| port.to_string()))
+            }
+            _ => (authority, None),
+        };
+        Self::Hierarchical {
+            scheme: scheme.to_string(),
+            host: host.trim_end_matches('.').to_string(),
+            port,
+            path,
+        }
+    }
+
+    /// Matches a lowercase `url`, parsed into `parsed` if it is valid.
+    fn matches(&self, url: &str, parsed: Option<&url::Url>) -> bool {
+        let (scheme, host_pattern, port, path) = match self {
+            Self::Opaque(pattern) => {
+                return !url.contains("://") && wildcard_match(pattern.as_bytes(), url.as_bytes());
+            }
+            Self::Hierarchical {
+                scheme,
+                host,
+                port,
+                path,
+            } => (scheme, host, port, path),
+        };
+        let Some(url) = parsed else {
+            return false;
+        };
+        let Some(host) = url.host_str().map(|host| host.trim_end_matches('.')) else {
+            return false;
+        };
+
+        let port_matches = port.as_ref().is_none_or(|port| {
+            url.port_or_known_default()
+                .is_some_and(|url_port| url_port.to_string() == *port)
+        });
+        let path_matches = path.as_ref().is_none_or(|path| {
+            let path_and_query = match url.query() {
+                Some(query) => format!("{}?{}", url.path(), query),
+                None => url.path().to_string(),
+            };
+            wildcard_match(path.as_bytes(), path_and_query.as_bytes())
+        });
+
+        wildcard_match(scheme.as_bytes(), url.scheme().as_bytes())
+            && (host_pattern == "*" || host_matches(host_pattern, host))
+            && port_matches
+            && path_matches
+    }
+}
+
+/// Headers added to the requests of a browser: some sent to every host, the
+/// others only to the hosts matching a pattern.
+///
+/// A host pattern is either a host name (`api.example.com`) or `*.` followed
+/// by a domain (`*.example.com`), which matches its subdomains but not the
+/// domain itself. Patterns never match URLs without a host (`data:`,
+/// `about:blank`, ...).
+#[derive(Debug, Default)]
+pub struct ExtraHeaders {
+    global: Vec<(String, String)>,
+    /// Lowercase host patterns with their headers, in the order they were set.
+    by_host: Vec<(String, Vec<(String, String)>)>,
+}
+
+impl ExtraHeaders {
+    /// Replaces the headers sent to every host.
+    pub fn set_global(&mut self, headers: Vec<(String, String)>) {
+        self.global = headers;
+    }
+
+    /// Replaces the headers sent to the hosts matching `pattern`. Empty
+    /// `headers` remove the pattern.
+    pub fn set_for_host(&mut self, pattern: &str, headers: Vec<(String, String)>) {
+        let pattern = pattern.trim().trim_end_matches('.').to_ascii_lowercase();
+        self.by_host.retain(|(existing, _)| *existing != pattern);
+        if !pattern.is_empty() && !headers.is_empty() {
+            self.by_host.push((pattern, headers));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.global.is_empty() && self.by_host.is_empty()
+    }
+
+    /// Returns the headers to set on a request to `url`: the global ones,
+    /// then those of every pattern matching its host. Later entries win
+    /// over earlier ones with the same name.
+    pub fn headers_for(&self, url: &str) -> Vec<(&str, &str)> {
+        let host = url_host(url);
+        let matching = self
+            .by_host
+            .iter()
+            .filter(|(pattern, _)| {
+                host.as_deref()
+                    .is_some_and(|host| host_matches(pattern, host))
+            })
+            .flat_map(|(_, headers)| headers);
+        self.global
+            .iter()
+            .chain(matching)
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect()
+    }
+
+    /// Returns the host-restricted headers that must not reach `url`, i.e.
+    /// those of the patterns that do not match its host. A request that was
+    /// redirected to another host may still carry them.
+    pub fn withheld_from(&self, url: &str) -> Vec<(&str, &str)> {
+        let host = url_host(url);
+        self.by_host
+            .iter()
+            .filter(|(pattern, _)| {
+                !host
+                    .as_deref()
+                    .is_some_and(|host| host_matches(pattern, host))
+            })
+            .flat_map(|(_, headers)| headers)
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect()
+    }
+}
+
+/// Returns whether two URLs (or serialized origins) share scheme, host and
+/// port. Also compares custom schemes, which `url` gives opaque origins.
+pub fn is_same_origin(a: &str, b: &str) -> bool {
+    let (Ok(a), Ok(b)) = (url::Url::parse(a), url::Url::parse(b)) else {
+        return false;
+    };
+    let host = |url: &url::Url| url.host_str().map(str::to_ascii_lowercase);
+    a.scheme() == b.scheme()
+        && host(&a).is_some()
+        && host(&a) == host(&b)
+        && a.port_or_known_default() == b.port_or_known_default()
+}
+
+/// Returns the lowercase host of a hierarchical URL, without user info,
+/// port or trailing dot. IPv6 addresses keep their brackets.
+fn url_host(url: &str) -> Option<String> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or(rest);
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = if host_port.starts_with('[') {
+        host_port.split_inclusive(']').next().unwrap_or(host_port)
+    } else {
+        host_port.split(':').next().unwrap_or(host_port)
+    };
+    let host = host.trim_end_matches('.');
+    (!host.is_empty()).then(|| host.to_ascii_lowercase())
+}
+
+/// Matches a lowercase `host` against an `ExtraHeaders` host pattern.
+fn host_matches(pattern: &str, host: &str) -> bool {
+    match pattern.strip_prefix("*.") {
+        Some(domain) => host
+            .strip_suffix(domain)
+            .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+        None => pattern == host,
+    }
+}
+
+/// Matches `text` against `pattern`, where `*` matches any sequence of bytes.
+fn wildcard_match(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` seen and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star, star_t)) = backtrack {
+            p = star + 1;
+            t = star_t + 1;
+            backtrack = Some((star, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&b| b == b'*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_certificate_fingerprint_format() {
+        let fingerprint = certificate_fingerprint(b"abc");
+        assert!(fingerprint.starts_with("BA:78:16:BF"));
+        assert_eq!(fingerprint.len(), 32 * 3 - 1);
+    }
+
+    #[test]
+    fn test_basetime_to_unix_seconds() {
+        assert_eq!(basetime_to_unix_seconds(0), 0);
+        assert_eq!(basetime_to_unix_seconds(WINDOWS_TO_UNIX_EPOCH_MICROS), 0);
+        assert_eq!(
+            basetime_to_unix_seconds(WINDOWS_TO_UNIX_EPOCH_MICROS + 1_700_000_000_500_000),
+            1_700_000_000
+        );
+        assert_eq!(
+            basetime_to_unix_seconds(unix_seconds_to_basetime(1_700_000_000)),
+            1_700_000_000
+        );
+    }
+
+    #[test]
+    fn test_certificate_decisions_resolve() {
+        let mut decisions = CertificateDecisions::default();
+        let first = decisions.add_pending("first", "AA:BB".to_string());
+        let second = decisions.add_pending("second", "AA:BB".to_string());
+        assert_ne!(first, second);
+
+        assert_eq!(decisions.resolve(first, false, true), Some("first"));
+        assert!(!decisions.is_allowed("AA:BB"));
+        assert_eq!(decisions.resolve(first, true, true), None);
+
+        assert_eq!(decisions.resolve(second, true, true), Some("second"));
+        assert!(decisions.is_allowed("AA:BB"));
+    }
+
+    #[test]
+    fn test_certificate_decisions_not_remembered() {
+        let mut decisions = CertificateDecisions::default();
+        let id = decisions.add_pending((), "AA:BB".to_string());
+        assert_eq!(decisions.resolve(id, true, false), Some(()));
+        assert!(!decisions.is_allowed("AA:BB"));
+
+        // Certificates without a fingerprint are never remembered
+        let id = decisions.add_pending((), String::new());
+        decisions.resolve(id, true, true);
+        assert!(!decisions.is_allowed(""));
+    }
+
+    #[test]
+    fn test_certificate_decisions_take_pending() {
+        let mut decisions = CertificateDecisions::default();
+        decisions.add_pending(1, "A".to_string());
+        decisions.add_pending(2, "B".to_string());
+        let mut callbacks = decisions.take_pending();
+        callbacks.sort();
+        assert_eq!(callbacks, vec![1, 2]);
+        assert!(decisions.take_pending().is_empty());
+    }
+
+    #[test]
+    fn test_client_certificate_requests_resolve() {
+        let mut requests = ClientCertificateRequests::default();
+        let first = requests.add_pending("first", vec!["a", "b"]);
+        let second = requests.add_pending("second", vec!["c"]);
+        assert_ne!(first, second);
+        assert_eq!(requests.certificate_count(first), Some(2));
+
+        assert_eq!(requests.resolve(first, Some(1)), Some(("first", Some("b"))));
+        assert_eq!(requests.resolve(first, Some(0)), None);
+        assert_eq!(requests.certificate_count(first), None);
+        // An index past the offered certificates declines
+        assert_eq!(requests.resolve(second, Some(1)), Some(("second", None)));
+    }
+
+    #[test]
+    fn test_client_certificate_requests_take_pending() {
+        let mut requests = ClientCertificateRequests::default();
+        requests.add_pending(1, vec![()]);
+        requests.add_pending(2, Vec::new());
+        let mut callbacks = requests.take_pending();
+        callbacks.sort();
+        assert_eq!(callbacks, vec![1, 2]);
+        assert!(requests.take_pending().is_empty());
+    }
+
+    #[test]
+    fn test_auto_select_client_certificate() {
+        let subjects = ["Kiosk 042", "John Doe", "kiosk 043"];
+        assert_eq!(auto_select_client_certificate("John*", subjects), Some(1));
+        assert_eq!(
+            auto_select_client_certificate("KIOSK 043", subjects),
+            Some(2)
+        );
+        // Several matches are left to GDScript
+        assert_eq!(auto_select_client_certificate("kiosk*", subjects), None);
+        assert_eq!(auto_select_client_certificate("*", ["Only"]), Some(0));
+        assert_eq!(auto_select_client_certificate("", ["Only"]), None);
+        assert_eq!(auto_select_client_certificate("Other", subjects), None);
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match(b"abc", b"abc"));
+        assert!(!wildcard_match(b"abc", b"abcd"));
+        assert!(wildcard_match(b"a*", b"abcd"));
+        assert!(wildcard_match(b"*", b""));
+        assert!(wildcard_match(b"a*c*e", b"abcde"));
+        assert!(wildcard_match(b"a*c", b"acbc"));
+        assert!(!wildcard_match(b"a*c", b"acb"));
+    }
+
+    #[test]
+    fn test_navigation_allowlist() {
+        let allowlist =
+            NavigationAllowlist::new(["https://example.com/*", "https://*.example.com/*"]);
+        assert!(allowlist.allows("https://example.com/"));
+        assert!(allowlist.allows("https://Example.com/shop?item=1"));
+        assert!(allowlist.allows("https://cdn.example.com/a.js"));
+        assert!(!allowlist.allows("https://example.com.evil.net/"));
+        assert!(!allowlist.allows("http://example.com/"));
+        assert!(!allowlist.allows("https://other.net/"));
+    }
+
+    #[test]
+    fn test_is_same_origin() {
+        assert!(is_same_origin(
+            "https://game.example/menu?x=1",
+            "https://game.example"
+        ));
+        assert!(is_same_origin(
+            "https://Game.Example:443/",
+            "https://game.example/"
+        ));
+        assert!(is_same_origin("res://ui/index.html", "res://ui"));
+        assert!(!is_same_origin(
+            "https://game.example/",
+            "https://ads.example/"
+        ));
+        assert!(!is_same_origin(
+            "https://game.example/",
+            "http://game.example/"
+        ));
+        assert!(!is_same_origin(
+            "https://game.example/",
+            "https://game.example:8443/"
+        ));
+        assert!(!is_same_origin("about:blank", "about:blank"));
+        assert!(!is_same_origin("", "https://game.example/"));
+    }
+
+    #[test]
+    fn test_navigation_allowlist_matches_parts_separately() {
+        let allowlist = NavigationAllowlist::new(["https://*.example.com/*"]);
+        assert!(!allowlist.allows("https://evil.net/x.example.com/"));
+        assert!(!allowlist.allows("https://evil.net/?next=https://a.example.com/"));
+        assert!(!allowlist.allows("https://a.example.com@evil.net/"));
+        assert!(!allowlist.allows("https://a.example.com.evil.net/"));
+        assert!(allowlist.allows("https://a.example.com./page"));
+
+        let allowlist =
+            NavigationAllowlist::new(["http://localhost:8080/game/*", "res://*", "about:blank"]);
+        assert!(allowlist.allows("http://localhost:8080/game/index.html"));
+        assert!(!allowlist.allows("http://localhost:9000/game/index.html"));
+        assert!(!allowlist.allows("http://localhost:8080/admin/"));
+        assert!(allowlist.allows("res://ui/index.html"));
+        assert!(allowlist.allows("about:blank"));
+        assert!(!allowlist.allows("about:blank#https://example.com/"));
+        assert!(!allowlist.allows("https://evil.net/about:blank"));
+    }
+
+    #[test]
+    fn test_empty_navigation_allowlist_allows_everything() {
+        let allowlist = NavigationAllowlist::new(["", "  "]);
+        assert_eq!(allowlist, NavigationAllowlist::default());
+        assert!(allowlist.allows("https://anything.net/"));
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://API.Example.com/v1").as_deref(),
+            Some("api.example.com")
+        );
+        assert_eq!(
+            url_host("http://user:pw@example.com:8080?q").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(
+            url_host("wss://example.com.#top").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(url_host("http://[::1]:9000/").as_deref(), Some("[::1]"));
+        assert_eq!(url_host("data:text/plain,x"), None);
+        assert_eq!(url_host("file:///tmp/page.html"), None);
+    }
+
+    #[test]
+    fn test_host_matches() {
+        assert!(host_matches("api.example.com", "api.example.com"));
+        assert!(!host_matches("api.example.com", "example.com"));
+        assert!(!host_matches("api.example.com", "evil-api.example.com"));
+        assert!(host_matches("*.example.com", "api.example.com"));
+        assert!(host_matches("*.example.com", "a.b.example.com"));
+        assert!(!host_matches("*.example.com", "example.com"));
+        assert!(!host_matches("*.example.com", "badexample.com"));
+        assert!(!host_matches("*.example.com", "example.com.evil.net"));
+    }
+
+    #[test]
+    fn test_extra_headers() {
+        let header = |name: &str, value: &str| (name.to_string(), value.to_string());
+        let mut headers = ExtraHeaders::default();
+        assert!(headers.is_empty());
+        headers.set_global(vec![header("X-Game", "1")]);
+        headers.set_for_host("API.example.com", vec![header("Authorization", "Bearer a")]);
+        headers.set_for_host("*.cdn.example.com", vec![header("X-Cdn", "c")]);
+
+        assert_eq!(
+            headers.headers_for("https://api.example.com/me"),
+            [("X-Game", "1"), ("Authorization", "Bearer a")]
+        );
+        assert_eq!(
+            headers.withheld_from("https://api.example.com/me"),
+            [("X-Cdn", "c")]
+        );
+        // A redirect to another host must drop the token
+        assert_eq!(headers.headers_for("https://evil.net/"), [("X-Game", "1")]);
+        assert_eq!(
+            headers.withheld_from("https://evil.net/"),
+            [("Authorization", "Bearer a"), ("X-Cdn", "c")]
+        );
+        assert_eq!(
+            headers.headers_for("https://img.cdn.example.com/a.png"),
+            [("X-Game", "1"), ("X-Cdn", "c")]
+        );
+
+        headers.set_for_host("api.example.com", vec![header("Authorization", "Bearer b")]);
+        headers.set_for_host("*.cdn.example.com", Vec::new());
+        headers.set_global(Vec::new());
+        assert_eq!(
+            headers.headers_for("https://api.example.com/"),
+            [("Authorization", "Bearer b")]
+        );
+        assert!(headers.withheld_from("https://api.example.com/").is_empty());
+        headers.set_for_host("api.example.com", Vec::new());
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn test_external_protocol_scheme() {
+        assert_eq!(
+            external_protocol_scheme("mailto:someone@example.com"),
+            Some("mailto".to_string())
+        );
+        assert_eq!(
+            external_protocol_scheme("Steam://run/480"),
+            Some("steam".to_string())
+        );
+        assert_eq!(
+            external_protocol_scheme("ms-settings:display"),
+            Some("ms-settings".to_string())
+        );
+        assert_eq!(external_protocol_scheme("javascript:alert(1)"), None);
+        assert_eq!(external_protocol_scheme(" JavaScript:alert(1)"), None);
+        assert_eq!(external_protocol_scheme("data:text/html,hi"), None);
+        assert_eq!(external_protocol_scheme("no scheme here"), None);
+        assert_eq!(external_protocol_scheme("1abc:foo"), None);
+    }
+
+    #[test]
+    fn test_parse_scheme_list() {
+        assert_eq!(
+            parse_scheme_list("mailto, Steam,discord://, ,tel:"),
+            vec!["mailto", "steam", "discord", "tel"]
+        );
+        assert!(parse_scheme_list("").is_empty());
+    }
+
+    #[test]
+    fn test_is_certificate_applicable() {
+        assert!(is_certificate_applicable("https://example.com/"));
+        assert!(is_certificate_applicable("HTTPS://example.com/"));
+        assert!(is_certificate_applicable("wss://example.com/socket"));
+        assert!(!is_certificate_applicable("http://example.com/"));
+        assert!(!is_certificate_applicable("res://ui/index.html"));
+        assert!(!is_certificate_applicable("about:blank"));
+        assert!(!is_certificate_applicable(""));
+    }
+}
@@ -1,34 +1,165 @@
+use crate::event_queue::{QueueCategory, QueueLimits};
+use crate::godot_protocol::{ResponseHeaderConfig, parse_extra_headers};
+use crate::security::parse_scheme_list;
 use cef_app::SecurityConfig;
 use godot::classes::ProjectSettings;
 use godot::global::PropertyHint;
 use godot::prelude::*;
+use std::collections::HashMap;
 use std::path::PathBuf;
+use std::time::Duration;
 
 const SETTING_DATA_PATH: &str = "godot_cef/storage/data_path";
+const SETTING_APPEND_PROCESS_ID: &str = "godot_cef/storage/append_process_id";
 const SETTING_ALLOW_INSECURE_CONTENT: &str = "godot_cef/security/allow_insecure_content";
 const SETTING_IGNORE_CERTIFICATE_ERRORS: &str = "godot_cef/security/ignore_certificate_errors";
 const SETTING_DISABLE_WEB_SECURITY: &str = "godot_cef/security/disable_web_security";
+const SETTING_AUTO_OPEN_PROTOCOLS: &str = "godot_cef/security/auto_open_protocols";
+const SETTING_ALLOW_DEVTOOLS_PROTOCOL: &str = "godot_cef/security/allow_devtools_protocol";
+const SETTING_ALLOW_WEB_NOTIFICATIONS: &str = "godot_cef/security/allow_web_notifications";
+const SETTING_CLIENT_CERTIFICATE_AUTO_SELECT: &str =
+    "godot_cef/security/client_certificate_auto_select";
 const SETTING_ENABLE_AUDIO_CAPTURE: &str = "godot_cef/audio/enable_audio_capture";
+const SETTING_ENABLE_ACCESSIBILITY: &str = "godot_cef/accessibility/enable_accessibility";
 const SETTING_REMOTE_DEVTOOLS_PORT: &str = "godot_cef/debug/remote_devtools_port";
+const SETTING_EMIT_RENDER_STATS: &str = "godot_cef/debug/emit_render_stats";
+const SETTING_COLLECT_CRASH_DUMPS: &str = "godot_cef/debug/collect_crash_dumps";
+const SETTING_DEBUG_OVERLAY_IN_RELEASE: &str = "godot_cef/debug/allow_debug_overlay_in_release";
+const SETTING_DEBUG_OVERLAY_ACTION: &str = "godot_cef/debug/debug_overlay_action";
+const SETTING_DOM_DUMP_MAX_LENGTH: &str = "godot_cef/debug/dom_dump_max_length";
+const SETTING_HELPER_PREFLIGHT_CHECK: &str = "godot_cef/debug/helper_preflight_check";
+const SETTING_BROWSER_STARTUP_TIMEOUT: &str = "godot_cef/debug/browser_startup_timeout_s";
 const SETTING_MAX_FRAME_RATE: &str = "godot_cef/performance/max_frame_rate";
+const SETTING_BACKGROUND_FRAME_RATE: &str = "godot_cef/performance/background_frame_rate";
+const SETTING_RESIZE_DEBOUNCE_MS: &str = "godot_cef/performance/resize_debounce_ms";
+const SETTING_THREADED_SOFTWARE_UPLOAD: &str = "godot_cef/performance/threaded_software_upload";
+const SETTING_DOUBLE_CLICK_TIME_MS: &str = "godot_cef/input/double_click_time_ms";
+const SETTING_SPATIAL_NAVIGATION: &str = "godot_cef/input/enable_spatial_navigation";
+const SETTING_MAX_DRAG_FILE_SIZE_MB: &str = "godot_cef/input/max_drag_file_size_mb";
 const SETTING_CACHE_SIZE_MB: &str = "godot_cef/storage/cache_size_mb";
 const SETTING_USER_AGENT: &str = "godot_cef/network/user_agent";
 const SETTING_PROXY_SERVER: &str = "godot_cef/network/proxy_server";
 const SETTING_PROXY_BYPASS_LIST: &str = "godot_cef/network/proxy_bypass_list";
+const SETTING_TREAT_LOCALHOST_AS_SECURE: &str = "godot_cef/network/treat_localhost_as_secure";
+const SETTING_LOCALHOST_PORTS: &str = "godot_cef/network/localhost_ports";
 const SETTING_CUSTOM_SWITCHES: &str = "godot_cef/advanced/custom_command_line_switches";
+const SETTING_ALLOW_AUTOPLAY: &str = "godot_cef/advanced/allow_autoplay";
+const SETTING_DISABLE_VULKAN_HOOK: &str = "godot_cef/advanced/disable_vulkan_hook";
+const SETTING_FORWARDED_ENV_VARS: &str = "godot_cef/advanced/forwarded_environment_variables";
+const SETTING_MIME_OVERRIDES: &str = "godot_cef/protocol/mime_overrides";
+const SETTING_LOG_REQUESTS: &str = "godot_cef/protocol/log_requests";
+const SETTING_ALLOW_ORIGIN: &str = "godot_cef/protocol/allow_origin";
+const SETTING_EXTRA_RESPONSE_HEADERS: &str = "godot_cef/protocol/extra_response_headers";
+const SETTING_STRICT_USER_ORIGIN: &str = "godot_cef/protocol/strict_user_origin";
+const SETTING_ENFORCE_CSP: &str = "godot_cef/protocol/enforce_csp";
+const SETTING_DIRECTORY_INDEX_REQUIRES_TRAILING_SLASH: &str =
+    "godot_cef/protocol/directory_index_requires_trailing_slash";
+const SETTING_REENCODE_IMPORTED_TEXTURES: &str = "godot_cef/protocol/reencode_imported_textures";
+const SETTING_MAX_BINARY_MESSAGE_SIZE_MB: &str = "godot_cef/ipc/max_binary_message_size_mb";
+/// Prefix of the queue capacity settings, followed by the category name and
+/// `_capacity`.
+const SETTING_QUEUE_CAPACITY_PREFIX: &str = "godot_cef/queues/";
+const SETTING_GRAYSCALE_ANTIALIASING: &str = "godot_cef/rendering/force_grayscale_antialiasing";
+const SETTING_DISABLE_SUBPIXEL_POSITIONING: &str =
+    "godot_cef/rendering/disable_font_subpixel_positioning";
+const SETTING_EDITOR_PREVIEW: &str = "godot_cef/editor/enable_preview";
+const SETTING_LOG_SEVERITY: &str = "godot_cef/logging/severity";
+const SETTING_LOG_FILE: &str = "godot_cef/logging/log_file";
+const SETTING_FORWARD_LOG: &str = "godot_cef/logging/forward_to_output";
+const SETTING_STANDARD_FONT_FAMILY: &str = "godot_cef/fonts/standard_family";
+const SETTING_SERIF_FONT_FAMILY: &str = "godot_cef/fonts/serif_family";
+const SETTING_SANS_SERIF_FONT_FAMILY: &str = "godot_cef/fonts/sans_serif_family";
+const SETTING_FIXED_FONT_FAMILY: &str = "godot_cef/fonts/fixed_family";
+const SETTING_FONT_FILES: &str = "godot_cef/fonts/font_files";
 
 const DEFAULT_DATA_PATH: &str = "user://cef-data";
+const DEFAULT_APPEND_PROCESS_ID: bool = false;
 const DEFAULT_ALLOW_INSECURE_CONTENT: bool = false;
 const DEFAULT_IGNORE_CERTIFICATE_ERRORS: bool = false;
 const DEFAULT_DISABLE_WEB_SECURITY: bool = false;
+const DEFAULT_AUTO_OPEN_PROTOCOLS: &str = "mailto,steam,discord";
+const DEFAULT_ALLOW_DEVTOOLS_PROTOCOL: bool = true;
+const DEFAULT_ALLOW_WEB_NOTIFICATIONS: bool = false;
+const DEFAULT_CLIENT_CERTIFICATE_AUTO_SELECT: &str = ""; // Empty = always ask GDScript
 const DEFAULT_ENABLE_AUDIO_CAPTURE: bool = false;
+const DEFAULT_ENABLE_ACCESSIBILITY: bool = false;
 const DEFAULT_REMOTE_DEVTOOLS_PORT: i64 = 9229;
+const DEFAULT_EMIT_RENDER_STATS: bool = false;
+const DEFAULT_COLLECT_CRASH_DUMPS: bool = true;
+const DEFAULT_DEBUG_OVERLAY_IN_RELEASE: bool = false;
+const DEFAULT_DEBUG_OVERLAY_ACTION: &str = ""; // Empty = toggled from code only
+const DEFAULT_DOM_DUMP_MAX_LENGTH: i64 = 256 * 1024;
+const DEFAULT_HELPER_PREFLIGHT_CHECK: bool = false;
+const DEFAULT_BROWSER_STARTUP_TIMEOUT: i64 = 20; // 0 = never time out
 const DEFAULT_MAX_FRAME_RATE: i64 = 0; // 0 = follow Godot engine FPS
+const DEFAULT_BACKGROUND_FRAME_RATE: i64 = 5; // 0 = keep the normal frame rate
+const DEFAULT_RESIZE_DEBOUNCE_MS: i64 = 100; // 0 = forward every resize immediately
+const DEFAULT_THREADED_SOFTWARE_UPLOAD: bool = false;
+const DEFAULT_DOUBLE_CLICK_TIME_MS: i64 =
+    crate::input::DEFAULT_DOUBLE_CLICK_TIME.as_millis() as i64;
+const DEFAULT_SPATIAL_NAVIGATION: bool = false;
+const DEFAULT_MAX_DRAG_FILE_SIZE_MB: i64 = 100; // 0 = never extract dragged files
 const DEFAULT_CACHE_SIZE_MB: i64 = 0; // 0 = use CEF default
 const DEFAULT_USER_AGENT: &str = ""; // Empty = use CEF default
 const DEFAULT_PROXY_SERVER: &str = ""; // Empty = direct connection
 const DEFAULT_PROXY_BYPASS_LIST: &str = ""; // Empty = no bypass
+const DEFAULT_TREAT_LOCALHOST_AS_SECURE: bool = false;
+const DEFAULT_LOCALHOST_PORTS: &str = "";
 const DEFAULT_CUSTOM_SWITCHES: &str = ""; // Empty = no custom switches
+const DEFAULT_ALLOW_AUTOPLAY: bool = false;
+const DEFAULT_DISABLE_VULKAN_HOOK: bool = false;
+const DEFAULT_FORWARDED_ENV_VARS: &str = ""; // Empty = only locale, fonts and time zone
+const DEFAULT_LOG_REQUESTS: bool = false;
+const DEFAULT_ALLOW_ORIGIN: &str = "*"; // Empty = header omitted
+const DEFAULT_EXTRA_RESPONSE_HEADERS: &str = ""; // Empty = no extra headers
+const DEFAULT_STRICT_USER_ORIGIN: bool = false;
+const DEFAULT_ENFORCE_CSP: bool = false;
+const DEFAULT_DIRECTORY_INDEX_REQUIRES_TRAILING_SLASH: bool = false;
+const DEFAULT_REENCODE_IMPORTED_TEXTURES: bool = true;
+const DEFAULT_GRAYSCALE_ANTIALIASING: bool = false;
+const DEFAULT_DISABLE_SUBPIXEL_POSITIONING: bool = false;
+const DEFAULT_EDITOR_PREVIEW: bool = false;
+const DEFAULT_LOG_SEVERITY: i64 = 0; // Default
+const DEFAULT_LOG_FILE: &str = ""; // Empty = stderr
+const DEFAULT_FORWARD_LOG: bool = false;
+const DEFAULT_FONT_FAMILY: &str = ""; // Empty = CEF default
+const DEFAULT_FONT_FILES: &str = ""; // Empty = no bundled fonts
+
+/// Values of the log severity setting, in order.
+const LOG_SEVERITY_NAMES: &str = "Default,Verbose,Info,Warning,Error,Fatal,Disable";
+
+/// Input actions of gamepad navigation, by role, with their settings and
+/// default actions. The scroll actions are created with the right stick if
+/// the project does not define them.
+pub const GAMEPAD_ACTIONS: [(&str, &str, &str); 10] = [
+    ("up", "godot_cef/gamepad/up_action", "ui_up"),
+    ("down", "godot_cef/gamepad/down_action", "ui_down"),
+    ("left", "godot_cef/gamepad/left_action", "ui_left"),
+    ("right", "godot_cef/gamepad/right_action", "ui_right"),
+    ("accept", "godot_cef/gamepad/accept_action", "ui_accept"),
+    ("cancel", "godot_cef/gamepad/cancel_action", "ui_cancel"),
+    (
+        "scroll_up",
+        "godot_cef/gamepad/scroll_up_action",
+        "cef_scroll_up",
+    ),
+    (
+        "scroll_down",
+        "godot_cef/gamepad/scroll_down_action",
+        "cef_scroll_down",
+    ),
+    (
+        "scroll_left",
+        "godot_cef/gamepad/scroll_left_action",
+        "cef_scroll_left",
+    ),
+    (
+        "scroll_right",
+        "godot_cef/gamepad/scroll_right_action",
+        "cef_scroll_right",
+    ),
+];
+const DEFAULT_MAX_BINARY_MESSAGE_SIZE_MB: i64 = 32;
 
 pub fn register_project_settings() {
     let mut settings = ProjectSettings::singleton();
@@ -41,6 +172,12 @@ pub fn register_project_settings() {
         "",
     );
 
+    register_bool_setting(
+        &mut settings,
+        SETTING_APPEND_PROCESS_ID,
+        DEFAULT_APPEND_PROCESS_ID,
+    );
+
     register_bool_setting(
         &mut settings,
         SETTING_ALLOW_INSECURE_CONTENT,
@@ -59,12 +196,46 @@ pub fn register_project_settings() {
         DEFAULT_DISABLE_WEB_SECURITY,
     );
 
+    register_string_setting(
+        &mut settings,
+        SETTING_AUTO_OPEN_PROTOCOLS,
+        DEFAULT_AUTO_OPEN_PROTOCOLS,
+        PropertyHint::PLACEHOLDER_TEXT,
+        "Comma-separated schemes, e.g., mailto,steam,discord",
+    );
+
+    register_string_setting(
+        &mut settings,
+        SETTING_CLIENT_CERTIFICATE_AUTO_SELECT,
+        DEFAULT_CLIENT_CERTIFICATE_AUTO_SELECT,
+        PropertyHint::PLACEHOLDER_TEXT,
+        "Subject pattern, e.g., Kiosk *",
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_ALLOW_DEVTOOLS_PROTOCOL,
+        DEFAULT_ALLOW_DEVTOOLS_PROTOCOL,
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_ALLOW_WEB_NOTIFICATIONS,
+        DEFAULT_ALLOW_WEB_NOTIFICATIONS,
+    );
+
     register_bool_setting(
         &mut settings,
         SETTING_ENABLE_AUDIO_CAPTURE,
         DEFAULT_ENABLE_AUDIO_CAPTURE,
     );
 
+    register_bool_setting(
+        &mut settings,
+        SETTING_ENABLE_ACCESSIBILITY,
+        DEFAULT_ENABLE_ACCESSIBILITY,
+    );
+
     register_int_setting(
         &mut settings,
         SETTING_REMOTE_DEVTOOLS_PORT,
@@ -73,6 +244,54 @@ pub fn register_project_settings() {
         "1,65535",
     );
 
+    register_bool_setting(
+        &mut settings,
+        SETTING_EMIT_RENDER_STATS,
+        DEFAULT_EMIT_RENDER_STATS,
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_COLLECT_CRASH_DUMPS,
+        DEFAULT_COLLECT_CRASH_DUMPS,
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_DEBUG_OVERLAY_IN_RELEASE,
+        DEFAULT_DEBUG_OVERLAY_IN_RELEASE,
+    );
+
+    register_string_setting(
+        &mut settings,
+        SETTING_DEBUG_OVERLAY_ACTION,
+        DEFAULT_DEBUG_OVERLAY_ACTION,
+        PropertyHint::PLACEHOLDER_TEXT,
+        "Input action toggling the debug overlay (empty = none)",
+    );
+
+    register_int_setting(
+        &mut settings,
+        SETTING_DOM_DUMP_MAX_LENGTH,
+        DEFAULT_DOM_DUMP_MAX_LENGTH,
+        PropertyHint::RANGE,
+        "1024,16777216,or_greater",
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_HELPER_PREFLIGHT_CHECK,
+        DEFAULT_HELPER_PREFLIGHT_CHECK,
+    );
+
+    register_int_setting(
+        &mut settings,
+        SETTING_BROWSER_STARTUP_TIMEOUT,
+        DEFAULT_BROWSER_STARTUP_TIMEOUT,
+        PropertyHint::RANGE,
+        "0,300",
+    );
+
     // Performance settings
     register_int_setting(
         &mut settings,
@@ -82,6 +301,56 @@ pub fn register_project_settings() {
         "0,240,or_greater",
     );
 
+    register_int_setting(
+        &mut settings,
+        SETTING_BACKGROUND_FRAME_RATE,
+        DEFAULT_BACKGROUND_FRAME_RATE,
+        PropertyHint::RANGE,
+        "0,60",
+    );
+
+    register_int_setting(
+        &mut settings,
+        SETTING_RESIZE_DEBOUNCE_MS,
+        DEFAULT_RESIZE_DEBOUNCE_MS,
+        PropertyHint::RANGE,
+        "0,1000",
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_THREADED_SOFTWARE_UPLOAD,
+        DEFAULT_THREADED_SOFTWARE_UPLOAD,
+    );
+
+    // Input settings
+    register_int_setting(
+        &mut settings,
+        SETTING_DOUBLE_CLICK_TIME_MS,
+        DEFAULT_DOUBLE_CLICK_TIME_MS,
+        PropertyHint::RANGE,
+        "100,2000",
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_SPATIAL_NAVIGATION,
+        DEFAULT_SPATIAL_NAVIGATION,
+    );
+
+    register_int_setting(
+        &mut settings,
+        SETTING_MAX_DRAG_FILE_SIZE_MB,
+        DEFAULT_MAX_DRAG_FILE_SIZE_MB,
+        PropertyHint::RANGE,
+        "0,4096,or_greater",
+    );
+
+    // Gamepad settings
+    for (_, name, default) in GAMEPAD_ACTIONS {
+        register_string_setting(&mut settings, name, default, PropertyHint::NONE, "");
+    }
+
     // Storage settings
     register_int_setting(
         &mut settings,
@@ -91,6 +360,69 @@ pub fn register_project_settings() {
         "0,10240,or_greater",
     );
 
+    // Rendering settings
+    register_bool_setting(
+        &mut settings,
+        SETTING_GRAYSCALE_ANTIALIASING,
+        DEFAULT_GRAYSCALE_ANTIALIASING,
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_DISABLE_SUBPIXEL_POSITIONING,
+        DEFAULT_DISABLE_SUBPIXEL_POSITIONING,
+    );
+
+    // Editor settings
+    register_bool_setting(
+        &mut settings,
+        SETTING_EDITOR_PREVIEW,
+        DEFAULT_EDITOR_PREVIEW,
+    );
+
+    // Logging settings
+    register_int_setting(
+        &mut settings,
+        SETTING_LOG_SEVERITY,
+        DEFAULT_LOG_SEVERITY,
+        PropertyHint::ENUM,
+        LOG_SEVERITY_NAMES,
+    );
+
+    register_string_setting(
+        &mut settings,
+        SETTING_LOG_FILE,
+        DEFAULT_LOG_FILE,
+        PropertyHint::PLACEHOLDER_TEXT,
+        "Log file, e.g. user://cef.log (empty = stderr)",
+    );
+
+    register_bool_setting(&mut settings, SETTING_FORWARD_LOG, DEFAULT_FORWARD_LOG);
+
+    // Font settings
+    for name in [
+        SETTING_STANDARD_FONT_FAMILY,
+        SETTING_SERIF_FONT_FAMILY,
+        SETTING_SANS_SERIF_FONT_FAMILY,
+        SETTING_FIXED_FONT_FAMILY,
+    ] {
+        register_string_setting(
+            &mut settings,
+            name,
+            DEFAULT_FONT_FAMILY,
+            PropertyHint::PLACEHOLDER_TEXT,
+            "Default",
+        );
+    }
+
+    register_string_setting(
+        &mut settings,
+        SETTING_FONT_FILES,
+        DEFAULT_FONT_FILES,
+        PropertyHint::PLACEHOLDER_TEXT,
+        "res://fonts/NotoSansCJK.otf,res://fonts/NotoSerifCJK.otf",
+    );
+
     // Network settings
     register_string_setting(
         &mut settings,
@@ -116,6 +448,20 @@ pub fn register_project_settings() {
         "Comma-separated list, e.g., localhost,127.0.0.1",
     );
 
+    register_bool_setting(
+        &mut settings,
+        SETTING_TREAT_LOCALHOST_AS_SECURE,
+        DEFAULT_TREAT_LOCALHOST_AS_SECURE,
+    );
+
+    register_string_setting(
+        &mut settings,
+        SETTING_LOCALHOST_PORTS,
+        DEFAULT_LOCALHOST_PORTS,
+        PropertyHint::PLACEHOLDER_TEXT,
+        "8080,9000",
+    );
+
     // Advanced settings
     register_string_setting(
         &mut settings,
@@ -124,6 +470,87 @@ pub fn register_project_settings() {
         PropertyHint::MULTILINE_TEXT,
         "",
     );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_ALLOW_AUTOPLAY,
+        DEFAULT_ALLOW_AUTOPLAY,
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_DISABLE_VULKAN_HOOK,
+        DEFAULT_DISABLE_VULKAN_HOOK,
+    );
+
+    register_string_setting(
+        &mut settings,
+        SETTING_FORWARDED_ENV_VARS,
+        DEFAULT_FORWARDED_ENV_VARS,
+        PropertyHint::PLACEHOLDER_TEXT,
+        "Comma-separated list, e.g., SteamAppId,MY_GAME_MODE",
+    );
+
+    // Protocol settings
+    register_dictionary_setting(&mut settings, SETTING_MIME_OVERRIDES);
+
+    register_bool_setting(&mut settings, SETTING_LOG_REQUESTS, DEFAULT_LOG_REQUESTS);
+
+    register_string_setting(
+        &mut settings,
+        SETTING_ALLOW_ORIGIN,
+        DEFAULT_ALLOW_ORIGIN,
+        PropertyHint::PLACEHOLDER_TEXT,
+        "Access-Control-Allow-Origin value (empty = omit header)",
+    );
+
+    register_string_setting(
+        &mut settings,
+        SETTING_EXTRA_RESPONSE_HEADERS,
+        DEFAULT_EXTRA_RESPONSE_HEADERS,
+        PropertyHint::MULTILINE_TEXT,
+        "",
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_STRICT_USER_ORIGIN,
+        DEFAULT_STRICT_USER_ORIGIN,
+    );
+
+    register_bool_setting(&mut settings, SETTING_ENFORCE_CSP, DEFAULT_ENFORCE_CSP);
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_DIRECTORY_INDEX_REQUIRES_TRAILING_SLASH,
+        DEFAULT_DIRECTORY_INDEX_REQUIRES_TRAILING_SLASH,
+    );
+
+    register_bool_setting(
+        &mut settings,
+        SETTING_REENCODE_IMPORTED_TEXTURES,
+        DEFAULT_REENCODE_IMPORTED_TEXTURES,
+    );
+
+    // IPC settings
+    register_int_setting(
+        &mut settings,
+        SETTING_MAX_BINARY_MESSAGE_SIZE_MB,
+        DEFAULT_MAX_BINARY_MESSAGE_SIZE_MB,
+        PropertyHint::RANGE,
+        "1,1024,or_greater",
+    );
+
+    // Event queue settings
+    for category in QueueCategory::ALL {
+        register_int_setting(
+            &mut settings,
+            &queue_capacity_setting(category),
+            category.default_capacity() as i64,
+            PropertyHint::RANGE,
+            "1,65536,or_greater",
+        );
+    }
 }
 
 fn register_string_setting(
@@ -198,6 +625,27 @@ fn register_int_setting(
     settings.add_property_info(&property_info);
 }
 
+fn register_dictionary_setting(settings: &mut Gd<ProjectSettings>, name: &str) {
+    let name_gstring: GString = name.into();
+    let default = VarDictionary::new();
+
+    if !settings.has_setting(&name_gstring) {
+        settings.set_setting(&name_gstring, &default.to_variant());
+    }
+
+    settings.set_initial_value(&name_gstring, &default.to_variant());
+    settings.set_as_basic(&name_gstring, true);
+
+    let property_info = vdict! {
+        "name": name_gstring.clone(),
+        "type": VariantType::DICTIONARY.ord(),
+        "hint": PropertyHint::NONE.ord(),
+        "hint_string": "",
+    };
+
+    settings.add_property_info(&property_info);
+}
+
 pub fn get_data_path() -> PathBuf {
     let settings = ProjectSettings::singleton();
     let name_gstring: GString = SETTING_DATA_PATH.into();
@@ -214,6 +662,142 @@ pub fn get_data_path() -> PathBuf {
     PathBuf::from(absolute_path)
 }
 
+/// Returns the data path used when the configured one is not writable.
+pub fn get_default_data_path() -> PathBuf {
+    let settings = ProjectSettings::singleton();
+    PathBuf::from(settings.globalize_path(DEFAULT_DATA_PATH).to_string())
+}
+
+/// Whether each process keeps its data in a subdirectory named after its
+/// process id, so several instances of a game can run side by side.
+pub fn is_process_id_appended_to_data_path() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_APPEND_PROCESS_ID)
+}
+
+/// Returns the index of the log severity in `LOG_SEVERITY_NAMES`.
+pub fn get_log_severity() -> i64 {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_LOG_SEVERITY.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let severity = if variant.is_nil() {
+        DEFAULT_LOG_SEVERITY
+    } else {
+        variant.to::<i64>()
+    };
+
+    let max = LOG_SEVERITY_NAMES.split(',').count() as i64 - 1;
+    severity.clamp(0, max)
+}
+
+/// Whether text is antialiased in grayscale, which avoids the color fringes
+/// of subpixel antialiasing on transparent pages.
+pub fn is_grayscale_antialiasing_forced() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_GRAYSCALE_ANTIALIASING)
+}
+
+/// Whether glyphs are placed on whole pixels, for sharper small text.
+pub fn is_font_subpixel_positioning_disabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_DISABLE_SUBPIXEL_POSITIONING)
+}
+
+/// Whether `CefTexture` nodes show their page in the editor instead of a
+/// placeholder.
+pub fn is_editor_preview_enabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_EDITOR_PREVIEW)
+}
+
+/// Whether CEF's warnings and errors are printed to the Godot output,
+/// through a log file when none is configured.
+pub fn is_log_forwarding_enabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_FORWARD_LOG)
+}
+
+/// Returns the absolute path of the log file. Returns `None` to log to
+/// stderr.
+pub fn get_log_file() -> Option<PathBuf> {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_LOG_FILE.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    if variant.is_nil() {
+        return None;
+    }
+    let path = variant.to::<GString>().to_string();
+    let path = path.trim();
+    if path.is_empty() {
+        return None;
+    }
+    Some(PathBuf::from(settings.globalize_path(path).to_string()))
+}
+
+/// Font families used by pages that do not set their own, by generic family.
+/// Empty fields keep CEF's default.
+pub struct FontFamilies {
+    pub standard: String,
+    pub serif: String,
+    pub sans_serif: String,
+    pub fixed: String,
+}
+
+pub fn get_font_families() -> FontFamilies {
+    let settings = ProjectSettings::singleton();
+    let get = |name: &str| {
+        let name_gstring: GString = name.into();
+        let variant = settings.get_setting(&name_gstring);
+        if variant.is_nil() {
+            DEFAULT_FONT_FAMILY.to_string()
+        } else {
+            variant.to::<GString>().to_string().trim().to_string()
+        }
+    };
+
+    FontFamilies {
+        standard: get(SETTING_STANDARD_FONT_FAMILY),
+        serif: get(SETTING_SERIF_FONT_FAMILY),
+        sans_serif: get(SETTING_SANS_SERIF_FONT_FAMILY),
+        fixed: get(SETTING_FIXED_FONT_FAMILY),
+    }
+}
+
+/// Returns the font resources made available to the pages.
+pub fn get_font_files() -> Vec<String> {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_FONT_FILES.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let raw = if variant.is_nil() {
+        DEFAULT_FONT_FILES.to_string()
+    } else {
+        variant.to::<GString>().to_string()
+    };
+
+    raw.split(',')
+        .map(|path| path.trim())
+        .filter(|path| !path.is_empty())
+        .map(|path| path.to_string())
+        .collect()
+}
+
+/// Returns the subject pattern of the client certificate selected without
+/// emitting `client_certificate_requested`. Empty means always ask.
+pub fn get_client_certificate_auto_select() -> String {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_CLIENT_CERTIFICATE_AUTO_SELECT.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    if variant.is_nil() {
+        DEFAULT_CLIENT_CERTIFICATE_AUTO_SELECT.to_string()
+    } else {
+        variant.to::<GString>().to_string()
+    }
+}
+
 pub fn get_security_config() -> SecurityConfig {
     let settings = ProjectSettings::singleton();
 
@@ -233,7 +817,30 @@ fn get_bool_setting(settings: &Gd<ProjectSettings>, name: &str) -> bool {
             SETTING_ALLOW_INSECURE_CONTENT => DEFAULT_ALLOW_INSECURE_CONTENT,
             SETTING_IGNORE_CERTIFICATE_ERRORS => DEFAULT_IGNORE_CERTIFICATE_ERRORS,
             SETTING_DISABLE_WEB_SECURITY => DEFAULT_DISABLE_WEB_SECURITY,
+            SETTING_ALLOW_DEVTOOLS_PROTOCOL => DEFAULT_ALLOW_DEVTOOLS_PROTOCOL,
+            SETTING_ALLOW_WEB_NOTIFICATIONS => DEFAULT_ALLOW_WEB_NOTIFICATIONS,
             SETTING_ENABLE_AUDIO_CAPTURE => DEFAULT_ENABLE_AUDIO_CAPTURE,
+            SETTING_ENABLE_ACCESSIBILITY => DEFAULT_ENABLE_ACCESSIBILITY,
+            SETTING_LOG_REQUESTS => DEFAULT_LOG_REQUESTS,
+            SETTING_STRICT_USER_ORIGIN => DEFAULT_STRICT_USER_ORIGIN,
+            SETTING_ENFORCE_CSP => DEFAULT_ENFORCE_CSP,
+            SETTING_THREADED_SOFTWARE_UPLOAD => DEFAULT_THREADED_SOFTWARE_UPLOAD,
+            SETTING_DIRECTORY_INDEX_REQUIRES_TRAILING_SLASH => {
+                DEFAULT_DIRECTORY_INDEX_REQUIRES_TRAILING_SLASH
+            }
+            SETTING_REENCODE_IMPORTED_TEXTURES => DEFAULT_REENCODE_IMPORTED_TEXTURES,
+            SETTING_EMIT_RENDER_STATS => DEFAULT_EMIT_RENDER_STATS,
+            SETTING_COLLECT_CRASH_DUMPS => DEFAULT_COLLECT_CRASH_DUMPS,
+            SETTING_HELPER_PREFLIGHT_CHECK => DEFAULT_HELPER_PREFLIGHT_CHECK,
+            SETTING_DEBUG_OVERLAY_IN_RELEASE => DEFAULT_DEBUG_OVERLAY_IN_RELEASE,
+            SETTING_DISABLE_VULKAN_HOOK => DEFAULT_DISABLE_VULKAN_HOOK,
+            SETTING_SPATIAL_NAVIGATION => DEFAULT_SPATIAL_NAVIGATION,
+            SETTING_APPEND_PROCESS_ID => DEFAULT_APPEND_PROCESS_ID,
+            SETTING_FORWARD_LOG => DEFAULT_FORWARD_LOG,
+            SETTING_EDITOR_PREVIEW => DEFAULT_EDITOR_PREVIEW,
+            SETTING_GRAYSCALE_ANTIALIASING => DEFAULT_GRAYSCALE_ANTIALIASING,
+            SETTING_DISABLE_SUBPIXEL_POSITIONING => DEFAULT_DISABLE_SUBPIXEL_POSITIONING,
+            SETTING_TREAT_LOCALHOST_AS_SECURE => DEFAULT_TREAT_LOCALHOST_AS_SECURE,
             _ => false,
         }
     } else {
@@ -246,6 +853,85 @@ pub fn is_audio_capture_enabled() -> bool {
     get_bool_setting(&settings, SETTING_ENABLE_AUDIO_CAPTURE)
 }
 
+/// Whether browsers build the accessibility tree read by
+/// `get_accessibility_tree`. Chromium only maintains it on request, as it
+/// costs CPU time on every DOM change.
+pub fn is_accessibility_enabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_ENABLE_ACCESSIBILITY)
+}
+
+/// Whether every browser may play media without a user gesture, through
+/// Chromium's autoplay policy switch.
+pub fn is_autoplay_allowed() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_ALLOW_AUTOPLAY)
+}
+
+/// Read at the Core initialization stage, before the setting is registered, so
+/// only the value saved in `project.godot` is seen.
+pub fn is_vulkan_hook_disabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_DISABLE_VULKAN_HOOK)
+}
+
+pub fn is_render_stats_signal_enabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_EMIT_RENDER_STATS)
+}
+
+/// Whether the helper is started once before CEF initializes, to check that
+/// it runs.
+pub fn is_helper_preflight_enabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_HELPER_PREFLIGHT_CHECK)
+}
+
+pub fn is_crash_dump_collection_enabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_COLLECT_CRASH_DUMPS)
+}
+
+/// Whether the debug overlay and `copy_debug_report` work in release
+/// exports. Debug builds and the editor always allow them.
+pub fn is_debug_overlay_allowed_in_release() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_DEBUG_OVERLAY_IN_RELEASE)
+}
+
+/// Returns the input action that toggles the debug overlay. Empty string
+/// means none.
+pub fn get_debug_overlay_action() -> String {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_DEBUG_OVERLAY_ACTION.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    if variant.is_nil() {
+        DEFAULT_DEBUG_OVERLAY_ACTION.to_string()
+    } else {
+        variant.to::<GString>().to_string()
+    }
+}
+
+/// Whether `send_devtools_message` may drive the browsers through the
+/// DevTools protocol.
+pub fn is_devtools_protocol_allowed() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_ALLOW_DEVTOOLS_PROTOCOL)
+}
+
+/// Whether pages may show notifications, which are granted without a prompt
+/// and forwarded to `web_notification`.
+pub fn are_web_notifications_allowed() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_ALLOW_WEB_NOTIFICATIONS)
+}
+
+pub fn is_scheme_request_logging_enabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_LOG_REQUESTS)
+}
+
 pub fn get_remote_devtools_port() -> u16 {
     let settings = ProjectSettings::singleton();
     let name_gstring: GString = SETTING_REMOTE_DEVTOOLS_PORT.into();
@@ -276,6 +962,129 @@ pub fn get_max_frame_rate() -> i32 {
     fps.max(0) as i32
 }
 
+pub fn get_background_frame_rate() -> i32 {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_BACKGROUND_FRAME_RATE.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let fps = if variant.is_nil() {
+        DEFAULT_BACKGROUND_FRAME_RATE
+    } else {
+        variant.to::<i64>()
+    };
+
+    fps.max(0) as i32
+}
+
+/// Returns how long a new browser may take to start its renderer process
+/// before `browser_startup_timeout` is emitted, or `None` if it never is.
+pub fn get_browser_startup_timeout() -> Option<Duration> {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_BROWSER_STARTUP_TIMEOUT.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let seconds = if variant.is_nil() {
+        DEFAULT_BROWSER_STARTUP_TIMEOUT
+    } else {
+        variant.to::<i64>()
+    };
+
+    (seconds > 0).then(|| Duration::from_secs(seconds as u64))
+}
+
+/// Returns the number of characters of HTML `dump_dom_async` returns before
+/// truncating it.
+pub fn get_dom_dump_max_length() -> i32 {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_DOM_DUMP_MAX_LENGTH.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let length = if variant.is_nil() {
+        DEFAULT_DOM_DUMP_MAX_LENGTH
+    } else {
+        variant.to::<i64>()
+    };
+
+    length.clamp(1, i32::MAX as i64) as i32
+}
+
+pub fn get_resize_debounce_ms() -> u64 {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_RESIZE_DEBOUNCE_MS.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let delay = if variant.is_nil() {
+        DEFAULT_RESIZE_DEBOUNCE_MS
+    } else {
+        variant.to::<i64>()
+    };
+
+    delay.max(0) as u64
+}
+
+/// Whether software-rendered frames are uploaded on a worker thread instead
+/// of the main thread. Read when a browser is created.
+pub fn is_threaded_software_upload_enabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_THREADED_SOFTWARE_UPLOAD)
+}
+
+/// Returns the maximum time in milliseconds between the presses of a double
+/// or triple click.
+pub fn get_double_click_time_ms() -> u64 {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_DOUBLE_CLICK_TIME_MS.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let time = if variant.is_nil() {
+        DEFAULT_DOUBLE_CLICK_TIME_MS
+    } else {
+        variant.to::<i64>()
+    };
+
+    time.max(0) as u64
+}
+
+/// Returns the size limit for extracting a file dragged out of the browser,
+/// in bytes. 0 means dragged files are never extracted.
+pub fn get_max_drag_file_size() -> u64 {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_MAX_DRAG_FILE_SIZE_MB.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let size_mb = if variant.is_nil() {
+        DEFAULT_MAX_DRAG_FILE_SIZE_MB
+    } else {
+        variant.to::<i64>()
+    };
+
+    (size_mb.max(0) as u64).saturating_mul(1024 * 1024)
+}
+
+/// Whether Chromium's spatial navigation is enabled, so arrow keys move the
+/// focus between elements.
+pub fn is_spatial_navigation_enabled() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_SPATIAL_NAVIGATION)
+}
+
+/// Returns the input action names of gamepad navigation, in the order of
+/// [`GAMEPAD_ACTIONS`].
+pub fn get_gamepad_actions() -> Vec<String> {
+    let settings = ProjectSettings::singleton();
+    GAMEPAD_ACTIONS
+        .iter()
+        .map(|(_, name, default)| {
+            let variant = settings.get_setting(&GString::from(*name));
+            if variant.is_nil() {
+                default.to_string()
+            } else {
+                variant.to::<GString>().to_string()
+            }
+        })
+        .collect()
+}
+
 /// Returns the cache size limit in megabytes. Returns 0 for CEF default.
 pub fn get_cache_size_mb() -> i32 {
     let settings = ProjectSettings::singleton();
@@ -291,6 +1100,42 @@ pub fn get_cache_size_mb() -> i32 {
     size.max(0) as i32
 }
 
+fn queue_capacity_setting(category: QueueCategory) -> String {
+    format!(
+        "{SETTING_QUEUE_CAPACITY_PREFIX}{}_capacity",
+        category.name()
+    )
+}
+
+/// Returns the capacity of the event queues of each category.
+pub fn get_queue_limits() -> QueueLimits {
+    let settings = ProjectSettings::singleton();
+    let mut limits = QueueLimits::default();
+    for category in QueueCategory::ALL {
+        let name_gstring: GString = queue_capacity_setting(category).as_str().into();
+        let variant = settings.get_setting(&name_gstring);
+        if !variant.is_nil() {
+            limits.set_capacity(category, variant.to::<i64>().max(1) as usize);
+        }
+    }
+    limits
+}
+
+/// Returns the largest binary IPC message a page may send, in bytes.
+pub fn get_max_binary_message_size() -> usize {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_MAX_BINARY_MESSAGE_SIZE_MB.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let size_mb = if variant.is_nil() {
+        DEFAULT_MAX_BINARY_MESSAGE_SIZE_MB
+    } else {
+        variant.to::<i64>()
+    };
+
+    (size_mb.max(1) as usize).saturating_mul(1024 * 1024)
+}
+
 /// Returns the custom user agent string. Empty string means use CEF default.
 pub fn get_user_agent() -> String {
     let settings = ProjectSettings::singleton();
@@ -304,6 +1149,22 @@ pub fn get_user_agent() -> String {
     }
 }
 
+/// Returns the external protocol schemes that `auto_open_external_protocols`
+/// may pass to `OS.shell_open`.
+pub fn get_auto_open_protocols() -> Vec<String> {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_AUTO_OPEN_PROTOCOLS.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let raw = if variant.is_nil() {
+        DEFAULT_AUTO_OPEN_PROTOCOLS.to_string()
+    } else {
+        variant.to::<GString>().to_string()
+    };
+
+    parse_scheme_list(&raw)
+}
+
 /// Returns the proxy server URL. Empty string means direct connection.
 pub fn get_proxy_server() -> String {
     let settings = ProjectSettings::singleton();
@@ -330,6 +1191,33 @@ pub fn get_proxy_bypass_list() -> String {
     }
 }
 
+/// Returns the ports of the local game servers pages may reach as secure
+/// origins. Empty unless `treat_localhost_as_secure` is enabled.
+pub fn get_secure_localhost_ports() -> Vec<u16> {
+    let settings = ProjectSettings::singleton();
+    if !get_bool_setting(&settings, SETTING_TREAT_LOCALHOST_AS_SECURE) {
+        return Vec::new();
+    }
+
+    let name_gstring: GString = SETTING_LOCALHOST_PORTS.into();
+    let variant = settings.get_setting(&name_gstring);
+    let raw = if variant.is_nil() {
+        DEFAULT_LOCALHOST_PORTS.to_string()
+    } else {
+        variant.to::<GString>().to_string()
+    };
+
+    let ports = cef_app::decode_ports(&raw);
+    if ports.is_empty() {
+        godot_warn!(
+            "[Settings] {} is enabled but {} lists no valid port",
+            SETTING_TREAT_LOCALHOST_AS_SECURE,
+            SETTING_LOCALHOST_PORTS
+        );
+    }
+    ports
+}
+
 /// Returns custom command-line switches as a list of strings.
 /// Each line in the multiline string is treated as a separate switch.
 pub fn get_custom_switches() -> Vec<String> {
@@ -350,6 +1238,103 @@ pub fn get_custom_switches() -> Vec<String> {
         .collect()
 }
 
+/// Returns the names of the environment variables forwarded verbatim to the
+/// CEF subprocesses.
+pub fn get_forwarded_env_vars() -> Vec<String> {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_FORWARDED_ENV_VARS.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let raw = if variant.is_nil() {
+        DEFAULT_FORWARDED_ENV_VARS.to_string()
+    } else {
+        variant.to::<GString>().to_string()
+    };
+
+    raw.split(',')
+        .map(|name| name.trim())
+        .filter(|name| !name.is_empty())
+        .map(|name| name.to_string())
+        .collect()
+}
+
+/// Returns the extension to MIME type overrides for the Godot schemes.
+/// Entries whose key or value is not a string are ignored.
+pub fn get_mime_overrides() -> HashMap<String, String> {
+    let settings = ProjectSettings::singleton();
+    let name_gstring: GString = SETTING_MIME_OVERRIDES.into();
+    let variant = settings.get_setting(&name_gstring);
+
+    let Ok(dict) = variant.try_to::<VarDictionary>() else {
+        return HashMap::new();
+    };
+
+    dict.iter_shared()
+        .filter_map(|(key, value)| {
+            let key = key.try_to::<GString>().ok()?.to_string();
+            let value = value.try_to::<GString>().ok()?.to_string();
+            Some((key, value))
+        })
+        .collect()
+}
+
+/// Returns the response header configuration for the Godot schemes.
+/// Invalid lines in the extra headers list are reported and skipped.
+pub fn get_response_header_config() -> ResponseHeaderConfig {
+    let settings = ProjectSettings::singleton();
+
+    let allow_origin_name: GString = SETTING_ALLOW_ORIGIN.into();
+    let allow_origin_variant = settings.get_setting(&allow_origin_name);
+    let allow_origin = if allow_origin_variant.is_nil() {
+        DEFAULT_ALLOW_ORIGIN.to_string()
+    } else {
+        allow_origin_variant.to::<GString>().to_string()
+    };
+
+    let extra_name: GString = SETTING_EXTRA_RESPONSE_HEADERS.into();
+    let extra_variant = settings.get_setting(&extra_name);
+    let extra_raw = if extra_variant.is_nil() {
+        DEFAULT_EXTRA_RESPONSE_HEADERS.to_string()
+    } else {
+        extra_variant.to::<GString>().to_string()
+    };
+
+    let (extra_headers, rejected) = parse_extra_headers(&extra_raw);
+    for line in rejected {
+        godot::global::godot_warn!(
+            "[GodotCef] Ignoring invalid entry in '{}': {}",
+            SETTING_EXTRA_RESPONSE_HEADERS,
+            line.escape_debug()
+        );
+    }
+
+    ResponseHeaderConfig {
+        allow_origin,
+        strict_user_origin: get_bool_setting(&settings, SETTING_STRICT_USER_ORIGIN),
+        extra_headers,
+    }
+}
+
+/// Returns whether pages served over the Godot schemes must honour their CSP.
+pub fn is_scheme_csp_enforced() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_ENFORCE_CSP)
+}
+
+/// Returns whether only URLs ending in `/` are served the folder's
+/// `index.html` by the Godot schemes.
+pub fn is_directory_index_trailing_slash_required() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_DIRECTORY_INDEX_REQUIRES_TRAILING_SLASH)
+}
+
+/// Whether `res://` serves textures that only exist imported, as in
+/// exported builds, re-encoded as PNG.
+pub fn are_imported_textures_reencoded() -> bool {
+    let settings = ProjectSettings::singleton();
+    get_bool_setting(&settings, SETTING_REENCODE_IMPORTED_TEXTURES)
+}
+
 pub fn warn_if_insecure_settings() {
     let config = get_security_config();
 
@@ -0,0 +1,332 @@
+//! Texture upload of the software render path on a worker thread.
+//!
+//! Copying a frame out of the frame buffer, compositing the popup over it,
+//! building the `Image` and updating the texture take several milliseconds
+//! at 4K. The [`SoftwareUploader`] does all of it on its own thread with
+//! `RenderingServer.texture_2d_update`, so the main thread only asks for an
+//! upload each frame. Updates require the texture to keep its size, so the
+//! main thread still recreates it on a resize, after [`SoftwareUploader::flush`]
+//! has made sure no update of the old size is still running. The same goes
+//! for turning mipmaps on or off.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+use cef_app::FrameBuffer;
+use godot::classes::image::Format as ImageFormat;
+use godot::classes::{Image, RenderingServer};
+use godot::prelude::*;
+use software_render::{DestBuffer, PopupBuffer, composite_popup};
+
+use crate::browser::PopupStateQueue;
+use crate::render_stats::RenderStatsHandle;
+
+/// Largest frame, in pixels, whose mipmaps are generated without
+/// `force_mipmaps`. Generating them takes longer than the upload itself.
+pub const MAX_SOFTWARE_MIPMAP_PIXELS: u64 = 2560 * 1600;
+
+/// Returns whether a software frame of this size gets mipmaps.
+pub fn software_mipmaps(generate: bool, force: bool, width: u32, height: u32) -> bool {
+    generate && (force || width as u64 * height as u64 <= MAX_SOFTWARE_MIPMAP_PIXELS)
+}
+
+/// Frame the main thread asked for: the texture's size, which the frame must
+/// still have, whether it has mipmaps, and the scale of the popup position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct UploadRequest {
+    width: u32,
+    height: u32,
+    mipmaps: bool,
+    display_scale: f32,
+}
+
+#[derive(Default)]
+struct UploadState {
+    /// Latest request, replacing any the worker did not get to.
+    requested: Option<UploadRequest>,
+    busy: bool,
+    shutdown: bool,
+    /// Uploads finished since the main thread last took them.
+    uploads: u64,
+}
+
+#[derive(Default)]
+struct UploadShared {
+    state: Mutex<UploadState>,
+    changed: Condvar,
+}
+
+/// Worker thread updating a software-rendered texture.
+pub struct SoftwareUploader {
+    shared: Arc<UploadShared>,
+    thread: Option<JoinHandle<()>>,
+    /// Size the texture was last created with on the main thread.
+    size: (u32, u32),
+    /// Whether it was created with mipmaps.
+    mipmaps: bool,
+}
+
+impl SoftwareUploader {
+    pub fn spawn(
+        frame_buffer: Arc<Mutex<FrameBuffer>>,
+        popup_state: Option<PopupStateQueue>,
+        texture: Rid,
+        render_stats: Option<RenderStatsHandle>,
+    ) -> std::io::Result<Self> {
+        let shared = Arc::new(UploadShared::default());
+        let worker_shared = shared.clone();
+        let thread = std::thread::Builder::new()
+            .name("godot-cef-upload".into())
+            .spawn(move || {
+                while let Some(request) = next_request(&worker_shared) {
+                    let uploaded = upload_frame(
+                        &frame_buffer,
+                        popup_state.as_ref(),
+                        texture,
+                        request,
+                        render_stats.as_ref(),
+                    );
+                    if let Ok(mut state) = worker_shared.state.lock() {
+                        state.busy = false;
+                        state.uploads += uploaded as u64;
+                    }
+                    worker_shared.changed.notify_all();
+                }
+            })?;
+
+        Ok(Self {
+            shared,
+            thread: Some(thread),
+            size: (0, 0),
+            mipmaps: false,
+        })
+    }
+
+    /// Size the texture was last created with.
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// Whether the texture was last created with mipmaps.
+    pub fn mipmaps(&self) -> bool {
+        self.mipmaps
+    }
+
+    pub fn set_format(&mut self, width: u32, height: u32, mipmaps: bool) {
+        self.size = (width, height);
+        self.mipmaps = mipmaps;
+    }
+
+    /// Asks for the current frame to be uploaded.
+    pub fn request(&self, display_scale: f32) {
+        let (width, height) = self.size;
+        if let Ok(mut state) = self.shared.state.lock() {
+            state.requested = Some(UploadRequest {
+                width,
+                height,
+                mipmaps: self.mipmaps,
+                display_scale,
+            });
+        }
+        self.shared.changed.notify_all();
+    }
+
+    /// Returns whether frames were uploaded since the last call.
+    pub fn take_uploads(&self) -> bool {
+        self.shared
+            .state
+            .lock()
+            .map(|mut state| std::mem::take(&mut state.uploads) > 0)
+            .unwrap_or(false)
+    }
+
+    /// Drops the pending request and waits for a running upload, so the
+    /// main thread can recreate the texture.
+    pub fn flush(&self) {
+        let Ok(mut state) = self.shared.state.lock() else {
+            return;
+        };
+        state.requested = None;
+        while state.busy {
+            state = match self.shared.changed.wait(state) {
+                Ok(state) => state,
+                Err(_) => return,
+            };
+        }
+    }
+}
+
+impl Drop for SoftwareUploader {
+    fn drop(&mut self) {
+        if let Ok(mut state) = self.shared.state.lock() {
+            state.shutdown = true;
+            state.requested = None;
+        }
+        self.shared.changed.notify_all();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+/// Waits for the next request; `None` once the uploader shuts down.
+fn next_request(shared: &UploadShared) -> Option<UploadRequest> {
+    let mut state = shared.state.lock().ok()?;
+    loop {
+        if state.shutdown {
+            return None;
+        }
+        if let Some(request) = state.requested.take() {
+            state.busy = true;
+            return Some(request);
+        }
+        state = shared.changed.wait(state).ok()?;
+    }
+}
+
+/// Uploads the current frame if it still has the texture's size. A frame of
+/// another size is left dirty for the main thread to resize the texture.
+fn upload_frame(
+    frame_buffer: &Mutex<FrameBuffer>,
+    popup_state: Option<&PopupStateQueue>,
+    texture: Rid,
+    request: UploadRequest,
+    render_stats: Option<&RenderStatsHandle>,
+) -> bool {
+    let data = {
+        let Ok(mut fb) = frame_buffer.lock() else {
+            return false;
+        };
+        if fb.data.is_empty() || (fb.width, fb.height) != (request.width, request.height) {
+            return false;
+        }
+        let data = composite_frame(&fb, popup_state, request.display_scale);
+        fb.mark_clean();
+        data
+    };
+
+    let upload_start = Instant::now();
+    let byte_array = PackedByteArray::from(data.as_slice());
+    let Some(mut image) = Image::create_from_data(
+        request.width as i32,
+        request.height as i32,
+        false,
+        ImageFormat::RGBA8,
+        &byte_array,
+    ) else {
+        return false;
+    };
+    if request.mipmaps {
+        image.generate_mipmaps();
+    }
+    RenderingServer::singleton().texture_2d_update(texture, &image, 0);
+    if let Some(stats) = render_stats {
+        stats.record_upload(upload_start.elapsed());
+    }
+    true
+}
+
+/// Returns a copy of the frame with the visible popup drawn over it, and
+/// marks the popup clean.
+pub fn composite_frame(
+    fb: &FrameBuffer,
+    popup_state: Option<&PopupStateQueue>,
+    display_scale: f32,
+) -> Vec<u8> {
+    let mut data = fb.data.clone();
+    let Some(mut popup) = popup_state.and_then(|ps| ps.lock().ok()) else {
+        return data;
+    };
+    if !popup.visible || popup.buffer.is_empty() {
+        return data;
+    }
+
+    composite_popup(
+        &mut DestBuffer {
+            data: &mut data,
+            width: fb.width,
+            height: fb.height,
+        },
+        &PopupBuffer {
+            data: &popup.buffer,
+            width: popup.width,
+            height: popup.height,
+            x: (popup.rect.x as f32 * display_scale) as i32,
+            y: (popup.rect.y as f32 * display_scale) as i32,
+        },
+    );
+    popup.mark_clean();
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_requests_coalesce_and_shutdown_ends_worker() {
+        let shared = UploadShared::default();
+        {
+            let mut state = shared.state.lock().unwrap();
+            state.requested = Some(UploadRequest {
+                width: 2,
+                height: 2,
+                mipmaps: false,
+                display_scale: 1.0,
+            });
+            state.requested = Some(UploadRequest {
+                width: 4,
+                height: 4,
+                mipmaps: false,
+                display_scale: 1.0,
+            });
+        }
+
+        let request = next_request(&shared).unwrap();
+        assert_eq!((request.width, request.height), (4, 4));
+        assert!(shared.state.lock().unwrap().busy);
+
+        shared.state.lock().unwrap().shutdown = true;
+        assert_eq!(next_request(&shared), None);
+    }
+
+    #[test]
+    fn test_frame_of_another_size_is_left_dirty() {
+        let frame_buffer = Mutex::new(FrameBuffer::new());
+        frame_buffer
+            .lock()
+            .unwrap()
+            .update(vec![255; 4 * 4 * 4], 4, 4);
+
+        let request = UploadRequest {
+            width: 2,
+            height: 2,
+            mipmaps: false,
+            display_scale: 1.0,
+        };
+        assert!(!upload_frame(
+            &frame_buffer,
+            None,
+            Rid::Invalid,
+            request,
+            None
+        ));
+        assert!(frame_buffer.lock().unwrap().dirty);
+    }
+
+    #[test]
+    fn test_software_mipmaps_skip_large_frames_unless_forced() {
+        assert!(!software_mipmaps(false, true, 16, 16));
+        assert!(software_mipmaps(true, false, 2560, 1600));
+        assert!(!software_mipmaps(true, false, 3840, 2160));
+        assert!(software_mipmaps(true, true, 3840, 2160));
+    }
+
+    #[test]
+    fn test_composite_frame_without_popup_copies() {
+        let mut fb = FrameBuffer::new();
+        fb.update(vec![1, 2, 3, 4], 1, 1);
+        assert_eq!(composite_frame(&fb, None, 1.0), vec![1, 2, 3, 4]);
+    }
+}
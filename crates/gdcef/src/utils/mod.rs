@@ -1,14 +1,22 @@
+mod process;
+
+pub use process::{
+    ProcessPriority, can_restore_helper_priority, gpu_process_id, helper_process_ids,
+    set_helper_process_priority,
+};
+
 use crate::error::{CefError, CefResult};
 use godot::{classes::DisplayServer, obj::Singleton};
 use process_path::get_dylib_path;
 use std::path::PathBuf;
 
-/// Returns the display scale factor for the primary screen.
+/// Returns the display scale factor of `screen`, a screen index or one of
+/// the `DisplayServer::SCREEN_*` constants.
 ///
 /// This value can be used to scale UI elements from logical pixels to
 /// physical pixels in order to appear consistent across different DPI
 /// and high-DPI displays. A value of `1.0` means "no scaling".
-pub fn get_display_scale_factor() -> f32 {
+pub fn get_screen_scale_factor(screen: i32) -> f32 {
     let display_server = DisplayServer::singleton();
 
     // NOTE: `display_server.screen_get_scale` is implemented on Android, iOS,
@@ -16,7 +24,7 @@ pub fn get_display_scale_factor() -> f32 {
     // 1.0, so we derive the scale from the screen DPI instead.
     #[cfg(target_os = "windows")]
     {
-        let dpi = display_server.screen_get_dpi();
+        let dpi = display_server.screen_get_dpi_ex().screen(screen).done();
         if dpi > 0 {
             (dpi as f32 / 96.0).max(1.0)
         } else {
@@ -26,7 +34,7 @@ pub fn get_display_scale_factor() -> f32 {
 
     #[cfg(not(target_os = "windows"))]
     {
-        display_server.screen_get_scale()
+        display_server.screen_get_scale_ex().screen(screen).done()
     }
 }
 
@@ -106,6 +114,66 @@ pub fn get_subprocess_path() -> CefResult<PathBuf> {
         .map_err(CefError::from)
 }
 
+/// Returns the files a working installation needs, as `(name, path)` pairs.
+///
+/// Paths are not canonicalized, so missing files keep their expected path.
+#[cfg(target_os = "macos")]
+pub fn installation_files() -> CefResult<Vec<(&'static str, PathBuf)>> {
+    let dylib_path = get_dylib_path_checked()?;
+    let app = dylib_path.join("../..").join("Godot CEF.app/Contents");
+
+    let framework_name = match std::env::consts::ARCH {
+        "aarch64" => "Chromium Embedded Framework (ARM64).framework",
+        _ => "Chromium Embedded Framework (X86_64).framework",
+    };
+    let framework = app.join("Frameworks").join(framework_name);
+
+    Ok(vec![
+        ("framework", framework.join("Chromium Embedded Framework")),
+        ("sandbox", framework.join("Libraries/libcef_sandbox.dylib")),
+        (
+            "helper",
+            app.join("Frameworks/Godot CEF Helper.app/Contents/MacOS/Godot CEF Helper"),
+        ),
+    ])
+}
+
+/// Returns the files a working installation needs, as `(name, path)` pairs.
+///
+/// Paths are not canonicalized, so missing files keep their expected path.
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub fn installation_files() -> CefResult<Vec<(&'static str, PathBuf)>> {
+    let dylib_path = get_dylib_path_checked()?;
+    let bin_dir = dylib_path
+        .parent()
+        .ok_or_else(|| CefError::ResourceNotFound("extension directory".to_string()))?;
+
+    #[cfg(target_os = "windows")]
+    let files = [
+        ("helper", "gdcef_helper.exe"),
+        ("libcef", "libcef.dll"),
+        ("chrome_elf", "chrome_elf.dll"),
+    ];
+    #[cfg(target_os = "linux")]
+    let files = [
+        ("helper", "gdcef_helper"),
+        ("libcef", "libcef.so"),
+        ("chrome_sandbox", "chrome-sandbox"),
+    ];
+
+    let data_files = [
+        ("icu_data", "icudtl.dat"),
+        ("resources", "resources.pak"),
+        ("v8_snapshot", "v8_context_snapshot.bin"),
+    ];
+
+    Ok(files
+        .into_iter()
+        .chain(data_files)
+        .map(|(name, file)| (name, bin_dir.join(file)))
+        .collect())
+}
+
 #[cfg(unix)]
 pub fn ensure_executable_permissions() -> CefResult<()> {
     use std::os::unix::fs::PermissionsExt;
@@ -0,0 +1,575 @@
+//! Process table helpers for the CEF helper processes.
+//!
+//! CEF runs its renderer, GPU and utility processes as descendants of the
+//! Godot process, all using the helper executable returned by
+//! [`get_subprocess_path`](super::get_subprocess_path). The process table is
+//! read with the platform API and filtered down to those helpers.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+/// One entry of the system process table.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub parent_pid: u32,
+    /// Executable path, or only its file name on platforms that do not
+    /// expose the full path cheaply.
+    pub exe: PathBuf,
+}
+
+/// Scheduling priority applied to the helper processes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProcessPriority {
+    Normal,
+    Background,
+}
+
+/// Returns the pids of the helper processes started by this process.
+pub fn helper_process_ids() -> Vec<u32> {
+    let Ok(helper_path) = super::get_subprocess_path() else {
+        return Vec::new();
+    };
+
+    let processes = platform::list_processes();
+    helper_pids(&processes, std::process::id(), &helper_path)
+}
+
+/// Returns the pid of the GPU helper process started by this process, if
+/// the platform tells it apart from the other helpers.
+pub fn gpu_process_id() -> Option<u32> {
+    let helper_path = super::get_subprocess_path().ok()?;
+
+    let processes = platform::list_processes();
+    helper_pids(&processes, std::process::id(), &helper_path)
+        .into_iter()
+        .find(|&pid| {
+            processes
+                .iter()
+                .any(|process| process.pid == pid && platform::is_gpu_process(process))
+        })
+}
+
+/// Applies `priority` to every helper process. Returns the number of
+/// processes whose priority could not be changed.
+pub fn set_helper_process_priority(priority: ProcessPriority) -> usize {
+    helper_process_ids()
+        .into_iter()
+        .filter(|&pid| !platform::set_priority(pid, priority))
+        .count()
+}
+
+/// Returns false if the helpers could be moved to background priority but
+/// not back to normal, e.g. an unprivileged process on Linux can raise its
+/// nice value but not lower it again.
+pub fn can_restore_helper_priority() -> bool {
+    platform::can_restore_priority()
+}
+
+/// Returns the pids of all descendants of `root_pid` running the helper
+/// executable, in breadth-first order.
+///
+/// Descendants are walked through non-matching processes too, since on Linux
+/// renderers are forked from the zygote rather than the browser process.
+pub fn helper_pids(processes: &[ProcessInfo], root_pid: u32, helper_path: &Path) -> Vec<u32> {
+    let mut children: HashMap<u32, Vec<&ProcessInfo>> = HashMap::new();
+    for process in processes {
+        if process.pid != process.parent_pid {
+            children
+                .entry(process.parent_pid)
+                .or_default()
+                .push(process);
+        }
+    }
+
+    let mut helpers = Vec::new();
+    let mut visited = HashSet::from([root_pid]);
+    let mut pending = VecDeque::from([root_pid]);
+    while let Some(pid) = pending.pop_front() {
+        for child in children.get(&pid).into_iter().flatten() {
+            if !visited.insert(child.pid) {
+                continue;
+            }
+            if is_helper_executable(&child.exe, helper_path) {
+                helpers.push(child.pid);
+            }
+            pending.push_back(child.pid);
+        }
+    }
+
+    helpers
+}
+
+/// Returns true if a helper's command line starts the GPU process.
+#[cfg(any(target_os = "linux", target_os = "windows", test))]
+fn is_gpu_command_line(command_line: &str) -> bool {
+    command_line
+        .split(['\0', ' '])
+        .any(|arg| arg == "--type=gpu-process")
+}
+
+/// Returns true if `exe` is the helper executable.
+///
+/// Only file names are compared (ignoring ASCII case), because Windows only
+/// reports the file name in its process snapshot. On macOS the GPU, renderer
+/// and plugin helpers are separate bundles named `<helper> (<kind>)`, which
+/// also match.
+pub fn is_helper_executable(exe: &Path, helper_path: &Path) -> bool {
+    let (Some(exe_name), Some(helper_name)) = (
+        exe.file_name().and_then(|n| n.to_str()),
+        helper_path.file_name().and_then(|n| n.to_str()),
+    ) else {
+        return false;
+    };
+
+    if exe_name.eq_ignore_ascii_case(helper_name) {
+        return true;
+    }
+
+    let Some(variant) = exe_name.get(..helper_name.len()) else {
+        return false;
+    };
+    variant.eq_ignore_ascii_case(helper_name)
+        && exe_name[helper_name.len()..].starts_with(" (")
+        && exe_name.ends_with(')')
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::{ProcessInfo, ProcessPriority};
+    use std::fs;
+
+    /// Nice value of helper threads in background mode.
+    const BACKGROUND_NICE: libc::c_int = 10;
+
+    pub fn list_processes() -> Vec<ProcessInfo> {
+        let Ok(entries) = fs::read_dir("/proc") else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+            .filter_map(|pid| {
+                let stat = fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+                Some(ProcessInfo {
+                    pid,
+                    parent_pid: parse_stat_parent_pid(&stat)?,
+                    exe: fs::read_link(format!("/proc/{pid}/exe")).ok()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Arguments in `/proc/<pid>/cmdline` are separated by NUL bytes.
+    pub fn is_gpu_process(process: &ProcessInfo) -> bool {
+        fs::read(format!("/proc/{}/cmdline", process.pid))
+            .is_ok_and(|cmdline| super::is_gpu_command_line(&String::from_utf8_lossy(&cmdline)))
+    }
+
+    /// Sets the nice value of every thread of `pid`, since `setpriority` only
+    /// affects a single thread on Linux.
+    ///
+    /// Raising the priority back requires `RLIMIT_NICE` (or `CAP_SYS_NICE`)
+    /// to allow nice value 0; see [`can_restore_priority`].
+    pub fn set_priority(pid: u32, priority: ProcessPriority) -> bool {
+        let nice = match priority {
+            ProcessPriority::Normal => 0,
+            ProcessPriority::Background => BACKGROUND_NICE,
+        };
+        let Ok(tasks) = fs::read_dir(format!("/proc/{pid}/task")) else {
+            return false;
+        };
+
+        let mut all_set = true;
+        for tid in tasks.filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok()) {
+            let result = unsafe { libc::setpriority(libc::PRIO_PROCESS, tid as libc::id_t, nice) };
+            all_set &= result == 0;
+        }
+        all_set
+    }
+
+    /// Returns true if `RLIMIT_NICE` allows lowering the nice value back to
+    /// 0, or the process runs as root.
+    pub fn can_restore_priority() -> bool {
+        if unsafe { libc::geteuid() } == 0 {
+            return true;
+        }
+        let mut limit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        if unsafe { libc::getrlimit(libc::RLIMIT_NICE, &mut limit) } != 0 {
+            return false;
+        }
+        nice_limit_allows(limit.rlim_cur, 0)
+    }
+
+    /// `RLIMIT_NICE` is a ceiling of `20 - nice`, so a limit of `l` allows
+    /// nice values (-20 to 19) down to `20 - l`.
+    pub(super) fn nice_limit_allows(limit: libc::rlim_t, nice: libc::c_int) -> bool {
+        limit >= (20 - nice) as libc::rlim_t
+    }
+
+    /// Parses the parent pid out of `/proc/<pid>/stat`.
+    ///
+    /// The command name is wrapped in parentheses and may itself contain
+    /// spaces or parentheses, so fields are counted after the last `)`.
+    pub(super) fn parse_stat_parent_pid(stat: &str) -> Option<u32> {
+        let (_, rest) = stat.rsplit_once(')')?;
+        let mut fields = rest.split_whitespace();
+        let _state = fields.next()?;
+        fields.next()?.parse().ok()
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::{ProcessInfo, ProcessPriority};
+    use std::path::PathBuf;
+    use windows::Wdk::System::Threading::{
+        NtQueryInformationProcess, ProcessCommandLineInformation,
+    };
+    use windows::Win32::Foundation::{CloseHandle, UNICODE_STRING};
+    use windows::Win32::System::Diagnostics::ToolHelp::{
+        CreateToolhelp32Snapshot, PROCESSENTRY32W, Process32FirstW, Process32NextW,
+        TH32CS_SNAPPROCESS,
+    };
+    use windows::Win32::System::Threading::{
+        BELOW_NORMAL_PRIORITY_CLASS, NORMAL_PRIORITY_CLASS, OpenProcess,
+        PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_SET_INFORMATION, SetPriorityClass,
+    };
+
+    pub fn list_processes() -> Vec<ProcessInfo> {
+        let Ok(snapshot) = (unsafe { CreateToolhelp32Snapshot(TH32CS_SNAPPROCESS, 0) }) else {
+            return Vec::new();
+        };
+
+        let mut processes = Vec::new();
+        let mut entry = PROCESSENTRY32W {
+            dwSize: std::mem::size_of::<PROCESSENTRY32W>() as u32,
+            ..Default::default()
+        };
+        let mut has_entry = unsafe { Process32FirstW(snapshot, &mut entry) }.is_ok();
+        while has_entry {
+            let name_len = entry
+                .szExeFile
+                .iter()
+                .position(|&c| c == 0)
+                .unwrap_or(entry.szExeFile.len());
+            processes.push(ProcessInfo {
+                pid: entry.th32ProcessID,
+                parent_pid: entry.th32ParentProcessID,
+                exe: PathBuf::from(String::from_utf16_lossy(&entry.szExeFile[..name_len])),
+            });
+            has_entry = unsafe { Process32NextW(snapshot, &mut entry) }.is_ok();
+        }
+
+        let _ = unsafe { CloseHandle(snapshot) };
+        processes
+    }
+
+    pub fn is_gpu_process(process: &ProcessInfo) -> bool {
+        command_line(process.pid)
+            .is_some_and(|command_line| super::is_gpu_command_line(&command_line))
+    }
+
+    /// Reads the command line of another process, which the snapshot does
+    /// not include.
+    fn command_line(pid: u32) -> Option<String> {
+        let process = unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, pid) }.ok()?;
+        // The first call reports the size of the UNICODE_STRING and its text
+        let mut length = 0u32;
+        let _ = unsafe {
+            NtQueryInformationProcess(
+                process,
+                ProcessCommandLineInformation,
+                std::ptr::null_mut(),
+                0,
+                &mut length,
+            )
+        };
+        // u64 elements keep the UNICODE_STRING header aligned
+        let mut buffer = vec![0u64; (length as usize).div_ceil(8)];
+        let status = unsafe {
+            NtQueryInformationProcess(
+                process,
+                ProcessCommandLineInformation,
+                buffer.as_mut_ptr().cast(),
+                (buffer.len() * 8) as u32,
+                &mut length,
+            )
+        };
+        let _ = unsafe { CloseHandle(process) };
+        if length == 0 || status.is_err() {
+            return None;
+        }
+
+        let string = unsafe { &*buffer.as_ptr().cast::<UNICODE_STRING>() };
+        if string.Buffer.is_null() {
+            return None;
+        }
+        let text =
+            unsafe { std::slice::from_raw_parts(string.Buffer.0, string.Length as usize / 2) };
+        Some(String::from_utf16_lossy(text))
+    }
+
+    pub fn can_restore_priority() -> bool {
+        true
+    }
+
+    pub fn set_priority(pid: u32, priority: ProcessPriority) -> bool {
+        let priority_class = match priority {
+            ProcessPriority::Normal => NORMAL_PRIORITY_CLASS,
+            ProcessPriority::Background => BELOW_NORMAL_PRIORITY_CLASS,
+        };
+        let Ok(process) = (unsafe { OpenProcess(PROCESS_SET_INFORMATION, false, pid) }) else {
+            return false;
+        };
+        let result = unsafe { SetPriorityClass(process, priority_class) };
+        let _ = unsafe { CloseHandle(process) };
+        result.is_ok()
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::{ProcessInfo, ProcessPriority};
+    use std::ffi::{CStr, c_void};
+    use std::os::unix::ffi::OsStrExt;
+    use std::path::PathBuf;
+
+    pub fn list_processes() -> Vec<ProcessInfo> {
+        let count = unsafe { libc::proc_listallpids(std::ptr::null_mut(), 0) };
+        if count <= 0 {
+            return Vec::new();
+        }
+
+        // Leave room for processes started between the two calls.
+        let mut pids = vec![0 as libc::pid_t; count as usize + 64];
+        let buffer_size = (pids.len() * std::mem::size_of::<libc::pid_t>()) as libc::c_int;
+        let count =
+            unsafe { libc::proc_listallpids(pids.as_mut_ptr() as *mut c_void, buffer_size) };
+        if count <= 0 {
+            return Vec::new();
+        }
+        pids.truncate(count as usize);
+
+        pids.into_iter()
+            .filter(|&pid| pid > 0)
+            .filter_map(|pid| {
+                Some(ProcessInfo {
+                    pid: pid as u32,
+                    parent_pid: parent_pid(pid)?,
+                    exe: executable_path(pid)?,
+                })
+            })
+            .collect()
+    }
+
+    /// The GPU helper is its own bundle, named `<helper> (GPU)`.
+    pub fn is_gpu_process(process: &ProcessInfo) -> bool {
+        process
+            .exe
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(" (GPU)"))
+    }
+
+    pub fn can_restore_priority() -> bool {
+        true
+    }
+
+    /// App Nap already throttles the helpers of an app in the background.
+    pub fn set_priority(_pid: u32, _priority: ProcessPriority) -> bool {
+        true
+    }
+
+    fn parent_pid(pid: libc::pid_t) -> Option<u32> {
+        let mut info: libc::proc_bsdinfo = unsafe { std::mem::zeroed() };
+        let size = std::mem::size_of::<libc::proc_bsdinfo>() as libc::c_int;
+        let written = unsafe {
+            libc::proc_pidinfo(
+                pid,
+                libc::PROC_PIDTBSDINFO,
+                0,
+                &mut info as *mut _ as *mut c_void,
+                size,
+            )
+        };
+        (written == size).then_some(info.pbi_ppid)
+    }
+
+    fn executable_path(pid: libc::pid_t) -> Option<PathBuf> {
+        let mut buffer = vec![0u8; libc::PROC_PIDPATHINFO_MAXSIZE as usize];
+        let len = unsafe {
+            libc::proc_pidpath(pid, buffer.as_mut_ptr() as *mut c_void, buffer.len() as u32)
+        };
+        if len <= 0 {
+            return None;
+        }
+        let path = CStr::from_bytes_until_nul(&buffer).ok()?;
+        Some(PathBuf::from(std::ffi::OsStr::from_bytes(path.to_bytes())))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::{ProcessInfo, ProcessPriority};
+
+    pub fn list_processes() -> Vec<ProcessInfo> {
+        Vec::new()
+    }
+
+    pub fn is_gpu_process(_process: &ProcessInfo) -> bool {
+        false
+    }
+
+    pub fn can_restore_priority() -> bool {
+        true
+    }
+
+    pub fn set_priority(_pid: u32, _priority: ProcessPriority) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, parent_pid: u32, exe: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            parent_pid,
+            exe: PathBuf::from(exe),
+        }
+    }
+
+    #[test]
+    fn test_is_helper_executable() {
+        let helper = Path::new("/game/addons/godot_cef/bin/gdcef_helper");
+        assert!(is_helper_executable(
+            Path::new("/other/dir/gdcef_helper"),
+            helper
+        ));
+        assert!(is_helper_executable(Path::new("GDCEF_HELPER"), helper));
+        assert!(!is_helper_executable(
+            Path::new("/usr/bin/gdcef_helper2"),
+            helper
+        ));
+        assert!(!is_helper_executable(Path::new("/usr/bin/godot"), helper));
+        assert!(!is_helper_executable(Path::new(""), helper));
+    }
+
+    #[test]
+    fn test_is_helper_executable_matches_macos_variants() {
+        let helper = Path::new("/Frameworks/Godot CEF Helper.app/Contents/MacOS/Godot CEF Helper");
+        assert!(is_helper_executable(
+            Path::new(
+                "/Frameworks/Godot CEF Helper (GPU).app/Contents/MacOS/Godot CEF Helper (GPU)"
+            ),
+            helper
+        ));
+        assert!(is_helper_executable(
+            Path::new("Godot CEF Helper (Renderer)"),
+            helper
+        ));
+        assert!(!is_helper_executable(
+            Path::new("Godot CEF Helper Tool"),
+            helper
+        ));
+        assert!(!is_helper_executable(
+            Path::new("Godot CEF Helper (GPU"),
+            helper
+        ));
+    }
+
+    #[test]
+    fn test_helper_pids_includes_nested_descendants() {
+        let helper = Path::new("/bin/gdcef_helper");
+        let processes = vec![
+            process(1, 0, "/sbin/init"),
+            process(100, 1, "/usr/bin/godot"),
+            process(101, 100, "/bin/gdcef_helper"),
+            process(102, 100, "/bin/gdcef_helper"),
+            // Renderer forked from the zygote.
+            process(103, 102, "/bin/gdcef_helper"),
+            process(104, 100, "/usr/bin/unrelated"),
+            process(105, 104, "/bin/gdcef_helper"),
+        ];
+
+        assert_eq!(
+            helper_pids(&processes, 100, helper),
+            vec![101, 102, 103, 105]
+        );
+    }
+
+    #[test]
+    fn test_helper_pids_ignores_other_process_trees() {
+        let helper = Path::new("gdcef_helper.exe");
+        let processes = vec![
+            process(100, 1, "godot.exe"),
+            process(200, 1, "godot.exe"),
+            process(201, 200, "gdcef_helper.exe"),
+        ];
+
+        assert!(helper_pids(&processes, 100, helper).is_empty());
+    }
+
+    #[test]
+    fn test_helper_pids_handles_parent_cycles() {
+        let helper = Path::new("gdcef_helper.exe");
+        // Windows reuses pids, so a stale parent pid can form a cycle.
+        let processes = vec![
+            process(0, 0, "[System Process]"),
+            process(100, 101, "godot.exe"),
+            process(101, 100, "gdcef_helper.exe"),
+        ];
+
+        assert_eq!(helper_pids(&processes, 100, helper), vec![101]);
+    }
+
+    #[test]
+    fn test_is_gpu_command_line() {
+        assert!(is_gpu_command_line(
+            "gdcef_helper\0--type=gpu-process\0--gpu-preferences=UAAAAAAA\0"
+        ));
+        assert!(is_gpu_command_line(
+            r#""C:\game\gdcef_helper.exe" --type=gpu-process --no-sandbox"#
+        ));
+        assert!(!is_gpu_command_line(
+            "gdcef_helper\0--type=renderer\0--lang=en-US\0"
+        ));
+        assert!(!is_gpu_command_line("gdcef_helper --type=gpu-process-x"));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_parse_stat_parent_pid() {
+        use super::platform::parse_stat_parent_pid;
+
+        assert_eq!(
+            parse_stat_parent_pid("1234 (gdcef_helper) S 1200 1234 1200 0 -1"),
+            Some(1200)
+        );
+        assert_eq!(
+            parse_stat_parent_pid("42 (weird ) name) R 7 42 42 0"),
+            Some(7)
+        );
+        assert_eq!(parse_stat_parent_pid("garbage"), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_nice_limit_allows() {
+        use super::platform::nice_limit_allows;
+
+        // The common default of 0 only allows raising the nice value
+        assert!(!nice_limit_allows(0, 0));
+        assert!(nice_limit_allows(0, 20));
+        assert!(!nice_limit_allows(19, 0));
+        assert!(nice_limit_allows(20, 0));
+        assert!(nice_limit_allows(10, 10));
+        assert!(nice_limit_allows(libc::RLIM_INFINITY, -20));
+    }
+}
@@ -0,0 +1,718 @@
+//! Vulkan loader layer that injects the external memory extensions.
+//!
+//! [`register_layer`] writes a layer manifest pointing at this library and
+//! enables it through the loader's `VK_ADD_LAYER_PATH` and `VK_INSTANCE_LAYERS`
+//! environment variables. When Godot creates its Vulkan instance, the loader
+//! negotiates with [`vkNegotiateLoaderLayerInterfaceVersion`] and routes
+//! `vkCreateInstance` and `vkCreateDevice` through this layer, which adds the
+//! extensions before passing the call on. Every other call goes straight to the
+//! next layer or driver.
+
+use ash::vk::{self, Handle};
+use std::ffi::{CStr, OsString, c_char, c_void};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use super::platform::{EXTERNAL_MEMORY_EXTENSIONS, LOG_PREFIX};
+use super::update_status;
+
+const LAYER_NAME: &str = "VK_LAYER_GODOT_CEF_external_memory";
+
+const ADD_LAYER_PATH_VAR: &str = "VK_ADD_LAYER_PATH";
+const INSTANCE_LAYERS_VAR: &str = "VK_INSTANCE_LAYERS";
+
+/// `VK_LAYER_LINK_INFO` from `vk_layer.h`.
+const LAYER_LINK_INFO: i32 = 0;
+/// `LAYER_NEGOTIATE_INTERFACE_STRUCT` from `vk_layer.h`.
+const LAYER_NEGOTIATE_INTERFACE_STRUCT: i32 = 1;
+/// Loader/layer interface version implemented here.
+const LAYER_INTERFACE_VERSION: u32 = 2;
+
+static HOOK_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Set once a device was created with every external memory extension enabled.
+static EXTERNAL_MEMORY_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Variable names and their values from before the layer was registered.
+type Environment = Vec<(&'static str, Option<OsString>)>;
+
+/// Environment variable values from before the layer was registered.
+static ORIGINAL_ENVIRONMENT: Mutex<Option<Environment>> = Mutex::new(None);
+
+/// Next `vkGetInstanceProcAddr` in the chain, keyed by dispatch key.
+static INSTANCES: Mutex<Vec<(usize, vk::Instance, vk::PFN_vkGetInstanceProcAddr)>> =
+    Mutex::new(Vec::new());
+
+/// Next `vkGetDeviceProcAddr` in the chain, keyed by dispatch key.
+static DEVICES: Mutex<Vec<(usize, vk::PFN_vkGetDeviceProcAddr)>> = Mutex::new(Vec::new());
+
+#[repr(C)]
+struct LayerInstanceLink {
+    next: *mut LayerInstanceLink,
+    next_get_instance_proc_addr: vk::PFN_vkGetInstanceProcAddr,
+    next_get_physical_device_proc_addr: *const c_void,
+}
+
+#[repr(C)]
+struct LayerDeviceLink {
+    next: *mut LayerDeviceLink,
+    next_get_instance_proc_addr: vk::PFN_vkGetInstanceProcAddr,
+    next_get_device_proc_addr: vk::PFN_vkGetDeviceProcAddr,
+}
+
+/// `VkLayerInstanceCreateInfo` / `VkLayerDeviceCreateInfo`, read through the
+/// `pLayerInfo` member of their union.
+#[repr(C)]
+struct LayerCreateInfo<L> {
+    s_type: vk::StructureType,
+    p_next: *const c_void,
+    function: i32,
+    layer_info: *mut L,
+}
+
+/// `VkNegotiateLayerInterface` from `vk_layer.h`.
+#[repr(C)]
+pub struct NegotiateLayerInterface {
+    s_type: i32,
+    p_next: *mut c_void,
+    loader_layer_interface_version: u32,
+    get_instance_proc_addr: Option<vk::PFN_vkGetInstanceProcAddr>,
+    get_device_proc_addr: Option<vk::PFN_vkGetDeviceProcAddr>,
+    get_physical_device_proc_addr: *const c_void,
+}
+
+/// Returns true if Godot's Vulkan device was created with the external memory
+/// extensions required to import CEF's shared textures.
+pub fn is_external_memory_enabled() -> bool {
+    EXTERNAL_MEMORY_ENABLED.load(Ordering::Acquire)
+}
+
+/// Records a registration failure in the hook status.
+fn registration_failed(error: String) {
+    eprintln!("{LOG_PREFIX} {}", error);
+    HOOK_INSTALLED.store(false, Ordering::SeqCst);
+    update_status(|status| status.error = Some(error));
+}
+
+pub fn register_layer() {
+    if HOOK_INSTALLED.swap(true, Ordering::SeqCst) {
+        eprintln!("{LOG_PREFIX} Layer already registered");
+        return;
+    }
+
+    let Some(library_path) = process_path::get_dylib_path() else {
+        registration_failed("Failed to locate the extension library".to_string());
+        return;
+    };
+
+    let Some(manifest_dir) = manifest_dir(&library_path) else {
+        registration_failed("No per-user directory for the layer manifest".to_string());
+        return;
+    };
+    if let Err(e) = write_manifest(&manifest_dir, &library_path) {
+        registration_failed(format!("Failed to write layer manifest: {}", e));
+        return;
+    }
+
+    let original = vec![
+        (ADD_LAYER_PATH_VAR, std::env::var_os(ADD_LAYER_PATH_VAR)),
+        (INSTANCE_LAYERS_VAR, std::env::var_os(INSTANCE_LAYERS_VAR)),
+    ];
+    let layer_path = prepend_to_list(
+        original[0].1.as_deref(),
+        manifest_dir.as_os_str().to_owned(),
+    );
+    let instance_layers = prepend_to_list(original[1].1.as_deref(), LAYER_NAME.into());
+    let (Some(layer_path), Some(instance_layers)) = (layer_path, instance_layers) else {
+        registration_failed("Layer path contains a list separator".to_string());
+        return;
+    };
+
+    // SAFETY: called at the Core initialization stage, before Godot starts the
+    // rendering server or any thread that reads the environment.
+    unsafe {
+        std::env::set_var(ADD_LAYER_PATH_VAR, layer_path);
+        std::env::set_var(INSTANCE_LAYERS_VAR, instance_layers);
+    }
+    if let Ok(mut saved) = ORIGINAL_ENVIRONMENT.lock() {
+        *saved = Some(original);
+    }
+    update_status(|status| status.installed = true);
+
+    eprintln!(
+        "{LOG_PREFIX} Registered Vulkan layer from {}",
+        manifest_dir.display()
+    );
+}
+
+/// Restores the loader environment variables changed by [`register_layer`].
+///
+/// Called once Godot's Vulkan device exists, so that CEF's subprocesses, which
+/// inherit the environment, do not load this library as a layer.
+pub fn restore_environment() {
+    let Some(original) = ORIGINAL_ENVIRONMENT.lock().ok().and_then(|mut o| o.take()) else {
+        return;
+    };
+
+    // SAFETY: called at the Scene initialization stage from the main thread,
+    // before CEF is initialized.
+    unsafe {
+        for (name, value) in original {
+            match value {
+                Some(value) => std::env::set_var(name, value),
+                None => std::env::remove_var(name),
+            }
+        }
+    }
+}
+
+/// Directory holding the layer manifest for this library. Each library path
+/// gets its own directory, because the loader reads every manifest in it.
+/// It lives under a per-user directory, so other local users cannot plant a
+/// manifest that Godot would load.
+fn manifest_dir(library_path: &Path) -> Option<PathBuf> {
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    library_path.hash(&mut hasher);
+    Some(
+        user_dir()?
+            .join("godot-cef")
+            .join(format!("vulkan-layer-{:016x}", hasher.finish())),
+    )
+}
+
+/// Reads an absolute directory path from an environment variable.
+fn absolute_dir_var(name: &str) -> Option<PathBuf> {
+    std::env::var_os(name)
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+}
+
+/// `$XDG_RUNTIME_DIR`, falling back to the user's cache directory.
+#[cfg(unix)]
+fn user_dir() -> Option<PathBuf> {
+    absolute_dir_var("XDG_RUNTIME_DIR")
+        .or_else(|| absolute_dir_var("XDG_CACHE_HOME"))
+        .or_else(|| absolute_dir_var("HOME").map(|home| home.join(".cache")))
+}
+
+/// `%LOCALAPPDATA%`, which only the user can write to.
+#[cfg(windows)]
+fn user_dir() -> Option<PathBuf> {
+    absolute_dir_var("LOCALAPPDATA")
+}
+
+/// Creates `dir` and its parent with mode 0700 and checks that both are
+/// directories owned by the current user, tightening their permissions if
+/// needed.
+#[cfg(unix)]
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+
+    std::fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o700)
+        .create(dir)?;
+
+    // SAFETY: geteuid has no preconditions.
+    let uid = unsafe { libc::geteuid() };
+    for path in [dir.parent(), Some(dir)].into_iter().flatten() {
+        let metadata = std::fs::symlink_metadata(path)?;
+        if !metadata.is_dir() || metadata.uid() != uid {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::PermissionDenied,
+                format!("{} is not a directory owned by this user", path.display()),
+            ));
+        }
+        if metadata.mode() & 0o077 != 0 {
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o700))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(windows)]
+fn create_private_dir(dir: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)
+}
+
+fn write_manifest(dir: &Path, library_path: &Path) -> std::io::Result<()> {
+    create_private_dir(dir)?;
+
+    // Write to a temporary file first so a concurrently starting process never
+    // reads a partial manifest
+    let manifest = layer_manifest(library_path);
+    let temp_path = dir.join(format!("layer.json.{}.tmp", std::process::id()));
+    std::fs::write(&temp_path, manifest)?;
+    std::fs::rename(&temp_path, dir.join("layer.json"))
+}
+
+fn layer_manifest(library_path: &Path) -> String {
+    format!(
+        r#"{{
+    "file_format_version": "1.1.2",
+    "layer": {{
+        "name": "{LAYER_NAME}",
+        "type": "GLOBAL",
+        "library_path": "{}",
+        "api_version": "1.3.0",
+        "implementation_version": "1",
+        "description": "Enables external memory extensions for Godot CEF",
+        "functions": {{
+            "vkNegotiateLoaderLayerInterfaceVersion": "vkNegotiateLoaderLayerInterfaceVersion"
+        }}
+    }}
+}}
+"#,
+        json_escape(&library_path.to_string_lossy())
+    )
+}
+
+fn json_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Puts `item` in front of an OS path-separated list. Returns `None` if the
+/// item itself contains a separator.
+fn prepend_to_list(existing: Option<&std::ffi::OsStr>, item: OsString) -> Option<OsString> {
+    let mut items = vec![PathBuf::from(item)];
+    if let Some(existing) = existing.filter(|existing| !existing.is_empty()) {
+        items.extend(std::env::split_paths(existing));
+    }
+    std::env::join_paths(items).ok()
+}
+
+/// Returns the required extensions that the device supports but that are not
+/// enabled yet.
+fn extensions_to_add(
+    required: &[&'static CStr],
+    supported: &[&CStr],
+    enabled: &[&CStr],
+) -> Vec<&'static CStr> {
+    required
+        .iter()
+        .copied()
+        .filter(|name| supported.contains(name) && !enabled.contains(name))
+        .collect()
+}
+
+/// Returns true if every required extension is in `enabled`.
+fn all_enabled(required: &[&CStr], enabled: &[&CStr]) -> bool {
+    required.iter().all(|name| enabled.contains(name))
+}
+
+/// Loader dispatch key of a dispatchable handle: the dispatch table pointer
+/// stored at the start of the object.
+unsafe fn dispatch_key(handle: u64) -> usize {
+    unsafe { *(handle as usize as *const usize) }
+}
+
+fn to_void_function(function: *const ()) -> vk::PFN_vkVoidFunction {
+    Some(unsafe { std::mem::transmute::<*const (), unsafe extern "system" fn()>(function) })
+}
+
+/// Finds the loader's link info for this layer in a create info `pNext` chain.
+unsafe fn find_link_info<L>(
+    mut p_next: *const c_void,
+    s_type: vk::StructureType,
+) -> *mut LayerCreateInfo<L> {
+    while !p_next.is_null() {
+        let info = p_next as *mut LayerCreateInfo<L>;
+        unsafe {
+            if (*info).s_type == s_type && (*info).function == LAYER_LINK_INFO {
+                return info;
+            }
+            p_next = (*info).p_next;
+        }
+    }
+    std::ptr::null_mut()
+}
+
+/// Entry point the loader looks up in the layer manifest.
+///
+/// # Safety
+///
+/// `interface` must be null or point to a valid `VkNegotiateLayerInterface`.
+#[allow(non_snake_case)]
+#[unsafe(no_mangle)]
+pub unsafe extern "system" fn vkNegotiateLoaderLayerInterfaceVersion(
+    interface: *mut NegotiateLayerInterface,
+) -> vk::Result {
+    let Some(interface) = (unsafe { interface.as_mut() }) else {
+        return vk::Result::ERROR_INITIALIZATION_FAILED;
+    };
+    if interface.s_type != LAYER_NEGOTIATE_INTERFACE_STRUCT
+        || interface.loader_layer_interface_version < LAYER_INTERFACE_VERSION
+    {
+        return vk::Result::ERROR_INITIALIZATION_FAILED;
+    }
+
+    interface.loader_layer_interface_version = LAYER_INTERFACE_VERSION;
+    interface.get_instance_proc_addr = Some(get_instance_proc_addr);
+    interface.get_device_proc_addr = Some(get_device_proc_addr);
+    interface.get_physical_device_proc_addr = std::ptr::null();
+    vk::Result::SUCCESS
+}
+
+fn intercepted_function(name: &CStr) -> Option<vk::PFN_vkVoidFunction> {
+    let function = match name.to_bytes() {
+        b"vkGetInstanceProcAddr" => get_instance_proc_addr as *const (),
+        b"vkGetDeviceProcAddr" => get_device_proc_addr as *const (),
+        b"vkCreateInstance" => create_instance as *const (),
+        b"vkCreateDevice" => create_device as *const (),
+        _ => return None,
+    };
+    Some(to_void_function(function))
+}
+
+/// Looks up the instance a dispatchable handle belongs to. Handles this
+/// layer did not see being created are not looked up in another instance.
+fn find_instance(key: usize) -> Option<(vk::Instance, vk::PFN_vkGetInstanceProcAddr)> {
+    let instances = INSTANCES.lock().ok()?;
+    instances
+        .iter()
+        .rev()
+        .find(|(instance_key, ..)| *instance_key == key)
+        .map(|&(_, instance, next)| (instance, next))
+}
+
+unsafe extern "system" fn get_instance_proc_addr(
+    instance: vk::Instance,
+    p_name: *const c_char,
+) -> vk::PFN_vkVoidFunction {
+    if p_name.is_null() {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(p_name) };
+    if let Some(function) = intercepted_function(name) {
+        return function;
+    }
+    if instance == vk::Instance::null() {
+        return None;
+    }
+
+    let (_, next) = find_instance(unsafe { dispatch_key(instance.as_raw()) })?;
+    unsafe { next(instance, p_name) }
+}
+
+unsafe extern "system" fn get_device_proc_addr(
+    device: vk::Device,
+    p_name: *const c_char,
+) -> vk::PFN_vkVoidFunction {
+    if p_name.is_null() || device == vk::Device::null() {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(p_name) };
+    if name.to_bytes() == b"vkGetDeviceProcAddr" {
+        return to_void_function(get_device_proc_addr as *const ());
+    }
+
+    let key = unsafe { dispatch_key(device.as_raw()) };
+    let next = {
+        let devices = DEVICES.lock().ok()?;
+        devices
+            .iter()
+            .rev()
+            .find(|(device_key, _)| *device_key == key)
+            .map(|&(_, next)| next)?
+    };
+    unsafe { next(device, p_name) }
+}
+
+unsafe extern "system" fn create_instance(
+    p_create_info: *const vk::InstanceCreateInfo<'_>,
+    p_allocator: *const vk::AllocationCallbacks<'_>,
+    p_instance: *mut vk::Instance,
+) -> vk::Result {
+    unsafe {
+        if p_create_info.is_null() {
+            return vk::Result::ERROR_INITIALIZATION_FAILED;
+        }
+        let link_info = find_link_info::<LayerInstanceLink>(
+            (*p_create_info).p_next,
+            vk::StructureType::LOADER_INSTANCE_CREATE_INFO,
+        );
+        if link_info.is_null() || (*link_info).layer_info.is_null() {
+            return vk::Result::ERROR_INITIALIZATION_FAILED;
+        }
+
+        // Advance the chain so the next layer finds its own link info
+        let link = (*link_info).layer_info;
+        let next_get_instance_proc_addr = (*link).next_get_instance_proc_addr;
+        (*link_info).layer_info = (*link).next;
+
+        let Some(next_create_instance) =
+            next_get_instance_proc_addr(vk::Instance::null(), c"vkCreateInstance".as_ptr())
+        else {
+            return vk::Result::ERROR_INITIALIZATION_FAILED;
+        };
+        let next_create_instance = std::mem::transmute::<
+            unsafe extern "system" fn(),
+            vk::PFN_vkCreateInstance,
+        >(next_create_instance);
+
+        let result = next_create_instance(p_create_info, p_allocator, p_instance);
+        if result == vk::Result::SUCCESS
+            && let Ok(mut instances) = INSTANCES.lock()
+        {
+            let instance = *p_instance;
+            instances.push((
+                dispatch_key(instance.as_raw()),
+                instance,
+                next_get_instance_proc_addr,
+            ));
+        }
+        result
+    }
+}
+
+/// Returns the extensions `physical_device` supports, as reported by the next
+/// layer or driver.
+unsafe fn supported_extensions(
+    instance: vk::Instance,
+    next_get_instance_proc_addr: vk::PFN_vkGetInstanceProcAddr,
+    physical_device: vk::PhysicalDevice,
+) -> Vec<vk::ExtensionProperties> {
+    unsafe {
+        let Some(enumerate) =
+            next_get_instance_proc_addr(instance, c"vkEnumerateDeviceExtensionProperties".as_ptr())
+        else {
+            return Vec::new();
+        };
+        let enumerate = std::mem::transmute::<
+            unsafe extern "system" fn(),
+            vk::PFN_vkEnumerateDeviceExtensionProperties,
+        >(enumerate);
+
+        let mut count = 0u32;
+        let result = enumerate(
+            physical_device,
+            std::ptr::null(),
+            &mut count,
+            std::ptr::null_mut(),
+        );
+        if result != vk::Result::SUCCESS || count == 0 {
+            return Vec::new();
+        }
+
+        let mut properties = vec![vk::ExtensionProperties::default(); count as usize];
+        let result = enumerate(
+            physical_device,
+            std::ptr::null(),
+            &mut count,
+            properties.as_mut_ptr(),
+        );
+        if result != vk::Result::SUCCESS {
+            return Vec::new();
+        }
+        properties.truncate(count as usize);
+        properties
+    }
+}
+
+unsafe extern "system" fn create_device(
+    physical_device: vk::PhysicalDevice,
+    p_create_info: *const vk::DeviceCreateInfo<'_>,
+    p_allocator: *const vk::AllocationCallbacks<'_>,
+    p_device: *mut vk::Device,
+) -> vk::Result {
+    unsafe {
+        if p_create_info.is_null() {
+            return vk::Result::ERROR_INITIALIZATION_FAILED;
+        }
+        let link_info = find_link_info::<LayerDeviceLink>(
+            (*p_create_info).p_next,
+            vk::StructureType::LOADER_DEVICE_CREATE_INFO,
+        );
+        if link_info.is_null() || (*link_info).layer_info.is_null() {
+            return vk::Result::ERROR_INITIALIZATION_FAILED;
+        }
+
+        // Advance the chain so the next layer finds its own link info
+        let link = (*link_info).layer_info;
+        let next_get_instance_proc_addr = (*link).next_get_instance_proc_addr;
+        let next_get_device_proc_addr = (*link).next_get_device_proc_addr;
+        (*link_info).layer_info = (*link).next;
+
+        let Some(next_create_device) =
+            next_get_instance_proc_addr(vk::Instance::null(), c"vkCreateDevice".as_ptr())
+        else {
+            return vk::Result::ERROR_INITIALIZATION_FAILED;
+        };
+        let next_create_device = std::mem::transmute::<
+            unsafe extern "system" fn(),
+            vk::PFN_vkCreateDevice,
+        >(next_create_device);
+
+        let original_info = &*p_create_info;
+        let mut extensions: Vec<*const c_char> = if original_info.enabled_extension_count > 0
+            && !original_info.pp_enabled_extension_names.is_null()
+        {
+            std::slice::from_raw_parts(
+                original_info.pp_enabled_extension_names,
+                original_info.enabled_extension_count as usize,
+            )
+            .to_vec()
+        } else {
+            Vec::new()
+        };
+        let enabled: Vec<&CStr> = extensions
+            .iter()
+            .filter(|name| !name.is_null())
+            .map(|&name| CStr::from_ptr(name))
+            .collect();
+
+        let instance = find_instance(dispatch_key(physical_device.as_raw()))
+            .map_or(vk::Instance::null(), |(instance, _)| instance);
+        let properties =
+            supported_extensions(instance, next_get_instance_proc_addr, physical_device);
+        let supported: Vec<&CStr> = properties
+            .iter()
+            .map(|property| CStr::from_ptr(property.extension_name.as_ptr()))
+            .collect();
+
+        let added = extensions_to_add(EXTERNAL_MEMORY_EXTENSIONS, &supported, &enabled);
+        let mut all_extensions = enabled.clone();
+        all_extensions.extend(added.iter().copied());
+        let external_memory = all_enabled(EXTERNAL_MEMORY_EXTENSIONS, &all_extensions);
+        if !external_memory {
+            eprintln!("{LOG_PREFIX} External memory extensions not supported by device");
+        }
+
+        let result = if added.is_empty() {
+            next_create_device(physical_device, p_create_info, p_allocator, p_device)
+        } else {
+            for name in &added {
+                eprintln!("{LOG_PREFIX} Adding {}", name.to_string_lossy());
+                extensions.push(name.as_ptr());
+            }
+            let modified_info = vk::DeviceCreateInfo {
+                enabled_extension_count: extensions.len() as u32,
+                pp_enabled_extension_names: extensions.as_ptr(),
+                ..*original_info
+            };
+            next_create_device(physical_device, &modified_info, p_allocator, p_device)
+        };
+
+        if result == vk::Result::SUCCESS {
+            let device = *p_device;
+            if let Ok(mut devices) = DEVICES.lock() {
+                devices.push((dispatch_key(device.as_raw()), next_get_device_proc_addr));
+            }
+            EXTERNAL_MEMORY_ENABLED.store(external_memory, Ordering::Release);
+            if external_memory {
+                eprintln!("{LOG_PREFIX} Created device with external memory extensions");
+            }
+            update_status(|status| {
+                status.injected_extensions = added
+                    .iter()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .collect();
+                status.external_memory_enabled = external_memory;
+                status.error = (!external_memory)
+                    .then(|| "External memory extensions not supported by device".to_string());
+            });
+        } else {
+            eprintln!("{LOG_PREFIX} Device creation failed: {:?}", result);
+            update_status(|status| {
+                status.error = Some(format!("Device creation failed: {:?}", result));
+            });
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REQUIRED: &[&CStr] = &[c"VK_KHR_external_memory", c"VK_KHR_external_memory_fd"];
+
+    #[test]
+    fn test_extensions_to_add() {
+        let supported = [
+            c"VK_KHR_swapchain",
+            c"VK_KHR_external_memory",
+            c"VK_KHR_external_memory_fd",
+        ];
+        assert_eq!(
+            extensions_to_add(REQUIRED, &supported, &[c"VK_KHR_swapchain"]),
+            REQUIRED.to_vec()
+        );
+        assert_eq!(
+            extensions_to_add(REQUIRED, &supported, &[c"VK_KHR_external_memory"]),
+            vec![c"VK_KHR_external_memory_fd"]
+        );
+        // Unsupported extensions are never added
+        assert!(extensions_to_add(REQUIRED, &[c"VK_KHR_swapchain"], &[]).is_empty());
+    }
+
+    #[test]
+    fn test_all_enabled() {
+        assert!(all_enabled(
+            REQUIRED,
+            &[
+                c"VK_KHR_swapchain",
+                c"VK_KHR_external_memory_fd",
+                c"VK_KHR_external_memory"
+            ]
+        ));
+        assert!(!all_enabled(REQUIRED, &[c"VK_KHR_external_memory"]));
+    }
+
+    #[test]
+    fn test_layer_manifest_escapes_library_path() {
+        let manifest = layer_manifest(Path::new(r#"C:\Games\My "Game"\gdcef.dll"#));
+        assert!(manifest.contains(r#""library_path": "C:\\Games\\My \"Game\"\\gdcef.dll""#));
+        assert!(manifest.contains(LAYER_NAME));
+    }
+
+    #[test]
+    fn test_prepend_to_list() {
+        let list = prepend_to_list(None, "a".into()).unwrap();
+        assert_eq!(list, OsString::from("a"));
+
+        let existing = std::env::join_paths(["b", "c"]).unwrap();
+        let list = prepend_to_list(Some(&existing), "a".into()).unwrap();
+        let items: Vec<PathBuf> = std::env::split_paths(&list).collect();
+        assert_eq!(
+            items,
+            vec![PathBuf::from("a"), PathBuf::from("b"), PathBuf::from("c")]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_private_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root =
+            std::env::temp_dir().join(format!("godot-cef-layer-test-{}", std::process::id()));
+        let dir = root.join("layer");
+        create_private_dir(&dir).unwrap();
+        let mode = |path: &Path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode(&dir), 0o700);
+
+        // A directory left readable by others is tightened
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o777)).unwrap();
+        create_private_dir(&dir).unwrap();
+        assert_eq!(mode(&dir), 0o700);
+
+        // A file in place of the directory is rejected
+        std::fs::remove_dir(&dir).unwrap();
+        std::fs::write(&dir, "").unwrap();
+        assert!(create_private_dir(&dir).is_err());
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
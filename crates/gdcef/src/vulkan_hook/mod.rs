@@ -1,32 +1,148 @@
 //! Vulkan device creation hook for injecting external memory extensions.
 //!
-//! This module hooks `vkCreateDevice` during GDExtension initialization (at the Core stage)
-//! to inject platform-specific external memory extensions that Godot doesn't enable by default.
+//! During GDExtension initialization (at the Core stage) this library registers itself
+//! as a Vulkan loader layer (see [`layer`]) that adds platform-specific external memory
+//! extensions to `vkCreateDevice`, which Godot doesn't enable by default. Going through
+//! the loader instead of patching code in memory does not depend on the CPU architecture.
 //!
 //! Platform-specific extensions:
 //! - Windows: `VK_KHR_external_memory_win32` for HANDLE sharing
 //! - Linux: `VK_EXT_external_memory_dma_buf` for DMA-Buf sharing
-//! - macOS: Not supported — Godot statically links MoltenVK, making hook injection impossible. Use the Metal backend instead, which supports IOSurface sharing natively.
+//! - macOS: Not supported — Godot statically links MoltenVK, so no loader is involved. Use the Metal backend instead, which supports IOSurface sharing natively.
+//!
+//! Registration can be skipped with the `GODOT_CEF_DISABLE_VK_HOOK=1` environment
+//! variable or the `godot_cef/advanced/disable_vulkan_hook` project setting, e.g.
+//! when it conflicts with another Vulkan layer. [`status`] reports the outcome.
+
+use godot::global::{godot_print, godot_warn};
+use std::ffi::OsStr;
+use std::sync::Mutex;
 
-#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+mod layer;
+
+#[cfg(target_os = "windows")]
 mod windows;
 
-#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+#[cfg(target_os = "linux")]
 mod linux;
 
-#[cfg(all(target_os = "windows", target_arch = "x86_64"))]
-pub use windows::install_vulkan_hook;
+#[cfg(target_os = "windows")]
+use windows as platform;
+
+#[cfg(target_os = "linux")]
+use linux as platform;
 
-#[cfg(all(target_os = "linux", target_arch = "x86_64"))]
-pub use linux::install_vulkan_hook;
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+pub use layer::{is_external_memory_enabled, restore_environment};
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn restore_environment() {}
+
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub fn is_external_memory_enabled() -> bool {
+    false
+}
+
+/// Environment variable that skips the layer registration when set to a truthy value.
+const DISABLE_ENV_VAR: &str = "GODOT_CEF_DISABLE_VK_HOOK";
+
+/// Outcome of the layer registration and of Godot's Vulkan device creation.
+#[derive(Debug, Clone, Default)]
+pub struct VulkanHookStatus {
+    /// The layer was registered with the Vulkan loader.
+    pub installed: bool,
+    /// Registration was skipped by the environment variable or project setting.
+    pub disabled: bool,
+    /// Extensions the layer added to Godot's `vkCreateDevice` call.
+    pub injected_extensions: Vec<String>,
+    /// Godot's device was created with every required external memory extension.
+    pub external_memory_enabled: bool,
+    pub error: Option<String>,
+}
+
+static STATUS: Mutex<VulkanHookStatus> = Mutex::new(VulkanHookStatus {
+    installed: false,
+    disabled: false,
+    injected_extensions: Vec::new(),
+    external_memory_enabled: false,
+    error: None,
+});
+
+/// Returns the current Vulkan hook status.
+pub fn status() -> VulkanHookStatus {
+    STATUS
+        .lock()
+        .map(|status| status.clone())
+        .unwrap_or_default()
+}
 
-#[cfg(not(any(
-    all(target_os = "windows", target_arch = "x86_64"),
-    all(target_os = "linux", target_arch = "x86_64")
-)))]
+fn update_status(update: impl FnOnce(&mut VulkanHookStatus)) {
+    if let Ok(mut status) = STATUS.lock() {
+        update(&mut status);
+    }
+}
+
+fn is_truthy(value: Option<&OsStr>) -> bool {
+    value
+        .and_then(OsStr::to_str)
+        .map(|value| value.trim().to_ascii_lowercase())
+        .is_some_and(|value| matches!(value.as_str(), "1" | "true" | "yes" | "on"))
+}
+
+/// Returns what disabled the hook, if anything.
+fn disabled_by() -> Option<&'static str> {
+    if is_truthy(std::env::var_os(DISABLE_ENV_VAR).as_deref()) {
+        Some(DISABLE_ENV_VAR)
+    } else if crate::settings::is_vulkan_hook_disabled() {
+        Some("the godot_cef/advanced/disable_vulkan_hook project setting")
+    } else {
+        None
+    }
+}
+
+/// Registers the Vulkan layer unless it was disabled. Must run at the Core
+/// initialization stage, before Godot creates its Vulkan instance.
 pub fn install_vulkan_hook() {
-    // No-op on unsupported platforms:
-    // - ARM64: retour doesn't support ARM64 architecture
-    // - macOS: Godot statically links MoltenVK, so there's no dynamic symbol to hook
-    //          (even if retour supported ARM64, hooking wouldn't work on macOS)
+    if let Some(source) = disabled_by() {
+        eprintln!("[VulkanHook] Layer registration disabled by {}", source);
+        update_status(|status| status.disabled = true);
+        return;
+    }
+
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
+    layer::register_layer();
+    // No-op on macOS: Godot statically links MoltenVK, so there's no loader to add a layer to
+}
+
+/// Logs the extensions injected into Godot's device and any hook error once
+/// Godot's rendering server exists.
+pub fn log_status() {
+    let status = status();
+    if status.disabled {
+        godot_print!("[VulkanHook] Disabled, Vulkan accelerated OSR is unavailable");
+    } else if !status.injected_extensions.is_empty() {
+        godot_print!(
+            "[VulkanHook] Injected extensions: {}",
+            status.injected_extensions.join(", ")
+        );
+    }
+    if let Some(error) = &status.error {
+        godot_warn!("[VulkanHook] {}", error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_truthy() {
+        assert!(is_truthy(Some(OsStr::new("1"))));
+        assert!(is_truthy(Some(OsStr::new(" TRUE "))));
+        assert!(is_truthy(Some(OsStr::new("yes"))));
+        assert!(!is_truthy(Some(OsStr::new("0"))));
+        assert!(!is_truthy(Some(OsStr::new(""))));
+        assert!(!is_truthy(None));
+    }
 }
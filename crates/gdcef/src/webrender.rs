@@ -1,18 +1,38 @@
 use cef::{self, rc::Rc, sys::cef_cursor_type_t, *};
 use cef_app::{CursorType, PhysicalSize};
+use software_render::{
+    DestBuffer, copy_paint_rows, is_plausible_paint_size, paint_buffer_len, paint_rows_read_len,
+};
 use std::collections::VecDeque;
 use std::sync::{Arc, Mutex};
 use wide::{i8x16, u8x16};
 
 use crate::accelerated_osr::PlatformAcceleratedRenderHandler;
+use crate::accessibility;
 use crate::browser::{
     AudioPacket, AudioPacketQueue, AudioParamsState, AudioSampleRateState, AudioShutdownFlag,
-    ConsoleMessageEvent, DownloadRequestEvent, DownloadUpdateEvent, DragDataInfo, DragEvent,
-    EventQueues, EventQueuesHandle, ImeCompositionRange, LoadingStateEvent,
+    BrowserShortcutsHandle, CertificateDecisionsHandle, CertificateErrorEvent, CertificateInfo,
+    CharacterBounds, ClientCertificateEvent, ClientCertificateRequestsHandle, ConsoleMessageEvent,
+    CookieImportFailure, CookiesImportedEvent, DevToolsEvent, DevToolsResultEvent,
+    DisplayMediaEvent, DomQueryEvent, DownloadRequestEvent, DownloadUpdateEvent, DragDataInfo,
+    DragEvent, DragFileSource, EventKind, EventQueues, EventQueuesHandle, ExtraHeadersHandle,
+    FirstPaintEvent, GeolocationOverrideHandle, GpuInfoRequestsHandle, HistoryEntry,
+    ImeCompositionRange, LoadingStateChangeEvent, LoadingStateEvent, NavigationAllowlistHandle,
+    NavigationBlockedEvent, PageSavedEvent, PageSavesHandle, RendererProcessEvent, ScrollPosition,
+    SecurityStateEvent, UrlChangeEvent, WebNotificationEvent,
+};
+use crate::cookies::CookieRecord;
+use crate::event_queue::PushOutcome;
+use crate::frame_pacing::{FramePacer, FramePacerHandle};
+use crate::page_snapshot;
+use crate::render_stats::{RenderStats, RenderStatsHandle};
+use crate::security::{
+    auto_select_client_certificate, basetime_to_unix_seconds, certificate_fingerprint,
+    external_protocol_scheme, is_certificate_applicable, is_same_origin,
 };
-use crate::utils::get_display_scale_factor;
 
 /// Bundles all the event queues and audio state used for browser-to-Godot communication.
+#[derive(Clone)]
 pub(crate) struct ClientQueues {
     /// Consolidated event queues (UI-thread callbacks).
     pub event_queues: EventQueuesHandle,
@@ -26,18 +46,66 @@ pub(crate) struct ClientQueues {
     pub audio_shutdown_flag: AudioShutdownFlag,
     /// Whether audio capture is enabled.
     pub enable_audio_capture: bool,
+    /// Certificate errors waiting for a decision from GDScript.
+    pub certificate_decisions: CertificateDecisionsHandle,
+    /// Whether certificate errors are globally ignored (no signal is emitted).
+    pub ignore_certificate_errors: bool,
+    /// Client certificate requests waiting for a selection from GDScript.
+    pub client_certificate_requests: ClientCertificateRequestsHandle,
+    /// Subject pattern of the client certificate selected without asking
+    /// GDScript (empty = always ask).
+    pub client_certificate_auto_select: String,
+    /// Allowlist checked before every main-frame navigation.
+    pub navigation_allowlist: NavigationAllowlistHandle,
+    /// Headers added to outgoing requests.
+    pub extra_headers: ExtraHeadersHandle,
+    /// Geolocation override; the permission is granted while one is active.
+    pub geolocation_override: GeolocationOverrideHandle,
+    /// Whether the notification permission is granted to pages.
+    pub web_notifications: bool,
+    /// Key chords taken from the page and reported as `browser_shortcut`.
+    pub browser_shortcuts: BrowserShortcutsHandle,
+    /// Frame render statistics, updated by the render handler.
+    pub render_stats: RenderStatsHandle,
+    /// Frames handed to Godot, for `frame_pacing`.
+    pub frame_pacer: FramePacerHandle,
+    /// The browser created with this client.
+    pub created_browser: CreatedBrowserHandle,
 }
 
 impl ClientQueues {
-    pub fn new(sample_rate: i32, enable_audio_capture: bool) -> Self {
+    pub fn new(
+        sample_rate: i32,
+        enable_audio_capture: bool,
+        client_certificate_auto_select: String,
+        navigation_allowlist: NavigationAllowlistHandle,
+        extra_headers: ExtraHeadersHandle,
+        geolocation_override: GeolocationOverrideHandle,
+        browser_shortcuts: BrowserShortcutsHandle,
+    ) -> Self {
         use std::sync::atomic::AtomicBool;
         Self {
-            event_queues: Arc::new(Mutex::new(EventQueues::new())),
+            event_queues: Arc::new(Mutex::new(EventQueues::new(
+                &crate::settings::get_queue_limits(),
+            ))),
             audio_packet_queue: Arc::new(Mutex::new(VecDeque::new())),
             audio_params: Arc::new(Mutex::new(None)),
             audio_sample_rate: Arc::new(Mutex::new(sample_rate)),
             audio_shutdown_flag: Arc::new(AtomicBool::new(false)),
             enable_audio_capture,
+            certificate_decisions: Arc::new(Mutex::new(Default::default())),
+            ignore_certificate_errors: crate::settings::get_security_config()
+                .ignore_certificate_errors,
+            client_certificate_requests: Arc::new(Mutex::new(Default::default())),
+            client_certificate_auto_select,
+            navigation_allowlist,
+            extra_headers,
+            geolocation_override,
+            web_notifications: crate::settings::are_web_notifications_allowed(),
+            browser_shortcuts,
+            render_stats: RenderStats::new(),
+            frame_pacer: FramePacer::new(),
+            created_browser: Arc::new(Mutex::new(None)),
         }
     }
 }
@@ -81,23 +149,34 @@ fn bgra_to_rgba(bgra: &[u8]) -> Vec<u8> {
     rgba
 }
 
+/// Reads the scale of the screen the view is on, as last set by the texture.
+fn read_display_scale(display_scale: &Arc<Mutex<f32>>) -> f32 {
+    display_scale.lock().map(|scale| *scale).unwrap_or(1.0)
+}
+
 /// Common helper for view_rect implementation.
-fn compute_view_rect(size: &Arc<Mutex<PhysicalSize<f32>>>, rect: Option<&mut Rect>) {
+fn compute_view_rect(
+    size: &Arc<Mutex<PhysicalSize<f32>>>,
+    display_scale: &Arc<Mutex<f32>>,
+    rect: Option<&mut Rect>,
+) {
+    let scale = read_display_scale(display_scale);
     if let Some(rect) = rect
         && let Ok(size) = size.lock()
         && size.width > 0.0
         && size.height > 0.0
     {
-        let scale = get_display_scale_factor();
-        rect.width = (size.width / scale) as i32;
-        rect.height = (size.height / scale) as i32;
+        (rect.width, rect.height) = crate::render::view_size_for(size.width, size.height, scale);
     }
 }
 
 /// Common helper for screen_info implementation.
-fn compute_screen_info(screen_info: Option<&mut ScreenInfo>) -> ::std::os::raw::c_int {
+fn compute_screen_info(
+    display_scale: &Arc<Mutex<f32>>,
+    screen_info: Option<&mut ScreenInfo>,
+) -> ::std::os::raw::c_int {
     if let Some(screen_info) = screen_info {
-        screen_info.device_scale_factor = get_display_scale_factor();
+        screen_info.device_scale_factor = read_display_scale(display_scale);
         return true as _;
     }
     false as _
@@ -154,24 +233,55 @@ fn handle_start_dragging(
 ) -> ::std::os::raw::c_int {
     if let Some(drag_data) = drag_data {
         let drag_info = extract_drag_data_info(drag_data);
+        // A copy, since the drag data belongs to this call
+        let file_source = if drag_info.is_file {
+            ImplDragData::clone(drag_data).map(DragFileSource)
+        } else {
+            None
+        };
         if let Ok(mut queues) = event_queues.lock() {
-            queues.drag_events.push_back(DragEvent::Started {
+            let outcome = queues.drag_events.push_back(DragEvent::Started {
                 drag_data: drag_info,
                 x,
                 y,
                 allowed_ops: drag_ops_to_u32(allowed_ops),
+                file_source,
             });
+            queues.record(EventKind::Drag, outcome);
         }
     }
     1
 }
 
+/// Common helper for on_ime_composition_range_changed implementation.
+fn handle_ime_composition_range_changed(
+    character_bounds: Option<&[Rect]>,
+    event_queues: &EventQueuesHandle,
+) {
+    let bounds = character_bounds
+        .unwrap_or_default()
+        .iter()
+        .map(|rect| CharacterBounds {
+            x: rect.x,
+            y: rect.y,
+            width: rect.width,
+            height: rect.height,
+        })
+        .collect();
+    if let Some(range) = ImeCompositionRange::from_character_bounds(bounds)
+        && let Ok(mut queues) = event_queues.lock()
+    {
+        queues.ime_composition_range = Some(range);
+    }
+}
+
 /// Common helper for update_drag_cursor implementation.
 fn handle_update_drag_cursor(operation: DragOperationsMask, event_queues: &EventQueuesHandle) {
     if let Ok(mut queues) = event_queues.lock() {
-        queues.drag_events.push_back(DragEvent::UpdateCursor {
+        let outcome = queues.drag_events.push_back(DragEvent::UpdateCursor {
             operation: drag_ops_to_u32(operation),
         });
+        queues.record(EventKind::Drag, outcome);
     }
 }
 
@@ -179,11 +289,19 @@ wrap_render_handler! {
     pub struct SoftwareOsrHandler {
         handler: cef_app::OsrRenderHandler,
         event_queues: EventQueuesHandle,
+        render_stats: RenderStatsHandle,
+        frame_pacer: FramePacerHandle,
+        rejected_paints: Arc<std::sync::atomic::AtomicU32>,
+        accessibility_handler: cef::AccessibilityHandler,
     }
 
     impl RenderHandler {
+        fn accessibility_handler(&self) -> Option<cef::AccessibilityHandler> {
+            Some(self.accessibility_handler.clone())
+        }
+
         fn view_rect(&self, _browser: Option<&mut Browser>, rect: Option<&mut Rect>) {
-            compute_view_rect(&self.handler.size, rect);
+            compute_view_rect(&self.handler.size, &self.handler.display_scale, rect);
         }
 
         fn screen_info(
@@ -191,7 +309,7 @@ wrap_render_handler! {
             _browser: Option<&mut Browser>,
             screen_info: Option<&mut ScreenInfo>,
         ) -> ::std::os::raw::c_int {
-            compute_screen_info(screen_info)
+            compute_screen_info(&self.handler.display_scale, screen_info)
         }
 
         fn screen_point(
@@ -223,7 +341,7 @@ wrap_render_handler! {
 
         fn on_paint(
             &self,
-            _browser: Option<&mut Browser>,
+            browser: Option<&mut Browser>,
             type_: PaintElementType,
             _dirty_rects: Option<&[Rect]>,
             buffer: *const u8,
@@ -236,13 +354,73 @@ wrap_render_handler! {
 
             let width = width as u32;
             let height = height as u32;
-            let buffer_size = (width * height * 4) as usize;
-            let bgra_data = unsafe { std::slice::from_raw_parts(buffer, buffer_size) };
-            let rgba_data = bgra_to_rgba(bgra_data);
+            // Size of the frame the paint is copied into
+            let mut frame_size = (width, height);
 
+            // A paint for a size the view no longer has (rapid resizes) is
+            // dropped and a fresh one requested, unless CEF keeps insisting.
+            // One close to the view's size is clamped to it.
             if type_ == PaintElementType::VIEW {
+                use std::sync::atomic::Ordering;
+                let (expected_width, expected_height) = self.expected_view_size();
+                let plausible =
+                    is_plausible_paint_size(width, height, expected_width, expected_height);
+                if !plausible
+                    && self.rejected_paints.fetch_add(1, Ordering::Relaxed) < MAX_REJECTED_PAINTS
+                {
+                    if let Some(host) = browser.and_then(|browser| browser.host()) {
+                        host.invalidate(PaintElementType::VIEW);
+                    }
+                    return;
+                }
+                self.rejected_paints.store(0, Ordering::Relaxed);
+                if plausible && expected_width > 0 && expected_height > 0 {
+                    frame_size = (expected_width, expected_height);
+                }
+
+                if self.frame_pacer.should_skip_paint() {
+                    self.render_stats.record_paint();
+                    self.render_stats.record_skipped_frame();
+                    return;
+                }
+            }
+
+            // Only the rows and columns the frame keeps are read
+            let (frame_width, frame_height) = frame_size;
+            let (Some(read_len), Some(frame_len)) = (
+                paint_rows_read_len(width, height, frame_width, frame_height),
+                paint_buffer_len(frame_width, frame_height),
+            ) else {
+                return;
+            };
+            let bgra_data = unsafe { std::slice::from_raw_parts(buffer, read_len) };
+            let convert_start = std::time::Instant::now();
+            let rgba_data = if frame_size == (width, height) {
+                bgra_to_rgba(bgra_data)
+            } else {
+                let mut frame = vec![0u8; frame_len];
+                copy_paint_rows(
+                    bgra_data,
+                    width,
+                    height,
+                    &mut DestBuffer {
+                        data: &mut frame,
+                        width: frame_width,
+                        height: frame_height,
+                    },
+                );
+                bgra_to_rgba(&frame)
+            };
+
+            if type_ == PaintElementType::VIEW {
+                self.render_stats.record_paint();
+                self.render_stats.record_convert(convert_start.elapsed());
                 if let Ok(mut frame_buffer) = self.handler.frame_buffer.lock() {
-                    frame_buffer.update(rgba_data, width, height);
+                    if frame_buffer.dirty {
+                        self.render_stats.record_dropped_frame();
+                    }
+                    frame_buffer.update(rgba_data, frame_width, frame_height);
+                    self.frame_pacer.frame_queued();
                 }
             } else if type_ == PaintElementType::POPUP
                 && let Ok(mut popup_state) = self.handler.popup_state.lock() {
@@ -256,14 +434,7 @@ wrap_render_handler! {
             _selected_range: Option<&Range>,
             character_bounds: Option<&[Rect]>,
         ) {
-            if let Some(bounds) = character_bounds.and_then(|b| b.last())
-                && let Ok(mut queues) = self.event_queues.lock() {
-                    queues.ime_composition_range = Some(ImeCompositionRange {
-                        caret_x: bounds.x,
-                        caret_y: bounds.y,
-                        caret_height: bounds.height,
-                    });
-                }
+            handle_ime_composition_range_changed(character_bounds, &self.event_queues);
         }
 
         fn start_dragging(
@@ -291,20 +462,51 @@ impl SoftwareOsrHandler {
     pub fn build(
         handler: cef_app::OsrRenderHandler,
         event_queues: EventQueuesHandle,
+        render_stats: RenderStatsHandle,
+        frame_pacer: FramePacerHandle,
     ) -> cef::RenderHandler {
-        Self::new(handler, event_queues)
+        let accessibility_handler = AccessibilityHandlerImpl::build(event_queues.clone());
+        Self::new(
+            handler,
+            event_queues,
+            render_stats,
+            frame_pacer,
+            Arc::new(std::sync::atomic::AtomicU32::new(0)),
+            accessibility_handler,
+        )
+    }
+
+    /// Pixel size CEF should paint the view at, based on the view rect it
+    /// was last given.
+    fn expected_view_size(&self) -> (u32, u32) {
+        let scale = read_display_scale(&self.handler.display_scale);
+        let Ok(size) = self.handler.size.lock() else {
+            return (0, 0);
+        };
+        let (view_width, view_height) =
+            crate::render::view_size_for(size.width, size.height, scale);
+        crate::render::paint_size_for(view_width, view_height, scale)
     }
 }
 
+/// Consecutive implausible paints dropped before one is accepted anyway, so
+/// a size we cannot predict never leaves the view blank.
+const MAX_REJECTED_PAINTS: u32 = 3;
+
 wrap_render_handler! {
     pub struct AcceleratedOsrHandler {
         handler: PlatformAcceleratedRenderHandler,
         event_queues: EventQueuesHandle,
+        accessibility_handler: cef::AccessibilityHandler,
     }
 
     impl RenderHandler {
+        fn accessibility_handler(&self) -> Option<cef::AccessibilityHandler> {
+            Some(self.accessibility_handler.clone())
+        }
+
         fn view_rect(&self, _browser: Option<&mut Browser>, rect: Option<&mut Rect>) {
-            compute_view_rect(&self.handler.size, rect);
+            compute_view_rect(&self.handler.size, &self.handler.display_scale, rect);
         }
 
         fn screen_info(
@@ -312,7 +514,7 @@ wrap_render_handler! {
             _browser: Option<&mut Browser>,
             screen_info: Option<&mut ScreenInfo>,
         ) -> ::std::os::raw::c_int {
-            compute_screen_info(screen_info)
+            compute_screen_info(&self.handler.display_scale, screen_info)
         }
 
         fn screen_point(
@@ -384,14 +586,7 @@ wrap_render_handler! {
             _selected_range: Option<&Range>,
             character_bounds: Option<&[Rect]>,
         ) {
-            if let Some(bounds) = character_bounds.and_then(|b| b.last())
-                && let Ok(mut queues) = self.event_queues.lock() {
-                    queues.ime_composition_range = Some(ImeCompositionRange {
-                        caret_x: bounds.x,
-                        caret_y: bounds.y,
-                        caret_height: bounds.height,
-                    });
-                }
+            handle_ime_composition_range_changed(character_bounds, &self.event_queues);
         }
 
         fn start_dragging(
@@ -420,7 +615,38 @@ impl AcceleratedOsrHandler {
         handler: PlatformAcceleratedRenderHandler,
         event_queues: EventQueuesHandle,
     ) -> cef::RenderHandler {
-        Self::new(handler, event_queues)
+        let accessibility_handler = AccessibilityHandlerImpl::build(event_queues.clone());
+        Self::new(handler, event_queues, accessibility_handler)
+    }
+}
+
+wrap_accessibility_handler! {
+    pub(crate) struct AccessibilityHandlerImpl {
+        event_queues: EventQueuesHandle,
+    }
+
+    impl AccessibilityHandler {
+        fn on_accessibility_tree_change(&self, value: Option<&mut Value>) {
+            let Some(value) = value else { return };
+            let events = accessibility::parse_tree_change(value);
+            if let Ok(mut queues) = self.event_queues.lock() {
+                queues.accessibility_events.extend(events);
+            }
+        }
+
+        fn on_accessibility_location_change(&self, value: Option<&mut Value>) {
+            let Some(value) = value else { return };
+            let events = accessibility::parse_location_change(value);
+            if let Ok(mut queues) = self.event_queues.lock() {
+                queues.accessibility_events.extend(events);
+            }
+        }
+    }
+}
+
+impl AccessibilityHandlerImpl {
+    pub fn build(event_queues: EventQueuesHandle) -> cef::AccessibilityHandler {
+        Self::new(event_queues)
     }
 }
 
@@ -461,6 +687,25 @@ macro_rules! handle_cursor_change {
     }};
 }
 
+/// Returns the names of all files of a drag, falling back to the single
+/// file name CEF reports for content dragged out of a page.
+fn drag_file_names(drag_data: &impl ImplDragData) -> Vec<String> {
+    let mut names = CefStringList::new();
+    if drag_data.file_names(Some(&mut names)) != 0 {
+        let names: Vec<String> = names.into_iter().filter(|name| !name.is_empty()).collect();
+        if !names.is_empty() {
+            return names;
+        }
+    }
+
+    let name = CefStringUtf16::from(&drag_data.file_name()).to_string();
+    if name.is_empty() {
+        Vec::new()
+    } else {
+        vec![name]
+    }
+}
+
 fn extract_drag_data_info(drag_data: &impl ImplDragData) -> DragDataInfo {
     let is_link = drag_data.is_link() != 0;
     let is_file = drag_data.is_file() != 0;
@@ -495,13 +740,7 @@ fn extract_drag_data_info(drag_data: &impl ImplDragData) -> DragDataInfo {
     };
 
     let file_names = if is_file {
-        let name = drag_data.file_name();
-        let name_str = CefStringUtf16::from(&name).to_string();
-        if name_str.is_empty() {
-            Vec::new()
-        } else {
-            vec![name_str]
-        }
+        drag_file_names(drag_data)
     } else {
         Vec::new()
     };
@@ -538,10 +777,11 @@ wrap_drag_handler! {
                     #[cfg(not(target_os = "windows"))]
                     let mask: u32 = mask.as_ref().0;
 
-                    queues.drag_events.push_back(DragEvent::Entered {
+                    let outcome = queues.drag_events.push_back(DragEvent::Entered {
                         drag_data: drag_info,
                         mask,
                     });
+                    queues.record(EventKind::Drag, outcome);
                 }
             }
             0
@@ -555,6 +795,69 @@ impl DragHandlerImpl {
     }
 }
 
+/// Helper to convert CertStatus to u32 in a cross-platform way.
+fn cert_status_to_u32(status: CertStatus) -> u32 {
+    *status.as_ref() as u32
+}
+
+/// Extracts the certificate details reported to GDScript.
+/// Must be called on the CEF UI thread.
+fn extract_certificate_info(cert: &X509Certificate) -> CertificateInfo {
+    let principal_name = |principal: Option<X509CertPrincipal>| {
+        principal
+            .map(|p| CefStringUtf16::from(&p.display_name()).to_string())
+            .unwrap_or_default()
+    };
+
+    let fingerprint_sha256 = cert
+        .derencoded()
+        .and_then(|der| {
+            let mut buffer = vec![0u8; der.size()];
+            let copied = der.data(Some(&mut buffer), 0);
+            buffer.truncate(copied);
+            (!buffer.is_empty()).then(|| certificate_fingerprint(&buffer))
+        })
+        .unwrap_or_default();
+
+    CertificateInfo {
+        subject: principal_name(cert.subject()),
+        issuer: principal_name(cert.issuer()),
+        valid_from: basetime_to_unix_seconds(cert.valid_start().val),
+        valid_until: basetime_to_unix_seconds(cert.valid_expiry().val),
+        fingerprint_sha256,
+    }
+}
+
+/// Reads the SSL status of the browser's current navigation entry.
+/// Must be called on the CEF UI thread.
+fn security_state_for(browser: &mut Browser, url: &str) -> SecurityStateEvent {
+    let mut state = SecurityStateEvent {
+        url: url.to_string(),
+        applicable: is_certificate_applicable(url),
+        is_secure_connection: false,
+        cert_status: 0,
+        certificate: None,
+    };
+    if !state.applicable {
+        return state;
+    }
+
+    let Some(ssl_status) = browser
+        .host()
+        .and_then(|host| host.visible_navigation_entry())
+        .and_then(|entry| entry.sslstatus())
+    else {
+        return state;
+    };
+
+    state.is_secure_connection = ssl_status.is_secure_connection() != 0;
+    state.cert_status = cert_status_to_u32(ssl_status.cert_status());
+    state.certificate = ssl_status
+        .x509_certificate()
+        .map(|cert| extract_certificate_info(&cert));
+    state
+}
+
 wrap_display_handler! {
     pub(crate) struct DisplayHandlerImpl {
         cursor_type: Arc<Mutex<CursorType>>,
@@ -597,14 +900,33 @@ wrap_display_handler! {
 
         fn on_address_change(
             &self,
-            _browser: Option<&mut Browser>,
-            _frame: Option<&mut Frame>,
+            browser: Option<&mut Browser>,
+            frame: Option<&mut Frame>,
             url: Option<&CefString>,
         ) {
             if let Some(url) = url {
                 let url_str = url.to_string();
+                let is_main = frame.as_ref().is_some_and(|frame| frame.is_main() != 0);
+                // The navigation has committed by now, so the visible entry
+                // carries the SSL status of the new main-frame document. A
+                // fragment or pushState change while the page is idle keeps
+                // the document and its status.
+                let security_state = match browser {
+                    Some(browser) if is_main && browser.is_loading() != 0 => {
+                        Some(security_state_for(browser, &url_str))
+                    }
+                    _ => None,
+                };
                 if let Ok(mut queues) = self.event_queues.lock() {
-                    queues.url_changes.push_back(url_str);
+                    let outcome = queues.url_changes.push_back(UrlChangeEvent {
+                        url: url_str,
+                        is_main,
+                    });
+                    queues.record(EventKind::UrlChange, outcome);
+                    if let Some(state) = security_state {
+                        let outcome = queues.security_states.push_back(state);
+                        queues.record(EventKind::SecurityState, outcome);
+                    }
                 }
             }
         }
@@ -617,11 +939,18 @@ wrap_display_handler! {
             if let Some(title) = title {
                 let title_str = title.to_string();
                 if let Ok(mut queues) = self.event_queues.lock() {
-                    queues.title_changes.push_back(title_str);
+                    let outcome = queues.title_changes.push_back(title_str);
+                    queues.record(EventKind::TitleChange, outcome);
                 }
             }
         }
 
+        fn on_loading_progress_change(&self, _browser: Option<&mut Browser>, progress: f64) {
+            if let Ok(mut queues) = self.event_queues.lock() {
+                queues.loading_progress = Some(progress);
+            }
+        }
+
         fn on_console_message(
             &self,
             _browser: Option<&mut Browser>,
@@ -638,12 +967,13 @@ wrap_display_handler! {
             let level: u32 = level.get_raw();
 
             if let Ok(mut queues) = self.event_queues.lock() {
-                queues.console_messages.push_back(ConsoleMessageEvent {
+                let outcome = queues.console_messages.push_back(ConsoleMessageEvent {
                     level,
                     message: message_str,
                     source: source_str,
                     line,
                 });
+                queues.record(EventKind::ConsoleMessage, outcome);
             }
 
             // Return false to allow default console output
@@ -685,10 +1015,26 @@ impl ContextMenuHandlerImpl {
     }
 }
 
+/// The browser a client was created for, recorded by `on_after_created`.
+/// `show_dev_tools` does not return the DevTools browser it creates, so
+/// `attach_dev_tools` takes it from here.
+pub(crate) type CreatedBrowserHandle = Arc<Mutex<Option<Browser>>>;
+
 wrap_life_span_handler! {
-    pub(crate) struct LifeSpanHandlerImpl {}
+    pub(crate) struct LifeSpanHandlerImpl {
+        created_browser: CreatedBrowserHandle,
+    }
 
     impl LifeSpanHandler {
+        fn on_after_created(&self, browser: Option<&mut Browser>) {
+            if let Some(browser) = browser
+                && let Ok(mut created_browser) = self.created_browser.lock()
+                && created_browser.is_none()
+            {
+                *created_browser = Some(browser.clone());
+            }
+        }
+
         // Disable popup for now
         fn on_before_popup(
             &self,
@@ -712,29 +1058,135 @@ wrap_life_span_handler! {
 }
 
 impl LifeSpanHandlerImpl {
-    pub fn build() -> cef::LifeSpanHandler {
-        Self::new()
+    pub fn build(created_browser: CreatedBrowserHandle) -> cef::LifeSpanHandler {
+        Self::new(created_browser)
     }
 }
 
+wrap_keyboard_handler! {
+    pub(crate) struct KeyboardHandlerImpl {
+        shortcuts: BrowserShortcutsHandle,
+        event_queues: EventQueuesHandle,
+    }
+
+    impl KeyboardHandler {
+        #[cfg(target_os = "windows")]
+        fn on_pre_key_event(
+            &self,
+            _browser: Option<&mut Browser>,
+            event: Option<&KeyEvent>,
+            _os_event: Option<&mut cef::sys::MSG>,
+            _is_keyboard_shortcut: Option<&mut ::std::os::raw::c_int>,
+        ) -> ::std::os::raw::c_int {
+            self.take_shortcut(event)
+        }
+
+        #[cfg(target_os = "macos")]
+        fn on_pre_key_event(
+            &self,
+            _browser: Option<&mut Browser>,
+            event: Option<&KeyEvent>,
+            _os_event: *mut u8,
+            _is_keyboard_shortcut: Option<&mut ::std::os::raw::c_int>,
+        ) -> ::std::os::raw::c_int {
+            self.take_shortcut(event)
+        }
+
+        #[cfg(target_os = "linux")]
+        fn on_pre_key_event(
+            &self,
+            _browser: Option<&mut Browser>,
+            event: Option<&KeyEvent>,
+            _os_event: Option<&mut cef::sys::XEvent>,
+            _is_keyboard_shortcut: Option<&mut ::std::os::raw::c_int>,
+        ) -> ::std::os::raw::c_int {
+            self.take_shortcut(event)
+        }
+    }
+}
+
+impl KeyboardHandlerImpl {
+    pub fn build(
+        shortcuts: BrowserShortcutsHandle,
+        event_queues: EventQueuesHandle,
+    ) -> cef::KeyboardHandler {
+        Self::new(shortcuts, event_queues)
+    }
+
+    /// Handles the key events of a registered chord so the page never sees
+    /// them, and queues its action when the key goes down. Chromium drops
+    /// the CHAR event of a handled key down by itself.
+    fn take_shortcut(&self, event: Option<&KeyEvent>) -> ::std::os::raw::c_int {
+        let Some(event) = event else {
+            return false as _;
+        };
+        // CHAR events carry the character instead of the key code
+        if event.type_ == KeyEventType::CHAR {
+            return false as _;
+        }
+        let Ok(shortcuts) = self.shortcuts.read() else {
+            return false as _;
+        };
+        let Some(action) = shortcuts.action(event.windows_key_code, event.modifiers) else {
+            return false as _;
+        };
+
+        // Auto-repeats and the release are only swallowed
+        if event.type_ == KeyEventType::RAWKEYDOWN
+            && let Ok(mut queues) = self.event_queues.lock()
+        {
+            let outcome = queues.browser_shortcuts.push_back(action.to_string());
+            queues.record(EventKind::BrowserShortcut, outcome);
+        }
+        true as _
+    }
+}
+
+/// Returns the name and whether it is the main frame, for load events.
+fn frame_metadata(frame: &Frame) -> (String, bool) {
+    let name = CefStringUtf16::from(&frame.name()).to_string();
+    (name, frame.is_main() != 0)
+}
+
 wrap_load_handler! {
     pub(crate) struct LoadHandlerImpl {
         event_queues: EventQueuesHandle,
     }
 
     impl LoadHandler {
+        fn on_loading_state_change(
+            &self,
+            _browser: Option<&mut Browser>,
+            is_loading: ::std::os::raw::c_int,
+            can_go_back: ::std::os::raw::c_int,
+            can_go_forward: ::std::os::raw::c_int,
+        ) {
+            if let Ok(mut queues) = self.event_queues.lock() {
+                let outcome = queues.loading_state_changes.push_back(LoadingStateChangeEvent {
+                    is_loading: is_loading != 0,
+                    can_go_back: can_go_back != 0,
+                    can_go_forward: can_go_forward != 0,
+                });
+                queues.record(EventKind::LoadingStateChange, outcome);
+            }
+        }
+
         fn on_load_start(
             &self,
             _browser: Option<&mut Browser>,
             frame: Option<&mut Frame>,
             _transition_type: TransitionType,
         ) {
-            if let Some(frame) = frame
-                && frame.is_main() != 0
-            {
+            if let Some(frame) = frame {
                 let url = CefStringUtf16::from(&frame.url()).to_string();
+                let (frame_name, is_main) = frame_metadata(frame);
                 if let Ok(mut queues) = self.event_queues.lock() {
-                    queues.loading_states.push_back(LoadingStateEvent::Started { url });
+                    let outcome = queues.loading_states.push_back(LoadingStateEvent::Started {
+                        url,
+                        frame_name,
+                        is_main,
+                    });
+                    queues.record(EventKind::LoadingState, outcome);
                 }
             }
         }
@@ -745,15 +1197,17 @@ wrap_load_handler! {
             frame: Option<&mut Frame>,
             http_status_code: ::std::os::raw::c_int,
         ) {
-            if let Some(frame) = frame
-                && frame.is_main() != 0
-            {
+            if let Some(frame) = frame {
                 let url = CefStringUtf16::from(&frame.url()).to_string();
+                let (frame_name, is_main) = frame_metadata(frame);
                 if let Ok(mut queues) = self.event_queues.lock() {
-                    queues.loading_states.push_back(LoadingStateEvent::Finished {
+                    let outcome = queues.loading_states.push_back(LoadingStateEvent::Finished {
                         url,
                         http_status_code,
+                        frame_name,
+                        is_main,
                     });
+                    queues.record(EventKind::LoadingState, outcome);
                 }
             }
         }
@@ -766,9 +1220,7 @@ wrap_load_handler! {
             error_string: Option<&CefString>,
             failed_url: Option<&CefString>,
         ) {
-            if let Some(frame) = frame
-                && frame.is_main() != 0
-            {
+            if let Some(frame) = frame {
                 let url = failed_url
                     .map(|u| u.to_string())
                     .unwrap_or_default();
@@ -777,12 +1229,16 @@ wrap_load_handler! {
                     .unwrap_or_default();
                 // Use the get_raw() method to safely convert Errorcode to i32
                 let error_code_i32: i32 = error_code.get_raw();
+                let (frame_name, is_main) = frame_metadata(frame);
                 if let Ok(mut queues) = self.event_queues.lock() {
-                    queues.loading_states.push_back(LoadingStateEvent::Error {
+                    let outcome = queues.loading_states.push_back(LoadingStateEvent::Error {
                         url,
                         error_code: error_code_i32,
                         error_text,
+                        frame_name,
+                        is_main,
                     });
+                    queues.record(EventKind::LoadingState, outcome);
                 }
             }
         }
@@ -974,6 +1430,7 @@ wrap_download_handler! {
                         mime_type,
                         total_bytes,
                     });
+                    queues.record(EventKind::DownloadRequest, PushOutcome::Queued);
                 }
 
                 if let Some(callback) = callback {
@@ -1003,7 +1460,7 @@ wrap_download_handler! {
                 let is_canceled = item.is_canceled() != 0;
 
                 if let Ok(mut queues) = self.event_queues.lock() {
-                    queues.download_updates.push_back(DownloadUpdateEvent {
+                    let outcome = queues.download_updates.push_back(DownloadUpdateEvent {
                         id,
                         url,
                         full_path,
@@ -1015,6 +1472,7 @@ wrap_download_handler! {
                         is_complete,
                         is_canceled,
                     });
+                    queues.record(EventKind::DownloadUpdate, outcome);
                 }
             }
         }
@@ -1027,7 +1485,755 @@ impl DownloadHandlerImpl {
     }
 }
 
-fn on_process_message_received(message: Option<&mut ProcessMessage>, ipc: &ClientIpcQueues) -> i32 {
+/// Returns whether a permission prompt asks for geolocation and nothing else.
+fn is_geolocation_only(requested_permissions: u32) -> bool {
+    let geolocation = sys::cef_permission_request_types_t::CEF_PERMISSION_TYPE_GEOLOCATION as u32;
+    requested_permissions == geolocation
+}
+
+/// Returns whether a permission prompt asks for notifications and nothing else.
+fn is_notifications_only(requested_permissions: u32) -> bool {
+    let notifications =
+        sys::cef_permission_request_types_t::CEF_PERMISSION_TYPE_NOTIFICATIONS as u32;
+    requested_permissions == notifications
+}
+
+fn is_desktop_capture(requested_permissions: u32) -> bool {
+    let desktop =
+        sys::cef_media_access_permission_types_t::CEF_MEDIA_PERMISSION_DESKTOP_AUDIO_CAPTURE as u32
+            | sys::cef_media_access_permission_types_t::CEF_MEDIA_PERMISSION_DESKTOP_VIDEO_CAPTURE
+                as u32;
+    requested_permissions & desktop != 0
+}
+
+wrap_permission_handler! {
+    pub(crate) struct PermissionHandlerImpl {
+        geolocation_override: GeolocationOverrideHandle,
+        // Grant notifications, which the renderer helper forwards to Godot.
+        web_notifications: bool,
+    }
+
+    impl PermissionHandler {
+        fn on_show_permission_prompt(
+            &self,
+            browser: Option<&mut Browser>,
+            _prompt_id: u64,
+            requesting_origin: Option<&CefString>,
+            requested_permissions: u32,
+            callback: Option<&mut PermissionPromptCallback>,
+        ) -> ::std::os::raw::c_int {
+            let override_active = self
+                .geolocation_override
+                .read()
+                .is_ok_and(|geolocation| geolocation.is_some());
+            // The override is meant for the page the game shows, not for
+            // iframes it embeds from other origins
+            let main_frame_origin = || {
+                let main_frame_url = browser
+                    .and_then(|browser| browser.main_frame())
+                    .map(|frame| CefStringUtf16::from(&frame.url()).to_string());
+                let requesting_origin = requesting_origin.map(|origin| origin.to_string());
+                main_frame_url
+                    .zip(requesting_origin)
+                    .is_some_and(|(url, origin)| is_same_origin(&url, &origin))
+            };
+            let granted = (override_active
+                && is_geolocation_only(requested_permissions)
+                && main_frame_origin())
+                || (self.web_notifications && is_notifications_only(requested_permissions));
+            if !granted {
+                // Default handling, which denies the prompt in windowless mode
+                return false as _;
+            }
+
+            // The page only ever sees the overridden position, and its
+            // notifications only reach the game
+            if let Some(callback) = callback {
+                callback.cont(PermissionRequestResult::from(
+                    sys::cef_permission_request_result_t::CEF_PERMISSION_RESULT_ACCEPT,
+                ));
+            }
+            true as _
+        }
+
+        fn on_request_media_access_permission(
+            &self,
+            _browser: Option<&mut Browser>,
+            _frame: Option<&mut Frame>,
+            _requesting_origin: Option<&CefString>,
+            requested_permissions: u32,
+            callback: Option<&mut MediaAccessCallback>,
+        ) -> ::std::os::raw::c_int {
+            if !is_desktop_capture(requested_permissions) {
+                return false as _;
+            }
+
+            // getDisplayMedia is answered with offer_display_media through the
+            // renderer helper; pages must never capture the real desktop
+            if let Some(callback) = callback {
+                callback.cancel();
+            }
+            true as _
+        }
+    }
+}
+
+impl PermissionHandlerImpl {
+    pub fn build(
+        geolocation_override: GeolocationOverrideHandle,
+        web_notifications: bool,
+    ) -> cef::PermissionHandler {
+        Self::new(geolocation_override, web_notifications)
+    }
+}
+
+wrap_dev_tools_message_observer! {
+    pub(crate) struct DevToolsMessageObserverImpl {
+        event_queues: EventQueuesHandle,
+        page_saves: PageSavesHandle,
+        gpu_info_requests: GpuInfoRequestsHandle,
+        // Whether other results and the events reach GDScript, see
+        // `allow_devtools_protocol`.
+        forward_protocol: bool,
+    }
+
+    impl DevToolsMessageObserver {
+        fn on_dev_tools_method_result(
+            &self,
+            _browser: Option<&mut Browser>,
+            message_id: ::std::os::raw::c_int,
+            success: ::std::os::raw::c_int,
+            result: Option<&[u8]>,
+        ) {
+            // Snapshots go to their file without becoming a string
+            let page_save = self
+                .page_saves
+                .lock()
+                .ok()
+                .and_then(|mut page_saves| page_saves.remove(&message_id));
+            if let Some((path, file)) = page_save {
+                let saved = if success != 0 {
+                    page_snapshot::save_snapshot(result.unwrap_or_default(), &file)
+                } else {
+                    Err(std::io::Error::other(
+                        String::from_utf8_lossy(result.unwrap_or_default()).into_owned(),
+                    ))
+                };
+                queue_page_saved(&self.event_queues, path, saved);
+                return;
+            }
+
+            let gpu_info = self
+                .gpu_info_requests
+                .lock()
+                .is_ok_and(|mut requests| requests.remove(&message_id));
+            if !gpu_info && !self.forward_protocol {
+                return;
+            }
+            let result = String::from_utf8_lossy(result.unwrap_or_default()).into_owned();
+            let event = DevToolsResultEvent {
+                message_id,
+                success: success != 0,
+                result,
+            };
+            if let Ok(mut queues) = self.event_queues.lock() {
+                if gpu_info {
+                    let outcome = queues.browser_gpu_info.push_back(event);
+                    queues.record(EventKind::BrowserGpuInfo, outcome);
+                } else {
+                    let outcome = queues.devtools_results.push_back(event);
+                    queues.record(EventKind::DevToolsResult, outcome);
+                }
+            }
+        }
+
+        fn on_dev_tools_event(
+            &self,
+            _browser: Option<&mut Browser>,
+            method: Option<&CefString>,
+            params: Option<&[u8]>,
+        ) {
+            if !self.forward_protocol {
+                return;
+            }
+            let method = method.map(|m| m.to_string()).unwrap_or_default();
+            let params = String::from_utf8_lossy(params.unwrap_or_default()).into_owned();
+            if let Some(message) = isolated_ipc_message(&method, &params) {
+                if let Ok(mut queues) = self.event_queues.lock() {
+                    let outcome = queues.messages.push_back(message);
+                    queues.record(EventKind::Message, outcome);
+                }
+                return;
+            }
+            if let Ok(mut queues) = self.event_queues.lock() {
+                let outcome = queues
+                    .devtools_events
+                    .push_back(DevToolsEvent { method, params });
+                queues.record(EventKind::DevToolsEvent, outcome);
+            }
+        }
+    }
+}
+
+/// Name of the protocol binding that gives user scripts in the isolated
+/// world the same `sendIpcMessage` as the page.
+pub(crate) const ISOLATED_IPC_BINDING: &str = "sendIpcMessage";
+
+/// Returns the message of a `sendIpcMessage` call from the isolated world,
+/// which arrives as a `Runtime.bindingCalled` event.
+fn isolated_ipc_message(method: &str, params: &str) -> Option<String> {
+    if method != "Runtime.bindingCalled" {
+        return None;
+    }
+    let params = cef::parse_json(Some(&CefString::from(params)), JsonParserOptions::default())
+        .and_then(|value| value.dictionary())?;
+    let name = CefStringUtf16::from(&params.string(Some(&CefString::from("name")))).to_string();
+    if name != ISOLATED_IPC_BINDING {
+        return None;
+    }
+    Some(CefStringUtf16::from(&params.string(Some(&CefString::from("payload")))).to_string())
+}
+
+impl DevToolsMessageObserverImpl {
+    pub fn build(
+        event_queues: EventQueuesHandle,
+        page_saves: PageSavesHandle,
+        gpu_info_requests: GpuInfoRequestsHandle,
+        forward_protocol: bool,
+    ) -> cef::DevToolsMessageObserver {
+        Self::new(
+            event_queues,
+            page_saves,
+            gpu_info_requests,
+            forward_protocol,
+        )
+    }
+}
+
+wrap_string_visitor! {
+    pub(crate) struct PageSourceVisitorImpl {
+        event_queues: EventQueuesHandle,
+        path: String,
+        file: std::path::PathBuf,
+    }
+
+    impl CefStringVisitor {
+        fn visit(&self, string: Option<&CefString>) {
+            let source = string.map(|s| s.to_string()).unwrap_or_default();
+            let saved = page_snapshot::save_source(&source, &self.file);
+            queue_page_saved(&self.event_queues, self.path.clone(), saved);
+        }
+    }
+}
+
+impl PageSourceVisitorImpl {
+    pub fn build(
+        event_queues: EventQueuesHandle,
+        path: String,
+        file: std::path::PathBuf,
+    ) -> cef::CefStringVisitor {
+        Self::new(event_queues, path, file)
+    }
+}
+
+/// Reports the outcome of a `save_page` call.
+fn queue_page_saved(event_queues: &EventQueuesHandle, path: String, saved: std::io::Result<()>) {
+    if let Err(e) = &saved {
+        godot::global::godot_warn!("[CefTexture] Failed to save the page to {}: {}", path, e);
+    }
+    if let Ok(mut queues) = event_queues.lock() {
+        let outcome = queues.pages_saved.push_back(PageSavedEvent {
+            path,
+            ok: saved.is_ok(),
+        });
+        queues.record(EventKind::PageSaved, outcome);
+    }
+}
+
+wrap_navigation_entry_visitor! {
+    pub(crate) struct NavigationEntryVisitorImpl {
+        history: Arc<Mutex<NavigationHistory>>,
+    }
+
+    impl NavigationEntryVisitor {
+        fn visit(
+            &self,
+            entry: Option<&mut NavigationEntry>,
+            current: ::std::os::raw::c_int,
+            index: ::std::os::raw::c_int,
+            _total: ::std::os::raw::c_int,
+        ) -> ::std::os::raw::c_int {
+            let Ok(mut history) = self.history.lock() else {
+                return false as _;
+            };
+            if let Some(entry) = entry {
+                history.entries.push(HistoryEntry {
+                    url: CefStringUtf16::from(&entry.url()).to_string(),
+                    title: CefStringUtf16::from(&entry.title()).to_string(),
+                });
+                if current != 0 {
+                    history.current_index = index;
+                }
+            }
+            true as _
+        }
+    }
+}
+
+/// The navigation history collected by a `NavigationEntryVisitorImpl`.
+#[derive(Default)]
+pub(crate) struct NavigationHistory {
+    pub entries: Vec<HistoryEntry>,
+    pub current_index: i32,
+}
+
+impl NavigationEntryVisitorImpl {
+    pub fn build(history: Arc<Mutex<NavigationHistory>>) -> cef::NavigationEntryVisitor {
+        Self::new(history)
+    }
+}
+
+/// The cookies of an `export_cookies` call. They are queued when CEF
+/// releases the visitor: after the last cookie, or right away when there
+/// are none or the cookies cannot be read.
+pub(crate) struct CookieExport {
+    cookies: Mutex<Vec<CookieRecord>>,
+    event_queues: EventQueuesHandle,
+}
+
+impl CookieExport {
+    pub fn new(event_queues: EventQueuesHandle) -> Arc<Self> {
+        Arc::new(Self {
+            cookies: Mutex::new(Vec::new()),
+            event_queues,
+        })
+    }
+}
+
+impl Drop for CookieExport {
+    fn drop(&mut self) {
+        let cookies = std::mem::take(self.cookies.get_mut().unwrap_or_else(|e| e.into_inner()));
+        if let Ok(mut queues) = self.event_queues.lock() {
+            let outcome = queues.cookies_exported.push_back(cookies);
+            queues.record(EventKind::CookiesExported, outcome);
+        }
+    }
+}
+
+wrap_cookie_visitor! {
+    pub(crate) struct CookieVisitorImpl {
+        export: Arc<CookieExport>,
+    }
+
+    impl CookieVisitor {
+        fn visit(
+            &self,
+            cookie: Option<&Cookie>,
+            _count: ::std::os::raw::c_int,
+            _total: ::std::os::raw::c_int,
+            _delete_cookie: Option<&mut ::std::os::raw::c_int>,
+        ) -> ::std::os::raw::c_int {
+            let Ok(mut cookies) = self.export.cookies.lock() else {
+                return false as _;
+            };
+            if let Some(cookie) = cookie {
+                cookies.push(CookieRecord::from_cef(cookie));
+            }
+            true as _
+        }
+    }
+}
+
+impl CookieVisitorImpl {
+    pub fn build(export: Arc<CookieExport>) -> cef::CookieVisitor {
+        Self::new(export)
+    }
+}
+
+/// The outcome of an `import_cookies` call, queued once the callbacks of
+/// every cookie that was set have run and been released.
+pub(crate) struct CookieImport {
+    outcome: Mutex<CookiesImportedEvent>,
+    event_queues: EventQueuesHandle,
+}
+
+impl CookieImport {
+    pub fn new(event_queues: EventQueuesHandle) -> Arc<Self> {
+        Arc::new(Self {
+            outcome: Mutex::new(CookiesImportedEvent::default()),
+            event_queues,
+        })
+    }
+
+    pub fn fail(&self, failure: CookieImportFailure) {
+        if let Ok(mut outcome) = self.outcome.lock() {
+            outcome.failed.push(failure);
+        }
+    }
+
+    fn succeed(&self) {
+        if let Ok(mut outcome) = self.outcome.lock() {
+            outcome.ok_count += 1;
+        }
+    }
+}
+
+impl Drop for CookieImport {
+    fn drop(&mut self) {
+        let mut outcome = std::mem::take(self.outcome.get_mut().unwrap_or_else(|e| e.into_inner()));
+        // Callbacks complete in any order
+        outcome.failed.sort_by_key(|failure| failure.index);
+        if let Ok(mut queues) = self.event_queues.lock() {
+            let outcome = queues.cookies_imported.push_back(outcome);
+            queues.record(EventKind::CookiesImported, outcome);
+        }
+    }
+}
+
+wrap_set_cookie_callback! {
+    pub(crate) struct SetCookieCallbackImpl {
+        import: Arc<CookieImport>,
+        // Reported if the cookie is not set, with the error filled in.
+        failure: CookieImportFailure,
+    }
+
+    impl SetCookieCallback {
+        fn on_complete(&self, success: ::std::os::raw::c_int) {
+            if success != 0 {
+                self.import.succeed();
+            } else {
+                self.import.fail(CookieImportFailure {
+                    error: "the browser rejected the cookie".to_string(),
+                    ..self.failure.clone()
+                });
+            }
+        }
+    }
+}
+
+impl SetCookieCallbackImpl {
+    pub fn build(
+        import: Arc<CookieImport>,
+        failure: CookieImportFailure,
+    ) -> cef::SetCookieCallback {
+        Self::new(import, failure)
+    }
+}
+
+wrap_resource_request_handler! {
+    pub(crate) struct ResourceRequestHandlerImpl {
+        event_queues: EventQueuesHandle,
+        extra_headers: ExtraHeadersHandle,
+    }
+
+    impl ResourceRequestHandler {
+        fn on_before_resource_load(
+            &self,
+            _browser: Option<&mut Browser>,
+            _frame: Option<&mut Frame>,
+            request: Option<&mut Request>,
+            _callback: Option<&mut Callback>,
+        ) -> ReturnValue {
+            let continue_load = ReturnValue::from(sys::cef_return_value_t::RV_CONTINUE);
+            let Some(request) = request else {
+                return continue_load;
+            };
+            let Ok(extra_headers) = self.extra_headers.read() else {
+                return continue_load;
+            };
+            if extra_headers.is_empty() {
+                return continue_load;
+            }
+
+            // Called again for every redirect, with the new URL: headers
+            // added for the previous host are cleared when this one does not
+            // match, unless the page set the same header to another value.
+            let url = CefStringUtf16::from(&request.url()).to_string();
+            for (name, value) in extra_headers.withheld_from(&url) {
+                let name = CefString::from(name);
+                let current = request.header_by_name(Some(&name));
+                if CefStringUtf16::from(&current).to_string() == value {
+                    request.set_header_by_name(Some(&name), Some(&"".into()), true as _);
+                }
+            }
+            for (name, value) in extra_headers.headers_for(&url) {
+                request.set_header_by_name(Some(&name.into()), Some(&value.into()), true as _);
+            }
+            continue_load
+        }
+
+        fn on_protocol_execution(
+            &self,
+            _browser: Option<&mut Browser>,
+            _frame: Option<&mut Frame>,
+            request: Option<&mut Request>,
+            allow_os_execution: Option<&mut ::std::os::raw::c_int>,
+        ) {
+            // CEF cannot launch external applications in windowless mode;
+            // the game decides what to do with the URL instead.
+            if let Some(allow_os_execution) = allow_os_execution {
+                *allow_os_execution = false as _;
+            }
+
+            let Some(request) = request else { return };
+            let url = CefStringUtf16::from(&request.url()).to_string();
+            if external_protocol_scheme(&url).is_none() {
+                return;
+            }
+
+            if let Ok(mut queues) = self.event_queues.lock() {
+                let outcome = queues.external_protocols.push_back(url);
+                queues.record(EventKind::ExternalProtocol, outcome);
+            }
+        }
+    }
+}
+
+impl ResourceRequestHandlerImpl {
+    pub fn build(
+        event_queues: EventQueuesHandle,
+        extra_headers: ExtraHeadersHandle,
+    ) -> cef::ResourceRequestHandler {
+        Self::new(event_queues, extra_headers)
+    }
+}
+
+wrap_request_handler! {
+    pub(crate) struct RequestHandlerImpl {
+        event_queues: EventQueuesHandle,
+        resource_request_handler: cef::ResourceRequestHandler,
+        certificates: CertificateHandling,
+        navigation_allowlist: NavigationAllowlistHandle,
+        extra_headers: ExtraHeadersHandle,
+        // Set until the first main-frame navigation (the initial `url` load).
+        initial_navigation: Arc<std::sync::atomic::AtomicBool>,
+    }
+
+    impl RequestHandler {
+        fn resource_request_handler(
+            &self,
+            _browser: Option<&mut Browser>,
+            _frame: Option<&mut Frame>,
+            _request: Option<&mut Request>,
+            is_navigation: ::std::os::raw::c_int,
+            _is_download: ::std::os::raw::c_int,
+            _request_initiator: Option<&CefString>,
+            _disable_default_handling: Option<&mut ::std::os::raw::c_int>,
+        ) -> Option<cef::ResourceRequestHandler> {
+            // Navigations may need their external protocol reported; other
+            // requests only go through the handler for extra headers
+            let has_extra_headers = self
+                .extra_headers
+                .read()
+                .is_ok_and(|extra_headers| !extra_headers.is_empty());
+            (is_navigation != 0 || has_extra_headers).then(|| self.resource_request_handler.clone())
+        }
+
+        fn on_before_browse(
+            &self,
+            _browser: Option<&mut Browser>,
+            frame: Option<&mut Frame>,
+            request: Option<&mut Request>,
+            user_gesture: ::std::os::raw::c_int,
+            is_redirect: ::std::os::raw::c_int,
+        ) -> ::std::os::raw::c_int {
+            use std::sync::atomic::Ordering;
+
+            let (Some(frame), Some(request)) = (frame, request) else {
+                return false as _;
+            };
+            if frame.is_main() == 0 {
+                return false as _;
+            }
+
+            // The initial load is exempt, but each of its redirect hops is checked
+            if is_redirect == 0 && self.initial_navigation.swap(false, Ordering::Relaxed) {
+                return false as _;
+            }
+
+            let url = CefStringUtf16::from(&request.url()).to_string();
+            let allowed = self
+                .navigation_allowlist
+                .read()
+                .map(|allowlist| allowlist.allows(&url))
+                .unwrap_or(true);
+            if allowed {
+                return false as _;
+            }
+
+            if let Ok(mut queues) = self.event_queues.lock() {
+                let outcome = queues.navigation_blocked.push_back(NavigationBlockedEvent {
+                    url,
+                    user_gesture: user_gesture != 0,
+                });
+                queues.record(EventKind::NavigationBlocked, outcome);
+            }
+            // Returning true cancels the navigation
+            true as _
+        }
+
+        fn on_certificate_error(
+            &self,
+            _browser: Option<&mut Browser>,
+            cert_error: Errorcode,
+            request_url: Option<&CefString>,
+            ssl_info: Option<&mut Sslinfo>,
+            callback: Option<&mut Callback>,
+        ) -> ::std::os::raw::c_int {
+            let Some(callback) = callback else {
+                return false as _;
+            };
+
+            // Preserve the all-or-nothing behavior of the global switch
+            if self.certificates.ignore_errors {
+                callback.cont();
+                return true as _;
+            }
+
+            let url = request_url.map(|u| u.to_string()).unwrap_or_default();
+            let (cert_status, certificate) = match ssl_info {
+                Some(ssl_info) => (
+                    cert_status_to_u32(ssl_info.cert_status()),
+                    ssl_info
+                        .x509_certificate()
+                        .map(|cert| extract_certificate_info(&cert))
+                        .unwrap_or_default(),
+                ),
+                None => (0, CertificateInfo::default()),
+            };
+
+            let error_id = {
+                let Ok(mut decisions) = self.certificates.decisions.lock() else {
+                    return false as _;
+                };
+                if decisions.is_allowed(&certificate.fingerprint_sha256) {
+                    callback.cont();
+                    return true as _;
+                }
+                decisions.add_pending(callback.clone(), certificate.fingerprint_sha256.clone())
+            };
+
+            if let Ok(mut queues) = self.event_queues.lock() {
+                queues.certificate_errors.push_back(CertificateErrorEvent {
+                    error_id,
+                    url,
+                    cert_status,
+                    error_code: cert_error.get_raw(),
+                    certificate,
+                });
+                queues.record(EventKind::CertificateError, PushOutcome::Queued);
+            }
+
+            // The callback is continued or cancelled from resolve_certificate_error
+            true as _
+        }
+
+        fn on_select_client_certificate(
+            &self,
+            _browser: Option<&mut Browser>,
+            _is_proxy: ::std::os::raw::c_int,
+            host: Option<&CefString>,
+            _port: ::std::os::raw::c_int,
+            certificates: Option<&[Option<X509Certificate>]>,
+            callback: Option<&mut SelectClientCertificateCallback>,
+        ) -> ::std::os::raw::c_int {
+            let Some(callback) = callback else {
+                return false as _;
+            };
+            let certificates: Vec<X509Certificate> =
+                certificates.unwrap_or_default().iter().flatten().cloned().collect();
+            let infos: Vec<CertificateInfo> =
+                certificates.iter().map(extract_certificate_info).collect();
+
+            let auto_selected = auto_select_client_certificate(
+                &self.certificates.client_certificate_auto_select,
+                infos.iter().map(|info| info.subject.as_str()),
+            );
+            if let Some(index) = auto_selected {
+                let mut certificate = certificates[index].clone();
+                callback.select(Some(&mut certificate));
+                return true as _;
+            }
+
+            let request_id = {
+                let Ok(mut requests) = self.certificates.client_certificate_requests.lock() else {
+                    return false as _;
+                };
+                requests.add_pending(callback.clone(), certificates)
+            };
+
+            if let Ok(mut queues) = self.event_queues.lock() {
+                queues.client_certificates.push_back(ClientCertificateEvent {
+                    request_id,
+                    host: host.map(|h| h.to_string()).unwrap_or_default(),
+                    certificates: infos,
+                });
+                queues.record(EventKind::ClientCertificate, PushOutcome::Queued);
+            }
+
+            // The callback is called from select_client_certificate or
+            // decline_client_certificate
+            true as _
+        }
+
+        fn on_render_process_terminated(
+            &self,
+            _browser: Option<&mut Browser>,
+            _status: TerminationStatus,
+            _error_code: ::std::os::raw::c_int,
+            _error_string: Option<&CefString>,
+        ) {
+            if let Ok(mut queues) = self.event_queues.lock() {
+                queues.renderer_process = Some(RendererProcessEvent::Terminated);
+            }
+        }
+    }
+}
+
+/// Certificate state the request handler shares with `CefTexture`.
+#[derive(Clone)]
+pub(crate) struct CertificateHandling {
+    pub decisions: CertificateDecisionsHandle,
+    /// Whether certificate errors are globally ignored (no signal is emitted).
+    pub ignore_errors: bool,
+    pub client_certificate_requests: ClientCertificateRequestsHandle,
+    /// Subject pattern of the client certificate selected without asking
+    /// GDScript (empty = always ask).
+    pub client_certificate_auto_select: String,
+}
+
+fn build_certificate_handling(queues: &ClientQueues) -> CertificateHandling {
+    CertificateHandling {
+        decisions: queues.certificate_decisions.clone(),
+        ignore_errors: queues.ignore_certificate_errors,
+        client_certificate_requests: queues.client_certificate_requests.clone(),
+        client_certificate_auto_select: queues.client_certificate_auto_select.clone(),
+    }
+}
+
+impl RequestHandlerImpl {
+    pub fn build(
+        event_queues: EventQueuesHandle,
+        certificates: CertificateHandling,
+        navigation_allowlist: NavigationAllowlistHandle,
+        extra_headers: ExtraHeadersHandle,
+    ) -> cef::RequestHandler {
+        Self::new(
+            event_queues.clone(),
+            ResourceRequestHandlerImpl::build(event_queues, extra_headers.clone()),
+            certificates,
+            navigation_allowlist,
+            extra_headers,
+            Arc::new(std::sync::atomic::AtomicBool::new(true)),
+        )
+    }
+}
+
+fn on_process_message_received(
+    frame: Option<&mut cef::Frame>,
+    message: Option<&mut ProcessMessage>,
+    ipc: &ClientIpcQueues,
+) -> i32 {
     let Some(message) = message else { return 0 };
     let route = CefStringUtf16::from(&message.name()).to_string();
 
@@ -1037,7 +2243,8 @@ fn on_process_message_received(message: Option<&mut ProcessMessage>, ipc: &Clien
                 let arg = args.string(0);
                 let msg_str = CefStringUtf16::from(&arg).to_string();
                 if let Ok(mut queues) = ipc.event_queues.lock() {
-                    queues.messages.push_back(msg_str);
+                    let outcome = queues.messages.push_back(msg_str);
+                    queues.record(EventKind::Message, outcome);
                 }
             }
         }
@@ -1052,7 +2259,8 @@ fn on_process_message_received(message: Option<&mut ProcessMessage>, ipc: &Clien
                     if copied > 0 {
                         buffer.truncate(copied);
                         if let Ok(mut queues) = ipc.event_queues.lock() {
-                            queues.binary_messages.push_back(buffer);
+                            let outcome = queues.binary_messages.push_back(buffer);
+                            queues.record(EventKind::BinaryMessage, outcome);
                         }
                     }
                 }
@@ -1060,10 +2268,11 @@ fn on_process_message_received(message: Option<&mut ProcessMessage>, ipc: &Clien
         }
         "triggerIme" => {
             if let Some(args) = message.argument_list() {
-                let arg = args.bool(0);
-                let enabled = arg != 0;
+                let enabled = args.bool(0) != 0;
+                let input_type = enabled
+                    .then(|| cef_app::TextInputType::from_i32(args.int(1)).unwrap_or_default());
                 if let Ok(mut queues) = ipc.event_queues.lock() {
-                    queues.ime_enables.push_back(enabled);
+                    queues.ime_enables.push_back(input_type);
                 }
             }
         }
@@ -1077,10 +2286,113 @@ fn on_process_message_received(message: Option<&mut ProcessMessage>, ipc: &Clien
                         caret_x: x,
                         caret_y: y,
                         caret_height: height,
+                        css_pixels: true,
+                        character_bounds: Vec::new(),
                     });
                 }
             }
         }
+        "scrollPosition" => {
+            if let Some(frame) = frame
+                && frame.is_main() != 0
+                && let Some(args) = message.argument_list()
+                && let Ok(mut queues) = ipc.event_queues.lock()
+            {
+                queues.scroll_position = Some(ScrollPosition {
+                    x: args.double(0),
+                    y: args.double(1),
+                    max_x: args.double(2),
+                    max_y: args.double(3),
+                });
+            }
+        }
+        "firstContentfulPaint" => {
+            if let Some(frame) = frame
+                && frame.is_main() != 0
+                && let Some(args) = message.argument_list()
+                && let Ok(mut queues) = ipc.event_queues.lock()
+            {
+                let outcome = queues.first_paints.push_back(FirstPaintEvent {
+                    url: CefStringUtf16::from(&frame.url()).to_string(),
+                    time_ms: args.double(0),
+                });
+                queues.record(EventKind::FirstPaint, outcome);
+            }
+        }
+        "domQueryResult" => {
+            if let Some(frame) = frame
+                && frame.is_main() != 0
+                && let Some(args) = message.argument_list()
+                && let Some(kind) = cef_app::DomQueryKind::from_i32(args.int(0))
+                && let Ok(mut queues) = ipc.event_queues.lock()
+            {
+                let selector = CefStringUtf16::from(&args.string(1)).to_string();
+                let outcome = queues.dom_queries.push_back(match kind {
+                    cef_app::DomQueryKind::Dump => DomQueryEvent::Dumped {
+                        selector,
+                        html: CefStringUtf16::from(&args.string(2)).to_string(),
+                    },
+                    cef_app::DomQueryKind::Count => DomQueryEvent::Counted {
+                        selector,
+                        count: args.int(2),
+                    },
+                });
+                queues.record(EventKind::DomQuery, outcome);
+            }
+        }
+        "rendererProcessId" => {
+            if let Some(args) = message.argument_list()
+                && let Ok(mut queues) = ipc.event_queues.lock()
+            {
+                queues.renderer_process = Some(RendererProcessEvent::Started(args.int(0) as u32));
+            }
+        }
+        "displayMediaRequest" | "displayMediaStopped" | "displayMediaReleased" => {
+            let Some(frame) = frame else { return 0 };
+            let frame_id = CefStringUtf16::from(&frame.identifier()).to_string();
+            let page_request_id = message.argument_list().map(|args| args.int(0));
+            let event = match (route.as_str(), page_request_id) {
+                ("displayMediaRequest", Some(page_request_id)) => DisplayMediaEvent::Requested {
+                    frame_id,
+                    page_request_id,
+                },
+                ("displayMediaStopped", Some(page_request_id)) => DisplayMediaEvent::Stopped {
+                    frame_id,
+                    page_request_id,
+                },
+                _ => DisplayMediaEvent::FrameReleased { frame_id },
+            };
+            if let Ok(mut queues) = ipc.event_queues.lock() {
+                let outcome = queues.display_media_events.push_back(event);
+                queues.record(EventKind::DisplayMedia, outcome);
+            }
+        }
+        "notificationShown" | "notificationClosed" => {
+            let Some(frame) = frame else { return 0 };
+            let Some(args) = message.argument_list() else {
+                return 0;
+            };
+            let frame_id = CefStringUtf16::from(&frame.identifier()).to_string();
+            let string = |index| CefStringUtf16::from(&args.string(index)).to_string();
+            let event = if route == "notificationShown" {
+                WebNotificationEvent::Shown {
+                    frame_id,
+                    title: string(0),
+                    body: string(1),
+                    icon_url: string(2),
+                    tag: string(3),
+                }
+            } else {
+                WebNotificationEvent::Closed {
+                    frame_id,
+                    tag: string(0),
+                }
+            };
+            if let Ok(mut queues) = ipc.event_queues.lock() {
+                let outcome = queues.web_notifications.push_back(event);
+                queues.record(EventKind::WebNotification, outcome);
+            }
+        }
         _ => {}
     }
 
@@ -1093,10 +2405,13 @@ pub(crate) struct ClientHandlers {
     pub display_handler: cef::DisplayHandler,
     pub context_menu_handler: cef::ContextMenuHandler,
     pub life_span_handler: cef::LifeSpanHandler,
+    pub keyboard_handler: cef::KeyboardHandler,
     pub load_handler: cef::LoadHandler,
     pub drag_handler: cef::DragHandler,
     pub audio_handler: Option<cef::AudioHandler>,
     pub download_handler: cef::DownloadHandler,
+    pub request_handler: cef::RequestHandler,
+    pub permission_handler: cef::PermissionHandler,
 }
 
 #[derive(Clone)]
@@ -1133,6 +2448,10 @@ wrap_client! {
             Some(self.handlers.life_span_handler.clone())
         }
 
+        fn keyboard_handler(&self) -> Option<cef::KeyboardHandler> {
+            Some(self.handlers.keyboard_handler.clone())
+        }
+
         fn load_handler(&self) -> Option<cef::LoadHandler> {
             Some(self.handlers.load_handler.clone())
         }
@@ -1149,14 +2468,22 @@ wrap_client! {
             Some(self.handlers.download_handler.clone())
         }
 
+        fn request_handler(&self) -> Option<cef::RequestHandler> {
+            Some(self.handlers.request_handler.clone())
+        }
+
+        fn permission_handler(&self) -> Option<cef::PermissionHandler> {
+            Some(self.handlers.permission_handler.clone())
+        }
+
         fn on_process_message_received(
             &self,
             _browser: Option<&mut cef::Browser>,
-            _frame: Option<&mut cef::Frame>,
+            frame: Option<&mut cef::Frame>,
             _source_process: ProcessId,
             message: Option<&mut ProcessMessage>,
         ) -> i32 {
-            on_process_message_received(message, &self.ipc)
+            on_process_message_received(frame, message, &self.ipc)
         }
     }
 }
@@ -1181,11 +2508,25 @@ fn build_client_handlers(
         render_handler,
         display_handler: DisplayHandlerImpl::build(cursor_type, queues.event_queues.clone()),
         context_menu_handler: ContextMenuHandlerImpl::build(),
-        life_span_handler: LifeSpanHandlerImpl::build(),
+        life_span_handler: LifeSpanHandlerImpl::build(queues.created_browser.clone()),
+        keyboard_handler: KeyboardHandlerImpl::build(
+            queues.browser_shortcuts.clone(),
+            queues.event_queues.clone(),
+        ),
         load_handler: LoadHandlerImpl::build(queues.event_queues.clone()),
         drag_handler: DragHandlerImpl::build(queues.event_queues.clone()),
         audio_handler,
         download_handler: DownloadHandlerImpl::build(queues.event_queues.clone()),
+        request_handler: RequestHandlerImpl::build(
+            queues.event_queues.clone(),
+            build_certificate_handling(queues),
+            queues.navigation_allowlist.clone(),
+            queues.extra_headers.clone(),
+        ),
+        permission_handler: PermissionHandlerImpl::build(
+            queues.geolocation_override.clone(),
+            queues.web_notifications,
+        ),
     }
 }
 
@@ -1197,7 +2538,12 @@ impl SoftwareClientImpl {
         let cursor_type = render_handler.get_cursor_type();
         let ipc = build_ipc_queues(&queues);
         let handlers = build_client_handlers(
-            SoftwareOsrHandler::build(render_handler, queues.event_queues.clone()),
+            SoftwareOsrHandler::build(
+                render_handler,
+                queues.event_queues.clone(),
+                queues.render_stats.clone(),
+                queues.frame_pacer.clone(),
+            ),
             cursor_type,
             &queues,
         );
@@ -1228,6 +2574,10 @@ wrap_client! {
             Some(self.handlers.life_span_handler.clone())
         }
 
+        fn keyboard_handler(&self) -> Option<cef::KeyboardHandler> {
+            Some(self.handlers.keyboard_handler.clone())
+        }
+
         fn load_handler(&self) -> Option<cef::LoadHandler> {
             Some(self.handlers.load_handler.clone())
         }
@@ -1244,14 +2594,22 @@ wrap_client! {
             Some(self.handlers.download_handler.clone())
         }
 
+        fn request_handler(&self) -> Option<cef::RequestHandler> {
+            Some(self.handlers.request_handler.clone())
+        }
+
+        fn permission_handler(&self) -> Option<cef::PermissionHandler> {
+            Some(self.handlers.permission_handler.clone())
+        }
+
         fn on_process_message_received(
             &self,
             _browser: Option<&mut cef::Browser>,
-            _frame: Option<&mut cef::Frame>,
+            frame: Option<&mut cef::Frame>,
             _source_process: ProcessId,
             message: Option<&mut ProcessMessage>,
         ) -> i32 {
-            on_process_message_received(message, &self.ipc)
+            on_process_message_received(frame, message, &self.ipc)
         }
     }
 }
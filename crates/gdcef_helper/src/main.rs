@@ -23,25 +23,59 @@ mod utils;
 
 fn main() -> std::process::ExitCode {
     #[cfg(target_os = "macos")]
-    {
-        let framework_path = utils::get_framework_path().expect("Failed to get CEF framework path");
-        cef_app::load_cef_framework_from_path(&framework_path);
+    let framework_path = match utils::get_framework_path() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("[gdcef_helper] Failed to get CEF framework path: {e}");
+            return std::process::ExitCode::FAILURE;
+        }
+    };
+
+    #[cfg(target_os = "macos")]
+    if let Err(e) = cef_app::load_cef_framework_from_path(&framework_path) {
+        eprintln!("[gdcef_helper] Failed to load CEF framework: {e}");
+        return std::process::ExitCode::FAILURE;
     }
 
     api_hash(cef::sys::CEF_API_VERSION_LAST, 0);
 
     let args = Args::new();
     let cmd = args.as_cmd_line().unwrap();
+    let switch = CefString::from("type");
 
-    #[cfg(target_os = "macos")]
+    // Started by the browser process only to check that the helper runs and
+    // can load CEF
+    if CefString::from(&cmd.switch_value(Some(&switch))).to_string()
+        == cef_app::PREFLIGHT_PROCESS_TYPE
     {
-        let framework_path = utils::get_framework_path().expect("Failed to get CEF framework path");
-        cef_app::load_sandbox_from_path(&framework_path, args.as_main_args());
+        return cef_app::PREFLIGHT_EXIT_CODE.into();
+    }
+
+    #[cfg(target_os = "macos")]
+    if let Err(e) = cef_app::load_sandbox_from_path(&framework_path, args.as_main_args()) {
+        eprintln!("[gdcef_helper] Failed to load CEF sandbox: {e}");
+        return std::process::ExitCode::FAILURE;
     }
 
-    let switch = CefString::from("type");
     let is_browser_process = cmd.has_switch(Some(&switch)) != 1;
-    let mut app = cef_app::AppBuilder::build(cef_app::OsrApp::new());
+    let subprocess_config = cef_app::SubprocessConfig::from_switches(|name| {
+        let name = CefString::from(name);
+        (cmd.has_switch(Some(&name)) == 1)
+            .then(|| CefString::from(&cmd.switch_value(Some(&name))).to_string())
+    });
+    // Games launched through some stores start with a stripped environment, so
+    // restore the browser process locale, fonts and time zone before CEF reads them.
+    for (name, value) in subprocess_config
+        .environment
+        .variables(|name| std::env::var_os(name).is_some())
+    {
+        // SAFETY: no other threads exist yet; CEF has not started.
+        unsafe { std::env::set_var(name, value) };
+    }
+    let osr_app = cef_app::OsrApp::builder()
+        .subprocess_config(subprocess_config)
+        .build();
+    let mut app = cef_app::AppBuilder::build(osr_app);
     let ret = execute_process(
         Some(args.as_main_args()),
         Some(&mut app),
@@ -44,3 +44,237 @@ pub fn composite_popup(dst: &mut DestBuffer, popup: &PopupBuffer) {
         }
     }
 }
+
+/// Returns the byte length of a `width` x `height` BGRA/RGBA buffer, or
+/// `None` if it does not fit in memory.
+pub fn paint_buffer_len(width: u32, height: u32) -> Option<usize> {
+    (width as usize)
+        .checked_mul(height as usize)?
+        .checked_mul(4)
+}
+
+/// Returns how many bytes of a `src_width` x `src_height` paint
+/// [`copy_paint_rows`] reads for a `dst_width` x `dst_height` destination:
+/// the rows and columns both have and nothing past the last of them, or
+/// `None` if that does not fit in memory.
+pub fn paint_rows_read_len(
+    src_width: u32,
+    src_height: u32,
+    dst_width: u32,
+    dst_height: u32,
+) -> Option<usize> {
+    let rows = src_height.min(dst_height) as usize;
+    let columns = src_width.min(dst_width) as usize;
+    if rows == 0 || columns == 0 {
+        return Some(0);
+    }
+    (rows - 1)
+        .checked_mul(src_width as usize)?
+        .checked_add(columns)?
+        .checked_mul(4)
+}
+
+/// Copies a paint with rows of `src_width` pixels into `dst` row by row,
+/// clamped to the rows and columns both have. The rest of `dst` is left as
+/// it is. A row `src` is too short for ends the copy, so a paint never reads
+/// past its buffer.
+pub fn copy_paint_rows(src: &[u8], src_width: u32, src_height: u32, dst: &mut DestBuffer) {
+    let rows = src_height.min(dst.height) as usize;
+    let row_bytes = src_width.min(dst.width) as usize * 4;
+    let src_stride = src_width as usize * 4;
+    let dst_stride = dst.width as usize * 4;
+
+    for row in 0..rows {
+        let src_start = row.saturating_mul(src_stride);
+        let dst_start = row.saturating_mul(dst_stride);
+        let (Some(src_row), Some(dst_row)) = (
+            src.get(src_start..src_start.saturating_add(row_bytes)),
+            dst.data
+                .get_mut(dst_start..dst_start.saturating_add(row_bytes)),
+        ) else {
+            break;
+        };
+        dst_row.copy_from_slice(src_row);
+    }
+}
+
+/// Returns true if a paint of `width` x `height` is plausible for a view
+/// expected to be painted at `expected_width` x `expected_height`.
+///
+/// During rapid resizes CEF can deliver a paint for a size the view no longer
+/// has. Paints off by more than a factor of two in either dimension are
+/// treated as stale. An unknown (zero) expected size accepts everything.
+pub fn is_plausible_paint_size(
+    width: u32,
+    height: u32,
+    expected_width: u32,
+    expected_height: u32,
+) -> bool {
+    let plausible = |actual: u32, expected: u32| {
+        expected == 0
+            || (actual as u64 <= expected as u64 * 2 && actual as u64 * 2 >= expected as u64)
+    };
+    plausible(width, expected_width) && plausible(height, expected_height)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paint_buffer_len() {
+        assert_eq!(paint_buffer_len(1920, 1080), Some(1920 * 1080 * 4));
+        assert_eq!(paint_buffer_len(0, 1080), Some(0));
+        // Would overflow a u32 computation
+        #[cfg(target_pointer_width = "64")]
+        assert_eq!(paint_buffer_len(40_000, 40_000), Some(6_400_000_000));
+    }
+
+    #[test]
+    fn test_is_plausible_paint_size() {
+        assert!(is_plausible_paint_size(800, 600, 800, 600));
+        assert!(is_plausible_paint_size(1600, 300, 800, 600));
+        assert!(!is_plausible_paint_size(1601, 600, 800, 600));
+        assert!(!is_plausible_paint_size(800, 299, 800, 600));
+        assert!(is_plausible_paint_size(123, 456, 0, 0));
+    }
+
+    #[test]
+    fn test_composite_popup_clamps_to_destination() {
+        let mut dst_data = vec![0u8; 4 * 4 * 4];
+        let popup_data = vec![255u8; 3 * 3 * 4];
+        let mut dst = DestBuffer {
+            data: &mut dst_data,
+            width: 4,
+            height: 4,
+        };
+        composite_popup(
+            &mut dst,
+            &PopupBuffer {
+                data: &popup_data,
+                width: 3,
+                height: 3,
+                x: 2,
+                y: -1,
+            },
+        );
+
+        // Only the 2x2 overlap in the top-right corner is written.
+        let written: Vec<usize> = (0..16).filter(|&i| dst_data[i * 4] == 255).collect();
+        assert_eq!(written, vec![2, 3, 6, 7]);
+    }
+
+    #[test]
+    fn test_copy_paint_rows_clamps_to_destination() {
+        // 3x2 paint, each pixel holding its index
+        let src: Vec<u8> = (0..6u8).flat_map(|i| [i; 4]).collect();
+
+        // Narrower and shorter destination: the top-left 2x1 pixels
+        let mut dst_data = vec![9u8; 2 * 4];
+        copy_paint_rows(
+            &src,
+            3,
+            2,
+            &mut DestBuffer {
+                data: &mut dst_data,
+                width: 2,
+                height: 1,
+            },
+        );
+        assert_eq!(dst_data, [[0u8; 4], [1; 4]].concat());
+
+        // Wider and taller destination: the paint in its top-left corner
+        let mut dst_data = vec![9u8; 4 * 3 * 4];
+        copy_paint_rows(
+            &src,
+            3,
+            2,
+            &mut DestBuffer {
+                data: &mut dst_data,
+                width: 4,
+                height: 3,
+            },
+        );
+        let pixels: Vec<u8> = dst_data.chunks_exact(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(pixels, [0, 1, 2, 9, 3, 4, 5, 9, 9, 9, 9, 9]);
+    }
+
+    #[test]
+    fn test_copy_paint_rows_stops_at_short_source() {
+        // Claims to be 2x3 but only holds one and a half rows
+        let src = vec![1u8; 3 * 4];
+        let mut dst_data = vec![0u8; 2 * 3 * 4];
+        copy_paint_rows(
+            &src,
+            2,
+            3,
+            &mut DestBuffer {
+                data: &mut dst_data,
+                width: 2,
+                height: 3,
+            },
+        );
+        let pixels: Vec<u8> = dst_data.chunks_exact(4).map(|pixel| pixel[0]).collect();
+        assert_eq!(pixels, [1, 1, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_paint_rows_read_len() {
+        assert_eq!(paint_rows_read_len(3, 2, 3, 2), paint_buffer_len(3, 2));
+        // Rows past the destination and columns past the last row are not read
+        assert_eq!(paint_rows_read_len(3, 2, 2, 1), Some(2 * 4));
+        assert_eq!(paint_rows_read_len(3, 2, 2, 5), Some((3 + 2) * 4));
+        assert_eq!(paint_rows_read_len(3, 2, 0, 5), Some(0));
+    }
+
+    #[test]
+    fn test_random_resizes_copy_within_the_paint() {
+        // Simulates a view resized every frame while paints for older sizes
+        // are still arriving, mirroring what the render handler receives.
+        // Each paint only holds the bytes `paint_rows_read_len` allows, so
+        // reading past them would leave rows uncopied.
+        let mut seed = 0x2545_f491_4f6c_dd1d_u64;
+        let mut next = |max: u32| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            (seed % max as u64) as u32 + 1
+        };
+
+        let mut paint = (64, 36);
+        for _ in 0..500 {
+            let expected = (next(192), next(108));
+            if !is_plausible_paint_size(paint.0, paint.1, expected.0, expected.1) {
+                paint = expected;
+                continue;
+            }
+
+            let (width, height) = paint;
+            let len = paint_rows_read_len(width, height, expected.0, expected.1).unwrap();
+            let src: Vec<u8> = (0..len / 4)
+                .flat_map(|i| [(i / width as usize % 251) as u8 + 1; 4])
+                .collect();
+            let mut dst_data = vec![0u8; paint_buffer_len(expected.0, expected.1).unwrap()];
+            copy_paint_rows(
+                &src,
+                width,
+                height,
+                &mut DestBuffer {
+                    data: &mut dst_data,
+                    width: expected.0,
+                    height: expected.1,
+                },
+            );
+
+            let columns = width.min(expected.0) as usize;
+            for (row, pixels) in dst_data.chunks_exact(expected.0 as usize * 4).enumerate() {
+                for (column, pixel) in pixels.chunks_exact(4).enumerate() {
+                    let copied = row < height as usize && column < columns;
+                    let value = if copied { (row % 251) as u8 + 1 } else { 0 };
+                    assert_eq!(pixel, [value; 4], "row {row}, column {column}");
+                }
+            }
+            paint = expected;
+        }
+    }
+}
@@ -30,12 +30,14 @@ export default withMermaid(defineConfig({
                 { text: '属性', link: '/zh_CN/api/properties' },
                 { text: '方法', link: '/zh_CN/api/methods' },
                 { text: '信号', link: '/zh_CN/api/signals' },
+                { text: 'CefManager', link: '/zh_CN/api/cef-manager' },
                 { text: '音频捕获', link: '/zh_CN/api/audio-capture' },
                 { text: '输入法（IME）支持', link: '/zh_CN/api/ime-support' },
                 { text: '拖放', link: '/zh_CN/api/drag-and-drop' },
                 { text: '下载', link: '/zh_CN/api/downloads' },
                 { text: 'Vulkan 支持', link: '/zh_CN/api/vulkan-support' },
-                { text: 'GPU 设备绑定', link: '/zh_CN/api/gpu-device-pinning' }
+                { text: 'GPU 设备绑定', link: '/zh_CN/api/gpu-device-pinning' },
+                { text: '自定义协议', link: '/zh_CN/api/custom-schemes' }
               ]
             }
           ]
@@ -77,12 +79,14 @@ export default withMermaid(defineConfig({
             { text: 'Properties', link: '/api/properties' },
             { text: 'Methods', link: '/api/methods' },
             { text: 'Signals', link: '/api/signals' },
+            { text: 'CefManager', link: '/api/cef-manager' },
             { text: 'Audio Capture', link: '/api/audio-capture' },
             { text: 'IME Support', link: '/api/ime-support' },
             { text: 'Drag and Drop', link: '/api/drag-and-drop' },
             { text: 'Downloads', link: '/api/downloads' },
             { text: 'Vulkan Support', link: '/api/vulkan-support' },
-            { text: 'GPU Device Pinning', link: '/api/gpu-device-pinning' }
+            { text: 'GPU Device Pinning', link: '/api/gpu-device-pinning' },
+            { text: 'Custom Schemes', link: '/api/custom-schemes' }
           ]
         }
       ]
@@ -0,0 +1,32 @@
+extends "res://test_case.gd"
+
+# Checks allow_autoplay: a muted <video autoplay> plays either way, while
+# <audio>.play() with sound, called from IPC without a user gesture, only
+# succeeds with allow_autoplay. godot_cef/advanced/allow_autoplay must stay
+# disabled. Needs a display:
+#   godot --path tests/project --script res://autoplay/autoplay_test.gd
+
+
+func _run() -> void:
+	await _check_page(false, "audio denied")
+	await _check_page(true, "audio ok")
+	_finish("Autoplay")
+
+
+func _check_page(allow_autoplay: bool, expected_audio: String) -> void:
+	var texture := _new_texture(Vector2(320, 240))
+	texture.allow_autoplay = allow_autoplay
+	texture.url = "res://autoplay/autoplay.html"
+	root.add_child(texture)
+
+	var label := "with" if allow_autoplay else "without"
+	var result: String = await _next_message()
+	_check(result == "video playing", "muted video autoplays %s allow_autoplay: got %s" % [label, result])
+
+	texture.send_ipc_message("audio")
+	result = await _next_message()
+	_check(result == expected_audio, "audio.play() %s allow_autoplay: got %s" % [label, result])
+
+	texture.queue_free()
+	await process_frame
+	messages.clear()
@@ -0,0 +1,36 @@
+extends "res://test_case.gd"
+
+# Checks that preferred_color_scheme switches the page's
+# @media (prefers-color-scheme) blocks, also after same-document and full
+# navigations. Needs a display:
+#   godot --path tests/project --script res://emulation/color_scheme_test.gd
+
+enum Scheme { SYSTEM, LIGHT, DARK }
+
+var texture: Control
+
+
+func _run() -> void:
+	texture = _new_texture(Vector2(320, 240))
+	texture.preferred_color_scheme = Scheme.DARK
+	texture.url = "res://emulation/color_scheme.html"
+	root.add_child(texture)
+
+	var result: String = await _next_message()
+	_check(result == "dark ", "initial page is dark: got %s" % result)
+
+	texture.send_ipc_message("#next")
+	result = await _next_message()
+	_check(result == "dark #next", "dark after a same-document navigation: got %s" % result)
+
+	texture.preferred_color_scheme = Scheme.LIGHT
+	texture.send_ipc_message("report")
+	result = await _next_message()
+	_check(result == "light #next", "switched to light at runtime: got %s" % result)
+
+	texture.preferred_color_scheme = Scheme.DARK
+	texture.url = "res://emulation/color_scheme.html?reload"
+	result = await _next_message()
+	_check(result == "dark ", "dark after navigating: got %s" % result)
+
+	_finish("Color scheme")
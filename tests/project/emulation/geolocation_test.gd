@@ -0,0 +1,38 @@
+extends "res://test_case.gd"
+
+# Checks that pages see the position set with set_geolocation_override(),
+# that it holds across navigations and that clearing it takes the
+# permission away again. Needs a display, unlike headless/:
+#   godot --path tests/project --script res://emulation/geolocation_test.gd
+
+var texture: Control
+
+
+func _check_position(result: Dictionary, latitude: float, longitude: float, what: String) -> void:
+	_check(not result.has("error") and not result.has("timeout"), "%s: got %s" % [what, result])
+	_check(is_equal_approx(result.get("latitude", 0.0), latitude), "%s: latitude" % what)
+	_check(is_equal_approx(result.get("longitude", 0.0), longitude), "%s: longitude" % what)
+
+
+func _run() -> void:
+	texture = _new_texture(Vector2(320, 240), true)
+	# Set before the browser exists; applied when the first page loads
+	texture.set_geolocation_override(35.6812, 139.7671, 10.0)
+	texture.url = "res://emulation/geolocation.html"
+	root.add_child(texture)
+
+	_check_position(await _next_message(), 35.6812, 139.7671, "initial page")
+
+	texture.url = "res://emulation/geolocation.html?reload"
+	_check_position(await _next_message(), 35.6812, 139.7671, "after navigating")
+
+	texture.set_geolocation_override(-33.8568, 151.2153, 25.0)
+	texture.send_ipc_message("locate")
+	_check_position(await _next_message(), -33.8568, 151.2153, "after changing the override")
+
+	texture.clear_geolocation_override()
+	texture.send_ipc_message("locate")
+	var cleared: Dictionary = await _next_message()
+	_check(cleared.has("error"), "no position after clearing: got %s" % cleared)
+
+	_finish("Geolocation")
@@ -0,0 +1,94 @@
+extends "res://test_case.gd"
+
+# Blocks the main thread for ten seconds while a page sends a heartbeat over
+# a WebSocket every second, and checks that with CEF scheduling its own
+# frames the heartbeats keep arriving and the socket stays open. The
+# WebSocket server runs on a worker thread, which the stall does not block.
+# Needs a display:
+#   godot --path tests/project --script res://frame_scheduling/frame_scheduling_test.gd
+
+const PORT := 45871
+const STALL_MSEC := 10000
+const MIN_BEATS_DURING_STALL := 8
+
+var server_thread := Thread.new()
+var mutex := Mutex.new()
+var stop_server := false
+var beats: Array[int] = []
+var socket_closed := false
+
+
+# Accepts one WebSocket connection and records when each heartbeat arrives.
+func _serve() -> void:
+	var server := TCPServer.new()
+	if server.listen(PORT, "127.0.0.1") != OK:
+		push_error("Cannot listen on port %d" % PORT)
+		return
+	var peer: WebSocketPeer = null
+	while true:
+		mutex.lock()
+		var stopping := stop_server
+		mutex.unlock()
+		if stopping:
+			break
+		if peer == null and server.is_connection_available():
+			peer = WebSocketPeer.new()
+			peer.accept_stream(server.take_connection())
+		if peer != null:
+			peer.poll()
+			while peer.get_available_packet_count() > 0:
+				peer.get_packet()
+				mutex.lock()
+				beats.append(Time.get_ticks_msec())
+				mutex.unlock()
+			if peer.get_ready_state() == WebSocketPeer.STATE_CLOSED:
+				mutex.lock()
+				socket_closed = true
+				mutex.unlock()
+				peer = null
+		OS.delay_msec(10)
+	server.stop()
+
+
+func _run() -> void:
+	server_thread.start(_serve)
+
+	var texture := _new_texture(Vector2(320, 180))
+	texture.frame_scheduling = 1
+	texture.url = "res://frame_scheduling/heartbeat.html?port=%d" % PORT
+	root.add_child(texture)
+
+	var result: String = await _next_message()
+	_check(result == "ready", "page connected to the WebSocket server: got %s" % result)
+
+	var stall_start := Time.get_ticks_msec()
+	OS.delay_msec(STALL_MSEC)
+	var stall_end := Time.get_ticks_msec()
+
+	mutex.lock()
+	var during_stall := beats.filter(func(time): return time >= stall_start and time <= stall_end)
+	var closed := socket_closed
+	var count_after_stall := beats.size()
+	mutex.unlock()
+	print("%d heartbeats arrived during the %d ms stall" % [during_stall.size(), STALL_MSEC])
+
+	_check(
+		during_stall.size() >= MIN_BEATS_DURING_STALL,
+		"heartbeats kept arriving during the stall: %d" % during_stall.size())
+	_check(not closed, "the WebSocket stayed open")
+
+	# The page keeps beating once the game runs again
+	for i in 120:
+		await process_frame
+	mutex.lock()
+	var after := beats.size()
+	mutex.unlock()
+	_check(after > count_after_stall, "heartbeats arrive after the stall")
+
+	texture.queue_free()
+	mutex.lock()
+	stop_server = true
+	mutex.unlock()
+	server_thread.wait_to_finish()
+
+	_finish("Frame scheduling")
@@ -0,0 +1,42 @@
+extends "res://test_case.gd"
+
+# Instantiates CefTexture under headless Godot, where CEF has nothing to
+# render into, and checks that the node turns into a no-op instead of
+# crashing. Run with:
+#   godot --headless --path tests/project --script res://headless/headless_test.gd
+
+
+func _run() -> void:
+	_check(ClassDB.class_exists("CefTexture"), "CefTexture is registered")
+	if failures > 0:
+		_finish("Headless")
+		return
+
+	var texture := _new_texture(Vector2(320, 240))
+	root.add_child(texture)
+
+	for i in 10:
+		await process_frame
+
+	_check(not texture.is_available(), "is_available() is false when headless")
+
+	var signals := []
+	texture.load_started.connect(func(url): signals.append(url))
+	texture.url = "https://example.com"
+	texture.eval("1 + 1")
+	texture.send_ipc_message("ping")
+	texture.reload()
+	texture.go_back()
+	texture.set_zoom_level(1.0)
+	texture.set_background_mode(true)
+	_check(not texture.is_loading(), "is_loading() is false")
+
+	for i in 10:
+		await process_frame
+	_check(signals.is_empty(), "no signals are emitted")
+	_check(messages.is_empty(), "no IPC messages arrive")
+
+	texture.queue_free()
+	await process_frame
+
+	_finish("Headless")
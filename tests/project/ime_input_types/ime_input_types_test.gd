@@ -0,0 +1,67 @@
+extends "res://test_case.gd"
+
+# Checks that focusing email, number, tel, url and password fields configures
+# the hidden IME proxy: secret for passwords, and the matching virtual
+# keyboard type. Needs a display, unlike headless/:
+#   godot --path tests/project --script res://ime_input_types/ime_input_types_test.gd
+
+# Field id, expected secret, expected virtual keyboard type
+const FIELDS := [
+	["email", false, LineEdit.KEYBOARD_TYPE_EMAIL_ADDRESS],
+	["number", false, LineEdit.KEYBOARD_TYPE_NUMBER],
+	["tel", false, LineEdit.KEYBOARD_TYPE_PHONE],
+	["url", false, LineEdit.KEYBOARD_TYPE_URL],
+	["password", true, LineEdit.KEYBOARD_TYPE_PASSWORD],
+	["revealed", true, LineEdit.KEYBOARD_TYPE_PASSWORD],
+	["decimal", false, LineEdit.KEYBOARD_TYPE_NUMBER_DECIMAL],
+	["notes", false, LineEdit.KEYBOARD_TYPE_MULTILINE],
+	["text", false, LineEdit.KEYBOARD_TYPE_DEFAULT],
+]
+
+var texture: Control
+var compositions := 0
+
+
+# The focus change reaches the browser process after the page answered, so
+# wait a few frames for it to be applied.
+func _settle() -> void:
+	for i in 10:
+		await process_frame
+
+
+func _proxy() -> LineEdit:
+	var proxies := texture.find_children("*", "LineEdit", false, false)
+	return proxies[0] if not proxies.is_empty() else null
+
+
+func _run() -> void:
+	texture = _new_texture(Vector2(640, 480))
+	texture.ime_composition_changed.connect(func(_text, _cursor): compositions += 1)
+	texture.url = "res://ime_input_types/input_types.html"
+	root.add_child(texture)
+
+	var result: String = await _next_message()
+	_check(result == "ready", "page loaded: got %s" % result)
+	texture.grab_focus()
+
+	var proxy := _proxy()
+	_check(proxy != null, "CefTexture has an IME proxy")
+	if proxy == null:
+		_finish("IME input types")
+		return
+
+	for field in FIELDS:
+		var id: String = field[0]
+		texture.send_ipc_message(id)
+		result = await _next_message()
+		_check(result == id, "focused %s: got %s" % [id, result])
+		await _settle()
+		_check(proxy.secret == field[1], "%s: secret is %s" % [id, proxy.secret])
+		_check(
+			proxy.virtual_keyboard_type == field[2],
+			"%s: virtual keyboard type is %d" % [id, proxy.virtual_keyboard_type]
+		)
+
+	_check(compositions == 0, "no composition was reported: got %d" % compositions)
+
+	_finish("IME input types")
@@ -0,0 +1,57 @@
+extends "res://test_case.gd"
+
+# Checks what res:// serves for imported files. From the project folder the
+# source files exist and are served as they are; from an exported pack only
+# their imported forms do, so the texture comes back re-encoded as PNG and
+# the sound as a 404 with a hint. Needs a display, unlike headless/:
+#   godot --path tests/project --script res://imported_resources/imported_resources_test.gd
+#   godot --headless --path tests/project --export-pack Pack tests.pck
+#   godot --main-pack tests/project/tests.pck \
+#       --script res://imported_resources/imported_resources_test.gd
+
+const IMAGE_SIZE := 4
+const IMAGE_PATH := "res://imported_resources/red_square.png"
+
+var texture: Control
+
+
+func _fetch(url: String) -> Dictionary:
+	texture.send_ipc_message(url)
+	return await _next_message()
+
+
+func _run() -> void:
+	texture = _new_texture(Vector2(320, 240), true)
+	texture.url = "res://imported_resources/fetch.html"
+	root.add_child(texture)
+
+	var ready: Dictionary = await _next_message()
+	_check(ready.get("ready", false), "page loaded: got %s" % ready)
+
+	# The exported pack has no source files, only what the import produced
+	var exported := not FileAccess.file_exists(IMAGE_PATH)
+	print("Running from %s" % ("an exported pack" if exported else "the project folder"))
+
+	var image := await _fetch(IMAGE_PATH)
+	_check(image.get("status") == 200, "image status: got %s" % image)
+	_check(image.get("width") == IMAGE_SIZE, "image decodes: got %s" % image)
+	_check(image.get("hint") == "", "no hint for the image: got %s" % image)
+	if exported:
+		_check(image.get("type") == "image/png", "re-encoded as PNG: got %s" % image)
+	else:
+		var source_size := FileAccess.get_file_as_bytes(IMAGE_PATH).size()
+		_check(image.get("length") == source_size, "source file served: got %s" % image)
+
+	var sound := await _fetch("res://imported_resources/click.wav")
+	if exported:
+		_check(sound.get("status") == 404, "sound status: got %s" % sound)
+		_check(str(sound.get("hint", "")).contains("AudioStreamWAV"), "hint names the type: got %s" % sound)
+	else:
+		_check(sound.get("status") == 200, "sound status: got %s" % sound)
+		_check(sound.get("hint") == "", "no hint for the sound: got %s" % sound)
+
+	var missing := await _fetch("res://imported_resources/missing.png")
+	_check(missing.get("status") == 404, "missing status: got %s" % missing)
+	_check(missing.get("hint") == "", "no hint for a missing file: got %s" % missing)
+
+	_finish("Imported resources")
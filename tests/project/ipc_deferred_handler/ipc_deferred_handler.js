@@ -0,0 +1,16 @@
+// Module scripts are deferred, so any message Godot sends while the document
+// is still parsing has to be buffered by the render process until this runs.
+const log = document.getElementById('log');
+
+// Wait a little longer to widen the window in which messages are buffered.
+await new Promise((resolve) => setTimeout(resolve, 250));
+
+window.onIpcMessage = (message) => {
+    log.textContent += message + '\n';
+    window.sendIpcMessage('echo:' + message);
+};
+
+window.onIpcBinaryMessage = (buffer) => {
+    log.textContent += '[binary ' + buffer.byteLength + ' bytes]\n';
+    window.sendIpcBinaryMessage(buffer);
+};
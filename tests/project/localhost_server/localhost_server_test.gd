@@ -0,0 +1,84 @@
+extends "res://test_case.gd"
+
+# Serves HTTP and WebSocket on the ports of godot_cef/network/localhost_ports
+# and checks that a res:// page reaches both, using window.GODOT_LOCAL_PORTS.
+# The settings are read when CEF starts, so they are set before the first
+# CefTexture. Needs a display, unlike headless/:
+#   godot --path tests/project --script res://localhost_server/localhost_server_test.gd
+
+const HTTP_PORT := 18080
+const WEBSOCKET_PORT := 18081
+
+var http_server := TCPServer.new()
+var websocket_server := TCPServer.new()
+var http_peers: Array[StreamPeerTCP] = []
+var websocket_peers: Array[WebSocketPeer] = []
+
+
+func _process(_delta: float) -> bool:
+	_serve_http()
+	_serve_websocket()
+	return false
+
+
+# Answers every request with "pong", allowing any origin
+func _serve_http() -> void:
+	while http_server.is_connection_available():
+		http_peers.append(http_server.take_connection())
+	for peer in http_peers.duplicate():
+		peer.poll()
+		if peer.get_status() != StreamPeerTCP.STATUS_CONNECTED:
+			http_peers.erase(peer)
+			continue
+		var available := peer.get_available_bytes()
+		if available == 0:
+			continue
+		var request := peer.get_utf8_string(available)
+		if not request.contains("\r\n\r\n"):
+			continue
+		var body := "pong"
+		var response := "HTTP/1.1 200 OK\r\n" \
+			+ "Content-Type: text/plain\r\n" \
+			+ "Access-Control-Allow-Origin: *\r\n" \
+			+ "Content-Length: %d\r\n" % body.length() \
+			+ "Connection: close\r\n\r\n" + body
+		peer.put_data(response.to_utf8_buffer())
+		peer.disconnect_from_host()
+		http_peers.erase(peer)
+
+
+# Echoes every message with a "pong: " prefix
+func _serve_websocket() -> void:
+	while websocket_server.is_connection_available():
+		var peer := WebSocketPeer.new()
+		peer.accept_stream(websocket_server.take_connection())
+		websocket_peers.append(peer)
+	for peer in websocket_peers.duplicate():
+		peer.poll()
+		if peer.get_ready_state() == WebSocketPeer.STATE_CLOSED:
+			websocket_peers.erase(peer)
+			continue
+		while peer.get_available_packet_count() > 0:
+			peer.send_text("pong: " + peer.get_packet().get_string_from_utf8())
+
+
+func _run() -> void:
+	ProjectSettings.set_setting("godot_cef/network/treat_localhost_as_secure", true)
+	ProjectSettings.set_setting("godot_cef/network/localhost_ports", "%d,%d" % [HTTP_PORT, WEBSOCKET_PORT])
+	_check(http_server.listen(HTTP_PORT, "127.0.0.1") == OK, "HTTP server listening")
+	_check(websocket_server.listen(WEBSOCKET_PORT, "127.0.0.1") == OK, "WebSocket server listening")
+
+	var texture := _new_texture(Vector2(400, 300), true)
+	texture.url = "res://localhost_server/local_server.html"
+	root.add_child(texture)
+
+	var result: Dictionary = await _next_message()
+	_check(not result.has("timeout"), "page reported back")
+	_check(result.get("ports") == [float(HTTP_PORT), float(WEBSOCKET_PORT)], "GODOT_LOCAL_PORTS: got %s" % result)
+	_check(result.get("fetch") == "pong", "fetch reached the HTTP server: got %s" % result)
+	_check(result.get("websocket") == "pong: ping", "WebSocket reached the server: got %s" % result)
+
+	texture.queue_free()
+	http_server.stop()
+	websocket_server.stop()
+	_finish("Localhost server")
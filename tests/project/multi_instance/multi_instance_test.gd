@@ -0,0 +1,80 @@
+extends "res://test_case.gd"
+
+# Measures the CPU cost of idle browsers: one panel, then INSTANCES panels
+# showing a static page. Begin frames are scheduled per browser and the
+# message loop is pumped once per frame, so the cost must grow slower than
+# the number of panels. Prints the main-thread process time and, on Linux,
+# the CPU time of the Godot process. Needs a display:
+#   godot --path tests/project --script res://multi_instance/multi_instance_test.gd
+
+const INSTANCES := 6
+const SETTLE_FRAMES := 120
+const MEASURED_FRAMES := 300
+
+
+# Returns the user and system CPU time of this process in clock ticks, or
+# -1 where /proc is not available.
+func _process_cpu_ticks() -> int:
+	var stat := FileAccess.get_file_as_string("/proc/self/stat")
+	if stat.is_empty():
+		return -1
+	# Fields after the parenthesised command name; utime and stime are the
+	# 14th and 15th fields of the line.
+	var fields := stat.substr(stat.rfind(")") + 2).split(" ")
+	return int(fields[11]) + int(fields[12])
+
+
+# Returns the average main-thread process time in milliseconds and the
+# process CPU ticks spent while `count` idle panels are open.
+func _measure(count: int) -> Dictionary:
+	var textures: Array[Control] = []
+	for i in count:
+		var texture := _new_texture(Vector2(640, 360))
+		texture.position = Vector2(40 * i, 40 * i)
+		texture.url = "res://multi_instance/idle.html"
+		root.add_child(texture)
+		textures.append(texture)
+
+	for i in count:
+		var result: String = await _next_message()
+		_check(result == "ready", "panel %d of %d loaded: got %s" % [i + 1, count, result])
+
+	for i in SETTLE_FRAMES:
+		await process_frame
+
+	var cpu_start := _process_cpu_ticks()
+	var total_ms := 0.0
+	for i in MEASURED_FRAMES:
+		await process_frame
+		total_ms += Performance.get_monitor(Performance.TIME_PROCESS) * 1000.0
+	var cpu_end := _process_cpu_ticks()
+
+	for texture in textures:
+		texture.queue_free()
+	await process_frame
+	return {
+		"process_ms": total_ms / MEASURED_FRAMES,
+		"cpu_ticks": cpu_end - cpu_start if cpu_start >= 0 else -1,
+	}
+
+
+func _run() -> void:
+	var single := await _measure(1)
+	var many := await _measure(INSTANCES)
+
+	print("1 idle panel:  %.3f ms process time per frame, %d CPU ticks" % [
+		single.process_ms, single.cpu_ticks])
+	print("%d idle panels: %.3f ms process time per frame, %d CPU ticks" % [
+		INSTANCES, many.process_ms, many.cpu_ticks])
+
+	_check(
+		many.process_ms < single.process_ms * INSTANCES,
+		"process time grows slower than the panel count: %.3f ms vs %.3f ms x %d" % [
+			many.process_ms, single.process_ms, INSTANCES])
+	if single.cpu_ticks > 0:
+		_check(
+			many.cpu_ticks < single.cpu_ticks * INSTANCES,
+			"CPU time grows slower than the panel count: %d vs %d x %d ticks" % [
+				many.cpu_ticks, single.cpu_ticks, INSTANCES])
+
+	_finish("Multi instance")
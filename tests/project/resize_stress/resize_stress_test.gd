@@ -0,0 +1,49 @@
+extends "res://test_case.gd"
+
+# Resizes a software-rendered texture to a random size every frame while it
+# plays a video, then checks the page still answers and keeps painting at
+# its final size. Paints of a stale size are copied row by row, clamped to
+# the view, so this must neither crash nor stall. Needs a display:
+#   godot --path tests/project --script res://resize_stress/resize_stress_test.gd
+
+const STRESS_MSEC := 5000
+const SETTLE_FRAMES := 30
+const FINAL_SIZE := Vector2(800, 600)
+
+
+func _run() -> void:
+	var texture := _new_texture(FINAL_SIZE)
+	texture.force_software_rendering = true
+	texture.url = "res://resize_stress/video.html"
+	root.add_child(texture)
+
+	var result: String = await _next_message()
+	_check(result == "ready", "video playing: got %s" % result)
+
+	var rng := RandomNumberGenerator.new()
+	var resizes := 0
+	var deadline := Time.get_ticks_msec() + STRESS_MSEC
+	while Time.get_ticks_msec() < deadline:
+		texture.size = Vector2(rng.randi_range(1, 1920), rng.randi_range(1, 1080))
+		resizes += 1
+		await process_frame
+
+	texture.size = FINAL_SIZE
+	for i in SETTLE_FRAMES:
+		await process_frame
+	texture.reset_render_stats()
+
+	texture.send_ipc_message("ping")
+	result = await _next_message()
+	_check(
+		result == "pong %dx%d" % [FINAL_SIZE.x, FINAL_SIZE.y],
+		"page answers at its final size after %d resizes: got %s" % [resizes, result])
+
+	for i in SETTLE_FRAMES:
+		await process_frame
+	var stats: Dictionary = texture.get_render_stats()
+	_check(stats.get("paint_count", 0) > 0, "paints continue after the resizes")
+
+	texture.queue_free()
+	await process_frame
+	_finish("Resize stress")
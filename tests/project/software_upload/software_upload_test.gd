@@ -0,0 +1,62 @@
+extends "res://test_case.gd"
+
+# Measures the average main-thread process time while a 4K page repaints
+# every frame, with software frames uploaded on the main thread and then on
+# the upload worker, and checks the worker keeps the texture updating.
+# Needs a display:
+#   godot --path tests/project --script res://software_upload/software_upload_test.gd
+
+const WARMUP_FRAMES := 60
+const MEASURED_FRAMES := 300
+const VIEW_SIZE := Vector2(3840, 2160)
+
+
+# Returns the average main-thread process time in milliseconds, and the
+# average upload time and paint count reported by the render stats.
+func _measure(threaded: bool) -> Dictionary:
+	ProjectSettings.set_setting("godot_cef/performance/threaded_software_upload", threaded)
+
+	var texture := _new_texture(VIEW_SIZE)
+	texture.force_software_rendering = true
+	texture.url = "res://software_upload/full_motion.html"
+	root.add_child(texture)
+
+	var result: String = await _next_message()
+	_check(result == "ready", "page loaded (threaded=%s): got %s" % [threaded, result])
+
+	for i in WARMUP_FRAMES:
+		await process_frame
+	texture.reset_render_stats()
+
+	var total_ms := 0.0
+	for i in MEASURED_FRAMES:
+		await process_frame
+		total_ms += Performance.get_monitor(Performance.TIME_PROCESS) * 1000.0
+	var stats: Dictionary = texture.get_render_stats()
+
+	texture.queue_free()
+	await process_frame
+	return {
+		"process_ms": total_ms / MEASURED_FRAMES,
+		"upload_ms": stats.get("average_upload_ms", 0.0),
+		"paints": stats.get("paint_count", 0),
+	}
+
+
+func _run() -> void:
+	ProjectSettings.set_setting("godot_cef/performance/max_frame_rate", 60)
+	var main_thread := await _measure(false)
+	var worker := await _measure(true)
+
+	print("Main-thread upload: %.2f ms process time per frame, %.2f ms per upload, %d paints" % [
+		main_thread.process_ms, main_thread.upload_ms, main_thread.paints])
+	print("Upload worker:      %.2f ms process time per frame, %.2f ms per upload, %d paints" % [
+		worker.process_ms, worker.upload_ms, worker.paints])
+
+	_check(worker.upload_ms > 0.0, "the upload worker updated the texture")
+	_check(
+		worker.process_ms < main_thread.process_ms,
+		"the upload worker lowers the main-thread time: %.2f ms vs %.2f ms" % [
+			worker.process_ms, main_thread.process_ms])
+
+	_finish("Software upload")
@@ -0,0 +1,91 @@
+extends "res://test_case.gd"
+
+# Checks that with render_target = SubViewport a shader on the node is
+# applied to live page content, and that clicks on the node reach the page
+# scaled to the viewport's size. Needs a display:
+#   godot --path tests/project --script res://subviewport_output/subviewport_output_test.gd
+# Its demo scene shows the page through a CRT shader:
+#   godot --path tests/project res://subviewport_output/crt_demo.tscn
+
+const VIEW_SIZE := Vector2(320, 240)
+const INVERT_SHADER := """
+shader_type canvas_item;
+
+void fragment() {
+	COLOR = vec4(1.0 - texture(TEXTURE, UV).rgb, 1.0);
+}
+"""
+
+var texture: Control
+
+
+# Returns the next message starting with `prefix`, dropping the others.
+func _next_message_with(prefix: String) -> String:
+	var deadline := Time.get_ticks_msec() + TIMEOUT_MSEC
+	while Time.get_ticks_msec() < deadline:
+		while not messages.is_empty():
+			var message: String = messages.pop_front()
+			if message.begins_with(prefix):
+				return message
+		await process_frame
+	return "timeout"
+
+
+func _is_color(actual: Color, expected: Color) -> bool:
+	return (absf(actual.r - expected.r) < 0.1
+		and absf(actual.g - expected.g) < 0.1
+		and absf(actual.b - expected.b) < 0.1)
+
+
+# Returns the color on screen at the center of the node, after the shader.
+func _screen_color() -> Color:
+	for i in 3:
+		await RenderingServer.frame_post_draw
+	var image := root.get_texture().get_image()
+	return image.get_pixelv(Vector2i(VIEW_SIZE / 2))
+
+
+func _run() -> void:
+	var shader := Shader.new()
+	shader.code = INVERT_SHADER
+	var material := ShaderMaterial.new()
+	material.shader = shader
+
+	texture = _new_texture(VIEW_SIZE)
+	texture.render_target = 1
+	texture.material = material
+	texture.url = "res://subviewport_output/subviewport_output.html"
+	root.add_child(texture)
+
+	var result := await _next_message_with("page ready")
+	_check(result == "page ready", "page loaded: got %s" % result)
+
+	var viewport: SubViewport = texture.get_output_viewport()
+	_check(viewport != null, "the output viewport exists")
+	if viewport == null:
+		_finish("SubViewport output")
+		return
+	_check(viewport.size == Vector2i(VIEW_SIZE), "the viewport has the node's size: %s" % viewport.size)
+
+	# Each color must show up inverted, so the shader runs over the page as
+	# it changes
+	for color in [Color.RED, Color.BLUE]:
+		texture.send_ipc_message("#" + color.to_html(false))
+		result = await _next_message_with("painted")
+		_check(result.begins_with("painted"), "page painted %s: got %s" % [color, result])
+		var viewport_color := viewport.get_texture().get_image().get_pixelv(viewport.size / 2)
+		_check(_is_color(viewport_color, color), "viewport shows %s: got %s" % [color, viewport_color])
+		var screen_color: Color = await _screen_color()
+		_check(_is_color(screen_color, color.inverted()), "screen shows %s inverted: got %s" % [color, screen_color])
+
+	# A viewport twice the node's size lays the page out at that size, and
+	# positions on the node are scaled to it
+	viewport.size = Vector2i(VIEW_SIZE * 2)
+	result = await _next_message_with("resized")
+	_check(result == "resized 640x480", "page follows the viewport's size: got %s" % result)
+
+	texture.inject_mouse_click(Vector2(80, 60), MOUSE_BUTTON_LEFT, false)
+	result = await _next_message_with("click")
+	_check(result == "click 160,120", "click is scaled to the viewport: got %s" % result)
+
+	_finish("SubViewport output")
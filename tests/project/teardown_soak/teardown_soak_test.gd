@@ -0,0 +1,44 @@
+extends "res://test_case.gd"
+
+# Adds a CefTexture with accelerated rendering every frame for a minute and
+# frees the one added a few frames before, so browsers are freed while they
+# paint and while their first copy is still in flight. Checks that some of
+# them rendered accelerated and that the process survives. Run it with GPU
+# validation and look for validation errors in the output. Needs a display:
+#   godot --path tests/project --gpu-validation --script res://teardown_soak/teardown_soak_test.gd
+
+const SOAK_MSEC := 60000
+const LIVE_TEXTURES := 4
+const VIEW_SIZE := Vector2(320, 240)
+
+
+func _run() -> void:
+	var textures: Array[Control] = []
+	var created := 0
+	var accelerated := 0
+	var deadline := Time.get_ticks_msec() + SOAK_MSEC
+	while Time.get_ticks_msec() < deadline:
+		var texture := _new_texture(VIEW_SIZE)
+		texture.position = Vector2(created % 4, created / 4 % 3) * VIEW_SIZE
+		texture.enable_accelerated_osr = true
+		texture.url = "res://teardown_soak/spinner.html"
+		root.add_child(texture)
+		textures.append(texture)
+		created += 1
+
+		if textures.size() > LIVE_TEXTURES:
+			var oldest: Control = textures.pop_front()
+			if oldest.get_render_stats().get("render_path") == "accelerated":
+				accelerated += 1
+			oldest.queue_free()
+		await process_frame
+
+	for texture in textures:
+		texture.queue_free()
+	# Let CEF release the closed browsers before quitting
+	for i in 30:
+		await process_frame
+
+	_check(accelerated > 0, "browsers rendered accelerated: %d of %d" % [accelerated, created])
+	print("Created and freed %d browsers" % created)
+	_finish("Teardown soak")
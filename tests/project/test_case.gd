@@ -0,0 +1,56 @@
+extends SceneTree
+
+# Base of the test scripts of this project. A test implements _run(),
+# reports with _check() and ends with _finish(), which quits with a failing
+# exit code if any check failed. Run a test with:
+#   godot --path tests/project --script res://<folder>/<name>_test.gd
+
+const TIMEOUT_MSEC := 15000
+
+var failures := 0
+# IPC messages from the textures of _new_texture(), oldest first
+var messages: Array = []
+# What _next_message() returns when no message arrives in time
+var timeout_message: Variant = "timeout"
+
+
+func _initialize() -> void:
+	_run.call_deferred()
+
+
+func _run() -> void:
+	_finish("Empty")
+
+
+func _check(condition: bool, message: String) -> void:
+	if not condition:
+		push_error("FAIL: " + message)
+		failures += 1
+
+
+func _finish(test_name: String) -> void:
+	if failures == 0:
+		print("%s test passed" % test_name)
+	quit(1 if failures > 0 else 0)
+
+
+# Returns a CefTexture of `size`, not yet in the tree, whose IPC messages
+# go to `messages`, parsed as JSON with `json`.
+func _new_texture(size: Vector2, json := false) -> Control:
+	var texture: Control = ClassDB.instantiate("CefTexture")
+	texture.size = size
+	if json:
+		timeout_message = {"timeout": true}
+		texture.ipc_message.connect(func(message): messages.append(JSON.parse_string(message)))
+	else:
+		texture.ipc_message.connect(func(message): messages.append(message))
+	return texture
+
+
+func _next_message() -> Variant:
+	var deadline := Time.get_ticks_msec() + TIMEOUT_MSEC
+	while messages.is_empty() and Time.get_ticks_msec() < deadline:
+		await process_frame
+	if messages.is_empty():
+		return timeout_message
+	return messages.pop_front()
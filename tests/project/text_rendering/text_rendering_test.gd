@@ -0,0 +1,38 @@
+extends "res://test_case.gd"
+
+# Checks that minimum_font_size enlarges small text and that
+# default_font_size applies to text without a font-size. Needs a display,
+# unlike headless/:
+#   godot --path tests/project --script res://text_rendering/text_rendering_test.gd
+# To compare antialiasing, open font_sizes.html in a CefTexture with a
+# transparent background_color and toggle
+# godot_cef/rendering/force_grayscale_antialiasing.
+
+
+func _measure(minimum_font_size: int, default_font_size: int) -> Dictionary:
+	var texture := _new_texture(Vector2(800, 400), true)
+	# Read when the browser is created, so set before entering the tree
+	texture.minimum_font_size = minimum_font_size
+	texture.default_font_size = default_font_size
+	texture.url = "res://text_rendering/font_sizes.html"
+	root.add_child(texture)
+	var result: Dictionary = await _next_message()
+	texture.queue_free()
+	return result
+
+
+func _run() -> void:
+	var defaults := await _measure(0, 0)
+	_check(not defaults.has("timeout"), "page loaded with the defaults")
+	_check(defaults.get("tiny", 0.0) < defaults.get("reference", 0.0), "6px text is smaller by default")
+	_check(defaults.get("default_font_size") == "16px", "default font size: got %s" % defaults)
+
+	var adjusted := await _measure(12, 20)
+	_check(not adjusted.has("timeout"), "page loaded with the adjusted sizes")
+	_check(
+		is_equal_approx(adjusted.get("tiny", 0.0), adjusted.get("reference", -1.0)),
+		"6px text enlarged to the minimum: got %s" % adjusted
+	)
+	_check(adjusted.get("default_font_size") == "20px", "default font size: got %s" % adjusted)
+
+	_finish("Text rendering")
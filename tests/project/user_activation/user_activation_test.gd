@@ -0,0 +1,51 @@
+extends "res://test_case.gd"
+
+# Checks that eval_with_user_gesture and send_ipc_message with user_gesture
+# run with user activation, so clipboard writes and fullscreen succeed, while
+# plain eval and IPC still fail. The plain checks run first: activation
+# lasts a few seconds. Needs a display:
+#   godot --path tests/project --script res://user_activation/user_activation_test.gd
+
+var texture: Control
+
+
+func _run() -> void:
+	texture = _new_texture(Vector2(320, 240))
+	texture.focus_mode = Control.FOCUS_ALL
+	texture.url = "res://user_activation/user_activation.html"
+	root.add_child(texture)
+	# The clipboard also needs the page to be focused
+	texture.grab_focus()
+
+	var result: String = await _next_message()
+	_check(result == "page ready", "page loaded: got %s" % result)
+
+	texture.eval("runCheck('activation')")
+	result = await _next_message()
+	_check(result == "activation false", "eval has no activation: got %s" % result)
+
+	texture.eval("runCheck('clipboard')")
+	result = await _next_message()
+	_check(result == "clipboard denied", "eval cannot write the clipboard: got %s" % result)
+
+	texture.eval("runCheck('fullscreen')")
+	result = await _next_message()
+	_check(result == "fullscreen denied", "eval cannot enter fullscreen: got %s" % result)
+
+	texture.send_ipc_message("activation")
+	result = await _next_message()
+	_check(result == "activation false", "IPC has no activation: got %s" % result)
+
+	texture.send_ipc_message("activation", true)
+	result = await _next_message()
+	_check(result == "activation true", "IPC with user_gesture has activation: got %s" % result)
+
+	texture.eval_with_user_gesture("runCheck('clipboard')")
+	result = await _next_message()
+	_check(result == "clipboard ok", "gesture writes the clipboard: got %s" % result)
+
+	texture.eval_with_user_gesture("runCheck('fullscreen')")
+	result = await _next_message()
+	_check(result == "fullscreen ok", "gesture enters fullscreen: got %s" % result)
+
+	_finish("User activation")
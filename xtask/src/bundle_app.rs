@@ -1,7 +1,10 @@
 use crate::bundle_common::{
-    AppInfoPlist, copy_directory, deploy_bundle_to_addon, get_cef_dir_arm64, get_cef_dir_x64,
-    get_target_dir, get_target_dir_for_target, run_cargo, run_lipo,
+    AppInfoPlist, BundleOptions, copy_directory, deploy_bundle_to_addon, get_cef_dir_arm64,
+    get_cef_dir_x64, get_target_dir, get_target_dir_for_target, prune_locales, run_cargo, run_lipo,
 };
+use crate::codesign::sign_app;
+use crate::fetch_cef::ensure_cef_dir;
+use crate::strip::strip_symbols;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -59,8 +62,7 @@ fn bundle(
 ) -> Result<PathBuf, Box<dyn std::error::Error>> {
     let main_app_path = create_app(target_dir, "Godot CEF", universal_helper, false)?;
 
-    let cef_path_arm64 = get_cef_dir_arm64()
-        .ok_or("CEF ARM64 directory not found. Please set CEF_PATH_ARM64 environment variable.")?;
+    let cef_path_arm64 = ensure_cef_dir(get_cef_dir_arm64(), TARGET_ARM64)?;
     let to_arm64 = main_app_path.join(FRAMEWORKS_PATH).join(FRAMEWORK_ARM64);
     if to_arm64.exists() {
         fs::remove_dir_all(&to_arm64)?;
@@ -68,8 +70,7 @@ fn bundle(
     copy_directory(&cef_path_arm64.join(FRAMEWORK), &to_arm64)?;
     println!("Copied: {}", FRAMEWORK_ARM64);
 
-    let cef_path_x64 = get_cef_dir_x64()
-        .ok_or("CEF X64 directory not found. Please set CEF_PATH_X64 environment variable.")?;
+    let cef_path_x64 = ensure_cef_dir(get_cef_dir_x64(), TARGET_X64)?;
     let to_x64 = main_app_path.join(FRAMEWORKS_PATH).join(FRAMEWORK_X64);
     if to_x64.exists() {
         fs::remove_dir_all(&to_x64)?;
@@ -90,7 +91,10 @@ fn bundle(
     Ok(main_app_path)
 }
 
-pub fn run(release: bool, target_dir: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+pub fn run(options: &BundleOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let release = options.release;
+    let target_dir = options.target_dir;
+
     let mut cargo_args_arm64 = vec!["build", "--bin", "gdcef_helper", "--target", TARGET_ARM64];
     if release {
         cargo_args_arm64.push("--release");
@@ -112,9 +116,20 @@ pub fn run(release: bool, target_dir: Option<&Path>) -> Result<(), Box<dyn std::
     let universal_helper = output_dir.join("gdcef_helper_universal");
 
     run_lipo(&helper_arm64, &helper_x64, &universal_helper)?;
+    if options.strip {
+        strip_symbols(&universal_helper, "gdcef_helper", options.debug_symbols_dir)?;
+    }
 
     let app_path = bundle(&output_dir, &universal_helper)?;
     fs::remove_file(&universal_helper)?;
+    // Pruning after signing would invalidate the signature
+    let removed = prune_locales(&app_path.join(FRAMEWORKS_PATH), options.locales)?;
+    if removed > 0 {
+        println!("  Pruned locales: {} bytes", removed);
+    }
+    if let Some(identity) = options.sign {
+        sign_app(&app_path, identity)?;
+    }
     deploy_bundle_to_addon(&app_path, PLATFORM_TARGET)?;
 
     Ok(())
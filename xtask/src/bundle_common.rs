@@ -84,6 +84,31 @@ pub struct FrameworkInfoPlist {
     pub ls_ui_element: Option<String>,
 }
 
+/// Entitlements the hardened runtime needs to run Chromium: V8 generates code
+/// at runtime, and the helpers load the CEF framework, which is signed by a
+/// different team.
+#[cfg(target_os = "macos")]
+#[derive(Serialize)]
+pub struct EntitlementsPlist {
+    #[serde(rename = "com.apple.security.cs.allow-jit")]
+    pub allow_jit: bool,
+    #[serde(rename = "com.apple.security.cs.allow-unsigned-executable-memory")]
+    pub allow_unsigned_executable_memory: bool,
+    #[serde(rename = "com.apple.security.cs.disable-library-validation")]
+    pub disable_library_validation: bool,
+}
+
+#[cfg(target_os = "macos")]
+impl Default for EntitlementsPlist {
+    fn default() -> Self {
+        Self {
+            allow_jit: true,
+            allow_unsigned_executable_memory: true,
+            disable_library_validation: true,
+        }
+    }
+}
+
 #[cfg(target_os = "macos")]
 impl AppInfoPlist {
     pub fn new(exec_name: &str, is_helper: bool) -> Self {
@@ -142,6 +167,20 @@ impl FrameworkInfoPlist {
     }
 }
 
+/// Options shared by the bundle commands
+pub struct BundleOptions<'a> {
+    pub release: bool,
+    pub target_dir: Option<&'a Path>,
+    /// macOS code signing identity
+    pub sign: Option<&'a str>,
+    /// Strip symbols from the gdcef library and helper
+    pub strip: bool,
+    /// Where stripped debug symbols are kept
+    pub debug_symbols_dir: Option<&'a Path>,
+    /// Locales to keep; empty keeps all of them
+    pub locales: &'a [String],
+}
+
 pub fn copy_directory(src: &Path, dst: &Path) -> Result<(), Box<dyn std::error::Error>> {
     fs::create_dir_all(dst)?;
     for entry in fs::read_dir(src)? {
@@ -170,6 +209,18 @@ pub fn run_cargo(args: &[&str]) -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+pub fn run_command(command: &mut Command) -> Result<(), Box<dyn std::error::Error>> {
+    let status = command
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()?;
+
+    if !status.success() {
+        return Err(format!("{:?} failed with status: {}", command, status).into());
+    }
+    Ok(())
+}
+
 pub fn get_target_dir(release: bool, custom_target_dir: Option<&Path>) -> PathBuf {
     let profile = if release { "release" } else { "debug" };
     let base = custom_target_dir.map(PathBuf::from).unwrap_or_else(|| {
@@ -314,3 +365,122 @@ pub fn deploy_bundle_to_addon(
 
     Ok(())
 }
+
+/// Chromium falls back to this locale, so it is never pruned
+const FALLBACK_LOCALE: &str = "en-US";
+
+fn normalize_locale(locale: &str) -> String {
+    locale.replace('_', "-").to_ascii_lowercase()
+}
+
+/// Returns whether the locale `name` (`zh-CN` on Windows and Linux, `zh_CN` or
+/// `en` on macOS) is one of `keep`. A language without a region matches any
+/// region of that language, as macOS ships `en.lproj` for `en-US`.
+fn keep_locale(name: &str, keep: &[String]) -> bool {
+    let name = normalize_locale(name);
+    std::iter::once(FALLBACK_LOCALE)
+        .chain(keep.iter().map(String::as_str))
+        .map(normalize_locale)
+        .any(|locale| {
+            locale == name
+                || (!name.contains('-') && locale.split('-').next() == Some(name.as_str()))
+        })
+}
+
+/// Removes the CEF locale data under `dir` that is not in `keep`: `.pak`
+/// files in `locales` directories and macOS `.lproj` directories. Returns the
+/// number of bytes removed.
+pub fn prune_locales(dir: &Path, keep: &[String]) -> Result<u64, Box<dyn std::error::Error>> {
+    if keep.is_empty() || !dir.exists() {
+        return Ok(0);
+    }
+
+    let in_locales_dir = dir.file_name().is_some_and(|name| name == "locales");
+    let mut removed = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+
+        if entry.file_type()?.is_dir() {
+            match file_name.strip_suffix(".lproj") {
+                Some(locale) if !keep_locale(locale, keep) => {
+                    removed += directory_size(&path)?;
+                    fs::remove_dir_all(&path)?;
+                }
+                Some(_) => {}
+                None => removed += prune_locales(&path, keep)?,
+            }
+        } else if in_locales_dir
+            && let Some(locale) = file_name.strip_suffix(".pak")
+            && !keep_locale(locale, keep)
+        {
+            removed += entry.metadata()?.len();
+            fs::remove_file(&path)?;
+        }
+    }
+
+    Ok(removed)
+}
+
+fn directory_size(dir: &Path) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut size = 0;
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        size += if entry.file_type()?.is_dir() {
+            directory_size(&entry.path())?
+        } else {
+            entry.metadata()?.len()
+        };
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keep_locale() {
+        let keep = ["zh-CN".to_string(), "pt_BR".to_string()];
+        assert!(keep_locale("zh-CN", &keep));
+        assert!(keep_locale("zh_CN", &keep));
+        assert!(keep_locale("pt-BR", &keep));
+        assert!(keep_locale("en-US", &keep));
+        assert!(keep_locale("en", &keep));
+        assert!(keep_locale("zh", &keep));
+        assert!(!keep_locale("zh-TW", &keep));
+        assert!(!keep_locale("en-GB", &keep));
+        assert!(!keep_locale("fr", &keep));
+    }
+
+    #[test]
+    fn test_prune_locales() {
+        let dir = std::env::temp_dir().join(format!("xtask-locales-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let locales = dir.join("locales");
+        let resources = dir.join("Chromium Embedded Framework.framework/Resources");
+        fs::create_dir_all(&locales).unwrap();
+        fs::create_dir_all(resources.join("en.lproj")).unwrap();
+        fs::create_dir_all(resources.join("fr.lproj")).unwrap();
+        fs::write(resources.join("fr.lproj/locale.pak"), "abcd").unwrap();
+        fs::write(resources.join("resources.pak"), "").unwrap();
+        for locale in ["en-US", "de", "fr"] {
+            fs::write(locales.join(format!("{}.pak", locale)), "ab").unwrap();
+        }
+        fs::write(dir.join("resources.pak"), "").unwrap();
+
+        let keep = ["de".to_string()];
+        assert_eq!(prune_locales(&dir, &keep).unwrap(), 6);
+        assert!(locales.join("en-US.pak").exists());
+        assert!(locales.join("de.pak").exists());
+        assert!(!locales.join("fr.pak").exists());
+        assert!(resources.join("en.lproj").exists());
+        assert!(!resources.join("fr.lproj").exists());
+        assert!(resources.join("resources.pak").exists());
+        assert!(dir.join("resources.pak").exists());
+
+        assert_eq!(prune_locales(&dir, &[]).unwrap(), 0);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,7 +1,9 @@
 use crate::bundle_common::{
-    FrameworkInfoPlist, deploy_bundle_to_addon, get_target_dir, get_target_dir_for_target,
-    run_cargo, run_lipo,
+    BundleOptions, FrameworkInfoPlist, deploy_bundle_to_addon, get_target_dir,
+    get_target_dir_for_target, run_cargo, run_lipo,
 };
+use crate::codesign::sign_framework;
+use crate::strip::strip_symbols;
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -51,7 +53,10 @@ fn bundle(
     Ok(fmwk_path)
 }
 
-pub fn run(release: bool, target_dir: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+pub fn run(options: &BundleOptions) -> Result<(), Box<dyn std::error::Error>> {
+    let release = options.release;
+    let target_dir = options.target_dir;
+
     let mut cargo_args_arm64 = vec![
         "build",
         "--lib",
@@ -87,9 +92,19 @@ pub fn run(release: bool, target_dir: Option<&Path>) -> Result<(), Box<dyn std::
     let universal_dylib = output_dir.join("libgdcef_universal.dylib");
 
     run_lipo(&dylib_arm64, &dylib_x64, &universal_dylib)?;
+    if options.strip {
+        strip_symbols(
+            &universal_dylib,
+            "libgdcef.dylib",
+            options.debug_symbols_dir,
+        )?;
+    }
 
     let fmwk_path = bundle(&output_dir, &universal_dylib)?;
     fs::remove_file(&universal_dylib)?;
+    if let Some(identity) = options.sign {
+        sign_framework(&fmwk_path, identity)?;
+    }
     deploy_bundle_to_addon(&fmwk_path, PLATFORM_TARGET)?;
 
     Ok(())
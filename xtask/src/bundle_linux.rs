@@ -1,8 +1,11 @@
 //! Linux bundling - copies CEF assets alongside the built binaries
 
 use crate::bundle_common::{
-    copy_directory, deploy_to_addon, get_cef_dir, get_target_dir, run_cargo,
+    BundleOptions, copy_directory, deploy_to_addon, get_addon_bin_dir, get_cef_dir, get_target_dir,
+    prune_locales, run_cargo,
 };
+use crate::fetch_cef::ensure_cef_dir;
+use crate::strip::strip_symbols;
 use std::fs;
 use std::path::Path;
 use std::process::{Command, Stdio};
@@ -34,8 +37,7 @@ const CEF_FILES: &[&str] = &[
 const CEF_DIRS: &[&str] = &["locales"];
 
 fn copy_cef_assets(target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let cef_dir = get_cef_dir()
-        .ok_or("CEF directory not found. Please set CEF_PATH environment variable.")?;
+    let cef_dir = ensure_cef_dir(get_cef_dir(), PLATFORM_TARGET)?;
 
     println!("Copying CEF assets from: {}", cef_dir.display());
 
@@ -122,29 +124,50 @@ const DEPLOY_FILES: &[&str] = &[
 /// Directories to deploy to the addon directory
 const DEPLOY_DIRS: &[&str] = &["locales"];
 
-fn bundle(target_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+/// Binaries built from this workspace, stripped with `--strip`
+const WORKSPACE_BINARIES: &[&str] = &["libgdcef.so", "gdcef_helper"];
+
+fn bundle(target_dir: &Path, options: &BundleOptions) -> Result<(), Box<dyn std::error::Error>> {
     copy_cef_assets(target_dir)?;
     strip_cef_binaries(target_dir)?;
     deploy_to_addon(target_dir, PLATFORM_TARGET, DEPLOY_FILES, DEPLOY_DIRS)?;
+
+    // Work on the deployed copies so the cargo outputs keep their symbols
+    let addon_bin_dir = get_addon_bin_dir(PLATFORM_TARGET);
+    if options.strip {
+        println!("Stripping workspace binaries...");
+        for binary in WORKSPACE_BINARIES {
+            strip_symbols(
+                &addon_bin_dir.join(binary),
+                binary,
+                options.debug_symbols_dir,
+            )?;
+        }
+    }
+    let removed = prune_locales(&addon_bin_dir.join("locales"), options.locales)?;
+    if removed > 0 {
+        println!("  Pruned locales: {} bytes", removed);
+    }
+
     println!("Linux bundle complete: {}", target_dir.display());
     Ok(())
 }
 
-pub fn run(release: bool, target_dir: Option<&Path>) -> Result<(), Box<dyn std::error::Error>> {
+pub fn run(options: &BundleOptions) -> Result<(), Box<dyn std::error::Error>> {
     let mut cargo_args = vec!["build", "--lib", "--package", "gdcef"];
-    if release {
+    if options.release {
         cargo_args.push("--release");
     }
     run_cargo(&cargo_args)?;
 
     let mut cargo_args = vec!["build", "--bin", "gdcef_helper"];
-    if release {
+    if options.release {
         cargo_args.push("--release");
     }
     run_cargo(&cargo_args)?;
 
-    let target_dir = get_target_dir(release, target_dir);
-    bundle(&target_dir)?;
+    let target_dir = get_target_dir(options.release, options.target_dir);
+    bundle(&target_dir, options)?;
 
     Ok(())
 }
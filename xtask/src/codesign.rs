@@ -0,0 +1,151 @@
+//! macOS code signing and notarization
+//!
+//! Bundles are signed inside-out with the hardened runtime: nested libraries
+//! first, then the frameworks and helper apps that contain them, then the
+//! outer bundle. `--deep` is only used to verify, never to sign, since it
+//! would apply the same entitlements to every nested binary.
+
+use crate::bundle_common::{EntitlementsPlist, run_command};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Ad-hoc signing identity, useful for local testing.
+const AD_HOC_IDENTITY: &str = "-";
+
+fn codesign(
+    path: &Path,
+    identity: &str,
+    entitlements: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut command = Command::new("codesign");
+    command.args(["--force", "--options", "runtime", "--sign", identity]);
+    // Ad-hoc signatures cannot be timestamped
+    if identity != AD_HOC_IDENTITY {
+        command.arg("--timestamp");
+    }
+    if let Some(entitlements) = entitlements {
+        command.arg("--entitlements").arg(entitlements);
+    }
+    run_command(command.arg(path))
+}
+
+/// Lists the entries of `dir` with the given extension, sorted by name.
+fn entries_with_extension(dir: &Path, extension: &str) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == extension))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn write_entitlements(dir: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = dir.join("entitlements.plist");
+    plist::to_file_xml(&path, &EntitlementsPlist::default())?;
+    Ok(path)
+}
+
+/// Checks the signature of `path` and everything nested in it.
+pub fn verify(path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    run_command(
+        Command::new("codesign")
+            .args(["--verify", "--deep", "--strict", "--verbose=2"])
+            .arg(path),
+    )
+}
+
+/// Signs `Godot CEF.app`: the CEF frameworks, the helper apps, then the app.
+pub fn sign_app(app_path: &Path, identity: &str) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Signing {} as {}", app_path.display(), identity);
+
+    let work_dir = app_path.parent().ok_or("Invalid app path")?;
+    let entitlements = write_entitlements(work_dir)?;
+    let frameworks_dir = app_path.join("Contents/Frameworks");
+
+    for framework in entries_with_extension(&frameworks_dir, "framework") {
+        for library in entries_with_extension(&framework.join("Libraries"), "dylib") {
+            codesign(&library, identity, None)?;
+        }
+        codesign(&framework, identity, None)?;
+    }
+
+    for helper in entries_with_extension(&frameworks_dir, "app") {
+        codesign(&helper, identity, Some(&entitlements))?;
+    }
+
+    codesign(app_path, identity, Some(&entitlements))?;
+    fs::remove_file(&entitlements)?;
+
+    verify(app_path)
+}
+
+/// Signs `Godot CEF.framework`: the GDExtension dylib, then the framework.
+pub fn sign_framework(fmwk_path: &Path, identity: &str) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Signing {} as {}", fmwk_path.display(), identity);
+
+    for library in entries_with_extension(fmwk_path, "dylib") {
+        codesign(&library, identity, None)?;
+    }
+    codesign(fmwk_path, identity, None)?;
+
+    verify(fmwk_path)
+}
+
+/// Submits each bundle to Apple's notary service, waits for the result and
+/// staples the ticket to app bundles.
+///
+/// The credentials are read by `notarytool` from `keychain_profile`, so no
+/// secret appears on a command line.
+pub fn notarize(
+    paths: &[PathBuf],
+    keychain_profile: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for path in paths {
+        verify(path)?;
+
+        let name = path
+            .file_name()
+            .ok_or("Invalid bundle path")?
+            .to_string_lossy()
+            .into_owned();
+        let zip_path = std::env::temp_dir().join(format!("{}.zip", name));
+        if zip_path.exists() {
+            fs::remove_file(&zip_path)?;
+        }
+
+        run_command(
+            Command::new("ditto")
+                .args(["-c", "-k", "--keepParent"])
+                .arg(path)
+                .arg(&zip_path),
+        )?;
+
+        println!("Submitting {} for notarization...", name);
+        let result = run_command(
+            Command::new("xcrun")
+                .arg("notarytool")
+                .arg("submit")
+                .arg(&zip_path)
+                .args(["--keychain-profile", keychain_profile])
+                .arg("--wait"),
+        );
+        fs::remove_file(&zip_path)?;
+        result?;
+
+        // Tickets can only be stapled to apps; Gatekeeper fetches the ticket
+        // of other bundles online
+        if path.extension().is_some_and(|ext| ext == "app") {
+            run_command(Command::new("xcrun").args(["stapler", "staple"]).arg(path))?;
+        } else {
+            println!("  Skipping staple for {} (not an app bundle)", name);
+        }
+    }
+
+    Ok(())
+}
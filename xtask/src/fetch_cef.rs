@@ -0,0 +1,439 @@
+//! Fetching CEF - downloads, verifies and stages the CEF binary distribution
+//!
+//! The minimal distribution for each platform is downloaded from the Spotify
+//! CEF build server into `target/cef/downloads`, verified against the SHA-1
+//! published in the build index, and extracted with `Release/` and
+//! `Resources/` flattened into a single directory, the layout the bundle
+//! commands read from `CEF_PATH` (`CEF_PATH_ARM64` / `CEF_PATH_X64` on macOS).
+
+use crate::bundle_common::{copy_directory, run_command};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// CEF version the `cef` crate in the workspace is built against.
+pub const CEF_VERSION: &str = "144.0.12";
+
+const CEF_BUILDS_URL: &str = "https://cef-builds.spotifycdn.com";
+
+/// File written next to the extracted binaries describing the archive they
+/// came from.
+const ARCHIVE_JSON: &str = "archive.json";
+
+/// An archive listed in the CEF build index.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CefFile {
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub name: String,
+    pub sha1: String,
+}
+
+#[derive(Deserialize)]
+struct CefVersion {
+    cef_version: String,
+    files: Vec<CefFile>,
+}
+
+#[derive(Deserialize)]
+struct CefPlatform {
+    versions: Vec<CefVersion>,
+}
+
+/// Returns the CEF build server name of a Rust target triple.
+pub fn cef_platform(target: &str) -> Option<&'static str> {
+    match target {
+        "x86_64-unknown-linux-gnu" => Some("linux64"),
+        "aarch64-unknown-linux-gnu" => Some("linuxarm64"),
+        "x86_64-pc-windows-msvc" => Some("windows64"),
+        "aarch64-pc-windows-msvc" => Some("windowsarm64"),
+        "x86_64-apple-darwin" => Some("macosx64"),
+        "aarch64-apple-darwin" => Some("macosarm64"),
+        _ => None,
+    }
+}
+
+/// Targets the bundle command of the current platform needs binaries for.
+pub fn host_targets() -> Vec<&'static str> {
+    if cfg!(target_os = "macos") {
+        vec!["aarch64-apple-darwin", "x86_64-apple-darwin"]
+    } else if cfg!(target_os = "windows") {
+        vec!["x86_64-pc-windows-msvc"]
+    } else {
+        vec!["x86_64-unknown-linux-gnu"]
+    }
+}
+
+fn workspace_target_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask should be in workspace")
+        .join("target")
+}
+
+/// Directory the binaries for `target` are extracted to when no `CEF_PATH`
+/// is set.
+pub fn default_cef_dir(target: &str) -> PathBuf {
+    workspace_target_dir().join("cef").join(target)
+}
+
+fn download_dir() -> PathBuf {
+    workspace_target_dir().join("cef/downloads")
+}
+
+/// File or directory whose presence means the binaries for `target` are staged.
+fn marker_file(target: &str) -> &'static str {
+    if target.contains("apple-darwin") {
+        "Chromium Embedded Framework.framework"
+    } else if target.contains("windows") {
+        "libcef.dll"
+    } else {
+        "libcef.so"
+    }
+}
+
+/// Returns whether the archive `name` belongs to CEF `version`.
+fn is_version(name: &str, version: &str) -> bool {
+    name.starts_with(&format!("cef_binary_{version}+"))
+}
+
+/// Finds the minimal distribution of `version` for `platform` in the build index.
+fn find_minimal_archive(
+    index: &HashMap<String, CefPlatform>,
+    platform: &str,
+    version: &str,
+) -> Option<CefFile> {
+    index
+        .get(platform)?
+        .versions
+        .iter()
+        .find(|entry| {
+            entry.cef_version == version || entry.cef_version.starts_with(&format!("{version}+"))
+        })?
+        .files
+        .iter()
+        .find(|file| file.file_type == "minimal")
+        .cloned()
+}
+
+/// Returns the archive the binaries in `dir` were extracted from, if known.
+fn installed_archive(dir: &Path) -> Option<CefFile> {
+    let json = fs::read_to_string(dir.join(ARCHIVE_JSON)).ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn download(url: &str, dst: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Downloading: {}", url);
+    if let Some(parent) = dst.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    // Download to a temporary file so an interrupted download is not reused
+    let partial = PathBuf::from(format!("{}.partial", dst.display()));
+    run_command(
+        Command::new("curl")
+            .args(["--fail", "--location", "--retry", "3", "--output"])
+            .arg(&partial)
+            .arg(url),
+    )?;
+    fs::rename(&partial, dst)?;
+    Ok(())
+}
+
+fn sha1_file(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = sha1_smol::Sha1::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(hasher.digest().to_string())
+}
+
+fn verify_sha1(path: &Path, expected: &str) -> Result<(), Box<dyn std::error::Error>> {
+    let actual = sha1_file(path)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(format!(
+            "SHA-1 mismatch for {}: expected {}, got {}",
+            path.display(),
+            expected,
+            actual
+        )
+        .into());
+    }
+    println!("  Verified SHA-1: {}", actual);
+    Ok(())
+}
+
+/// Moves the contents of `src` into `dst`, replacing existing entries.
+fn move_contents(src: &Path, dst: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
+        let dst_path = dst.join(entry.file_name());
+        if dst_path.is_dir() {
+            fs::remove_dir_all(&dst_path)?;
+        } else if dst_path.exists() {
+            fs::remove_file(&dst_path)?;
+        }
+
+        if fs::rename(entry.path(), &dst_path).is_err() {
+            if entry.file_type()?.is_dir() {
+                copy_directory(&entry.path(), &dst_path)?;
+            } else {
+                fs::copy(entry.path(), &dst_path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Extracts `archive` into `output`, flattening `Release/` and `Resources/`.
+fn extract(
+    archive: &Path,
+    file: &CefFile,
+    output: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("Extracting to: {}", output.display());
+
+    let staging = PathBuf::from(format!("{}.extracting", output.display()));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+    run_command(
+        Command::new("tar")
+            .arg("-xjf")
+            .arg(archive)
+            .arg("-C")
+            .arg(&staging),
+    )?;
+
+    // The archive contains a single `cef_binary_<version>_<platform>_minimal` directory
+    let root = fs::read_dir(&staging)?
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .find(|path| path.is_dir())
+        .ok_or("CEF archive is empty")?;
+
+    fs::create_dir_all(output)?;
+    for dir in ["Release", "Resources"] {
+        let src = root.join(dir);
+        if src.exists() {
+            move_contents(&src, output)?;
+            fs::remove_dir_all(&src)?;
+        }
+    }
+    move_contents(&root, output)?;
+    fs::remove_dir_all(&staging)?;
+
+    fs::write(
+        output.join(ARCHIVE_JSON),
+        serde_json::to_string_pretty(file)?,
+    )?;
+    Ok(())
+}
+
+/// Downloads (or takes from `archive`) and stages the binaries for `target`.
+///
+/// An archive is verified against the `.sha1` file next to it, which must
+/// exist unless `no_verify` is set.
+fn fetch(
+    version: &str,
+    target: &str,
+    archive: Option<&Path>,
+    no_verify: bool,
+    output: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let platform = cef_platform(target).ok_or_else(|| format!("Unsupported target: {}", target))?;
+
+    let (archive_path, file) = match archive {
+        Some(archive) => {
+            let name = archive
+                .file_name()
+                .ok_or("Invalid archive path")?
+                .to_string_lossy()
+                .into_owned();
+            if !is_version(&name, version) {
+                println!("  Warning: {} does not look like CEF {}", name, version);
+            }
+
+            // The build server publishes `<archive>.sha1` next to each archive
+            let mut sha1_path = archive.as_os_str().to_owned();
+            sha1_path.push(".sha1");
+            let sha1_path = PathBuf::from(sha1_path);
+            let sha1 = match fs::read_to_string(&sha1_path) {
+                Ok(contents) => {
+                    let sha1 = contents.split_whitespace().next().unwrap_or_default();
+                    verify_sha1(archive, sha1)?;
+                    sha1.to_string()
+                }
+                Err(_) if no_verify => {
+                    println!(
+                        "  Warning: {} not found, skipping verification (--no-verify)",
+                        sha1_path.display()
+                    );
+                    sha1_file(archive)?
+                }
+                Err(e) => {
+                    return Err(format!(
+                        "Cannot verify {}: {}: {}. Download the .sha1 file published \
+                         next to the archive, or pass --no-verify",
+                        archive.display(),
+                        sha1_path.display(),
+                        e
+                    )
+                    .into());
+                }
+            };
+
+            let file = CefFile {
+                file_type: "minimal".to_string(),
+                name,
+                sha1,
+            };
+            (archive.to_path_buf(), file)
+        }
+        None => {
+            let index_path = download_dir().join("index.json");
+            download(&format!("{}/index.json", CEF_BUILDS_URL), &index_path)?;
+            let index: HashMap<String, CefPlatform> =
+                serde_json::from_str(&fs::read_to_string(&index_path)?)?;
+            let file = find_minimal_archive(&index, platform, version)
+                .ok_or_else(|| format!("CEF {} is not available for {}", version, platform))?;
+
+            let archive_path = download_dir().join(&file.name);
+            let cached = archive_path.exists() && verify_sha1(&archive_path, &file.sha1).is_ok();
+            if cached {
+                println!("Using cached archive: {}", archive_path.display());
+            } else {
+                let url = format!("{}/{}", CEF_BUILDS_URL, file.name.replace('+', "%2B"));
+                download(&url, &archive_path)?;
+                verify_sha1(&archive_path, &file.sha1)?;
+            }
+            (archive_path, file)
+        }
+    };
+
+    extract(&archive_path, &file, output)?;
+    println!("Staged {} for {}", file.name, target);
+    Ok(())
+}
+
+/// Returns the CEF directory for `target`, fetching the binaries first if
+/// `configured` (the `CEF_PATH*` variable) or the default directory lacks them.
+pub fn ensure_cef_dir(
+    configured: Option<PathBuf>,
+    target: &str,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let cef_dir = configured.unwrap_or_else(|| default_cef_dir(target));
+
+    if !cef_dir.join(marker_file(target)).exists() {
+        println!(
+            "CEF binaries for {} not found in {}, fetching CEF {}",
+            target,
+            cef_dir.display(),
+            CEF_VERSION
+        );
+        fetch(CEF_VERSION, target, None, false, &cef_dir)?;
+    }
+
+    match installed_archive(&cef_dir) {
+        Some(file) => {
+            println!("Using CEF {} from {}", file.name, cef_dir.display());
+            if !is_version(&file.name, CEF_VERSION) {
+                println!(
+                    "  Warning: expected CEF {}, run `cargo xtask fetch-cef` to update",
+                    CEF_VERSION
+                );
+            }
+        }
+        None => println!("Using CEF (unknown version) from {}", cef_dir.display()),
+    }
+
+    Ok(cef_dir)
+}
+
+pub fn run(
+    version: Option<&str>,
+    platform: Option<&str>,
+    archive: Option<&Path>,
+    no_verify: bool,
+    output: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let version = version.unwrap_or(CEF_VERSION);
+    let targets = match platform {
+        Some(target) => vec![target],
+        None => host_targets(),
+    };
+
+    if (archive.is_some() || output.is_some()) && targets.len() > 1 {
+        return Err("--archive and --output need a single --platform".into());
+    }
+
+    for target in targets {
+        let output = output
+            .map(PathBuf::from)
+            .unwrap_or_else(|| default_cef_dir(target));
+        fetch(version, target, archive, no_verify, &output)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index() -> HashMap<String, CefPlatform> {
+        serde_json::from_str(
+            r#"{
+                "linux64": {
+                    "versions": [
+                        {
+                            "cef_version": "144.0.13+gdef+chromium-144.0.7559.97",
+                            "files": [
+                                {"type": "minimal", "name": "cef_binary_144.0.13+gdef+chromium-144.0.7559.97_linux64_minimal.tar.bz2", "sha1": "bbb"}
+                            ]
+                        },
+                        {
+                            "cef_version": "144.0.12+gabc+chromium-144.0.7559.59",
+                            "files": [
+                                {"type": "standard", "name": "cef_binary_144.0.12+gabc+chromium-144.0.7559.59_linux64.tar.bz2", "sha1": "aaa"},
+                                {"type": "minimal", "name": "cef_binary_144.0.12+gabc+chromium-144.0.7559.59_linux64_minimal.tar.bz2", "sha1": "ccc", "size": 1}
+                            ]
+                        }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_find_minimal_archive() {
+        let file = find_minimal_archive(&index(), "linux64", "144.0.12").unwrap();
+        assert_eq!(file.sha1, "ccc");
+        assert!(is_version(&file.name, "144.0.12"));
+        assert!(!is_version(&file.name, "144.0.1"));
+
+        assert!(find_minimal_archive(&index(), "linux64", "144.0.1").is_none());
+        assert!(find_minimal_archive(&index(), "windows64", "144.0.12").is_none());
+    }
+
+    #[test]
+    fn test_cef_platform() {
+        assert_eq!(cef_platform("aarch64-apple-darwin"), Some("macosarm64"));
+        assert_eq!(cef_platform("x86_64-pc-windows-msvc"), Some("windows64"));
+        assert_eq!(cef_platform("wasm32-unknown-unknown"), None);
+        for target in host_targets() {
+            assert!(cef_platform(target).is_some());
+        }
+    }
+}
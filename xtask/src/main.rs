@@ -5,6 +5,10 @@
 //!   cargo xtask bundle-app [--release]       # Bundle helper app (macOS only)
 //!   cargo xtask bundle-framework [--release] # Bundle framework (macOS only)
 //!   cargo xtask pack <artifacts> <output>    # Pack CI artifacts into distributable addon
+//!   cargo xtask fetch-cef [--version <v>]    # Download CEF binaries into target/cef/
+//!   cargo xtask notarize [<bundles>]         # Notarize the signed macOS bundles
+//!   cargo xtask verify <addon_dir>           # Check a packed addon against its manifest
+//!   cargo xtask size-report [<addon_dir>]    # Show the size of each part of the addon
 
 #[cfg(target_os = "macos")]
 mod bundle_app;
@@ -15,9 +19,16 @@ mod bundle_framework;
 mod bundle_linux;
 #[cfg(target_os = "windows")]
 mod bundle_windows;
+#[cfg(target_os = "macos")]
+mod codesign;
+mod fetch_cef;
+mod manifest;
 mod pack;
+mod size_report;
+mod strip;
 
-use clap::{Parser, Subcommand};
+use bundle_common::BundleOptions;
+use clap::{Args, Parser, Subcommand};
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -32,35 +43,28 @@ struct Cli {
 enum Commands {
     /// Bundle for the current platform and deploy to addons/godot_cef/bin/
     Bundle {
-        /// Build in release mode
-        #[arg(long, short)]
-        release: bool,
+        #[command(flatten)]
+        args: BundleArgs,
 
-        /// Custom target directory
-        #[arg(long)]
-        target_dir: Option<PathBuf>,
+        /// Locales to keep, e.g. "en-US,zh-CN" (en-US is always kept)
+        #[arg(long, value_delimiter = ',')]
+        minimal_locales: Vec<String>,
     },
 
     /// Bundle the helper app for macOS
     BundleApp {
-        /// Build in release mode
-        #[arg(long, short)]
-        release: bool,
+        #[command(flatten)]
+        args: BundleArgs,
 
-        /// Custom target directory
-        #[arg(long)]
-        target_dir: Option<PathBuf>,
+        /// Locales to keep, e.g. "en-US,zh-CN" (en-US is always kept)
+        #[arg(long, value_delimiter = ',')]
+        minimal_locales: Vec<String>,
     },
 
     /// Bundle the GDExtension framework for macOS
     BundleFramework {
-        /// Build in release mode
-        #[arg(long, short)]
-        release: bool,
-
-        /// Custom target directory
-        #[arg(long)]
-        target_dir: Option<PathBuf>,
+        #[command(flatten)]
+        args: BundleArgs,
     },
 
     /// Pack CI artifacts from multiple platforms into a distributable addon
@@ -76,56 +80,158 @@ enum Commands {
         /// Path to addon source files (gdextension, icons)
         #[arg(long)]
         addon_src: Option<PathBuf>,
+
+        /// Include macOS bundles that are not code signed
+        #[arg(long)]
+        allow_unsigned: bool,
+
+        /// Locales to keep, e.g. "en-US,zh-CN" (en-US is always kept)
+        #[arg(long, value_delimiter = ',')]
+        minimal_locales: Vec<String>,
+    },
+
+    /// Download, verify and extract the CEF binaries the bundle commands use
+    FetchCef {
+        /// CEF version (defaults to the version the workspace is built against)
+        #[arg(long)]
+        version: Option<String>,
+
+        /// Rust target triple (defaults to the targets of the current platform)
+        #[arg(long)]
+        platform: Option<String>,
+
+        /// Use a pre-downloaded archive instead of downloading (offline mode)
+        #[arg(long)]
+        archive: Option<PathBuf>,
+
+        /// Stage an --archive that has no `.sha1` file next to it
+        #[arg(long, requires = "archive")]
+        no_verify: bool,
+
+        /// Extraction directory (defaults to target/cef/<platform>)
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+    },
+
+    /// Submit signed macOS bundles for notarization and staple the tickets
+    Notarize {
+        /// Keychain profile holding the notary credentials, created with
+        /// `xcrun notarytool store-credentials`
+        #[arg(long, env = "APPLE_NOTARY_PROFILE")]
+        keychain_profile: String,
+
+        /// Bundles to notarize (defaults to the bundles in addons/godot_cef/bin/universal-apple-darwin)
+        paths: Vec<PathBuf>,
+    },
+
+    /// Check the files of a packed addon against its manifest.json
+    Verify {
+        /// Addon directory containing manifest.json
+        addon_dir: PathBuf,
+    },
+
+    /// Show how much each part of a bundled addon weighs
+    SizeReport {
+        /// Addon directory (defaults to addons/godot_cef)
+        addon_dir: Option<PathBuf>,
+
+        /// Number of files to list, largest first
+        #[arg(long, default_value_t = 25)]
+        top: usize,
+
+        /// Also write the full report as JSON to this path
+        #[arg(long)]
+        json: Option<PathBuf>,
     },
 }
 
+#[derive(Args)]
+struct BundleArgs {
+    /// Build in release mode
+    #[arg(long, short)]
+    release: bool,
+
+    /// Custom target directory
+    #[arg(long)]
+    target_dir: Option<PathBuf>,
+
+    /// Code signing identity for the macOS bundles (e.g. "Developer ID Application: ...")
+    #[arg(long, env = "APPLE_SIGNING_IDENTITY")]
+    sign: Option<String>,
+
+    /// Strip symbols from the gdcef library and helper
+    #[arg(long)]
+    strip: bool,
+
+    /// Keep the stripped debug symbols in this directory
+    #[arg(long, requires = "strip")]
+    debug_symbols_dir: Option<PathBuf>,
+}
+
+impl BundleArgs {
+    fn options<'a>(&'a self, locales: &'a [String]) -> BundleOptions<'a> {
+        BundleOptions {
+            release: self.release,
+            target_dir: self.target_dir.as_deref(),
+            sign: self.sign.as_deref(),
+            strip: self.strip,
+            debug_symbols_dir: self.debug_symbols_dir.as_deref(),
+            locales,
+        }
+    }
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let cli = Cli::parse();
 
     match cli.command {
         Commands::Bundle {
-            release,
-            target_dir,
+            args,
+            minimal_locales,
         } => {
+            let options = args.options(&minimal_locales);
+
             #[cfg(target_os = "macos")]
             {
-                bundle_app::run(release, target_dir.as_deref())?;
-                bundle_framework::run(release, target_dir.as_deref())?;
+                bundle_app::run(&options)?;
+                bundle_framework::run(&options)?;
             }
 
             #[cfg(target_os = "windows")]
             {
-                bundle_windows::run(release, target_dir.as_deref())?;
+                bundle_windows::run(&options)?;
             }
 
             #[cfg(target_os = "linux")]
             {
-                bundle_linux::run(release, target_dir.as_deref())?;
+                bundle_linux::run(&options)?;
+            }
+
+            #[cfg(not(target_os = "macos"))]
+            if options.sign.is_some() {
+                eprintln!("--sign is only supported on macOS, ignoring");
             }
         }
         Commands::BundleApp {
-            release,
-            target_dir,
+            args,
+            minimal_locales,
         } => {
             #[cfg(target_os = "macos")]
-            bundle_app::run(release, target_dir.as_deref())?;
+            bundle_app::run(&args.options(&minimal_locales))?;
 
             #[cfg(not(target_os = "macos"))]
             {
-                let _ = (release, target_dir);
+                let _ = (args, minimal_locales);
                 eprintln!("bundle-app is only supported on macOS");
             }
         }
-        Commands::BundleFramework {
-            release,
-            target_dir,
-        } => {
+        Commands::BundleFramework { args } => {
             #[cfg(target_os = "macos")]
-            bundle_framework::run(release, target_dir.as_deref())?;
+            bundle_framework::run(&args.options(&[]))?;
 
             #[cfg(not(target_os = "macos"))]
             {
-                let _ = (release, target_dir);
+                let _ = args;
                 eprintln!("bundle-framework is only supported on macOS");
             }
         }
@@ -133,8 +239,65 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             artifacts,
             output,
             addon_src,
+            allow_unsigned,
+            minimal_locales,
+        } => {
+            pack::run(
+                &artifacts,
+                &output,
+                addon_src.as_deref(),
+                allow_unsigned,
+                &minimal_locales,
+            )?;
+        }
+        Commands::FetchCef {
+            version,
+            platform,
+            archive,
+            no_verify,
+            output,
+        } => {
+            fetch_cef::run(
+                version.as_deref(),
+                platform.as_deref(),
+                archive.as_deref(),
+                no_verify,
+                output.as_deref(),
+            )?;
+        }
+        Commands::Notarize {
+            keychain_profile,
+            paths,
+        } => {
+            #[cfg(target_os = "macos")]
+            {
+                let paths = if paths.is_empty() {
+                    let bin_dir = bundle_common::get_addon_bin_dir("universal-apple-darwin");
+                    vec![
+                        bin_dir.join("Godot CEF.app"),
+                        bin_dir.join("Godot CEF.framework"),
+                    ]
+                } else {
+                    paths
+                };
+                codesign::notarize(&paths, &keychain_profile)?;
+            }
+
+            #[cfg(not(target_os = "macos"))]
+            {
+                let _ = (keychain_profile, paths);
+                eprintln!("notarize is only supported on macOS");
+            }
+        }
+        Commands::Verify { addon_dir } => {
+            manifest::verify(&addon_dir)?;
+        }
+        Commands::SizeReport {
+            addon_dir,
+            top,
+            json,
         } => {
-            pack::run(&artifacts, &output, addon_src.as_deref())?;
+            size_report::run(addon_dir.as_deref(), top, json.as_deref())?;
         }
     }
 
@@ -0,0 +1,264 @@
+//! Addon manifest - versions and per-file checksums of a packed addon
+//!
+//! `pack` writes `manifest.json` at the addon root; `verify` re-checks it
+//! against the installed files, e.g. when triaging a support request.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// Path relative to the addon root, with `/` separators.
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlatformManifest {
+    pub files: Vec<ManifestFile>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub addon_version: String,
+    pub cef_version: String,
+    pub git_commit: String,
+    pub build_date: String,
+    pub godot_minimum: String,
+    /// Keyed by platform target, e.g. `x86_64-unknown-linux-gnu`.
+    pub platforms: BTreeMap<String, PlatformManifest>,
+}
+
+fn sha256_file(path: &Path) -> Result<String, Box<dyn std::error::Error>> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+pub fn collect_files(
+    dir: &Path,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            collect_files(&entry.path(), files)?;
+        } else {
+            files.push(entry.path());
+        }
+    }
+    Ok(())
+}
+
+/// Returns `path` relative to `root` with `/` separators.
+pub fn relative_path(root: &Path, path: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Lists every file under `bin/<platform_target>` of the addon at `addon_dir`.
+pub fn platform_manifest(
+    addon_dir: &Path,
+    platform_target: &str,
+) -> Result<PlatformManifest, Box<dyn std::error::Error>> {
+    let mut paths = Vec::new();
+    collect_files(&addon_dir.join("bin").join(platform_target), &mut paths)?;
+    paths.sort();
+
+    let files = paths
+        .iter()
+        .map(|path| {
+            Ok(ManifestFile {
+                path: relative_path(addon_dir, path),
+                size: fs::metadata(path)?.len(),
+                sha256: sha256_file(path)?,
+            })
+        })
+        .collect::<Result<_, Box<dyn std::error::Error>>>()?;
+    Ok(PlatformManifest { files })
+}
+
+/// Commit the addon is built from: `GITHUB_SHA` in CI, otherwise `git rev-parse`.
+pub fn git_commit() -> String {
+    if let Ok(sha) = std::env::var("GITHUB_SHA") {
+        return sha;
+    }
+    Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// Formats seconds since the Unix epoch as an RFC 3339 UTC timestamp.
+fn format_utc(secs: u64) -> String {
+    let days = (secs / 86_400) as i64;
+    let secs_of_day = secs % 86_400;
+
+    // Civil date from days since 1970-01-01 (Howard Hinnant's algorithm)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        secs_of_day / 3_600,
+        secs_of_day % 3_600 / 60,
+        secs_of_day % 60
+    )
+}
+
+/// Build timestamp, honoring `SOURCE_DATE_EPOCH` for reproducible builds.
+pub fn build_date() -> String {
+    let secs = std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs())
+        });
+    format_utc(secs)
+}
+
+pub fn write(addon_dir: &Path, manifest: &Manifest) -> Result<(), Box<dyn std::error::Error>> {
+    let json = serde_json::to_string_pretty(manifest)?;
+    fs::write(addon_dir.join(MANIFEST_FILE), json + "\n")?;
+    Ok(())
+}
+
+/// Checks every file listed in the manifest of `addon_dir`. Returns an
+/// error describing the missing or modified files.
+pub fn verify(addon_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let manifest_path = addon_dir.join(MANIFEST_FILE);
+    let json = fs::read_to_string(&manifest_path)
+        .map_err(|e| format!("Failed to read {}: {}", manifest_path.display(), e))?;
+    let manifest: Manifest = serde_json::from_str(&json)?;
+
+    println!("Godot CEF {}", manifest.addon_version);
+    println!("  CEF: {}", manifest.cef_version);
+    println!("  Commit: {}", manifest.git_commit);
+    println!("  Built: {}", manifest.build_date);
+    println!("  Godot: {}+", manifest.godot_minimum);
+
+    let mut problems = 0;
+    for (platform, platform_manifest) in &manifest.platforms {
+        let mut platform_problems = 0;
+        for file in &platform_manifest.files {
+            let path = addon_dir.join(&file.path);
+            let problem = match fs::metadata(&path) {
+                Err(_) => Some("missing".to_string()),
+                Ok(metadata) if metadata.len() != file.size => {
+                    Some(format!("size {} (expected {})", metadata.len(), file.size))
+                }
+                Ok(_) if sha256_file(&path)? != file.sha256 => {
+                    Some("checksum mismatch".to_string())
+                }
+                Ok(_) => None,
+            };
+            if let Some(problem) = problem {
+                println!("  {}: {}", file.path, problem);
+                platform_problems += 1;
+            }
+        }
+
+        if platform_problems == 0 {
+            println!(
+                "  {}: OK ({} files)",
+                platform,
+                platform_manifest.files.len()
+            );
+        }
+        problems += platform_problems;
+    }
+
+    if problems > 0 {
+        return Err(format!("{} file(s) do not match the manifest", problems).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_utc() {
+        assert_eq!(format_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_utc(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_utc(1_760_700_645), "2025-10-17T11:30:45Z");
+    }
+
+    #[test]
+    fn test_platform_manifest_and_verify() {
+        let dir = std::env::temp_dir().join(format!("xtask-manifest-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let bin_dir = dir.join("bin/x86_64-unknown-linux-gnu");
+        fs::create_dir_all(bin_dir.join("locales")).unwrap();
+        fs::write(bin_dir.join("libgdcef.so"), "abc").unwrap();
+        fs::write(bin_dir.join("locales/en-US.pak"), "").unwrap();
+
+        let platform = platform_manifest(&dir, "x86_64-unknown-linux-gnu").unwrap();
+        assert_eq!(
+            platform.files[0],
+            ManifestFile {
+                path: "bin/x86_64-unknown-linux-gnu/libgdcef.so".to_string(),
+                size: 3,
+                sha256: "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+                    .to_string(),
+            }
+        );
+        assert_eq!(
+            platform.files[1].path,
+            "bin/x86_64-unknown-linux-gnu/locales/en-US.pak"
+        );
+
+        let manifest = Manifest {
+            addon_version: "1.0.0".to_string(),
+            cef_version: "144.0.12".to_string(),
+            git_commit: "abc".to_string(),
+            build_date: format_utc(0),
+            godot_minimum: "4.5".to_string(),
+            platforms: BTreeMap::from([("x86_64-unknown-linux-gnu".to_string(), platform)]),
+        };
+        write(&dir, &manifest).unwrap();
+        assert!(verify(&dir).is_ok());
+
+        fs::write(bin_dir.join("libgdcef.so"), "abd").unwrap();
+        assert!(verify(&dir).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,14 +1,52 @@
 //! Pack command - assembles all platform artifacts into a single Godot addon
 
-use crate::bundle_common::copy_directory;
+use crate::bundle_common::{copy_directory, prune_locales};
+use crate::fetch_cef::CEF_VERSION;
+use crate::manifest::{self, Manifest};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::Path;
 
-/// Platform targets and their artifact directory names
-const PLATFORMS: &[(&str, &str)] = &[
-    ("universal-apple-darwin", "gdcef-universal-apple-darwin"),
-    ("x86_64-pc-windows-msvc", "gdcef-x86_64-pc-windows-msvc"),
-    ("x86_64-unknown-linux-gnu", "gdcef-x86_64-unknown-linux-gnu"),
+const MACOS_ARTIFACT: &str = "gdcef-universal-apple-darwin";
+
+const GDEXTENSION_FILE: &str = "godot_cef.gdextension";
+
+/// A platform the addon ships binaries for
+struct Platform {
+    /// Directory under `bin/`
+    target: &'static str,
+    /// CI artifact directory name
+    artifact: &'static str,
+    /// Godot feature tag used in the `.gdextension` file
+    feature: &'static str,
+    /// GDExtension library inside the platform directory
+    library: &'static str,
+    /// Where exported games place the dependencies ("" = next to the executable)
+    dependency_target: &'static str,
+}
+
+const PLATFORMS: &[Platform] = &[
+    Platform {
+        target: "universal-apple-darwin",
+        artifact: MACOS_ARTIFACT,
+        feature: "macos",
+        library: "Godot CEF.framework",
+        dependency_target: "Contents/Frameworks",
+    },
+    Platform {
+        target: "x86_64-pc-windows-msvc",
+        artifact: "gdcef-x86_64-pc-windows-msvc",
+        feature: "windows.x86_64",
+        library: "gdcef.dll",
+        dependency_target: "",
+    },
+    Platform {
+        target: "x86_64-unknown-linux-gnu",
+        artifact: "gdcef-x86_64-unknown-linux-gnu",
+        feature: "linux.x86_64",
+        library: "libgdcef.so",
+        dependency_target: "",
+    },
 ];
 
 fn copy_platform_artifacts(
@@ -35,13 +73,47 @@ fn copy_platform_artifacts(
     Ok(true)
 }
 
-fn copy_addon_files(addon_src: &Path, output_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
-    let gdext_src = addon_src.join("godot_cef.gdextension");
-    if gdext_src.exists() {
-        fs::copy(&gdext_src, output_dir.join("godot_cef.gdextension"))?;
-        println!("  Copied: godot_cef.gdextension");
+/// macOS bundles that must be code signed before they are distributed
+const MACOS_BUNDLES: &[&str] = &["Godot CEF.app", "Godot CEF.framework"];
+
+/// Returns whether `bundle` carries a code signature. Works on any host, so
+/// artifacts can be checked where they are packed.
+fn is_bundle_signed(bundle: &Path) -> bool {
+    let app_signature = bundle.join("Contents/_CodeSignature/CodeResources");
+    let framework_signature = bundle.join("_CodeSignature/CodeResources");
+    app_signature.exists() || framework_signature.exists()
+}
+
+/// Fails if the macOS artifact contains unsigned bundles, unless
+/// `allow_unsigned` is set.
+fn check_macos_signatures(
+    artifact_dir: &Path,
+    allow_unsigned: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let unsigned: Vec<&str> = MACOS_BUNDLES
+        .iter()
+        .copied()
+        .filter(|bundle| {
+            let path = artifact_dir.join(bundle);
+            path.exists() && !is_bundle_signed(&path)
+        })
+        .collect();
+
+    if unsigned.is_empty() {
+        return Ok(());
+    }
+    if allow_unsigned {
+        println!("  Warning: including unsigned {}", unsigned.join(", "));
+        return Ok(());
     }
+    Err(format!(
+        "{} not code signed; bundle with --sign or pass --allow-unsigned",
+        unsigned.join(", ")
+    )
+    .into())
+}
 
+fn copy_addon_files(addon_src: &Path, output_dir: &Path) -> Result<(), Box<dyn std::error::Error>> {
     let icons_src = addon_src.join("icons");
     if icons_src.exists() {
         let icons_dst = output_dir.join("icons");
@@ -55,49 +127,272 @@ fn copy_addon_files(addon_src: &Path, output_dir: &Path) -> Result<(), Box<dyn s
     Ok(())
 }
 
+/// Replaces the body of the named sections of an INI-style `.gdextension`
+/// file, keeping every other section as is.
+fn replace_sections(template: &str, replacements: &[(&str, String)]) -> String {
+    let mut output = String::new();
+    let mut skipping = false;
+
+    for line in template.lines() {
+        if let Some(name) = line
+            .strip_prefix('[')
+            .and_then(|rest| rest.strip_suffix(']'))
+        {
+            skipping = false;
+            if let Some((_, body)) = replacements.iter().find(|(section, _)| *section == name) {
+                output.push_str(line);
+                output.push('\n');
+                output.push_str(body);
+                output.push('\n');
+                skipping = true;
+                continue;
+            }
+        }
+        if !skipping {
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+
+    output.trim_end().to_string() + "\n"
+}
+
+/// Builds the `[libraries]` and `[dependencies]` sections from the files
+/// actually packed in `bin_dir`.
+fn gdextension_sections(
+    bin_dir: &Path,
+    platforms: &[&Platform],
+) -> Result<(String, String), Box<dyn std::error::Error>> {
+    let width = platforms
+        .iter()
+        .map(|platform| platform.feature.len())
+        .max()
+        .unwrap_or(0);
+
+    let mut libraries = String::new();
+    let mut dependencies = String::new();
+    for platform in platforms {
+        let library = format!("bin/{}/{}", platform.target, platform.library);
+        libraries.push_str(&format!(
+            "{:width$} = \"{}\"\n",
+            platform.feature,
+            library,
+            width = width
+        ));
+
+        let mut names: Vec<String> = fs::read_dir(bin_dir.join(platform.target))?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().into_owned()))
+            .collect::<Result<_, std::io::Error>>()?;
+        names.sort();
+        names.retain(|name| name != platform.library);
+
+        let entries: Vec<String> = std::iter::once(library)
+            .chain(
+                names
+                    .iter()
+                    .map(|name| format!("bin/{}/{}", platform.target, name)),
+            )
+            .map(|path| format!("  \"{}\" : \"{}\"", path, platform.dependency_target))
+            .collect();
+        dependencies.push_str(&format!(
+            "\n{} = {{\n{}\n}}\n",
+            platform.feature,
+            entries.join(",\n")
+        ));
+    }
+
+    Ok((libraries, dependencies))
+}
+
+/// Returns `compatibility_minimum` from a `.gdextension` file.
+fn godot_minimum(gdextension: &str) -> Option<String> {
+    gdextension.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "compatibility_minimum").then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn find_addon_src(addon_src: Option<&Path>) -> Option<std::path::PathBuf> {
+    match addon_src {
+        Some(path) => Some(path.to_path_buf()),
+        None => {
+            let workspace_addon = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .parent()
+                .expect("xtask should be in workspace")
+                .join("addons/godot_cef");
+            workspace_addon.exists().then_some(workspace_addon)
+        }
+    }
+}
+
 pub fn run(
     artifacts_dir: &Path,
     output_dir: &Path,
     addon_src: Option<&Path>,
+    allow_unsigned: bool,
+    locales: &[String],
 ) -> Result<(), Box<dyn std::error::Error>> {
     println!("Packing Godot addon from artifacts...");
     println!("  Artifacts: {}", artifacts_dir.display());
     println!("  Output: {}", output_dir.display());
 
+    let macos_artifact = artifacts_dir.join(MACOS_ARTIFACT);
+    check_macos_signatures(&macos_artifact, allow_unsigned)?;
+
     if output_dir.exists() {
         fs::remove_dir_all(output_dir)?;
     }
     let bin_dir = output_dir.join("bin");
     fs::create_dir_all(&bin_dir)?;
 
-    if let Some(addon_path) = addon_src {
+    let addon_src = find_addon_src(addon_src);
+    if let Some(addon_path) = &addon_src {
         copy_addon_files(addon_path, output_dir)?;
-    } else {
-        let workspace_addon = Path::new(env!("CARGO_MANIFEST_DIR"))
-            .parent()
-            .expect("xtask should be in workspace")
-            .join("addons/godot_cef");
-        if workspace_addon.exists() {
-            copy_addon_files(&workspace_addon, output_dir)?;
-        }
     }
 
-    let mut platforms_found = 0;
-    for (platform_target, artifact_name) in PLATFORMS {
-        if copy_platform_artifacts(artifacts_dir, &bin_dir, platform_target, artifact_name)? {
-            platforms_found += 1;
+    let mut packed = Vec::new();
+    for platform in PLATFORMS {
+        if copy_platform_artifacts(artifacts_dir, &bin_dir, platform.target, platform.artifact)? {
+            packed.push(platform);
         }
     }
 
-    if platforms_found == 0 {
+    if packed.is_empty() {
         return Err("No platform artifacts found!".into());
     }
 
+    for platform in &packed {
+        let platform_dir = bin_dir.join(platform.target);
+        // Removing files from a signed bundle would invalidate its signature
+        if platform.target == "universal-apple-darwin"
+            && MACOS_BUNDLES
+                .iter()
+                .any(|bundle| is_bundle_signed(&platform_dir.join(bundle)))
+        {
+            if !locales.is_empty() {
+                println!(
+                    "  Warning: not pruning locales of the signed macOS bundles; bundle with --minimal-locales instead"
+                );
+            }
+            continue;
+        }
+        let removed = prune_locales(&platform_dir, locales)?;
+        if removed > 0 {
+            println!("  Pruned locales of {}: {} bytes", platform.target, removed);
+        }
+    }
+
+    // Only list the platforms that were packed, so a platform that failed to
+    // build does not leave a dangling library entry
+    let template = addon_src
+        .as_ref()
+        .and_then(|path| fs::read_to_string(path.join(GDEXTENSION_FILE)).ok());
+    match &template {
+        Some(template) => {
+            let (libraries, dependencies) = gdextension_sections(&bin_dir, &packed)?;
+            let gdextension = replace_sections(
+                template,
+                &[("libraries", libraries), ("dependencies", dependencies)],
+            );
+            fs::write(output_dir.join(GDEXTENSION_FILE), gdextension)?;
+            println!("  Generated: {}", GDEXTENSION_FILE);
+        }
+        None => println!("  Warning: {} template not found", GDEXTENSION_FILE),
+    }
+
+    let mut platforms = BTreeMap::new();
+    for platform in &packed {
+        platforms.insert(
+            platform.target.to_string(),
+            manifest::platform_manifest(output_dir, platform.target)?,
+        );
+    }
+    let manifest = Manifest {
+        addon_version: env!("CARGO_PKG_VERSION").to_string(),
+        cef_version: CEF_VERSION.to_string(),
+        git_commit: manifest::git_commit(),
+        build_date: manifest::build_date(),
+        godot_minimum: template
+            .as_deref()
+            .and_then(godot_minimum)
+            .unwrap_or_else(|| "unknown".to_string()),
+        platforms,
+    };
+    manifest::write(output_dir, &manifest)?;
+    println!("  Generated: {}", manifest::MANIFEST_FILE);
+
     println!(
         "Pack complete! {} platform(s) included in {}",
-        platforms_found,
+        packed.len(),
         output_dir.display()
     );
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_macos_signatures() {
+        let dir = std::env::temp_dir().join(format!("xtask-pack-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("Godot CEF.app/Contents/MacOS")).unwrap();
+        fs::create_dir_all(dir.join("Godot CEF.framework/_CodeSignature")).unwrap();
+        fs::write(
+            dir.join("Godot CEF.framework/_CodeSignature/CodeResources"),
+            "",
+        )
+        .unwrap();
+
+        assert!(check_macos_signatures(&dir, false).is_err());
+        assert!(check_macos_signatures(&dir, true).is_ok());
+
+        fs::create_dir_all(dir.join("Godot CEF.app/Contents/_CodeSignature")).unwrap();
+        fs::write(
+            dir.join("Godot CEF.app/Contents/_CodeSignature/CodeResources"),
+            "",
+        )
+        .unwrap();
+        assert!(check_macos_signatures(&dir, false).is_ok());
+
+        // A missing artifact is skipped later rather than rejected here
+        assert!(check_macos_signatures(&dir.join("missing"), false).is_ok());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_generate_gdextension() {
+        let dir = std::env::temp_dir().join(format!("xtask-gdext-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let linux_dir = dir.join("x86_64-unknown-linux-gnu");
+        fs::create_dir_all(linux_dir.join("locales")).unwrap();
+        fs::write(linux_dir.join("libgdcef.so"), "").unwrap();
+        fs::write(linux_dir.join("gdcef_helper"), "").unwrap();
+
+        let template = "[configuration]\ncompatibility_minimum = 4.5\n\n[libraries]\nmacos = \"bin/universal-apple-darwin/Godot CEF.framework\"\n\n[icons]\nCefTexture = \"icons/webview.png\"\n\n[dependencies]\n\nmacos = {\n  \"bin/universal-apple-darwin/Godot CEF.app\" : \"Contents/Frameworks\"\n}\n";
+        let linux = PLATFORMS
+            .iter()
+            .find(|platform| platform.feature == "linux.x86_64")
+            .unwrap();
+        let (libraries, dependencies) = gdextension_sections(&dir, &[linux]).unwrap();
+        let gdextension = replace_sections(
+            template,
+            &[("libraries", libraries), ("dependencies", dependencies)],
+        );
+
+        assert_eq!(
+            gdextension,
+            "[configuration]\ncompatibility_minimum = 4.5\n\n\
+             [libraries]\nlinux.x86_64 = \"bin/x86_64-unknown-linux-gnu/libgdcef.so\"\n\n\
+             [icons]\nCefTexture = \"icons/webview.png\"\n\n\
+             [dependencies]\n\nlinux.x86_64 = {\n\
+             \x20 \"bin/x86_64-unknown-linux-gnu/libgdcef.so\" : \"\",\n\
+             \x20 \"bin/x86_64-unknown-linux-gnu/gdcef_helper\" : \"\",\n\
+             \x20 \"bin/x86_64-unknown-linux-gnu/locales\" : \"\"\n}\n"
+        );
+        assert_eq!(godot_minimum(template), Some("4.5".to_string()));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
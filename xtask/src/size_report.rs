@@ -0,0 +1,319 @@
+//! Size report - where the bytes of a bundled addon go
+
+use crate::manifest::{collect_files, relative_path};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+enum Category {
+    CefCore,
+    Locales,
+    Helper,
+    Gdextension,
+    Resources,
+    Other,
+}
+
+impl Category {
+    fn as_str(self) -> &'static str {
+        match self {
+            Category::CefCore => "CEF core",
+            Category::Locales => "locales",
+            Category::Helper => "helper",
+            Category::Gdextension => "gdextension",
+            Category::Resources => "resources",
+            Category::Other => "other",
+        }
+    }
+}
+
+/// CEF libraries and executables shipped next to the GDExtension
+const CEF_CORE_PREFIXES: &[&str] = &[
+    "libcef",
+    "chrome_elf",
+    "chrome-sandbox",
+    "libEGL",
+    "libGLESv2",
+    "libvk_swiftshader",
+    "vk_swiftshader",
+    "libvulkan",
+    "vulkan-1",
+    "d3dcompiler",
+    "dxcompiler",
+    "dxil",
+    "bootstrap",
+];
+
+/// Data files CEF loads at runtime
+const RESOURCE_EXTENSIONS: &[&str] = &["pak", "dat", "bin", "json"];
+
+/// Categorizes a file by its `/`-separated path relative to the addon.
+fn categorize(path: &str) -> Category {
+    let (dirs, file_name) = path.rsplit_once('/').unwrap_or(("", path));
+    let in_dir = |predicate: fn(&str) -> bool| dirs.split('/').any(predicate);
+
+    if in_dir(|component| component == "locales" || component.ends_with(".lproj")) {
+        Category::Locales
+    } else if file_name.ends_with(".gdextension")
+        || file_name.starts_with("libgdcef.")
+        || file_name.starts_with("gdcef.")
+        || in_dir(|component| component == "Godot CEF.framework")
+    {
+        Category::Gdextension
+    } else if Path::new(file_name)
+        .extension()
+        .is_some_and(|ext| RESOURCE_EXTENSIONS.iter().any(|resource| ext == *resource))
+    {
+        Category::Resources
+    } else if CEF_CORE_PREFIXES
+        .iter()
+        .any(|prefix| file_name.starts_with(prefix))
+        || in_dir(|component| component.starts_with("Chromium Embedded Framework"))
+    {
+        Category::CefCore
+    } else if file_name.starts_with("gdcef_helper")
+        || in_dir(|component| component.ends_with(".app"))
+    {
+        Category::Helper
+    } else {
+        Category::Other
+    }
+}
+
+/// Formats a byte count with binary units, e.g. `1.5 MiB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct FileSize {
+    path: String,
+    category: Category,
+    size: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct SizeReport {
+    total: u64,
+    categories: BTreeMap<Category, u64>,
+    /// Sorted by size, largest first
+    files: Vec<FileSize>,
+}
+
+fn size_report(addon_dir: &Path) -> Result<SizeReport, Box<dyn std::error::Error>> {
+    let mut paths = Vec::new();
+    collect_files(addon_dir, &mut paths)?;
+
+    let mut files = paths
+        .iter()
+        .map(|path| {
+            let relative = relative_path(addon_dir, path);
+            Ok(FileSize {
+                category: categorize(&relative),
+                path: relative,
+                size: fs::metadata(path)?.len(),
+            })
+        })
+        .collect::<Result<Vec<_>, Box<dyn std::error::Error>>>()?;
+    files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+
+    let mut categories = BTreeMap::new();
+    for file in &files {
+        *categories.entry(file.category).or_insert(0) += file.size;
+    }
+
+    Ok(SizeReport {
+        total: files.iter().map(|file| file.size).sum(),
+        categories,
+        files,
+    })
+}
+
+fn default_addon_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("xtask should be in workspace")
+        .join("addons/godot_cef")
+}
+
+/// Prints the `top` largest files of the addon and the total per category,
+/// and optionally writes the full report to `json`.
+pub fn run(
+    addon_dir: Option<&Path>,
+    top: usize,
+    json: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let addon_dir = addon_dir.map_or_else(default_addon_dir, Path::to_path_buf);
+    if !addon_dir.exists() {
+        return Err(format!("{} does not exist", addon_dir.display()).into());
+    }
+    let report = size_report(&addon_dir)?;
+
+    println!("Size report: {}", addon_dir.display());
+    println!();
+    println!("{:>10}  {:<12}  File", "Size", "Category");
+    for file in report.files.iter().take(top) {
+        println!(
+            "{:>10}  {:<12}  {}",
+            format_size(file.size),
+            file.category.as_str(),
+            file.path
+        );
+    }
+    if report.files.len() > top {
+        println!("{:>10}  {:<12}  ...", "", "");
+    }
+
+    let mut categories: Vec<_> = report.categories.iter().collect();
+    categories.sort_by(|a, b| b.1.cmp(a.1));
+    println!();
+    for (category, size) in categories {
+        println!("{:>10}  {}", format_size(*size), category.as_str());
+    }
+    println!(
+        "{:>10}  total ({} files)",
+        format_size(report.total),
+        report.files.len()
+    );
+
+    if let Some(json) = json {
+        fs::write(json, serde_json::to_string_pretty(&report)? + "\n")?;
+        println!("Wrote: {}", json.display());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_categorize() {
+        let linux = "bin/x86_64-unknown-linux-gnu";
+        assert_eq!(
+            categorize(&format!("{}/libcef.so", linux)),
+            Category::CefCore
+        );
+        assert_eq!(
+            categorize(&format!("{}/locales/zh-CN.pak", linux)),
+            Category::Locales
+        );
+        assert_eq!(
+            categorize(&format!("{}/gdcef_helper", linux)),
+            Category::Helper
+        );
+        assert_eq!(
+            categorize(&format!("{}/libgdcef.so", linux)),
+            Category::Gdextension
+        );
+        assert_eq!(
+            categorize(&format!("{}/icudtl.dat", linux)),
+            Category::Resources
+        );
+        assert_eq!(categorize("godot_cef.gdextension"), Category::Gdextension);
+        assert_eq!(categorize("icons/webview.png"), Category::Other);
+
+        let windows = "bin/x86_64-pc-windows-msvc";
+        assert_eq!(
+            categorize(&format!("{}/gdcef.dll", windows)),
+            Category::Gdextension
+        );
+        assert_eq!(
+            categorize(&format!("{}/gdcef_helper.exe", windows)),
+            Category::Helper
+        );
+        assert_eq!(
+            categorize(&format!("{}/chrome_elf.dll", windows)),
+            Category::CefCore
+        );
+
+        let app = "bin/universal-apple-darwin/Godot CEF.app/Contents";
+        let framework = "Frameworks/Chromium Embedded Framework (ARM64).framework";
+        assert_eq!(
+            categorize(&format!(
+                "{}/{}/Chromium Embedded Framework",
+                app, framework
+            )),
+            Category::CefCore
+        );
+        assert_eq!(
+            categorize(&format!(
+                "{}/{}/Libraries/libcef_sandbox.dylib",
+                app, framework
+            )),
+            Category::CefCore
+        );
+        assert_eq!(
+            categorize(&format!(
+                "{}/{}/Resources/fr.lproj/locale.pak",
+                app, framework
+            )),
+            Category::Locales
+        );
+        assert_eq!(
+            categorize(&format!("{}/{}/Resources/resources.pak", app, framework)),
+            Category::Resources
+        );
+        assert_eq!(
+            categorize(&format!(
+                "{}/Frameworks/Godot CEF Helper (GPU).app/Contents/MacOS/Godot CEF Helper (GPU)",
+                app
+            )),
+            Category::Helper
+        );
+        assert_eq!(
+            categorize("bin/universal-apple-darwin/Godot CEF.framework/libgdcef.dylib"),
+            Category::Gdextension
+        );
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(250 * 1024 * 1024), "250.0 MiB");
+    }
+
+    #[test]
+    fn test_size_report() {
+        let dir = std::env::temp_dir().join(format!("xtask-size-test-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let bin_dir = dir.join("bin/x86_64-unknown-linux-gnu");
+        fs::create_dir_all(bin_dir.join("locales")).unwrap();
+        fs::write(bin_dir.join("libcef.so"), "abcdef").unwrap();
+        fs::write(bin_dir.join("libgdcef.so"), "abc").unwrap();
+        fs::write(bin_dir.join("locales/en-US.pak"), "ab").unwrap();
+        fs::write(bin_dir.join("locales/de.pak"), "ab").unwrap();
+
+        let report = size_report(&dir).unwrap();
+        assert_eq!(report.total, 13);
+        assert_eq!(
+            report.files[0].path,
+            "bin/x86_64-unknown-linux-gnu/libcef.so"
+        );
+        assert_eq!(
+            report.files[2].path,
+            "bin/x86_64-unknown-linux-gnu/locales/de.pak"
+        );
+        assert_eq!(report.categories[&Category::Locales], 4);
+        assert_eq!(report.categories[&Category::CefCore], 6);
+        assert!(!report.categories.contains_key(&Category::Helper));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,105 @@
+//! Symbol stripping for the gdcef library and helper
+//!
+//! Debug symbols are optionally kept next to the build in
+//! `--debug-symbols-dir`, so crash reports from stripped builds can still be
+//! symbolicated.
+
+#[cfg(not(target_os = "windows"))]
+use crate::bundle_common::run_command;
+use std::fs;
+use std::path::Path;
+#[cfg(not(target_os = "windows"))]
+use std::process::Command;
+
+/// Strips `binary` in place. `name` is the file name the debug symbols are
+/// saved under (`<name>.debug`).
+#[cfg(target_os = "linux")]
+pub fn strip_symbols(
+    binary: &Path,
+    name: &str,
+    debug_symbols_dir: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("  Stripping: {}", binary.display());
+
+    let debug_file = match debug_symbols_dir {
+        Some(dir) => {
+            fs::create_dir_all(dir)?;
+            let debug_file = dir.join(format!("{}.debug", name));
+            run_command(
+                Command::new("objcopy")
+                    .arg("--only-keep-debug")
+                    .arg(binary)
+                    .arg(&debug_file),
+            )?;
+            Some(debug_file)
+        }
+        None => None,
+    };
+
+    run_command(
+        Command::new("strip")
+            .args(["--strip-debug", "--strip-unneeded"])
+            .arg(binary),
+    )?;
+
+    // Lets gdb and crash tools find the symbols again
+    if let Some(debug_file) = debug_file {
+        let mut debuglink = std::ffi::OsString::from("--add-gnu-debuglink=");
+        debuglink.push(&debug_file);
+        run_command(Command::new("objcopy").arg(debuglink).arg(binary))?;
+        println!("  Debug symbols: {}", debug_file.display());
+    }
+
+    Ok(())
+}
+
+/// Strips `binary` in place. `name` is the file name the debug symbols are
+/// saved under (`<name>.dSYM`).
+#[cfg(target_os = "macos")]
+pub fn strip_symbols(
+    binary: &Path,
+    name: &str,
+    debug_symbols_dir: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    println!("  Stripping: {}", binary.display());
+
+    // dsymutil reads the debug map, so it has to run before strip
+    if let Some(dir) = debug_symbols_dir {
+        fs::create_dir_all(dir)?;
+        let dsym = dir.join(format!("{}.dSYM", name));
+        if dsym.exists() {
+            fs::remove_dir_all(&dsym)?;
+        }
+        run_command(Command::new("dsymutil").arg(binary).arg("-o").arg(&dsym))?;
+        println!("  Debug symbols: {}", dsym.display());
+    }
+
+    // -x keeps the global symbols the loader needs
+    run_command(Command::new("strip").arg("-x").arg(binary))
+}
+
+/// MSVC already keeps debug info in a separate PDB that is never deployed,
+/// so there is nothing to strip; the PDB next to `binary` is copied to
+/// `debug_symbols_dir`.
+#[cfg(target_os = "windows")]
+pub fn strip_symbols(
+    binary: &Path,
+    name: &str,
+    debug_symbols_dir: Option<&Path>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let Some(dir) = debug_symbols_dir else {
+        return Ok(());
+    };
+
+    let pdb_name = Path::new(name).with_extension("pdb");
+    let pdb = binary.with_file_name(&pdb_name);
+    if !pdb.exists() {
+        println!("  Warning: {} not found", pdb.display());
+        return Ok(());
+    }
+
+    fs::create_dir_all(dir)?;
+    fs::copy(&pdb, dir.join(&pdb_name))?;
+    println!("  Debug symbols: {}", dir.join(&pdb_name).display());
+    Ok(())
+}